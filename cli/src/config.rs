@@ -125,6 +125,51 @@ impl Deref for Manifest {
     }
 }
 
+impl WithPath<Manifest> {
+    // Adds the errors of the shared error enums extended by the program at
+    // `lib`, with `#[error(extends(..))]`, to its IDL. The crates defining
+    // them must be path dependencies of the program.
+    pub fn extend_idl_errors(&self, idl: &mut Idl, lib: &Path) -> Result<()> {
+        let mut shared = vec![];
+        for path in anchor_syn::idl::file::parse_extended_errors(lib)? {
+            let crate_name = path.segments.first().unwrap().ident.to_string();
+            let name = path.segments.last().unwrap().ident.to_string();
+            if path.segments.len() < 2 {
+                return Err(anyhow!(
+                    "Extended errors {} must be given by their full path",
+                    name
+                ));
+            }
+            let file = match crate_name.as_str() {
+                "crate" | "self" | "super" => lib.to_path_buf(),
+                _ => {
+                    let dep_path = self
+                        .dependencies
+                        .iter()
+                        .find(|(dep, _)| dep.replace('-', "_") == crate_name)
+                        .and_then(|(_, dep)| match dep {
+                            cargo_toml::Dependency::Detailed(detail) => detail.path.as_ref(),
+                            cargo_toml::Dependency::Simple(_) => None,
+                        })
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "{} must be a path dependency to extend its errors",
+                                crate_name
+                            )
+                        })?;
+                    self.path()
+                        .parent()
+                        .unwrap()
+                        .join(dep_path)
+                        .join("src/lib.rs")
+                }
+            };
+            shared.extend(anchor_syn::idl::file::parse_error_codes(file, &name)?);
+        }
+        anchor_syn::idl::file::extend_errors(idl, shared)
+    }
+}
+
 impl WithPath<Config> {
     pub fn get_program_list(&self) -> Result<Vec<PathBuf>> {
         // Canonicalize the workspace filepaths to compare with relative paths.
@@ -159,10 +204,17 @@ impl WithPath<Config> {
     pub fn read_all_programs(&self) -> Result<Vec<Program>> {
         let mut r = vec![];
         for path in self.get_program_list()? {
-            let cargo = Manifest::from_path(&path.join("Cargo.toml"))?;
+            let cargo = WithPath::new(
+                Manifest::from_path(&path.join("Cargo.toml"))?,
+                path.join("Cargo.toml"),
+            );
             let lib_name = cargo.lib_name()?;
             let version = cargo.version();
-            let idl = anchor_syn::idl::file::parse(path.join("src/lib.rs"), version)?;
+            let lib = path.join("src/lib.rs");
+            let mut idl = anchor_syn::idl::file::parse(&lib, version)?;
+            if let Some(idl) = &mut idl {
+                cargo.extend_idl_errors(idl, &lib)?;
+            }
             r.push(Program {
                 lib_name,
                 path,
@@ -494,6 +546,7 @@ fn deser_programs(
 pub struct Test {
     pub genesis: Option<Vec<GenesisEntry>>,
     pub clone: Option<Vec<CloneEntry>>,
+    pub account: Option<Vec<AccountEntry>>,
     pub validator: Option<Validator>,
     pub startup_wait: Option<i32>,
 }
@@ -512,6 +565,14 @@ pub struct CloneEntry {
     pub address: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEntry {
+    // Base58 pubkey string.
+    pub address: String,
+    // Filepath to the JSON account fixture, as written by `solana account`.
+    pub filename: String,
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Validator {
     // IP address to bind the validator ports. [default: 0.0.0.0]
@@ -599,9 +660,21 @@ impl Program {
     }
 
     pub fn binary_path(&self) -> PathBuf {
-        std::env::current_dir()
-            .expect("Must have current dir")
-            .join(format!("target/deploy/{}.so", self.lib_name))
+        self.binary_path_in(&std::env::current_dir().expect("Must have current dir"))
+    }
+
+    // The path of the program's binary in the workspace at `workspace_dir`.
+    pub fn binary_path_in(&self, workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join(format!("target/deploy/{}.so", self.lib_name))
+    }
+
+    // The program's address, read from its existing keypair in the workspace
+    // at `workspace_dir`.
+    pub fn pubkey_in(&self, workspace_dir: &Path) -> Result<Pubkey> {
+        let path = workspace_dir.join(format!("target/deploy/{}-keypair.json", self.lib_name));
+        solana_sdk::signature::read_keypair_file(&path)
+            .map(|kp| kp.pubkey())
+            .map_err(|_| anyhow!("failed to read keypair for program: {}", self.lib_name))
     }
 }
 
@@ -1,6 +1,9 @@
 use crate::config::{
     AnchorPackage, BootstrapMode, BuildConfig, Config, ConfigOverride, Manifest, ProgramDeployment,
-    ProgramWorkspace, Test, WithPath,
+    ProgramWorkspace, WithPath,
+};
+use crate::test_validator::{
+    start_test_validator, stream_logs, test_validator_rpc_url, validator_flags,
 };
 use anchor_client::Cluster;
 use anchor_lang::idl::{IdlAccount, IdlInstruction};
@@ -36,12 +39,13 @@ use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Stdio};
+use std::process::Stdio;
 use std::string::ToString;
 use tar::Archive;
 
 pub mod config;
 pub mod template;
+pub mod test_validator;
 
 // Version of the docker image.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -244,6 +248,20 @@ pub enum Command {
 #[derive(Debug, Clap)]
 pub enum KeysCommand {
     List,
+    /// Updates the program ids declared with `declare_id!` to match the
+    /// program keypairs in target/deploy.
+    Sync {
+        #[clap(short, long)]
+        program_name: Option<String>,
+        /// Only reports mismatched ids, failing if there are any.
+        #[clap(long)]
+        check: bool,
+    },
+    /// Replaces a program's keypair with a new one and updates its declared
+    /// id. The previous keypair is kept in target/deploy.
+    Rotate {
+        program_name: String,
+    },
 }
 
 #[derive(Debug, Clap)]
@@ -310,6 +328,9 @@ pub enum IdlCommand {
         /// Output file for the TypeScript IDL.
         #[clap(short = 't', long)]
         out_ts: Option<String>,
+        /// Output file for the C header of the program's instructions.
+        #[clap(long)]
+        out_c: Option<String>,
     },
     /// Fetches an IDL for the given address from a cluster.
     /// The address can be a program, IDL account, or IDL buffer.
@@ -752,6 +773,9 @@ fn build_cwd_verifiable(
                 let ts_file = workspace_dir.join(format!("target/types/{}.ts", idl.name));
                 fs::write(&ts_file, template::idl_ts(&idl)?)?;
 
+                // Write out the C header, if asked for.
+                write_c_header(&idl, "src/lib.rs", &workspace_dir.join("target/include"))?;
+
                 // Copy out the TypeScript type.
                 if !&cfg.workspace.types.is_empty() {
                     fs::copy(
@@ -1006,6 +1030,10 @@ fn _build_cwd(
     let exit = std::process::Command::new("cargo")
         .arg("build-bpf")
         .args(cargo_args)
+        .env(
+            anchor_syn::keys::CLUSTER_ENV,
+            cfg.provider.cluster.to_string(),
+        )
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .output()
@@ -1031,8 +1059,10 @@ fn _build_cwd(
         write_idl(&idl, OutFile::File(out))?;
         // Write out the TypeScript type.
         fs::write(&ts_out, template::idl_ts(&idl)?)?;
-        // Copy out the TypeScript type.
+        // Write out the C header, if asked for by `#[program(c_header)]`.
         let cfg_parent = cfg.path().parent().expect("Invalid Anchor.toml");
+        write_c_header(&idl, "src/lib.rs", &cfg_parent.join("target/include"))?;
+        // Copy out the TypeScript type.
         if !&cfg.workspace.types.is_empty() {
             fs::copy(
                 &ts_out,
@@ -1269,7 +1299,11 @@ fn extract_idl(file: &str) -> Result<Option<Idl>> {
         std::env::current_dir()?.join(PathBuf::from(&*file).parent().unwrap().to_path_buf());
     let cargo = Manifest::discover_from_path(manifest_from_path)?
         .ok_or_else(|| anyhow!("Cargo.toml not found"))?;
-    anchor_syn::idl::file::parse(&*file, cargo.version())
+    let mut idl = anchor_syn::idl::file::parse(&*file, cargo.version())?;
+    if let Some(idl) = &mut idl {
+        cargo.extend_idl_errors(idl, Path::new(&*file))?;
+    }
+    Ok(idl)
 }
 
 fn idl(cfg_override: &ConfigOverride, subcmd: IdlCommand) -> Result<()> {
@@ -1296,7 +1330,12 @@ fn idl(cfg_override: &ConfigOverride, subcmd: IdlCommand) -> Result<()> {
         } => idl_set_authority(cfg_override, program_id, address, new_authority),
         IdlCommand::EraseAuthority { program_id } => idl_erase_authority(cfg_override, program_id),
         IdlCommand::Authority { program_id } => idl_authority(cfg_override, program_id),
-        IdlCommand::Parse { file, out, out_ts } => idl_parse(file, out, out_ts),
+        IdlCommand::Parse {
+            file,
+            out,
+            out_ts,
+            out_c,
+        } => idl_parse(file, out, out_ts, out_c),
         IdlCommand::Fetch { address, out } => idl_fetch(cfg_override, address, out),
     }
 }
@@ -1555,7 +1594,12 @@ fn idl_write(cfg: &Config, program_id: &Pubkey, idl: &Idl, idl_address: Pubkey)
     Ok(())
 }
 
-fn idl_parse(file: String, out: Option<String>, out_ts: Option<String>) -> Result<()> {
+fn idl_parse(
+    file: String,
+    out: Option<String>,
+    out_ts: Option<String>,
+    out_c: Option<String>,
+) -> Result<()> {
     let idl = extract_idl(&file)?.ok_or_else(|| anyhow!("IDL not parsed"))?;
     let out = match out {
         None => OutFile::Stdout,
@@ -1568,6 +1612,27 @@ fn idl_parse(file: String, out: Option<String>, out_ts: Option<String>) -> Resul
         fs::write(out, template::idl_ts(&idl)?)?;
     }
 
+    // Write out the C header.
+    if let Some(out) = out_c {
+        fs::write(out, anchor_syn::idl::c_header::generate(&idl))?;
+    }
+
+    Ok(())
+}
+
+// Writes the C header of the program at `file` into `out_dir`, if the
+// program is declared with `#[program(c_header)]`.
+fn write_c_header(idl: &Idl, file: &str, out_dir: &Path) -> Result<()> {
+    let c_header = anchor_syn::idl::file::parse_program_args(file)?
+        .map(|args| args.c_header)
+        .unwrap_or(false);
+    if c_header {
+        fs::create_dir_all(out_dir)?;
+        fs::write(
+            out_dir.join(&idl.name).with_extension("h"),
+            anchor_syn::idl::c_header::generate(idl),
+        )?;
+    }
     Ok(())
 }
 
@@ -1718,236 +1783,11 @@ fn test(
     })
 }
 
-// Returns the solana-test-validator flags. This will embed the workspace
-// programs in the genesis block so we don't have to deploy every time. It also
-// allows control of other solana-test-validator features.
-fn validator_flags(cfg: &WithPath<Config>) -> Result<Vec<String>> {
-    let programs = cfg.programs.get(&Cluster::Localnet);
-
-    let mut flags = Vec::new();
-    for mut program in cfg.read_all_programs()? {
-        let binary_path = program.binary_path().display().to_string();
-
-        // Use the [programs.cluster] override and fallback to the keypair
-        // files if no override is given.
-        let address = programs
-            .and_then(|m| m.get(&program.lib_name))
-            .map(|deployment| Ok(deployment.address.to_string()))
-            .unwrap_or_else(|| program.pubkey().map(|p| p.to_string()))?;
-
-        flags.push("--bpf-program".to_string());
-        flags.push(address.clone());
-        flags.push(binary_path);
-
-        if let Some(mut idl) = program.idl.as_mut() {
-            // Add program address to the IDL.
-            idl.metadata = Some(serde_json::to_value(IdlTestMetadata { address })?);
-
-            // Persist it.
-            let idl_out = PathBuf::from("target/idl")
-                .join(&idl.name)
-                .with_extension("json");
-            write_idl(idl, OutFile::File(idl_out))?;
-        }
-    }
-
-    if let Some(test) = cfg.test.as_ref() {
-        if let Some(genesis) = &test.genesis {
-            for entry in genesis {
-                let program_path = Path::new(&entry.program);
-                if !program_path.exists() {
-                    return Err(anyhow!(
-                        "Program in genesis configuration does not exist at path: {}",
-                        program_path.display()
-                    ));
-                }
-                flags.push("--bpf-program".to_string());
-                flags.push(entry.address.clone());
-                flags.push(entry.program.clone());
-            }
-        }
-        if let Some(clone) = &test.clone {
-            for entry in clone {
-                flags.push("--clone".to_string());
-                flags.push(entry.address.clone());
-            }
-        }
-        if let Some(validator) = &test.validator {
-            for (key, value) in serde_json::to_value(validator)?.as_object().unwrap() {
-                if key == "ledger" {
-                    continue;
-                };
-                flags.push(format!("--{}", key.replace("_", "-")));
-                if let serde_json::Value::String(v) = value {
-                    flags.push(v.to_string());
-                } else {
-                    flags.push(value.to_string());
-                }
-            }
-        }
-    }
-
-    Ok(flags)
-}
-
-fn stream_logs(config: &WithPath<Config>, rpc_url: &str) -> Result<Vec<std::process::Child>> {
-    let program_logs_dir = ".anchor/program-logs";
-    if Path::new(program_logs_dir).exists() {
-        fs::remove_dir_all(program_logs_dir)?;
-    }
-    fs::create_dir_all(program_logs_dir)?;
-    let mut handles = vec![];
-    for program in config.read_all_programs()? {
-        let mut file = File::open(&format!("target/idl/{}.json", program.lib_name))?;
-        let mut contents = vec![];
-        file.read_to_end(&mut contents)?;
-        let idl: Idl = serde_json::from_slice(&contents)?;
-        let metadata = idl
-            .metadata
-            .ok_or_else(|| anyhow!("Program address not found."))?;
-        let metadata: IdlTestMetadata = serde_json::from_value(metadata)?;
-
-        let log_file = File::create(format!(
-            "{}/{}.{}.log",
-            program_logs_dir, metadata.address, program.lib_name,
-        ))?;
-        let stdio = std::process::Stdio::from(log_file);
-        let child = std::process::Command::new("solana")
-            .arg("logs")
-            .arg(metadata.address)
-            .arg("--url")
-            .arg(rpc_url)
-            .stdout(stdio)
-            .spawn()?;
-        handles.push(child);
-    }
-    if let Some(test) = config.test.as_ref() {
-        if let Some(genesis) = &test.genesis {
-            for entry in genesis {
-                let log_file = File::create(format!("{}/{}.log", program_logs_dir, entry.address))?;
-                let stdio = std::process::Stdio::from(log_file);
-                let child = std::process::Command::new("solana")
-                    .arg("logs")
-                    .arg(entry.address.clone())
-                    .arg("--url")
-                    .arg(rpc_url)
-                    .stdout(stdio)
-                    .spawn()?;
-                handles.push(child);
-            }
-        }
-    }
-
-    Ok(handles)
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct IdlTestMetadata {
     address: String,
 }
 
-fn start_test_validator(
-    cfg: &Config,
-    flags: Option<Vec<String>>,
-    test_log_stdout: bool,
-) -> Result<Child> {
-    //
-    let (test_ledger_directory, test_ledger_log_filename) = test_validator_file_paths(cfg);
-
-    // Start a validator for testing.
-    let (test_validator_stdout, test_validator_stderr) = match test_log_stdout {
-        true => {
-            let test_validator_stdout_file = File::create(&test_ledger_log_filename)?;
-            let test_validator_sterr_file = test_validator_stdout_file.try_clone()?;
-            (
-                Stdio::from(test_validator_stdout_file),
-                Stdio::from(test_validator_sterr_file),
-            )
-        }
-        false => (Stdio::inherit(), Stdio::inherit()),
-    };
-
-    let rpc_url = test_validator_rpc_url(cfg);
-
-    let mut validator_handle = std::process::Command::new("solana-test-validator")
-        .arg("--ledger")
-        .arg(test_ledger_directory)
-        .arg("--mint")
-        .arg(cfg.wallet_kp()?.pubkey().to_string())
-        .args(flags.unwrap_or_default())
-        .stdout(test_validator_stdout)
-        .stderr(test_validator_stderr)
-        .spawn()
-        .map_err(|e| anyhow::format_err!("{}", e.to_string()))?;
-
-    // Wait for the validator to be ready.
-    let client = RpcClient::new(rpc_url);
-    let mut count = 0;
-    let ms_wait = cfg
-        .test
-        .as_ref()
-        .and_then(|test| test.startup_wait)
-        .unwrap_or(5_000);
-    while count < ms_wait {
-        let r = client.get_recent_blockhash();
-        if r.is_ok() {
-            break;
-        }
-        std::thread::sleep(std::time::Duration::from_millis(1));
-        count += 1;
-    }
-    if count == ms_wait {
-        eprintln!(
-            "Unable to start test validator. Check {} for errors.",
-            test_ledger_log_filename
-        );
-        validator_handle.kill()?;
-        std::process::exit(1);
-    }
-    Ok(validator_handle)
-}
-
-// Return the URL that solana-test-validator should be running on given the
-// configuration
-fn test_validator_rpc_url(cfg: &Config) -> String {
-    match &cfg.test.as_ref() {
-        Some(Test {
-            validator: Some(validator),
-            ..
-        }) => format!("http://{}:{}", validator.bind_address, validator.rpc_port),
-        _ => "http://localhost:8899".to_string(),
-    }
-}
-
-// Setup and return paths to the solana-test-validator ledger directory and log
-// files given the configuration
-fn test_validator_file_paths(cfg: &Config) -> (String, String) {
-    let ledger_directory = match &cfg.test.as_ref() {
-        Some(Test {
-            validator: Some(validator),
-            ..
-        }) => &validator.ledger,
-        _ => ".anchor/test-ledger",
-    };
-
-    if !Path::new(&ledger_directory).is_relative() {
-        // Prevent absolute paths to avoid someone using / or similar, as the
-        // directory gets removed
-        eprintln!("Ledger directory {} must be relative", ledger_directory);
-        std::process::exit(1);
-    }
-    if Path::new(&ledger_directory).exists() {
-        fs::remove_dir_all(&ledger_directory).unwrap();
-    }
-
-    fs::create_dir_all(&ledger_directory).unwrap();
-
-    (
-        ledger_directory.to_string(),
-        format!("{}/test-ledger-log.txt", ledger_directory),
-    )
-}
-
 fn cluster_url(cfg: &Config) -> String {
     let is_localnet = cfg.provider.cluster == Cluster::Localnet;
     match is_localnet {
@@ -2594,6 +2434,11 @@ fn registry_api_token(_cfg_override: &ConfigOverride) -> Result<String> {
 fn keys(cfg_override: &ConfigOverride, cmd: KeysCommand) -> Result<()> {
     match cmd {
         KeysCommand::List => keys_list(cfg_override),
+        KeysCommand::Sync {
+            program_name,
+            check,
+        } => keys_sync(cfg_override, program_name, check),
+        KeysCommand::Rotate { program_name } => keys_rotate(cfg_override, program_name),
     }
 }
 
@@ -2606,6 +2451,67 @@ fn keys_list(cfg_override: &ConfigOverride) -> Result<()> {
     Ok(())
 }
 
+fn keys_sync(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    check: bool,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let mut mismatched = false;
+        for program in cfg.read_all_programs()? {
+            if let Some(program_name) = program_name.as_ref() {
+                if &program.lib_name != program_name {
+                    continue;
+                }
+            }
+            let expected = program.pubkey()?.to_string();
+            let mismatches = match check {
+                true => anchor_syn::keys::check_declared_ids(&program.path, &expected)?,
+                false => anchor_syn::keys::sync_declared_ids(&program.path, &expected)?,
+            };
+            for mismatch in &mismatches {
+                println!(
+                    "{}:{}: {} declared {}, keypair is {}",
+                    mismatch.declared.path.display(),
+                    mismatch.declared.line,
+                    program.lib_name,
+                    mismatch.declared.id,
+                    mismatch.expected,
+                );
+            }
+            mismatched |= !mismatches.is_empty();
+        }
+        if check && mismatched {
+            return Err(anyhow!(
+                "Declared program ids don't match the program keypairs, run `anchor keys sync`"
+            ));
+        }
+        Ok(())
+    })
+}
+
+fn keys_rotate(cfg_override: &ConfigOverride, program_name: String) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let program = cfg
+            .read_all_programs()?
+            .into_iter()
+            .find(|program| program.lib_name == program_name)
+            .ok_or_else(|| anyhow!("Program {} not found in workspace", program_name))?;
+        let previous = program.pubkey()?;
+        let keypair_path = program.keypair_file()?.path().clone();
+        let backup_path =
+            keypair_path.with_file_name(format!("{}-keypair-{}.json", program.lib_name, previous));
+        fs::copy(&keypair_path, &backup_path)?;
+
+        let keypair = Keypair::generate(&mut OsRng);
+        fs::write(&keypair_path, format!("{:?}", &keypair.to_bytes()))?;
+        anchor_syn::keys::sync_declared_ids(&program.path, &keypair.pubkey().to_string())?;
+        println!("{}: {} -> {}", program.lib_name, previous, keypair.pubkey());
+        println!("Previous keypair saved to {}", backup_path.display());
+        Ok(())
+    })
+}
+
 fn localnet(
     cfg_override: &ConfigOverride,
     skip_build: bool,
@@ -62,7 +62,11 @@ name = "{1}"
 no-entrypoint = []
 no-idl = []
 no-log-ix-name = []
+profile = []
+dynamic-program-id = []
+check-invariants = []
 cpi = ["no-entrypoint"]
+instruction-parser = ["no-entrypoint"]
 default = []
 
 [dependencies]
@@ -0,0 +1,366 @@
+//! Starts a `solana-test-validator` for the workspace, as `anchor test` and
+//! `anchor localnet` do. Exposed as a library, via `anchor-test-validator`,
+//! so that Rust test harnesses and CI jobs can boot a validator without
+//! spawning the `anchor` binary.
+
+use crate::config::{Config, Test, WithPath};
+use crate::{write_idl, IdlTestMetadata, OutFile};
+use anchor_client::Cluster;
+use anchor_syn::idl::Idl;
+use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+
+/// A running test validator, started with [`start`]. The validator and its
+/// program log streams are killed when this is dropped.
+pub struct TestValidator {
+    rpc_url: String,
+    websocket_url: String,
+    validator: Option<Child>,
+    log_streams: Vec<Child>,
+}
+
+impl TestValidator {
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    pub fn websocket_url(&self) -> &str {
+        &self.websocket_url
+    }
+
+    /// Kills the validator and its log streams.
+    pub fn shutdown(mut self) -> Result<()> {
+        self.kill()
+    }
+
+    fn kill(&mut self) -> Result<()> {
+        for mut child in self.log_streams.drain(..) {
+            child.kill()?;
+        }
+        if let Some(mut validator) = self.validator.take() {
+            validator.kill()?;
+            validator.wait()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        if let Err(err) = self.kill() {
+            eprintln!("Failed to shut down test validator: {}", err);
+        }
+    }
+}
+
+/// Starts a validator for the workspace of `cfg` with its programs built
+/// into the genesis block, along with the `[test]` genesis programs, account
+/// fixtures and clones of Anchor.toml, and streams the programs' logs to
+/// `.anchor/program-logs`. The programs must already be built. Returns once
+/// the validator's RPC is up.
+///
+/// Paths are resolved from the workspace root, regardless of the current
+/// directory, so validators of several workspaces can be started
+/// concurrently.
+pub fn start(cfg: &WithPath<Config>) -> Result<TestValidator> {
+    let flags = validator_flags(cfg)?;
+    let mut validator = start_test_validator(cfg, Some(flags), true)?;
+    let rpc_url = test_validator_rpc_url(cfg);
+    let log_streams = match stream_logs(cfg, &rpc_url) {
+        Ok(log_streams) => log_streams,
+        Err(err) => {
+            validator.kill()?;
+            return Err(err);
+        }
+    };
+    Ok(TestValidator {
+        rpc_url,
+        websocket_url: test_validator_websocket_url(cfg),
+        validator: Some(validator),
+        log_streams,
+    })
+}
+
+// Returns the solana-test-validator flags. This will embed the workspace
+// programs in the genesis block so we don't have to deploy every time. It also
+// allows control of other solana-test-validator features.
+pub(crate) fn validator_flags(cfg: &WithPath<Config>) -> Result<Vec<String>> {
+    let workspace_dir = workspace_dir(cfg)?;
+    let programs = cfg.programs.get(&Cluster::Localnet);
+
+    let mut flags = Vec::new();
+    for mut program in cfg.read_all_programs()? {
+        let binary_path = program.binary_path_in(workspace_dir).display().to_string();
+
+        // Use the [programs.cluster] override and fallback to the keypair
+        // files if no override is given.
+        let address = programs
+            .and_then(|m| m.get(&program.lib_name))
+            .map(|deployment| Ok(deployment.address.to_string()))
+            .unwrap_or_else(|| program.pubkey_in(workspace_dir).map(|p| p.to_string()))?;
+
+        flags.push("--bpf-program".to_string());
+        flags.push(address.clone());
+        flags.push(binary_path);
+
+        if let Some(mut idl) = program.idl.as_mut() {
+            // Add program address to the IDL.
+            idl.metadata = Some(serde_json::to_value(IdlTestMetadata { address })?);
+
+            // Persist it.
+            let idl_out = workspace_dir
+                .join("target/idl")
+                .join(&idl.name)
+                .with_extension("json");
+            write_idl(idl, OutFile::File(idl_out))?;
+        }
+    }
+
+    if let Some(test) = cfg.test.as_ref() {
+        if let Some(genesis) = &test.genesis {
+            for entry in genesis {
+                let program_path = workspace_dir.join(&entry.program);
+                if !program_path.exists() {
+                    return Err(anyhow!(
+                        "Program in genesis configuration does not exist at path: {}",
+                        program_path.display()
+                    ));
+                }
+                flags.push("--bpf-program".to_string());
+                flags.push(entry.address.clone());
+                flags.push(entry.program.clone());
+            }
+        }
+        if let Some(clone) = &test.clone {
+            for entry in clone {
+                flags.push("--clone".to_string());
+                flags.push(entry.address.clone());
+            }
+        }
+        if let Some(account) = &test.account {
+            for entry in account {
+                flags.push("--account".to_string());
+                flags.push(entry.address.clone());
+                flags.push(entry.filename.clone());
+            }
+        }
+        if let Some(validator) = &test.validator {
+            for (key, value) in serde_json::to_value(validator)?.as_object().unwrap() {
+                if key == "ledger" {
+                    continue;
+                };
+                flags.push(format!("--{}", key.replace("_", "-")));
+                if let serde_json::Value::String(v) = value {
+                    flags.push(v.to_string());
+                } else {
+                    flags.push(value.to_string());
+                }
+            }
+        }
+    }
+
+    Ok(flags)
+}
+
+pub(crate) fn stream_logs(
+    config: &WithPath<Config>,
+    rpc_url: &str,
+) -> Result<Vec<std::process::Child>> {
+    let workspace_dir = workspace_dir(config)?;
+    let program_logs_dir = workspace_dir.join(".anchor/program-logs");
+    if program_logs_dir.exists() {
+        fs::remove_dir_all(&program_logs_dir)?;
+    }
+    fs::create_dir_all(&program_logs_dir)?;
+    let mut handles = vec![];
+    for program in config.read_all_programs()? {
+        let mut file = File::open(
+            workspace_dir
+                .join("target/idl")
+                .join(&program.lib_name)
+                .with_extension("json"),
+        )?;
+        let mut contents = vec![];
+        file.read_to_end(&mut contents)?;
+        let idl: Idl = serde_json::from_slice(&contents)?;
+        let metadata = idl
+            .metadata
+            .ok_or_else(|| anyhow!("Program address not found."))?;
+        let metadata: IdlTestMetadata = serde_json::from_value(metadata)?;
+
+        let log_file = File::create(
+            program_logs_dir.join(format!("{}.{}.log", metadata.address, program.lib_name)),
+        )?;
+        let stdio = std::process::Stdio::from(log_file);
+        let child = std::process::Command::new("solana")
+            .arg("logs")
+            .arg(metadata.address)
+            .arg("--url")
+            .arg(rpc_url)
+            .current_dir(workspace_dir)
+            .stdout(stdio)
+            .spawn()?;
+        handles.push(child);
+    }
+    if let Some(test) = config.test.as_ref() {
+        if let Some(genesis) = &test.genesis {
+            for entry in genesis {
+                let log_file =
+                    File::create(program_logs_dir.join(format!("{}.log", entry.address)))?;
+                let stdio = std::process::Stdio::from(log_file);
+                let child = std::process::Command::new("solana")
+                    .arg("logs")
+                    .arg(entry.address.clone())
+                    .arg("--url")
+                    .arg(rpc_url)
+                    .current_dir(workspace_dir)
+                    .stdout(stdio)
+                    .spawn()?;
+                handles.push(child);
+            }
+        }
+    }
+
+    Ok(handles)
+}
+
+pub(crate) fn start_test_validator(
+    cfg: &WithPath<Config>,
+    flags: Option<Vec<String>>,
+    test_log_stdout: bool,
+) -> Result<Child> {
+    let workspace_dir = workspace_dir(cfg)?;
+    let (test_ledger_directory, test_ledger_log_filename) =
+        test_validator_file_paths(cfg, workspace_dir)?;
+
+    // Start a validator for testing.
+    let (test_validator_stdout, test_validator_stderr) = match test_log_stdout {
+        true => {
+            let test_validator_stdout_file = File::create(&test_ledger_log_filename)?;
+            let test_validator_sterr_file = test_validator_stdout_file.try_clone()?;
+            (
+                Stdio::from(test_validator_stdout_file),
+                Stdio::from(test_validator_sterr_file),
+            )
+        }
+        false => (Stdio::inherit(), Stdio::inherit()),
+    };
+
+    let rpc_url = test_validator_rpc_url(cfg);
+
+    let mut validator_handle = std::process::Command::new("solana-test-validator")
+        .arg("--ledger")
+        .arg(test_ledger_directory)
+        .arg("--mint")
+        .arg(wallet_pubkey(cfg, workspace_dir)?.to_string())
+        .args(flags.unwrap_or_default())
+        .current_dir(workspace_dir)
+        .stdout(test_validator_stdout)
+        .stderr(test_validator_stderr)
+        .spawn()
+        .map_err(|e| anyhow::format_err!("{}", e.to_string()))?;
+
+    // Wait for the validator to be ready.
+    let client = RpcClient::new(rpc_url);
+    let mut count = 0;
+    let ms_wait = cfg
+        .test
+        .as_ref()
+        .and_then(|test| test.startup_wait)
+        .unwrap_or(5_000);
+    while count < ms_wait {
+        let r = client.get_recent_blockhash();
+        if r.is_ok() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        count += 1;
+    }
+    if count == ms_wait {
+        validator_handle.kill()?;
+        return Err(anyhow!(
+            "Unable to start test validator. Check {} for errors.",
+            test_ledger_log_filename.display()
+        ));
+    }
+    Ok(validator_handle)
+}
+
+// Return the URL that solana-test-validator should be running on given the
+// configuration
+pub(crate) fn test_validator_rpc_url(cfg: &Config) -> String {
+    match &cfg.test.as_ref() {
+        Some(Test {
+            validator: Some(validator),
+            ..
+        }) => format!("http://{}:{}", validator.bind_address, validator.rpc_port),
+        _ => "http://localhost:8899".to_string(),
+    }
+}
+
+// Return the URL of the RPC websocket, served on the port following the RPC
+// port.
+fn test_validator_websocket_url(cfg: &Config) -> String {
+    match &cfg.test.as_ref() {
+        Some(Test {
+            validator: Some(validator),
+            ..
+        }) => format!("ws://{}:{}", validator.bind_address, validator.rpc_port + 1),
+        _ => "ws://localhost:8900".to_string(),
+    }
+}
+
+// The directory of the workspace's Anchor.toml, which relative paths of the
+// configuration are resolved from.
+fn workspace_dir(cfg: &WithPath<Config>) -> Result<&Path> {
+    cfg.path()
+        .parent()
+        .ok_or_else(|| anyhow!("Anchor.toml has no parent directory"))
+}
+
+// The address of the provider's wallet, whose path may be relative to the
+// workspace.
+fn wallet_pubkey(cfg: &Config, workspace_dir: &Path) -> Result<Pubkey> {
+    solana_sdk::signature::read_keypair_file(workspace_dir.join(cfg.provider.wallet.to_string()))
+        .map(|kp| kp.pubkey())
+        .map_err(|_| anyhow!("Unable to read keypair file"))
+}
+
+// Setup and return paths to the solana-test-validator ledger directory and log
+// files given the configuration. The ledger directory is relative to the
+// workspace, the log file's path is resolved from `workspace_dir`.
+fn test_validator_file_paths(cfg: &Config, workspace_dir: &Path) -> Result<(String, PathBuf)> {
+    let ledger_directory = match &cfg.test.as_ref() {
+        Some(Test {
+            validator: Some(validator),
+            ..
+        }) => &validator.ledger,
+        _ => ".anchor/test-ledger",
+    };
+
+    if !Path::new(&ledger_directory).is_relative() {
+        // Prevent absolute paths to avoid someone using / or similar, as the
+        // directory gets removed
+        return Err(anyhow!(
+            "Ledger directory {} must be relative",
+            ledger_directory
+        ));
+    }
+    let ledger_path = workspace_dir.join(ledger_directory);
+    if ledger_path.exists() {
+        fs::remove_dir_all(&ledger_path)?;
+    }
+
+    fs::create_dir_all(&ledger_path)?;
+
+    Ok((
+        ledger_directory.to_string(),
+        ledger_path.join("test-ledger-log.txt"),
+    ))
+}
@@ -0,0 +1,258 @@
+//! A cache of decoded program accounts, kept up to date through websocket
+//! subscriptions, for clients that read the same accounts over and over,
+//! e.g. market making bots.
+
+use crate::{ClientError, Program};
+use anchor_lang::__private::base64;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::AccountDeserialize;
+use solana_account_decoder::{UiAccount, UiAccountData, UiAccountEncoding};
+use solana_client::pubsub_client::{PubsubClient, PubsubClientSubscription};
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_response::{Response as RpcResponse, RpcKeyedAccount};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+// Maximum number of accounts fetched by a single `getMultipleAccounts`.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+type Accounts<T> = Arc<RwLock<HashMap<Pubkey, CachedAccount<T>>>>;
+
+/// A decoded account, along with the slot it was read at.
+#[derive(Debug)]
+pub struct CachedAccount<T> {
+    pub slot: u64,
+    pub account: Arc<T>,
+}
+
+impl<T> Clone for CachedAccount<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot,
+            account: self.account.clone(),
+        }
+    }
+}
+
+/// Decoded copies of a set of a program's accounts of type `T`. Each account
+/// is updated whenever the cluster notifies a change of it, unless the cached
+/// copy was read at a later slot. Accounts that are closed, or no longer
+/// decode as `T`, are removed.
+///
+/// The cache holds a single subscription to the program's accounts, which
+/// ends when the cache is dropped.
+pub struct ProgramCache<T> {
+    accounts: Accounts<T>,
+    _subscription: PubsubClientSubscription<RpcResponse<RpcKeyedAccount>>,
+}
+
+impl<T: AccountDeserialize + Send + Sync + 'static> ProgramCache<T> {
+    /// Caches the accounts at the given `addresses`. Changes of the
+    /// program's other accounts are notified as well, and ignored.
+    pub fn new(program: &Program, addresses: &[Pubkey]) -> Result<Self, ClientError> {
+        let accounts: Accounts<T> = Default::default();
+        let cached: HashSet<Pubkey> = addresses.iter().copied().collect();
+        // Subscribe before fetching, so that no change is missed.
+        let subscription = subscribe(program, vec![], &accounts, move |address| {
+            cached.contains(address)
+        })?;
+        let rpc_client = program.rpc();
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let response = rpc_client.get_multiple_accounts_with_commitment(
+                chunk,
+                program.cfg.options.unwrap_or_default(),
+            )?;
+            for (address, account) in chunk.iter().zip(response.value) {
+                update(
+                    &accounts,
+                    *address,
+                    response.context.slot,
+                    account.map(|account| account.data),
+                );
+            }
+        }
+        Ok(Self {
+            accounts,
+            _subscription: subscription,
+        })
+    }
+
+    /// Caches all of the program's accounts matching `filters` and decoding
+    /// as `T`, including the ones created later on.
+    ///
+    /// The accounts fetched initially are assumed to be read at the slot
+    /// preceding the fetch.
+    pub fn new_filtered(
+        program: &Program,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Self, ClientError> {
+        let accounts: Accounts<T> = Default::default();
+        let subscription = subscribe(program, filters.clone(), &accounts, |_| true)?;
+        let rpc_client = program.rpc();
+        let slot = rpc_client.get_slot_with_commitment(program.cfg.options.unwrap_or_default())?;
+        for (address, account) in rpc_client.get_program_accounts_with_config(
+            &program.program_id,
+            program_config(program, filters),
+        )? {
+            update(&accounts, address, slot, Some(account.data));
+        }
+        Ok(Self {
+            accounts,
+            _subscription: subscription,
+        })
+    }
+}
+
+// Subscribes to the changes of the program's accounts matching `filters`,
+// updating the ones at the addresses `cached` accepts from a single thread.
+fn subscribe<T: AccountDeserialize + Send + Sync + 'static>(
+    program: &Program,
+    filters: Vec<RpcFilterType>,
+    accounts: &Accounts<T>,
+    cached: impl Fn(&Pubkey) -> bool + Send + 'static,
+) -> Result<PubsubClientSubscription<RpcResponse<RpcKeyedAccount>>, ClientError> {
+    let (subscription, receiver) = PubsubClient::program_subscribe(
+        program.cfg.cluster.ws_url(),
+        &program.program_id,
+        Some(program_config(program, filters)),
+    )?;
+    let accounts = accounts.clone();
+    std::thread::spawn(move || {
+        while let Ok(response) = receiver.recv() {
+            match Pubkey::from_str(&response.value.pubkey) {
+                Ok(address) if cached(&address) => update(
+                    &accounts,
+                    address,
+                    response.context.slot,
+                    ui_account_data(&response.value.account),
+                ),
+                _ => {}
+            }
+        }
+    });
+    Ok(subscription)
+}
+
+impl<T> ProgramCache<T> {
+    /// Returns the cached account at `address`.
+    pub fn get(&self, address: &Pubkey) -> Option<Arc<T>> {
+        self.get_with_slot(address).map(|cached| cached.account)
+    }
+
+    /// Returns the cached account at `address`, along with the slot it was
+    /// read at.
+    pub fn get_with_slot(&self, address: &Pubkey) -> Option<CachedAccount<T>> {
+        self.accounts.read().unwrap().get(address).cloned()
+    }
+
+    /// Returns the addresses of all the cached accounts.
+    pub fn addresses(&self) -> Vec<Pubkey> {
+        self.accounts.read().unwrap().keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn program_config(program: &Program, filters: Vec<RpcFilterType>) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: if filters.is_empty() {
+            None
+        } else {
+            Some(filters)
+        },
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: program.cfg.options,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn ui_account_data(account: &UiAccount) -> Option<Vec<u8>> {
+    match &account.data {
+        UiAccountData::Binary(data, UiAccountEncoding::Base64) => base64::decode(data).ok(),
+        _ => None,
+    }
+}
+
+// Replaces the cached account at `address` with the one read at `slot`, or
+// removes it if it doesn't exist or decode anymore, unless the cached one is
+// more recent.
+fn update<T: AccountDeserialize>(
+    accounts: &RwLock<HashMap<Pubkey, CachedAccount<T>>>,
+    address: Pubkey,
+    slot: u64,
+    data: Option<Vec<u8>>,
+) {
+    let account = data.and_then(|data| T::try_deserialize(&mut &data[..]).ok());
+    let mut accounts = accounts.write().unwrap();
+    if matches!(accounts.get(&address), Some(cached) if cached.slot > slot) {
+        return;
+    }
+    match account {
+        Some(account) => {
+            accounts.insert(
+                address,
+                CachedAccount {
+                    slot,
+                    account: Arc::new(account),
+                },
+            );
+        }
+        None => {
+            accounts.remove(&address);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+    #[account(owner_crate = self)]
+    #[derive(Debug, PartialEq)]
+    struct Counter {
+        count: u64,
+    }
+
+    fn data(count: u64) -> Option<Vec<u8>> {
+        let mut data = vec![];
+        Counter { count }.try_serialize(&mut data).unwrap();
+        Some(data)
+    }
+
+    #[test]
+    fn updates_keep_the_latest_slot() {
+        let accounts = RwLock::new(HashMap::new());
+        let address = Pubkey::new_unique();
+        let count = |accounts: &RwLock<HashMap<Pubkey, CachedAccount<Counter>>>| {
+            accounts
+                .read()
+                .unwrap()
+                .get(&address)
+                .map(|c| c.account.count)
+        };
+
+        update(&accounts, address, 10, data(1));
+        assert_eq!(count(&accounts), Some(1));
+        update(&accounts, address, 9, data(2));
+        assert_eq!(count(&accounts), Some(1));
+        update(&accounts, address, 11, data(3));
+        assert_eq!(count(&accounts), Some(3));
+        // Closed accounts are removed.
+        update(&accounts, address, 12, Some(vec![]));
+        assert_eq!(count(&accounts), None);
+    }
+}
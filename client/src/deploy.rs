@@ -0,0 +1,242 @@
+//! Deployment and upgrades of programs owned by the upgradeable BPF loader,
+//! for automation that would otherwise shell out to `solana program deploy`.
+//!
+//! A program is first written to a buffer account, see
+//! [`Program::create_buffer`] and [`Program::write_buffer`], which is then
+//! either deployed as a new program with [`Program::deploy`], or replaces the
+//! code of an existing one with [`Program::upgrade`]. The program is the one
+//! the [`Program`] client was created for, and the transactions are paid for
+//! by its payer.
+//!
+//! ```ignore
+//! let program = client.program(program_keypair.pubkey());
+//! let buffer = Keypair::new();
+//! program.create_buffer(&buffer, &program.payer(), elf.len())?;
+//! program.write_buffer(&buffer.pubkey(), &payer, &elf, 3)?;
+//! program.deploy(&program_keypair, &buffer.pubkey(), &payer, elf.len() * 2)?;
+//! ```
+
+use crate::{ClientError, Program};
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::Message;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+// Sizes of the loader's account states preceding the program's code, if any.
+const BUFFER_METADATA_LEN: usize = 37;
+const PROGRAM_LEN: usize = 36;
+
+impl Program {
+    /// Creates the `buffer` account for a program of `program_len` bytes,
+    /// writable by `authority`.
+    pub fn create_buffer(
+        &self,
+        buffer: &dyn Signer,
+        authority: &Pubkey,
+        program_len: usize,
+    ) -> Result<Signature, ClientError> {
+        let lamports = self
+            .rpc()
+            .get_minimum_balance_for_rent_exemption(BUFFER_METADATA_LEN + program_len)?;
+        let ixs = bpf_loader_upgradeable::create_buffer(
+            &self.payer(),
+            &buffer.pubkey(),
+            authority,
+            lamports,
+            program_len,
+        )?;
+        ixs.into_iter()
+            .fold(self.request(), |request, ix| request.instruction(ix))
+            .signer(buffer)
+            .send()
+    }
+
+    /// Writes `program_data` to `buffer`, in as many transactions as needed.
+    /// Each write is retried up to `max_retries` times before failing, and
+    /// can be resumed by writing the same data again.
+    pub fn write_buffer(
+        &self,
+        buffer: &Pubkey,
+        authority: &dyn Signer,
+        program_data: &[u8],
+        max_retries: usize,
+    ) -> Result<(), ClientError> {
+        let chunk_size = write_chunk_size(&self.payer(), buffer, &authority.pubkey());
+        for ix in write_instructions(buffer, &authority.pubkey(), program_data, chunk_size) {
+            let mut retries = 0;
+            while let Err(e) = self
+                .request()
+                .instruction(ix.clone())
+                .signer(authority)
+                .send()
+            {
+                if retries == max_retries {
+                    return Err(e);
+                }
+                retries += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Transfers the authority of `buffer` to `new_authority`, e.g. to the
+    /// program's upgrade authority before it upgrades the program.
+    pub fn set_buffer_authority(
+        &self,
+        buffer: &Pubkey,
+        authority: &dyn Signer,
+        new_authority: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        self.request()
+            .instruction(bpf_loader_upgradeable::set_buffer_authority(
+                buffer,
+                &authority.pubkey(),
+                new_authority,
+            ))
+            .signer(authority)
+            .send()
+    }
+
+    /// Deploys the program from `buffer`, which must be owned by
+    /// `upgrade_authority`, leaving room for upgrades of up to
+    /// `max_data_len` bytes. `program` is the program's keypair.
+    pub fn deploy(
+        &self,
+        program: &dyn Signer,
+        buffer: &Pubkey,
+        upgrade_authority: &dyn Signer,
+        max_data_len: usize,
+    ) -> Result<Signature, ClientError> {
+        if program.pubkey() != self.program_id {
+            return Err(ClientError::DeployError(format!(
+                "program keypair {} doesn't match the program id {}",
+                program.pubkey(),
+                self.program_id
+            )));
+        }
+        let lamports = self
+            .rpc()
+            .get_minimum_balance_for_rent_exemption(PROGRAM_LEN)?;
+        let ixs = bpf_loader_upgradeable::deploy_with_max_program_len(
+            &self.payer(),
+            &self.program_id,
+            buffer,
+            &upgrade_authority.pubkey(),
+            lamports,
+            max_data_len,
+        )?;
+        ixs.into_iter()
+            .fold(self.request(), |request, ix| request.instruction(ix))
+            .signer(program)
+            .signer(upgrade_authority)
+            .send()
+    }
+
+    /// Replaces the program's code with the one in `buffer`, which must be
+    /// owned by the program's `upgrade_authority`. The buffer is closed, and
+    /// its lamports are sent to `spill`.
+    pub fn upgrade(
+        &self,
+        buffer: &Pubkey,
+        upgrade_authority: &dyn Signer,
+        spill: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        self.request()
+            .instruction(bpf_loader_upgradeable::upgrade(
+                &self.program_id,
+                buffer,
+                &upgrade_authority.pubkey(),
+                spill,
+            ))
+            .signer(upgrade_authority)
+            .send()
+    }
+
+    /// Closes `buffer`, e.g. after a failed deployment, sending its lamports
+    /// to `recipient`.
+    pub fn close_buffer(
+        &self,
+        buffer: &Pubkey,
+        authority: &dyn Signer,
+        recipient: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        self.request()
+            .instruction(bpf_loader_upgradeable::close(
+                buffer,
+                recipient,
+                &authority.pubkey(),
+            ))
+            .signer(authority)
+            .send()
+    }
+}
+
+// Largest number of bytes a single transaction can write to a buffer.
+fn write_chunk_size(payer: &Pubkey, buffer: &Pubkey, authority: &Pubkey) -> usize {
+    let ix = bpf_loader_upgradeable::write(buffer, authority, 0, vec![]);
+    let tx = Transaction::new_unsigned(Message::new(&[ix], Some(payer)));
+    let tx_len = bincode::serialized_size(&tx).unwrap() as usize;
+    // The instruction data's length prefix grows by a byte once it holds
+    // more than 127 bytes.
+    PACKET_DATA_SIZE - tx_len - 1
+}
+
+fn write_instructions(
+    buffer: &Pubkey,
+    authority: &Pubkey,
+    program_data: &[u8],
+    chunk_size: usize,
+) -> Vec<Instruction> {
+    program_data
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(i, chunk)| {
+            bpf_loader_upgradeable::write(
+                buffer,
+                authority,
+                (i * chunk_size) as u32,
+                chunk.to_vec(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::loader_upgradeable_instruction::UpgradeableLoaderInstruction;
+
+    #[test]
+    fn writes_fit_in_a_transaction() {
+        let (payer, buffer, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let chunk_size = write_chunk_size(&payer, &buffer, &authority);
+        let program_data: Vec<u8> = (0..3 * chunk_size - 1).map(|i| i as u8).collect();
+        let ixs = write_instructions(&buffer, &authority, &program_data, chunk_size);
+        assert_eq!(ixs.len(), 3);
+
+        let mut written = vec![];
+        for ix in ixs {
+            let tx =
+                Transaction::new_unsigned(Message::new(std::slice::from_ref(&ix), Some(&payer)));
+            assert!(
+                bincode::serialized_size(&tx).unwrap() as usize
+                    <= PACKET_DATA_SIZE
+            );
+            match bincode::deserialize(&ix.data).unwrap() {
+                UpgradeableLoaderInstruction::Write { offset, bytes } => {
+                    assert_eq!(offset as usize, written.len());
+                    written.extend(bytes);
+                }
+                _ => panic!("not a write"),
+            }
+        }
+        assert_eq!(written, program_data);
+    }
+}
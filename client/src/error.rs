@@ -0,0 +1,161 @@
+//! Decoding of the error codes of failed transactions into
+//! [`ClientError::Program`], against anchor's own codes and the ones of the
+//! program's `#[error]` enum given to
+//! [`RequestBuilder::errors`](crate::RequestBuilder::errors):
+//!
+//! ```ignore
+//! let err = program
+//!     .request()
+//!     .accounts(accounts)
+//!     .args(instruction::Withdraw { amount })
+//!     .errors::<ErrorCode>()
+//!     .send()
+//!     .unwrap_err();
+//! assert!(matches!(err, ClientError::Program { name, .. } if name == "InsufficientFunds"));
+//! ```
+
+use crate::ClientError;
+use anchor_lang::error::{ErrorCode, ErrorCodes};
+use anchor_lang::solana_program::instruction::InstructionError;
+use regex::Regex;
+use solana_client::client_error::{ClientError as SolanaClientError, ClientErrorKind};
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::transaction::TransactionError;
+
+/// Decodes the error of a failed transaction, from the transaction error or,
+/// failing that, its logs. Errors without a known code are returned as is.
+pub(crate) fn decode(
+    err: SolanaClientError,
+    tables: &[&'static [(u32, &'static str, &'static str)]],
+) -> ClientError {
+    let (tx_err, logs) = match err.kind() {
+        ClientErrorKind::TransactionError(e) => (Some(e.clone()), vec![]),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => (result.err.clone(), result.logs.clone().unwrap_or_default()),
+        _ => return err.into(),
+    };
+    program_error(tx_err.as_ref(), logs, tables).unwrap_or_else(|| err.into())
+}
+
+/// Decodes the error of a failed simulation, if its code is known.
+pub(crate) fn decode_simulation(
+    result: &RpcSimulateTransactionResult,
+    tables: &[&'static [(u32, &'static str, &'static str)]],
+) -> Option<ClientError> {
+    let tx_err = result.err.as_ref()?;
+    program_error(
+        Some(tx_err),
+        result.logs.clone().unwrap_or_default(),
+        tables,
+    )
+}
+
+fn program_error(
+    tx_err: Option<&TransactionError>,
+    logs: Vec<String>,
+    tables: &[&'static [(u32, &'static str, &'static str)]],
+) -> Option<ClientError> {
+    let code = match tx_err {
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => *code,
+        _ => custom_code_from_logs(&logs)?,
+    };
+    let (code, name, msg) = tables
+        .iter()
+        .copied()
+        .chain([ErrorCode::ERROR_CODES])
+        .flatten()
+        .find(|(c, _, _)| *c == code)?;
+    Some(ClientError::Program {
+        code: *code,
+        name: name.to_string(),
+        msg: msg.to_string(),
+        logs,
+    })
+}
+
+// The code of the last custom program error the runtime logged. Program ids
+// are matched as base58 tokens, so that program logs, e.g. `Program log:
+// Program .. failed: custom program error: 0x0`, aren't taken for the
+// runtime's.
+fn custom_code_from_logs(logs: &[String]) -> Option<u32> {
+    let re = Regex::new(
+        r"^Program [1-9A-HJ-NP-Za-km-z]+ failed: custom program error: 0x([0-9a-fA-F]+)$",
+    )
+    .unwrap();
+    logs.iter()
+        .rev()
+        .find_map(|l| re.captures(l))
+        .and_then(|c| u32::from_str_radix(&c[1], 16).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODES: &[&[(u32, &str, &str)]] = &[
+        &[(6000, "InsufficientFunds", "Not enough funds")],
+        &[(6100, "Paused", "The protocol is paused")],
+    ];
+
+    #[test]
+    fn decodes_custom_codes() {
+        let tx_err = TransactionError::InstructionError(0, InstructionError::Custom(6000));
+        match program_error(Some(&tx_err), vec![], CODES) {
+            Some(ClientError::Program {
+                code, name, msg, ..
+            }) => {
+                assert_eq!(
+                    (code, name.as_str(), msg.as_str()),
+                    (6000, "InsufficientFunds", "Not enough funds")
+                );
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Anchor's own codes are always known.
+        let tx_err = TransactionError::InstructionError(1, InstructionError::Custom(2000));
+        match program_error(Some(&tx_err), vec![], &[]) {
+            Some(ClientError::Program { name, .. }) => assert_eq!(name, "ConstraintMut"),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // As are the codes of later tables, e.g. of extended enums.
+        let tx_err = TransactionError::InstructionError(0, InstructionError::Custom(6100));
+        match program_error(Some(&tx_err), vec![], CODES) {
+            Some(ClientError::Program { name, .. }) => assert_eq!(name, "Paused"),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let tx_err = TransactionError::InstructionError(0, InstructionError::Custom(7000));
+        assert!(program_error(Some(&tx_err), vec![], CODES).is_none());
+    }
+
+    #[test]
+    fn decodes_codes_from_logs() {
+        let logs = vec![
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS invoke [1]".to_string(),
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS failed: custom program error: 0x1770"
+                .to_string(),
+        ];
+        match program_error(None, logs.clone(), CODES) {
+            Some(ClientError::Program { code, logs: l, .. }) => {
+                assert_eq!(code, 6000);
+                assert_eq!(l, logs);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ignores_program_logs_imitating_failures() {
+        let logs = vec![
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS invoke [1]".to_string(),
+            "Program log: Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS failed: custom program error: 0x1770"
+                .to_string(),
+        ];
+        assert!(program_error(None, logs, CODES).is_none());
+    }
+}
@@ -0,0 +1,241 @@
+//! Account fixtures, i.e. snapshots of accounts' lamports, owner and data,
+//! for deterministic tests against real cluster state.
+//!
+//! A fixture is fetched from a cluster, optionally modified through its typed
+//! helpers and saved to disk, either as JSON in the format written by
+//! `solana account --output json`, or as bincode. JSON fixtures can be
+//! loaded into the test validator with Anchor.toml's `[[test.account]]`, and
+//! any fixture into `solana-program-test` with
+//! `program_test.add_account(fixture.address, fixture.account)`.
+
+use crate::ClientError;
+use anchor_lang::__private::base64;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{AccountDeserialize, AccountSerialize, Owner};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A snapshot of the account at `address`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountFixture {
+    pub address: Pubkey,
+    pub account: Account,
+}
+
+impl AccountFixture {
+    pub fn new(address: Pubkey, account: Account) -> Self {
+        Self { address, account }
+    }
+
+    /// Creates a fixture holding the serialized `account`, owned by its
+    /// program.
+    pub fn from_typed<T: AccountSerialize + Owner>(
+        address: Pubkey,
+        lamports: u64,
+        account: &T,
+    ) -> Result<Self, ClientError> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data)?;
+        Ok(Self::new(
+            address,
+            Account {
+                lamports,
+                data,
+                owner: T::owner(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ))
+    }
+
+    /// Fetches the account at `address`.
+    pub fn fetch(rpc_client: &RpcClient, address: Pubkey) -> Result<Self, ClientError> {
+        let account = rpc_client
+            .get_account_with_commitment(&address, CommitmentConfig::processed())?
+            .value
+            .ok_or(ClientError::AccountNotFound)?;
+        Ok(Self::new(address, account))
+    }
+
+    /// Deserializes the account's data.
+    pub fn deserialize<T: AccountDeserialize>(&self) -> Result<T, ClientError> {
+        let mut data: &[u8] = &self.account.data;
+        T::try_deserialize(&mut data).map_err(Into::into)
+    }
+
+    /// Overwrites the account's data with `value`. The data keeps its length,
+    /// unless `value` doesn't fit, in which case it's extended.
+    pub fn set<T: AccountSerialize>(&mut self, value: &T) -> Result<(), ClientError> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data)?;
+        if data.len() > self.account.data.len() {
+            self.account.data.resize(data.len(), 0);
+        }
+        self.account.data[..data.len()].copy_from_slice(&data);
+        Ok(())
+    }
+
+    /// Deserializes the account's data, applies `f` to it and writes it back.
+    pub fn mutate<T: AccountSerialize + AccountDeserialize>(
+        &mut self,
+        f: impl FnOnce(&mut T),
+    ) -> Result<(), ClientError> {
+        let mut value = self.deserialize::<T>()?;
+        f(&mut value);
+        self.set(&value)
+    }
+
+    pub fn to_json(&self) -> Result<String, ClientError> {
+        serde_json::to_string_pretty(&JsonFixture::from(self))
+            .map_err(|e| ClientError::FixtureError(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        let fixture: JsonFixture =
+            serde_json::from_str(json).map_err(|e| ClientError::FixtureError(e.to_string()))?;
+        fixture.into_fixture()
+    }
+
+    pub fn to_bincode(&self) -> Result<Vec<u8>, ClientError> {
+        bincode::serialize(self).map_err(|e| ClientError::FixtureError(e.to_string()))
+    }
+
+    pub fn from_bincode(data: &[u8]) -> Result<Self, ClientError> {
+        bincode::deserialize(data).map_err(|e| ClientError::FixtureError(e.to_string()))
+    }
+
+    /// Writes the fixture to `path`, as JSON if its extension is `json` and
+    /// as bincode otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ClientError> {
+        let path = path.as_ref();
+        let data = match is_json(path) {
+            true => self.to_json()?.into_bytes(),
+            false => self.to_bincode()?,
+        };
+        std::fs::write(path, data).map_err(Into::into)
+    }
+
+    /// Reads a fixture written by [`save`](Self::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        match is_json(path) {
+            true => Self::from_json(
+                std::str::from_utf8(&data).map_err(|e| ClientError::FixtureError(e.to_string()))?,
+            ),
+            false => Self::from_bincode(&data),
+        }
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "json")
+}
+
+// The JSON representation written by `solana account --output json`.
+#[derive(Serialize, Deserialize)]
+struct JsonFixture {
+    pubkey: String,
+    account: JsonAccount,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonAccount {
+    lamports: u64,
+    // Base64 encoded data and the encoding.
+    data: (String, String),
+    owner: String,
+    executable: bool,
+    rent_epoch: u64,
+}
+
+impl From<&AccountFixture> for JsonFixture {
+    fn from(fixture: &AccountFixture) -> Self {
+        Self {
+            pubkey: fixture.address.to_string(),
+            account: JsonAccount {
+                lamports: fixture.account.lamports,
+                data: (base64::encode(&fixture.account.data), "base64".to_string()),
+                owner: fixture.account.owner.to_string(),
+                executable: fixture.account.executable,
+                rent_epoch: fixture.account.rent_epoch,
+            },
+        }
+    }
+}
+
+impl JsonFixture {
+    fn into_fixture(self) -> Result<AccountFixture, ClientError> {
+        let parse_pubkey =
+            |s: &str| Pubkey::from_str(s).map_err(|e| ClientError::FixtureError(e.to_string()));
+        let (data, encoding) = self.account.data;
+        if encoding != "base64" {
+            return Err(ClientError::FixtureError(format!(
+                "Unsupported data encoding: {}",
+                encoding
+            )));
+        }
+        Ok(AccountFixture::new(
+            parse_pubkey(&self.pubkey)?,
+            Account {
+                lamports: self.account.lamports,
+                data: base64::decode(&data)
+                    .map_err(|e| ClientError::FixtureError(e.to_string()))?,
+                owner: parse_pubkey(&self.account.owner)?,
+                executable: self.account.executable,
+                rent_epoch: self.account.rent_epoch,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+    #[account(owner_crate = self)]
+    #[derive(Debug, PartialEq)]
+    struct Counter {
+        count: u64,
+    }
+
+    #[test]
+    fn typed_mutation() {
+        let mut fixture =
+            AccountFixture::from_typed(Pubkey::new_unique(), 1, &Counter { count: 1 }).unwrap();
+        assert_eq!(fixture.account.owner, ID);
+        fixture
+            .mutate(|counter: &mut Counter| counter.count += 1)
+            .unwrap();
+        assert_eq!(
+            fixture.deserialize::<Counter>().unwrap(),
+            Counter { count: 2 }
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let fixture =
+            AccountFixture::from_typed(Pubkey::new_unique(), 1, &Counter { count: 1 }).unwrap();
+        let json = fixture.to_json().unwrap();
+        assert!(json.contains(&format!("\"pubkey\": \"{}\"", fixture.address)));
+        assert!(json.contains("\"rentEpoch\": 0"));
+        assert_eq!(AccountFixture::from_json(&json).unwrap(), fixture);
+    }
+
+    #[test]
+    fn bincode_round_trip() {
+        let fixture =
+            AccountFixture::from_typed(Pubkey::new_unique(), 1, &Counter { count: 1 }).unwrap();
+        let data = fixture.to_bincode().unwrap();
+        assert_eq!(AccountFixture::from_bincode(&data).unwrap(), fixture);
+    }
+}
@@ -1,17 +1,31 @@
 //! `anchor_client` provides an RPC client to send transactions and fetch
 //! deserialized accounts from Solana programs written in `anchor_lang`.
 
-use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::error::ErrorCodes;
+use anchor_lang::interface_registry::{
+    interface_hash, InterfaceRegistration, REGISTRATION_ACCOUNT_LEN,
+};
+use anchor_lang::paged_vec::PagedVecHeader;
+use anchor_lang::schema_registry::{self, ProgramSchema, SchemaDrift};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction, InstructionError};
 use anchor_lang::solana_program::program_error::ProgramError;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang::solana_program::system_program;
-use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_lang::{
+    AccountDeserialize, AccountField, AnchorDeserialize, Discriminator, InstructionData,
+    ProgramData, ResolveAccounts, ToAccountMetas,
+};
 use regex::Regex;
 use solana_client::client_error::ClientError as SolanaClientError;
 use solana_client::pubsub_client::{PubsubClient, PubsubClientError, PubsubClientSubscription};
-use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
-use solana_client::rpc_response::{Response as RpcResponse, RpcLogsResponse};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{
+    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_response::{
+    Response as RpcResponse, RpcLogsResponse, RpcSimulateTransactionResult,
+};
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::signature::{Keypair, Signature, Signer};
 use solana_sdk::transaction::Transaction;
@@ -19,11 +33,25 @@ use std::convert::Into;
 use thiserror::Error;
 
 pub use anchor_lang;
+pub use cache::ProgramCache;
 pub use cluster::Cluster;
+pub use fixture::AccountFixture;
+pub use paged_vec::PagedVec;
+pub use program_accounts::ProgramAccounts;
+pub use simulation::SimulationResult;
 pub use solana_client;
 pub use solana_sdk;
+pub use token::{Mint, TokenAccount};
 
+pub mod cache;
 mod cluster;
+pub mod deploy;
+mod error;
+pub mod fixture;
+pub mod paged_vec;
+pub mod program_accounts;
+pub mod simulation;
+pub mod token;
 
 /// EventHandle unsubscribes from a program event stream on drop.
 pub type EventHandle = PubsubClientSubscription<RpcResponse<RpcLogsResponse>>;
@@ -110,22 +138,162 @@ impl Program {
 
     /// Returns the account at the given address.
     pub fn account<T: AccountDeserialize>(&self, address: Pubkey) -> Result<T, ClientError> {
-        let rpc_client = RpcClient::new_with_commitment(
-            self.cfg.cluster.url().to_string(),
-            self.cfg.options.unwrap_or_default(),
-        );
-        let account = rpc_client
+        let data = self.account_data(address)?;
+        T::try_deserialize(&mut &data[..]).map_err(Into::into)
+    }
+
+    /// Returns all of the program's accounts of type `T` matching `filters`,
+    /// along with their addresses.
+    pub fn accounts<T: AccountDeserialize + Discriminator>(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, T)>, ClientError> {
+        self.accounts_lazy(filters)?.collect()
+    }
+
+    /// Returns an iterator fetching the program's accounts of type `T`
+    /// matching `filters` page by page, for programs with too many accounts
+    /// to hold in memory. See [`ProgramAccounts`].
+    pub fn accounts_lazy<T: AccountDeserialize + Discriminator>(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<ProgramAccounts<T>, ClientError> {
+        ProgramAccounts::new(self, filters)
+    }
+
+    /// Returns `field` of each of the program's accounts of type `T` matching
+    /// `filters`, along with their addresses, fetching only the accounts'
+    /// data up to the end of the field, e.g. only the `authority` of every
+    /// pool:
+    ///
+    /// ```ignore
+    /// let authorities = program.account_fields(account_field!(Pool, authority), vec![])?;
+    /// ```
+    pub fn account_fields<T: Discriminator, F: AnchorDeserialize>(
+        &self,
+        field: AccountField<T, F>,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, F)>, ClientError> {
+        let listed = program_accounts::list(self, filters, field.end())?;
+        program_accounts::decode_fields(field, listed)
+    }
+
+    /// Returns the number of the program's accounts of type `T` matching
+    /// `filters`, fetching only their discriminators.
+    pub fn account_count<T: Discriminator>(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<usize, ClientError> {
+        let discriminator = T::discriminator();
+        Ok(program_accounts::list(self, filters, 8)?
+            .iter()
+            .filter(|(_, account)| account.data.starts_with(&discriminator))
+            .count())
+    }
+
+    /// Returns an iterator over the items of the paged list with `header`,
+    /// held by the account at `list`, fetching them page by page. See
+    /// [`PagedVec`].
+    pub fn paged_vec<T: AnchorDeserialize>(
+        &self,
+        list: Pubkey,
+        header: PagedVecHeader,
+    ) -> PagedVec<T> {
+        PagedVec::new(self, list, header)
+    }
+
+    /// Returns the programs registered by `authority` as implementing the
+    /// `#[interface]` trait named `interface`, if this program was built
+    /// with `#[program(interface_registry)]`.
+    pub fn interface_implementations(
+        &self,
+        interface: &str,
+        authority: &Pubkey,
+    ) -> Result<Vec<Pubkey>, ClientError> {
+        let interface = interface_hash(interface);
+        let filters = vec![RpcFilterType::DataSize(REGISTRATION_ACCOUNT_LEN as u64)];
+        Ok(self
+            .accounts::<InterfaceRegistration>(filters)?
+            .into_iter()
+            .map(|(_, registration)| registration)
+            .filter(|r| r.interface == interface && &r.authority == authority)
+            .map(|r| r.implementation)
+            .collect())
+    }
+
+    /// Returns the schema record the program published, if it was built with
+    /// `#[program(schema_registry)]`.
+    pub fn schema(&self) -> Result<ProgramSchema, ClientError> {
+        self.account(schema_registry::schema_address(&self.program_id).0)
+    }
+
+    /// Checks that the program was deployed with the IDL `idl_json`, as
+    /// written by `anchor build`, and the event schema version
+    /// `event_schema_version`, according to the schema record it published
+    /// for its current deployment. Fails with `ClientError::SchemaDrift`
+    /// otherwise, e.g. for indexers to stop decoding.
+    pub fn verify_schema(
+        &self,
+        idl_json: &[u8],
+        event_schema_version: u32,
+    ) -> Result<(), ClientError> {
+        let schema = self.schema()?;
+        let program_data: ProgramData =
+            self.account(schema_registry::program_data_address(&self.program_id))?;
+        schema.check(
+            &schema_registry::idl_hash(idl_json),
+            event_schema_version,
+            program_data.slot,
+        )?;
+        Ok(())
+    }
+
+    /// Returns the SPL token account at the given address.
+    pub fn token_account(&self, address: Pubkey) -> Result<TokenAccount, ClientError> {
+        token::unpack_token_account(&self.account_data(address)?)
+    }
+
+    /// Returns the SPL token mint at the given address.
+    pub fn mint(&self, address: Pubkey) -> Result<Mint, ClientError> {
+        token::unpack_mint(&self.account_data(address)?)
+    }
+
+    /// Returns the balance of the SPL token account at the given address.
+    pub fn token_balance(&self, address: Pubkey) -> Result<u64, ClientError> {
+        Ok(self.token_account(address)?.amount)
+    }
+
+    /// Returns the balance of the associated token account of `owner` for
+    /// `mint`.
+    pub fn associated_token_balance(
+        &self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<u64, ClientError> {
+        self.token_balance(token::get_associated_token_address(owner, mint))
+    }
+
+    fn account_data(&self, address: Pubkey) -> Result<Vec<u8>, ClientError> {
+        let account = self
+            .rpc()
             .get_account_with_commitment(&address, CommitmentConfig::processed())?
             .value
             .ok_or(ClientError::AccountNotFound)?;
-        let mut data: &[u8] = &account.data;
-        T::try_deserialize(&mut data).map_err(Into::into)
+        Ok(account.data)
+    }
+
+    /// Returns a snapshot of the account at the given address, to be used as
+    /// a test fixture.
+    pub fn account_fixture(&self, address: Pubkey) -> Result<AccountFixture, ClientError> {
+        AccountFixture::fetch(&self.rpc(), address)
     }
 
     pub fn state<T: AccountDeserialize>(&self) -> Result<T, ClientError> {
         self.account(anchor_lang::__private::state::address(&self.program_id))
     }
 
+    /// Returns an RPC client of the program's cluster, with its commitment,
+    /// for requests without a typed helper.
     pub fn rpc(&self) -> RpcClient {
         RpcClient::new_with_commitment(
             self.cfg.cluster.url().to_string(),
@@ -137,6 +305,57 @@ impl Program {
         self.program_id
     }
 
+    /// Airdrops `lamports` to the payer and waits for the airdrop to be
+    /// confirmed. Requires a cluster with a faucet, e.g. localnet.
+    pub fn request_airdrop_and_confirm(&self, lamports: u64) -> Result<Signature, ClientError> {
+        self.airdrop_and_confirm(&self.payer(), lamports)
+    }
+
+    /// Airdrops `lamports` to `to` and waits for the airdrop to be confirmed.
+    pub fn airdrop_and_confirm(
+        &self,
+        to: &Pubkey,
+        lamports: u64,
+    ) -> Result<Signature, ClientError> {
+        let rpc_client = self.rpc();
+        let signature = rpc_client.request_airdrop(to, lamports)?;
+        rpc_client
+            .poll_for_signature_with_commitment(&signature, self.cfg.options.unwrap_or_default())?;
+        Ok(signature)
+    }
+
+    /// Returns a new keypair funded with `lamports` via airdrop.
+    pub fn funded_keypair(&self, lamports: u64) -> Result<Keypair, ClientError> {
+        let keypair = Keypair::new();
+        self.airdrop_and_confirm(&keypair.pubkey(), lamports)?;
+        Ok(keypair)
+    }
+
+    /// Creates the associated token account of `owner` for `mint`, paid for
+    /// by the payer, and returns its address.
+    pub fn create_associated_token_account(
+        &self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<Pubkey, ClientError> {
+        self.request()
+            .create_associated_token_account(owner, mint)
+            .send()?;
+        Ok(token::get_associated_token_address(owner, mint))
+    }
+
+    /// Returns a new keypair funded with `lamports` via airdrop, along with
+    /// its newly created associated token account for `mint`.
+    pub fn funded_keypair_with_ata(
+        &self,
+        lamports: u64,
+        mint: &Pubkey,
+    ) -> Result<(Keypair, Pubkey), ClientError> {
+        let keypair = self.funded_keypair(lamports)?;
+        let ata = self.create_associated_token_account(&keypair.pubkey(), mint)?;
+        Ok((keypair, ata))
+    }
+
     pub fn on<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
         &self,
         f: impl Fn(&EventContext, T) + Send + 'static,
@@ -200,16 +419,125 @@ impl Program {
         });
         Ok(client)
     }
+
+    /// Fetches the events of type `T` emitted by the program, including via
+    /// CPI, in successful transactions from `start_slot` to `end_slot`
+    /// inclusive. Events are returned oldest first.
+    ///
+    /// This pages through the program's signature history from the newest
+    /// transaction backwards, so it's meant for backfilling an indexer up to
+    /// the point where it can switch to [`on`](Self::on).
+    pub fn events_between<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
+        &self,
+        start_slot: u64,
+        end_slot: u64,
+    ) -> Result<Vec<(EventContext, T)>, ClientError> {
+        let rpc_client = self.rpc();
+
+        // Signatures are returned newest first, one page at a time.
+        let mut signatures = Vec::new();
+        let mut before = None;
+        'pages: loop {
+            let page = rpc_client.get_signatures_for_address_with_config(
+                &self.program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURES_PAGE_LIMIT),
+                    commitment: self.cfg.options,
+                },
+            )?;
+            for status in &page {
+                if status.slot < start_slot {
+                    break 'pages;
+                }
+                if status.slot <= end_slot && status.err.is_none() {
+                    let signature: Signature = status
+                        .signature
+                        .parse()
+                        .map_err(|_| ClientError::LogParseError(status.signature.clone()))?;
+                    signatures.push((signature, status.slot));
+                }
+            }
+            match page.last() {
+                Some(last) if page.len() == SIGNATURES_PAGE_LIMIT => {
+                    before = Some(
+                        last.signature
+                            .parse()
+                            .map_err(|_| ClientError::LogParseError(last.signature.clone()))?,
+                    );
+                }
+                _ => break,
+            }
+        }
+
+        let self_program_str = self.program_id.to_string();
+        let mut events = Vec::new();
+        for (signature, slot) in signatures.into_iter().rev() {
+            let tx = rpc_client.get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    commitment: self.cfg.options,
+                    ..RpcTransactionConfig::default()
+                },
+            )?;
+            // `log_messages` is an `OptionSerializer` in newer
+            // solana-transaction-status releases.
+            #[allow(clippy::useless_conversion)]
+            let logs: Option<Vec<String>> = match tx.transaction.meta {
+                Some(meta) => meta.log_messages.into(),
+                None => None,
+            };
+            for event in parse_logs::<T>(&self_program_str, &logs.unwrap_or_default())? {
+                events.push((EventContext { signature, slot }, event));
+            }
+        }
+        Ok(events)
+    }
+}
+
+// Maximum page size of `getSignaturesForAddress`.
+const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
+// Decodes the events emitted by `self_program_str` from a transaction's logs.
+// Unlike the subscription in `Program::on`, this tracks the full invocation
+// stack so that events emitted from CPIs into the program and from any of
+// the transaction's instructions are found.
+fn parse_logs<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
+    self_program_str: &str,
+    logs: &[String],
+) -> Result<Vec<T>, ClientError> {
+    // Program ids are matched as base58 tokens, so that program logs aren't
+    // taken for invocations.
+    let invoke_re = Regex::new(r"^Program ([1-9A-HJ-NP-Za-km-z]+) invoke \[\d+\]$").unwrap();
+    let return_re = Regex::new(r"^Program [1-9A-HJ-NP-Za-km-z]+ (success|failed: .*)$").unwrap();
+
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for l in logs {
+        if let Some(c) = invoke_re.captures(l) {
+            stack.push(c.get(1).unwrap().as_str());
+        } else if return_re.is_match(l) {
+            stack.pop();
+        } else if stack.last() == Some(&self_program_str) {
+            if let (Some(event), _, _) = handle_program_log::<T>(self_program_str, l)? {
+                events.push(event);
+            }
+        }
+    }
+    Ok(events)
 }
 
 fn handle_program_log<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
     self_program_str: &str,
     l: &str,
 ) -> Result<(Option<T>, Option<String>, bool), ClientError> {
-    // Log emitted from the current program.
-    if l.starts_with("Program log:") {
-        let log = l.to_string().split_off("Program log: ".len());
-        let borsh_bytes = match anchor_lang::__private::base64::decode(&log) {
+    // Log emitted from the current program, either with `msg!` or as data.
+    if let Some(log) = l
+        .strip_prefix("Program log: ")
+        .or_else(|| l.strip_prefix("Program data: "))
+    {
+        let borsh_bytes = match anchor_lang::__private::base64::decode(log) {
             Ok(borsh_bytes) => borsh_bytes,
             _ => {
                 #[cfg(feature = "debug")]
@@ -218,6 +546,17 @@ fn handle_program_log<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
             }
         };
 
+        // Anonymous events can't be told apart from other logs, so any log
+        // decoding into one is taken as one.
+        if T::ANONYMOUS {
+            let event = T::try_from_slice(&borsh_bytes).ok();
+            return Ok((event, None, false));
+        }
+
+        if borsh_bytes.len() < 8 {
+            return Ok((None, None, false));
+        }
+
         let mut slice: &[u8] = &borsh_bytes[..];
         let disc: [u8; 8] = {
             let mut disc = [0; 8];
@@ -311,6 +650,27 @@ pub enum ClientError {
     SolanaClientPubsubError(#[from] PubsubClientError),
     #[error("Unable to parse log: {0}")]
     LogParseError(String),
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid account fixture: {0}")]
+    FixtureError(String),
+    #[error("{0}")]
+    InstructionError(#[from] InstructionError),
+    #[error("Deployment failed: {0}")]
+    DeployError(String),
+    /// The program's schema record doesn't match the expected schema, see
+    /// [`Program::verify_schema`].
+    #[error("Schema drift: {0}")]
+    SchemaDrift(#[from] SchemaDrift),
+    /// A transaction failed with a known error code, of anchor or of the
+    /// program's `#[error]` enum, see [`RequestBuilder::errors`].
+    #[error("{name} ({code}): {msg}")]
+    Program {
+        code: u32,
+        name: String,
+        msg: String,
+        logs: Vec<String>,
+    },
 }
 
 /// `RequestBuilder` provides a builder interface to create and send
@@ -327,6 +687,8 @@ pub struct RequestBuilder<'a> {
     signers: Vec<&'a dyn Signer>,
     // True if the user is sending a state instruction.
     namespace: RequestNamespace,
+    // The tables of error codes decoded on failure.
+    error_codes: Vec<&'static [(u32, &'static str, &'static str)]>,
 }
 
 #[derive(PartialEq)]
@@ -357,6 +719,7 @@ impl<'a> RequestBuilder<'a> {
             instruction_data: None,
             signers: Vec::new(),
             namespace,
+            error_codes: Vec::new(),
         }
     }
 
@@ -370,6 +733,16 @@ impl<'a> RequestBuilder<'a> {
         self
     }
 
+    /// Decodes the codes of the program's `#[error]` enum `E`, and of the
+    /// enums it extends, when the transaction fails, into
+    /// [`ClientError::Program`]. It can be called again for the errors of
+    /// other programs, e.g. ones invoked by CPI. Anchor's own codes are
+    /// decoded regardless.
+    pub fn errors<E: ErrorCodes>(mut self) -> Self {
+        self.error_codes.extend(E::error_tables());
+        self
+    }
+
     pub fn instruction(mut self, ix: Instruction) -> Self {
         self.instructions.push(ix);
         self
@@ -380,12 +753,42 @@ impl<'a> RequestBuilder<'a> {
         self
     }
 
+    /// Adds an instruction creating the associated token account of `owner`
+    /// for `mint`, paid for by the request's payer.
+    pub fn create_associated_token_account(self, owner: &Pubkey, mint: &Pubkey) -> Self {
+        let ix = token::create_associated_token_account(&self.payer.pubkey(), owner, mint);
+        self.instruction(ix)
+    }
+
     pub fn accounts(mut self, accounts: impl ToAccountMetas) -> Self {
         let mut metas = accounts.to_account_metas(None);
         self.accounts.append(&mut metas);
         self
     }
 
+    /// Same as [`accounts`](Self::accounts), but first fills in the accounts
+    /// left as `Pubkey::default()` whose address follows from the others,
+    /// see [`ResolveAccounts`]: sysvars, programs, PDAs, associated token
+    /// accounts, and accounts stored in another one of the given accounts,
+    /// e.g. a `market`'s `authority` when the market has a
+    /// `has_one = authority` constraint.
+    ///
+    /// Accounts that don't exist are left unset, while RPC errors are
+    /// returned.
+    pub fn resolve_accounts(
+        self,
+        mut accounts: impl ToAccountMetas + ResolveAccounts,
+    ) -> Result<Self, ClientError> {
+        let rpc_client = RpcClient::new_with_commitment(self.cluster.clone(), self.options);
+        accounts.resolve_accounts(&self.program_id, &mut |address| -> Result<_, ClientError> {
+            Ok(rpc_client
+                .get_account_with_commitment(address, self.options)?
+                .value
+                .map(|account| account.data))
+        })?;
+        Ok(self.accounts(accounts))
+    }
+
     pub fn options(mut self, options: CommitmentConfig) -> Self {
         self.options = options;
         self
@@ -447,33 +850,77 @@ impl<'a> RequestBuilder<'a> {
         Ok(instructions)
     }
 
-    pub fn send(self) -> Result<Signature, ClientError> {
+    fn signed_transaction(&self, rpc_client: &RpcClient) -> Result<Transaction, ClientError> {
         let instructions = self.instructions()?;
 
-        let mut signers = self.signers;
+        let mut signers = self.signers.clone();
         signers.push(&self.payer);
 
-        let rpc_client = RpcClient::new_with_commitment(self.cluster, self.options);
+        let (recent_hash, _fee_calc) = rpc_client.get_recent_blockhash()?;
+        Ok(Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.payer.pubkey()),
+            &signers,
+            recent_hash,
+        ))
+    }
 
-        let tx = {
-            let (recent_hash, _fee_calc) = rpc_client.get_recent_blockhash()?;
-            Transaction::new_signed_with_payer(
-                &instructions,
-                Some(&self.payer.pubkey()),
-                &signers,
-                recent_hash,
-            )
-        };
+    pub fn send(self) -> Result<Signature, ClientError> {
+        let rpc_client = RpcClient::new_with_commitment(self.cluster.clone(), self.options);
+        let tx = self.signed_transaction(&rpc_client)?;
 
         rpc_client
             .send_and_confirm_transaction(&tx)
-            .map_err(Into::into)
+            .map_err(|e| error::decode(e, &self.error_codes))
+    }
+
+    /// Simulates the transaction without submitting it, e.g. to invoke
+    /// `#[simulation_only]` instructions. A simulation failing with a known
+    /// error code is returned as [`ClientError::Program`], like a failed
+    /// [`send`](Self::send). Other failures are left in the result's `err`.
+    pub fn simulate(self) -> Result<RpcSimulateTransactionResult, ClientError> {
+        let rpc_client = RpcClient::new_with_commitment(self.cluster.clone(), self.options);
+        let tx = self.signed_transaction(&rpc_client)?;
+
+        let result = rpc_client
+            .simulate_transaction(&tx)
+            .map_err(|e| error::decode(e, &self.error_codes))?
+            .value;
+        match error::decode_simulation(&result, &self.error_codes) {
+            Some(err) => Err(err),
+            None => Ok(result),
+        }
+    }
+
+    /// Simulates the transaction like [`simulate`](Self::simulate), decoding
+    /// the program's return data as `T`. The result's events can be decoded
+    /// and asserted on for any event type, see [`SimulationResult`].
+    pub fn simulate_result<T: AnchorDeserialize>(self) -> Result<SimulationResult<T>, ClientError> {
+        let program_id = self.program_id;
+        let result = self.simulate()?;
+        SimulationResult::from_logs(program_id, result.err, result.logs.unwrap_or_default())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+
+    #[event]
+    struct Counted {
+        count: u64,
+    }
+
+    fn event_log(count: u64) -> String {
+        let mut data = Counted::discriminator().to_vec();
+        data.extend_from_slice(&count.to_le_bytes());
+        format!(
+            "Program log: {}",
+            anchor_lang::__private::base64::encode(&data)
+        )
+    }
     #[test]
     fn new_execution() {
         let mut logs: &[String] =
@@ -500,4 +947,43 @@ mod tests {
         assert_eq!(program, None);
         assert!(!did_pop);
     }
+
+    #[test]
+    fn parse_logs_nested() {
+        let program = "7Y8VDzehoewALqJfyxZYMgYCnMTCDhWuGfJKUvjYWATw";
+        let other = "7swsTUiQ6KUK4uFYquQKg4epFRsBnvbrTf2fZQCa2sTJ";
+        let logs = vec![
+            format!("Program {} invoke [1]", other),
+            event_log(1),
+            format!("Program {} invoke [2]", program),
+            "Program log: Instruction: Count".to_string(),
+            event_log(2),
+            format!("Program {} consumed 2000 of 200000 compute units", program),
+            format!("Program {} success", program),
+            event_log(3),
+            format!("Program {} success", other),
+            format!("Program {} invoke [1]", program),
+            event_log(4),
+            format!("Program {} success", program),
+        ];
+        let events: Vec<u64> = parse_logs::<Counted>(program, &logs)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.count)
+            .collect();
+        assert_eq!(events, vec![2, 4]);
+    }
+
+    #[test]
+    fn parse_logs_skips_program_logs_ending_like_returns() {
+        let program = "7Y8VDzehoewALqJfyxZYMgYCnMTCDhWuGfJKUvjYWATw";
+        let logs = vec![
+            format!("Program {} invoke [1]", program),
+            "Program log: Transfer success".to_string(),
+            event_log(1),
+            format!("Program {} success", program),
+        ];
+        let events = parse_logs::<Counted>(program, &logs).unwrap();
+        assert_eq!(events.len(), 1);
+    }
 }
@@ -0,0 +1,128 @@
+//! Iteration over the items of a program's paged lists, see
+//! [`anchor_lang::paged_vec`].
+//!
+//! Pages are fetched one at a time as the iterator advances, so that only a
+//! page of items is held in memory.
+
+use crate::{ClientError, Program};
+use anchor_lang::paged_vec::{page_address, Page, PagedVecHeader};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::VecDeque;
+
+/// An iterator over the items of the paged list held by `list`, in order.
+///
+/// A failed page fetch is yielded as an error, after which the iterator can
+/// be advanced again to retry it.
+pub struct PagedVec<T> {
+    rpc_client: RpcClient,
+    commitment: CommitmentConfig,
+    program_id: Pubkey,
+    list: Pubkey,
+    header: PagedVecHeader,
+    // The index of the next page to fetch, and the items fetched but not
+    // yielded yet.
+    next_page: u32,
+    items: VecDeque<T>,
+}
+
+impl<T: AnchorDeserialize> PagedVec<T> {
+    pub(crate) fn new(program: &Program, list: Pubkey, header: PagedVecHeader) -> Self {
+        Self {
+            rpc_client: program.rpc(),
+            commitment: program.cfg.options.unwrap_or_default(),
+            program_id: program.program_id,
+            list,
+            header,
+            next_page: 0,
+            items: VecDeque::new(),
+        }
+    }
+
+    /// Number of items not yielded yet.
+    pub fn remaining(&self) -> u64 {
+        let fetched =
+            (self.next_page as u64 * self.header.page_capacity as u64).min(self.header.len);
+        self.header.len - fetched + self.items.len() as u64
+    }
+
+    fn fetch_page(&mut self) -> Result<(), ClientError> {
+        let (address, _) = page_address(&self.program_id, &self.list, self.next_page);
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(&address, self.commitment)?
+            .value
+            .ok_or(ClientError::AccountNotFound)?;
+        if account.owner != self.program_id {
+            return Err(ClientError::ProgramError(
+                anchor_lang::__private::ErrorCode::AccountNotProgramOwned.into(),
+            ));
+        }
+        let page = decode_page(&self.list, self.next_page, &account.data)?;
+        self.items.extend(page.items);
+        self.next_page += 1;
+        Ok(())
+    }
+}
+
+// Decodes the data of the list's page `index`.
+fn decode_page<T: AnchorDeserialize>(
+    list: &Pubkey,
+    index: u32,
+    data: &[u8],
+) -> Result<Page<T>, ClientError> {
+    let page = Page::<T>::try_deserialize(&mut &data[..])?;
+    if &page.list != list || page.index != index {
+        return Err(ClientError::ProgramError(
+            anchor_lang::__private::ErrorCode::PagedVecWrongPage.into(),
+        ));
+    }
+    Ok(page)
+}
+
+impl<T: AnchorDeserialize> Iterator for PagedVec<T> {
+    type Item = Result<T, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.items.is_empty() {
+            let page_count = match self.header.page_count() {
+                Ok(page_count) => page_count,
+                Err(err) => return Some(Err(ClientError::ProgramError(err))),
+            };
+            if self.next_page < page_count {
+                if let Err(err) = self.fetch_page() {
+                    return Some(Err(err));
+                }
+            }
+        }
+        self.items.pop_front().map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{AnchorSerialize, Discriminator};
+
+    fn page_data(list: Pubkey, index: u32, items: Vec<u64>) -> Vec<u8> {
+        let mut data = Page::<u64>::discriminator().to_vec();
+        let page = Page { list, index, items };
+        data.extend(page.try_to_vec().unwrap());
+        data.resize(100, 0);
+        data
+    }
+
+    #[test]
+    fn decodes_pages_of_the_list() {
+        let list = Pubkey::new_unique();
+        let data = page_data(list, 1, vec![3, 4]);
+        assert_eq!(
+            decode_page::<u64>(&list, 1, &data).unwrap().items,
+            vec![3, 4]
+        );
+        assert!(decode_page::<u64>(&list, 0, &data).is_err());
+        assert!(decode_page::<u64>(&Pubkey::new_unique(), 1, &data).is_err());
+    }
+}
@@ -0,0 +1,300 @@
+//! Lazy iteration over a program's accounts of a type, for programs with too
+//! many accounts to fetch them all at once.
+//!
+//! The accounts' addresses are listed up front by a single
+//! `getProgramAccounts` returning only their discriminators. The accounts
+//! themselves are then fetched in pages with `getMultipleAccounts` as the
+//! iterator advances, so that only a page of them is held in memory.
+//!
+//! Addresses are visited in ascending order, which makes the last address
+//! yielded, see [`ProgramAccounts::cursor`], a cursor that a later iteration
+//! can resume after with [`ProgramAccounts::after`].
+//!
+//! Jobs only interested in some of the accounts' fields, e.g. indexers, can
+//! instead fetch a single [`AccountField`] of all of the accounts, see
+//! [`Program::account_fields`].
+
+use crate::{ClientError, Program};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{AccountDeserialize, AccountField, AnchorDeserialize, Discriminator};
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::VecDeque;
+
+/// Maximum number of accounts fetched by a single `getMultipleAccounts`.
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// An iterator over a program's accounts of type `T`, yielding them along
+/// with their addresses. Accounts closed since their address was listed are
+/// skipped.
+///
+/// A failed page fetch is yielded as an error, after which the iterator can
+/// be advanced again to retry it.
+pub struct ProgramAccounts<T> {
+    rpc_client: RpcClient,
+    commitment: CommitmentConfig,
+    // Listed addresses, in ascending order, and the index of the first one
+    // not fetched yet.
+    addresses: Vec<Pubkey>,
+    next: usize,
+    page_size: usize,
+    page: VecDeque<(Pubkey, Result<T, ClientError>)>,
+    cursor: Option<Pubkey>,
+}
+
+impl<T: AccountDeserialize + Discriminator> ProgramAccounts<T> {
+    pub(crate) fn new(program: &Program, filters: Vec<RpcFilterType>) -> Result<Self, ClientError> {
+        let listed = list(program, filters, 8)?;
+        Ok(Self::from_addresses(
+            program.rpc(),
+            program.cfg.options.unwrap_or_default(),
+            matching_addresses::<T>(listed),
+        ))
+    }
+}
+
+// Lists the program's accounts matching `filters`, with only the first `len`
+// bytes of their data.
+pub(crate) fn list(
+    program: &Program,
+    filters: Vec<RpcFilterType>,
+    len: usize,
+) -> Result<Vec<(Pubkey, Account)>, ClientError> {
+    let config = RpcProgramAccountsConfig {
+        filters: match filters.is_empty() {
+            true => None,
+            false => Some(filters),
+        },
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: len,
+            }),
+            commitment: program.cfg.options,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    program
+        .rpc()
+        .get_program_accounts_with_config(&program.program_id, config)
+        .map_err(Into::into)
+}
+
+impl<T> ProgramAccounts<T> {
+    fn from_addresses(
+        rpc_client: RpcClient,
+        commitment: CommitmentConfig,
+        mut addresses: Vec<Pubkey>,
+    ) -> Self {
+        addresses.sort();
+        Self {
+            rpc_client,
+            commitment,
+            addresses,
+            next: 0,
+            page_size: MAX_PAGE_SIZE,
+            page: VecDeque::new(),
+            cursor: None,
+        }
+    }
+
+    /// Sets the number of accounts fetched at once, at most
+    /// [`MAX_PAGE_SIZE`].
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Skips the accounts at addresses up to and including `cursor`, i.e.
+    /// resumes an iteration whose last yielded address was `cursor`.
+    pub fn after(mut self, cursor: Pubkey) -> Self {
+        self.page.retain(|(address, _)| *address > cursor);
+        self.next += self.addresses[self.next..].partition_point(|address| *address <= cursor);
+        self
+    }
+
+    /// The address of the last account yielded.
+    pub fn cursor(&self) -> Option<Pubkey> {
+        self.cursor
+    }
+
+    /// Number of listed accounts not yielded yet, including the ones that
+    /// turn out to be closed.
+    pub fn remaining(&self) -> usize {
+        self.page.len() + self.addresses.len() - self.next
+    }
+}
+
+impl<T: AccountDeserialize> ProgramAccounts<T> {
+    fn fetch_page(&mut self) -> Result<(), ClientError> {
+        let end = (self.next + self.page_size).min(self.addresses.len());
+        let addresses = &self.addresses[self.next..end];
+        let accounts = self
+            .rpc_client
+            .get_multiple_accounts_with_commitment(addresses, self.commitment)?
+            .value;
+        self.page = decode_page(addresses, accounts);
+        self.next = end;
+        Ok(())
+    }
+}
+
+impl<T: AccountDeserialize> Iterator for ProgramAccounts<T> {
+    type Item = Result<(Pubkey, T), ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.page.is_empty() {
+            if self.next == self.addresses.len() {
+                return None;
+            }
+            if let Err(e) = self.fetch_page() {
+                return Some(Err(e));
+            }
+        }
+        let (address, account) = self.page.pop_front()?;
+        self.cursor = Some(address);
+        Some(account.map(|account| (address, account)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining()))
+    }
+}
+
+// Addresses of the listed accounts starting with `T`'s discriminator.
+fn matching_addresses<T: Discriminator>(listed: Vec<(Pubkey, Account)>) -> Vec<Pubkey> {
+    let discriminator = T::discriminator();
+    listed
+        .into_iter()
+        .filter(|(_, account)| account.data.starts_with(&discriminator))
+        .map(|(address, _)| address)
+        .collect()
+}
+
+// Decodes `field` of the listed accounts starting with `T`'s discriminator.
+pub(crate) fn decode_fields<T: Discriminator, F: AnchorDeserialize>(
+    field: AccountField<T, F>,
+    listed: Vec<(Pubkey, Account)>,
+) -> Result<Vec<(Pubkey, F)>, ClientError> {
+    let discriminator = T::discriminator();
+    listed
+        .into_iter()
+        .filter(|(_, account)| account.data.starts_with(&discriminator))
+        .map(|(address, account)| Ok((address, field.read(&account.data)?)))
+        .collect()
+}
+
+// Decodes the fetched accounts, skipping the closed ones.
+fn decode_page<T: AccountDeserialize>(
+    addresses: &[Pubkey],
+    accounts: Vec<Option<Account>>,
+) -> VecDeque<(Pubkey, Result<T, ClientError>)> {
+    addresses
+        .iter()
+        .zip(accounts)
+        .filter_map(|(address, account)| {
+            let account = account?;
+            let decoded = T::try_deserialize(&mut &account.data[..]).map_err(Into::into);
+            Some((*address, decoded))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+    use solana_sdk::account::Account;
+
+    declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+    #[account(owner_crate = self)]
+    #[derive(Debug, PartialEq)]
+    struct Counter {
+        count: u64,
+    }
+
+    #[account(owner_crate = self)]
+    struct Other {}
+
+    #[account(owner_crate = self)]
+    struct Pool {
+        authority: Pubkey,
+        name: String,
+    }
+
+    fn account<A: AccountSerialize>(value: &A) -> Account {
+        let mut data = vec![];
+        value.try_serialize(&mut data).unwrap();
+        Account {
+            lamports: 1,
+            data,
+            owner: ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn iter(addresses: Vec<Pubkey>) -> ProgramAccounts<Counter> {
+        ProgramAccounts::from_addresses(
+            RpcClient::new("http://localhost:8899".to_string()),
+            CommitmentConfig::default(),
+            addresses,
+        )
+    }
+
+    #[test]
+    fn lists_accounts_of_the_type() {
+        let (counter, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let listed = vec![
+            (counter, account(&Counter { count: 1 })),
+            (other, account(&Other {})),
+        ];
+        assert_eq!(matching_addresses::<Counter>(listed), vec![counter]);
+    }
+
+    #[test]
+    fn decodes_fields_of_the_type() {
+        let (pool, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let authority = Pubkey::new_unique();
+        let field = account_field!(Pool, authority);
+        let mut sliced = account(&Pool {
+            authority,
+            name: "pool".to_string(),
+        });
+        sliced.data.truncate(field.end());
+        let listed = vec![(pool, sliced), (other, account(&Other {}))];
+        assert_eq!(
+            decode_fields(field, listed).unwrap(),
+            vec![(pool, authority)]
+        );
+    }
+
+    #[test]
+    fn decodes_pages_skipping_closed_accounts() {
+        let addresses = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let page: VecDeque<(Pubkey, Result<Counter, ClientError>)> =
+            decode_page(&addresses, vec![None, Some(account(&Counter { count: 2 }))]);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].0, addresses[1]);
+        assert_eq!(page[0].1.as_ref().unwrap(), &Counter { count: 2 });
+    }
+
+    #[test]
+    fn resumes_after_cursor() {
+        let mut addresses: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        addresses.reverse();
+        let accounts = iter(addresses.clone());
+        assert_eq!(accounts.remaining(), 5);
+
+        addresses.sort();
+        assert_eq!(accounts.after(addresses[1]).remaining(), 3);
+        assert_eq!(iter(addresses.clone()).after(addresses[4]).remaining(), 0);
+    }
+}
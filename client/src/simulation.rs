@@ -0,0 +1,215 @@
+//! Decoded results of simulated transactions, for testing instructions
+//! without submitting them.
+//!
+//! [`RequestBuilder::simulate_result`](crate::RequestBuilder::simulate_result)
+//! bundles the transaction's error, logs, compute units, the program's return
+//! data decoded as `T` and its events, which can be decoded as any event type
+//! and asserted on:
+//!
+//! ```ignore
+//! let result = program
+//!     .request()
+//!     .accounts(accounts)
+//!     .args(instruction::Quote { amount: 1234 })
+//!     .simulate_result::<u64>()?;
+//! assert_eq!(result.return_data, Some(1234));
+//! result.assert_emitted::<Quoted>(|e| e.amount == 1234);
+//! ```
+
+use crate::{parse_logs, ClientError};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{AnchorDeserialize, Event};
+use regex::Regex;
+use solana_sdk::transaction::TransactionError;
+
+/// The outcome of a simulated transaction, with the return data of the
+/// program decoded as `T`.
+#[derive(Debug)]
+pub struct SimulationResult<T> {
+    pub program_id: Pubkey,
+    /// The error the transaction failed with, if any.
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    /// The data the program last returned, with `set_return_data`.
+    pub return_data: Option<T>,
+    /// The compute units consumed by all of the transaction's instructions.
+    pub units_consumed: u64,
+}
+
+impl<T: AnchorDeserialize> SimulationResult<T> {
+    /// Decodes the result of simulating a transaction invoking `program_id`
+    /// from its error and logs.
+    pub fn from_logs(
+        program_id: Pubkey,
+        err: Option<TransactionError>,
+        logs: Vec<String>,
+    ) -> Result<Self, ClientError> {
+        // Program ids are matched as base58 tokens, so that program logs,
+        // e.g. `Program log: Transfer success`, aren't taken for them.
+        let invoke_re = Regex::new(r"^Program [1-9A-HJ-NP-Za-km-z]+ invoke \[\d+\]$").unwrap();
+        let return_re =
+            Regex::new(r"^Program [1-9A-HJ-NP-Za-km-z]+ (success|failed: .*)$").unwrap();
+        let consumed_re =
+            Regex::new(r"^Program [1-9A-HJ-NP-Za-km-z]+ consumed (\d+) of \d+ compute units$")
+                .unwrap();
+        let program_str = program_id.to_string();
+
+        let mut depth = 0;
+        let mut return_data = None;
+        let mut units_consumed = 0;
+        for l in &logs {
+            if invoke_re.is_match(l) {
+                depth += 1;
+            } else if return_re.is_match(l) {
+                depth -= 1;
+            } else if let Some(c) = consumed_re.captures(l) {
+                // Units of inner invocations are included in the outer ones'.
+                if depth == 1 {
+                    units_consumed += c[1]
+                        .parse::<u64>()
+                        .map_err(|_| ClientError::LogParseError(l.to_string()))?;
+                }
+            } else if let Some(data) = l.strip_prefix("Program return: ") {
+                let (program, data) = data
+                    .split_once(' ')
+                    .ok_or_else(|| ClientError::LogParseError(l.to_string()))?;
+                if program == program_str {
+                    let bytes = anchor_lang::__private::base64::decode(data)
+                        .map_err(|_| ClientError::LogParseError(l.to_string()))?;
+                    let value = T::try_from_slice(&bytes)
+                        .map_err(|e| ClientError::LogParseError(e.to_string()))?;
+                    return_data = Some(value);
+                }
+            }
+        }
+
+        Ok(Self {
+            program_id,
+            err,
+            logs,
+            return_data,
+            units_consumed,
+        })
+    }
+}
+
+impl<T> SimulationResult<T> {
+    /// The events of type `E` emitted by the program, including via CPI.
+    pub fn events<E: Event + AnchorDeserialize>(&self) -> Result<Vec<E>, ClientError> {
+        parse_logs(&self.program_id.to_string(), &self.logs)
+    }
+
+    /// Panics unless the program emitted an event of type `E` matching `f`.
+    pub fn assert_emitted<E: Event + AnchorDeserialize>(&self, f: impl Fn(&E) -> bool) {
+        let events = self.events::<E>().unwrap();
+        assert!(
+            events.iter().any(f),
+            "no matching {} event emitted, logs: {:#?}",
+            std::any::type_name::<E>(),
+            self.logs
+        );
+    }
+
+    /// Panics if the program emitted an event of type `E`.
+    pub fn assert_not_emitted<E: Event + AnchorDeserialize>(&self) {
+        let events = self.events::<E>().unwrap();
+        assert!(
+            events.is_empty(),
+            "{} event emitted, logs: {:#?}",
+            std::any::type_name::<E>(),
+            self.logs
+        );
+    }
+
+    /// Panics if the transaction failed.
+    pub fn assert_ok(&self) {
+        assert!(
+            self.err.is_none(),
+            "simulation failed with {:?}, logs: {:#?}",
+            self.err,
+            self.logs
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+
+    #[event]
+    struct Quoted {
+        amount: u64,
+    }
+
+    #[event]
+    struct Cancelled {}
+
+    fn event_log(amount: u64) -> String {
+        let mut data = Quoted::discriminator().to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        format!(
+            "Program data: {}",
+            anchor_lang::__private::base64::encode(&data)
+        )
+    }
+
+    #[test]
+    fn decodes_events_return_data_and_units() {
+        let program = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program),
+            event_log(1234),
+            format!("Program {} invoke [2]", other),
+            format!("Program return: {} AQAAAAAAAAA=", other),
+            format!("Program {} consumed 500 of 199000 compute units", other),
+            format!("Program {} success", other),
+            format!(
+                "Program return: {} {}",
+                program,
+                anchor_lang::__private::base64::encode(&1234u64.to_le_bytes())
+            ),
+            format!("Program {} consumed 2000 of 200000 compute units", program),
+            format!("Program {} success", program),
+            format!("Program {} invoke [1]", other),
+            format!("Program {} consumed 300 of 198000 compute units", other),
+            format!("Program {} success", other),
+        ];
+        let result = SimulationResult::<u64>::from_logs(program, None, logs).unwrap();
+        assert_eq!(result.return_data, Some(1234));
+        assert_eq!(result.units_consumed, 2300);
+        result.assert_ok();
+        result.assert_emitted::<Quoted>(|e| e.amount == 1234);
+        result.assert_not_emitted::<Cancelled>();
+        assert_eq!(result.events::<Quoted>().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn program_logs_are_not_taken_for_invocations() {
+        let program = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program),
+            "Program log: Transfer success".to_string(),
+            "Program log: Program x invoke [2]".to_string(),
+            format!("Program {} consumed 2000 of 200000 compute units", program),
+            format!("Program {} success", program),
+        ];
+        let result = SimulationResult::<()>::from_logs(program, None, logs).unwrap();
+        assert_eq!(result.units_consumed, 2000);
+    }
+
+    #[test]
+    #[should_panic(expected = "no matching")]
+    fn missing_events_panic() {
+        let program = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program),
+            event_log(1),
+            format!("Program {} success", program),
+        ];
+        let result = SimulationResult::<()>::from_logs(program, None, logs).unwrap();
+        result.assert_emitted::<Quoted>(|e| e.amount == 2);
+    }
+}
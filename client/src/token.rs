@@ -0,0 +1,51 @@
+//! Helpers for the SPL token and associated token account programs, used by
+//! nearly every client of a program dealing with tokens.
+
+use crate::ClientError;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::pubkey::Pubkey;
+
+pub use spl_associated_token_account::get_associated_token_address;
+pub use spl_token::state::{Account as TokenAccount, Mint};
+
+/// Returns the instruction creating the associated token account of `owner`
+/// for `mint`, paid for by `payer`.
+pub fn create_associated_token_account(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    spl_associated_token_account::create_associated_token_account(payer, owner, mint)
+}
+
+/// Decodes the data of a token account.
+pub fn unpack_token_account(data: &[u8]) -> Result<TokenAccount, ClientError> {
+    TokenAccount::unpack(data).map_err(Into::into)
+}
+
+/// Decodes the data of a mint.
+pub fn unpack_mint(data: &[u8]) -> Result<Mint, ClientError> {
+    Mint::unpack(data).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token::state::AccountState;
+
+    #[test]
+    fn unpack_token_account_balance() {
+        let account = TokenAccount {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 42,
+            state: AccountState::Initialized,
+            ..TokenAccount::default()
+        };
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(account, &mut data).unwrap();
+        assert_eq!(unpack_token_account(&data).unwrap(), account);
+        assert!(unpack_mint(&data).is_err());
+    }
+}
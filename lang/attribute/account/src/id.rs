@@ -208,7 +208,7 @@ impl Parse for RespanInput {
     }
 }
 
-fn parse_pubkey(
+pub(crate) fn parse_pubkey(
     id_literal: &LitStr,
     pubkey_type: &proc_macro2::TokenStream,
 ) -> Result<proc_macro2::TokenStream> {
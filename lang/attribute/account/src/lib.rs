@@ -1,5 +1,6 @@
 extern crate proc_macro;
 
+use heck::{ShoutySnakeCase, SnakeCase};
 use quote::quote;
 use syn::parse_macro_input;
 
@@ -20,6 +21,94 @@ mod id;
 /// check this discriminator. If it doesn't match, an invalid account was given,
 /// and the account deserialization will exit with an error.
 ///
+/// # Codecs
+///
+/// By default, the account data following the discriminator is encoded with
+/// borsh. A different encoding can be selected with the `codec` argument:
+///
+/// ```ignore
+/// #[account(codec = "bytemuck")]
+/// ```
+///
+/// - `"borsh"`: the default.
+/// - `"bytemuck"`: the account is `#[repr(C)]` and its bytes are copied
+///   as is, so all fields must be [`Pod`](../bytemuck/trait.Pod.html) and the
+///   struct must not contain any padding. Both are checked at compile time.
+///   Such accounts can't be generic.
+/// - `"custom"`: the account must implement
+///   [`AccountCodec`](./trait.AccountCodec.html).
+///
+/// The codec is recorded in the IDL so that clients decode the account
+/// correctly.
+///
+/// # Compression
+///
+/// Accounts declared with `#[account(compressed)]` are borsh serialized and
+/// then zlib compressed, for archival accounts where rent dominates the cost
+/// of the extra compute. This requires anchor-lang's `compression` feature.
+/// The compressed body is prefixed with its compressed and uncompressed
+/// lengths, so it can shrink or grow within the space allocated for the
+/// account. The account must be allocated with an explicit `space`, large
+/// enough for the compressed body in the worst case.
+///
+/// # Transient Fields
+///
+/// Fields marked `#[transient]` live only in memory, e.g. to cache values
+/// derived during a handler. They're skipped when the account is serialized,
+/// set to their `Default` when it's deserialized and left out of the IDL,
+/// so they don't take up any space on chain:
+///
+/// ```ignore
+/// #[account]
+/// pub struct Pool {
+///     pub reserves: u64,
+///     #[transient]
+///     pub price: Option<u64>,
+/// }
+/// ```
+///
+/// Transient fields are only supported by borsh encoded accounts, including
+/// compressed ones.
+///
+/// # Invariants
+///
+/// Conditions that must always hold for the account's state can be declared
+/// with `#[invariant(<expr>)]`, below the `#[account]` attribute:
+///
+/// ```ignore
+/// #[account]
+/// #[invariant(self.total >= self.locked)]
+/// pub struct Stake {
+///     pub total: u64,
+///     pub locked: u64,
+/// }
+/// ```
+///
+/// The invariants are checked whenever the account is deserialized and
+/// before it's written back on exit, failing with `AccountInvariantViolated`
+/// and logging the violated invariant. The checks only run in builds with
+/// debug assertions, unless the program is built with its
+/// `check-invariants` feature. Invariants aren't supported by zero copy
+/// accounts.
+///
+/// # Owner
+///
+/// The generated [`Owner`](./trait.Owner.html) implementation returns the
+/// `id()` of the crate the account is declared in. Accounts declared in a
+/// shared library crate can instead name the program that owns them, so
+/// that every program and client depending on the library uses the same
+/// definition:
+///
+/// ```ignore
+/// #[account(owner_crate = my_program)]
+/// pub struct Pool { .. }
+/// ```
+///
+/// When the program is built with its `dynamic-program-id` feature, accounts
+/// without an `owner_crate` are instead expected to be owned by the program
+/// id the program is executing as, so that the same binary can be deployed
+/// under several program ids.
+///
 /// # Zero Copy Deserialization
 ///
 /// **WARNING**: Zero copy deserialization is an experimental feature. It's
@@ -61,13 +150,15 @@ pub fn account(
 ) -> proc_macro::TokenStream {
     let mut namespace = "".to_string();
     let mut is_zero_copy = false;
+    let mut codec = None;
+    let mut owner_crate: Option<proc_macro2::TokenStream> = None;
     let args_str = args.to_string();
     let args: Vec<&str> = args_str.split(',').collect();
     if args.len() > 2 {
         panic!("Only two args are allowed to the account attribute.")
     }
     for arg in args {
-        let ns = arg
+        let ns: String = arg
             .to_string()
             .replace("\"", "")
             .chars()
@@ -75,41 +166,82 @@ pub fn account(
             .collect();
         if ns == "zero_copy" {
             is_zero_copy = true;
+        } else if ns == "compressed" {
+            if codec.is_some() {
+                panic!("Only one of codec or compressed can be given");
+            }
+            codec = Some(Codec::Compressed);
+        } else if let Some(c) = ns.strip_prefix("codec=") {
+            if codec.is_some() {
+                panic!("Only one of codec or compressed can be given");
+            }
+            codec = Some(match c {
+                "borsh" => Codec::Borsh,
+                "bytemuck" => Codec::Bytemuck,
+                "custom" => Codec::Custom,
+                _ => panic!("Invalid codec: expected \"borsh\", \"bytemuck\" or \"custom\""),
+            });
+        } else if let Some(owner) = ns.strip_prefix("owner_crate=") {
+            owner_crate = Some(owner.parse().expect("Invalid owner_crate path"));
         } else {
             namespace = ns;
         }
     }
+    if is_zero_copy && codec.is_some() {
+        panic!("zero_copy accounts can't specify a codec or be compressed");
+    }
+    if owner_crate.is_some() && !namespace.is_empty() {
+        panic!("Namespaced accounts can't specify an owner_crate");
+    }
+    let owner_crate_given = owner_crate.is_some();
+    let owner = owner_crate.unwrap_or_else(|| quote! { crate });
+    let codec = codec.unwrap_or(Codec::Borsh);
 
-    let account_strct = parse_macro_input!(input as syn::ItemStruct);
+    let mut account_strct = parse_macro_input!(input as syn::ItemStruct);
+    if strip_transient(&mut account_strct) && (is_zero_copy || !codec.is_borsh()) {
+        panic!("#[transient] fields are only supported by borsh encoded accounts");
+    }
+    strip_idl_attrs(&mut account_strct.fields);
+    let invariants = strip_invariants(&mut account_strct);
+    if is_zero_copy && !invariants.is_empty() {
+        panic!("#[invariant] is not supported by zero_copy accounts");
+    }
+    if matches!(codec, Codec::Bytemuck) && !account_strct.generics.params.is_empty() {
+        panic!("bytemuck encoded accounts can't be generic");
+    }
     let account_name = &account_strct.ident;
     let (impl_gen, type_gen, where_clause) = account_strct.generics.split_for_impl();
 
     let discriminator: proc_macro2::TokenStream = {
         // Namespace the discriminator to prevent collisions.
-        let discriminator_preimage = {
-            // For now, zero copy accounts can't be namespaced.
-            if namespace.is_empty() {
-                format!("account:{}", account_name)
-            } else {
-                format!("{}:{}", namespace, account_name)
-            }
-        };
-
-        let mut discriminator = [0u8; 8];
-        discriminator.copy_from_slice(
-            &anchor_syn::hash::hash(discriminator_preimage.as_bytes()).to_bytes()[..8],
+        let discriminator = anchor_syn::codegen::program::common::account_discriminator(
+            &namespace,
+            &account_name.to_string(),
         );
         format!("{:?}", discriminator).parse().unwrap()
     };
 
     let owner_impl = {
         if namespace.is_empty() {
+            // Accounts of the crate's own program are owned by whichever id
+            // the program executes as when built with `dynamic-program-id`.
+            let owner_for = match owner_crate_given {
+                true => quote! {},
+                false => quote! {
+                    #[cfg(feature = "dynamic-program-id")]
+                    fn owner_for(program_id: &anchor_lang::solana_program::pubkey::Pubkey) -> anchor_lang::solana_program::pubkey::Pubkey {
+                        *program_id
+                    }
+                },
+            };
             quote! {
                 #[automatically_derived]
                 impl #impl_gen anchor_lang::Owner for #account_name #type_gen #where_clause {
-                    fn owner() -> Pubkey {
-                        crate::ID
+                    fn owner() -> anchor_lang::solana_program::pubkey::Pubkey {
+                        #owner::id()
                     }
+
+                    #owner_for
                 }
             }
         } else {
@@ -119,8 +251,25 @@ pub fn account(
 
     proc_macro::TokenStream::from({
         if is_zero_copy {
+            let layouts = field_layouts(&account_strct, false);
+            // Byte offsets of each field within the packed struct, used by
+            // constraints to read fields without loading the account.
+            let field_offsets: Vec<proc_macro2::TokenStream> = layouts
+                .iter()
+                .map(|layout| {
+                    let offset_name = quote::format_ident!("__anchor_offset_{}", layout.ident);
+                    let offset = &layout.offset;
+                    quote! {
+                        #[doc(hidden)]
+                        #[allow(non_upper_case_globals)]
+                        pub const #offset_name: usize = #offset - 8;
+                    }
+                })
+                .collect();
+            let field_accessors = field_accessors(&layouts);
+            let offsets_module = offsets_module(&account_strct, &layouts);
             quote! {
-                #[zero_copy]
+                #[anchor_lang::zero_copy]
                 #account_strct
 
                 #[automatically_derived]
@@ -131,6 +280,14 @@ pub fn account(
                 #[automatically_derived]
                 impl #impl_gen anchor_lang::ZeroCopy for #account_name #type_gen #where_clause {}
 
+                #[automatically_derived]
+                impl #impl_gen #account_name #type_gen #where_clause {
+                    #(#field_offsets)*
+                    #(#field_accessors)*
+                }
+
+                #offsets_module
+
                 #[automatically_derived]
                 impl #impl_gen anchor_lang::Discriminator for #account_name #type_gen #where_clause {
                     fn discriminator() -> [u8; 8] {
@@ -142,7 +299,7 @@ pub fn account(
                 // It's expected on-chain programs deserialize via zero-copy.
                 #[automatically_derived]
                 impl #impl_gen anchor_lang::AccountDeserialize for #account_name #type_gen #where_clause {
-                    fn try_deserialize(buf: &mut &[u8]) -> std::result::Result<Self, ProgramError> {
+                    fn try_deserialize(buf: &mut &[u8]) -> std::result::Result<Self, anchor_lang::solana_program::program_error::ProgramError> {
                         if buf.len() < #discriminator.len() {
                             return Err(anchor_lang::__private::ErrorCode::AccountDiscriminatorNotFound.into());
                         }
@@ -153,7 +310,7 @@ pub fn account(
                         Self::try_deserialize_unchecked(buf)
                     }
 
-                    fn try_deserialize_unchecked(buf: &mut &[u8]) -> std::result::Result<Self, ProgramError> {
+                    fn try_deserialize_unchecked(buf: &mut &[u8]) -> std::result::Result<Self, anchor_lang::solana_program::program_error::ProgramError> {
                         let data: &[u8] = &buf[8..];
                         // Re-interpret raw bytes into the POD data structure.
                         let account = anchor_lang::__private::bytemuck::from_bytes(data);
@@ -165,26 +322,168 @@ pub fn account(
                 #owner_impl
             }
         } else {
+            let (derives, serialize, deserialize) = match codec {
+                Codec::Borsh => (
+                    quote! {
+                        #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize, Clone)]
+                    },
+                    quote! {
+                        anchor_lang::AnchorSerialize::serialize(
+                            self,
+                            writer
+                        )
+                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotSerialize)?;
+                    },
+                    quote! {
+                        let mut data: &[u8] = &buf[8..];
+                        anchor_lang::AnchorDeserialize::deserialize(&mut data)
+                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotDeserialize.into())
+                    },
+                ),
+                Codec::Bytemuck => (
+                    quote! {
+                        #[derive(Copy, Clone)]
+                        #[repr(C)]
+                    },
+                    quote! {
+                        writer.write_all(anchor_lang::__private::bytemuck::bytes_of(self))
+                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotSerialize)?;
+                    },
+                    quote! {
+                        let data: &[u8] = &buf[8..];
+                        let size = std::mem::size_of::<Self>();
+                        if data.len() < size {
+                            return Err(anchor_lang::__private::ErrorCode::AccountDidNotDeserialize.into());
+                        }
+                        // Copy instead of casting since the data isn't
+                        // guaranteed to be aligned.
+                        let mut account = <Self as anchor_lang::__private::bytemuck::Zeroable>::zeroed();
+                        anchor_lang::__private::bytemuck::bytes_of_mut(&mut account)
+                            .copy_from_slice(&data[..size]);
+                        Ok(account)
+                    },
+                ),
+                Codec::Compressed => (
+                    quote! {
+                        #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize, Clone)]
+                    },
+                    quote! {
+                        let data = anchor_lang::AnchorSerialize::try_to_vec(self)
+                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotSerialize)?;
+                        anchor_lang::compression::encode(&data, writer)?;
+                    },
+                    quote! {
+                        let data = anchor_lang::compression::decode(&buf[8..])?;
+                        anchor_lang::AnchorDeserialize::deserialize(&mut &data[..])
+                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotDeserialize.into())
+                    },
+                ),
+                Codec::Custom => (
+                    quote! {
+                        #[derive(Clone)]
+                    },
+                    quote! {
+                        anchor_lang::AccountCodec::encode(self, writer)?;
+                    },
+                    quote! {
+                        let mut data: &[u8] = &buf[8..];
+                        anchor_lang::AccountCodec::decode(&mut data)
+                    },
+                ),
+            };
+            // Invariants are checked whenever the account is serialized or
+            // deserialized, in debug builds or with the program's
+            // `check-invariants` feature.
+            let check_invariants = |account: proc_macro2::TokenStream| match invariants.is_empty() {
+                true => quote! {},
+                false => quote! {
+                    if cfg!(any(debug_assertions, feature = "check-invariants")) {
+                        #account.__anchor_check_invariants()?;
+                    }
+                },
+            };
+            let check_invariants_on_serialize = check_invariants(quote! { self });
+            let check_invariants_on_deserialize = check_invariants(quote! { account });
+            let invariants_impl = match invariants.is_empty() {
+                true => quote! {},
+                false => {
+                    let checks = invariants.iter().map(|invariant| {
+                        let description = format!("{}: {}", account_name, quote! { #invariant });
+                        quote! {
+                            if !(#invariant) {
+                                anchor_lang::solana_program::msg!("Invariant violated: {}", #description);
+                                return Err(anchor_lang::__private::ErrorCode::AccountInvariantViolated.into());
+                            }
+                        }
+                    });
+                    quote! {
+                        #[automatically_derived]
+                        impl #impl_gen #account_name #type_gen #where_clause {
+                            #[doc(hidden)]
+                            pub fn __anchor_check_invariants(&self) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+                                #(#checks)*
+                                Ok(())
+                            }
+                        }
+                    }
+                }
+            };
+            let field_accessors = match codec {
+                Codec::Borsh => {
+                    let layouts = field_layouts(&account_strct, true);
+                    let accessors = field_accessors(&layouts);
+                    let offsets_module = offsets_module(&account_strct, &layouts);
+                    quote! {
+                        #[automatically_derived]
+                        impl #impl_gen #account_name #type_gen #where_clause {
+                            #(#accessors)*
+                        }
+
+                        #offsets_module
+                    }
+                }
+                _ => quote! {},
+            };
+            let pod_impls = match codec {
+                Codec::Bytemuck => {
+                    let field_tys: Vec<&syn::Type> =
+                        account_strct.fields.iter().map(|f| &f.ty).collect();
+                    quote! {
+                        // Every field must be `Pod` and the struct mustn't
+                        // contain any padding for the struct to be `Pod`.
+                        const _: fn() = || {
+                            fn assert_pod<T: anchor_lang::__private::bytemuck::Pod>() {}
+                            #(assert_pod::<#field_tys>();)*
+                        };
+                        const _: [(); 0] = [(); std::mem::size_of::<#account_name>() - (0 #(+ std::mem::size_of::<#field_tys>())*)];
+
+                        #[automatically_derived]
+                        unsafe impl anchor_lang::__private::bytemuck::Pod for #account_name {}
+                        #[automatically_derived]
+                        unsafe impl anchor_lang::__private::bytemuck::Zeroable for #account_name {}
+                    }
+                }
+                _ => quote! {},
+            };
             quote! {
-                #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+                #derives
                 #account_strct
 
+                #pod_impls
+
                 #[automatically_derived]
                 impl #impl_gen anchor_lang::AccountSerialize for #account_name #type_gen #where_clause {
-                    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> std::result::Result<(), ProgramError> {
+                    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> std::result::Result<(), anchor_lang::solana_program::program_error::ProgramError> {
+                        #check_invariants_on_serialize
                         writer.write_all(&#discriminator).map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotSerialize)?;
-                        AnchorSerialize::serialize(
-                            self,
-                            writer
-                        )
-                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotSerialize)?;
+                        #serialize
                         Ok(())
                     }
                 }
 
                 #[automatically_derived]
                 impl #impl_gen anchor_lang::AccountDeserialize for #account_name #type_gen #where_clause {
-                    fn try_deserialize(buf: &mut &[u8]) -> std::result::Result<Self, ProgramError> {
+                    fn try_deserialize(buf: &mut &[u8]) -> std::result::Result<Self, anchor_lang::solana_program::program_error::ProgramError> {
                         if buf.len() < #discriminator.len() {
                             return Err(anchor_lang::__private::ErrorCode::AccountDiscriminatorNotFound.into());
                         }
@@ -192,13 +491,13 @@ pub fn account(
                         if &#discriminator != given_disc {
                             return Err(anchor_lang::__private::ErrorCode::AccountDiscriminatorMismatch.into());
                         }
-                        Self::try_deserialize_unchecked(buf)
+                        let account = Self::try_deserialize_unchecked(buf)?;
+                        #check_invariants_on_deserialize
+                        Ok(account)
                     }
 
-                    fn try_deserialize_unchecked(buf: &mut &[u8]) -> std::result::Result<Self, ProgramError> {
-                        let mut data: &[u8] = &buf[8..];
-                        AnchorDeserialize::deserialize(&mut data)
-                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotDeserialize.into())
+                    fn try_deserialize_unchecked(buf: &mut &[u8]) -> std::result::Result<Self, anchor_lang::solana_program::program_error::ProgramError> {
+                        #deserialize
                     }
                 }
 
@@ -209,12 +508,248 @@ pub fn account(
                     }
                 }
 
+                #invariants_impl
+
+                #field_accessors
+
                 #owner_impl
             }
         }
     })
 }
 
+enum Codec {
+    Borsh,
+    Bytemuck,
+    Custom,
+    Compressed,
+}
+
+impl Codec {
+    fn is_borsh(&self) -> bool {
+        matches!(self, Codec::Borsh | Codec::Compressed)
+    }
+}
+
+// The offset and length of a field at a fixed offset in the account's data,
+// including the discriminator.
+struct FieldLayout<'a> {
+    ident: &'a syn::Ident,
+    ty: &'a syn::Type,
+    offset: proc_macro2::TokenStream,
+    len: proc_macro2::TokenStream,
+}
+
+// The layout of each field at a fixed offset in the account's data. With
+// `borsh`, these are the fields before the first one whose encoded size isn't
+// known from its type, skipping transient fields.
+//
+// The offsets refer to the account's offsets module, see `offsets_module`,
+// unless the account is generic.
+fn field_layouts(account_strct: &syn::ItemStruct, borsh: bool) -> Vec<FieldLayout> {
+    let fields = match &account_strct.fields {
+        syn::Fields::Named(fields) => &fields.named,
+        _ => return vec![],
+    };
+    let module = offsets_module_ident(account_strct);
+    let mut prev_tys: Vec<&syn::Type> = Vec::new();
+    let mut layouts = Vec::new();
+    for f in fields {
+        if borsh {
+            if f.attrs.iter().any(|attr| attr.path.is_ident("borsh_skip")) {
+                continue;
+            }
+            if !is_fixed_size(&f.ty) {
+                break;
+            }
+        }
+        let ident = f.ident.as_ref().unwrap();
+        let ty = &f.ty;
+        let (offset, len) = match &module {
+            Some(module) => {
+                let (offset, len) = offset_consts(ident);
+                (quote! { #module::#offset }, quote! { #module::#len })
+            }
+            None => (
+                quote! { 8 #(+ std::mem::size_of::<#prev_tys>())* },
+                quote! { std::mem::size_of::<#ty>() },
+            ),
+        };
+        layouts.push(FieldLayout {
+            ident,
+            ty,
+            offset,
+            len,
+        });
+        prev_tys.push(ty);
+    }
+    layouts
+}
+
+// The module holding the field offsets of a non generic account, e.g.
+// `pool_offsets` for `Pool`.
+fn offsets_module_ident(account_strct: &syn::ItemStruct) -> Option<syn::Ident> {
+    let generic = account_strct
+        .generics
+        .params
+        .iter()
+        .any(|param| !matches!(param, syn::GenericParam::Lifetime(_)));
+    match generic {
+        true => None,
+        false => Some(quote::format_ident!(
+            "{}_offsets",
+            account_strct.ident.to_string().to_snake_case()
+        )),
+    }
+}
+
+// The names of the offset and length constants of a field.
+fn offset_consts(field: &syn::Ident) -> (syn::Ident, syn::Ident) {
+    let name = field.to_string().to_shouty_snake_case();
+    (
+        quote::format_ident!("{}", name),
+        quote::format_ident!("{}_LEN", name),
+    )
+}
+
+// A module with the byte offset and length of each field at a fixed offset,
+// the single source of the account's layout for the accessors below, the
+// constraints reading fields without loading the account, and clients.
+fn offsets_module(
+    account_strct: &syn::ItemStruct,
+    layouts: &[FieldLayout],
+) -> proc_macro2::TokenStream {
+    let module = match offsets_module_ident(account_strct) {
+        Some(module) => module,
+        None => return quote! {},
+    };
+    let vis = &account_strct.vis;
+    let doc = format!(
+        " Byte offsets and lengths of the fields of [`{}`] in the account's data,\n \
+         including its 8 byte discriminator, e.g. for `memcmp` filters and data\n \
+         slices. Only the fields at a fixed offset are listed.",
+        account_strct.ident
+    );
+    let mut prev: Option<(syn::Ident, syn::Ident)> = None;
+    let consts: Vec<proc_macro2::TokenStream> = layouts
+        .iter()
+        .map(|layout| {
+            let (offset, len) = offset_consts(layout.ident);
+            let ty = layout.ty;
+            let start = match &prev {
+                None => quote! { 8 },
+                Some((prev_offset, prev_len)) => quote! { #prev_offset + #prev_len },
+            };
+            let consts = quote! {
+                pub const #offset: usize = #start;
+                pub const #len: usize = std::mem::size_of::<#ty>();
+            };
+            prev = Some((offset, len));
+            consts
+        })
+        .collect();
+    quote! {
+        #[doc = #doc]
+        #[allow(unused_imports)]
+        #vis mod #module {
+            use super::*;
+
+            #(#consts)*
+        }
+    }
+}
+
+// Hidden functions returning the `AccountField` of each field at a fixed
+// offset in the account's data, see `account_field!`.
+fn field_accessors(layouts: &[FieldLayout]) -> Vec<proc_macro2::TokenStream> {
+    layouts
+        .iter()
+        .map(|layout| {
+            let FieldLayout {
+                ident,
+                ty,
+                offset,
+                len,
+            } = layout;
+            let name = quote::format_ident!("__anchor_field_{}", ident);
+            quote! {
+                #[doc(hidden)]
+                pub fn #name() -> anchor_lang::AccountField<Self, #ty> {
+                    anchor_lang::AccountField::new(#offset, #len)
+                }
+            }
+        })
+        .collect()
+}
+
+// Whether the borsh encoding of `ty` is as large as its in memory
+// representation, i.e. it's an integer, bool, pubkey, or an array of them.
+fn is_fixed_size(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Array(array) => is_fixed_size(&array.elem),
+        syn::Type::Path(path) => {
+            let segment = match path.path.segments.last() {
+                Some(segment) => segment,
+                None => return false,
+            };
+            segment.arguments.is_empty()
+                && matches!(
+                    segment.ident.to_string().as_str(),
+                    "u8" | "i8"
+                        | "u16"
+                        | "i16"
+                        | "u32"
+                        | "i32"
+                        | "u64"
+                        | "i64"
+                        | "u128"
+                        | "i128"
+                        | "bool"
+                        | "Pubkey"
+                )
+        }
+        _ => false,
+    }
+}
+
+// Replaces `#[transient]` field attributes with `#[borsh_skip]`, so that the
+// fields are left out of the serialized account and set to their `Default`
+// on deserialization. Returns whether there were any.
+fn strip_transient(account_strct: &mut syn::ItemStruct) -> bool {
+    let mut found = false;
+    for field in account_strct.fields.iter_mut() {
+        for attr in field.attrs.iter_mut() {
+            if attr.path.is_ident("transient") {
+                *attr = syn::parse_quote! { #[borsh_skip] };
+                found = true;
+            }
+        }
+    }
+    found
+}
+
+// Removes the fields' `#[idl(..)]` attributes, which are only read by the
+// IDL parser.
+fn strip_idl_attrs(fields: &mut syn::Fields) {
+    for field in fields.iter_mut() {
+        field.attrs.retain(|attr| !attr.path.is_ident("idl"));
+    }
+}
+
+// Removes the struct's `#[invariant(<expr>)]` attributes, returning their
+// expressions.
+fn strip_invariants(account_strct: &mut syn::ItemStruct) -> Vec<syn::Expr> {
+    let (invariants, attrs): (Vec<syn::Attribute>, Vec<syn::Attribute>) = account_strct
+        .attrs
+        .drain(..)
+        .partition(|attr| attr.path.is_ident("invariant"));
+    account_strct.attrs = attrs;
+    invariants
+        .into_iter()
+        .map(|attr| attr.parse_args().expect("Invalid invariant expression"))
+        .collect()
+}
+
 #[proc_macro_derive(ZeroCopyAccessor, attributes(accessor))]
 pub fn derive_zero_copy_accessor(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let account_strct = parse_macro_input!(item as syn::ItemStruct);
@@ -285,7 +820,8 @@ pub fn zero_copy(
     _args: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let account_strct = parse_macro_input!(item as syn::ItemStruct);
+    let mut account_strct = parse_macro_input!(item as syn::ItemStruct);
+    strip_idl_attrs(&mut account_strct.fields);
 
     proc_macro::TokenStream::from(quote! {
         #[derive(anchor_lang::__private::ZeroCopyAccessor, Copy, Clone)]
@@ -294,10 +830,282 @@ pub fn zero_copy(
     })
 }
 
+/// Turns a fieldless enum into a typed bitflag type, backed by a `u32` or, with
+/// `#[flags(u64)]`, a `u64`.
+///
+/// Each variant becomes an associated constant with a shouty snake case name.
+/// Variants without a discriminant take the next bit. Explicit discriminants
+/// give the flag's mask and must be an integer literal or a shift of one.
+///
+/// ```ignore
+/// #[flags]
+/// pub enum Permissions {
+///     Mint,
+///     Burn,
+///     Freeze = 1 << 4,
+/// }
+///
+/// let perms = Permissions::MINT | Permissions::FREEZE;
+/// assert!(perms.contains(Permissions::MINT));
+/// ```
+///
+/// The generated type is `#[repr(transparent)]` and implements
+/// `AnchorSerialize`, `AnchorDeserialize` and bytemuck's `Pod`, so it can be
+/// used in both borsh and zero copy accounts. Since any bit pattern is a
+/// valid value of a `Pod` type, both keep bits without a defined flag, which
+/// can be checked with `has_undefined_bits`. `from_bits` is the constructor
+/// refusing them. The flag names and values are emitted to the IDL.
+#[proc_macro_attribute]
+pub fn flags(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = match args.is_empty() {
+        true => None,
+        false => Some(parse_macro_input!(args as anchor_syn::FlagsArgs)),
+    };
+    let flags_enum = parse_macro_input!(input as syn::ItemEnum);
+    match anchor_syn::parser::flags::parse(&flags_enum, args) {
+        Ok(flags) => anchor_syn::codegen::flags::generate(flags).into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Gives a type, and with `#[idl(name = "<name>")]` on its fields, its
+/// fields, a name in the IDL other than their Rust name, e.g. to keep
+/// clients and indexers keyed on the old names working after a rename.
+///
+/// ```ignore
+/// #[account]
+/// #[idl(name = "Pool")]
+/// pub struct LiquidityPool {
+///     #[idl(name = "feeBps")]
+///     pub fee_rate_bps: u16,
+/// }
+/// ```
+///
+/// The Rust names are recorded as the IDL entries' `aliases`, which the
+/// TypeScript client accepts as well. A bare `#[idl]` only allows renaming
+/// the fields. Fields of `#[account]` and `#[zero_copy]` structs can be
+/// renamed without it.
+#[proc_macro_attribute]
+pub fn idl(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = proc_macro2::TokenStream::from(args);
+    let attr: syn::Attribute = match args.is_empty() {
+        true => syn::parse_quote! { #[idl] },
+        false => syn::parse_quote! { #[idl(#args)] },
+    };
+    if let Err(err) = anchor_syn::parser::parse_idl_name(&attr) {
+        return err.to_compile_error().into();
+    }
+    let mut item = parse_macro_input!(input as syn::Item);
+    match &mut item {
+        syn::Item::Struct(strct) => strip_idl_attrs(&mut strct.fields),
+        syn::Item::Enum(enm) => enm
+            .variants
+            .iter_mut()
+            .for_each(|variant| strip_idl_attrs(&mut variant.fields)),
+        _ => {
+            return syn::Error::new_spanned(item, "#[idl] is only supported on structs and enums")
+                .to_compile_error()
+                .into()
+        }
+    }
+    proc_macro::TokenStream::from(quote! { #item })
+}
+
+/// Returns the [`AccountField`](./struct.AccountField.html) of a field of an
+/// `#[account]` type, for reading or fetching only that field of accounts of
+/// the type.
+///
+/// ```ignore
+/// let authority = account_field!(Pool, authority).read(&info.try_borrow_data()?)?;
+/// ```
+///
+/// The field must be at a fixed offset, see
+/// [`account_field`](./account_field/index.html).
+#[proc_macro]
+pub fn account_field(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(input as AccountFieldArgs);
+    let account = &args.account;
+    let accessor = quote::format_ident!("__anchor_field_{}", args.field);
+    proc_macro::TokenStream::from(quote! {
+        <#account>::#accessor()
+    })
+}
+
+// Arguments of `account_field!`, the account type and the field's name.
+struct AccountFieldArgs {
+    account: syn::Type,
+    field: syn::Ident,
+}
+
+impl syn::parse::Parse for AccountFieldArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let account = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let field = input.parse()?;
+        if input.peek(syn::Token![,]) {
+            input.parse::<syn::Token![,]>()?;
+        }
+        Ok(Self { account, field })
+    }
+}
+
+/// Declares a dependency on another program, optionally pinned to the SHA256 of
+/// its on-chain IDL (the `data` field of its `IdlAccount`, hex encoded).
+///
+/// ```ignore
+/// declare_dependency!(
+///     swap,
+///     "22Y43yTVxuUkoRKdm9thyRhQ3SdgQS7c7kB6UNCiaczD",
+///     idl_hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+/// );
+/// ```
+///
+/// This generates a `swap` module containing the dependency's `ID` and a
+/// [`Dependency`](./struct.Dependency.html) constant, `swap::DEPENDENCY`,
+/// whose `check_program` and `check_idl` methods verify the dependency at
+/// runtime. Declared dependencies are also emitted to the IDL.
+#[proc_macro]
+pub fn declare_dependency(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let dependency = parse_macro_input!(input as anchor_syn::Dependency);
+    let pubkey_type = quote! { anchor_lang::solana_program::pubkey::Pubkey };
+    let program_id = match id::parse_pubkey(&dependency.program_id, &pubkey_type) {
+        Ok(program_id) => program_id,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let name = &dependency.name;
+    let name_str = name.to_string();
+    let idl_hash = match &dependency.idl_hash {
+        None => quote! { None },
+        Some(idl_hash) => {
+            let value = idl_hash.value();
+            let bytes = (0..32).map(|i| u8::from_str_radix(&value[2 * i..2 * i + 2], 16).unwrap());
+            quote! { Some([#(#bytes,)*]) }
+        }
+    };
+    let doc = format!(" Dependency on the `{}` program.", name_str);
+    proc_macro::TokenStream::from(quote! {
+        #[doc = #doc]
+        pub mod #name {
+            /// The dependency's program ID.
+            pub static ID: #pubkey_type = #program_id;
+
+            /// Returns the dependency's program ID.
+            pub fn id() -> #pubkey_type {
+                ID
+            }
+
+            pub const DEPENDENCY: anchor_lang::Dependency = anchor_lang::Dependency {
+                name: #name_str,
+                program_id: #program_id,
+                idl_hash: #idl_hash,
+            };
+        }
+    })
+}
+
 /// Defines the program's ID. This should be used at the root of all Anchor
 /// based programs.
+///
+/// With anchor-lang's `check-program-id` feature, compilation fails if the
+/// declared ID doesn't match the program's keypair in the workspace's
+/// `target/deploy` directory, i.e. the key `anchor deploy` would deploy the
+/// program with. Run `anchor keys sync` to update the declared ID.
+///
+/// The declared ID must also match the program's address in the
+/// `[programs.<cluster>]` section of the workspace's `Anchor.toml`, if it's
+/// listed there, for the cluster the program is built for. It's the
+/// `ANCHOR_CLUSTER` environment variable, set by `anchor build` from
+/// `--provider.cluster`, or the `[provider]` cluster of `Anchor.toml`.
 #[proc_macro]
 pub fn declare_id(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    #[cfg(feature = "check-program-id")]
+    let checked_files = match check_program_id(&input) {
+        Ok(files) => files,
+        Err(err) => return err.to_compile_error().into(),
+    };
     let id = parse_macro_input!(input as id::Id);
+    #[cfg(feature = "check-program-id")]
+    let id = {
+        // Rebuilds the crate, and so rechecks the ID, when the cluster or
+        // the files it was checked against change. The constants are unused,
+        // so the files don't end up in the program.
+        let cluster_env = anchor_syn::keys::CLUSTER_ENV;
+        let checked_files = checked_files.iter().map(|path| path.display().to_string());
+        quote! {
+            #id
+            const _: Option<&str> = option_env!(#cluster_env);
+            #(const _: &[u8] = include_bytes!(#checked_files);)*
+        }
+    };
     proc_macro::TokenStream::from(quote! {#id})
 }
+
+// Compares a literal program ID with the keypair of the crate being compiled,
+// and with its address in `Anchor.toml`, returning the files it was compared
+// with. Crates outside of an Anchor workspace, or without a keypair or address
+// yet, aren't checked.
+#[cfg(feature = "check-program-id")]
+fn check_program_id(input: &proc_macro::TokenStream) -> syn::Result<Vec<std::path::PathBuf>> {
+    let mut checked_files = vec![];
+    let id = match syn::parse::<syn::LitStr>(input.clone()) {
+        Ok(id) => id,
+        Err(_) => return Ok(checked_files),
+    };
+    let (manifest_dir, lib_name) = match (
+        std::env::var("CARGO_MANIFEST_DIR"),
+        std::env::var("CARGO_CRATE_NAME"),
+    ) {
+        (Ok(manifest_dir), Ok(lib_name)) => (manifest_dir, lib_name),
+        _ => return Ok(checked_files),
+    };
+    let workspace_root = match std::path::Path::new(&manifest_dir)
+        .ancestors()
+        .find(|dir| dir.join("Anchor.toml").exists())
+    {
+        Some(root) => root,
+        None => return Ok(checked_files),
+    };
+    let keypair = anchor_syn::keys::keypair_path(workspace_root, &lib_name);
+    if keypair.exists() {
+        checked_files.push(keypair.clone());
+        let expected = anchor_syn::keys::read_keypair_pubkey(&keypair)
+            .map_err(|e| syn::Error::new(id.span(), e.to_string()))?;
+        if id.value() != expected {
+            return Err(syn::Error::new(
+                id.span(),
+                format!(
+                    "declared program id {} doesn't match the keypair {} ({}), run `anchor keys sync`",
+                    id.value(),
+                    keypair.display(),
+                    expected,
+                ),
+            ));
+        }
+    }
+    checked_files.push(workspace_root.join("Anchor.toml"));
+    let cluster = std::env::var(anchor_syn::keys::CLUSTER_ENV).ok();
+    let configured =
+        anchor_syn::keys::configured_program_id(workspace_root, cluster.as_deref(), &lib_name)
+            .map_err(|e| syn::Error::new(id.span(), e.to_string()))?;
+    if let Some((cluster, expected)) = configured {
+        if id.value() != expected {
+            return Err(syn::Error::new(
+                id.span(),
+                format!(
+                    "declared program id {} doesn't match the address {} of {} in the [programs.{}] section of Anchor.toml",
+                    id.value(),
+                    expected,
+                    lib_name,
+                    cluster,
+                ),
+            ));
+        }
+    }
+    Ok(checked_files)
+}
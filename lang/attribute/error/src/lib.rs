@@ -5,12 +5,12 @@ use anchor_syn::parser::error as error_parser;
 use anchor_syn::ErrorArgs;
 use syn::parse_macro_input;
 
-/// Generates `Error` and `type Result<T> = Result<T, Error>` types to be
-/// used as return types from Anchor instruction handlers. Importantly, the
-/// attribute implements
+/// Generates `Error` and `type Result<T> = Result<T, anchor_lang::error::Error>`
+/// types to be used as return types from Anchor instruction handlers.
+/// Importantly, the attribute implements
 /// [`From`](https://doc.rust-lang.org/std/convert/trait.From.html) on the
 /// `ErrorCode` to support converting from the user defined error enum *into*
-/// the generated `Error`.
+/// the generated `Error`, `anchor_lang::error::Error` and `ProgramError`.
 ///
 /// # Example
 ///
@@ -35,17 +35,65 @@ use syn::parse_macro_input;
 /// }
 /// ```
 ///
-/// Note that we generate a new `Error` type so that we can return either the
-/// user defined error enum *or* a
-/// [`ProgramError`](../solana_program/enum.ProgramError.html), which is used
-/// pervasively, throughout solana program crates. The generated `Error` type
-/// should almost never be used directly, as the user defined error is
-/// preferred. In the example above, `MyError::Hello.into()`.
+/// The generated `Result` returns
+/// [`anchor_lang::error::Error`](../anchor_lang/error/enum.Error.html),
+/// which any program's error enum, anchor's own error codes and
+/// [`ProgramError`](../solana_program/enum.ProgramError.html) convert into,
+/// so `?` can be used on all of them. It keeps the error's name and message,
+/// see `error_name()` and `error_code()`. The generated `Error` type, holding
+/// either the user defined error enum or a `ProgramError`, should almost
+/// never be used directly.
 ///
 /// # Msg
 ///
 /// The `#[msg(..)]` attribute is inert, and is used only as a marker so that
 /// parsers  and IDLs can map error codes to error messages.
+///
+/// # Fields
+///
+/// Variants may carry named fields, which can be referenced by name in their
+/// message. When such an error is converted into a `ProgramError`, its fields
+/// are Borsh serialized and logged as `Error data: <base64>`. The field types
+/// are emitted to the IDL so that clients can decode them.
+///
+/// ```ignore
+/// #[error]
+/// pub enum MyError {
+///     #[msg("Insufficient funds: needed {needed}, available {available}")]
+///     InsufficientFunds { needed: u64, available: u64 },
+/// }
+/// ```
+///
+/// Enums with fields are not `#[repr(u32)]`, so their variants can't be cast
+/// to error codes with `as`.
+///
+/// # Derives
+///
+/// Additional traits can be derived for both the error enum and the generated
+/// `Error` type with `#[error(derive(PartialEq, Eq))]`, e.g. to compare
+/// errors in tests. This can be combined with an offset, as in
+/// `#[error(offset = 6000, derive(PartialEq))]`.
+///
+/// # Shared errors
+///
+/// Errors common to all programs of a protocol can be defined once, with
+/// their own offset, in a crate the programs depend on:
+///
+/// ```ignore
+/// #[error(offset = 9000)]
+/// pub enum ProtocolError {
+///     #[msg("The protocol is paused")]
+///     Paused,
+/// }
+/// ```
+///
+/// Each program then extends them with
+/// `#[error(extends(protocol_errors::ProtocolError))]`, so they keep the
+/// same codes in every program. The program's `Error` type converts from
+/// them, compilation fails if their codes overlap with the program's own,
+/// and `anchor build` adds them to the program's IDL. The crates defining
+/// them must be path dependencies of the program, and the enums must be
+/// given by their full path.
 #[proc_macro_attribute]
 pub fn error(
     args: proc_macro::TokenStream,
@@ -7,32 +7,47 @@ use syn::parse_macro_input;
 /// [emit!](./macro.emit.html) so that programs can log significant events in
 /// their programs that clients can subscribe to. Currently, this macro is for
 /// structs only.
+///
+/// Additional traits can be derived for the event with
+/// `#[event(derive(Clone, PartialEq))]`.
+///
+/// Events marked `#[event(anonymous)]` are logged without their 8 byte
+/// discriminator, saving log space for programs emitting events at a high
+/// rate. Since the logs no longer say which event they are, clients must
+/// identify them from context, e.g. in programs emitting a single event. The
+/// IDL marks these events as anonymous.
 #[proc_macro_attribute]
 pub fn event(
-    _args: proc_macro::TokenStream,
+    args: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as anchor_syn::EventArgs);
     let event_strct = parse_macro_input!(input as syn::ItemStruct);
+    let derives = &args.derives;
 
     let event_name = &event_strct.ident;
 
     let discriminator: proc_macro2::TokenStream = {
-        let discriminator_preimage = format!("event:{}", event_name);
-        let mut discriminator = [0u8; 8];
-        discriminator.copy_from_slice(
-            &anchor_syn::hash::hash(discriminator_preimage.as_bytes()).to_bytes()[..8],
-        );
+        let discriminator =
+            anchor_syn::codegen::program::common::event_discriminator(&event_name.to_string());
         format!("{:?}", discriminator).parse().unwrap()
     };
+    let anonymous = args.anonymous;
+    let prefix = match anonymous {
+        true => quote! { Vec::new() },
+        false => quote! { #discriminator.to_vec() },
+    };
 
     proc_macro::TokenStream::from(quote! {
-        #[derive(anchor_lang::__private::EventIndex, AnchorSerialize, AnchorDeserialize)]
+        #[derive(anchor_lang::__private::EventIndex, anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
         #event_strct
 
         impl anchor_lang::Event for #event_name {
+            const ANONYMOUS: bool = #anonymous;
+
             fn data(&self) -> Vec<u8> {
-                let mut d = #discriminator.to_vec();
-                d.append(&mut self.try_to_vec().unwrap());
+                let mut d = #prefix;
+                anchor_lang::AnchorSerialize::serialize(self, &mut d).unwrap();
                 d
             }
         }
@@ -48,16 +63,17 @@ pub fn event(
 /// Creates an event that can be subscribed to by clients. Calling this method
 /// will internally borsh serialize the [event](./attr.event.html), base64
 /// encode the bytes, and then add a [msg!](../solana_program/macro.msg.html)
-/// log to the transaction.
+/// log to the transaction. Events are serialized on the stack unless they're
+/// large.
+///
+/// With anchor-lang's `event-log-data` feature, which enables `solana-1-9`
+/// and so requires solana-program 1.9 or later, the bytes are instead logged
+/// as data, skipping the base64 encoding.
 #[proc_macro]
 pub fn emit(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let data: proc_macro2::TokenStream = input.into();
     proc_macro::TokenStream::from(quote! {
-        {
-            let data = anchor_lang::Event::data(&#data);
-            let msg_str = &anchor_lang::__private::base64::encode(data);
-            anchor_lang::solana_program::msg!(msg_str);
-        }
+        anchor_lang::__private::emit(&#data)
     })
 }
 
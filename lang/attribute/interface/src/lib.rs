@@ -4,6 +4,7 @@ use anchor_syn::parser;
 use heck::SnakeCase;
 use quote::quote;
 use syn::parse_macro_input;
+use syn::spanned::Spanned;
 
 /// The `#[interface]` attribute allows one to define an external program
 /// dependency, without having any knowledge about the program, other than
@@ -130,11 +131,47 @@ use syn::parse_macro_input;
 /// with the SPL's [Shared Memory Program](https://github.com/solana-labs/solana-program-library/tree/master/shared-memory).
 /// In the future, Anchor will add the ability to return values across CPI
 /// without having to worry about the details of shared memory accounts.
+///
+/// # Registries
+///
+/// With `#[interface(registry)]`, the generated module also has a
+/// `registry` module, for discovering and validating the programs
+/// implementing the interface through the registrations kept by a program
+/// built with `#[program(interface_registry)]`, instead of hardcoding their
+/// program ids. For example, the counter above can accept any auth program
+/// its admin registered, passing the registration along with it:
+///
+/// ```ignore
+/// auth::registry::verify(
+///     &crate::ID,
+///     &ctx.accounts.registration,
+///     &self.admin,
+///     ctx.accounts.auth_program.key,
+/// )?;
+/// ```
+///
+/// Clients register an implementation with
+/// `auth::registry::register_instruction` and find the registered ones by
+/// fetching the registry program's
+/// [`InterfaceRegistration`](../anchor_lang/interface_registry/struct.InterfaceRegistration.html)
+/// accounts, see [`interface_registry`](../anchor_lang/interface_registry/index.html).
 #[proc_macro_attribute]
 pub fn interface(
-    _args: proc_macro::TokenStream,
+    args: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let registry = match args.to_string().as_str() {
+        "" => false,
+        "registry" => true,
+        _ => {
+            return syn::Error::new(
+                proc_macro2::TokenStream::from(args).span(),
+                "expected `registry`",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
     let item_trait = parse_macro_input!(input as syn::ItemTrait);
 
     let trait_name = item_trait.ident.to_string();
@@ -211,14 +248,15 @@ pub fn interface(
                             .map_err(|_| anchor_lang::__private::ErrorCode::InstructionDidNotSerialize)?;
                         let mut data = #sighash_tts.to_vec();
                         data.append(&mut ix_data);
-                        let accounts = ctx.to_account_metas(None);
+                        let accounts = anchor_lang::ToAccountMetas::to_account_metas(&ctx, None);
                         anchor_lang::solana_program::instruction::Instruction {
                             program_id: *ctx.program.key,
                             accounts,
                             data,
                         }
                     };
-                    let mut acc_infos = ctx.to_account_infos();
+                    ctx.check_compute_budget()?;
+                    let mut acc_infos = anchor_lang::ToAccountInfos::to_account_infos(&ctx);
                     acc_infos.push(ctx.program.clone());
                     anchor_lang::solana_program::program::invoke_signed(
                         &ix,
@@ -230,6 +268,8 @@ pub fn interface(
         })
         .collect();
 
+    let registry = registry.then(|| generate_registry(&trait_name));
+
     proc_macro::TokenStream::from(quote! {
         #item_trait
 
@@ -238,6 +278,119 @@ pub fn interface(
         mod #mod_name {
             use super::*;
             #(#methods)*
+            #registry
         }
     })
 }
+
+// Generates the `registry` module of an `#[interface(registry)]`, wrapping
+// `anchor_lang::interface_registry` for the interface.
+fn generate_registry(trait_name: &str) -> proc_macro2::TokenStream {
+    use anchor_syn::codegen::program::common::{
+        sighash, sighash_interface_register, sighash_interface_unregister,
+    };
+    let to_tts =
+        |bytes: [u8; 8]| -> proc_macro2::TokenStream { format!("{:?}", bytes).parse().unwrap() };
+    let interface_hash = to_tts(sighash("interface", trait_name));
+    let register_sighash = to_tts(sighash_interface_register());
+    let unregister_sighash = to_tts(sighash_interface_unregister());
+    quote! {
+        /// Registrations of the programs implementing the interface, see
+        /// `anchor_lang::interface_registry`.
+        #[allow(dead_code)]
+        pub mod registry {
+            use anchor_lang::solana_program::account_info::AccountInfo;
+            use anchor_lang::solana_program::entrypoint::ProgramResult;
+            use anchor_lang::solana_program::instruction::Instruction;
+            use anchor_lang::solana_program::pubkey::Pubkey;
+
+            /// The hash identifying the interface, see
+            /// `anchor_lang::interface_registry::interface_hash`.
+            pub const INTERFACE_HASH: [u8; 8] = #interface_hash;
+
+            /// Returns the address of the registration of `implementation`
+            /// by `authority` in `registry_program`, and its bump.
+            pub fn address(
+                registry_program: &Pubkey,
+                authority: &Pubkey,
+                implementation: &Pubkey,
+            ) -> (Pubkey, u8) {
+                anchor_lang::interface_registry::registration_address(
+                    registry_program,
+                    &INTERFACE_HASH,
+                    authority,
+                    implementation,
+                )
+            }
+
+            /// Checks that `registration` is the registration of
+            /// `implementation` by `authority` in `registry_program`.
+            pub fn verify(
+                registry_program: &Pubkey,
+                registration: &AccountInfo,
+                authority: &Pubkey,
+                implementation: &Pubkey,
+            ) -> ProgramResult {
+                anchor_lang::interface_registry::verify(
+                    registry_program,
+                    registration,
+                    &INTERFACE_HASH,
+                    authority,
+                    implementation,
+                )
+            }
+
+            /// The `interface_register` instruction of `registry_program`,
+            /// registering `implementation` by `authority`.
+            pub fn register_instruction(
+                registry_program: &Pubkey,
+                authority: &Pubkey,
+                implementation: &Pubkey,
+            ) -> Instruction {
+                Instruction {
+                    program_id: *registry_program,
+                    accounts: anchor_lang::interface_registry::register_account_metas(
+                        registry_program,
+                        &INTERFACE_HASH,
+                        authority,
+                        implementation,
+                    ),
+                    data: [
+                        &#register_sighash[..],
+                        &anchor_lang::interface_registry::instruction_args(
+                            &INTERFACE_HASH,
+                            implementation,
+                        ),
+                    ]
+                    .concat(),
+                }
+            }
+
+            /// The `interface_unregister` instruction of `registry_program`,
+            /// closing the registration of `implementation` by `authority`.
+            pub fn unregister_instruction(
+                registry_program: &Pubkey,
+                authority: &Pubkey,
+                implementation: &Pubkey,
+            ) -> Instruction {
+                Instruction {
+                    program_id: *registry_program,
+                    accounts: anchor_lang::interface_registry::unregister_account_metas(
+                        registry_program,
+                        &INTERFACE_HASH,
+                        authority,
+                        implementation,
+                    ),
+                    data: [
+                        &#unregister_sighash[..],
+                        &anchor_lang::interface_registry::instruction_args(
+                            &INTERFACE_HASH,
+                            implementation,
+                        ),
+                    ]
+                    .concat(),
+                }
+            }
+        }
+    }
+}
@@ -5,12 +5,216 @@ use syn::parse_macro_input;
 
 /// The `#[program]` attribute defines the module containing all instruction
 /// handlers defining all entries into a Solana program.
+///
+/// Handlers marked `#[simulation_only]` are part of the IDL and clients, but
+/// are only dispatched when the program is built with the `simulation`
+/// feature. Otherwise they fail with `InstructionSimulationOnly`, so they can
+/// only be run via transaction simulation against a simulation build.
+///
+/// Handlers marked `#[deprecated_instruction]` are flagged as deprecated in
+/// the IDL and log a warning whenever they're invoked. All arguments are
+/// optional:
+///
+/// ```ignore
+/// #[deprecated_instruction(since = "0.3", note = "use swap_v2", sunset_slot = 150_000_000)]
+/// pub fn swap(ctx: Context<Swap>, amount: u64) -> ProgramResult { .. }
+/// ```
+///
+/// Once the cluster reaches `sunset_slot`, the handler is refused with
+/// `InstructionSunset`, giving clients a known deadline to migrate.
+///
+/// The handler marked `#[transfer_hook]` is dispatched by the SPL transfer
+/// hook interface's `Execute` discriminator instead of its own sighash, so
+/// that the program can be invoked as a mint's transfer hook. See
+/// `anchor_spl::transfer_hook`.
+///
+/// Handlers marked `#[instruction(zero_copy)]` cast their args directly from
+/// the instruction data instead of borsh deserializing them. All args must be
+/// fixed size [`Pod`](../bytemuck/trait.Pod.html) types, e.g. integers, byte
+/// arrays and `Pubkey`s. Clients encode these instructions as usual.
+///
+/// Functions marked `#[instruction_prefix(<byte>, ..)]` receive, instead of
+/// sighash dispatch, all instructions whose data starts with the given bytes,
+/// e.g. to keep accepting the instruction layout of a native program being
+/// replaced:
+///
+/// ```ignore
+/// #[instruction_prefix(3)]
+/// pub fn legacy_transfer(
+///     program_id: &Pubkey,
+///     accounts: &[AccountInfo],
+///     data: &[u8],
+/// ) -> ProgramResult { .. }
+/// ```
+///
+/// The handler is given the raw, unstripped instruction data. Prefixes may not
+/// overlap with each other, or with the first bytes of any of the program's
+/// sighashes.
+///
+/// Handlers marked `#[idempotent]` run at most once per client chosen nonce,
+/// e.g. for payments that clients must be able to retry safely. The program
+/// macro adds a `nonce: [u8; 16]` arg to the instruction, which the handler
+/// doesn't receive, and expects three more accounts after the handler's: the
+/// replay account derived from the nonce, the payer funding it and the system
+/// program, see `anchor_lang::idempotent::replay_account_metas`. Executing the
+/// instruction again with the same nonce fails with
+/// `InstructionAlreadyExecuted`.
+///
+/// ```ignore
+/// #[idempotent(expiry = 86_400)]
+/// pub fn pay(ctx: Context<Pay>, amount: u64) -> ProgramResult { .. }
+/// ```
+///
+/// Once `expiry` seconds have passed, one day by default, anyone can close
+/// the replay account with the generated `close_replay` instruction, refunding
+/// its payer. The nonce can then be used again.
+///
+/// Handlers marked `#[skip_exit]` don't run the exit routine persisting
+/// their accounts, saving compute units for instructions that only read
+/// accounts. Their accounts struct, including nested ones, may not have any
+/// `mut` accounts, which is checked at compile time through
+/// [`ReadOnlyAccounts`](../anchor_lang/trait.ReadOnlyAccounts.html).
+///
+/// Handlers marked `#[batch]` process a vec of items in a single
+/// instruction, each with its own chunk of accounts. Besides the context, the
+/// handler takes a `&mut` accounts struct validating one chunk, and one item:
+///
+/// ```ignore
+/// #[batch]
+/// pub fn settle(
+///     ctx: Context<Settle>,
+///     position: &mut SettlePosition,
+///     args: SettleArgs,
+/// ) -> ProgramResult { .. }
+/// ```
+///
+/// The instruction takes an `items: Vec<SettleArgs>` arg, and after the
+/// handler's accounts, a `SettlePosition` chunk of accounts for each item, in
+/// order. All chunks are validated before the handler is invoked for each
+/// item, and are exited along with the handler's accounts. Accounts after
+/// the last chunk are the context's remaining accounts.
+///
+/// The structs generated in the `instruction` module only derive the
+/// (de)serialization traits. Additional traits can be derived for them with
+/// `#[program(derive(Clone, PartialEq))]`, e.g. to compare instructions in
+/// tests. The structs in `cpi::accounts` always implement `Clone`, `Debug`,
+/// `PartialEq` and `Eq`, comparing the accounts' keys.
+///
+/// With the program crate's `instruction-parser` feature, the `instruction`
+/// module also exports the `ProgramInstruction` enum, with a variant for
+/// each of the program's instruction structs, and
+/// `try_parse_instruction(data: &[u8]) -> Option<ProgramInstruction>`,
+/// decoding an instruction from its sighash and args. Programs inspecting the
+/// program's instructions, e.g. through the instructions sysvar, can use them
+/// instead of copying its instruction layouts.
+///
+/// Off chain consumers of transactions, e.g. indexers, can decode an
+/// instruction along with its accounts into a `ParsedInstruction`, with
+/// `try_parse_compiled_instruction(ix, account_keys)` given a message's
+/// `CompiledInstruction` and account keys, or with
+/// `try_parse_instruction_with_accounts(data, keys)`. Each variant holds the
+/// instruction struct as `args`, the `accounts` module's struct of its
+/// accounts' keys as `accounts`, and the keys of any `remaining_accounts`.
+///
+/// # CPI allowlist
+///
+/// With `#[program(cpi_allowlist(token::ID, associated_token::ID))]`, the
+/// program refuses instructions given any program other than itself, the
+/// system program and the listed ones, failing with `CpiProgramNotAllowed`.
+/// Since only the programs given to an instruction can be invoked, this
+/// rules out invoking an attacker supplied account in place of an expected
+/// program. Programs given only to be read are refused as well, so they must
+/// be listed too. Programs allowed at runtime, e.g. by an allowlist account,
+/// can be accepted with `cpi_allowlist_hook = <path>`, see
+/// [`cpi_allowlist`](../anchor_lang/cpi_allowlist/index.html).
+///
+/// # Timelocks
+///
+/// With `#[program(timelock)]`, the program gets the `timelock_queue` and
+/// `timelock_cancel` instructions, queueing and dropping an action on the
+/// signing authority's timelock. Instructions whose accounts check a
+/// timelock with `#[account(mut, timelock = <pubkey>, min_delay = <seconds>)]`
+/// can then only be executed once queued for `min_delay` seconds, see
+/// [`timelock`](../anchor_lang/timelock/index.html).
+///
+/// # Interface registries
+///
+/// With `#[program(interface_registry)]`, the program gets the
+/// `interface_register` and `interface_unregister` instructions, with which
+/// an authority registers and unregisters programs implementing an
+/// `#[interface]`. Callers can then validate an implementation against the
+/// registrations of the authority they trust, see
+/// [`interface_registry`](../anchor_lang/interface_registry/index.html).
+///
+/// # Schema registries
+///
+/// With `#[program(schema_registry)]`, the program gets the `schema_publish`
+/// instruction, with which its upgrade authority records the hash of the
+/// program's IDL and the version of its event schemas for the current
+/// deployment, e.g. right after an upgrade. Indexers check the record before
+/// decoding, to detect schema drift, see
+/// [`schema_registry`](../anchor_lang/schema_registry/index.html).
+///
+/// # Dependencies
+///
+/// With `#[program(dependencies(<module>, ..))]`, listing modules declared
+/// with `declare_dependency!`, the program gets the `check_dependencies`
+/// instruction, failing unless each dependency is deployed with its declared
+/// IDL hash, if any. Deploy tooling invokes it first after deploying or
+/// upgrading the program, with the accounts given by
+/// `instruction::CheckDependencies::accounts()`, see
+/// [`dependency`](../anchor_lang/dependency/index.html).
+///
+/// # Arenas
+///
+/// With `#[program(arena = <bytes>)]`, or `#[program(arena)]` for the
+/// default capacity, each instruction gets an arena of that many bytes,
+/// reserved on the heap by its first allocation. The instruction's event
+/// buffer and batch accounts are allocated from it, as are the temporary
+/// `ArenaVec`s and `ArenaString`s of handlers using `ctx.arena()`, see
+/// [`arena`](../anchor_lang/arena/index.html).
+///
+/// # Instruction data
+///
+/// Instruction args are deserialized one at a time, and an arg that doesn't
+/// deserialize is logged with its name, type and offset before failing with
+/// `InstructionDidNotDeserialize`. Data left after the last arg is ignored,
+/// so that clients can append args the deployed program doesn't know of yet.
+/// With `#[program(deny_trailing_args)]`, it is refused with
+/// `InstructionDataTooLong` instead.
+///
+/// # C headers
+///
+/// With `#[program(c_header)]`, `anchor build` also writes a C header of the
+/// program's instructions to `target/include/<program>.h`, for C and C++
+/// clients. It has each instruction's discriminator, a packed struct of its
+/// data if its args are fixed size, and the indices of its accounts. The
+/// option doesn't change the program itself.
+///
+/// # Panics
+///
+/// A panicking program aborts with an opaque error. With
+/// `#[program(panic_handler)]`, panics are logged with their location,
+/// followed by the `ProgramPanicked` error, see
+/// [`log_panic`](./panic/fn.log_panic.html). A custom handler can be given
+/// as `#[program(panic_handler = <path>)]`, with the signature
+/// `fn(&core::panic::PanicInfo)`. This replaces the panic handler provided
+/// by `solana_program`'s entrypoint.
 #[proc_macro_attribute]
 pub fn program(
-    _args: proc_macro::TokenStream,
+    args: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    parse_macro_input!(input as anchor_syn::Program)
-        .to_token_stream()
-        .into()
+    let args = parse_macro_input!(args as anchor_syn::ProgramArgs);
+    let mut program = parse_macro_input!(input as anchor_syn::Program);
+    program.panic_handler = args.panic_handler;
+    program.derives = args.derives;
+    program.cpi_allowlist = args.cpi_allowlist;
+    program.timelock = args.timelock;
+    program.interface_registry = args.interface_registry;
+    program.schema_registry = args.schema_registry;
+    program.dependencies = args.dependencies;
+    program.arena = args.arena;
+    program.deny_trailing_args = args.deny_trailing_args;
+    program.to_token_stream().into()
 }
@@ -43,8 +43,7 @@ pub fn state(
             quote! {
                 impl anchor_lang::__private::AccountSize for #struct_ident {
                     fn size(&self) -> std::result::Result<u64, anchor_lang::solana_program::program_error::ProgramError> {
-                        Ok(8 + self
-                           .try_to_vec()
+                        Ok(8 + anchor_lang::AnchorSerialize::try_to_vec(self)
                            .map_err(|_| anchor_lang::__private::ErrorCode::AccountDidNotSerialize)?
                            .len() as u64)
                     }
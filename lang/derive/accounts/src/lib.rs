@@ -38,23 +38,123 @@ use syn::parse_macro_input;
 /// | Attribute | Location | Description |
 /// |:--|:--|:--|
 /// | `#[account(signer)]`<br><br>`#[account(signer @ <custom_error>)]` | On raw `AccountInfo` structs. | Checks the given account signed the transaction. Custom errors are supported via `@`. |
-/// | `#[account(mut)]`<br><br>`#[account(mut @ <custom_error>)]` | On `AccountInfo`, `ProgramAccount` or `CpiAccount` structs. | Marks the account as mutable and persists the state transition. Custom errors are supported via `@`. |
-/// | `#[account(init)]` | On `ProgramAccount` structs. | Marks the account as being initialized, creating the account via the system program. |
-/// | `#[account(init_if_needed)]` | On `ProgramAccount` structs. | Same as `init` but skip if already initialized. |
+/// | `#[account(mut)]`<br><br>`#[account(mut @ <custom_error>)]` | On `AccountInfo`, `ProgramAccount` or `CpiAccount` structs. | Marks the account as mutable and persists the state transition. Custom errors are supported via `@`. Modifying an `Account` field that isn't `mut` in an instruction handler, e.g. `ctx.accounts.counter.count += 1`, warns at compile time and, with `anchor-debug`, fails at exit. |
+/// | `#[account(init)]` | On `ProgramAccount` structs. | Marks the account as being initialized, creating the account via the system program. Without `space`, the account's size is that of its type's `Default` value, or for `AccountLoader` accounts `8 + ZeroCopy::SIZE`, so zero copy structs don't need to implement `Default`. |
+/// | `#[account(init, payer = self)]` | On `ProgramAccount` structs. | Same as `init`, but the account pays for its own rent exemption out of lamports it already holds, e.g. from a transfer earlier in the transaction, so no payer is needed. Fails with `ConstraintSelfFunded` if it doesn't hold enough. Not supported for associated token accounts. |
+/// | `#[account(init, payer = [<target>, ..])]` | On `ProgramAccount` structs. | Same as `init`, but the rent exemption is split evenly between the given payers, with the first payer covering any remainder. Each payer must be a signer. Not supported for associated token accounts. |
+/// | `#[account(init_if_needed)]` | On `ProgramAccount` structs. | Same as `init` but skip if already initialized. On `AccountLoader` structs, an already initialized account must have a matching discriminator, and the data should be loaded with `load_init_if_needed`. |
 /// | `#[account(zero)]` | On `ProgramAccount` structs. | Asserts the account discriminator is zero. |
 /// | `#[account(close = <target>)]` | On `ProgramAccount` and `Loader` structs. | Marks the account as being closed at the end of the instruction's execution, sending the rent exemption lamports to the specified <target>. |
-/// | `#[account(has_one = <target>)]`<br><br>`#[account(has_one = <target> @ <custom_error>)]` | On `ProgramAccount` or `CpiAccount` structs | Checks the `target` field on the account matches the `target` field in the struct deriving `Accounts`. Custom errors are supported via `@`. |
-/// | `#[account(seeds = [<seeds>], bump? = <target>, payer? = <target>, space? = <target>, owner? = <target>)]` | On `AccountInfo` structs | Seeds for the program derived address an `AccountInfo` struct represents. If bump is provided, then appends it to the seeds. On initialization, validates the given bump is the bump provided by `Pubkey::find_program_address`.|
+/// | `#[account(has_one = <target>)]`<br><br>`#[account(has_one::allow_none = <target>)]`<br><br>`#[account(has_one = <target> @ <custom_error>)]` | On `ProgramAccount` or `CpiAccount` structs | Checks the `target` field on the account matches the `target` field in the struct deriving `Accounts`. The field can be an `Option<Pubkey>`, in which case `None` fails the check, unless given as `has_one::allow_none = <target>`. Custom errors are supported via `@`. |
+/// | `#[account(seeds = [<seeds>], bump? = <target>, payer? = <target>, space? = <target>, owner? = <target>)]` | On `AccountInfo` structs | Seeds for the program derived address an `AccountInfo` struct represents. If bump is provided, then appends it to the seeds. On initialization, validates the given bump is the bump provided by `Pubkey::find_program_address`. Integer seeds can be given as `le(<expr>)` or `be(<expr>)`, which converts them to their little or big endian bytes and records the endianness in the IDL. Seeds can be hashed into a single seed with `hash(<seed>, ..)`, e.g. to derive an address from more than 16 seeds or seeds longer than 32 bytes, see [`seeds::hash`](../anchor_lang/seeds/fn.hash.html). The bump is recorded in the handler's `ctx.bumps`, e.g. `ctx.bumps.vault`, and `ctx.signer_seeds_for("<field>", &[<seeds>])` gives the seeds signing for the account.|
 /// | `#[account(constraint = <expression>)]`<br><br>`#[account(constraint = <expression> @ <custom_error>)]` | On any type deriving `Accounts` | Executes the given code as a constraint. The expression should evaluate to a boolean. Custom errors are supported via `@`. |
-/// | `#[account("<literal>")]` | Deprecated | Executes the given code literal as a constraint. The literal should evaluate to a boolean. |
+/// | `#[account("<literal>")]` | Removed | A compile error suggesting the equivalent `constraint = <expression>`. Only accepted, with a deprecation warning, when built with the `deprecated-literal-constraint` feature. |
 /// | `#[account(rent_exempt = <skip>)]` | On `AccountInfo` or `ProgramAccount` structs | Optional attribute to skip the rent exemption check. By default, all accounts marked with `#[account(init)]` will be rent exempt, and so this should rarely (if ever) be used. Similarly, omitting `= skip` will mark the account rent exempt. |
+/// | `#[account(mut, rent_exempt = top_up(<target>))]` | On any type deriving `Accounts` | Transfers the lamports the account needs to be rent exempt for its current data length, if any, from the target account, e.g. after the account's data was grown. Requires `mut` and a `system_program` field. |
 /// | `#[account(executable)]` | On `AccountInfo` structs | Checks the given account is an executable program. |
 /// | `#[account(state = <target>)]` | On `CpiState` structs | Checks the given state is the canonical state account for the target program. |
 /// | `#[account(owner = <target>)]`<br><br>`#[account(owner = <target> @ <custom_error>)]` | On `CpiState`, `CpiAccount`, and `AccountInfo` | Checks the account owner matches the target. Custom errors are supported via `@`. |
-/// | `#[account(address = <pubkey>)]`<br><br>`#[account(address = <pubkey> @ <custom_error>)]` | On `AccountInfo` and `Account` | Checks the account key matches the pubkey. Custom errors are supported via `@`. |
+/// | `#[account(address = <pubkey>)]`<br><br>`#[account(address = <pubkey> @ <custom_error>)]` | On `AccountInfo` and `Account` | Checks the account key matches the pubkey. Custom errors are supported via `@`. The pubkey can be a field of another account, e.g. `config.oracle`, including zero-copy accounts, which are loaded to read it. |
+/// | `#[account(price_feed::max_age = <slots>)]` | On `Account<'info, anchor_spl::pyth::PriceFeed>` | Checks the Pyth price was published at most `max_age` slots before the current slot. |
+/// | `#[account(program::stable = <programdata>)]` | On `Program` structs | Checks the program wasn't upgraded in the current slot, given its program data account. |
+/// | `#[account(validate_after = <target>)]` | On any type deriving `Accounts` | Checks the account's constraints after the target field's, e.g. when its seeds read the target account's data. By default, `init` accounts are checked first, each after the `init` accounts it refers to, followed by the other accounts in declaration order. Can be given several times. Cycles are a compile error. |
+/// | `#[account(mut, assign_to = <pubkey>)]` | On any type deriving `Accounts` | Checks on exit that the account is owned by the pubkey, e.g. after allocating and assigning it by hand during the instruction. Combine with `owner = <target>` to check its owner before the instruction too. The pubkey is evaluated on exit, so it may refer to `program_id` but not to other fields. Fails with `ConstraintAssignTo`. |
+/// | `#[account(mut, timelock = <pubkey>, min_delay = <seconds>)]` | On `AccountInfo` and `UncheckedAccount` | Checks the account is the timelock at the pubkey, and that this instruction was queued on it at least `min_delay` seconds ago, consuming it. See [`timelock`](../anchor_lang/timelock/index.html). |
+/// | `#[account(active_window = <start>..<end>)]` | On any type deriving `Accounts` | Checks the clock's unix timestamp is within the range, the end being excluded, e.g. `#[account(active_window = config.start..config.end)]` to gate an instruction on a launch window stored in a config account. Zero copy accounts of the struct are loaded to read their bounds. Fails with `ConstraintActiveWindowNotStarted` before the start and `ConstraintActiveWindowEnded` from the end on. The bounds are emitted to the IDL. |
+/// | `#[account(when = <expression>, ...)]` | On any type deriving `Accounts` | Checks the account's other constraints only if the expression is true, e.g. `#[account(when = args.use_escrow, seeds = [b"escrow"], bump = args.escrow_bump)]` for an account only used in one of the instruction's modes. The expression may refer to the arguments declared with `#[instruction(..)]` and to any field. `mut` is checked either way, and `when` can't be combined with `init`, `zero`, `close` or `assign_to`. |
+///
+/// # Struct Constraints
+///
+/// Invariants spanning several accounts can be given on the struct itself,
+/// with `#[accounts(constraint = <expression>)]` or
+/// `#[accounts(constraint = <expression> @ <custom_error>)]`. They're
+/// checked in order after all the fields have been deserialized and
+/// validated, and may refer to any field, as well as to the arguments
+/// declared with `#[instruction(..)]`:
+///
+/// ```ignore
+/// #[derive(Accounts)]
+/// #[instruction(amount: u64)]
+/// #[accounts(constraint = from.mint == to.mint @ ErrorCode::MintMismatch)]
+/// #[accounts(constraint = from.amount >= amount)]
+/// pub struct Transfer<'info> {
+///     pub from: Account<'info, TokenAccount>,
+///     pub to: Account<'info, TokenAccount>,
+/// }
+/// ```
+///
+/// # Remaining Accounts
+///
+/// The accounts given after the struct's fields, i.e. the instruction's
+/// `ctx.remaining_accounts`, can be checked with
+/// `#[remaining_accounts(..)]` on the instruction's accounts struct, taking
+/// any of
+///
+/// * `min = <expr>` and `max = <expr>`, bounding their number.
+/// * `owner = <expr>`, the program owning each of them.
+/// * `writable`, requiring each of them to be writable.
+///
+/// ```ignore
+/// #[derive(Accounts)]
+/// #[remaining_accounts(min = 1, owner = token::ID, writable)]
+/// pub struct Distribute<'info> {
+///     pub authority: Signer<'info>,
+/// }
+/// ```
+///
+/// They're checked after the fields' constraints. On a nested accounts
+/// struct, they'd check the accounts following it in the outer struct, so
+/// they're only meant for the instruction's accounts struct.
+///
+/// # Snapshots
+///
+/// Structs marked `#[accounts(snapshot)]` get a `<Name>Snapshot` struct
+/// with the same fields, holding each account's key, lamports and
+/// deserialized value by value in an
+/// [`AccountSnapshot`](../anchor_lang/struct.AccountSnapshot.html), without
+/// borrowing the accounts. It's created with `From<&Name>`, e.g. to log the
+/// accounts before and after a handler, or to assert on what a handler
+/// changed in tests:
+///
+/// ```ignore
+/// let before = IncrementSnapshot::from(&*ctx.accounts);
+/// ctx.accounts.counter.count += 1;
+/// let after = IncrementSnapshot::from(&*ctx.accounts);
+/// msg!("{:?} -> {:?}", before.counter.value, after.counter.value);
+/// ```
+///
+/// Snapshots derive `Debug`, `Clone` and `PartialEq`, so the accounts'
+/// types must implement them too. Nested accounts structs must be marked
+/// `#[accounts(snapshot)]` as well.
+///
+/// # Profiling
+///
+/// If the program is built with a `profile` feature, declared as
+/// `profile = []` in its `Cargo.toml`, `try_accounts` logs the remaining
+/// compute units before each account is deserialized and before its
+/// constraints are checked, labeled `<Struct>.<field>: deserialize` and
+/// `<Struct>.<field>: constraints`, and before any struct constraints,
+/// labeled `<Struct>: constraints`.
+///
+/// # Lints
+///
+/// With anchor-lang's `lints` feature, suspicious patterns are reported as
+/// compile time warnings on the field concerned:
+///
+/// - An `AccountInfo` or `UncheckedAccount` named `authority` or
+///   `<name>_authority` that isn't required to sign.
+/// - A payer of initialized accounts that isn't `mut` or isn't required to
+///   sign.
+/// - A mutable `payer`, or a payer of initialized accounts, without a
+///   `system_program` field in the struct, unless it has composite fields.
+///
+/// Initializing an account without a payer is always an error.
 // TODO: How do we make the markdown render correctly without putting everything
 //       on absurdly long lines?
-#[proc_macro_derive(Accounts, attributes(account, instruction))]
+#[proc_macro_derive(
+    Accounts,
+    attributes(account, accounts, instruction, remaining_accounts)
+)]
 pub fn derive_anchor_deserialize(item: TokenStream) -> TokenStream {
     parse_macro_input!(item as anchor_syn::AccountsStruct)
         .to_token_stream()
@@ -1,4 +1,5 @@
 use crate::error::ErrorCode;
+use crate::Result;
 use crate::*;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
@@ -34,25 +35,60 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Owner + Clone> Account<'a, T
     /// Deserializes the given `info` into a `Account`.
     #[inline(never)]
     pub fn try_from(info: &AccountInfo<'a>) -> Result<Account<'a, T>, ProgramError> {
+        Self::try_from_owned_by(info, &T::owner())
+    }
+
+    /// Same as [`try_from`](Self::try_from), but checks the owner the
+    /// account has while executing as `program_id`, see [`Owner::owner_for`].
+    #[inline(never)]
+    pub fn try_from_for(
+        program_id: &Pubkey,
+        info: &AccountInfo<'a>,
+    ) -> Result<Account<'a, T>, ProgramError> {
+        Self::try_from_owned_by(info, &T::owner_for(program_id))
+    }
+
+    /// Deserializes the given `info` into a `Account` without checking
+    /// the account discriminator. Be careful when using this and avoid it if
+    /// possible.
+    #[inline(never)]
+    pub fn try_from_unchecked(info: &AccountInfo<'a>) -> Result<Account<'a, T>, ProgramError> {
+        Self::try_from_unchecked_owned_by(info, &T::owner())
+    }
+
+    /// Same as [`try_from_unchecked`](Self::try_from_unchecked), but checks
+    /// the owner the account has while executing as `program_id`, see
+    /// [`Owner::owner_for`].
+    #[inline(never)]
+    pub fn try_from_unchecked_for(
+        program_id: &Pubkey,
+        info: &AccountInfo<'a>,
+    ) -> Result<Account<'a, T>, ProgramError> {
+        Self::try_from_unchecked_owned_by(info, &T::owner_for(program_id))
+    }
+
+    fn try_from_owned_by(
+        info: &AccountInfo<'a>,
+        owner: &Pubkey,
+    ) -> Result<Account<'a, T>, ProgramError> {
         if info.owner == &system_program::ID && info.lamports() == 0 {
             return Err(ErrorCode::AccountNotInitialized.into());
         }
-        if info.owner != &T::owner() {
+        if info.owner != owner {
             return Err(ErrorCode::AccountNotProgramOwned.into());
         }
         let mut data: &[u8] = &info.try_borrow_data()?;
         Ok(Account::new(info.clone(), T::try_deserialize(&mut data)?))
     }
 
-    /// Deserializes the given `info` into a `Account` without checking
-    /// the account discriminator. Be careful when using this and avoid it if
-    /// possible.
-    #[inline(never)]
-    pub fn try_from_unchecked(info: &AccountInfo<'a>) -> Result<Account<'a, T>, ProgramError> {
+    fn try_from_unchecked_owned_by(
+        info: &AccountInfo<'a>,
+        owner: &Pubkey,
+    ) -> Result<Account<'a, T>, ProgramError> {
         if info.owner == &system_program::ID && info.lamports() == 0 {
             return Err(ErrorCode::AccountNotInitialized.into());
         }
-        if info.owner != &T::owner() {
+        if info.owner != owner {
             return Err(ErrorCode::AccountNotProgramOwned.into());
         }
         let mut data: &[u8] = &info.try_borrow_data()?;
@@ -70,11 +106,27 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Owner + Clone> Account<'a, T
         Ok(())
     }
 
+    /// Returns true if the account has been modified since it was loaded,
+    /// i.e. if its serialization differs from the account's data.
+    pub fn is_dirty(&self) -> Result<bool, ProgramError> {
+        let mut serialized = Vec::new();
+        self.account.try_serialize(&mut serialized)?;
+        let data = self.info.try_borrow_data()?;
+        Ok(data.get(..serialized.len()) != Some(&serialized[..]))
+    }
+
     pub fn into_inner(self) -> T {
         self.account
     }
 }
 
+impl<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone> Bumps for Account<'info, T>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    type Bumps = ();
+}
+
 impl<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone> Accounts<'info>
     for Account<'info, T>
 where
@@ -82,25 +134,25 @@ where
 {
     #[inline(never)]
     fn try_accounts(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
         let account = &accounts[0];
         *accounts = &accounts[1..];
-        Account::try_from(account)
+        Ok(Account::try_from_for(program_id, account)?)
     }
 }
 
 impl<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone> AccountsExit<'info>
     for Account<'info, T>
 {
-    fn exit(&self, program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
         // Only persist if the owner is the current program.
-        if &T::owner() == program_id {
+        if &T::owner_for(program_id) == program_id {
             let info = self.to_account_info();
             let mut data = info.try_borrow_mut_data()?;
             let dst: &mut [u8] = &mut data;
@@ -114,8 +166,11 @@ impl<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone> AccountsEx
 impl<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone> AccountsClose<'info>
     for Account<'info, T>
 {
-    fn close(&self, sol_destination: AccountInfo<'info>) -> ProgramResult {
-        crate::common::close(self.to_account_info(), sol_destination)
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()> {
+        Ok(crate::common::close(
+            self.to_account_info(),
+            sol_destination,
+        )?)
     }
 }
 
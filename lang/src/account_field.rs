@@ -0,0 +1,98 @@
+//! Fields read from an account's data at a fixed offset, without
+//! deserializing the rest of the account.
+//!
+//! `#[account]` provides the [`AccountField`] of each field of a zero copy
+//! account, and of each leading field of a borsh encoded account, up to the
+//! first field whose encoded size isn't known from its type, i.e. isn't an
+//! integer, `bool`, `Pubkey` or an array of them. They're named with
+//! [`account_field!`](macro@crate::account_field):
+//!
+//! ```ignore
+//! let authority = account_field!(Pool, authority).read(&info.try_borrow_data()?)?;
+//! ```
+//!
+//! Clients can fetch only a field of many accounts, see
+//! `anchor_client::Program::account_fields`.
+//!
+//! The same offsets and lengths are also emitted as constants, in a module
+//! named after the account type, e.g. for `memcmp` filters:
+//!
+//! ```ignore
+//! let filter = RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+//!     pool_offsets::AUTHORITY,
+//!     authority.to_bytes().to_vec(),
+//! ));
+//! assert_eq!(pool_offsets::AUTHORITY_LEN, 32);
+//! ```
+//!
+//! Generic account types don't have an offsets module.
+
+use crate::error::ErrorCode;
+use crate::AnchorDeserialize;
+use solana_program::program_error::ProgramError;
+use std::marker::PhantomData;
+
+/// The field of type `F` of the account type `T`.
+pub struct AccountField<T, F> {
+    offset: usize,
+    size: usize,
+    _marker: PhantomData<fn() -> (T, F)>,
+}
+
+impl<T, F> AccountField<T, F> {
+    #[doc(hidden)]
+    pub fn new(offset: usize, size: usize) -> Self {
+        Self {
+            offset,
+            size,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Offset of the field in the account's data, including the
+    /// discriminator.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Size of the field in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Offset of the end of the field in the account's data.
+    pub fn end(&self) -> usize {
+        self.offset + self.size
+    }
+
+    /// The field's bytes in the account's `data`.
+    pub fn bytes<'a>(&self, data: &'a [u8]) -> Result<&'a [u8], ProgramError> {
+        data.get(self.offset..self.end())
+            .ok_or_else(|| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+impl<T, F: AnchorDeserialize> AccountField<T, F> {
+    /// Decodes the field from the account's `data`, without checking the
+    /// account's discriminator.
+    pub fn read(&self, data: &[u8]) -> Result<F, ProgramError> {
+        F::try_from_slice(self.bytes(data)?).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+impl<T, F> Clone for AccountField<T, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, F> Copy for AccountField<T, F> {}
+
+impl<T, F> std::fmt::Debug for AccountField<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountField")
+            .field("offset", &self.offset)
+            .field("size", &self.size)
+            .finish()
+    }
+}
@@ -0,0 +1,375 @@
+//! An arena for the temporary vectors and strings of an instruction.
+//!
+//! Solana's allocator never frees memory, so temporaries allocated in a loop,
+//! e.g. for each CPI or each item of a batch, add up until the 32KB heap is
+//! exhausted. An [`Arena`] reserves a region of the heap on its first
+//! allocation, from which [`ArenaVec`]s and [`ArenaString`]s are allocated,
+//! and which is reused after a [`reset`](Arena::reset).
+//!
+//! Programs built with `#[program(arena = <bytes>)]` get an arena of that
+//! capacity, or of [`DEFAULT_CAPACITY`] with `#[program(arena)]`, for each
+//! instruction. The generated handler allocates the instruction's event
+//! buffer and the accounts of the items of a `#[batch]` instruction from it,
+//! and handlers get it as `ctx.arena()`:
+//!
+//! ```ignore
+//! let mut seeds = ArenaVec::new_in(ctx.arena());
+//! for user in users {
+//!     seeds.clear();
+//!     seeds.try_extend_from_slice(user.key.as_ref())?;
+//!     ..
+//! }
+//! ```
+//!
+//! Without an arena, the collections are allocated on the heap, like `Vec`
+//! and `String`.
+
+use crate::error::ErrorCode;
+use solana_program::program_error::ProgramError;
+use std::alloc::{self, Layout};
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+/// Capacity of the arena of programs with `#[program(arena)]`.
+pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+// Alignment of the arena's region, the largest alignment on Solana.
+const REGION_ALIGN: usize = 8;
+
+/// A region of the heap allocated from front to back, and freed at once by
+/// [`reset`](Self::reset). The region is only reserved by the first
+/// allocation, so instructions that don't use their arena don't pay for it.
+pub struct Arena {
+    region: Cell<Option<NonNull<u8>>>,
+    capacity: usize,
+    used: Cell<usize>,
+}
+
+impl Arena {
+    /// An arena of `capacity` bytes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            region: Cell::new(None),
+            capacity,
+            used: Cell::new(0),
+        }
+    }
+
+    /// Whether the region was reserved, i.e. something was allocated from
+    /// the arena.
+    pub fn is_reserved(&self) -> bool {
+        self.region.get().is_some()
+    }
+
+    // The region, reserved on first use.
+    fn region(&self) -> NonNull<u8> {
+        if let Some(region) = self.region.get() {
+            return region;
+        }
+        let region = match self.capacity {
+            0 => NonNull::dangling(),
+            _ => {
+                let layout = Layout::from_size_align(self.capacity, REGION_ALIGN).unwrap();
+                // Safe as the layout isn't zero sized.
+                let region = unsafe { alloc::alloc(layout) };
+                NonNull::new(region).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+            }
+        };
+        self.region.set(Some(region));
+        region
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of bytes allocated since the last reset.
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    /// Frees everything allocated from the arena. The collections allocated
+    /// from it borrow it, so none is left.
+    pub fn reset(&mut self) {
+        self.used.set(0);
+    }
+
+    // Allocates `layout` at the end of the used region.
+    fn alloc(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let region = self.region();
+        let base = region.as_ptr() as usize;
+        let start = align_up(base + self.used.get(), layout.align())? - base;
+        let end = start.checked_add(layout.size())?;
+        if end > self.capacity {
+            return None;
+        }
+        self.used.set(end);
+        // Safe as `start` is within the region.
+        NonNull::new(unsafe { region.as_ptr().add(start) })
+    }
+
+    // Grows the allocation of `old_size` bytes at `ptr` to `new_size` bytes
+    // in place, if it's the last allocation and the region has room for it.
+    fn grow_last(&self, ptr: NonNull<u8>, old_size: usize, new_size: usize) -> bool {
+        let start = ptr.as_ptr() as usize - self.region().as_ptr() as usize;
+        if start + old_size != self.used.get() || start + new_size > self.capacity {
+            return false;
+        }
+        self.used.set(start + new_size);
+        true
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        if let (Some(region), true) = (self.region.get(), self.capacity > 0) {
+            let layout = Layout::from_size_align(self.capacity, REGION_ALIGN).unwrap();
+            // Safe as the region was allocated with this layout.
+            unsafe { alloc::dealloc(region.as_ptr(), layout) };
+        }
+    }
+}
+
+impl fmt::Debug for Arena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("capacity", &self.capacity)
+            .field("used", &self.used.get())
+            .finish()
+    }
+}
+
+fn align_up(addr: usize, align: usize) -> Option<usize> {
+    Some(addr.checked_add(align - 1)? & !(align - 1))
+}
+
+/// A vector allocated from an [`Arena`], or from the heap without one.
+/// Pushing fails with `ArenaExhausted` once the arena is full.
+pub struct ArenaVec<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    capacity: usize,
+    arena: Option<&'a Arena>,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T> ArenaVec<'a, T> {
+    /// An empty vector allocating from `arena`, or from the heap if `None`.
+    pub fn new_in(arena: Option<&'a Arena>) -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            len: 0,
+            capacity: match mem::size_of::<T>() {
+                0 => usize::MAX,
+                _ => 0,
+            },
+            arena,
+            marker: PhantomData,
+        }
+    }
+
+    /// An empty vector with room for `capacity` items.
+    pub fn with_capacity_in(
+        capacity: usize,
+        arena: Option<&'a Arena>,
+    ) -> Result<Self, ProgramError> {
+        let mut vec = Self::new_in(arena);
+        vec.reserve(capacity)?;
+        Ok(vec)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Makes room for `additional` more items.
+    pub fn reserve(&mut self, additional: usize) -> Result<(), ProgramError> {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .ok_or(ErrorCode::ArenaExhausted)?;
+        if needed <= self.capacity {
+            return Ok(());
+        }
+        let capacity = needed.max(self.capacity * 2).max(4);
+        self.grow(capacity)
+    }
+
+    fn grow(&mut self, capacity: usize) -> Result<(), ProgramError> {
+        let layout = Layout::array::<T>(capacity).map_err(|_| ErrorCode::ArenaExhausted)?;
+        let old_size = self.capacity * mem::size_of::<T>();
+        let ptr = match self.arena {
+            Some(arena) => {
+                if self.capacity > 0 && arena.grow_last(self.ptr.cast(), old_size, layout.size()) {
+                    self.capacity = capacity;
+                    return Ok(());
+                }
+                arena.alloc(layout).ok_or(ErrorCode::ArenaExhausted)?
+            }
+            None => {
+                // Safe as the layout isn't zero sized, `T` and `capacity`
+                // not being zero.
+                let ptr = unsafe { alloc::alloc(layout) };
+                NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+            }
+        };
+        let ptr = ptr.cast::<T>();
+        // Safe as both allocations hold at least `len` items and don't
+        // overlap.
+        unsafe { ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len) };
+        self.free();
+        self.ptr = ptr;
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    // Frees the allocation, if it was made from the heap.
+    fn free(&mut self) {
+        if self.arena.is_none() && self.capacity > 0 && mem::size_of::<T>() > 0 {
+            let layout = Layout::array::<T>(self.capacity).unwrap();
+            // Safe as the allocation was made with this layout.
+            unsafe { alloc::dealloc(self.ptr.as_ptr().cast(), layout) };
+        }
+    }
+
+    pub fn push(&mut self, item: T) -> Result<(), ProgramError> {
+        self.reserve(1)?;
+        // Safe as there's room for the item.
+        unsafe { ptr::write(self.ptr.as_ptr().add(self.len), item) };
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // Safe as the item was initialized and is no longer part of the
+        // vector.
+        Some(unsafe { ptr::read(self.ptr.as_ptr().add(self.len)) })
+    }
+
+    /// Drops the items, keeping the allocation.
+    pub fn clear(&mut self) {
+        let items: *mut [T] = &mut **self;
+        self.len = 0;
+        // Safe as the items were initialized and are no longer part of the
+        // vector.
+        unsafe { ptr::drop_in_place(items) };
+    }
+}
+
+impl<'a, T: Clone> ArenaVec<'a, T> {
+    pub fn try_extend_from_slice(&mut self, items: &[T]) -> Result<(), ProgramError> {
+        self.reserve(items.len())?;
+        for item in items {
+            self.push(item.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> io::Write for ArenaVec<'a, u8> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.try_extend_from_slice(buf)
+            .map_err(|_| io::Error::from(io::ErrorKind::OutOfMemory))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a, T> Deref for ArenaVec<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // Safe as the first `len` items are initialized.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<'a, T> DerefMut for ArenaVec<'a, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // Safe as the first `len` items are initialized.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<'a, T> Drop for ArenaVec<'a, T> {
+    fn drop(&mut self) {
+        self.clear();
+        self.free();
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for ArenaVec<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A string allocated from an [`Arena`], or from the heap without one. It
+/// implements `fmt::Write`, which fails once the arena is full.
+pub struct ArenaString<'a> {
+    bytes: ArenaVec<'a, u8>,
+}
+
+impl<'a> ArenaString<'a> {
+    /// An empty string allocating from `arena`, or from the heap if `None`.
+    pub fn new_in(arena: Option<&'a Arena>) -> Self {
+        Self {
+            bytes: ArenaVec::new_in(arena),
+        }
+    }
+
+    pub fn push_str(&mut self, s: &str) -> Result<(), ProgramError> {
+        self.bytes.try_extend_from_slice(s.as_bytes())
+    }
+
+    pub fn push(&mut self, c: char) -> Result<(), ProgramError> {
+        self.push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Safe as only strings are pushed.
+        unsafe { std::str::from_utf8_unchecked(&self.bytes) }
+    }
+}
+
+impl<'a> Deref for ArenaString<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a> fmt::Write for ArenaString<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<'a> fmt::Debug for ArenaString<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a> fmt::Display for ArenaString<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
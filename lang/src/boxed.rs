@@ -1,23 +1,45 @@
-use crate::{Accounts, AccountsClose, AccountsExit, ToAccountInfos, ToAccountMetas};
+use crate::Result;
+use crate::{Accounts, AccountsClose, AccountsExit, Bumps, ToAccountInfos, ToAccountMetas};
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use std::ops::Deref;
 
+impl<T: Bumps> Bumps for Box<T> {
+    type Bumps = T::Bumps;
+}
+
 impl<'info, T: Accounts<'info>> Accounts<'info> for Box<T> {
     fn try_accounts(
         program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         T::try_accounts(program_id, accounts, ix_data).map(Box::new)
     }
+
+    fn try_accounts_with_bumps(
+        program_id: &Pubkey,
+        accounts: &mut &[AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut Self::Bumps,
+    ) -> Result<Self> {
+        T::try_accounts_with_bumps(program_id, accounts, ix_data, bumps).map(Box::new)
+    }
+
+    fn try_accounts_for_instruction(
+        program_id: &Pubkey,
+        accounts: &mut &[AccountInfo<'info>],
+        sighash: Option<&[u8; 8]>,
+        ix_data: &[u8],
+        bumps: &mut Self::Bumps,
+    ) -> Result<Self> {
+        T::try_accounts_for_instruction(program_id, accounts, sighash, ix_data, bumps).map(Box::new)
+    }
 }
 
 impl<'info, T: AccountsExit<'info>> AccountsExit<'info> for Box<T> {
-    fn exit(&self, program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
         T::exit(Deref::deref(self), program_id)
     }
 }
@@ -35,7 +57,7 @@ impl<T: ToAccountMetas> ToAccountMetas for Box<T> {
 }
 
 impl<'info, T: AccountsClose<'info>> AccountsClose<'info> for Box<T> {
-    fn close(&self, sol_destination: AccountInfo<'info>) -> ProgramResult {
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()> {
         T::close(self, sol_destination)
     }
 }
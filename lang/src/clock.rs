@@ -0,0 +1,118 @@
+//! The clock read by generated time constraints, e.g. `price_feed_max_age`,
+//! `program::stable`, deprecated instructions' sunset slot and timelocks,
+//! and by `Context::clock`.
+//!
+//! On chain it's always the clock sysvar. Off chain, unit tests can replace
+//! it for the current thread with any [`ClockProvider`], typically a
+//! [`MockClock`], which they warp to the slots and times they test:
+//!
+//! ```ignore
+//! let clock = MockClock::new(Clock::default());
+//! clock.install();
+//! clock.advance(86_400);
+//! assert!(vesting::entry(&ID, &accounts, &data).is_ok());
+//! ```
+
+use crate::compat::GetSysvar;
+use solana_program::clock::Clock;
+use solana_program::epoch_schedule::EpochSchedule;
+use solana_program::program_error::ProgramError;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A source of the current clock.
+pub trait ClockProvider {
+    fn clock(&self) -> Result<Clock, ProgramError>;
+}
+
+/// The clock sysvar.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SysvarClock;
+
+impl ClockProvider for SysvarClock {
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        Clock::get_sysvar()
+    }
+}
+
+/// A fixed clock.
+impl ClockProvider for Clock {
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        Ok(self.clone())
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+thread_local! {
+    static PROVIDER: RefCell<Option<Box<dyn ClockProvider>>> = RefCell::new(None);
+}
+
+/// The current clock, given by the thread's provider off chain if one was
+/// installed with [`set_provider`].
+pub fn get() -> Result<Clock, ProgramError> {
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        let clock = PROVIDER.with(|provider| provider.borrow().as_ref().map(|p| p.clock()));
+        if let Some(clock) = clock {
+            return clock;
+        }
+    }
+    SysvarClock.clock()
+}
+
+/// Replaces the clock of the current thread with `provider`.
+#[cfg(not(target_arch = "bpf"))]
+pub fn set_provider(provider: impl ClockProvider + 'static) {
+    PROVIDER.with(|p| *p.borrow_mut() = Some(Box::new(provider)));
+}
+
+/// Restores the clock sysvar as the clock of the current thread.
+#[cfg(not(target_arch = "bpf"))]
+pub fn clear_provider() {
+    PROVIDER.with(|p| *p.borrow_mut() = None);
+}
+
+/// A clock for tests, shared by its clones, so that a test can warp the
+/// clock it installed.
+#[derive(Debug, Clone, Default)]
+pub struct MockClock(Rc<RefCell<Clock>>);
+
+impl MockClock {
+    pub fn new(clock: Clock) -> Self {
+        Self(Rc::new(RefCell::new(clock)))
+    }
+
+    /// Makes this the clock of the current thread, see [`set_provider`].
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn install(&self) {
+        set_provider(self.clone());
+    }
+
+    pub fn set(&self, clock: Clock) {
+        *self.0.borrow_mut() = clock;
+    }
+
+    /// Moves the clock to `slot`, keeping its epoch and time.
+    pub fn warp_to_slot(&self, slot: u64) {
+        self.0.borrow_mut().slot = slot;
+    }
+
+    /// Moves the clock to the first slot of `epoch`, as scheduled by
+    /// `schedule`.
+    pub fn warp_to_epoch(&self, epoch: u64, schedule: &EpochSchedule) {
+        let mut clock = self.0.borrow_mut();
+        clock.epoch = epoch;
+        clock.slot = schedule.get_first_slot_in_epoch(epoch);
+    }
+
+    /// Moves the unix timestamp `seconds` forward, or backward if negative.
+    pub fn advance(&self, seconds: i64) {
+        self.0.borrow_mut().unix_timestamp += seconds;
+    }
+}
+
+impl ClockProvider for MockClock {
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        Ok(self.0.borrow().clone())
+    }
+}
@@ -1,6 +1,11 @@
+use crate::compat::GetSysvar;
 use crate::error::ErrorCode;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
 use std::io::Write;
 
 pub fn close<'info>(
@@ -22,3 +27,53 @@ pub fn close<'info>(
         .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
     Ok(())
 }
+
+// Creates the rent exempt, program owned account at the program address
+// derived from `seeds`, funded by `payer`.
+pub fn create_pda<'info>(
+    program_id: &Pubkey,
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let infos = [account.clone(), payer.clone(), system_program.clone()];
+    let rent = Rent::get_sysvar()?.minimum_balance(space);
+    // The address may have been funded up front, in which case it can't be
+    // created with `create_account`.
+    let lamports = account.lamports();
+    if lamports == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                rent,
+                space as u64,
+                program_id,
+            ),
+            &infos,
+            &[seeds],
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(lamports);
+        if top_up > 0 {
+            invoke_signed(
+                &system_instruction::transfer(payer.key, account.key, top_up),
+                &infos,
+                &[],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(account.key, space as u64),
+            &infos,
+            &[seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(account.key, program_id),
+            &infos,
+            &[seeds],
+        )?;
+    }
+    Ok(())
+}
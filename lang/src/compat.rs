@@ -0,0 +1,104 @@
+//! Version independent wrappers around `solana_program` APIs.
+//!
+//! Several `solana_program` APIs have been renamed, deprecated or had their
+//! semantics changed across the 1.x releases (e.g. the unchecked instructions
+//! sysvar loaders were deprecated in 1.8 in favor of their `_checked`
+//! counterparts, and the `Fees`/`RecentBlockhashes` sysvars in 1.9). Both
+//! Anchor's generated code and programs can go through this module instead of
+//! calling those APIs directly, so that the same anchor-lang release compiles
+//! against any supported `solana-program` version.
+//!
+//! APIs added in solana-program 1.9 are used when anchor-lang is built with
+//! the `solana-1-9` feature, which must only be enabled when the program
+//! depends on solana-program 1.9 or later. Without it, the wrappers fall back
+//! to an implementation over the 1.8 APIs, or aren't available when there is
+//! none, e.g. for the return data syscalls.
+
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::{self, Sysvar};
+
+/// Fetches a sysvar without requiring its account to be passed to the
+/// program.
+pub trait GetSysvar: Sized {
+    fn get_sysvar() -> Result<Self, ProgramError>;
+}
+
+impl<T: Sysvar> GetSysvar for T {
+    fn get_sysvar() -> Result<Self, ProgramError> {
+        T::get()
+    }
+}
+
+/// Returns the index of the currently executing instruction, read from the
+/// instructions sysvar account.
+pub fn load_current_index(instructions_sysvar: &AccountInfo) -> Result<u16, ProgramError> {
+    sysvar::instructions::load_current_index_checked(instructions_sysvar)
+}
+
+/// Returns the instruction at `index` in the currently executing
+/// transaction, read from the instructions sysvar account.
+pub fn load_instruction_at(
+    index: usize,
+    instructions_sysvar: &AccountInfo,
+) -> Result<Instruction, ProgramError> {
+    sysvar::instructions::load_instruction_at_checked(index, instructions_sysvar)
+}
+
+/// Returns the instruction at `index_relative_to_current` from the currently
+/// executing one, e.g. `-1` for the previous instruction of the transaction.
+pub fn get_instruction_relative(
+    index_relative_to_current: i64,
+    instructions_sysvar: &AccountInfo,
+) -> Result<Instruction, ProgramError> {
+    #[cfg(feature = "solana-1-9")]
+    {
+        sysvar::instructions::get_instruction_relative(
+            index_relative_to_current,
+            instructions_sysvar,
+        )
+    }
+    #[cfg(not(feature = "solana-1-9"))]
+    {
+        let current_index = load_current_index(instructions_sysvar)? as i64;
+        let index = current_index.saturating_add(index_relative_to_current);
+        if index < 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        load_instruction_at(index as usize, instructions_sysvar)
+    }
+}
+
+/// Sets the data returned to the caller of the current instruction.
+#[cfg(feature = "solana-1-9")]
+pub fn set_return_data(data: &[u8]) {
+    solana_program::program::set_return_data(data)
+}
+
+/// Returns the data set by the last invoked program, along with its id.
+#[cfg(feature = "solana-1-9")]
+pub fn get_return_data() -> Option<(solana_program::pubkey::Pubkey, Vec<u8>)> {
+    solana_program::program::get_return_data()
+}
+
+/// Returns the number of compute units remaining in the current
+/// transaction, or `None` if the runtime can't report it.
+///
+/// The `sol_remaining_compute_units` syscall is only available on recent
+/// runtimes, and a program referencing it fails to deploy on clusters that
+/// don't have it, so it's only used when anchor-lang is built with the
+/// `remaining-compute-units` feature.
+pub fn remaining_compute_units() -> Option<u64> {
+    #[cfg(all(target_arch = "bpf", feature = "remaining-compute-units"))]
+    {
+        extern "C" {
+            fn sol_remaining_compute_units() -> u64;
+        }
+        Some(unsafe { sol_remaining_compute_units() })
+    }
+    #[cfg(not(all(target_arch = "bpf", feature = "remaining-compute-units")))]
+    {
+        None
+    }
+}
@@ -0,0 +1,51 @@
+//! zlib compressed account bodies, used by accounts declared with
+//! `#[account(compressed)]`. Enabled with the `compression` feature.
+//!
+//! The body following the account discriminator is laid out as
+//!
+//! `uncompressed_len: u32 || compressed_len: u32 || zlib(data)`
+//!
+//! with any remaining bytes of the account left unused, so that the
+//! compressed body can shrink and grow within the account's allocated space.
+
+use crate::error::ErrorCode;
+use solana_program::program_error::ProgramError;
+use std::convert::TryInto;
+use std::io::Write;
+
+/// Length of the header preceding the compressed data.
+pub const HEADER_LEN: usize = 8;
+
+// Favors speed over ratio, since compression runs on every write.
+const COMPRESSION_LEVEL: u8 = 1;
+
+/// Compresses `data` and writes it, with its header, to `writer`.
+pub fn encode<W: Write>(data: &[u8], writer: &mut W) -> Result<(), ProgramError> {
+    let compressed = miniz_oxide::deflate::compress_to_vec_zlib(data, COMPRESSION_LEVEL);
+    writer
+        .write_all(&(data.len() as u32).to_le_bytes())
+        .and_then(|_| writer.write_all(&(compressed.len() as u32).to_le_bytes()))
+        .and_then(|_| writer.write_all(&compressed))
+        .map_err(|_| ErrorCode::AccountDidNotSerialize.into())
+}
+
+/// Reads and decompresses a body written by [`encode`].
+pub fn decode(buf: &[u8]) -> Result<Vec<u8>, ProgramError> {
+    if buf.len() < HEADER_LEN {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    let uncompressed_len = u32::from_le_bytes(buf[..4].try_into().unwrap()) as usize;
+    let compressed_len = u32::from_le_bytes(buf[4..8].try_into().unwrap()) as usize;
+    let compressed = buf
+        .get(HEADER_LEN..HEADER_LEN + compressed_len)
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    // Inflates no more than the header's length, so that a small account
+    // can't expand to more than the program's heap.
+    let data =
+        miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(compressed, uncompressed_len)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+    if data.len() != uncompressed_len {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    Ok(data)
+}
@@ -1,11 +1,18 @@
-use crate::{Accounts, ToAccountInfos, ToAccountMetas};
+use crate::arena::Arena;
+use crate::error::ErrorCode;
+use crate::event::{EventBuffer, Events};
+use crate::{AccountBumps, AccountMetaTemplate, Accounts, Bumps, ToAccountInfos, ToAccountMetas};
 use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use std::fmt;
+use std::ops::Deref;
 
 /// Provides non-argument inputs to the program.
-pub struct Context<'a, 'b, 'c, 'info, T> {
+pub struct Context<'a, 'b, 'c, 'info, T: Bumps> {
     /// Currently executing program id.
     pub program_id: &'a Pubkey,
     /// Deserialized accounts.
@@ -13,14 +20,22 @@ pub struct Context<'a, 'b, 'c, 'info, T> {
     /// Remaining accounts given but not deserialized or validated.
     /// Be very careful when using this directly.
     pub remaining_accounts: &'c [AccountInfo<'info>],
+    /// Buffer of the events pushed with `events()`, if any.
+    pub event_buffer: Option<&'b EventBuffer<'a>>,
+    /// Arena of the instruction's temporaries, if any.
+    pub arena: Option<&'a Arena>,
+    /// Bumps of the accounts checked with `seeds` constraints, e.g.
+    /// `ctx.bumps.vault`, see [`Bumps`].
+    pub bumps: T::Bumps,
 }
 
-impl<'a, 'b, 'c, 'info, T: fmt::Debug> fmt::Debug for Context<'a, 'b, 'c, 'info, T> {
+impl<'a, 'b, 'c, 'info, T: Bumps + fmt::Debug> fmt::Debug for Context<'a, 'b, 'c, 'info, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Context")
             .field("program_id", &self.program_id)
             .field("accounts", &self.accounts)
             .field("remaining_accounts", &self.remaining_accounts)
+            .field("bumps", &self.bumps)
             .finish()
     }
 }
@@ -35,10 +50,117 @@ impl<'a, 'b, 'c, 'info, T: Accounts<'info>> Context<'a, 'b, 'c, 'info, T> {
             program_id,
             accounts,
             remaining_accounts,
+            event_buffer: None,
+            arena: None,
+            bumps: T::Bumps::default(),
         }
     }
 }
 
+impl<'a, 'b, 'c, 'info, T: Bumps> Context<'a, 'b, 'c, 'info, T> {
+    /// Buffers the events pushed with `events()` in `event_buffer`, which
+    /// the caller logs once the instruction succeeded. Generated instruction
+    /// handlers do so after the accounts are persisted.
+    pub fn with_event_buffer(mut self, event_buffer: &'b EventBuffer<'a>) -> Self {
+        self.event_buffer = Some(event_buffer);
+        self
+    }
+
+    /// Allocates the instruction's temporaries from `arena`, see
+    /// [`arena`](crate::arena). Generated instruction handlers do so for
+    /// programs with `#[program(arena = <bytes>)]`.
+    pub fn with_arena(mut self, arena: &'a Arena) -> Self {
+        self.arena = Some(arena);
+        self
+    }
+
+    /// Sets the bumps found while validating the accounts. Generated
+    /// instruction handlers do so.
+    pub fn with_bumps(mut self, bumps: T::Bumps) -> Self {
+        self.bumps = bumps;
+        self
+    }
+
+    /// The seeds signing for the account `name` of the accounts struct,
+    /// i.e. `seeds` followed by the account's bump, found when validating
+    /// its `seeds` constraint. Fails with `BumpNotFound` if the account has
+    /// no such constraint.
+    ///
+    /// ```ignore
+    /// let seeds = ctx.signer_seeds_for("vault", &[b"vault", user.key.as_ref()])?;
+    /// token::transfer(
+    ///     CpiContext::new_with_signer(program, accounts, &[&seeds]),
+    ///     amount,
+    /// )?;
+    /// ```
+    pub fn signer_seeds_for<'s>(
+        &self,
+        name: &str,
+        seeds: &[&'s [u8]],
+    ) -> Result<SignerSeeds<'s>, ProgramError> {
+        let bump = self.bumps.get(name).ok_or(ErrorCode::BumpNotFound)?;
+        Ok(SignerSeeds::new(seeds, bump))
+    }
+
+    /// The instruction's events, e.g. `ctx.events().push(MyEvent { .. })?`.
+    /// Without an event buffer, they're emitted right away, like `emit!`.
+    pub fn events(&self) -> Events<'b, 'a> {
+        Events::new(self.event_buffer)
+    }
+
+    /// The instruction's arena, to allocate `ArenaVec`s and `ArenaString`s
+    /// from. Without an arena, they're allocated on the heap.
+    pub fn arena(&self) -> Option<&'a Arena> {
+        self.arena
+    }
+
+    /// The current clock, which unit tests can replace, see
+    /// [`clock`](crate::clock).
+    pub fn clock(&self) -> Result<Clock, ProgramError> {
+        crate::clock::get()
+    }
+}
+
+// Each possible bump as a seed, so that seeds can borrow them for any
+// lifetime.
+static BUMP_SEEDS: [[u8; 1]; 256] = {
+    let mut seeds = [[0; 1]; 256];
+    let mut bump = 0;
+    while bump < 256 {
+        seeds[bump][0] = bump as u8;
+        bump += 1;
+    }
+    seeds
+};
+
+/// The seeds of a program derived address followed by its bump, signing for
+/// it in `invoke_signed` and `CpiContext::new_with_signer`. Dereferences to
+/// the seeds, so a single signer is given as `&[&seeds]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignerSeeds<'a> {
+    seeds: Vec<&'a [u8]>,
+}
+
+impl<'a> SignerSeeds<'a> {
+    pub fn new(seeds: &[&'a [u8]], bump: u8) -> Self {
+        let mut seeds = seeds.to_vec();
+        seeds.push(&BUMP_SEEDS[bump as usize]);
+        Self { seeds }
+    }
+
+    pub fn bump(&self) -> u8 {
+        self.seeds[self.seeds.len() - 1][0]
+    }
+}
+
+impl<'a> Deref for SignerSeeds<'a> {
+    type Target = [&'a [u8]];
+
+    fn deref(&self) -> &Self::Target {
+        &self.seeds
+    }
+}
+
 /// Context specifying non-argument inputs for cross-program-invocations.
 pub struct CpiContext<'a, 'b, 'c, 'info, T>
 where
@@ -48,6 +170,8 @@ where
     pub remaining_accounts: Vec<AccountInfo<'info>>,
     pub program: AccountInfo<'info>,
     pub signer_seeds: &'a [&'b [&'c [u8]]],
+    /// Compute units that must remain before invoking the program.
+    min_compute_units: Option<u64>,
 }
 
 impl<'a, 'b, 'c, 'info, T> CpiContext<'a, 'b, 'c, 'info, T>
@@ -60,6 +184,7 @@ where
             program,
             remaining_accounts: Vec::new(),
             signer_seeds: &[],
+            min_compute_units: None,
         }
     }
 
@@ -73,6 +198,7 @@ where
             program,
             signer_seeds,
             remaining_accounts: Vec::new(),
+            min_compute_units: None,
         }
     }
 
@@ -81,10 +207,56 @@ where
         self
     }
 
+    /// Passes the accounts with the signer and writable flags of their
+    /// `AccountInfo`s, which aren't necessarily the ones the callee expects,
+    /// e.g. for an account that is writable in the transaction but only read
+    /// by the caller.
+    #[deprecated(note = "Use `with_extra`, giving each account's role")]
     pub fn with_remaining_accounts(mut self, ra: Vec<AccountInfo<'info>>) -> Self {
         self.remaining_accounts = ra;
         self
     }
+
+    /// Appends an account to the remaining accounts, with the flags of its
+    /// role, e.g.
+    ///
+    /// ```ignore
+    /// let ctx = CpiContext::new(program, accounts)
+    ///     .with_extra(ExtraAccount::Writer(referral))
+    ///     .with_extra(ExtraAccount::Reader(oracle));
+    /// ```
+    pub fn with_extra(mut self, account: ExtraAccount<'info>) -> Self {
+        self.remaining_accounts.push(account.into_account_info());
+        self
+    }
+
+    /// Fails the invocation with `InsufficientComputeUnits`, before the
+    /// callee runs, if fewer than `min_compute_units` remain. This avoids
+    /// running out of compute inside the callee after state has already
+    /// been changed.
+    ///
+    /// Only available with anchor-lang's `remaining-compute-units` feature,
+    /// see [`remaining_compute_units`](crate::compat::remaining_compute_units).
+    /// If the remaining compute units can't be read, e.g. off chain, the
+    /// invocation fails rather than skipping the check.
+    #[cfg(feature = "remaining-compute-units")]
+    pub fn with_compute_budget_check(mut self, min_compute_units: u64) -> Self {
+        self.min_compute_units = Some(min_compute_units);
+        self
+    }
+
+    /// Checks the threshold set by `with_compute_budget_check`. Called by
+    /// CPI clients right before invoking the program.
+    pub fn check_compute_budget(&self) -> ProgramResult {
+        let min_compute_units = match self.min_compute_units {
+            None => return Ok(()),
+            Some(min_compute_units) => min_compute_units,
+        };
+        match crate::compat::remaining_compute_units() {
+            Some(remaining) if remaining >= min_compute_units => Ok(()),
+            _ => Err(ErrorCode::InsufficientComputeUnits.into()),
+        }
+    }
 }
 
 impl<'info, T: ToAccountInfos<'info> + ToAccountMetas> ToAccountInfos<'info>
@@ -103,6 +275,7 @@ impl<'info, T: ToAccountInfos<'info> + ToAccountMetas> ToAccountMetas
 {
     fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
         let mut metas = self.accounts.to_account_metas(is_signer);
+        AccountMetaTemplate::apply(T::metas_template(), &mut metas);
         metas.append(
             &mut self
                 .remaining_accounts
@@ -117,6 +290,37 @@ impl<'info, T: ToAccountInfos<'info> + ToAccountMetas> ToAccountMetas
     }
 }
 
+/// An account passed to a cross-program-invocation in addition to the ones
+/// of its accounts struct, see [`CpiContext::with_extra`]. Its role gives the
+/// flags it's passed with, whatever the flags of the `AccountInfo`.
+#[derive(Clone, Debug)]
+pub enum ExtraAccount<'info> {
+    /// A read-only account.
+    Reader(AccountInfo<'info>),
+    /// A writable account.
+    Writer(AccountInfo<'info>),
+    /// A read-only signer, either signing the transaction or a PDA signing
+    /// with the context's signer seeds.
+    Signer(AccountInfo<'info>),
+    /// A writable signer, e.g. a payer.
+    WritableSigner(AccountInfo<'info>),
+}
+
+impl<'info> ExtraAccount<'info> {
+    /// The account, with the signer and writable flags of its role.
+    pub fn into_account_info(self) -> AccountInfo<'info> {
+        let (mut acc, is_signer, is_writable) = match self {
+            ExtraAccount::Reader(acc) => (acc, false, false),
+            ExtraAccount::Writer(acc) => (acc, false, true),
+            ExtraAccount::Signer(acc) => (acc, true, false),
+            ExtraAccount::WritableSigner(acc) => (acc, true, true),
+        };
+        acc.is_signer = is_signer;
+        acc.is_writable = is_writable;
+        acc
+    }
+}
+
 /// Context specifying non-argument inputs for cross-program-invocations
 /// targeted at program state instructions.
 #[deprecated]
@@ -135,6 +339,7 @@ impl<'a, 'b, 'c, 'info, T: Accounts<'info>> CpiStateContext<'a, 'b, 'c, 'info, T
                 program,
                 signer_seeds: &[],
                 remaining_accounts: Vec::new(),
+                min_compute_units: None,
             },
         }
     }
@@ -152,6 +357,7 @@ impl<'a, 'b, 'c, 'info, T: Accounts<'info>> CpiStateContext<'a, 'b, 'c, 'info, T
                 program,
                 signer_seeds,
                 remaining_accounts: Vec::new(),
+                min_compute_units: None,
             },
         }
     }
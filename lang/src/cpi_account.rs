@@ -1,4 +1,5 @@
 use crate::error::ErrorCode;
+use crate::Result;
 use crate::*;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
@@ -43,6 +44,14 @@ impl<'a, T: AccountDeserialize + Clone> CpiAccount<'a, T> {
     }
 }
 
+#[allow(deprecated)]
+impl<'info, T> Bumps for CpiAccount<'info, T>
+where
+    T: AccountDeserialize + Clone,
+{
+    type Bumps = ();
+}
+
 #[allow(deprecated)]
 impl<'info, T> Accounts<'info> for CpiAccount<'info, T>
 where
@@ -53,7 +62,7 @@ where
         _program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
@@ -116,7 +125,7 @@ impl<'a, T: AccountDeserialize + Clone> DerefMut for CpiAccount<'a, T> {
 
 #[allow(deprecated)]
 impl<'info, T: AccountDeserialize + Clone> AccountsExit<'info> for CpiAccount<'info, T> {
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         // no-op
         Ok(())
     }
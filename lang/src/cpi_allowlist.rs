@@ -0,0 +1,54 @@
+//! Guards against cross-program invocations of programs a program doesn't
+//! expect to call, e.g. an attacker supplied account given in place of the
+//! token program.
+//!
+//! A program can only invoke the programs whose accounts are given to its
+//! instruction. So with `#[program(cpi_allowlist(<program id>, ..))]`, the
+//! generated entrypoint refuses instructions given an executable account
+//! other than the program itself, the system program and the listed
+//! programs, failing with `CpiProgramNotAllowed`. This covers the generated
+//! invocations, e.g. of `init` constraints, as well as the handlers' own.
+//!
+//! The check is stricter than guarding the invocations themselves: since it
+//! runs before the handler, it can't tell which programs will be invoked, so
+//! a program given only to be read, e.g. to check its address or data, is
+//! refused as well unless listed or accepted by the hook.
+//!
+//! Programs allowed at runtime, e.g. by an allowlist account, are accepted by
+//! a [`Hook`] given as `#[program(cpi_allowlist(..), cpi_allowlist_hook =
+//! <path>)]`, which is called for each executable account not allowed by the
+//! static list.
+
+use crate::error::ErrorCode;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+/// Accepts the executable account `program`, given to the instruction with
+/// `accounts`, or fails, usually with `CpiProgramNotAllowed`. The first
+/// argument is the executing program's id.
+pub type Hook = fn(&Pubkey, &[AccountInfo], &AccountInfo) -> ProgramResult;
+
+/// Checks that `accounts` don't include programs other than the executing
+/// program, the system program and the ones in `allowlist` or accepted by
+/// `hook`.
+pub fn check(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    allowlist: &[Pubkey],
+    hook: Option<Hook>,
+) -> ProgramResult {
+    for account in accounts.iter().filter(|account| account.executable) {
+        if account.key == program_id
+            || account.key == &solana_program::system_program::ID
+            || allowlist.contains(account.key)
+        {
+            continue;
+        }
+        match hook {
+            Some(hook) => hook(program_id, accounts, account)?,
+            None => return Err(ErrorCode::CpiProgramNotAllowed.into()),
+        }
+    }
+    Ok(())
+}
@@ -1,12 +1,12 @@
 use crate::error::ErrorCode;
+use crate::Result;
 use crate::{
-    AccountDeserialize, AccountSerialize, Accounts, AccountsExit, Key, ToAccountInfo,
+    AccountDeserialize, AccountSerialize, Accounts, AccountsExit, Bumps, Key, ToAccountInfo,
     ToAccountInfos, ToAccountMetas,
 };
 #[allow(deprecated)]
 use crate::{CpiStateContext, ProgramState};
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
@@ -62,6 +62,14 @@ impl<'info, T: AccountSerialize + AccountDeserialize + Clone> CpiState<'info, T>
     }
 }
 
+#[allow(deprecated)]
+impl<'info, T> Bumps for CpiState<'info, T>
+where
+    T: AccountSerialize + AccountDeserialize + Clone,
+{
+    type Bumps = ();
+}
+
 #[allow(deprecated)]
 impl<'info, T> Accounts<'info> for CpiState<'info, T>
 where
@@ -72,7 +80,7 @@ where
         _program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
@@ -82,7 +90,7 @@ where
         // No owner or address check is done here. One must use the
         // #[account(state = <account-name>)] constraint.
 
-        CpiState::try_from(account)
+        Ok(CpiState::try_from(account)?)
     }
 }
 
@@ -147,7 +155,7 @@ impl<'info, T: AccountSerialize + AccountDeserialize + Clone> DerefMut for CpiSt
 impl<'info, T: AccountSerialize + AccountDeserialize + Clone> AccountsExit<'info>
     for CpiState<'info, T>
 {
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         // no-op
         Ok(())
     }
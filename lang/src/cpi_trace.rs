@@ -0,0 +1,77 @@
+//! Tracing of the cross-program invocations made by generated code, e.g. the
+//! `cpi` module's methods and `init` constraints.
+//!
+//! With anchor-lang's `cpi-trace` feature, each invocation logs the callee
+//! program, the instruction name and its number of accounts before it's made,
+//! and its outcome and the compute units it consumed after, e.g.
+//!
+//! ```text
+//! Program log: CPI 11111111111111111111111111111111 create_account accounts=2
+//! Program log: CPI 11111111111111111111111111111111 create_account ok consumed=1480
+//! ```
+//!
+//! The consumed compute units are only known with the
+//! `remaining-compute-units` feature, see
+//! [`remaining_compute_units`](crate::compat::remaining_compute_units).
+//! Otherwise the remaining units are logged before and after the invocation,
+//! as checkpoints. Without the `cpi-trace` feature these functions are plain
+//! `invoke_signed` calls.
+
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::Instruction;
+
+/// Invokes `ix`, named `name` in the trace.
+pub fn invoke(name: &str, ix: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    invoke_signed(name, ix, account_infos, &[])
+}
+
+/// Invokes `ix` with the given signer seeds, named `name` in the trace.
+#[cfg_attr(not(feature = "cpi-trace"), allow(unused_variables))]
+pub fn invoke_signed(
+    name: &str,
+    ix: &Instruction,
+    account_infos: &[AccountInfo],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    #[cfg(feature = "cpi-trace")]
+    {
+        use crate::compat::remaining_compute_units;
+        use solana_program::log::sol_log_compute_units;
+        use solana_program::msg;
+
+        msg!(
+            "CPI {} {} accounts={}",
+            ix.program_id,
+            name,
+            ix.accounts.len()
+        );
+        let before = remaining_compute_units();
+        if before.is_none() {
+            sol_log_compute_units();
+        }
+        let result = solana_program::program::invoke_signed(ix, account_infos, signers_seeds);
+        let outcome = match &result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => format!("failed: {}", e),
+        };
+        match (before, remaining_compute_units()) {
+            (Some(before), Some(after)) => msg!(
+                "CPI {} {} {} consumed={}",
+                ix.program_id,
+                name,
+                outcome,
+                before.saturating_sub(after)
+            ),
+            _ => {
+                sol_log_compute_units();
+                msg!("CPI {} {} {}", ix.program_id, name, outcome);
+            }
+        }
+        result
+    }
+    #[cfg(not(feature = "cpi-trace"))]
+    {
+        solana_program::program::invoke_signed(ix, account_infos, signers_seeds)
+    }
+}
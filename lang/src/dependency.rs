@@ -0,0 +1,96 @@
+//! Programs a program is built against, declared with `declare_dependency!`.
+//!
+//! Programs built with `#[program(dependencies(<module>, ..))]` get the
+//! `check_dependencies` instruction, failing unless the listed dependencies
+//! are deployed, with their declared IDL hash if any. Deploy tooling invokes
+//! it right after deploying or upgrading the program, so that a program
+//! built against other versions of its dependencies is caught on its first
+//! invocation.
+
+use crate::error::ErrorCode;
+use crate::idl::IdlAccount;
+use crate::AccountDeserialize;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+/// A program that another program was built against, declared with
+/// [`declare_dependency!`](./macro.declare_dependency.html).
+///
+/// Dependencies are emitted to the IDL so that deploy tooling can order
+/// upgrades. They can also be verified at runtime, given the dependency's
+/// program and IDL accounts, e.g. by the `check_dependencies` instruction of
+/// programs listing them in `#[program(dependencies(..))]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dependency {
+    pub name: &'static str,
+    pub program_id: Pubkey,
+    /// SHA256 of the dependency's compressed on-chain IDL, i.e. the `data`
+    /// field of its [`IdlAccount`](./idl/struct.IdlAccount.html).
+    pub idl_hash: Option<[u8; 32]>,
+}
+
+impl Dependency {
+    /// Checks that `program` is the deployed dependency.
+    pub fn check_program(&self, program: &AccountInfo) -> ProgramResult {
+        if program.key != &self.program_id || !program.executable {
+            return Err(ErrorCode::DependencyProgramMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that `idl` is the dependency's IDL account and, if an IDL hash
+    /// was declared, that its contents match it.
+    pub fn check_idl(&self, idl: &AccountInfo) -> ProgramResult {
+        let idl_hash = match self.idl_hash {
+            None => return Ok(()),
+            Some(idl_hash) => idl_hash,
+        };
+        if idl.key != &IdlAccount::address(&self.program_id) || idl.owner != &self.program_id {
+            return Err(ErrorCode::DependencyIdlMismatch.into());
+        }
+        let data = idl.try_borrow_data()?;
+        let idl_account = IdlAccount::try_deserialize(&mut &data[..])
+            .map_err(|_| ErrorCode::DependencyIdlMismatch)?;
+        if hash(&idl_account.data).to_bytes() != idl_hash {
+            return Err(ErrorCode::DependencyIdlMismatch.into());
+        }
+        Ok(())
+    }
+}
+
+/// Checks the deployed `dependencies`, given for each of them, in order, its
+/// program account followed, if it declares an IDL hash, by its IDL account.
+/// This is the `check_dependencies` instruction generated by
+/// `#[program(dependencies(..))]`, which deploy tooling invokes first after
+/// deploying or upgrading the program.
+pub fn check(dependencies: &[Dependency], accounts: &[AccountInfo]) -> ProgramResult {
+    let mut accounts = accounts.iter();
+    for dependency in dependencies {
+        let program = accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        dependency.check_program(program)?;
+        if dependency.idl_hash.is_some() {
+            let idl = accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            dependency.check_idl(idl)?;
+        }
+    }
+    Ok(())
+}
+
+/// The accounts of the `check_dependencies` instruction, see [`check`].
+pub fn account_metas(dependencies: &[Dependency]) -> Vec<AccountMeta> {
+    let mut metas = Vec::new();
+    for dependency in dependencies {
+        metas.push(AccountMeta::new_readonly(dependency.program_id, false));
+        if dependency.idl_hash.is_some() {
+            metas.push(AccountMeta::new_readonly(
+                IdlAccount::address(&dependency.program_id),
+                false,
+            ));
+        }
+    }
+    metas
+}
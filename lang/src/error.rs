@@ -1,7 +1,177 @@
+//! Errors of Anchor programs.
+//!
+//! [`Error`] unifies anchor's own [`ErrorCode`]s, the error codes of a
+//! program's `#[error]` enums, and any other `ProgramError`, so that a
+//! function returning [`Result`] can use `?` on any of them. It converts into
+//! the `ProgramError` the program exits with, and back.
+
 use crate::error;
+use solana_program::program_error::ProgramError;
+use std::fmt;
+
+/// Result of anchor programs, defaulting to anchor's [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error returned by an anchor program.
+///
+/// Errors compare equal when the program would exit with the same error,
+/// e.g. an anchor error code and the `ProgramError::Custom` holding its code.
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// An error code of anchor or of a program's `#[error]` enum.
+    AnchorError(AnchorError),
+    /// Any other error, including error codes whose name isn't known, e.g.
+    /// the ones returned by other programs.
+    ProgramError(ProgramError),
+}
+
+/// A named error code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorError {
+    pub error_name: String,
+    pub error_code_number: u32,
+    pub error_msg: String,
+}
+
+impl Error {
+    /// The code the runtime reports when the program exits with the error,
+    /// e.g. `6000` for the first variant of a program's `#[error]` enum.
+    pub fn error_code(&self) -> u64 {
+        ProgramError::from(self.clone()).into()
+    }
+
+    /// The name of the error, i.e. its variant in its error enum, or in
+    /// `ProgramError` for errors that aren't named anchor error codes.
+    pub fn error_name(&self) -> String {
+        match self {
+            Error::AnchorError(e) => e.error_name.clone(),
+            Error::ProgramError(e) => program_error_name(e).to_string(),
+        }
+    }
+}
+
+// The name of the `ProgramError` variant. Variants added by later versions of
+// solana-program than the one anchor is built against are reported as
+// `ProgramError`.
+#[allow(unreachable_patterns)]
+fn program_error_name(e: &ProgramError) -> &'static str {
+    match e {
+        ProgramError::Custom(_) => "Custom",
+        ProgramError::InvalidArgument => "InvalidArgument",
+        ProgramError::InvalidInstructionData => "InvalidInstructionData",
+        ProgramError::InvalidAccountData => "InvalidAccountData",
+        ProgramError::AccountDataTooSmall => "AccountDataTooSmall",
+        ProgramError::InsufficientFunds => "InsufficientFunds",
+        ProgramError::IncorrectProgramId => "IncorrectProgramId",
+        ProgramError::MissingRequiredSignature => "MissingRequiredSignature",
+        ProgramError::AccountAlreadyInitialized => "AccountAlreadyInitialized",
+        ProgramError::UninitializedAccount => "UninitializedAccount",
+        ProgramError::NotEnoughAccountKeys => "NotEnoughAccountKeys",
+        ProgramError::AccountBorrowFailed => "AccountBorrowFailed",
+        ProgramError::MaxSeedLengthExceeded => "MaxSeedLengthExceeded",
+        ProgramError::InvalidSeeds => "InvalidSeeds",
+        ProgramError::BorshIoError(_) => "BorshIoError",
+        ProgramError::AccountNotRentExempt => "AccountNotRentExempt",
+        ProgramError::UnsupportedSysvar => "UnsupportedSysvar",
+        ProgramError::IllegalOwner => "IllegalOwner",
+        _ => "ProgramError",
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AnchorError(e) => fmt::Display::fmt(&e.error_msg, f),
+            Error::ProgramError(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        ProgramError::from(self.clone()) == ProgramError::from(other.clone())
+    }
+}
+
+impl Eq for Error {}
+
+impl From<ProgramError> for Error {
+    fn from(e: ProgramError) -> Self {
+        Error::ProgramError(e)
+    }
+}
+
+impl From<Error> for ProgramError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::AnchorError(e) => ProgramError::Custom(e.error_code_number),
+            Error::ProgramError(e) => e,
+        }
+    }
+}
+
+/// The codes of an `#[error]` enum, for clients to decode the codes of failed
+/// transactions with, e.g. `anchor_client::RequestBuilder::errors`.
+pub trait ErrorCodes {
+    /// The code, name and message of each variant. The messages of variants
+    /// with fields are their `#[msg]` format strings.
+    const ERROR_CODES: &'static [(u32, &'static str, &'static str)];
+
+    /// The codes of the enum followed by the ones of the enums it extends
+    /// with `#[error(extends(..))]`, which the program returns as well.
+    fn error_tables() -> Vec<&'static [(u32, &'static str, &'static str)]> {
+        vec![Self::ERROR_CODES]
+    }
+}
+
+/// Converts an `Option` or `Result` into a typed error, instead of unwrapping
+/// it into an opaque panic.
+///
+/// ```ignore
+/// let config = maybe_config.or_err(ErrorCode::MissingConfig)?;
+/// ```
+///
+/// On failure, the location of the call is logged, and for a `Result`, the
+/// original error is dropped.
+pub trait OrErr<T> {
+    /// The value, or `error` if there's none.
+    fn or_err<E>(self, error: E) -> std::result::Result<T, E>;
+}
+
+impl<T> OrErr<T> for Option<T> {
+    #[track_caller]
+    fn or_err<E>(self, error: E) -> std::result::Result<T, E> {
+        match self {
+            Some(value) => Ok(value),
+            None => {
+                log_or_err(std::panic::Location::caller());
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<T, F> OrErr<T> for std::result::Result<T, F> {
+    #[track_caller]
+    fn or_err<E>(self, error: E) -> std::result::Result<T, E> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(_) => {
+                log_or_err(std::panic::Location::caller());
+                Err(error)
+            }
+        }
+    }
+}
+
+fn log_or_err(location: &std::panic::Location) {
+    solana_program::msg!("or_err failed at {}:{}", location.file(), location.line());
+}
 
 // Error codes that can be returned by internal framework code.
-#[error(offset = 0)]
+#[error(offset = 0, codes_only)]
 pub enum ErrorCode {
     // Instructions.
     #[msg("8 byte instruction identifier not provided")]
@@ -12,6 +182,12 @@ pub enum ErrorCode {
     InstructionDidNotDeserialize,
     #[msg("The program could not serialize the given instruction")]
     InstructionDidNotSerialize,
+    #[msg("The instruction can only be executed in simulation")]
+    InstructionSimulationOnly,
+    #[msg("The deprecated instruction is past its sunset slot")]
+    InstructionSunset,
+    #[msg("The instruction data is longer than the instruction's arguments")]
+    InstructionDataTooLong,
 
     // IDL instructions.
     #[msg("The program was compiled without idl instructions")]
@@ -61,6 +237,26 @@ pub enum ErrorCode {
     ConstraintMintDecimals,
     #[msg("A space constraint was violated")]
     ConstraintSpace,
+    #[msg("The account doesn't hold enough lamports to pay for its own initialization")]
+    ConstraintSelfFunded,
+    #[msg("The price feed hasn't been updated within its max age")]
+    ConstraintPriceFeedStale,
+    #[msg("The program was upgraded in the current slot")]
+    ConstraintProgramStable,
+    #[msg("The action isn't queued on the timelock")]
+    ConstraintTimelock,
+    #[msg("The number of remaining accounts is out of the declared bounds")]
+    ConstraintRemainingAccountsCount,
+    #[msg("A remaining account isn't owned by the declared owner")]
+    ConstraintRemainingAccountsOwner,
+    #[msg("A remaining account isn't writable")]
+    ConstraintRemainingAccountsMut,
+    #[msg("The account wasn't assigned to the expected owner")]
+    ConstraintAssignTo,
+    #[msg("The instruction's active window hasn't started yet")]
+    ConstraintActiveWindowNotStarted,
+    #[msg("The instruction's active window has ended")]
+    ConstraintActiveWindowEnded,
 
     // Accounts.
     #[msg("The account discriminator was already set on this account")]
@@ -91,11 +287,69 @@ pub enum ErrorCode {
     AccountNotInitialized,
     #[msg("The given account is not a program data account")]
     AccountNotProgramData,
+    #[msg("The account data is mutably borrowed and cannot be loaded")]
+    AccountBorrowedMutably,
+    #[msg("The account data is already loaded and cannot be borrowed mutably")]
+    AccountAlreadyLoaded,
+    #[msg("The given program is not the declared dependency")]
+    DependencyProgramMismatch,
+    #[msg("The dependency's IDL doesn't match the declared IDL hash")]
+    DependencyIdlMismatch,
+    #[msg("The given account is not owned by the native loader")]
+    AccountNotNativeOwned,
+    #[msg("The given account is not owned by the sysvar program")]
+    AccountNotSysvarOwned,
+    #[msg("The account data must be zeroed before its owner is changed")]
+    AccountNotZeroed,
+    #[msg("The account data can't grow by more than 10 KiB per instruction")]
+    AccountReallocTooLarge,
+    #[msg("An invariant of the account doesn't hold")]
+    AccountInvariantViolated,
 
     // State.
     #[msg("The given state account does not have the correct address")]
     StateInvalidAddress = 4000,
 
+    // Miscellaneous.
+    #[msg("Not enough compute units remain for the cross-program invocation")]
+    InsufficientComputeUnits = 4100,
+    #[msg("The program panicked")]
+    ProgramPanicked,
+    #[msg("An arithmetic operation overflowed")]
+    MathOverflow,
+    #[msg("The instruction was already executed with this nonce")]
+    InstructionAlreadyExecuted,
+    #[msg("The replay account hasn't expired yet")]
+    ReplayNotExpired,
+    #[msg("The replay account was funded by a different payer")]
+    ReplayPayerMismatch,
+    #[msg("The program isn't allowed to be invoked")]
+    CpiProgramNotAllowed,
+    #[msg("The timelock's delay hasn't elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("The timelock already has a queued action")]
+    TimelockActionPending,
+    #[msg("The string doesn't fit in the fixed string")]
+    FixedStrTooLong,
+    #[msg("The fixed string isn't valid UTF-8")]
+    FixedStrInvalidUtf8,
+    #[msg("The program isn't a registered implementation of the interface")]
+    InterfaceNotRegistered,
+    #[msg("No bump was found for the account")]
+    BumpNotFound,
+    #[msg("The account isn't the paged list's page")]
+    PagedVecWrongPage,
+    #[msg("The index is out of the paged list's bounds")]
+    PagedVecIndexOutOfBounds,
+    #[msg("The signer isn't the program's upgrade authority")]
+    SchemaAuthorityMismatch,
+    #[msg("The instruction's arena is exhausted")]
+    ArenaExhausted,
+    #[msg("The paged list's pages can't hold any item")]
+    PagedVecZeroCapacity,
+    #[msg("The token amount isn't a non-negative decimal number")]
+    InvalidTokenAmount,
+
     // Used for APIs that shouldn't be used anymore.
     #[msg("The API being used is deprecated and should no longer be used")]
     Deprecated = 5000,
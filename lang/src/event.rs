@@ -0,0 +1,157 @@
+//! Logging of the events emitted with `emit!`.
+//!
+//! Events are logged as the base64 encoded discriminator and borsh
+//! serialized event, following a `Program log: ` prefix. With the
+//! `event-log-data` feature, which enables `solana-1-9` since `sol_log_data`
+//! was added in solana-program 1.9, they're instead logged as raw data,
+//! following a `Program data: ` prefix, saving the encoding. Events declared with
+//! `#[event(anonymous)]` are logged without their discriminator.
+//!
+//! Events pushed with `ctx.events().push(..)` are instead buffered by the
+//! instruction's [`EventBuffer`], and logged together once the instruction
+//! succeeded, see [`EventBuffer::flush`].
+
+use crate::arena::{Arena, ArenaVec};
+use crate::error::ErrorCode;
+use crate::Event;
+use solana_program::program_error::ProgramError;
+use std::cell::RefCell;
+use std::io::Write;
+
+// Events up to this size, including their discriminator, are serialized
+// without allocating.
+const STACK_BUFFER_LEN: usize = 512;
+
+// Length of the base64 encoding of a full stack buffer.
+const ENCODED_BUFFER_LEN: usize = STACK_BUFFER_LEN / 3 * 4 + 4;
+
+/// Logs the discriminator, unless the event is anonymous, and serialized
+/// `event`.
+#[inline(never)]
+pub fn emit<E: Event>(event: &E) {
+    let mut buf = [0u8; STACK_BUFFER_LEN];
+    let mut writer: &mut [u8] = &mut buf;
+    let prefix: &[u8] = match E::ANONYMOUS {
+        true => &[],
+        false => &E::discriminator(),
+    };
+    let serialized = writer
+        .write_all(prefix)
+        .and_then(|_| event.serialize(&mut writer))
+        .is_ok();
+    match serialized {
+        true => {
+            let len = STACK_BUFFER_LEN - writer.len();
+            log_data(&buf[..len]);
+        }
+        false => log_data(&event.data()),
+    }
+}
+
+#[cfg(feature = "event-log-data")]
+fn log_data(data: &[u8]) {
+    solana_program::log::sol_log_data(&[data]);
+}
+
+#[cfg(not(feature = "event-log-data"))]
+fn log_data(data: &[u8]) {
+    if data.len() > STACK_BUFFER_LEN {
+        return solana_program::log::sol_log(&base64::encode(data));
+    }
+    let mut encoded = [0u8; ENCODED_BUFFER_LEN];
+    let len = base64::encode_config_slice(data, base64::STANDARD, &mut encoded);
+    // Base64 is always ASCII.
+    solana_program::log::sol_log(std::str::from_utf8(&encoded[..len]).unwrap());
+}
+
+/// Events buffered by an instruction, logged together once it succeeded, so
+/// that the events of a failed instruction are never logged. The buffer is
+/// allocated from the instruction's arena, if any, see
+/// [`arena`](crate::arena).
+#[derive(Debug)]
+pub struct EventBuffer<'a> {
+    data: RefCell<ArenaVec<'a, u8>>,
+}
+
+impl<'a> EventBuffer<'a> {
+    /// Prefix of the log of buffered events, the first 8 bytes of
+    /// `sha256("anchor:event_batch")`. It's followed by each event's
+    /// discriminator and serialized data, prefixed by their `u32` length.
+    pub const DISCRIMINATOR: [u8; 8] = [228, 185, 162, 196, 63, 106, 83, 153];
+
+    pub fn new() -> Self {
+        Self::new_in(None)
+    }
+
+    /// A buffer allocated from `arena`, or from the heap if `None`.
+    pub fn new_in(arena: Option<&'a Arena>) -> Self {
+        Self {
+            data: RefCell::new(ArenaVec::new_in(arena)),
+        }
+    }
+
+    /// Buffers `event`, failing with `ArenaExhausted` once the arena is
+    /// full.
+    pub fn push<E: Event>(&self, event: &E) -> Result<(), ProgramError> {
+        let mut data = self.data.borrow_mut();
+        if data.is_empty() {
+            data.try_extend_from_slice(&Self::DISCRIMINATOR)?;
+        }
+        let start = data.len();
+        data.try_extend_from_slice(&[0; 4])?;
+        if !E::ANONYMOUS {
+            data.try_extend_from_slice(&E::discriminator())?;
+        }
+        event
+            .serialize(&mut *data)
+            .map_err(|_| ErrorCode::ArenaExhausted)?;
+        let len = (data.len() - start - 4) as u32;
+        data[start..start + 4].copy_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Logs the buffered events, if any, as a single log, and clears the
+    /// buffer.
+    pub fn flush(&self) {
+        let mut data = self.data.borrow_mut();
+        if !data.is_empty() {
+            log_data(&data);
+        }
+        data.clear();
+    }
+}
+
+impl<'a> Default for EventBuffer<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Emits the events of an instruction handler, see `Context::events`.
+#[derive(Debug, Clone, Copy)]
+pub struct Events<'a, 'e> {
+    buffer: Option<&'a EventBuffer<'e>>,
+}
+
+impl<'a, 'e> Events<'a, 'e> {
+    pub(crate) fn new(buffer: Option<&'a EventBuffer<'e>>) -> Self {
+        Self { buffer }
+    }
+
+    /// Buffers `event` until the instruction succeeded, or emits it right
+    /// away if the context has no buffer. Fails with `ArenaExhausted` once
+    /// the instruction's arena is full.
+    pub fn push<E: Event>(&self, event: E) -> Result<(), ProgramError> {
+        match self.buffer {
+            Some(buffer) => buffer.push(&event),
+            None => {
+                emit(&event);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Strings stored in a fixed number of bytes.
+//!
+//! A [`FixedStr<N>`] holds up to `N` bytes of UTF-8, padded with zeroes. It's
+//! `Pod`, so it can be used in zero-copy accounts, and it's borsh encoded as
+//! its `N` bytes, without a length prefix. In the IDL it's a
+//! `{ "fixedString": N }`, which clients decode as a string.
+
+use crate::error::ErrorCode;
+use crate::{AnchorDeserialize, AnchorSerialize};
+use solana_program::program_error::ProgramError;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Write};
+
+/// A string of up to `N` bytes of UTF-8, padded with zeroes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct FixedStr<const N: usize>([u8; N]);
+
+impl<const N: usize> FixedStr<N> {
+    /// The string `s`, which must fit in `N` bytes.
+    pub fn new(s: &str) -> Result<Self, ProgramError> {
+        let mut fixed = Self::default();
+        fixed.set(s)?;
+        Ok(fixed)
+    }
+
+    /// The string of the UTF-8 `bytes`, up to their first zero, which must
+    /// fit in `N` bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        let s = std::str::from_utf8(&bytes[..len]).map_err(|_| ErrorCode::FixedStrInvalidUtf8)?;
+        Self::new(s)
+    }
+
+    /// Replaces the string with `s`, which must fit in `N` bytes.
+    pub fn set(&mut self, s: &str) -> Result<(), ProgramError> {
+        if s.len() > N {
+            return Err(ErrorCode::FixedStrTooLong.into());
+        }
+        if s.as_bytes().contains(&0) {
+            return Err(ErrorCode::FixedStrInvalidUtf8.into());
+        }
+        self.0 = [0; N];
+        self.0[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(())
+    }
+
+    /// The string. Bytes that aren't valid UTF-8, which can only be written
+    /// to the account data directly, end the string.
+    pub fn as_str(&self) -> &str {
+        let bytes = self.as_bytes();
+        match std::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap(),
+        }
+    }
+
+    /// The bytes of the string, without the padding.
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = self.0.iter().position(|b| *b == 0).unwrap_or(N);
+        &self.0[..len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The maximum length of the string, in bytes.
+    pub const fn capacity() -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Default for FixedStr<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for FixedStr<N> {
+    type Error = ProgramError;
+
+    fn try_from(s: &str) -> Result<Self, ProgramError> {
+        Self::new(s)
+    }
+}
+
+impl<const N: usize> AsRef<str> for FixedStr<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq<str> for FixedStr<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for FixedStr<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+// Any bytes are a valid `FixedStr`, since invalid UTF-8 is never read.
+unsafe impl<const N: usize> bytemuck::Zeroable for FixedStr<N> {}
+unsafe impl<const N: usize> bytemuck::Pod for FixedStr<N> {}
+
+impl<const N: usize> AnchorSerialize for FixedStr<N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.0)
+    }
+}
+
+impl<const N: usize> AnchorDeserialize for FixedStr<N> {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        if buf.len() < N {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let (bytes, rest) = buf.split_at(N);
+        let fixed = Self::from_bytes(bytes)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid fixed string"))?;
+        *buf = rest;
+        Ok(fixed)
+    }
+}
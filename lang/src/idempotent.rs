@@ -0,0 +1,155 @@
+//! Replay protection for instructions marked `#[idempotent]`.
+//!
+//! An idempotent instruction takes an extra `nonce: [u8; 16]` arg, chosen by
+//! the client, e.g. a random UUID per logical payment. The first execution
+//! creates a replay account at the program address derived from
+//! `[REPLAY_SEED, nonce]`, and any later execution with the same nonce fails
+//! with `InstructionAlreadyExecuted`. Since the replay account is created in
+//! the same transaction as the instruction's effects, both are committed or
+//! reverted together, so a client can safely retry with the same nonce until
+//! the instruction is confirmed.
+//!
+//! The replay account, the payer funding it and the system program follow the
+//! instruction's accounts, see [`replay_account_metas`]. Once expired, the
+//! replay account can be closed by anyone with the generated `close_replay`
+//! instruction, refunding its rent to the payer, see
+//! [`close_replay_account_metas`]. A nonce can be executed again after its
+//! replay account is closed, so clients must not retry past the expiry.
+
+use crate::error::ErrorCode;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::AccountMeta;
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+
+/// Seed prefix of replay account addresses.
+pub const REPLAY_SEED: &[u8] = b"anchor:replay";
+
+/// Seconds after which a replay account can be closed, unless the
+/// instruction sets its own with `#[idempotent(expiry = <seconds>)]`.
+pub const DEFAULT_EXPIRY: i64 = 24 * 60 * 60;
+
+// The payer to refund followed by the unix timestamp from which the account
+// can be closed.
+const REPLAY_ACCOUNT_LEN: usize = 32 + 8;
+
+/// Returns the replay account address for `nonce` and its bump.
+pub fn replay_address(program_id: &Pubkey, nonce: &[u8; 16]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REPLAY_SEED, nonce], program_id)
+}
+
+/// Accounts to append to an idempotent instruction's accounts.
+pub fn replay_account_metas(
+    program_id: &Pubkey,
+    nonce: &[u8; 16],
+    payer: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(replay_address(program_id, nonce).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+    ]
+}
+
+/// Accounts of the `close_replay` instruction, where `payer` is the account
+/// that funded the replay account.
+pub fn close_replay_account_metas(
+    program_id: &Pubkey,
+    nonce: &[u8; 16],
+    payer: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(replay_address(program_id, nonce).0, false),
+        AccountMeta::new(*payer, false),
+    ]
+}
+
+/// Creates the replay account for `nonce`, failing if it already exists.
+/// Takes the replay account, payer and system program off the front of
+/// `accounts`.
+#[doc(hidden)]
+pub fn record<'info>(
+    program_id: &Pubkey,
+    nonce: &[u8; 16],
+    expiry: i64,
+    accounts: &mut &[AccountInfo<'info>],
+) -> ProgramResult {
+    if accounts.len() < 3 {
+        return Err(ErrorCode::AccountNotEnoughKeys.into());
+    }
+    let replay = &accounts[0];
+    let payer = &accounts[1];
+    let system_program = &accounts[2];
+    *accounts = &accounts[3..];
+
+    let (address, bump) = replay_address(program_id, nonce);
+    if replay.key != &address {
+        return Err(ErrorCode::ConstraintSeeds.into());
+    }
+    if replay.owner == program_id {
+        return Err(ErrorCode::InstructionAlreadyExecuted.into());
+    }
+
+    let seeds: &[&[u8]] = &[REPLAY_SEED, nonce, &[bump]];
+    crate::common::create_pda(
+        program_id,
+        replay,
+        payer,
+        system_program,
+        REPLAY_ACCOUNT_LEN,
+        seeds,
+    )?;
+
+    let expires_at = crate::clock::get()?
+        .unix_timestamp
+        .checked_add(expiry)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let mut data = replay.try_borrow_mut_data()?;
+    data[..32].copy_from_slice(payer.key.as_ref());
+    data[32..REPLAY_ACCOUNT_LEN].copy_from_slice(&expires_at.to_le_bytes());
+    Ok(())
+}
+
+/// Handler of the `close_replay` instruction, closing the expired replay
+/// account for the nonce in `ix_data` and refunding its payer.
+#[doc(hidden)]
+pub fn close_replay(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    ix_data: &[u8],
+) -> ProgramResult {
+    let nonce: [u8; 16] = ix_data
+        .get(..16)
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or(ErrorCode::InstructionDidNotDeserialize)?;
+    let (replay, payer) = match accounts {
+        [replay, payer, ..] => (replay, payer),
+        _ => return Err(ErrorCode::AccountNotEnoughKeys.into()),
+    };
+    if replay.key != &replay_address(program_id, &nonce).0 {
+        return Err(ErrorCode::ConstraintSeeds.into());
+    }
+    if replay.owner != program_id {
+        return Err(ErrorCode::AccountNotProgramOwned.into());
+    }
+    if !replay.is_writable || !payer.is_writable {
+        return Err(ErrorCode::ConstraintMut.into());
+    }
+
+    let expires_at = {
+        let data = replay.try_borrow_data()?;
+        if data.len() < REPLAY_ACCOUNT_LEN {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        if &data[..32] != payer.key.as_ref() {
+            return Err(ErrorCode::ReplayPayerMismatch.into());
+        }
+        i64::from_le_bytes(data[32..REPLAY_ACCOUNT_LEN].try_into().unwrap())
+    };
+    if crate::clock::get()?.unix_timestamp < expires_at {
+        return Err(ErrorCode::ReplayNotExpired.into());
+    }
+
+    crate::common::close(replay.clone(), payer.clone())
+}
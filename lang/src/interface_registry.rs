@@ -0,0 +1,286 @@
+//! Registries of the programs implementing an `#[interface]`, so that
+//! callers can discover and validate implementations, e.g. the adapters an
+//! admin approved, instead of hardcoding their program ids.
+//!
+//! A registration is a program account at the address derived from
+//! `[INTERFACE_REGISTRY_SEED, interface, authority, implementation]`, where
+//! `interface` is the [`interface_hash`] of the trait's name, so each
+//! authority keeps its own list of implementations. Programs built with
+//! `#[program(interface_registry)]` get two instructions managing them:
+//! `interface_register`, see [`register_account_metas`], and
+//! `interface_unregister`, see [`unregister_account_metas`]. Registrations
+//! are paid for by their authority, which gets the rent back when
+//! unregistering.
+//!
+//! Interfaces declared with `#[interface(registry)]` get a `registry`
+//! module with the interface's hash and typed helpers, e.g. for `Auth`
+//!
+//! ```ignore
+//! auth::registry::verify(
+//!     &crate::ID,
+//!     &ctx.accounts.registration,
+//!     &config.admin,
+//!     ctx.accounts.auth_program.key,
+//! )?;
+//! ```
+//!
+//! which only passes if the admin registered the auth program. Clients find
+//! the registered implementations by fetching the registry program's
+//! [`InterfaceRegistration`] accounts.
+
+use crate::error::ErrorCode;
+use crate::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+
+/// Seed prefix of registration addresses.
+pub const INTERFACE_REGISTRY_SEED: &[u8] = b"anchor:interface";
+
+/// Length of a registration account, its discriminator followed by the
+/// borsh encoded [`InterfaceRegistration`].
+pub const REGISTRATION_ACCOUNT_LEN: usize = 8 + 8 + 32 + 32;
+
+/// The registration of an implementation of an interface by an authority.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceRegistration {
+    /// The [`interface_hash`] of the interface.
+    pub interface: [u8; 8],
+    pub authority: Pubkey,
+    /// The program implementing the interface.
+    pub implementation: Pubkey,
+}
+
+impl Discriminator for InterfaceRegistration {
+    fn discriminator() -> [u8; 8] {
+        hash(b"account:InterfaceRegistration").to_bytes()[..8]
+            .try_into()
+            .unwrap()
+    }
+}
+
+impl AccountDeserialize for InterfaceRegistration {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        if buf.len() < 8 {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        }
+        if buf[..8] != Self::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        let mut data = buf.get(8..).unwrap_or_default();
+        AnchorDeserialize::deserialize(&mut data)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+impl InterfaceRegistration {
+    /// Decodes the registration account `info` of the registry program
+    /// `program_id`.
+    pub fn try_from_account(program_id: &Pubkey, info: &AccountInfo) -> Result<Self, ProgramError> {
+        if info.owner != program_id {
+            return Err(ErrorCode::AccountNotProgramOwned.into());
+        }
+        Self::try_deserialize(&mut &info.try_borrow_data()?[..])
+    }
+}
+
+/// The hash identifying the interface declared by the trait named `name`,
+/// i.e. `Sha256("interface:<name>")[..8]`.
+pub fn interface_hash(name: &str) -> [u8; 8] {
+    hash(format!("interface:{}", name).as_bytes()).to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// Returns the address of the registration of `implementation` by
+/// `authority` in the registry program `program_id`, and its bump.
+pub fn registration_address(
+    program_id: &Pubkey,
+    interface: &[u8; 8],
+    authority: &Pubkey,
+    implementation: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            INTERFACE_REGISTRY_SEED,
+            interface,
+            authority.as_ref(),
+            implementation.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Instruction data of the `interface_register` and `interface_unregister`
+/// instructions, following their sighash.
+pub fn instruction_args(interface: &[u8; 8], implementation: &Pubkey) -> Vec<u8> {
+    [&interface[..], implementation.as_ref()].concat()
+}
+
+/// Accounts of the `interface_register` instruction.
+pub fn register_account_metas(
+    program_id: &Pubkey,
+    interface: &[u8; 8],
+    authority: &Pubkey,
+    implementation: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(
+            registration_address(program_id, interface, authority, implementation).0,
+            false,
+        ),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+    ]
+}
+
+/// Accounts of the `interface_unregister` instruction.
+pub fn unregister_account_metas(
+    program_id: &Pubkey,
+    interface: &[u8; 8],
+    authority: &Pubkey,
+    implementation: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(
+            registration_address(program_id, interface, authority, implementation).0,
+            false,
+        ),
+        AccountMeta::new(*authority, true),
+    ]
+}
+
+/// Checks that `registration` is the registry program's registration of
+/// `implementation` by `authority`.
+pub fn verify(
+    program_id: &Pubkey,
+    registration: &AccountInfo,
+    interface: &[u8; 8],
+    authority: &Pubkey,
+    implementation: &Pubkey,
+) -> ProgramResult {
+    let (address, _) = registration_address(program_id, interface, authority, implementation);
+    if registration.key != &address {
+        return Err(ErrorCode::InterfaceNotRegistered.into());
+    }
+    let state = InterfaceRegistration::try_from_account(program_id, registration)
+        .map_err(|_| ErrorCode::InterfaceNotRegistered)?;
+    if &state.interface != interface
+        || &state.authority != authority
+        || &state.implementation != implementation
+    {
+        return Err(ErrorCode::InterfaceNotRegistered.into());
+    }
+    Ok(())
+}
+
+// Decodes the instruction data of the registry's instructions.
+fn parse_args(ix_data: &[u8]) -> Result<([u8; 8], Pubkey), ProgramError> {
+    if ix_data.len() < 40 {
+        return Err(ErrorCode::InstructionDidNotDeserialize.into());
+    }
+    Ok((
+        ix_data[..8].try_into().unwrap(),
+        Pubkey::new_from_array(ix_data[8..40].try_into().unwrap()),
+    ))
+}
+
+// Checks that `registration` is the authority's registration of the
+// implementation, and that the authority signed.
+fn check_authority(
+    program_id: &Pubkey,
+    registration: &AccountInfo,
+    authority: &AccountInfo,
+    interface: &[u8; 8],
+    implementation: &Pubkey,
+) -> Result<u8, ProgramError> {
+    if !authority.is_signer {
+        return Err(ErrorCode::ConstraintSigner.into());
+    }
+    if !registration.is_writable {
+        return Err(ErrorCode::ConstraintMut.into());
+    }
+    let (address, bump) =
+        registration_address(program_id, interface, authority.key, implementation);
+    if registration.key != &address {
+        return Err(ErrorCode::ConstraintSeeds.into());
+    }
+    Ok(bump)
+}
+
+/// Handler of the `interface_register` instruction, registering the
+/// implementation of the interface given in `ix_data` by the signing
+/// authority. Registering an implementation again has no effect.
+#[doc(hidden)]
+pub fn register(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+    let (interface, implementation) = parse_args(ix_data)?;
+    let (registration, authority, system_program) = match accounts {
+        [registration, authority, system_program, ..] => (registration, authority, system_program),
+        _ => return Err(ErrorCode::AccountNotEnoughKeys.into()),
+    };
+    let bump = check_authority(
+        program_id,
+        registration,
+        authority,
+        &interface,
+        &implementation,
+    )?;
+    if registration.owner != program_id {
+        let seeds: &[&[u8]] = &[
+            INTERFACE_REGISTRY_SEED,
+            &interface,
+            authority.key.as_ref(),
+            implementation.as_ref(),
+            &[bump],
+        ];
+        crate::common::create_pda(
+            program_id,
+            registration,
+            authority,
+            system_program,
+            REGISTRATION_ACCOUNT_LEN,
+            seeds,
+        )?;
+    }
+
+    let mut data = registration.try_borrow_mut_data()?;
+    data[..8].copy_from_slice(&InterfaceRegistration::discriminator());
+    let state = InterfaceRegistration {
+        interface,
+        authority: *authority.key,
+        implementation,
+    };
+    state
+        .serialize(&mut &mut data[8..])
+        .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+    Ok(())
+}
+
+/// Handler of the `interface_unregister` instruction, closing the signing
+/// authority's registration of the implementation given in `ix_data`.
+#[doc(hidden)]
+pub fn unregister(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+    let (interface, implementation) = parse_args(ix_data)?;
+    let (registration, authority) = match accounts {
+        [registration, authority, ..] => (registration, authority),
+        _ => return Err(ErrorCode::AccountNotEnoughKeys.into()),
+    };
+    check_authority(
+        program_id,
+        registration,
+        authority,
+        &interface,
+        &implementation,
+    )?;
+    InterfaceRegistration::try_from_account(program_id, registration)
+        .map_err(|_| ErrorCode::InterfaceNotRegistered)?;
+    crate::common::close(registration.clone(), authority.clone())
+}
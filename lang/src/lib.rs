@@ -25,66 +25,101 @@ extern crate self as anchor_lang;
 
 use bytemuck::{Pod, Zeroable};
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use std::io::Write;
 
 mod account;
+pub mod account_field;
 mod account_info;
 mod account_meta;
+pub mod arena;
 mod boxed;
 mod bpf_upgradeable_state;
+pub mod clock;
 mod common;
+pub mod compat;
+#[cfg(feature = "compression")]
+pub mod compression;
 mod context;
 mod cpi_account;
+pub mod cpi_allowlist;
 mod cpi_state;
+pub mod cpi_trace;
 mod ctor;
-mod error;
+pub mod dependency;
+pub mod error;
+mod event;
+mod fixed_str;
+pub mod idempotent;
 #[doc(hidden)]
 pub mod idl;
+pub mod interface_registry;
 mod loader;
 mod loader_account;
+pub mod math;
+mod native_account;
+pub mod paged_vec;
+pub mod panic;
 mod program;
 mod program_account;
+pub mod rent;
+#[cfg(feature = "router")]
+pub mod router;
+pub mod schema_registry;
+pub mod seeds;
 mod signer;
+mod snapshot;
 pub mod state;
 mod system_account;
 mod system_program;
 mod sysvar;
+mod sysvar_account;
+pub mod timelock;
 mod unchecked_account;
 mod vec;
 
 pub use crate::account::Account;
+pub use crate::account_field::AccountField;
 pub use crate::bpf_upgradeable_state::*;
 #[doc(hidden)]
 #[allow(deprecated)]
 pub use crate::context::CpiStateContext;
-pub use crate::context::{Context, CpiContext};
+pub use crate::context::{Context, CpiContext, ExtraAccount, SignerSeeds};
 #[doc(hidden)]
 #[allow(deprecated)]
 pub use crate::cpi_account::CpiAccount;
 #[doc(hidden)]
 #[allow(deprecated)]
 pub use crate::cpi_state::CpiState;
+pub use crate::dependency::Dependency;
+pub use crate::error::{OrErr, Result};
+pub use crate::event::{EventBuffer, Events};
+pub use crate::fixed_str::FixedStr;
 #[allow(deprecated)]
 pub use crate::loader::Loader;
 pub use crate::loader_account::AccountLoader;
+pub use crate::native_account::NativeAccount;
 pub use crate::program::Program;
 #[doc(hidden)]
 #[allow(deprecated)]
 pub use crate::program_account::ProgramAccount;
+pub use crate::rent::RentExemption;
 pub use crate::signer::Signer;
+pub use crate::snapshot::AccountSnapshot;
 #[doc(hidden)]
 #[allow(deprecated)]
 pub use crate::state::ProgramState;
 pub use crate::system_account::SystemAccount;
 pub use crate::system_program::System;
 pub use crate::sysvar::Sysvar;
-pub use crate::unchecked_account::UncheckedAccount;
+pub use crate::sysvar_account::SysvarAccount;
+pub use crate::unchecked_account::{UncheckedAccount, UncheckedLamports};
 pub use anchor_attribute_access_control::access_control;
-pub use anchor_attribute_account::{account, declare_id, zero_copy};
+pub use anchor_attribute_account::{
+    account, account_field, declare_dependency, declare_id, flags, idl, zero_copy,
+};
 pub use anchor_attribute_constant::constant;
 pub use anchor_attribute_error::error;
 pub use anchor_attribute_event::{emit, event};
@@ -102,7 +137,7 @@ pub use solana_program;
 /// maintain any invariants required for the program to run securely. In most
 /// cases, it's recommended to use the [`Accounts`](./derive.Accounts.html)
 /// derive macro to implement this trait.
-pub trait Accounts<'info>: ToAccountMetas + ToAccountInfos<'info> + Sized {
+pub trait Accounts<'info>: ToAccountMetas + ToAccountInfos<'info> + Bumps + Sized {
     /// Returns the validated accounts struct. What constitutes "valid" is
     /// program dependent. However, users of these types should never have to
     /// worry about account substitution attacks. For example, if a program
@@ -118,20 +153,76 @@ pub trait Accounts<'info>: ToAccountMetas + ToAccountInfos<'info> + Sized {
         program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         ix_data: &[u8],
-    ) -> Result<Self, ProgramError>;
+    ) -> Result<Self>;
+
+    /// Same as [`try_accounts`](Self::try_accounts), also recording in
+    /// `bumps` the bump of each account checked with a `seeds` constraint.
+    fn try_accounts_with_bumps(
+        program_id: &Pubkey,
+        accounts: &mut &[AccountInfo<'info>],
+        ix_data: &[u8],
+        bumps: &mut Self::Bumps,
+    ) -> Result<Self> {
+        let _ = bumps;
+        Self::try_accounts(program_id, accounts, ix_data)
+    }
+
+    /// Same as [`try_accounts_with_bumps`](Self::try_accounts_with_bumps),
+    /// for the instruction identified by `sighash`, if known. The `timelock`
+    /// constraint binds queued actions to it. Generated instruction handlers
+    /// validate their accounts with it.
+    fn try_accounts_for_instruction(
+        program_id: &Pubkey,
+        accounts: &mut &[AccountInfo<'info>],
+        sighash: Option<&[u8; 8]>,
+        ix_data: &[u8],
+        bumps: &mut Self::Bumps,
+    ) -> Result<Self> {
+        let _ = sighash;
+        Self::try_accounts_with_bumps(program_id, accounts, ix_data, bumps)
+    }
+}
+
+/// The bumps of an accounts struct. The [`Accounts`](./derive.Accounts.html)
+/// derive macro generates a `<Struct>Bumps` struct, with a `u8` field for each
+/// account checked with a `seeds` constraint, an `Option<u8>` if the check is
+/// gated by `when`, and a field for each nested accounts struct, holding its
+/// bumps. Other account types have none, i.e. `()`.
+pub trait Bumps {
+    type Bumps: AccountBumps;
+}
+
+/// Bumps found while validating accounts, see [`Bumps`].
+pub trait AccountBumps: Default + Clone + std::fmt::Debug {
+    /// The bump of the account `name`, if it was found. Those of nested
+    /// accounts structs are prefixed with the field holding them, as in
+    /// `"parent.child"`.
+    fn get(&self, name: &str) -> Option<u8>;
+}
+
+impl AccountBumps for () {
+    fn get(&self, _name: &str) -> Option<u8> {
+        None
+    }
 }
 
 /// The exit procedure for an account. Any cleanup or persistence to storage
 /// should be done here.
 pub trait AccountsExit<'info>: ToAccountMetas + ToAccountInfos<'info> {
     /// `program_id` is the currently executing program.
-    fn exit(&self, program_id: &Pubkey) -> ProgramResult;
+    fn exit(&self, program_id: &Pubkey) -> Result<()>;
 }
 
+/// Marker for accounts structs without `mut` accounts, including in nested
+/// structs, whose exit procedure has nothing to persist. Implemented by the
+/// [`Accounts`](./derive.Accounts.html) derive macro and required by
+/// `#[skip_exit]` instruction handlers.
+pub trait ReadOnlyAccounts {}
+
 /// The close procedure to initiate garabage collection of an account, allowing
 /// one to retrieve the rent exemption.
 pub trait AccountsClose<'info>: ToAccountInfos<'info> {
-    fn close(&self, sol_destination: AccountInfo<'info>) -> ProgramResult;
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()>;
 }
 
 /// Transformation to
@@ -144,6 +235,103 @@ pub trait ToAccountMetas {
     /// before the relay. The client cannot mark the field as a signer, and so
     /// we have to override the is_signer meta field given by the client.
     fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta>;
+
+    /// The flags each account returned by `to_account_metas` must at least
+    /// have, if known at compile time. Generated for the `cpi::accounts`
+    /// structs from the callee's constraints, so that a
+    /// [`CpiContext`](./context/struct.CpiContext.html) never passes an
+    /// account with weaker flags than the callee requires.
+    fn metas_template() -> &'static [AccountMetaTemplate]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+}
+
+/// Flags of an account position in a [`ToAccountMetas::metas_template`].
+#[derive(Clone, Copy, Debug)]
+pub enum AccountMetaTemplate {
+    Account {
+        is_signer: bool,
+        is_writable: bool,
+    },
+    /// The positions of a nested accounts struct, given by its
+    /// `metas_template`.
+    Composite(fn() -> &'static [AccountMetaTemplate]),
+}
+
+impl PartialEq for AccountMetaTemplate {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                AccountMetaTemplate::Account {
+                    is_signer,
+                    is_writable,
+                },
+                AccountMetaTemplate::Account {
+                    is_signer: other_is_signer,
+                    is_writable: other_is_writable,
+                },
+            ) => is_signer == other_is_signer && is_writable == other_is_writable,
+            (AccountMetaTemplate::Composite(nested), AccountMetaTemplate::Composite(other)) => {
+                nested() == other()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AccountMetaTemplate {}
+
+impl AccountMetaTemplate {
+    /// Raises the flags of `metas` to the ones given by `template`, returning
+    /// the number of positions the template covers.
+    pub fn apply(template: &[AccountMetaTemplate], metas: &mut [AccountMeta]) -> usize {
+        let mut idx = 0;
+        for position in template {
+            match position {
+                AccountMetaTemplate::Account {
+                    is_signer,
+                    is_writable,
+                } => {
+                    if let Some(meta) = metas.get_mut(idx) {
+                        meta.is_signer |= is_signer;
+                        meta.is_writable |= is_writable;
+                    }
+                    idx += 1;
+                }
+                AccountMetaTemplate::Composite(nested) => {
+                    idx += Self::apply(nested(), metas.get_mut(idx..).unwrap_or_default());
+                }
+            }
+        }
+        idx
+    }
+}
+
+/// Fills in the accounts of a client side accounts struct that were left as
+/// `Pubkey::default()`, but whose address follows from their type or
+/// constraints: sysvars, programs, accounts stored in another one of its
+/// accounts as declared by its `has_one` constraints, PDAs of the program
+/// `program_id` whose seeds are constants or other accounts of the struct,
+/// and associated token accounts whose wallet and mint are other accounts of
+/// the struct. `fetch` returns the data of the account at the given address,
+/// or `None` if it doesn't exist. Errors of `fetch` are returned as is.
+pub trait ResolveAccounts {
+    fn resolve_accounts<E: From<ProgramError>>(
+        &mut self,
+        program_id: &Pubkey,
+        fetch: &mut dyn FnMut(&Pubkey) -> Result<Option<Vec<u8>>, E>,
+    ) -> Result<(), E>;
+}
+
+/// Client accounts structs, built from the keys of an instruction's accounts
+/// in order, e.g. to decode the instructions of a transaction.
+pub trait FromAccountKeys: Sized {
+    /// Takes the struct's accounts off the front of `keys`, returning `None`
+    /// if there are too few.
+    fn from_account_keys(keys: &mut &[Pubkey]) -> Option<Self>;
 }
 
 /// Transformation to
@@ -194,8 +382,26 @@ pub trait AccountDeserialize: Sized {
     fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError>;
 }
 
+/// A user provided encoding for an account's data, used by accounts declared
+/// with `#[account(codec = "custom")]`. The 8 byte account discriminator is
+/// written and checked by the generated `AccountSerialize` and
+/// `AccountDeserialize` implementations, so `encode` and `decode` only see
+/// the bytes following it.
+pub trait AccountCodec: Sized {
+    /// Encodes the account into `writer`.
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<(), ProgramError>;
+
+    /// Decodes the account from `buf`, advancing it past the bytes read.
+    fn decode(buf: &mut &[u8]) -> Result<Self, ProgramError>;
+}
+
 /// An account data structure capable of zero copy deserialization.
-pub trait ZeroCopy: Discriminator + Copy + Clone + Zeroable + Pod {}
+pub trait ZeroCopy: Discriminator + Copy + Clone + Zeroable + Pod {
+    /// The size of the account's data, excluding its discriminator. Used to
+    /// infer the `space` of accounts initialized without one, so the struct
+    /// doesn't need to implement `Default`.
+    const SIZE: usize = std::mem::size_of::<Self>();
+}
 
 /// Calculates the data for an instruction invocation, where the data is
 /// `Sha256(<namespace>::<method_name>)[..8] || BorshSerialize(args)`.
@@ -207,6 +413,12 @@ pub trait InstructionData: AnchorSerialize {
 
 /// An event that can be emitted via a Solana log.
 pub trait Event: AnchorSerialize + AnchorDeserialize + Discriminator {
+    /// Whether the event is logged without its discriminator, see
+    /// `#[event(anonymous)]`.
+    const ANONYMOUS: bool = false;
+
+    /// The logged bytes, the discriminator, unless the event is anonymous,
+    /// followed by the serialized event.
     fn data(&self) -> Vec<u8>;
 }
 
@@ -231,11 +443,27 @@ pub trait Bump {
 /// Defines an address expected to own an account.
 pub trait Owner {
     fn owner() -> Pubkey;
+
+    /// The owner expected while executing as `program_id`. Accounts declared
+    /// with `#[account]` in a program built with the `dynamic-program-id`
+    /// feature are owned by whichever program id the program is executing
+    /// as, so the same binary can be deployed under several program ids.
+    fn owner_for(_program_id: &Pubkey) -> Pubkey {
+        Self::owner()
+    }
 }
 
 /// Defines the id of a program.
 pub trait Id {
     fn id() -> Pubkey;
+
+    /// The id expected while executing as `program_id`. The program type of a
+    /// program built with the `dynamic-program-id` feature has whichever id
+    /// the program is executing as, so that `Program<'info, MyProgram>`
+    /// accepts the program itself. `ID` and `id()` stay the declared id.
+    fn id_for(_program_id: &Pubkey) -> Pubkey {
+        Self::id()
+    }
 }
 
 /// Defines the Pubkey of an account.
@@ -251,38 +479,76 @@ impl Key for Pubkey {
 
 /// The prelude contains all commonly used components of the crate.
 /// All programs should include it via `anchor_lang::prelude::*;`.
+///
+/// Programs whose names clash with the prelude's can instead import the
+/// narrower [`account_types`](prelude::account_types),
+/// [`cpi_types`](prelude::cpi_types) and
+/// [`macro_exports`](prelude::macro_exports) groups it's made of. They aren't
+/// named `accounts` and `cpi`, so that they don't clash with the modules
+/// generated by `#[program]` when both are glob imported.
 pub mod prelude {
+    pub use self::{account_types::*, cpi_types::*, macro_exports::*};
+
+    pub use super::compat::GetSysvar;
     pub use super::{
-        access_control, account, constant, declare_id, emit, error, event, interface, program,
-        require, solana_program::bpf_loader_upgradeable::UpgradeableLoaderState, state, zero_copy,
-        Account, AccountDeserialize, AccountLoader, AccountSerialize, Accounts, AccountsExit,
-        AnchorDeserialize, AnchorSerialize, Context, CpiContext, Id, Key, Owner, Program,
-        ProgramData, Signer, System, SystemAccount, Sysvar, ToAccountInfo, ToAccountInfos,
-        ToAccountMetas, UncheckedAccount,
+        solana_program::bpf_loader_upgradeable::UpgradeableLoaderState, AccountCodec, OrErr,
     };
-
-    #[allow(deprecated)]
-    pub use super::{CpiAccount, CpiState, CpiStateContext, Loader, ProgramAccount, ProgramState};
-
-    pub use borsh;
-    pub use solana_program::account_info::{next_account_info, AccountInfo};
-    pub use solana_program::entrypoint::ProgramResult;
-    pub use solana_program::instruction::AccountMeta;
-    pub use solana_program::msg;
-    pub use solana_program::program_error::ProgramError;
-    pub use solana_program::pubkey::Pubkey;
-    pub use solana_program::sysvar::clock::Clock;
-    pub use solana_program::sysvar::epoch_schedule::EpochSchedule;
-    pub use solana_program::sysvar::fees::Fees;
-    pub use solana_program::sysvar::instructions::Instructions;
-    pub use solana_program::sysvar::recent_blockhashes::RecentBlockhashes;
-    pub use solana_program::sysvar::rent::Rent;
-    pub use solana_program::sysvar::rewards::Rewards;
-    pub use solana_program::sysvar::slot_hashes::SlotHashes;
-    pub use solana_program::sysvar::slot_history::SlotHistory;
-    pub use solana_program::sysvar::stake_history::StakeHistory;
+    pub use solana_program::account_info::next_account_info;
     pub use solana_program::sysvar::Sysvar as SolanaSysvar;
-    pub use thiserror;
+
+    /// Account types, the traits implemented by them and the types they're
+    /// declared with, for writing `#[derive(Accounts)]` structs and their
+    /// instruction handlers.
+    pub mod account_types {
+        pub use crate::{
+            Account, AccountDeserialize, AccountField, AccountLoader, AccountSerialize, Accounts,
+            AccountsExit, Context, FixedStr, Id, Key, NativeAccount, Owner, Program, ProgramData,
+            RentExemption, Signer, System, SystemAccount, Sysvar, SysvarAccount, ToAccountInfo,
+            UncheckedAccount, ZeroCopy,
+        };
+
+        #[allow(deprecated)]
+        pub use crate::{CpiAccount, CpiState, Loader, ProgramAccount, ProgramState};
+
+        pub use solana_program::account_info::AccountInfo;
+        pub use solana_program::entrypoint::ProgramResult;
+        pub use solana_program::program_error::ProgramError;
+        pub use solana_program::pubkey::Pubkey;
+        pub use solana_program::sysvar::clock::Clock;
+        pub use solana_program::sysvar::epoch_schedule::EpochSchedule;
+        pub use solana_program::sysvar::fees::Fees;
+        pub use solana_program::sysvar::instructions::Instructions;
+        pub use solana_program::sysvar::recent_blockhashes::RecentBlockhashes;
+        pub use solana_program::sysvar::rent::Rent;
+        pub use solana_program::sysvar::rewards::Rewards;
+        pub use solana_program::sysvar::slot_hashes::SlotHashes;
+        pub use solana_program::sysvar::slot_history::SlotHistory;
+        pub use solana_program::sysvar::stake_history::StakeHistory;
+    }
+
+    /// Contexts and traits for invoking other programs.
+    pub mod cpi_types {
+        pub use crate::{CpiContext, ExtraAccount, ToAccountInfos, ToAccountMetas};
+
+        #[allow(deprecated)]
+        pub use crate::CpiStateContext;
+
+        pub use solana_program::instruction::AccountMeta;
+    }
+
+    /// The attribute and function-like macros, and the serialization derives
+    /// along with the crates their expansions refer to.
+    pub mod macro_exports {
+        pub use crate::{
+            access_control, account, account_field, assert_keys_eq, assert_owner, constant,
+            declare_dependency, declare_id, emit, error, event, flags, idl, interface, program,
+            require, require_some, seed_bytes, state, zero_copy, AnchorDeserialize,
+            AnchorSerialize,
+        };
+        pub use borsh;
+        pub use solana_program::msg;
+        pub use thiserror;
+    }
 }
 
 // Internal module used by macros and unstable apis.
@@ -293,11 +559,25 @@ pub mod __private {
 
     pub use crate::ctor::Ctor;
     pub use crate::error::{Error, ErrorCode};
+    pub use crate::event::emit;
     pub use anchor_attribute_account::ZeroCopyAccessor;
     pub use anchor_attribute_event::EventIndex;
     pub use base64;
     pub use bytemuck;
 
+    // Brings the traits whose methods generated code calls into scope, without
+    // importing their names, so that it compiles in modules not using the
+    // prelude.
+    pub mod traits {
+        pub use crate::{
+            AccountDeserialize as _, AccountSerialize as _, Accounts as _, AccountsClose as _,
+            AccountsExit as _, AnchorDeserialize as _, AnchorSerialize as _, Discriminator as _,
+            FromAccountKeys as _, InstructionData as _, Key as _, ResolveAccounts as _,
+            ToAccountInfo as _, ToAccountInfos as _, ToAccountMetas as _,
+        };
+        pub use solana_program::sysvar::Sysvar as _;
+    }
+
     pub mod state {
         pub use crate::state::*;
     }
@@ -305,6 +585,77 @@ pub mod __private {
     // The starting point for user defined error codes.
     pub const ERROR_CODE_OFFSET: u32 = 6000;
 
+    // Maximum size of a serialized transaction.
+    pub const PACKET_DATA_SIZE: usize = 1232;
+
+    // Size of a legacy transaction holding a single instruction with
+    // `accounts_len` distinct accounts (one of them the fee payer) and
+    // `data_len` bytes of data, signed by the fee payer alone. Any other
+    // transaction carrying the instruction is at least as large.
+    pub const fn min_transaction_size(accounts_len: usize, data_len: usize) -> usize {
+        const fn compact_u16_len(len: usize) -> usize {
+            if len < 0x80 {
+                1
+            } else if len < 0x4000 {
+                2
+            } else {
+                3
+            }
+        }
+        let keys_len = accounts_len + 1;
+        // Signatures.
+        compact_u16_len(1) + 64
+            // Message header.
+            + 3
+            // Account keys, including the program id.
+            + compact_u16_len(keys_len) + 32 * keys_len
+            // Recent blockhash.
+            + 32
+            // Instruction: program id index, account indices and data.
+            + compact_u16_len(1)
+            + 1
+            + compact_u16_len(accounts_len)
+            + accounts_len
+            + compact_u16_len(data_len)
+            + data_len
+    }
+
+    // Deserializes the instruction argument `name` off the front of `data`,
+    // logging the argument, its type and its offset in the instruction's
+    // `args_len` bytes of arguments if it doesn't deserialize.
+    pub fn deserialize_ix_arg<T: crate::AnchorDeserialize>(
+        data: &mut &[u8],
+        args_len: usize,
+        name: &str,
+        ty: &str,
+    ) -> Result<T, ProgramError> {
+        let offset = args_len - data.len();
+        T::deserialize(data).map_err(|e| {
+            solana_program::msg!(
+                "Instruction argument {}: {} did not deserialize at offset {}: {}",
+                name,
+                ty,
+                offset,
+                e
+            );
+            ErrorCode::InstructionDidNotDeserialize.into()
+        })
+    }
+
+    // Refuses the bytes left over after the last instruction argument, for
+    // programs denying trailing bytes.
+    pub fn check_ix_args_end(rest: &[u8], args_len: usize) -> Result<(), ProgramError> {
+        if !rest.is_empty() {
+            solana_program::msg!(
+                "Instruction data has {} unexpected bytes after the arguments, at offset {}",
+                rest.len(),
+                args_len - rest.len()
+            );
+            return Err(ErrorCode::InstructionDataTooLong.into());
+        }
+        Ok(())
+    }
+
     // Calculates the size of an account, which may be larger than the deserialized
     // data in it. This trait is currently only used for `#[state]` accounts.
     #[doc(hidden)]
@@ -327,6 +678,24 @@ pub mod __private {
         }
     }
 
+    // The address stored in a field targeted by a `has_one` constraint, which
+    // is either a `Pubkey` or an optional one.
+    pub trait HasOneKey {
+        fn has_one_key(&self) -> Option<Pubkey>;
+    }
+
+    impl HasOneKey for Pubkey {
+        fn has_one_key(&self) -> Option<Pubkey> {
+            Some(*self)
+        }
+    }
+
+    impl HasOneKey for Option<Pubkey> {
+        fn has_one_key(&self) -> Option<Pubkey> {
+            *self
+        }
+    }
+
     pub use crate::state::PROGRAM_STATE_SEED;
     pub const CLOSED_ACCOUNT_DISCRIMINATOR: [u8; 8] = [255, 255, 255, 255, 255, 255, 255, 255];
 }
@@ -366,3 +735,144 @@ macro_rules! require {
         }
     };
 }
+
+/// Evaluates to the value of an `Option`, or returns the given error if it's
+/// `None`, instead of unwrapping it into an opaque panic.
+///
+/// ```ignore
+/// let config = require_some!(maybe_config, MissingConfig);
+/// let config = require_some!(maybe_config, ErrorCode::MissingConfig);
+/// ```
+///
+/// Like [`require!`], a bare variant name refers to the program's
+/// `ErrorCode`. The source location of the failed requirement is logged. For
+/// the same check in an expression, see [`OrErr`].
+#[macro_export]
+macro_rules! require_some {
+    ($option:expr, $error:tt $(,)?) => {
+        match $option {
+            Some(value) => value,
+            None => {
+                $crate::solana_program::msg!("require_some! failed at {}:{}", file!(), line!());
+                return Err(crate::ErrorCode::$error.into());
+            }
+        }
+    };
+    ($option:expr, $error:expr $(,)?) => {
+        match $option {
+            Some(value) => value,
+            None => {
+                $crate::solana_program::msg!("require_some! failed at {}:{}", file!(), line!());
+                return Err($error.into());
+            }
+        }
+    };
+}
+
+/// Checks that two keys, `Pubkey`s or references to them, are equal, e.g.
+/// for accounts validated by hand in a fallback function or while iterating
+/// over the remaining accounts.
+///
+/// ```ignore
+/// assert_keys_eq!(account.key, config.authority);
+/// assert_keys_eq!(account.key, config.authority, ErrorCode::InvalidAuthority);
+/// ```
+///
+/// Like the `address` constraint, a mismatch fails with `ConstraintAddress`,
+/// or the given error. The keys are logged along with the source location of
+/// the failed assertion.
+#[macro_export]
+macro_rules! assert_keys_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_keys_eq!(
+            $left,
+            $right,
+            $crate::__private::ErrorCode::ConstraintAddress
+        )
+    };
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let left: &$crate::solana_program::pubkey::Pubkey =
+                    ::core::borrow::Borrow::borrow(left);
+                let right: &$crate::solana_program::pubkey::Pubkey =
+                    ::core::borrow::Borrow::borrow(right);
+                if left != right {
+                    $crate::solana_program::msg!(
+                        "assert_keys_eq! failed at {}:{}: {} != {}",
+                        file!(),
+                        line!(),
+                        left,
+                        right
+                    );
+                    return Err($error.into());
+                }
+            }
+        }
+    };
+}
+
+/// Checks that an `AccountInfo` is owned by the given program.
+///
+/// ```ignore
+/// assert_owner!(account, token::ID);
+/// assert_owner!(account, token::ID, ErrorCode::NotATokenAccount);
+/// ```
+///
+/// Like the `owner` constraint, a mismatch fails with `ConstraintOwner`, or
+/// the given error. The account and its owner are logged along with the
+/// source location of the failed assertion.
+#[macro_export]
+macro_rules! assert_owner {
+    ($account:expr, $owner:expr $(,)?) => {
+        $crate::assert_owner!(
+            $account,
+            $owner,
+            $crate::__private::ErrorCode::ConstraintOwner
+        )
+    };
+    ($account:expr, $owner:expr, $error:expr $(,)?) => {
+        match (&$account, &$owner) {
+            (account, owner) => {
+                let account: &$crate::solana_program::account_info::AccountInfo = account;
+                let owner: &$crate::solana_program::pubkey::Pubkey =
+                    ::core::borrow::Borrow::borrow(owner);
+                if account.owner != owner {
+                    $crate::solana_program::msg!(
+                        "assert_owner! failed at {}:{}: {} is owned by {}, not {}",
+                        file!(),
+                        line!(),
+                        account.key,
+                        account.owner,
+                        owner
+                    );
+                    return Err($error.into());
+                }
+            }
+        }
+    };
+}
+
+/// Returns the bytes of an integer for use as a PDA seed, little endian by
+/// default or as given by a trailing `le` or `be`.
+///
+/// ```ignore
+/// let seeds: &[&[u8]] = &[b"order", seed_bytes!(order_id), seed_bytes!(epoch, be)];
+/// ```
+///
+/// The bytes are borrowed from a temporary, so the macro must be used
+/// directly in the seeds expression. In `seeds` constraints, the same
+/// conversion is written `le(<expr>)` or `be(<expr>)`, which also records the
+/// endianness in the IDL.
+#[macro_export]
+macro_rules! seed_bytes {
+    ($value:expr, le $(,)?) => {
+        &($value).to_le_bytes()
+    };
+    ($value:expr, be $(,)?) => {
+        &($value).to_be_bytes()
+    };
+    ($value:expr $(,)?) => {
+        &($value).to_le_bytes()
+    };
+}
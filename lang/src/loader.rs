@@ -1,10 +1,10 @@
 use crate::error::ErrorCode;
+use crate::Result;
 use crate::{
-    Accounts, AccountsClose, AccountsExit, Key, ToAccountInfo, ToAccountInfos, ToAccountMetas,
-    ZeroCopy,
+    Accounts, AccountsClose, AccountsExit, Bumps, Key, ToAccountInfo, ToAccountInfos,
+    ToAccountMetas, ZeroCopy,
 };
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
@@ -139,6 +139,10 @@ impl<'info, T: ZeroCopy> Loader<'info, T> {
         if discriminator != 0 {
             return Err(ErrorCode::AccountDiscriminatorAlreadySet.into());
         }
+        // The account must fit the struct exactly, for it to be cast in place.
+        if data.len() != 8 + T::SIZE {
+            return Err(ErrorCode::ConstraintSpace.into());
+        }
 
         Ok(RefMut::map(data, |data| {
             bytemuck::from_bytes_mut(&mut data.deref_mut()[8..])
@@ -146,6 +150,11 @@ impl<'info, T: ZeroCopy> Loader<'info, T> {
     }
 }
 
+#[allow(deprecated)]
+impl<'info, T: ZeroCopy> Bumps for Loader<'info, T> {
+    type Bumps = ();
+}
+
 #[allow(deprecated)]
 impl<'info, T: ZeroCopy> Accounts<'info> for Loader<'info, T> {
     #[inline(never)]
@@ -153,7 +162,7 @@ impl<'info, T: ZeroCopy> Accounts<'info> for Loader<'info, T> {
         program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
@@ -167,7 +176,7 @@ impl<'info, T: ZeroCopy> Accounts<'info> for Loader<'info, T> {
 #[allow(deprecated)]
 impl<'info, T: ZeroCopy> AccountsExit<'info> for Loader<'info, T> {
     // The account *cannot* be loaded when this is called.
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         let mut data = self.acc_info.try_borrow_mut_data()?;
         let dst: &mut [u8] = &mut data;
         let mut cursor = std::io::Cursor::new(dst);
@@ -178,8 +187,11 @@ impl<'info, T: ZeroCopy> AccountsExit<'info> for Loader<'info, T> {
 
 #[allow(deprecated)]
 impl<'info, T: ZeroCopy> AccountsClose<'info> for Loader<'info, T> {
-    fn close(&self, sol_destination: AccountInfo<'info>) -> ProgramResult {
-        crate::common::close(self.to_account_info(), sol_destination)
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()> {
+        Ok(crate::common::close(
+            self.to_account_info(),
+            sol_destination,
+        )?)
     }
 }
 
@@ -1,10 +1,10 @@
 use crate::error::ErrorCode;
+use crate::Result;
 use crate::{
-    Accounts, AccountsClose, AccountsExit, Key, Owner, ToAccountInfo, ToAccountInfos,
+    Accounts, AccountsClose, AccountsExit, Bumps, Key, Owner, ToAccountInfo, ToAccountInfos,
     ToAccountMetas, ZeroCopy,
 };
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
@@ -21,10 +21,14 @@ use std::ops::DerefMut;
 /// to the account. For more details on zero-copy-deserialization, see the
 /// [`account`](./attr.account.html) attribute.
 ///
-/// When using it's important to be mindful of any calls to `load` so as not to
-/// induce a `RefCell` panic, especially when sharing accounts across CPI
-/// boundaries. When in doubt, one should make sure all refs resulting from a
-/// call to `load` are dropped before CPI.
+/// Any number of `load` borrows can be held at the same time, including
+/// borrows of the same account taken by constraints or helper functions.
+/// `load_mut` and `load_init`, however, require exclusive access, and return
+/// an `AccountAlreadyLoaded` error (rather than panicking) if any other
+/// borrow is still alive. Likewise, `load` returns `AccountBorrowedMutably`
+/// while a mutable borrow is outstanding. When sharing accounts across CPI
+/// boundaries, make sure all refs resulting from a call to `load` are dropped
+/// before the CPI.
 #[derive(Clone)]
 pub struct AccountLoader<'info, T: ZeroCopy + Owner> {
     acc_info: AccountInfo<'info>,
@@ -53,16 +57,31 @@ impl<'info, T: ZeroCopy + Owner> AccountLoader<'info, T> {
     pub fn try_from(
         acc_info: &AccountInfo<'info>,
     ) -> Result<AccountLoader<'info, T>, ProgramError> {
-        if acc_info.owner != &T::owner() {
+        Self::try_from_owned_by(acc_info, &T::owner())
+    }
+
+    /// Same as [`try_from`](Self::try_from), but checks the owner the
+    /// account has while executing as `program_id`, see [`Owner::owner_for`].
+    #[inline(never)]
+    pub fn try_from_for(
+        program_id: &Pubkey,
+        acc_info: &AccountInfo<'info>,
+    ) -> Result<AccountLoader<'info, T>, ProgramError> {
+        Self::try_from_owned_by(acc_info, &T::owner_for(program_id))
+    }
+
+    fn try_from_owned_by(
+        acc_info: &AccountInfo<'info>,
+        owner: &Pubkey,
+    ) -> Result<AccountLoader<'info, T>, ProgramError> {
+        if acc_info.owner != owner {
             return Err(ErrorCode::AccountNotProgramOwned.into());
         }
-        let data: &[u8] = &acc_info.try_borrow_data()?;
         // Discriminator must match.
-        let mut disc_bytes = [0u8; 8];
-        disc_bytes.copy_from_slice(&data[..8]);
-        if disc_bytes != T::discriminator() {
-            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
-        }
+        let data = acc_info
+            .try_borrow_data()
+            .map_err(|_| ErrorCode::AccountBorrowedMutably)?;
+        Self::check_discriminator(&data)?;
 
         Ok(AccountLoader::new(acc_info.clone()))
     }
@@ -70,29 +89,30 @@ impl<'info, T: ZeroCopy + Owner> AccountLoader<'info, T> {
     /// Constructs a new `Loader` from an uninitialized account.
     #[inline(never)]
     pub fn try_from_unchecked(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         acc_info: &AccountInfo<'info>,
     ) -> Result<AccountLoader<'info, T>, ProgramError> {
-        if acc_info.owner != &T::owner() {
+        if acc_info.owner != &T::owner_for(program_id) {
             return Err(ErrorCode::AccountNotProgramOwned.into());
         }
         Ok(AccountLoader::new(acc_info.clone()))
     }
 
-    /// Returns a Ref to the account data structure for reading.
+    /// Returns a Ref to the account data structure for reading. Shared loads
+    /// don't conflict with each other, so this can be called any number of
+    /// times while previously returned refs are still alive.
     pub fn load(&self) -> Result<Ref<T>, ProgramError> {
-        let data = self.acc_info.try_borrow_data()?;
-
-        let mut disc_bytes = [0u8; 8];
-        disc_bytes.copy_from_slice(&data[..8]);
-        if disc_bytes != T::discriminator() {
-            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
-        }
+        let data = self
+            .acc_info
+            .try_borrow_data()
+            .map_err(|_| ErrorCode::AccountBorrowedMutably)?;
+        Self::check_discriminator(&data)?;
 
         Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..])))
     }
 
     /// Returns a `RefMut` to the account data structure for reading or writing.
+    /// Fails if any other ref to the account data is still alive.
     pub fn load_mut(&self) -> Result<RefMut<T>, ProgramError> {
         // AccountInfo api allows you to borrow mut even if the account isn't
         // writable, so add this check for a better dev experience.
@@ -100,13 +120,11 @@ impl<'info, T: ZeroCopy + Owner> AccountLoader<'info, T> {
             return Err(ErrorCode::AccountNotMutable.into());
         }
 
-        let data = self.acc_info.try_borrow_mut_data()?;
-
-        let mut disc_bytes = [0u8; 8];
-        disc_bytes.copy_from_slice(&data[..8]);
-        if disc_bytes != T::discriminator() {
-            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
-        }
+        let data = self
+            .acc_info
+            .try_borrow_mut_data()
+            .map_err(|_| ErrorCode::AccountAlreadyLoaded)?;
+        Self::check_discriminator(&data)?;
 
         Ok(RefMut::map(data, |data| {
             bytemuck::from_bytes_mut(&mut data.deref_mut()[8..])
@@ -122,43 +140,90 @@ impl<'info, T: ZeroCopy + Owner> AccountLoader<'info, T> {
             return Err(ErrorCode::AccountNotMutable.into());
         }
 
-        let data = self.acc_info.try_borrow_mut_data()?;
+        let data = self
+            .acc_info
+            .try_borrow_mut_data()
+            .map_err(|_| ErrorCode::AccountAlreadyLoaded)?;
 
         // The discriminator should be zero, since we're initializing.
+        if data.len() < 8 {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        }
         let mut disc_bytes = [0u8; 8];
         disc_bytes.copy_from_slice(&data[..8]);
         let discriminator = u64::from_le_bytes(disc_bytes);
         if discriminator != 0 {
             return Err(ErrorCode::AccountDiscriminatorAlreadySet.into());
         }
+        // The account must fit the struct exactly, for it to be cast in place.
+        if data.len() != 8 + T::SIZE {
+            return Err(ErrorCode::ConstraintSpace.into());
+        }
 
         Ok(RefMut::map(data, |data| {
             bytemuck::from_bytes_mut(&mut data.deref_mut()[8..])
         }))
     }
+
+    /// Returns a `RefMut` to the account data structure for reading or writing,
+    /// like [`load_init`](Self::load_init) if the account's discriminator
+    /// hasn't been set yet and like [`load_mut`](Self::load_mut) otherwise.
+    /// Meant for accounts declared with `init_if_needed`.
+    pub fn load_init_if_needed(&self) -> Result<RefMut<T>, ProgramError> {
+        let initialized = {
+            let data = self
+                .acc_info
+                .try_borrow_data()
+                .map_err(|_| ErrorCode::AccountAlreadyLoaded)?;
+            data.len() >= 8 && data[..8] != [0; 8]
+        };
+        match initialized {
+            true => self.load_mut(),
+            false => self.load_init(),
+        }
+    }
+
+    // Verifies the account discriminator independently of how the data was
+    // borrowed, so that shared and mutable loads perform identical checks.
+    fn check_discriminator(data: &[u8]) -> Result<(), ProgramError> {
+        if data.len() < 8 {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        }
+        if data[..8] != T::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Ok(())
+    }
+}
+
+impl<'info, T: ZeroCopy + Owner> Bumps for AccountLoader<'info, T> {
+    type Bumps = ();
 }
 
 impl<'info, T: ZeroCopy + Owner> Accounts<'info> for AccountLoader<'info, T> {
     #[inline(never)]
     fn try_accounts(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
         let account = &accounts[0];
         *accounts = &accounts[1..];
-        let l = AccountLoader::try_from(account)?;
+        let l = AccountLoader::try_from_owned_by(account, &T::owner_for(program_id))?;
         Ok(l)
     }
 }
 
 impl<'info, T: ZeroCopy + Owner> AccountsExit<'info> for AccountLoader<'info, T> {
     // The account *cannot* be loaded when this is called.
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
-        let mut data = self.acc_info.try_borrow_mut_data()?;
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
+        let mut data = self
+            .acc_info
+            .try_borrow_mut_data()
+            .map_err(|_| ErrorCode::AccountAlreadyLoaded)?;
         let dst: &mut [u8] = &mut data;
         let mut cursor = std::io::Cursor::new(dst);
         cursor.write_all(&T::discriminator()).unwrap();
@@ -167,8 +232,11 @@ impl<'info, T: ZeroCopy + Owner> AccountsExit<'info> for AccountLoader<'info, T>
 }
 
 impl<'info, T: ZeroCopy + Owner> AccountsClose<'info> for AccountLoader<'info, T> {
-    fn close(&self, sol_destination: AccountInfo<'info>) -> ProgramResult {
-        crate::common::close(self.to_account_info(), sol_destination)
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()> {
+        Ok(crate::common::close(
+            self.to_account_info(),
+            sol_destination,
+        )?)
     }
 }
 
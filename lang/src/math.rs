@@ -0,0 +1,119 @@
+//! Share math for vaults, i.e. programs holding a pool of assets on behalf of
+//! depositors, who are issued shares of the pool in return.
+//!
+//! Every conversion rounds in favor of the vault, so that no sequence of
+//! deposits and withdrawals can extract more assets than were put in:
+//!
+//! * [`shares_for_deposit`] and [`assets_for_redeem`] round down, i.e. the
+//!   user receives less.
+//! * [`assets_for_mint`] and [`shares_for_withdraw`] round up, i.e. the user
+//!   pays more.
+//!
+//! Both totals are offset by [`VIRTUAL_ASSETS`] and [`VIRTUAL_SHARES`], which
+//! the vault behaves as if it held from the start. This defines the price of
+//! the first shares, and defeats the inflation attack, where the first
+//! depositor mints a single share and then donates assets to the vault so
+//! that later deposits round down to zero shares: the donation is shared
+//! with the virtual shares, making the attack cost far more than it can
+//! steal.
+//!
+//! `total_assets` and `total_shares` are the vault's holdings and share supply
+//! before the operation.
+
+use crate::error::ErrorCode;
+use solana_program::program_error::ProgramError;
+use std::convert::TryFrom;
+
+/// Shares the vault behaves as if were issued from the start.
+pub const VIRTUAL_SHARES: u64 = 1_000;
+
+/// Assets the vault behaves as if it held from the start.
+pub const VIRTUAL_ASSETS: u64 = 1;
+
+/// Shares minted for depositing `assets`, rounded down.
+pub fn shares_for_deposit(
+    assets: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> Result<u64, ProgramError> {
+    mul_div(
+        assets,
+        shares_with_offset(total_shares),
+        assets_with_offset(total_assets),
+        Rounding::Down,
+    )
+}
+
+/// Assets to deposit in order to mint `shares`, rounded up.
+pub fn assets_for_mint(
+    shares: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> Result<u64, ProgramError> {
+    mul_div(
+        shares,
+        assets_with_offset(total_assets),
+        shares_with_offset(total_shares),
+        Rounding::Up,
+    )
+}
+
+/// Shares burned for withdrawing `assets`, rounded up.
+pub fn shares_for_withdraw(
+    assets: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> Result<u64, ProgramError> {
+    mul_div(
+        assets,
+        shares_with_offset(total_shares),
+        assets_with_offset(total_assets),
+        Rounding::Up,
+    )
+}
+
+/// Assets withdrawn for redeeming `shares`, rounded down.
+pub fn assets_for_redeem(
+    shares: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> Result<u64, ProgramError> {
+    mul_div(
+        shares,
+        assets_with_offset(total_assets),
+        shares_with_offset(total_shares),
+        Rounding::Down,
+    )
+}
+
+#[derive(Clone, Copy)]
+enum Rounding {
+    Down,
+    Up,
+}
+
+fn shares_with_offset(total_shares: u64) -> u128 {
+    total_shares as u128 + VIRTUAL_SHARES as u128
+}
+
+fn assets_with_offset(total_assets: u64) -> u128 {
+    total_assets as u128 + VIRTUAL_ASSETS as u128
+}
+
+// `value * numerator / denominator`, failing if the result doesn't fit a u64.
+fn mul_div(
+    value: u64,
+    numerator: u128,
+    denominator: u128,
+    rounding: Rounding,
+) -> Result<u64, ProgramError> {
+    let product = (value as u128)
+        .checked_mul(numerator)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let quotient = product / denominator;
+    let quotient = match rounding {
+        Rounding::Up if product % denominator != 0 => quotient + 1,
+        _ => quotient,
+    };
+    u64::try_from(quotient).map_err(|_| ErrorCode::MathOverflow.into())
+}
@@ -0,0 +1,104 @@
+use crate::error::ErrorCode;
+use crate::Result;
+use crate::*;
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::ops::Deref;
+
+// The native loader isn't part of `solana_program`.
+mod native_loader {
+    solana_program::declare_id!("NativeLoader1111111111111111111111111111111");
+}
+
+/// An account owned by the native loader, e.g. a builtin program, for which
+/// there's no typed wrapper.
+#[derive(Debug, Clone)]
+pub struct NativeAccount<'info> {
+    info: AccountInfo<'info>,
+}
+
+impl<'info> NativeAccount<'info> {
+    fn new(info: AccountInfo<'info>) -> NativeAccount<'info> {
+        Self { info }
+    }
+
+    #[inline(never)]
+    pub fn try_from(info: &AccountInfo<'info>) -> Result<NativeAccount<'info>, ProgramError> {
+        if *info.owner != native_loader::ID {
+            return Err(ErrorCode::AccountNotNativeOwned.into());
+        }
+        Ok(NativeAccount::new(info.clone()))
+    }
+}
+
+impl<'info> Bumps for NativeAccount<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info> for NativeAccount<'info> {
+    #[inline(never)]
+    fn try_accounts(
+        _program_id: &Pubkey,
+        accounts: &mut &[AccountInfo<'info>],
+        _ix_data: &[u8],
+    ) -> Result<Self> {
+        if accounts.is_empty() {
+            return Err(ErrorCode::AccountNotEnoughKeys.into());
+        }
+        let account = &accounts[0];
+        *accounts = &accounts[1..];
+        Ok(NativeAccount::try_from(account)?)
+    }
+}
+
+impl<'info> AccountsExit<'info> for NativeAccount<'info> {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
+        // No-op.
+        Ok(())
+    }
+}
+
+impl<'info> ToAccountMetas for NativeAccount<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let is_signer = is_signer.unwrap_or(self.info.is_signer);
+        let meta = match self.info.is_writable {
+            false => AccountMeta::new_readonly(*self.info.key, is_signer),
+            true => AccountMeta::new(*self.info.key, is_signer),
+        };
+        vec![meta]
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for NativeAccount<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.info.clone()]
+    }
+}
+
+impl<'info> ToAccountInfo<'info> for NativeAccount<'info> {
+    fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+}
+
+impl<'info> AsRef<AccountInfo<'info>> for NativeAccount<'info> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info> Deref for NativeAccount<'info> {
+    type Target = AccountInfo<'info>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.info
+    }
+}
+
+impl<'info> Key for NativeAccount<'info> {
+    fn key(&self) -> Pubkey {
+        *self.info.key
+    }
+}
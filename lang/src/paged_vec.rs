@@ -0,0 +1,261 @@
+//! Lists of unbounded length, sharded across program owned pages.
+//!
+//! A list is described by a [`PagedVecHeader`], embedded in one of the
+//! program's accounts, e.g.
+//!
+//! ```ignore
+//! #[account]
+//! pub struct Registry {
+//!     pub entries: PagedVecHeader,
+//! }
+//! ```
+//!
+//! Its items are stored in order in [`Page`] accounts holding up to
+//! `page_capacity` items each, at the addresses derived from
+//! `[PAGED_VEC_SEED, list, index]`, see [`page_address`], where `list` is the
+//! address of the account holding the header and `index` the page's little
+//! endian `u32` index. Pages are created as the list grows, paid for by the
+//! payer given to [`push`]:
+//!
+//! ```ignore
+//! let registry = &mut ctx.accounts.registry;
+//! paged_vec::push(
+//!     ctx.program_id,
+//!     &registry.key(),
+//!     &mut registry.entries,
+//!     &ctx.accounts.page,
+//!     &ctx.accounts.payer,
+//!     &ctx.accounts.system_program,
+//!     &entry,
+//! )?;
+//! ```
+//!
+//! where `page` is the account at `page_address` of
+//! [`PagedVecHeader::next_page`]. Items are read with [`get`], or page by
+//! page with [`load_page`]. Clients iterate over a list with
+//! `anchor_client::Program::paged_vec`.
+
+use crate::error::ErrorCode;
+use crate::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+
+/// Seed prefix of page addresses.
+pub const PAGED_VEC_SEED: &[u8] = b"anchor:paged-vec";
+
+/// The length and layout of a paged list. Pages hold at least one item.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PagedVecHeader {
+    /// The number of items in the list.
+    pub len: u64,
+    /// The maximum number of items per page.
+    pub page_capacity: u32,
+    /// The maximum size of an item, borsh encoded.
+    pub item_size: u32,
+}
+
+impl PagedVecHeader {
+    pub fn new(page_capacity: u32, item_size: u32) -> Result<Self, ProgramError> {
+        if page_capacity == 0 {
+            return Err(ErrorCode::PagedVecZeroCapacity.into());
+        }
+        Ok(Self {
+            len: 0,
+            page_capacity,
+            item_size,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of pages holding the list's items.
+    pub fn page_count(&self) -> Result<u32, ProgramError> {
+        let (pages, partial) = self.locate(self.len)?;
+        Ok(pages + (partial > 0) as u32)
+    }
+
+    /// The index of the page holding the item at `index`, and the item's
+    /// position in the page. Fails for headers with a zero page capacity,
+    /// e.g. read from an account that was never initialized.
+    pub fn locate(&self, index: u64) -> Result<(u32, usize), ProgramError> {
+        let capacity = self.page_capacity as u64;
+        if capacity == 0 {
+            return Err(ErrorCode::PagedVecZeroCapacity.into());
+        }
+        Ok(((index / capacity) as u32, (index % capacity) as usize))
+    }
+
+    /// The index of the page the next pushed item is stored in.
+    pub fn next_page(&self) -> Result<u32, ProgramError> {
+        Ok(self.locate(self.len)?.0)
+    }
+
+    /// The length of a page account, fitting `page_capacity` items of
+    /// `item_size`.
+    pub fn page_space(&self) -> usize {
+        8 + 32 + 4 + 4 + self.page_capacity as usize * self.item_size as usize
+    }
+}
+
+/// A page of a paged list, holding its items from
+/// `index * page_capacity` on.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    /// The address of the account holding the list's header.
+    pub list: Pubkey,
+    pub index: u32,
+    pub items: Vec<T>,
+}
+
+impl<T> Discriminator for Page<T> {
+    fn discriminator() -> [u8; 8] {
+        hash(b"account:PagedVecPage").to_bytes()[..8]
+            .try_into()
+            .unwrap()
+    }
+}
+
+impl<T: AnchorDeserialize> AccountDeserialize for Page<T> {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        if buf.len() < 8 {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        }
+        if buf[..8] != Self::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        let mut data = buf.get(8..).unwrap_or_default();
+        AnchorDeserialize::deserialize(&mut data)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+/// Returns the address of page `index` of the list held by `list` in the
+/// program `program_id`, and its bump.
+pub fn page_address(program_id: &Pubkey, list: &Pubkey, index: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PAGED_VEC_SEED, list.as_ref(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Decodes the page account `page` of the list held by `list`, checking
+/// that it's the program's page at its index.
+pub fn load_page<T: AnchorDeserialize>(
+    program_id: &Pubkey,
+    list: &Pubkey,
+    page: &AccountInfo,
+) -> Result<Page<T>, ProgramError> {
+    if page.owner != program_id {
+        return Err(ErrorCode::AccountNotProgramOwned.into());
+    }
+    let state = Page::<T>::try_deserialize(&mut &page.try_borrow_data()?[..])?;
+    if &state.list != list || page.key != &page_address(program_id, list, state.index).0 {
+        return Err(ErrorCode::PagedVecWrongPage.into());
+    }
+    Ok(state)
+}
+
+/// Returns the item at `index` of the list, given the page holding it.
+pub fn get<T: AnchorDeserialize>(
+    program_id: &Pubkey,
+    list: &Pubkey,
+    header: &PagedVecHeader,
+    page: &AccountInfo,
+    index: u64,
+) -> Result<T, ProgramError> {
+    if index >= header.len {
+        return Err(ErrorCode::PagedVecIndexOutOfBounds.into());
+    }
+    let (page_index, position) = header.locate(index)?;
+    let state = load_page::<T>(program_id, list, page)?;
+    if state.index != page_index {
+        return Err(ErrorCode::PagedVecWrongPage.into());
+    }
+    state
+        .items
+        .into_iter()
+        .nth(position)
+        .ok_or_else(|| ErrorCode::PagedVecIndexOutOfBounds.into())
+}
+
+/// Appends `item` to the list, given the page at
+/// [`PagedVecHeader::next_page`]. The page is created, funded by `payer`, if
+/// it doesn't exist yet.
+pub fn push<'info, T: AnchorSerialize + AnchorDeserialize>(
+    program_id: &Pubkey,
+    list: &Pubkey,
+    header: &mut PagedVecHeader,
+    page: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    item: &T,
+) -> ProgramResult {
+    let item = item
+        .try_to_vec()
+        .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+    if item.len() > header.item_size as usize {
+        return Err(ErrorCode::AccountDidNotSerialize.into());
+    }
+    let page_index = header.next_page()?;
+    let (address, bump) = page_address(program_id, list, page_index);
+    if page.key != &address {
+        return Err(ErrorCode::PagedVecWrongPage.into());
+    }
+    if page.owner != program_id {
+        let seeds: &[&[u8]] = &[
+            PAGED_VEC_SEED,
+            list.as_ref(),
+            &page_index.to_le_bytes(),
+            &[bump],
+        ];
+        crate::common::create_pda(
+            program_id,
+            page,
+            payer,
+            system_program,
+            header.page_space(),
+            seeds,
+        )?;
+        let mut data = page.try_borrow_mut_data()?;
+        data[..8].copy_from_slice(&Page::<T>::discriminator());
+        let empty = Page::<T> {
+            list: *list,
+            index: page_index,
+            items: vec![],
+        };
+        empty
+            .serialize(&mut &mut data[8..])
+            .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+    }
+
+    // Items are appended in place, after bumping the vec's length.
+    let mut data = page.try_borrow_mut_data()?;
+    if data.len() < 48 || data[..8] != Page::<T>::discriminator() {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    let count = u32::from_le_bytes(data[44..48].try_into().unwrap());
+    let end = {
+        let mut items = &data[48..];
+        for _ in 0..count {
+            T::deserialize(&mut items).map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        }
+        data.len() - items.len()
+    };
+    if end + item.len() > data.len() {
+        return Err(ErrorCode::AccountDidNotSerialize.into());
+    }
+    data[end..end + item.len()].copy_from_slice(&item);
+    data[44..48].copy_from_slice(&(count + 1).to_le_bytes());
+    header.len += 1;
+    Ok(())
+}
@@ -0,0 +1,20 @@
+//! Panic handling for programs declared with `#[program(panic_handler)]`.
+//!
+//! A panic aborts the program, so it can't be turned into an error returned
+//! by the instruction. Instead, the handler logs the panic's message and
+//! location, followed by the `ProgramPanicked` error in the format errors
+//! returned by instructions are logged in, so that clients can attribute the
+//! failure.
+
+use crate::error::ErrorCode;
+use core::fmt;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+
+/// The default panic handler, logging `info` and the `ProgramPanicked`
+/// error. Besides the program's `PanicInfo`, it takes the info given to a
+/// panic hook, e.g. to log panics the same way in host tests.
+pub fn log_panic<I: fmt::Display + ?Sized>(info: &I) {
+    msg!("Program panicked: {}", info);
+    msg!(&ProgramError::from(ErrorCode::ProgramPanicked).to_string());
+}
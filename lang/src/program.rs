@@ -1,7 +1,9 @@
 use crate::error::ErrorCode;
+use crate::Result;
 use crate::*;
 use solana_program::account_info::AccountInfo;
 use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
@@ -42,7 +44,24 @@ impl<'a, T: Id + AccountDeserialize + Clone> Program<'a, T> {
     /// Deserializes the given `info` into a `Program`.
     #[inline(never)]
     pub fn try_from(info: &AccountInfo<'a>) -> Result<Program<'a, T>, ProgramError> {
-        if info.key != &T::id() {
+        Self::try_from_with_id(info, &T::id())
+    }
+
+    /// Same as [`try_from`](Self::try_from), but checks the id the program
+    /// has while executing as `program_id`, see [`Id::id_for`].
+    #[inline(never)]
+    pub fn try_from_for(
+        program_id: &Pubkey,
+        info: &AccountInfo<'a>,
+    ) -> Result<Program<'a, T>, ProgramError> {
+        Self::try_from_with_id(info, &T::id_for(program_id))
+    }
+
+    fn try_from_with_id(
+        info: &AccountInfo<'a>,
+        id: &Pubkey,
+    ) -> Result<Program<'a, T>, ProgramError> {
+        if info.key != id {
             return Err(ErrorCode::InvalidProgramId.into());
         }
         if !info.executable {
@@ -87,6 +106,36 @@ impl<'a, T: Id + AccountDeserialize + Clone> Program<'a, T> {
     pub fn programdata_address(&self) -> Option<Pubkey> {
         self.programdata_address
     }
+
+    /// Checks the program wasn't upgraded in the current slot, given its
+    /// program data account. Programs that aren't upgradeable are always
+    /// stable.
+    ///
+    /// Invoking a program upgraded within the same slot means invoking code
+    /// that may not have been observable on chain before the transaction was
+    /// signed.
+    pub fn check_stable(&self, programdata: &AccountInfo) -> ProgramResult {
+        let programdata_address = match self.programdata_address {
+            None => return Ok(()),
+            Some(programdata_address) => programdata_address,
+        };
+        if programdata.key != &programdata_address {
+            return Err(ErrorCode::AccountNotProgramData.into());
+        }
+        let programdata = ProgramData::try_deserialize(&mut &programdata.try_borrow_data()?[..])?;
+        let clock = crate::clock::get()?;
+        if programdata.slot == clock.slot {
+            return Err(ErrorCode::ConstraintProgramStable.into());
+        }
+        Ok(())
+    }
+}
+
+impl<'info, T: Id + Clone> Bumps for Program<'info, T>
+where
+    T: Id + AccountDeserialize,
+{
+    type Bumps = ();
 }
 
 impl<'info, T: Id + Clone> Accounts<'info> for Program<'info, T>
@@ -95,16 +144,16 @@ where
 {
     #[inline(never)]
     fn try_accounts(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
         let account = &accounts[0];
         *accounts = &accounts[1..];
-        Program::try_from(account)
+        Ok(Program::try_from_for(program_id, account)?)
     }
 }
 
@@ -146,7 +195,7 @@ impl<'info, T: Id + AccountDeserialize + Clone> Deref for Program<'info, T> {
 }
 
 impl<'info, T: AccountDeserialize + Id + Clone> AccountsExit<'info> for Program<'info, T> {
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         // No-op.
         Ok(())
     }
@@ -1,12 +1,12 @@
 use crate::error::ErrorCode;
 #[allow(deprecated)]
 use crate::CpiAccount;
+use crate::Result;
 use crate::{
-    AccountDeserialize, AccountSerialize, Accounts, AccountsClose, AccountsExit, Key,
+    AccountDeserialize, AccountSerialize, Accounts, AccountsClose, AccountsExit, Bumps, Key,
     ToAccountInfo, ToAccountInfos, ToAccountMetas,
 };
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
@@ -73,6 +73,14 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Clone> ProgramAccount<'a, T>
     }
 }
 
+#[allow(deprecated)]
+impl<'info, T> Bumps for ProgramAccount<'info, T>
+where
+    T: AccountSerialize + AccountDeserialize + Clone,
+{
+    type Bumps = ();
+}
+
 #[allow(deprecated)]
 impl<'info, T> Accounts<'info> for ProgramAccount<'info, T>
 where
@@ -83,13 +91,13 @@ where
         program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
         let account = &accounts[0];
         *accounts = &accounts[1..];
-        ProgramAccount::try_from(program_id, account)
+        Ok(ProgramAccount::try_from(program_id, account)?)
     }
 }
 
@@ -97,7 +105,7 @@ where
 impl<'info, T: AccountSerialize + AccountDeserialize + Clone> AccountsExit<'info>
     for ProgramAccount<'info, T>
 {
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         let info = self.to_account_info();
         let mut data = info.try_borrow_mut_data()?;
         let dst: &mut [u8] = &mut data;
@@ -111,8 +119,11 @@ impl<'info, T: AccountSerialize + AccountDeserialize + Clone> AccountsExit<'info
 impl<'info, T: AccountSerialize + AccountDeserialize + Clone> AccountsClose<'info>
     for ProgramAccount<'info, T>
 {
-    fn close(&self, sol_destination: AccountInfo<'info>) -> ProgramResult {
-        crate::common::close(self.to_account_info(), sol_destination)
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()> {
+        Ok(crate::common::close(
+            self.to_account_info(),
+            sol_destination,
+        )?)
     }
 }
 
@@ -0,0 +1,37 @@
+//! Rent exemption of accounts.
+//!
+//! An account is exempt from rent while it holds at least the minimum
+//! balance for its data length, which it can fall short of after being
+//! reallocated. Account wrappers can check this with the
+//! [`RentExemption`] methods, and accounts structs can top up the shortfall
+//! from a payer with the `rent_exempt = top_up(<payer>)` constraint.
+
+use crate::compat::GetSysvar;
+use crate::ToAccountInfo;
+use solana_program::account_info::AccountInfo;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+
+/// The lamports `info` needs to be rent exempt under `rent`, zero if it
+/// already is.
+pub fn lamports_needed_for_exemption(rent: &Rent, info: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(rent
+        .minimum_balance(info.try_data_len()?)
+        .saturating_sub(info.lamports()))
+}
+
+/// Rent exemption of an account, under the rent sysvar.
+pub trait RentExemption<'info>: ToAccountInfo<'info> {
+    /// True if the account holds enough lamports for its data length.
+    fn is_rent_exempt(&self) -> Result<bool, ProgramError> {
+        Ok(self.lamports_needed_for_exemption()? == 0)
+    }
+
+    /// The lamports the account needs to be rent exempt, zero if it already
+    /// is.
+    fn lamports_needed_for_exemption(&self) -> Result<u64, ProgramError> {
+        lamports_needed_for_exemption(&Rent::get_sysvar()?, &self.to_account_info())
+    }
+}
+
+impl<'info, T: ToAccountInfo<'info>> RentExemption<'info> for T {}
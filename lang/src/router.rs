@@ -0,0 +1,137 @@
+//! Instruction routing for plugin style programs, enabled with the `router`
+//! feature.
+//!
+//! A [`Router`] dispatches instructions by their sighash to handlers
+//! registered at runtime, or forwards them via CPI to plugin programs. This
+//! lets a single front program expose instructions implemented in several
+//! deployed program binaries. It's typically invoked from a program's
+//! fallback function, so instructions not defined in the `#[program]` module
+//! are routed:
+//!
+//! ```ignore
+//! #[program]
+//! pub mod front {
+//!     use super::*;
+//!
+//!     pub fn fallback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+//!         Router::new()
+//!             .handler("deposit", deposit)
+//!             .plugin("swap", swap_program::ID)
+//!             .dispatch(program_id, accounts, data)
+//!     }
+//! }
+//!
+//! fn deposit(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+//!     router::handle(program_id, accounts, ix_data, |ctx: Context<Deposit>| {
+//!         ..
+//!     })
+//! }
+//! ```
+
+use crate::error::ErrorCode;
+use crate::{Accounts, AccountsExit, Context};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke;
+use solana_program::pubkey::Pubkey;
+
+/// An instruction handler registered with a [`Router`]. `ix_data` is the
+/// instruction data following the 8 byte sighash.
+pub type Handler = for<'info> fn(&Pubkey, &[AccountInfo<'info>], &[u8]) -> ProgramResult;
+
+enum Route {
+    Handler(Handler),
+    Plugin(Pubkey),
+}
+
+/// Dispatches instructions to handlers and plugin programs registered at
+/// runtime. See the [module docs](./index.html).
+#[derive(Default)]
+pub struct Router {
+    routes: Vec<([u8; 8], Route)>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Routes the global instruction `name` to `handler`.
+    pub fn handler(mut self, name: &str, handler: Handler) -> Self {
+        self.routes.push((sighash(name), Route::Handler(handler)));
+        self
+    }
+
+    /// Routes the global instruction `name` to the plugin program
+    /// `program_id`, which is invoked with the same instruction data and
+    /// accounts. The plugin program must be one of the given accounts.
+    pub fn plugin(mut self, name: &str, program_id: Pubkey) -> Self {
+        self.routes.push((sighash(name), Route::Plugin(program_id)));
+        self
+    }
+
+    /// Dispatches the instruction `data`, including its sighash, to the
+    /// registered route. Fails with `InstructionFallbackNotFound` if there is
+    /// none.
+    pub fn dispatch<'info>(
+        &self,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        data: &[u8],
+    ) -> ProgramResult {
+        if data.len() < 8 {
+            return Err(ErrorCode::InstructionMissing.into());
+        }
+        let route = self
+            .routes
+            .iter()
+            .find(|(sighash, _)| sighash[..] == data[..8])
+            .map(|(_, route)| route)
+            .ok_or(ErrorCode::InstructionFallbackNotFound)?;
+        match route {
+            Route::Handler(handler) => handler(program_id, accounts, &data[8..]),
+            Route::Plugin(plugin_id) => {
+                let ix = Instruction {
+                    program_id: *plugin_id,
+                    accounts: accounts
+                        .iter()
+                        .filter(|acc| acc.key != plugin_id)
+                        .map(|acc| AccountMeta {
+                            pubkey: *acc.key,
+                            is_signer: acc.is_signer,
+                            is_writable: acc.is_writable,
+                        })
+                        .collect(),
+                    data: data.to_vec(),
+                };
+                invoke(&ix, accounts)
+            }
+        }
+    }
+}
+
+/// Validates the accounts `T`, invokes `f` and runs the accounts' exit
+/// routine, as the handlers generated by `#[program]` do.
+pub fn handle<'info, T, F>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    ix_data: &[u8],
+    f: F,
+) -> ProgramResult
+where
+    T: Accounts<'info> + AccountsExit<'info>,
+    F: FnOnce(Context<'_, '_, '_, 'info, T>) -> ProgramResult,
+{
+    let mut remaining_accounts: &[AccountInfo<'info>] = accounts;
+    let mut accounts = T::try_accounts(program_id, &mut remaining_accounts, ix_data)?;
+    f(Context::new(program_id, &mut accounts, remaining_accounts))?;
+    Ok(accounts.exit(program_id)?)
+}
+
+fn sighash(name: &str) -> [u8; 8] {
+    let mut sighash = [0u8; 8];
+    sighash.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    sighash
+}
@@ -0,0 +1,231 @@
+//! A record of the schema a program was deployed with, so that indexers
+//! detect schema drift after an upgrade instead of decoding garbage.
+//!
+//! Programs built with `#[program(schema_registry)]` get the
+//! `schema_publish` instruction, with which the program's upgrade authority
+//! writes the [`idl_hash`] of the program's IDL and the version of its event
+//! schemas to the [`ProgramSchema`] account at [`schema_address`], e.g.
+//! right after upgrading it, see [`publish_account_metas`]. The record keeps
+//! the slot the program was last deployed at, so a record that wasn't
+//! published again after an upgrade is detected as stale.
+//!
+//! Indexers check the record against the IDL and event schema version they
+//! decode with, e.g. with anchor-client's `Program::verify_schema`, or with
+//! [`ProgramSchema::check`].
+
+use crate::bpf_upgradeable_state::ProgramData;
+use crate::error::ErrorCode;
+use crate::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator};
+use solana_program::account_info::AccountInfo;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+use std::fmt;
+
+/// Seed of the schema record's address.
+pub const SCHEMA_SEED: &[u8] = b"anchor:schema";
+
+/// Length of the schema record account, its discriminator followed by the
+/// borsh encoded [`ProgramSchema`].
+pub const SCHEMA_ACCOUNT_LEN: usize = 8 + 32 + 4 + 8;
+
+/// The schema a program was deployed with.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProgramSchema {
+    /// The [`idl_hash`] of the program's IDL.
+    pub idl_hash: [u8; 32],
+    /// The version of the program's event schemas, bumped by the program's
+    /// authors when an event's layout changes.
+    pub event_schema_version: u32,
+    /// The slot the program was last deployed at when the record was
+    /// published.
+    pub deployment_slot: u64,
+}
+
+impl Discriminator for ProgramSchema {
+    fn discriminator() -> [u8; 8] {
+        hash(b"account:ProgramSchema").to_bytes()[..8]
+            .try_into()
+            .unwrap()
+    }
+}
+
+impl AccountDeserialize for ProgramSchema {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        if buf.len() < 8 {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        }
+        if buf[..8] != Self::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        let mut data = buf.get(8..).unwrap_or_default();
+        AnchorDeserialize::deserialize(&mut data)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+impl ProgramSchema {
+    /// Checks the record against the IDL hash and event schema version a
+    /// client decodes with, and the slot the program was last deployed at.
+    pub fn check(
+        &self,
+        idl_hash: &[u8; 32],
+        event_schema_version: u32,
+        deployment_slot: u64,
+    ) -> Result<(), SchemaDrift> {
+        if self.deployment_slot != deployment_slot {
+            return Err(SchemaDrift::Stale {
+                published: self.deployment_slot,
+                deployed: deployment_slot,
+            });
+        }
+        if &self.idl_hash != idl_hash {
+            return Err(SchemaDrift::IdlHash);
+        }
+        if self.event_schema_version != event_schema_version {
+            return Err(SchemaDrift::EventSchemaVersion {
+                published: self.event_schema_version,
+                expected: event_schema_version,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// How a [`ProgramSchema`] differs from the schema a client expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDrift {
+    /// The record was published for a previous deployment of the program.
+    Stale { published: u64, deployed: u64 },
+    /// The program was deployed with a different IDL.
+    IdlHash,
+    /// The program was deployed with a different version of its events.
+    EventSchemaVersion { published: u32, expected: u32 },
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaDrift::Stale {
+                published,
+                deployed,
+            } => write!(
+                f,
+                "the schema was published for the deployment at slot {}, the program was deployed at slot {}",
+                published, deployed
+            ),
+            SchemaDrift::IdlHash => write!(f, "the program was deployed with a different IDL"),
+            SchemaDrift::EventSchemaVersion {
+                published,
+                expected,
+            } => write!(
+                f,
+                "the program's event schema version is {}, expected {}",
+                published, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaDrift {}
+
+/// The hash of a program's IDL, `Sha256(idl_json)` over the JSON written by
+/// `anchor build`.
+pub fn idl_hash(idl_json: &[u8]) -> [u8; 32] {
+    hash(idl_json).to_bytes()
+}
+
+/// Returns the address of the schema record of `program_id`, and its bump.
+pub fn schema_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SCHEMA_SEED], program_id)
+}
+
+/// Returns the address of the program data account of the upgradeable
+/// program `program_id`.
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Accounts of the `schema_publish` instruction.
+pub fn publish_account_metas(program_id: &Pubkey, authority: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(schema_address(program_id).0, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(program_data_address(program_id), false),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+    ]
+}
+
+// Decodes the instruction data of the `schema_publish` instruction.
+fn parse_args(ix_data: &[u8]) -> Result<([u8; 32], u32), ProgramError> {
+    if ix_data.len() < 36 {
+        return Err(ErrorCode::InstructionDidNotDeserialize.into());
+    }
+    Ok((
+        ix_data[..32].try_into().unwrap(),
+        u32::from_le_bytes(ix_data[32..36].try_into().unwrap()),
+    ))
+}
+
+/// Handler of the `schema_publish` instruction, recording the IDL hash and
+/// event schema version given in `ix_data` for the program's current
+/// deployment. Only the program's upgrade authority can publish.
+#[doc(hidden)]
+pub fn publish(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+    let (idl_hash, event_schema_version) = parse_args(ix_data)?;
+    let (schema, authority, program_data, system_program) = match accounts {
+        [schema, authority, program_data, system_program, ..] => {
+            (schema, authority, program_data, system_program)
+        }
+        _ => return Err(ErrorCode::AccountNotEnoughKeys.into()),
+    };
+    if !authority.is_signer {
+        return Err(ErrorCode::ConstraintSigner.into());
+    }
+    if !schema.is_writable {
+        return Err(ErrorCode::ConstraintMut.into());
+    }
+    let (address, bump) = schema_address(program_id);
+    if schema.key != &address {
+        return Err(ErrorCode::ConstraintSeeds.into());
+    }
+    if program_data.key != &program_data_address(program_id)
+        || program_data.owner != &bpf_loader_upgradeable::ID
+    {
+        return Err(ErrorCode::AccountNotProgramData.into());
+    }
+    let program_data = ProgramData::try_deserialize(&mut &program_data.try_borrow_data()?[..])?;
+    if program_data.upgrade_authority_address != Some(*authority.key) {
+        return Err(ErrorCode::SchemaAuthorityMismatch.into());
+    }
+
+    if schema.owner != program_id {
+        crate::common::create_pda(
+            program_id,
+            schema,
+            authority,
+            system_program,
+            SCHEMA_ACCOUNT_LEN,
+            &[SCHEMA_SEED, &[bump]],
+        )?;
+    }
+    let mut data = schema.try_borrow_mut_data()?;
+    data[..8].copy_from_slice(&ProgramSchema::discriminator());
+    let record = ProgramSchema {
+        idl_hash,
+        event_schema_version,
+        deployment_slot: program_data.slot,
+    };
+    record
+        .serialize(&mut &mut data[8..])
+        .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+    Ok(())
+}
@@ -0,0 +1,28 @@
+//! Hashing of seeds, for addresses derived from more or longer seeds than
+//! `find_program_address` takes, i.e. up to 16 seeds of up to 32 bytes.
+//!
+//! In `seeds` constraints, `hash(<seed>, ..)` is a single 32 byte seed
+//! hashing the given ones with [`hash`], which clients derive the same way,
+//! e.g.
+//!
+//! ```ignore
+//! #[account(seeds = [b"listing", hash(name.as_bytes(), le(edition))], bump)]
+//! pub listing: Account<'info, Listing>,
+//! ```
+
+use solana_program::hash::hashv;
+
+/// The sha256 hash of `seeds`, each prefixed by its `u32` little endian
+/// length, so that different seeds never hash the same when concatenated.
+pub fn hash(seeds: &[&[u8]]) -> [u8; 32] {
+    let lens: Vec<[u8; 4]> = seeds
+        .iter()
+        .map(|seed| (seed.len() as u32).to_le_bytes())
+        .collect();
+    let data: Vec<&[u8]> = lens
+        .iter()
+        .zip(seeds)
+        .flat_map(|(len, seed)| [&len[..], *seed])
+        .collect();
+    hashv(&data).to_bytes()
+}
@@ -0,0 +1,23 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::pubkey::Pubkey;
+
+/// An account of an accounts struct's snapshot, generated with
+/// `#[accounts(snapshot)]`. The value is the deserialized account for
+/// `Account`s, `Some` loaded account for `AccountLoader`s that could be
+/// loaded, and `()` for all other accounts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountSnapshot<T = ()> {
+    pub key: Pubkey,
+    pub lamports: u64,
+    pub value: T,
+}
+
+impl<T> AccountSnapshot<T> {
+    pub fn new(info: &AccountInfo, value: T) -> Self {
+        Self {
+            key: *info.key,
+            lamports: info.lamports(),
+            value,
+        }
+    }
+}
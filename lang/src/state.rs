@@ -1,12 +1,12 @@
 use crate::error::ErrorCode;
 #[allow(deprecated)]
 use crate::CpiAccount;
+use crate::Result;
 use crate::{
-    AccountDeserialize, AccountSerialize, Accounts, AccountsExit, Key, ToAccountInfo,
+    AccountDeserialize, AccountSerialize, Accounts, AccountsExit, Bumps, Key, ToAccountInfo,
     ToAccountInfos, ToAccountMetas,
 };
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
@@ -65,6 +65,14 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Clone> ProgramState<'a, T> {
     }
 }
 
+#[allow(deprecated)]
+impl<'info, T> Bumps for ProgramState<'info, T>
+where
+    T: AccountSerialize + AccountDeserialize + Clone,
+{
+    type Bumps = ();
+}
+
 #[allow(deprecated)]
 impl<'info, T> Accounts<'info> for ProgramState<'info, T>
 where
@@ -75,13 +83,13 @@ where
         program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
         let account = &accounts[0];
         *accounts = &accounts[1..];
-        ProgramState::try_from(program_id, account)
+        Ok(ProgramState::try_from(program_id, account)?)
     }
 }
 
@@ -156,7 +164,7 @@ where
 impl<'info, T: AccountSerialize + AccountDeserialize + Clone> AccountsExit<'info>
     for ProgramState<'info, T>
 {
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         let info = self.to_account_info();
         let mut data = info.try_borrow_mut_data()?;
         let dst: &mut [u8] = &mut data;
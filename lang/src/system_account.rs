@@ -1,7 +1,7 @@
 use crate::error::ErrorCode;
+use crate::Result;
 use crate::*;
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
@@ -27,24 +27,28 @@ impl<'info> SystemAccount<'info> {
     }
 }
 
+impl<'info> Bumps for SystemAccount<'info> {
+    type Bumps = ();
+}
+
 impl<'info> Accounts<'info> for SystemAccount<'info> {
     #[inline(never)]
     fn try_accounts(
         _program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
         let account = &accounts[0];
         *accounts = &accounts[1..];
-        SystemAccount::try_from(account)
+        Ok(SystemAccount::try_from(account)?)
     }
 }
 
 impl<'info> AccountsExit<'info> for SystemAccount<'info> {
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         // No-op.
         Ok(())
     }
@@ -0,0 +1,101 @@
+use crate::error::ErrorCode;
+use crate::Result;
+use crate::*;
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar;
+use std::ops::Deref;
+
+/// An account owned by the sysvar program. Unlike [`Sysvar`](./struct.Sysvar.html),
+/// the account's data isn't deserialized, so any sysvar can be accepted,
+/// including ones without typed support.
+#[derive(Debug, Clone)]
+pub struct SysvarAccount<'info> {
+    info: AccountInfo<'info>,
+}
+
+impl<'info> SysvarAccount<'info> {
+    fn new(info: AccountInfo<'info>) -> SysvarAccount<'info> {
+        Self { info }
+    }
+
+    #[inline(never)]
+    pub fn try_from(info: &AccountInfo<'info>) -> Result<SysvarAccount<'info>, ProgramError> {
+        if *info.owner != sysvar::ID {
+            return Err(ErrorCode::AccountNotSysvarOwned.into());
+        }
+        Ok(SysvarAccount::new(info.clone()))
+    }
+}
+
+impl<'info> Bumps for SysvarAccount<'info> {
+    type Bumps = ();
+}
+
+impl<'info> Accounts<'info> for SysvarAccount<'info> {
+    #[inline(never)]
+    fn try_accounts(
+        _program_id: &Pubkey,
+        accounts: &mut &[AccountInfo<'info>],
+        _ix_data: &[u8],
+    ) -> Result<Self> {
+        if accounts.is_empty() {
+            return Err(ErrorCode::AccountNotEnoughKeys.into());
+        }
+        let account = &accounts[0];
+        *accounts = &accounts[1..];
+        Ok(SysvarAccount::try_from(account)?)
+    }
+}
+
+impl<'info> AccountsExit<'info> for SysvarAccount<'info> {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
+        // No-op.
+        Ok(())
+    }
+}
+
+impl<'info> ToAccountMetas for SysvarAccount<'info> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let is_signer = is_signer.unwrap_or(self.info.is_signer);
+        let meta = match self.info.is_writable {
+            false => AccountMeta::new_readonly(*self.info.key, is_signer),
+            true => AccountMeta::new(*self.info.key, is_signer),
+        };
+        vec![meta]
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for SysvarAccount<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.info.clone()]
+    }
+}
+
+impl<'info> ToAccountInfo<'info> for SysvarAccount<'info> {
+    fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+}
+
+impl<'info> AsRef<AccountInfo<'info>> for SysvarAccount<'info> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info> Deref for SysvarAccount<'info> {
+    type Target = AccountInfo<'info>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.info
+    }
+}
+
+impl<'info> Key for SysvarAccount<'info> {
+    fn key(&self) -> Pubkey {
+        *self.info.key
+    }
+}
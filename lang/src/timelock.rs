@@ -0,0 +1,233 @@
+//! Timelocked admin actions, e.g. upgrades or parameter changes that users
+//! should be able to see coming.
+//!
+//! An authority's timelock is a program account at the address derived from
+//! `[TIMELOCK_SEED, authority]`, holding at most one queued action. Programs
+//! built with `#[program(timelock)]` get two instructions managing it:
+//! `timelock_queue`, queueing an action, see [`queue_instruction`], and
+//! `timelock_cancel`, dropping the queued action, see
+//! [`cancel_account_metas`]. The timelock is created by its first queue,
+//! paid for by its authority.
+//!
+//! The timelocked instruction itself executes the action. Its accounts
+//! struct marks the timelock with
+//!
+//! ```ignore
+//! #[account(mut, timelock = config.timelock, min_delay = 86_400)]
+//! pub timelock: UncheckedAccount<'info>,
+//! ```
+//!
+//! which only passes if the timelock is the one given, the queued action is
+//! this instruction of this program with the same args, see
+//! [`action_hash`], and it was queued at least
+//! `min_delay` seconds ago. The action is then consumed, so it must be
+//! queued again to be executed again.
+
+use crate::error::ErrorCode;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{hash, hashv};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+
+/// Seed prefix of timelock addresses.
+pub const TIMELOCK_SEED: &[u8] = b"anchor:timelock";
+
+// The authority, the queued action's hash, all zeros if none, and the unix
+// timestamp it was queued at.
+const TIMELOCK_ACCOUNT_LEN: usize = 32 + 32 + 8;
+
+/// The state of a timelock account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timelock {
+    pub authority: Pubkey,
+    /// The hash of the queued action, see [`action_hash`].
+    pub action: Option<[u8; 32]>,
+    /// Unix timestamp the action was queued at.
+    pub queued_at: i64,
+}
+
+impl Timelock {
+    /// Decodes the timelock account `info` of the program `program_id`.
+    pub fn try_from_account(program_id: &Pubkey, info: &AccountInfo) -> Result<Self, ProgramError> {
+        if info.owner != program_id {
+            return Err(ErrorCode::AccountNotProgramOwned.into());
+        }
+        let data = info.try_borrow_data()?;
+        if data.len() < TIMELOCK_ACCOUNT_LEN {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        let action: [u8; 32] = data[32..64].try_into().unwrap();
+        Ok(Self {
+            authority: Pubkey::new_from_array(data[..32].try_into().unwrap()),
+            action: match action == [0; 32] {
+                true => None,
+                false => Some(action),
+            },
+            queued_at: i64::from_le_bytes(data[64..TIMELOCK_ACCOUNT_LEN].try_into().unwrap()),
+        })
+    }
+
+    /// Unix timestamp from which the queued action can be executed by an
+    /// instruction with the given `min_delay`.
+    pub fn executable_at(&self, min_delay: i64) -> Option<i64> {
+        self.action?;
+        self.queued_at.checked_add(min_delay)
+    }
+}
+
+/// Returns the timelock address of `authority` and its bump.
+pub fn timelock_address(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TIMELOCK_SEED, authority.as_ref()], program_id)
+}
+
+/// The action executed by the instruction of the program `program_id` with
+/// the data `ix_data`, i.e. its sighash followed by its serialized args.
+pub fn action_hash(program_id: &Pubkey, ix_data: &[u8]) -> [u8; 32] {
+    hashv(&[TIMELOCK_SEED, program_id.as_ref(), ix_data]).to_bytes()
+}
+
+/// The `timelock_queue` instruction of the authority, queueing `action` on
+/// the timelock of its program.
+pub fn queue_instruction(authority: &Pubkey, action: &Instruction) -> Instruction {
+    let sighash = &hash(b"global:timelock_queue").to_bytes()[..8];
+    let action_hash = action_hash(&action.program_id, &action.data);
+    Instruction {
+        program_id: action.program_id,
+        accounts: queue_account_metas(&action.program_id, authority),
+        data: [sighash, &action_hash[..]].concat(),
+    }
+}
+
+/// Accounts of the `timelock_queue` instruction.
+pub fn queue_account_metas(program_id: &Pubkey, authority: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(timelock_address(program_id, authority).0, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+    ]
+}
+
+/// Accounts of the `timelock_cancel` instruction.
+pub fn cancel_account_metas(program_id: &Pubkey, authority: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(timelock_address(program_id, authority).0, false),
+        AccountMeta::new_readonly(*authority, true),
+    ]
+}
+
+// Checks that `timelock` is the authority's timelock and that the authority
+// signed.
+fn check_authority(
+    program_id: &Pubkey,
+    timelock: &AccountInfo,
+    authority: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    if !authority.is_signer {
+        return Err(ErrorCode::ConstraintSigner.into());
+    }
+    if !timelock.is_writable {
+        return Err(ErrorCode::ConstraintMut.into());
+    }
+    let (address, bump) = timelock_address(program_id, authority.key);
+    if timelock.key != &address {
+        return Err(ErrorCode::ConstraintSeeds.into());
+    }
+    Ok(bump)
+}
+
+/// Handler of the `timelock_queue` instruction, queueing the action hash in
+/// `ix_data` on the authority's timelock, which is created if needed.
+#[doc(hidden)]
+pub fn queue(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+    let action: [u8; 32] = ix_data
+        .get(..32)
+        .and_then(|action| action.try_into().ok())
+        .ok_or(ErrorCode::InstructionDidNotDeserialize)?;
+    if action == [0; 32] {
+        return Err(ErrorCode::InstructionDidNotDeserialize.into());
+    }
+    let (timelock, authority, system_program) = match accounts {
+        [timelock, authority, system_program, ..] => (timelock, authority, system_program),
+        _ => return Err(ErrorCode::AccountNotEnoughKeys.into()),
+    };
+    let bump = check_authority(program_id, timelock, authority)?;
+    if timelock.owner != program_id {
+        let seeds: &[&[u8]] = &[TIMELOCK_SEED, authority.key.as_ref(), &[bump]];
+        crate::common::create_pda(
+            program_id,
+            timelock,
+            authority,
+            system_program,
+            TIMELOCK_ACCOUNT_LEN,
+            seeds,
+        )?;
+    } else if Timelock::try_from_account(program_id, timelock)?
+        .action
+        .is_some()
+    {
+        return Err(ErrorCode::TimelockActionPending.into());
+    }
+
+    let now = crate::clock::get()?.unix_timestamp;
+    let mut data = timelock.try_borrow_mut_data()?;
+    data[..32].copy_from_slice(authority.key.as_ref());
+    data[32..64].copy_from_slice(&action);
+    data[64..TIMELOCK_ACCOUNT_LEN].copy_from_slice(&now.to_le_bytes());
+    Ok(())
+}
+
+/// Handler of the `timelock_cancel` instruction, dropping the action queued
+/// on the authority's timelock.
+#[doc(hidden)]
+pub fn cancel(program_id: &Pubkey, accounts: &[AccountInfo], _ix_data: &[u8]) -> ProgramResult {
+    let (timelock, authority) = match accounts {
+        [timelock, authority, ..] => (timelock, authority),
+        _ => return Err(ErrorCode::AccountNotEnoughKeys.into()),
+    };
+    check_authority(program_id, timelock, authority)?;
+    if Timelock::try_from_account(program_id, timelock)?
+        .action
+        .is_none()
+    {
+        return Err(ErrorCode::ConstraintTimelock.into());
+    }
+    timelock.try_borrow_mut_data()?[32..64].copy_from_slice(&[0; 32]);
+    Ok(())
+}
+
+/// Checks that the action queued on `timelock`, which must be the account at
+/// `expected`, is the instruction with the given sighash and args, and that
+/// it was queued at least `min_delay` seconds ago. The action is then
+/// consumed.
+#[doc(hidden)]
+pub fn execute(
+    program_id: &Pubkey,
+    timelock: &AccountInfo,
+    expected: &Pubkey,
+    sighash: &[u8; 8],
+    args: &[u8],
+    min_delay: i64,
+) -> ProgramResult {
+    if timelock.key != expected {
+        return Err(ErrorCode::ConstraintTimelock.into());
+    }
+    if !timelock.is_writable {
+        return Err(ErrorCode::ConstraintMut.into());
+    }
+    let state = Timelock::try_from_account(program_id, timelock)?;
+    let action = hashv(&[TIMELOCK_SEED, program_id.as_ref(), sighash, args]).to_bytes();
+    if state.action != Some(action) {
+        return Err(ErrorCode::ConstraintTimelock.into());
+    }
+    let executable_at = state
+        .executable_at(min_delay)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if crate::clock::get()?.unix_timestamp < executable_at {
+        return Err(ErrorCode::TimelockNotElapsed.into());
+    }
+    timelock.try_borrow_mut_data()?[32..64].copy_from_slice(&[0; 32]);
+    Ok(())
+}
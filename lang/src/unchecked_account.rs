@@ -1,5 +1,6 @@
 use crate::error::ErrorCode;
-use crate::{Accounts, AccountsExit, Key, ToAccountInfo, ToAccountInfos, ToAccountMetas};
+use crate::Result;
+use crate::{Accounts, AccountsExit, Bumps, Key, ToAccountInfo, ToAccountInfos, ToAccountMetas};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::AccountMeta;
@@ -15,6 +16,118 @@ impl<'info> UncheckedAccount<'info> {
     pub fn try_from(acc_info: AccountInfo<'info>) -> Self {
         Self(acc_info)
     }
+
+    /// The account's lamports, with checked arithmetic for changing them.
+    pub fn unchecked_lamports(&self) -> UncheckedLamports<'_, 'info> {
+        UncheckedLamports(&self.0)
+    }
+
+    /// Assigns the account to `owner`. The account must be writable and its
+    /// data zeroed, otherwise the runtime refuses the change.
+    pub fn unchecked_assign(&self, owner: &Pubkey) -> ProgramResult {
+        if !self.is_writable {
+            return Err(ErrorCode::AccountNotMutable.into());
+        }
+        if self.try_borrow_data()?.iter().any(|byte| *byte != 0) {
+            return Err(ErrorCode::AccountNotZeroed.into());
+        }
+        self.0.assign(owner);
+        Ok(())
+    }
+
+    /// Resizes the account's data to `new_len` bytes, zeroing any added
+    /// bytes. The data can grow by at most 10 KiB in an instruction, and
+    /// only accounts given to the executing program can be resized, since
+    /// the data's length is stored in the program's input.
+    ///
+    /// The account must hold enough lamports to stay rent exempt at its new
+    /// size. Resizing accounts is only supported by solana 1.9 onwards, so
+    /// this requires the `solana-1-9` feature.
+    #[cfg(feature = "solana-1-9")]
+    pub fn unchecked_realloc(&self, new_len: usize) -> ProgramResult {
+        if !self.is_writable {
+            return Err(ErrorCode::AccountNotMutable.into());
+        }
+        // The runtime stores the length of the data at the start of the
+        // instruction in the padding preceding the key, and leaves 10 KiB of
+        // room after it.
+        let original_len = unsafe { *(self.key as *const Pubkey as *const u32).offset(-1) };
+        if new_len.saturating_sub(original_len as usize)
+            > solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE
+        {
+            return Err(ErrorCode::AccountReallocTooLarge.into());
+        }
+        self.0.realloc(new_len, true)
+    }
+}
+
+/// Lamports of an [`UncheckedAccount`], see
+/// [`UncheckedAccount::unchecked_lamports`]. Changes fail if the account
+/// isn't writable or the balance would overflow.
+pub struct UncheckedLamports<'a, 'info>(&'a AccountInfo<'info>);
+
+impl<'a, 'info> UncheckedLamports<'a, 'info> {
+    pub fn get(&self) -> Result<u64, ProgramError> {
+        Ok(**self.0.try_borrow_lamports()?)
+    }
+
+    pub fn add(&self, amount: u64) -> ProgramResult {
+        add_lamports(self.0, amount)
+    }
+
+    /// Fails with `InsufficientFunds` if the account holds less than
+    /// `amount`.
+    pub fn sub(&self, amount: u64) -> ProgramResult {
+        sub_lamports(self.0, amount)
+    }
+
+    /// Moves `amount` lamports to `to`, which must be writable too. Neither
+    /// balance changes if the transfer fails.
+    pub fn transfer_to(&self, to: &AccountInfo<'info>, amount: u64) -> ProgramResult {
+        if !self.0.is_writable || !to.is_writable {
+            return Err(ErrorCode::AccountNotMutable.into());
+        }
+        let from_lamports = self
+            .get()?
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        if self.0.key == to.key {
+            return Ok(());
+        }
+        let to_lamports = to
+            .lamports()
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        **self.0.try_borrow_mut_lamports()? = from_lamports;
+        **to.try_borrow_mut_lamports()? = to_lamports;
+        Ok(())
+    }
+}
+
+fn add_lamports(account: &AccountInfo, amount: u64) -> ProgramResult {
+    if !account.is_writable {
+        return Err(ErrorCode::AccountNotMutable.into());
+    }
+    let mut lamports = account.try_borrow_mut_lamports()?;
+    **lamports = lamports
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+fn sub_lamports(account: &AccountInfo, amount: u64) -> ProgramResult {
+    if !account.is_writable {
+        return Err(ErrorCode::AccountNotMutable.into());
+    }
+    let mut lamports = account.try_borrow_mut_lamports()?;
+    **lamports = lamports
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    Ok(())
+}
+
+impl<'info> Bumps for UncheckedAccount<'info> {
+    type Bumps = ();
 }
 
 impl<'info> Accounts<'info> for UncheckedAccount<'info> {
@@ -22,7 +135,7 @@ impl<'info> Accounts<'info> for UncheckedAccount<'info> {
         _program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         _ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         if accounts.is_empty() {
             return Err(ErrorCode::AccountNotEnoughKeys.into());
         }
@@ -56,7 +169,7 @@ impl<'info> ToAccountInfo<'info> for UncheckedAccount<'info> {
 }
 
 impl<'info> AccountsExit<'info> for UncheckedAccount<'info> {
-    fn exit(&self, _program_id: &Pubkey) -> ProgramResult {
+    fn exit(&self, _program_id: &Pubkey) -> Result<()> {
         // no-op
         Ok(())
     }
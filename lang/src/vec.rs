@@ -1,7 +1,7 @@
-use crate::{Accounts, ToAccountInfos, ToAccountMetas};
+use crate::Result;
+use crate::{Accounts, Bumps, ToAccountInfos, ToAccountMetas};
 use solana_program::account_info::AccountInfo;
 use solana_program::instruction::AccountMeta;
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 
 impl<'info, T: ToAccountInfos<'info>> ToAccountInfos<'info> for Vec<T> {
@@ -20,12 +20,16 @@ impl<T: ToAccountMetas> ToAccountMetas for Vec<T> {
     }
 }
 
+impl<T> Bumps for Vec<T> {
+    type Bumps = ();
+}
+
 impl<'info, T: Accounts<'info>> Accounts<'info> for Vec<T> {
     fn try_accounts(
         program_id: &Pubkey,
         accounts: &mut &[AccountInfo<'info>],
         ix_data: &[u8],
-    ) -> Result<Self, ProgramError> {
+    ) -> Result<Self> {
         let mut vec: Vec<T> = Vec::new();
         T::try_accounts(program_id, accounts, ix_data).map(|item| vec.push(item))?;
         Ok(vec)
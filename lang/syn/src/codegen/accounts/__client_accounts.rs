@@ -1,6 +1,11 @@
-use crate::{AccountField, AccountsStruct, Ty};
+use crate::{
+    AccountField, AccountsStruct, ConstraintInitGroup, ConstraintSeedsGroup, Field, InitKind,
+    SysvarTy, Ty,
+};
 use heck::SnakeCase;
+use proc_macro2::TokenTree;
 use quote::quote;
+use syn::Expr;
 
 // Generates the private `__client_accounts` mod implementation, containing
 // a generated struct mapping 1-1 to the `Accounts` struct, except with
@@ -70,6 +75,61 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
             }
         })
         .collect();
+    let accounts_len: Vec<proc_macro2::TokenStream> = accs
+        .fields
+        .iter()
+        .map(|f: &AccountField| match f {
+            AccountField::CompositeField(s) => {
+                let symbol: proc_macro2::TokenStream = format!(
+                    "__client_accounts_{0}::{1}",
+                    s.symbol.to_snake_case(),
+                    s.symbol,
+                )
+                .parse()
+                .unwrap();
+                quote! { #symbol::ACCOUNTS_LEN }
+            }
+            AccountField::Field(_) => quote! { 1 },
+        })
+        .collect();
+    let from_keys: Vec<proc_macro2::TokenStream> = accs
+        .fields
+        .iter()
+        .map(|f: &AccountField| match f {
+            AccountField::CompositeField(s) => {
+                let name = &s.ident;
+                quote! {
+                    let #name = anchor_lang::FromAccountKeys::from_account_keys(__anchor_keys)?;
+                }
+            }
+            AccountField::Field(f) => {
+                let name = &f.ident;
+                quote! {
+                    let (#name, __anchor_rest) = __anchor_keys.split_first()?;
+                    *__anchor_keys = __anchor_rest;
+                }
+            }
+        })
+        .collect();
+    let field_values: Vec<proc_macro2::TokenStream> = accs
+        .fields
+        .iter()
+        .map(|f: &AccountField| match f {
+            AccountField::CompositeField(s) => {
+                let name = &s.ident;
+                quote! { #name }
+            }
+            AccountField::Field(f) => {
+                let name = &f.ident;
+                quote! { #name: *#name }
+            }
+        })
+        .collect();
+    let accounts_len = match accounts_len.is_empty() {
+        true => quote! { 0 },
+        false => quote! { #(#accounts_len)+* },
+    };
+    let account_resolves = generate_account_resolves(accs);
     // Re-export all composite account structs (i.e. other structs deriving
     // accounts embedded into this struct. Required because, these embedded
     // structs are *not* visible from the #[program] macro, which is responsible
@@ -111,14 +171,20 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         /// `accounts` module (also generated), which re-exports this.
         pub(crate) mod #account_mod_name {
             use super::*;
+            use anchor_lang::__private::traits::*;
             use anchor_lang::prelude::borsh;
             #(#re_exports)*
 
-            #[derive(anchor_lang::AnchorSerialize)]
+            #[derive(anchor_lang::AnchorSerialize, Debug, Clone, PartialEq, Eq, Hash)]
             pub struct #name {
                 #(#account_struct_fields),*
             }
 
+            impl #name {
+                /// Number of account metas the accounts serialize to.
+                pub const ACCOUNTS_LEN: usize = #accounts_len;
+            }
+
             #[automatically_derived]
             impl anchor_lang::ToAccountMetas for #name {
                 fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
@@ -129,6 +195,298 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                     account_metas
                 }
             }
+
+            #[automatically_derived]
+            impl anchor_lang::FromAccountKeys for #name {
+                #[allow(unused_variables)]
+                fn from_account_keys(__anchor_keys: &mut &[anchor_lang::solana_program::pubkey::Pubkey]) -> Option<Self> {
+                    #(#from_keys)*
+                    Some(Self {
+                        #(#field_values),*
+                    })
+                }
+            }
+
+            #[automatically_derived]
+            impl anchor_lang::ResolveAccounts for #name {
+                #[allow(unused_variables)]
+                fn resolve_accounts<E: From<anchor_lang::solana_program::program_error::ProgramError>>(
+                    &mut self,
+                    program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                    fetch: &mut dyn FnMut(&anchor_lang::solana_program::pubkey::Pubkey) -> std::result::Result<Option<Vec<u8>>, E>,
+                ) -> std::result::Result<(), E> {
+                    #account_resolves
+                    Ok(())
+                }
+            }
         }
     }
 }
+
+// The unset accounts joined by a `has_one` constraint, read from the
+// accounts storing them.
+fn generate_relation_resolves(accs: &AccountsStruct) -> Vec<proc_macro2::TokenStream> {
+    // The account types of generic structs aren't nameable from the client
+    // struct.
+    match accs.generics.type_params().next() {
+        Some(_) => vec![],
+        None => accs
+            .fields
+            .iter()
+            .filter_map(|f| match f {
+                AccountField::Field(f) if is_typed(f) => Some(f),
+                _ => None,
+            })
+            .flat_map(|f| {
+                f.has_one_targets()
+                    .into_iter()
+                    .filter(move |target| {
+                        *target != &f.ident
+                            && accs.fields.iter().any(|af| {
+                                matches!(af, AccountField::Field(sibling) if &sibling.ident == *target)
+                            })
+                    })
+                    .map(move |target| {
+                        let name = &f.ident;
+                        let account_ty = f.account_ty();
+                        quote! {
+                            if self.#target == anchor_lang::solana_program::pubkey::Pubkey::default()
+                                && self.#name != anchor_lang::solana_program::pubkey::Pubkey::default()
+                            {
+                                if let Some(data) = fetch(&self.#name)? {
+                                    let account = <#account_ty as anchor_lang::AccountDeserialize>::try_deserialize(&mut &data[..])?;
+                                    match anchor_lang::__private::HasOneKey::has_one_key(&account.#target) {
+                                        Some(key) if key != anchor_lang::solana_program::pubkey::Pubkey::default() => {
+                                            self.#target = key;
+                                            resolved = true;
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                            }
+                        }
+                    })
+            })
+            .collect(),
+    }
+}
+
+// Resolves the composite fields, and fills in the unset sysvars and
+// programs, whose addresses are known from their types. Then fills in the
+// accounts stored in other accounts, the PDAs whose seeds and the associated
+// token accounts whose wallet and mint are (now) set, until no more can be
+// resolved.
+fn generate_account_resolves(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let composite_resolves = accs.fields.iter().filter_map(|f| match f {
+        AccountField::CompositeField(s) => {
+            let name = &s.ident;
+            Some(quote! {
+                self.#name.resolve_accounts(program_id, fetch)?;
+            })
+        }
+        AccountField::Field(_) => None,
+    });
+    let fields: Vec<&Field> = accs
+        .fields
+        .iter()
+        .filter_map(|f| match f {
+            AccountField::Field(f) => Some(f),
+            AccountField::CompositeField(_) => None,
+        })
+        .collect();
+    // The program types of generic structs aren't nameable from the client
+    // struct.
+    let is_generic = accs.generics.type_params().next().is_some();
+    let known_addresses = fields.iter().filter_map(|f| {
+        let address = match &f.ty {
+            Ty::Sysvar(ty) => {
+                let module = match ty {
+                    SysvarTy::Clock => quote! { clock },
+                    SysvarTy::Rent => quote! { rent },
+                    SysvarTy::EpochSchedule => quote! { epoch_schedule },
+                    SysvarTy::Fees => quote! { fees },
+                    SysvarTy::RecentBlockhashes => quote! { recent_blockhashes },
+                    SysvarTy::SlotHashes => quote! { slot_hashes },
+                    SysvarTy::SlotHistory => quote! { slot_history },
+                    SysvarTy::StakeHistory => quote! { stake_history },
+                    SysvarTy::Instructions => quote! { instructions },
+                    SysvarTy::Rewards => quote! { rewards },
+                };
+                quote! { anchor_lang::solana_program::sysvar::#module::ID }
+            }
+            Ty::Program(ty) if !is_generic => {
+                let program = &ty.account_type_path;
+                quote! { <#program as anchor_lang::Id>::id() }
+            }
+            _ => return None,
+        };
+        let name = &f.ident;
+        Some(quote! {
+            if self.#name == anchor_lang::solana_program::pubkey::Pubkey::default() {
+                self.#name = #address;
+            }
+        })
+    });
+    let sibling = |expr: &Expr| match expr {
+        syn::Expr::Path(p) => p
+            .path
+            .get_ident()
+            .filter(|ident| fields.iter().any(|f| &f.ident == *ident))
+            .cloned(),
+        _ => None,
+    };
+    let associated_tokens = fields.iter().filter_map(|f| {
+        let (wallet, mint) = match (&f.constraints.associated_token, &f.constraints.init) {
+            (Some(c), _) => (&c.wallet, &c.mint),
+            (
+                None,
+                Some(ConstraintInitGroup {
+                    kind: InitKind::AssociatedToken { owner, mint },
+                    ..
+                }),
+            ) => (owner, mint),
+            _ => return None,
+        };
+        let wallet = sibling(wallet)?;
+        let mint = sibling(mint)?;
+        let name = &f.ident;
+        Some(quote! {
+            if self.#name == anchor_lang::solana_program::pubkey::Pubkey::default()
+                && self.#wallet != anchor_lang::solana_program::pubkey::Pubkey::default()
+                && self.#mint != anchor_lang::solana_program::pubkey::Pubkey::default()
+            {
+                self.#name = anchor_spl::associated_token::get_associated_token_address(&self.#wallet, &self.#mint);
+                resolved = true;
+            }
+        })
+    });
+    let field_idents: Vec<&syn::Ident> = fields.iter().map(|f| &f.ident).collect();
+    let arg_idents: Vec<syn::Ident> = accs
+        .instruction_api
+        .iter()
+        .flatten()
+        .filter_map(|arg| match arg {
+            Expr::Type(arg) => match &*arg.expr {
+                Expr::Path(path) => path.path.get_ident().cloned(),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let pdas = fields.iter().filter_map(|f| {
+        let seeds = f.constraints.seeds.as_ref()?;
+        let mut keys = vec![];
+        let client_seeds = ConstraintSeedsGroup {
+            seeds: seeds
+                .seeds
+                .iter()
+                .map(|seed| client_seed(seed, &field_idents, &arg_idents, &mut keys))
+                .collect::<Option<_>>()?,
+            ..seeds.clone()
+        }
+        .seed_bytes();
+        let name = &f.ident;
+        Some(quote! {
+            if self.#name == anchor_lang::solana_program::pubkey::Pubkey::default()
+                #(&& self.#keys != anchor_lang::solana_program::pubkey::Pubkey::default())*
+            {
+                let seeds: &[&[u8]] = &[#client_seeds];
+                self.#name = anchor_lang::solana_program::pubkey::Pubkey::find_program_address(seeds, program_id).0;
+                resolved = true;
+            }
+        })
+    });
+    let derived: Vec<proc_macro2::TokenStream> = generate_relation_resolves(accs)
+        .into_iter()
+        .chain(pdas)
+        .chain(associated_tokens)
+        .collect();
+    let derived = match derived.is_empty() {
+        true => quote! {},
+        false => quote! {
+            loop {
+                let mut resolved = false;
+                #(#derived)*
+                if !resolved {
+                    break;
+                }
+            }
+        },
+    };
+    quote! {
+        #(#composite_resolves)*
+        #(#known_addresses)*
+        #derived
+    }
+}
+
+// The seed as read from the client struct, e.g. `self.authority.as_ref()`
+// for `authority.key().as_ref()`, adding the accounts it reads to `keys`.
+// Returns `None` if the seed reads an account's data or an instruction arg.
+fn client_seed(
+    seed: &Expr,
+    fields: &[&syn::Ident],
+    args: &[syn::Ident],
+    keys: &mut Vec<syn::Ident>,
+) -> Option<Expr> {
+    if let Some(hashed) = ConstraintSeedsGroup::hash_seed(seed) {
+        let hashed = hashed
+            .iter()
+            .map(|seed| client_seed(seed, fields, args, keys))
+            .collect::<Option<Vec<Expr>>>()?;
+        return Some(syn::parse_quote! { hash(#(#hashed),*) });
+    }
+    if let Some(key) = account_key(seed).filter(|key| fields.contains(key)) {
+        keys.push(key.clone());
+        return Some(syn::parse_quote! { self.#key.as_ref() });
+    }
+    match reads_any(quote! { #seed }, fields, args) {
+        true => None,
+        false => Some(seed.clone()),
+    }
+}
+
+// The account whose key `seed` is, e.g. `authority` for
+// `authority.key().as_ref()` or `&authority.key`.
+fn account_key(seed: &Expr) -> Option<&syn::Ident> {
+    match seed {
+        Expr::Reference(reference) => account_key(&reference.expr),
+        Expr::MethodCall(call)
+            if call.args.is_empty()
+                && ["key", "to_account_info", "as_ref", "to_bytes"]
+                    .iter()
+                    .any(|method| call.method == method) =>
+        {
+            account_key(&call.receiver)
+        }
+        Expr::Field(field) if matches!(&field.member, syn::Member::Named(key) if key == "key") => {
+            account_key(&field.base)
+        }
+        Expr::Path(path) => path.path.get_ident(),
+        _ => None,
+    }
+}
+
+// Whether the tokens name one of the accounts or args.
+fn reads_any(
+    tokens: proc_macro2::TokenStream,
+    fields: &[&syn::Ident],
+    args: &[syn::Ident],
+) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Group(group) => reads_any(group.stream(), fields, args),
+        TokenTree::Ident(ident) => fields.contains(&&ident) || args.contains(&ident),
+        _ => false,
+    })
+}
+
+fn is_typed(f: &Field) -> bool {
+    matches!(
+        f.ty,
+        Ty::Account(_)
+            | Ty::AccountLoader(_)
+            | Ty::ProgramAccount(_)
+            | Ty::Loader(_)
+            | Ty::CpiAccount(_)
+    )
+}
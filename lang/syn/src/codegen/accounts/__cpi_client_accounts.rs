@@ -71,6 +71,41 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         })
         .collect();
 
+    // Flags of each position, as required by the callee.
+    let account_struct_template: Vec<proc_macro2::TokenStream> = accs
+        .fields
+        .iter()
+        .map(|f: &AccountField| match f {
+            AccountField::CompositeField(s) => {
+                let symbol: proc_macro2::TokenStream = format!(
+                    "__cpi_client_accounts_{0}::{1}",
+                    s.symbol.to_snake_case(),
+                    s.symbol,
+                )
+                .parse()
+                .unwrap();
+                quote! {
+                    anchor_lang::AccountMetaTemplate::Composite(
+                        <#symbol<'static> as anchor_lang::ToAccountMetas>::metas_template
+                    )
+                }
+            }
+            AccountField::Field(f) => {
+                let is_signer = match f.ty {
+                    Ty::Signer => true,
+                    _ => f.constraints.is_signer(),
+                };
+                let is_writable = f.constraints.is_mutable();
+                quote! {
+                    anchor_lang::AccountMetaTemplate::Account {
+                        is_signer: #is_signer,
+                        is_writable: #is_writable,
+                    }
+                }
+            }
+        })
+        .collect();
+
     let account_struct_infos: Vec<proc_macro2::TokenStream> = accs
         .fields
         .iter()
@@ -134,13 +169,27 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         /// `cpi::accounts` module (also generated), which re-exports this.
         pub(crate) mod #account_mod_name {
             use super::*;
+            use anchor_lang::__private::traits::*;
 
             #(#re_exports)*
 
+            #[derive(Clone, Debug)]
             pub struct #name#generics {
                 #(#account_struct_fields),*
             }
 
+            // Two sets of accounts are equal if they reference the same
+            // accounts, since `AccountInfo`s can't be compared themselves.
+            #[automatically_derived]
+            impl#generics PartialEq for #name#generics {
+                fn eq(&self, other: &Self) -> bool {
+                    self.to_account_metas(None) == other.to_account_metas(None)
+                }
+            }
+
+            #[automatically_derived]
+            impl#generics Eq for #name#generics {}
+
             #[automatically_derived]
             impl#generics anchor_lang::ToAccountMetas for #name#generics {
                 fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
@@ -148,6 +197,14 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                     #(#account_struct_metas)*
                     account_metas
                 }
+
+                fn metas_template() -> &'static [anchor_lang::AccountMetaTemplate] {
+                    // A constant, so that the template is `'static`.
+                    const TEMPLATE: &[anchor_lang::AccountMetaTemplate] = &[
+                        #(#account_struct_template),*
+                    ];
+                    TEMPLATE
+                }
             }
 
             #[automatically_derived]
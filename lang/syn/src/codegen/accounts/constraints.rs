@@ -1,4 +1,5 @@
 use crate::*;
+#[cfg(feature = "deprecated-literal-constraint")]
 use proc_macro2_diagnostics::SpanDiagnosticExt;
 use quote::quote;
 use syn::Expr;
@@ -8,18 +9,100 @@ pub fn generate(f: &Field) -> proc_macro2::TokenStream {
 
     let rent = constraints
         .iter()
-        .any(|c| matches!(c, Constraint::RentExempt(ConstraintRentExempt::Enforce)))
-        .then(|| quote! { let __anchor_rent = Rent::get()?; })
+        .any(|c| {
+            matches!(
+                c,
+                Constraint::RentExempt(
+                    ConstraintRentExempt::Enforce | ConstraintRentExempt::TopUp { .. }
+                )
+            )
+        })
+        .then(|| {
+            quote! {
+                let __anchor_rent = <anchor_lang::solana_program::sysvar::rent::Rent as anchor_lang::compat::GetSysvar>::get_sysvar()?;
+            }
+        })
         .unwrap_or_else(|| quote! {});
 
-    let checks: Vec<proc_macro2::TokenStream> = constraints
+    // `init` and `zero` rebind the field to the deserialized account, so
+    // they run before the field's account info is bound once for all the
+    // other constraints.
+    let (rebinds, checks): (Vec<&Constraint>, Vec<&Constraint>) = constraints
         .iter()
+        .partition(|c| matches!(c, Constraint::Init(_) | Constraint::Zeroed(_)));
+    let rebinds: Vec<proc_macro2::TokenStream> = rebinds
+        .into_iter()
+        .map(|c| generate_constraint(f, c))
+        .collect();
+    let info = checks.iter().any(|c| uses_info(f, c)).then(|| {
+        let field = &f.ident;
+        let info = info_ident(f);
+        quote! {
+            let #info = #field.to_account_info();
+        }
+    });
+    // `when` gates all checks but `mut`, as the account is written on exit
+    // either way.
+    let (mutable, checks): (Vec<&Constraint>, Vec<&Constraint>) = match f.constraints.when() {
+        Some(_) => checks
+            .into_iter()
+            .partition(|c| matches!(c, Constraint::Mut(_))),
+        None => (Vec::new(), checks),
+    };
+    let mutable: Vec<proc_macro2::TokenStream> = mutable
+        .into_iter()
+        .map(|c| generate_constraint(f, c))
+        .collect();
+    let checks: Vec<proc_macro2::TokenStream> = checks
+        .into_iter()
         .map(|c| generate_constraint(f, c))
         .collect();
 
-    quote! {
-        #rent
-        #(#checks)*
+    match f.constraints.when() {
+        Some(w) => {
+            let cond = &w.cond;
+            quote! {
+                #(#rebinds)*
+                #info
+                #(#mutable)*
+                if #cond {
+                    #rent
+                    #(#checks)*
+                }
+            }
+        }
+        None => quote! {
+            #rent
+            #(#rebinds)*
+            #info
+            #(#checks)*
+        },
+    }
+}
+
+// The binding of the field's account info shared by its constraints.
+fn info_ident(f: &Field) -> proc_macro2::Ident {
+    quote::format_ident!("__anchor_info_{}", f.ident)
+}
+
+// True if the constraint's generated code reads the field's account info.
+fn uses_info(f: &Field, c: &Constraint) -> bool {
+    match c {
+        Constraint::Mut(_)
+        | Constraint::Signer(_)
+        | Constraint::Owner(_)
+        | Constraint::RentExempt(
+            ConstraintRentExempt::Enforce | ConstraintRentExempt::TopUp { .. },
+        )
+        | Constraint::Seeds(_)
+        | Constraint::Executable(_)
+        | Constraint::State(_)
+        | Constraint::Close(_)
+        | Constraint::Address(_)
+        | Constraint::AssociatedToken(_)
+        | Constraint::Timelock(_) => true,
+        Constraint::HasOne(_) => matches!(f.ty, Ty::Loader(_) | Ty::AccountLoader(_)),
+        _ => false,
     }
 }
 
@@ -57,6 +140,13 @@ pub fn linearize(c_group: &ConstraintGroup) -> Vec<Constraint> {
         close,
         address,
         associated_token,
+        price_feed_max_age,
+        program_stable,
+        timelock,
+        active_window,
+        assign_to: _,
+        validate_after: _,
+        when: _,
     } = c_group.clone();
 
     let mut constraints = Vec::new();
@@ -100,6 +190,18 @@ pub fn linearize(c_group: &ConstraintGroup) -> Vec<Constraint> {
     if let Some(c) = address {
         constraints.push(Constraint::Address(c));
     }
+    if let Some(c) = price_feed_max_age {
+        constraints.push(Constraint::PriceFeedMaxAge(c));
+    }
+    if let Some(c) = program_stable {
+        constraints.push(Constraint::ProgramStable(c));
+    }
+    if let Some(c) = timelock {
+        constraints.push(Constraint::Timelock(c));
+    }
+    if let Some(c) = active_window {
+        constraints.push(Constraint::ActiveWindow(c));
+    }
     constraints
 }
 
@@ -120,6 +222,10 @@ fn generate_constraint(f: &Field, c: &Constraint) -> proc_macro2::TokenStream {
         Constraint::Close(c) => generate_constraint_close(f, c),
         Constraint::Address(c) => generate_constraint_address(f, c),
         Constraint::AssociatedToken(c) => generate_constraint_associated_token(f, c),
+        Constraint::PriceFeedMaxAge(c) => generate_constraint_price_feed_max_age(f, c),
+        Constraint::ProgramStable(c) => generate_constraint_program_stable(f, c),
+        Constraint::Timelock(c) => generate_constraint_timelock(f, c),
+        Constraint::ActiveWindow(c) => generate_constraint_active_window(c),
     }
 }
 
@@ -132,16 +238,35 @@ fn generate_constraint_composite(_f: &CompositeField, c: &Constraint) -> proc_ma
 }
 
 fn generate_constraint_address(f: &Field, c: &ConstraintAddress) -> proc_macro2::TokenStream {
-    let field = &f.ident;
-    let addr = &c.address;
+    let info = info_ident(f);
+    let addr = match c.zero_copy_base {
+        false => {
+            let addr = &c.address;
+            quote! { #addr }
+        }
+        true => load_address_base(&c.address),
+    };
     let error = generate_custom_error(&c.error, quote! { ConstraintAddress });
     quote! {
-        if #field.to_account_info().key != &#addr {
+        if #info.key != &#addr {
             return Err(#error);
         }
     }
 }
 
+// Rewrites `<account>.<field>..` to read the field from the loaded zero-copy
+// account.
+fn load_address_base(address: &Expr) -> proc_macro2::TokenStream {
+    match address {
+        Expr::Field(field) => {
+            let member = &field.member;
+            let base = load_address_base(&field.base);
+            quote! { #base.#member }
+        }
+        base => quote! { #base.load()? },
+    }
+}
+
 pub fn generate_constraint_init(f: &Field, c: &ConstraintInitGroup) -> proc_macro2::TokenStream {
     generate_constraint_init_group(f, c)
 }
@@ -165,20 +290,20 @@ pub fn generate_constraint_zeroed(f: &Field, _c: &ConstraintZeroed) -> proc_macr
 }
 
 pub fn generate_constraint_close(f: &Field, c: &ConstraintClose) -> proc_macro2::TokenStream {
-    let field = &f.ident;
+    let info = info_ident(f);
     let target = &c.sol_dest;
     quote! {
-        if #field.to_account_info().key == #target.to_account_info().key {
+        if #info.key == #target.to_account_info().key {
             return Err(anchor_lang::__private::ErrorCode::ConstraintClose.into());
         }
     }
 }
 
 pub fn generate_constraint_mut(f: &Field, c: &ConstraintMut) -> proc_macro2::TokenStream {
-    let ident = &f.ident;
+    let info = info_ident(f);
     let error = generate_custom_error(&c.error, quote! { ConstraintMut });
     quote! {
-        if !#ident.to_account_info().is_writable {
+        if !#info.is_writable {
             return Err(#error);
         }
     }
@@ -187,30 +312,61 @@ pub fn generate_constraint_mut(f: &Field, c: &ConstraintMut) -> proc_macro2::Tok
 pub fn generate_constraint_has_one(f: &Field, c: &ConstraintHasOne) -> proc_macro2::TokenStream {
     let target = c.join_target.clone();
     let ident = &f.ident;
-    let field = match &f.ty {
-        Ty::Loader(_) => quote! {#ident.load()?},
-        Ty::AccountLoader(_) => quote! {#ident.load()?},
-        _ => quote! {#ident},
-    };
+    let info = info_ident(f);
     let error = generate_custom_error(&c.error, quote! { ConstraintHasOne });
+    let account_ty = match &f.ty {
+        Ty::Loader(ty) => &ty.account_type_path,
+        Ty::AccountLoader(ty) => &ty.account_type_path,
+        _ => {
+            // An unset optional key only fails without `allow_none`.
+            let none = match c.allow_none {
+                true => quote! {},
+                false => quote! { None => return Err(#error), },
+            };
+            return quote! {
+                match anchor_lang::__private::HasOneKey::has_one_key(&#ident.#target) {
+                    Some(key) if &key != #target.to_account_info().key => return Err(#error),
+                    #none
+                    _ => {}
+                }
+            };
+        }
+    };
+    // Zero copy accounts read the key straight from the account data at the
+    // field's offset, instead of loading the whole account.
+    let offset: proc_macro2::TokenStream = format!("__anchor_offset_{}", quote! {#target})
+        .parse()
+        .unwrap();
     quote! {
-        if &#field.#target != #target.to_account_info().key {
-            return Err(#error);
+        {
+            // Type checks the target field, like a `load()` would.
+            let _: fn(&#account_ty) -> &anchor_lang::solana_program::pubkey::Pubkey =
+                |account| &account.#target;
+            let __anchor_offset = 8 + <#account_ty>::#offset;
+            let __anchor_data = #info
+                .try_borrow_data()
+                .map_err(|_| anchor_lang::__private::ErrorCode::AccountBorrowedMutably)?;
+            let __anchor_key = __anchor_data
+                .get(__anchor_offset..__anchor_offset + 32)
+                .ok_or(anchor_lang::__private::ErrorCode::AccountDidNotDeserialize)?;
+            if __anchor_key != #target.to_account_info().key.as_ref() {
+                return Err(#error);
+            }
         }
     }
 }
 
 pub fn generate_constraint_signer(f: &Field, c: &ConstraintSigner) -> proc_macro2::TokenStream {
-    let ident = &f.ident;
-    let info = match f.ty {
-        Ty::AccountInfo => quote! { #ident },
-        Ty::ProgramAccount(_) => quote! { #ident.to_account_info() },
-        Ty::Account(_) => quote! { #ident.to_account_info() },
-        Ty::Loader(_) => quote! { #ident.to_account_info() },
-        Ty::AccountLoader(_) => quote! { #ident.to_account_info() },
-        Ty::CpiAccount(_) => quote! { #ident.to_account_info() },
+    match f.ty {
+        Ty::AccountInfo
+        | Ty::ProgramAccount(_)
+        | Ty::Account(_)
+        | Ty::Loader(_)
+        | Ty::AccountLoader(_)
+        | Ty::CpiAccount(_) => {}
         _ => panic!("Invalid syntax: signer cannot be specified."),
-    };
+    }
+    let info = info_ident(f);
     let error = generate_custom_error(&c.error, quote! { ConstraintSigner });
     quote! {
         if !#info.is_signer {
@@ -223,11 +379,13 @@ pub fn generate_constraint_literal(c: &ConstraintLiteral) -> proc_macro2::TokenS
     let lit: proc_macro2::TokenStream = {
         let lit = &c.lit;
         let constraint = lit.value().replace("\"", "");
-        let message = format!(
-            "Deprecated. Should be used with constraint: #[account(constraint = {})]",
-            constraint,
-        );
-        lit.span().warning(message).emit_as_item_tokens();
+        #[cfg(feature = "deprecated-literal-constraint")]
+        lit.span()
+            .warning(format!(
+                "Deprecated. Should be used with constraint: #[account(constraint = {})]",
+                constraint,
+            ))
+            .emit_as_item_tokens();
         constraint.parse().unwrap()
     };
     quote! {
@@ -248,11 +406,11 @@ pub fn generate_constraint_raw(c: &ConstraintRaw) -> proc_macro2::TokenStream {
 }
 
 pub fn generate_constraint_owner(f: &Field, c: &ConstraintOwner) -> proc_macro2::TokenStream {
-    let ident = &f.ident;
+    let info = info_ident(f);
     let owner_address = &c.owner_address;
     let error = generate_custom_error(&c.error, quote! { ConstraintOwner });
     quote! {
-        if #ident.to_account_info().owner != &#owner_address {
+        if #info.owner != &#owner_address {
             return Err(#error);
         }
     }
@@ -262,10 +420,7 @@ pub fn generate_constraint_rent_exempt(
     f: &Field,
     c: &ConstraintRentExempt,
 ) -> proc_macro2::TokenStream {
-    let ident = &f.ident;
-    let info = quote! {
-        #ident.to_account_info()
-    };
+    let info = info_ident(f);
     match c {
         ConstraintRentExempt::Skip => quote! {},
         ConstraintRentExempt::Enforce => quote! {
@@ -273,26 +428,54 @@ pub fn generate_constraint_rent_exempt(
                 return Err(anchor_lang::__private::ErrorCode::ConstraintRentExempt.into());
             }
         },
+        ConstraintRentExempt::TopUp { payer } => quote! {
+            // Fund the account for rent exemption if it has fallen short,
+            // e.g. after growing its data.
+            let __anchor_shortfall =
+                anchor_lang::rent::lamports_needed_for_exemption(&__anchor_rent, &#info)?;
+            if __anchor_shortfall > 0 {
+                anchor_lang::cpi_trace::invoke(
+                    "transfer",
+                    &anchor_lang::solana_program::system_instruction::transfer(
+                        #payer.to_account_info().key,
+                        #info.key,
+                        __anchor_shortfall,
+                    ),
+                    &[
+                        #payer.to_account_info(),
+                        #info.clone(),
+                        system_program.to_account_info(),
+                    ],
+                )?;
+            }
+        },
     }
 }
 
 fn generate_constraint_init_group(f: &Field, c: &ConstraintInitGroup) -> proc_macro2::TokenStream {
-    let payer = {
-        let p = &c.payer;
-        quote! {
-            let payer = #p.to_account_info();
+    let (payer, funding) = match (c.is_self_funded(), c.split_payers()) {
+        (true, _) => (quote! {}, Funding::SelfFunded),
+        (false, Some(payers)) => (
+            quote! {
+                let payers = [#(#payers.to_account_info()),*];
+            },
+            Funding::SplitPayers,
+        ),
+        (false, None) => {
+            let p = &c.payer;
+            (
+                quote! {
+                    let payer = #p.to_account_info();
+                },
+                Funding::Payer,
+            )
         }
     };
 
     let seeds_with_nonce = match &c.seeds {
         None => quote! {},
         Some(c) => {
-            let s = &mut c.seeds.clone();
-            // If the seeds came with a trailing comma, we need to chop it off
-            // before we interpolate them below.
-            if let Some(pair) = s.pop() {
-                s.push_value(pair.into_value());
-            }
+            let s = &c.seed_bytes();
             let maybe_seeds_plus_comma = (!s.is_empty()).then(|| {
                 quote! { #s, }
             });
@@ -303,7 +486,7 @@ fn generate_constraint_init_group(f: &Field, c: &ConstraintInitGroup) -> proc_ma
                         [
                             #maybe_seeds_plus_comma
                             &[
-                                Pubkey::find_program_address(
+                                anchor_lang::solana_program::pubkey::Pubkey::find_program_address(
                                     &[#s],
                                     program_id,
                                 ).1
@@ -321,17 +504,26 @@ fn generate_constraint_init_group(f: &Field, c: &ConstraintInitGroup) -> proc_ma
             }
         }
     };
-    generate_init(f, c.if_needed, seeds_with_nonce, payer, &c.space, &c.kind)
+    generate_init(
+        f,
+        c.if_needed,
+        seeds_with_nonce,
+        payer,
+        funding,
+        &c.space,
+        &c.kind,
+    )
 }
 
 fn generate_constraint_seeds(f: &Field, c: &ConstraintSeedsGroup) -> proc_macro2::TokenStream {
-    let name = &f.ident;
-    let s = &mut c.seeds.clone();
-    // If the seeds came with a trailing comma, we need to chop it off
-    // before we interpolate them below.
-    if let Some(pair) = s.pop() {
-        s.push_value(pair.into_value());
-    }
+    let info = info_ident(f);
+    let ident = &f.ident;
+    // The bumps of fields gated by `when` are only found if they're checked.
+    let record_bump = match f.constraints.when() {
+        None => quote! { __bumps.#ident = __bump; },
+        Some(_) => quote! { __bumps.#ident = Some(__bump); },
+    };
+    let s = &c.seed_bytes();
 
     // If the bump is provided with init *and target*, then force it to be the
     // canonical bump.
@@ -342,47 +534,41 @@ fn generate_constraint_seeds(f: &Field, c: &ConstraintSeedsGroup) -> proc_macro2
                 &[#s],
                 program_id,
             );
-            if #name.to_account_info().key != &__program_signer {
+            if #info.key != &__program_signer {
                 return Err(anchor_lang::__private::ErrorCode::ConstraintSeeds.into());
             }
             if __bump != #b {
                 return Err(anchor_lang::__private::ErrorCode::ConstraintSeeds.into());
             }
+            #record_bump
         }
     } else {
         let maybe_seeds_plus_comma = (!s.is_empty()).then(|| {
             quote! { #s, }
         });
-        let seeds = match c.bump.as_ref() {
+        let bump = match c.bump.as_ref() {
             // Bump target not given. Find it.
             None => {
                 quote! {
-                    [
-                        #maybe_seeds_plus_comma
-                        &[
-                            Pubkey::find_program_address(
-                                &[#s],
-                                program_id,
-                            ).1
-                        ][..]
-                    ]
+                    anchor_lang::solana_program::pubkey::Pubkey::find_program_address(
+                        &[#s],
+                        program_id,
+                    ).1
                 }
             }
             // Bump target given. Use it.
-            Some(b) => {
-                quote! {
-                    [#maybe_seeds_plus_comma &[#b][..]]
-                }
-            }
+            Some(b) => quote! { #b },
         };
         quote! {
-            let __program_signer = Pubkey::create_program_address(
-                &#seeds[..],
+            let __bump: u8 = #bump;
+            let __program_signer = anchor_lang::solana_program::pubkey::Pubkey::create_program_address(
+                &[#maybe_seeds_plus_comma &[__bump][..]][..],
                 program_id,
             ).map_err(|_| anchor_lang::__private::ErrorCode::ConstraintSeeds)?;
-            if #name.to_account_info().key != &__program_signer {
+            if #info.key != &__program_signer {
                 return Err(anchor_lang::__private::ErrorCode::ConstraintSeeds.into());
             }
+            #record_bump
         }
     }
 }
@@ -391,29 +577,132 @@ fn generate_constraint_associated_token(
     f: &Field,
     c: &ConstraintAssociatedToken,
 ) -> proc_macro2::TokenStream {
-    let name = &f.ident;
+    let info = info_ident(f);
     let wallet_address = &c.wallet;
     let spl_token_mint_address = &c.mint;
     quote! {
         let __associated_token_address = anchor_spl::associated_token::get_associated_token_address(&#wallet_address.key(), &#spl_token_mint_address.key());
-        if #name.to_account_info().key != &__associated_token_address {
+        if #info.key != &__associated_token_address {
             return Err(anchor_lang::__private::ErrorCode::ConstraintAssociated.into());
         }
+        // The address only proves which account this is, so the token
+        // account's own mint and owner are checked as well, unless it
+        // doesn't exist yet.
+        {
+            let __data = #info.try_borrow_data()?;
+            if !__data.is_empty() {
+                if __data.len() < 64 {
+                    return Err(anchor_lang::__private::ErrorCode::ConstraintAssociated.into());
+                }
+                if __data[..32] != #spl_token_mint_address.key().to_bytes() {
+                    return Err(anchor_lang::__private::ErrorCode::ConstraintTokenMint.into());
+                }
+                if __data[32..64] != #wallet_address.key().to_bytes() {
+                    return Err(anchor_lang::__private::ErrorCode::ConstraintTokenOwner.into());
+                }
+            }
+        }
+    }
+}
+
+fn generate_constraint_price_feed_max_age(
+    f: &Field,
+    c: &ConstraintPriceFeedMaxAge,
+) -> proc_macro2::TokenStream {
+    let name = &f.ident;
+    let max_age = &c.max_age;
+    quote! {
+        {
+            let __anchor_clock = anchor_lang::clock::get()?;
+            if __anchor_clock.slot.saturating_sub(#name.publish_slot) > #max_age {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintPriceFeedStale.into());
+            }
+        }
+    }
+}
+
+fn generate_constraint_program_stable(
+    f: &Field,
+    c: &ConstraintProgramStable,
+) -> proc_macro2::TokenStream {
+    let name = &f.ident;
+    let programdata = &c.programdata;
+    quote! {
+        #name.check_stable(&#programdata.to_account_info())?;
     }
 }
 
+// Consumes the action queued on the timelock, identified by the
+// instruction's sighash and its args bound by `try_accounts`. Accounts
+// validated without knowing their instruction can't execute any action.
+fn generate_constraint_timelock(f: &Field, c: &ConstraintTimelock) -> proc_macro2::TokenStream {
+    let info = info_ident(f);
+    let address = &c.address;
+    let min_delay = &c.min_delay;
+    quote! {
+        anchor_lang::timelock::execute(
+            program_id,
+            &#info,
+            &#address,
+            __anchor_sighash.ok_or(anchor_lang::__private::ErrorCode::ConstraintTimelock)?,
+            __anchor_ix_args,
+            #min_delay,
+        )?;
+    }
+}
+
+fn generate_constraint_active_window(c: &ConstraintActiveWindow) -> proc_macro2::TokenStream {
+    let bound = |bound: &Expr, zero_copy: bool| match zero_copy {
+        false => quote! { #bound },
+        true => load_address_base(bound),
+    };
+    let start = bound(&c.start, c.zero_copy_start);
+    let end = bound(&c.end, c.zero_copy_end);
+    quote! {
+        {
+            let __anchor_clock = anchor_lang::clock::get()?;
+            if __anchor_clock.unix_timestamp < #start {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintActiveWindowNotStarted.into());
+            }
+            if __anchor_clock.unix_timestamp >= #end {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintActiveWindowEnded.into());
+            }
+        }
+    }
+}
+
+// How the rent of an account created by `generate_create_account` is paid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Funding {
+    // A single `payer` variable is in scope.
+    Payer,
+    // A `payers` array is in scope, which split the rent evenly.
+    SplitPayers,
+    // `payer = self`, i.e., the account pays for its own initialization.
+    SelfFunded,
+}
+
 // `if_needed` is set if account allocation and initialization is optional.
+//
+// `payer` defines the payer variables for `funding`.
 pub fn generate_init(
     f: &Field,
     if_needed: bool,
     seeds_with_nonce: proc_macro2::TokenStream,
     payer: proc_macro2::TokenStream,
+    funding: Funding,
     space: &Option<Expr>,
     kind: &InitKind,
 ) -> proc_macro2::TokenStream {
     let field = &f.ident;
     let ty_decl = f.ty_decl();
     let from_account_info = f.from_account_info_unchecked(Some(kind));
+    // `init_if_needed` creates associated token accounts idempotently, so
+    // that a concurrent creation doesn't fail the instruction.
+    let create_associated_token = match if_needed {
+        false => quote! { anchor_spl::associated_token::create },
+        true => quote! { anchor_spl::associated_token::create_idempotent },
+    };
     let if_needed = if if_needed {
         quote! {true}
     } else {
@@ -426,6 +715,7 @@ pub fn generate_init(
                 quote! {anchor_spl::token::TokenAccount::LEN},
                 quote! {token_program.to_account_info().key},
                 seeds_with_nonce,
+                funding,
             );
             quote! {
                 let #field: #ty_decl = {
@@ -444,7 +734,7 @@ pub fn generate_init(
                             authority: #owner.to_account_info(),
                             rent: rent.to_account_info(),
                         };
-                        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+                        let cpi_ctx = anchor_lang::CpiContext::new(cpi_program, accounts);
                         anchor_spl::token::initialize_account(cpi_ctx)?;
                     }
 
@@ -477,8 +767,8 @@ pub fn generate_init(
                             token_program: token_program.to_account_info(),
                             rent: rent.to_account_info(),
                         };
-                        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-                        anchor_spl::associated_token::create(cpi_ctx)?;
+                        let cpi_ctx = anchor_lang::CpiContext::new(cpi_program, cpi_accounts);
+                        #create_associated_token(cpi_ctx)?;
                     }
                     let pa: #ty_decl = #from_account_info;
                     if !(!#if_needed || #field.to_account_info().owner == &anchor_lang::solana_program::system_program::ID) {
@@ -503,6 +793,7 @@ pub fn generate_init(
                 quote! {anchor_spl::token::Mint::LEN},
                 quote! {token_program.to_account_info().key},
                 seeds_with_nonce,
+                funding,
             );
             let freeze_authority = match freeze_authority {
                 Some(fa) => quote! { Option::<&anchor_lang::prelude::Pubkey>::Some(&#fa.key()) },
@@ -523,7 +814,7 @@ pub fn generate_init(
                             mint: #field.to_account_info(),
                             rent: rent.to_account_info(),
                         };
-                        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+                        let cpi_ctx = anchor_lang::CpiContext::new(cpi_program, accounts);
                         anchor_spl::token::initialize_mint(cpi_ctx, #decimals, &#owner.key(), #freeze_authority)?;
                     }
                     let pa: #ty_decl = #from_account_info;
@@ -554,12 +845,12 @@ pub fn generate_init(
                     match matches!(f.ty, Ty::Loader(_) | Ty::AccountLoader(_)) {
                         false => {
                             quote! {
-                                let space = 8 + #account_ty::default().try_to_vec().unwrap().len();
+                                let space = 8 + anchor_lang::AnchorSerialize::try_to_vec(&#account_ty::default()).unwrap().len();
                             }
                         }
                         true => {
                             quote! {
-                                let space = 8 + anchor_lang::__private::bytemuck::bytes_of(&#account_ty::default()).len();
+                                let space = 8 + <#account_ty as anchor_lang::ZeroCopy>::SIZE;
                             }
                         }
                     }
@@ -593,19 +884,43 @@ pub fn generate_init(
             } else {
                 quote! {}
             };
-            let create_account =
-                generate_create_account(field, quote! {space}, owner.clone(), seeds_with_nonce);
+            let create_account = generate_create_account(
+                field,
+                quote! {space},
+                owner.clone(),
+                seeds_with_nonce,
+                funding,
+            );
+            // Zero-copy accounts aren't deserialized up front, so an already
+            // initialized account must have its discriminator checked here,
+            // whereas a newly created one is left for `load_init`.
+            let from_account_info = match &f.ty {
+                Ty::AccountLoader(_) => {
+                    let container_ty = f.container_ty();
+                    quote! {
+                        if __anchor_create {
+                            #from_account_info
+                        } else {
+                            #container_ty::try_from_for(program_id, &#field)?
+                        }
+                    }
+                }
+                _ => from_account_info,
+            };
             quote! {
                 let #field = {
                     let actual_field = #field.to_account_info();
                     let actual_owner = actual_field.owner;
+                    // Decided before creating the account, as its owner is
+                    // updated in place by the system program.
+                    let __anchor_create = !#if_needed || actual_owner == &anchor_lang::solana_program::system_program::ID;
                     #space
-                    if !#if_needed || actual_owner == &anchor_lang::solana_program::system_program::ID {
+                    if __anchor_create {
                         #payer
                         #create_account
                     }
                     let pa: #ty_decl = #from_account_info;
-                    if !(!#if_needed || actual_owner == &anchor_lang::solana_program::system_program::ID) {
+                    if !__anchor_create {
                         if space != actual_field.data_len() {
                             return Err(anchor_lang::__private::ErrorCode::ConstraintSpace.into());
                         }
@@ -628,12 +943,88 @@ pub fn generate_init(
 //
 // `seeds_with_nonce` should be given for creating PDAs. Otherwise it's an
 // empty stream.
+//
+// For `Funding::SelfFunded` no payer is in scope and the account must
+// already hold enough lamports to be rent exempt.
 pub fn generate_create_account(
     field: &Ident,
     space: proc_macro2::TokenStream,
     owner: proc_macro2::TokenStream,
     seeds_with_nonce: proc_macro2::TokenStream,
+    funding: Funding,
 ) -> proc_macro2::TokenStream {
+    let allocate_and_assign = quote! {
+        // Allocate space.
+        anchor_lang::cpi_trace::invoke_signed(
+            "allocate",
+            &anchor_lang::solana_program::system_instruction::allocate(
+                #field.to_account_info().key,
+                #space as u64,
+            ),
+            &[
+                #field.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[#seeds_with_nonce],
+        )?;
+        // Assign to the spl token program.
+        anchor_lang::cpi_trace::invoke_signed(
+            "assign",
+            &anchor_lang::solana_program::system_instruction::assign(
+                #field.to_account_info().key,
+                #owner,
+            ),
+            &[
+                #field.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[#seeds_with_nonce],
+        )?;
+    };
+    if funding == Funding::SplitPayers {
+        return quote! {
+            // Each payer transfers its share of the rent, after which the
+            // account is allocated and assigned.
+            let __current_lamports = #field.to_account_info().lamports();
+            let required_lamports = __anchor_rent
+                .minimum_balance(#space)
+                .max(1)
+                .saturating_sub(__current_lamports);
+            let share = required_lamports / payers.len() as u64;
+            let remainder = required_lamports % payers.len() as u64;
+            for (i, payer) in payers.iter().enumerate() {
+                // The first payer covers the remainder.
+                let lamports = if i == 0 { share + remainder } else { share };
+                if lamports > 0 {
+                    anchor_lang::cpi_trace::invoke(
+                        "transfer",
+                        &anchor_lang::solana_program::system_instruction::transfer(
+                            payer.key,
+                            #field.to_account_info().key,
+                            lamports,
+                        ),
+                        &[
+                            payer.clone(),
+                            #field.to_account_info(),
+                            system_program.to_account_info(),
+                        ],
+                    )?;
+                }
+            }
+            #allocate_and_assign
+        };
+    }
+    if funding == Funding::SelfFunded {
+        return quote! {
+            // The account pays for itself out of lamports it was funded with
+            // earlier, so it only needs to be allocated and assigned.
+            let __current_lamports = #field.to_account_info().lamports();
+            if __current_lamports < __anchor_rent.minimum_balance(#space).max(1) {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintSelfFunded.into());
+            }
+            #allocate_and_assign
+        };
+    }
     quote! {
         // If the account being initialized already has lamports, then
         // return them all back to the payer so that the account has
@@ -643,7 +1034,8 @@ pub fn generate_create_account(
         if __current_lamports == 0 {
             // Create the token account with right amount of lamports and space, and the correct owner.
             let lamports = __anchor_rent.minimum_balance(#space);
-            anchor_lang::solana_program::program::invoke_signed(
+            anchor_lang::cpi_trace::invoke_signed(
+                "create_account",
                 &anchor_lang::solana_program::system_instruction::create_account(
                     payer.to_account_info().key,
                     #field.to_account_info().key,
@@ -665,7 +1057,8 @@ pub fn generate_create_account(
                 .max(1)
                 .saturating_sub(__current_lamports);
             if required_lamports > 0 {
-                anchor_lang::solana_program::program::invoke(
+                anchor_lang::cpi_trace::invoke(
+                    "transfer",
                     &anchor_lang::solana_program::system_instruction::transfer(
                         payer.to_account_info().key,
                         #field.to_account_info().key,
@@ -678,30 +1071,7 @@ pub fn generate_create_account(
                     ],
                 )?;
             }
-            // Allocate space.
-            anchor_lang::solana_program::program::invoke_signed(
-                &anchor_lang::solana_program::system_instruction::allocate(
-                    #field.to_account_info().key,
-                    #space as u64,
-                ),
-                &[
-                    #field.to_account_info(),
-                    system_program.to_account_info(),
-                ],
-                &[#seeds_with_nonce],
-            )?;
-            // Assign to the spl token program.
-            anchor_lang::solana_program::program::invoke_signed(
-                &anchor_lang::solana_program::system_instruction::assign(
-                    #field.to_account_info().key,
-                    #owner,
-                ),
-                &[
-                    #field.to_account_info(),
-                    system_program.to_account_info(),
-                ],
-                &[#seeds_with_nonce],
-            )?;
+            #allocate_and_assign
         }
     }
 }
@@ -710,9 +1080,9 @@ pub fn generate_constraint_executable(
     f: &Field,
     _c: &ConstraintExecutable,
 ) -> proc_macro2::TokenStream {
-    let name = &f.ident;
+    let info = info_ident(f);
     quote! {
-        if !#name.to_account_info().executable {
+        if !#info.executable {
             return Err(anchor_lang::__private::ErrorCode::ConstraintExecutable.into());
         }
     }
@@ -720,7 +1090,7 @@ pub fn generate_constraint_executable(
 
 pub fn generate_constraint_state(f: &Field, c: &ConstraintState) -> proc_macro2::TokenStream {
     let program_target = c.program_target.clone();
-    let ident = &f.ident;
+    let info = info_ident(f);
     let account_ty = match &f.ty {
         Ty::CpiState(ty) => &ty.account_type_path,
         _ => panic!("Invalid state constraint"),
@@ -728,10 +1098,10 @@ pub fn generate_constraint_state(f: &Field, c: &ConstraintState) -> proc_macro2:
     quote! {
         // Checks the given state account is the canonical state account for
         // the target program.
-        if #ident.to_account_info().key != &anchor_lang::CpiState::<#account_ty>::address(#program_target.to_account_info().key) {
+        if #info.key != &anchor_lang::CpiState::<#account_ty>::address(#program_target.to_account_info().key) {
             return Err(anchor_lang::__private::ErrorCode::ConstraintState.into());
         }
-        if #ident.to_account_info().owner != #program_target.to_account_info().key {
+        if #info.owner != #program_target.to_account_info().key {
             return Err(anchor_lang::__private::ErrorCode::ConstraintState.into());
         }
     }
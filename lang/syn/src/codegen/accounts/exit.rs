@@ -1,6 +1,6 @@
 use crate::codegen::accounts::{generics, ParsedGenerics};
-use crate::{AccountField, AccountsStruct};
-use quote::quote;
+use crate::{AccountField, AccountsStruct, Field, Ty};
+use quote::{format_ident, quote};
 
 // Generates the `Exit` trait implementation.
 pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
@@ -34,10 +34,31 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                     }
                 } else {
                     match f.constraints.is_mutable() {
-                        false => quote! {},
-                        true => quote! {
-                            anchor_lang::AccountsExit::exit(&self.#ident, program_id)?;
+                        false => match &f.ty {
+                            // Changes to an account not marked `mut` are
+                            // silently dropped, so catch them in debug builds.
+                            Ty::Account(_) => {
+                                let msg = format!(
+                                    "Account {} was modified but isn't mut, the changes are not persisted",
+                                    ident
+                                );
+                                quote! {
+                                    #[cfg(feature = "anchor-debug")]
+                                    if self.#ident.is_dirty()? {
+                                        anchor_lang::solana_program::msg!(#msg);
+                                        return Err(anchor_lang::__private::ErrorCode::ConstraintMut.into());
+                                    }
+                                }
+                            }
+                            _ => quote! {},
                         },
+                        true => {
+                            let assign_to = generate_assign_to(f);
+                            quote! {
+                                anchor_lang::AccountsExit::exit(&self.#ident, program_id)?;
+                                #assign_to
+                            }
+                        }
                     }
                 }
             }
@@ -46,10 +67,113 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
     quote! {
         #[automatically_derived]
         impl<#combined_generics> anchor_lang::AccountsExit<#trait_generics> for #name<#struct_generics> #where_clause{
-            fn exit(&self, program_id: &anchor_lang::solana_program::pubkey::Pubkey) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+            fn exit(&self, program_id: &anchor_lang::solana_program::pubkey::Pubkey) -> anchor_lang::Result<()> {
+                use anchor_lang::__private::traits::*;
                 #(#on_save)*
                 Ok(())
             }
         }
     }
 }
+
+// Checks the owner an `assign_to` field was expected to be assigned to during
+// the instruction.
+fn generate_assign_to(f: &Field) -> proc_macro2::TokenStream {
+    let c = match f.constraints.assign_to() {
+        None => return quote! {},
+        Some(c) => c,
+    };
+    let ident = &f.ident;
+    let owner = &c.owner;
+    let name = ident.to_string();
+    quote! {
+        {
+            let __anchor_owner = &#owner;
+            let __anchor_owner: &anchor_lang::solana_program::pubkey::Pubkey =
+                ::core::borrow::Borrow::borrow(__anchor_owner);
+            let __anchor_info = self.#ident.to_account_info();
+            if __anchor_info.owner != __anchor_owner {
+                anchor_lang::solana_program::msg!(
+                    "Account {} was expected to be assigned to {}, but is owned by {}",
+                    #name,
+                    __anchor_owner,
+                    __anchor_info.owner
+                );
+                return Err(anchor_lang::__private::ErrorCode::ConstraintAssignTo.into());
+            }
+        }
+    }
+}
+
+// Generates a marker constant per field, referred to by `#[program]` for the
+// accounts a handler modifies. Markers of fields that aren't `mut` are
+// deprecated, so that modifying those warns at compile time.
+pub fn generate_mut_markers(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let name = &accs.ident;
+    let (impl_generics, ty_generics, where_clause) = accs.generics.split_for_impl();
+
+    let markers: Vec<proc_macro2::TokenStream> = accs
+        .fields
+        .iter()
+        .map(|af: &AccountField| {
+            let ident = match af {
+                AccountField::Field(f) => &f.ident,
+                AccountField::CompositeField(s) => &s.ident,
+            };
+            let marker = format_ident!("__anchor_mut_{}", ident);
+            match af {
+                AccountField::Field(f) if !f.constraints.is_mutable() => {
+                    let note = format!(
+                        "`{}` is modified by an instruction handler but isn't marked `mut`, so the changes won't be persisted. Use #[account(mut)].",
+                        ident
+                    );
+                    quote! {
+                        #[deprecated(note = #note)]
+                        pub const #marker: () = ();
+                    }
+                }
+                _ => quote! {
+                    pub const #marker: () = ();
+                },
+            }
+        })
+        .collect();
+    quote! {
+        #[automatically_derived]
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#markers)*
+        }
+    }
+}
+
+// Implements `ReadOnlyAccounts` if no field is `mut`, provided the nested
+// accounts structs are read only too.
+pub fn generate_read_only(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let has_mut = accs.fields.iter().any(|af| match af {
+        AccountField::Field(f) => f.constraints.is_mutable(),
+        AccountField::CompositeField(_) => false,
+    });
+    if has_mut {
+        return quote! {};
+    }
+    let name = &accs.ident;
+    let (impl_generics, ty_generics, where_clause) = accs.generics.split_for_impl();
+    let mut where_clause = where_clause.cloned().unwrap_or_else(|| syn::WhereClause {
+        where_token: Default::default(),
+        predicates: Default::default(),
+    });
+    for af in &accs.fields {
+        if let AccountField::CompositeField(s) = af {
+            let ty = &s.raw_field.ty;
+            where_clause
+                .predicates
+                .push(syn::parse_quote! { #ty: anchor_lang::ReadOnlyAccounts });
+        }
+    }
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics anchor_lang::ReadOnlyAccounts for #name #ty_generics #where_clause {}
+    }
+}
@@ -0,0 +1,173 @@
+use crate::*;
+use quote::quote_spanned;
+
+// Emits a warning at compile time for each suspicious pattern found in the
+// accounts struct, by referring to a deprecated constant whose note explains
+// the issue. Only enabled with the `lints` feature.
+pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    if !cfg!(feature = "lints") {
+        return quote! {};
+    }
+    let warnings = lints(accs).into_iter().map(|(ident, note)| {
+        quote_spanned! { ident.span() =>
+            const _: () = {
+                #[deprecated(note = #note)]
+                const LINT: () = ();
+                LINT
+            };
+        }
+    });
+    quote! {
+        #(#warnings)*
+    }
+}
+
+// Suspicious patterns in the accounts struct, as the field they concern and
+// an explanation.
+pub fn lints(accs: &AccountsStruct) -> Vec<(&Ident, String)> {
+    let fields: Vec<&Field> = accs
+        .fields
+        .iter()
+        .filter_map(|af| match af {
+            AccountField::Field(f) => Some(f),
+            AccountField::CompositeField(_) => None,
+        })
+        .collect();
+    let field = |name: &str| fields.iter().find(|f| f.ident == name);
+    let mut lints = Vec::new();
+
+    // Authorities that don't sign can be impersonated by passing any account.
+    for f in &fields {
+        let name = f.ident.to_string();
+        if (name == "authority" || name.ends_with("_authority"))
+            && matches!(f.ty, Ty::AccountInfo | Ty::UncheckedAccount)
+            && !f.constraints.is_signer()
+        {
+            lints.push((
+                &f.ident,
+                format!(
+                    "`{}` looks like an authority but isn't required to sign, so any account can be passed as it. Use Signer<'info> or #[account(signer)].",
+                    name
+                ),
+            ));
+        }
+    }
+
+    // Payers of initialized accounts must sign, and be mutable to be
+    // debited.
+    let mut payers: Vec<&Field> = Vec::new();
+    for f in &fields {
+        let payer = match f.constraints.init.as_ref().and_then(|c| c.payer.as_ref()) {
+            Some(payer) => payer,
+            None => continue,
+        };
+        let targets = ConstraintPayer::split_targets(payer).unwrap_or_else(|| vec![payer.clone()]);
+        for target in targets {
+            let payer = match &target {
+                Expr::Path(p) => p
+                    .path
+                    .get_ident()
+                    .and_then(|ident| field(&ident.to_string())),
+                _ => None,
+            };
+            if let Some(payer) = payer {
+                if !payers.iter().any(|p| p.ident == payer.ident) {
+                    payers.push(payer);
+                }
+            }
+        }
+    }
+    for payer in &payers {
+        if !payer.constraints.is_mutable() {
+            lints.push((
+                &payer.ident,
+                format!(
+                    "`{}` pays for initialized accounts but isn't marked `mut`, so it can't be debited. Use #[account(mut)].",
+                    payer.ident
+                ),
+            ));
+        }
+        if payer.ty != Ty::Signer && !payer.constraints.is_signer() {
+            lints.push((
+                &payer.ident,
+                format!(
+                    "`{}` pays for initialized accounts but isn't required to sign, so the transfer will fail. Use Signer<'info>.",
+                    payer.ident
+                ),
+            ));
+        }
+    }
+
+    // Lamports are moved out of a payer through the system program, which
+    // may be nested in a composite field.
+    let has_composite = accs
+        .fields
+        .iter()
+        .any(|af| matches!(af, AccountField::CompositeField(_)));
+    if !has_composite && field("system_program").is_none() {
+        if let Some(payer) = field("payer")
+            .filter(|f| f.constraints.is_mutable())
+            .or_else(|| payers.first())
+        {
+            lints.push((
+                &payer.ident,
+                format!(
+                    "`{}` is a mutable payer but the accounts don't include the system program, which is needed to move its lamports. Add `pub system_program: Program<'info, System>`.",
+                    payer.ident
+                ),
+            ));
+        }
+    }
+
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_fields(accs: AccountsStruct) -> Vec<String> {
+        lints(&accs)
+            .into_iter()
+            .map(|(ident, _)| ident.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn authorities_must_sign() {
+        let accs: AccountsStruct = syn::parse_quote! {
+            pub struct Withdraw<'info> {
+                #[account(has_one = authority, has_one = freeze_authority)]
+                pub vault: Account<'info, Vault>,
+                pub authority: AccountInfo<'info>,
+                #[account(signer)]
+                pub freeze_authority: AccountInfo<'info>,
+                pub new_authority: Signer<'info>,
+            }
+        };
+        assert_eq!(lint_fields(accs), ["authority"]);
+    }
+
+    #[test]
+    fn payers_must_be_mutable_signers_with_the_system_program() {
+        let accs: AccountsStruct = syn::parse_quote! {
+            pub struct Initialize<'info> {
+                #[account(init, payer = payer, space = 8)]
+                pub counter: Account<'info, Counter>,
+                pub payer: AccountInfo<'info>,
+            }
+        };
+        assert_eq!(lint_fields(accs), ["payer", "payer", "payer"]);
+
+        let accs: AccountsStruct = syn::parse_quote! {
+            pub struct Initialize<'info> {
+                #[account(init, payer = payer, space = 8)]
+                pub counter: Account<'info, Counter>,
+                #[account(mut)]
+                pub payer: Signer<'info>,
+                pub system_program: Program<'info, System>,
+            }
+        };
+        assert!(lint_fields(accs).is_empty());
+    }
+}
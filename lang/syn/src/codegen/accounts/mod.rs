@@ -7,8 +7,10 @@ use syn::{GenericParam, PredicateLifetime, WhereClause, WherePredicate};
 
 mod __client_accounts;
 mod __cpi_client_accounts;
-mod constraints;
+pub(crate) mod constraints;
 mod exit;
+pub(crate) mod lints;
+mod snapshot;
 mod to_account_infos;
 mod to_account_metas;
 mod try_accounts;
@@ -18,6 +20,10 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
     let impl_to_account_infos = to_account_infos::generate(accs);
     let impl_to_account_metas = to_account_metas::generate(accs);
     let impl_exit = exit::generate(accs);
+    let impl_mut_markers = exit::generate_mut_markers(accs);
+    let impl_read_only = exit::generate_read_only(accs);
+    let lints = lints::generate(accs);
+    let snapshot = snapshot::generate(accs);
 
     let __client_accounts_mod = __client_accounts::generate(accs);
     let __cpi_client_accounts_mod = __cpi_client_accounts::generate(accs);
@@ -27,6 +33,10 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         #impl_to_account_infos
         #impl_to_account_metas
         #impl_exit
+        #impl_mut_markers
+        #impl_read_only
+        #lints
+        #snapshot
 
         #__client_accounts_mod
         #__cpi_client_accounts_mod
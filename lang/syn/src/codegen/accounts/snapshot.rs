@@ -0,0 +1,114 @@
+use crate::{AccountField, AccountsStruct, Ty};
+use quote::quote;
+
+// Generates the `<Name>Snapshot` struct of `#[accounts(snapshot)]` structs,
+// holding each account's key, lamports and deserialized value, along with
+// its `From<&<Name>>` implementation.
+pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    if !accs.snapshot {
+        return quote! {};
+    }
+    let name = &accs.ident;
+    let vis = &accs.vis;
+    let snapshot = snapshot_ident(name);
+    let (fields, values): (Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>) = accs
+        .fields
+        .iter()
+        .map(|af| match af {
+            AccountField::CompositeField(s) => {
+                let ident = &s.ident;
+                let ty = match &s.raw_field.ty {
+                    syn::Type::Path(ty) => {
+                        let mut path = ty.path.clone();
+                        let segment = path.segments.last_mut().unwrap();
+                        segment.ident = snapshot_ident(&segment.ident);
+                        segment.arguments = syn::PathArguments::None;
+                        path
+                    }
+                    _ => panic!("Invalid composite field type"),
+                };
+                (
+                    quote! { pub #ident: #ty },
+                    quote! { #ident: #ty::from(&accounts.#ident) },
+                )
+            }
+            AccountField::Field(f) => {
+                let ident = &f.ident;
+                let (ty, value) = match &f.ty {
+                    Ty::Account(ty) => {
+                        let path = &ty.account_type_path;
+                        (
+                            quote! { #path },
+                            quote! { <#path as Clone>::clone(&accounts.#ident) },
+                        )
+                    }
+                    Ty::ProgramAccount(ty) => {
+                        let path = &ty.account_type_path;
+                        (
+                            quote! { #path },
+                            quote! { <#path as Clone>::clone(&accounts.#ident) },
+                        )
+                    }
+                    Ty::CpiAccount(ty) => {
+                        let path = &ty.account_type_path;
+                        (
+                            quote! { #path },
+                            quote! { <#path as Clone>::clone(&accounts.#ident) },
+                        )
+                    }
+                    Ty::AccountLoader(ty) => {
+                        let path = &ty.account_type_path;
+                        (
+                            quote! { Option<#path> },
+                            quote! { accounts.#ident.load().ok().map(|account| *account) },
+                        )
+                    }
+                    Ty::Loader(ty) => {
+                        let path = &ty.account_type_path;
+                        (
+                            quote! { Option<#path> },
+                            quote! { accounts.#ident.load().ok().map(|account| *account) },
+                        )
+                    }
+                    _ => (quote! { () }, quote! { () }),
+                };
+                (
+                    quote! { pub #ident: anchor_lang::AccountSnapshot<#ty> },
+                    quote! {
+                        #ident: anchor_lang::AccountSnapshot::new(
+                            &accounts.#ident.to_account_info(),
+                            #value,
+                        )
+                    },
+                )
+            }
+        })
+        .unzip();
+    let (impl_generics, ty_generics, where_clause) = accs.generics.split_for_impl();
+    let doc = format!(
+        " A snapshot of [`{}`]'s accounts, e.g. to compare them before and after an instruction.",
+        name
+    );
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, PartialEq)]
+        #vis struct #snapshot {
+            #(#fields,)*
+        }
+
+        #[automatically_derived]
+        impl #impl_generics From<&#name #ty_generics> for #snapshot #where_clause {
+            #[allow(deprecated)]
+            fn from(accounts: &#name #ty_generics) -> Self {
+                use anchor_lang::__private::traits::*;
+                Self {
+                    #(#values,)*
+                }
+            }
+        }
+    }
+}
+
+fn snapshot_ident(name: &syn::Ident) -> syn::Ident {
+    quote::format_ident!("{}Snapshot", name)
+}
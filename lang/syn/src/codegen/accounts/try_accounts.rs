@@ -1,5 +1,5 @@
 use crate::codegen::accounts::{constraints, generics, ParsedGenerics};
-use crate::{AccountField, AccountsStruct};
+use crate::{AccountField, AccountsStruct, CompositeField, ConstraintRemainingAccounts};
 use quote::quote;
 use syn::Expr;
 
@@ -22,10 +22,12 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                 AccountField::CompositeField(s) => {
                     let name = &s.ident;
                     let ty = &s.raw_field.ty;
+                    let checkpoint = profile_checkpoint(format!("{}.{}: deserialize", accs.ident, name));
                     quote! {
+                        #checkpoint
                         #[cfg(feature = "anchor-debug")]
-                        ::solana_program::log::sol_log(stringify!(#name));
-                        let #name: #ty = anchor_lang::Accounts::try_accounts(program_id, accounts, ix_data)?;
+                        anchor_lang::solana_program::log::sol_log(stringify!(#name));
+                        let #name: #ty = anchor_lang::Accounts::try_accounts_for_instruction(program_id, accounts, __anchor_sighash, ix_data, &mut __bumps.#name)?;
                     }
                 }
                 AccountField::Field(f) => {
@@ -39,10 +41,12 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                             *accounts = &accounts[1..];
                         }
                     } else {
+                        let checkpoint = profile_checkpoint(format!("{}.{}: deserialize", accs.ident, &f.ident));
                         let name = f.typed_ident();
                         quote! {
+                            #checkpoint
                             #[cfg(feature = "anchor-debug")]
-                            ::solana_program::log::sol_log(stringify!(#name));
+                            anchor_lang::solana_program::log::sol_log(stringify!(#name));
                             let #name = anchor_lang::Accounts::try_accounts(program_id, accounts, ix_data)?;
                         }
                     }
@@ -52,7 +56,12 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         .collect();
 
     let constraints = generate_constraints(accs);
+    let remaining_accounts = accs
+        .remaining_accounts
+        .as_ref()
+        .map(generate_remaining_accounts);
     let accounts_instance = generate_accounts_instance(accs);
+    let end_checkpoint = profile_checkpoint(format!("{}: end", name));
 
     let ix_de = match &accs.instruction_api {
         None => quote! {},
@@ -84,21 +93,66 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         }
     };
 
+    // Timelocked actions are identified by the instruction's sighash and
+    // args, bound before the args are deserialized.
+    let timelock_action = accs
+        .fields
+        .iter()
+        .any(|af| matches!(af, AccountField::Field(f) if f.constraints.is_timelock()))
+        .then(|| {
+            quote! {
+                let __anchor_ix_args = ix_data;
+            }
+        });
+
+    let bumps = generate_bumps(accs);
+    let bumps_name = bumps_ident(&accs.ident);
+
     quote! {
+        #bumps
+
+        #[automatically_derived]
+        impl<#combined_generics> anchor_lang::Bumps for #name<#struct_generics> #where_clause {
+            type Bumps = #bumps_name;
+        }
+
         #[automatically_derived]
         impl<#combined_generics> anchor_lang::Accounts<#trait_generics> for #name<#struct_generics> #where_clause {
-            #[inline(never)]
             fn try_accounts(
                 program_id: &anchor_lang::solana_program::pubkey::Pubkey,
                 accounts: &mut &[anchor_lang::solana_program::account_info::AccountInfo<'info>],
                 ix_data: &[u8],
-            ) -> std::result::Result<Self, anchor_lang::solana_program::program_error::ProgramError> {
+            ) -> anchor_lang::Result<Self> {
+                Self::try_accounts_for_instruction(program_id, accounts, None, ix_data, &mut #bumps_name::default())
+            }
+
+            fn try_accounts_with_bumps(
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                accounts: &mut &[anchor_lang::solana_program::account_info::AccountInfo<'info>],
+                ix_data: &[u8],
+                bumps: &mut #bumps_name,
+            ) -> anchor_lang::Result<Self> {
+                Self::try_accounts_for_instruction(program_id, accounts, None, ix_data, bumps)
+            }
+
+            #[inline(never)]
+            fn try_accounts_for_instruction(
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                accounts: &mut &[anchor_lang::solana_program::account_info::AccountInfo<'info>],
+                __anchor_sighash: Option<&[u8; 8]>,
+                ix_data: &[u8],
+                __bumps: &mut #bumps_name,
+            ) -> anchor_lang::Result<Self> {
+                use anchor_lang::__private::traits::*;
+                #timelock_action
                 // Deserialize instruction, if declared.
                 #ix_de
                 // Deserialize each account.
                 #(#deser_fields)*
                 // Execute accounts constraints.
                 #constraints
+                #remaining_accounts
+                #end_checkpoint
                 // Success. Return the validated accounts.
                 Ok(#accounts_instance)
             }
@@ -106,37 +160,170 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
     }
 }
 
-pub fn generate_constraints(accs: &AccountsStruct) -> proc_macro2::TokenStream {
-    let non_init_fields: Vec<&AccountField> =
-        accs.fields.iter().filter(|af| !is_init(af)).collect();
+// The `<Struct>Bumps` struct, holding the bump of each field checked with a
+// `seeds` constraint and the bumps of each nested accounts struct.
+fn generate_bumps(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let name = bumps_ident(&accs.ident);
+    let doc = format!(
+        " The bumps found while validating [`{}`], see `anchor_lang::Bumps`.",
+        accs.ident
+    );
+    let mut fields = vec![];
+    let mut gets = vec![];
+    for af in &accs.fields {
+        match af {
+            AccountField::Field(f) if f.constraints.seeds.is_some() => {
+                let ident = &f.ident;
+                let field_name = ident.to_string();
+                match f.constraints.when().is_some() {
+                    false => {
+                        fields.push(quote! { pub #ident: u8 });
+                        gets.push(quote! { #field_name => Some(self.#ident) });
+                    }
+                    true => {
+                        fields.push(quote! { pub #ident: Option<u8> });
+                        gets.push(quote! { #field_name => self.#ident });
+                    }
+                }
+            }
+            AccountField::Field(_) => {}
+            AccountField::CompositeField(s) => {
+                let ident = &s.ident;
+                let ty = composite_bumps_ty(s);
+                fields.push(quote! { pub #ident: #ty });
+                let prefix = format!("{}.", ident);
+                gets.push(quote! {
+                    _ if name.starts_with(#prefix) => {
+                        anchor_lang::AccountBumps::get(&self.#ident, &name[#prefix.len()..])
+                    }
+                });
+            }
+        }
+    }
+    quote! {
+        #[doc = #doc]
+        #[derive(Default, Clone, Debug)]
+        pub struct #name {
+            #(#fields,)*
+        }
 
-    // Deserialization for each pda init field. This must be after
-    // the inital extraction from the accounts slice and before access_checks.
-    let init_fields: Vec<proc_macro2::TokenStream> = accs
-        .fields
-        .iter()
-        .filter_map(|af| match af {
-            AccountField::CompositeField(_s) => None,
-            AccountField::Field(f) => match is_init(af) {
-                false => None,
-                true => Some(f),
-            },
+        #[automatically_derived]
+        impl anchor_lang::AccountBumps for #name {
+            fn get(&self, name: &str) -> Option<u8> {
+                match name {
+                    #(#gets,)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+fn bumps_ident(ident: &syn::Ident) -> syn::Ident {
+    quote::format_ident!("{}Bumps", ident)
+}
+
+// The bumps struct of a nested accounts struct, i.e. its type's path with
+// the last segment named `<Struct>Bumps`.
+fn composite_bumps_ty(s: &CompositeField) -> proc_macro2::TokenStream {
+    match &s.raw_field.ty {
+        syn::Type::Path(ty) => {
+            let mut path = ty.path.clone();
+            let last = path.segments.last_mut().unwrap();
+            last.ident = bumps_ident(&last.ident);
+            last.arguments = syn::PathArguments::None;
+            quote! { #path }
+        }
+        ty => syn::Error::new_spanned(ty, "nested accounts structs must be named by a path")
+            .to_compile_error(),
+    }
+}
+
+pub fn generate_constraints(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    // Constraint checks for each account field, including the deserialization
+    // of `init` fields. These must be after the initial extraction from the
+    // accounts slice.
+    let field_checks: Vec<proc_macro2::TokenStream> = order_fields(&accs.fields)
+        .into_iter()
+        .map(|af| {
+            let constraints = match af {
+                AccountField::Field(f) => constraints::generate(f),
+                AccountField::CompositeField(s) => constraints::generate_composite(s),
+            };
+            let checkpoint =
+                profile_checkpoint(format!("{}.{}: constraints", accs.ident, af.ident()));
+            quote! {
+                #checkpoint
+                #constraints
+            }
         })
-        .map(constraints::generate)
         .collect();
 
-    // Constraint checks for each account fields.
-    let access_checks: Vec<proc_macro2::TokenStream> = non_init_fields
+    // Constraints on the whole struct, which may refer to any of the fields
+    // and so are checked last.
+    let struct_checks: Vec<proc_macro2::TokenStream> = accs
+        .struct_constraints
         .iter()
-        .map(|af: &&AccountField| match af {
-            AccountField::Field(f) => constraints::generate(f),
-            AccountField::CompositeField(s) => constraints::generate_composite(s),
-        })
+        .map(constraints::generate_constraint_raw)
         .collect();
+    let struct_checkpoint = match struct_checks.is_empty() {
+        true => quote! {},
+        false => profile_checkpoint(format!("{}: constraints", accs.ident)),
+    };
+
+    quote! {
+        #(#field_checks)*
+        #struct_checkpoint
+        #(#struct_checks)*
+    }
+}
 
+// Checks the accounts left after the struct's fields, i.e. the instruction's
+// remaining accounts when the struct is the instruction's accounts struct.
+fn generate_remaining_accounts(c: &ConstraintRemainingAccounts) -> proc_macro2::TokenStream {
+    let min = c.min.as_ref().map(|min| {
+        quote! {
+            if __anchor_remaining.len() < #min {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintRemainingAccountsCount.into());
+            }
+        }
+    });
+    let max = c.max.as_ref().map(|max| {
+        quote! {
+            if __anchor_remaining.len() > #max {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintRemainingAccountsCount.into());
+            }
+        }
+    });
+    let owner = c.owner.as_ref().map(|owner| {
+        quote! {
+            if __anchor_account.owner != &#owner {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintRemainingAccountsOwner.into());
+            }
+        }
+    });
+    let writable = c.writable.then(|| {
+        quote! {
+            if !__anchor_account.is_writable {
+                return Err(anchor_lang::__private::ErrorCode::ConstraintRemainingAccountsMut.into());
+            }
+        }
+    });
+    let each = (owner.is_some() || writable.is_some()).then(|| {
+        quote! {
+            for __anchor_account in __anchor_remaining {
+                #owner
+                #writable
+            }
+        }
+    });
     quote! {
-        #(#init_fields)*
-        #(#access_checks)*
+        {
+            let __anchor_remaining: &[anchor_lang::solana_program::account_info::AccountInfo] = *accounts;
+            #min
+            #max
+            #each
+        }
     }
 }
 
@@ -164,9 +351,183 @@ pub fn generate_accounts_instance(accs: &AccountsStruct) -> proc_macro2::TokenSt
     }
 }
 
+// Orders the fields' constraint checks. The `init` fields come first, each
+// after the other `init` fields its constraints refer to, e.g. a token
+// account after its mint, or a mint after the PDA given as its authority.
+// Fields marked `validate_after` come after their targets. Fields are
+// otherwise kept in declaration order.
+fn order_fields(fields: &[AccountField]) -> Vec<&AccountField> {
+    let fields: Vec<&AccountField> = fields
+        .iter()
+        .filter(|af| is_init(af))
+        .chain(fields.iter().filter(|af| !is_init(af)))
+        .collect();
+    let position = |ident: &proc_macro2::Ident| fields.iter().position(|af| af.ident() == ident);
+    let explicit: Vec<Vec<usize>> = fields
+        .iter()
+        .map(|af| {
+            af.constraints()
+                .validate_after()
+                .into_iter()
+                .filter_map(position)
+                .collect()
+        })
+        .collect();
+    let implicit: Vec<Vec<usize>> = fields
+        .iter()
+        .map(|af| match af {
+            AccountField::Field(f) if is_init(af) => {
+                let refs = f.constraints.init.as_ref().unwrap().references();
+                fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other)| {
+                        is_init(other) && other.ident() != &f.ident && refs.contains(other.ident())
+                    })
+                    .map(|(idx, _)| idx)
+                    .collect()
+            }
+            _ => vec![],
+        })
+        .collect();
+    let mut done = vec![false; fields.len()];
+    let mut ordered = Vec::with_capacity(fields.len());
+    while ordered.len() < fields.len() {
+        let mut pending = (0..fields.len()).filter(|idx| !done[*idx]);
+        let ready = |deps: &[usize]| deps.iter().all(|dep| done[*dep]);
+        // The dependencies can't form cycles, see `check_validation_order`,
+        // so some field is always ready.
+        let next = pending
+            .find(|idx| ready(&explicit[*idx]) && ready(&implicit[*idx]))
+            .unwrap();
+        done[next] = true;
+        ordered.push(fields[next]);
+    }
+    ordered
+}
+
+// Logs `label` followed by the remaining compute units, if the program is
+// built with the `profile` feature, so the cost of each account's
+// deserialization and constraints can be read off the logs.
+fn profile_checkpoint(label: String) -> proc_macro2::TokenStream {
+    quote! {
+        #[cfg(feature = "profile")]
+        {
+            anchor_lang::solana_program::log::sol_log(#label);
+            anchor_lang::solana_program::log::sol_log_compute_units();
+        }
+    }
+}
+
 fn is_init(af: &AccountField) -> bool {
     match af {
         AccountField::CompositeField(_s) => false,
         AccountField::Field(f) => f.constraints.init.is_some(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_fields_follow_their_dependencies() {
+        let accs: AccountsStruct = syn::parse_quote! {
+            pub struct Initialize<'info> {
+                #[account(init, payer = payer, token::mint = mint, token::authority = authority)]
+                pub token: Account<'info, TokenAccount>,
+                #[account(init, payer = payer, mint::decimals = 6, mint::authority = authority)]
+                pub mint: Account<'info, Mint>,
+                #[account(init, payer = payer, space = 8, seeds = [b"authority"], bump)]
+                pub authority: Account<'info, Authority>,
+                #[account(mut)]
+                pub payer: Signer<'info>,
+            }
+        };
+        let order: Vec<String> = order_fields(&accs.fields)
+            .iter()
+            .map(|af| af.ident().to_string())
+            .collect();
+        assert_eq!(order, ["authority", "mint", "token", "payer"]);
+    }
+
+    #[test]
+    fn fields_follow_their_validate_after_targets() {
+        let accs: AccountsStruct = syn::parse_quote! {
+            pub struct Settle<'info> {
+                #[account(init, payer = payer, space = 8, seeds = [config.seed.as_ref()], bump, validate_after = config)]
+                pub receipt: Account<'info, Receipt>,
+                #[account(validate_after = market)]
+                pub config: Account<'info, Config>,
+                pub market: Account<'info, Market>,
+                #[account(mut)]
+                pub payer: Signer<'info>,
+            }
+        };
+        let order: Vec<String> = order_fields(&accs.fields)
+            .iter()
+            .map(|af| af.ident().to_string())
+            .collect();
+        assert_eq!(order, ["market", "config", "receipt", "payer"]);
+    }
+
+    #[test]
+    fn validate_after_cycles_are_rejected() {
+        let cycle = syn::parse2::<AccountsStruct>(quote! {
+            pub struct Cycle<'info> {
+                #[account(validate_after = b)]
+                pub a: Signer<'info>,
+                #[account(validate_after = c)]
+                pub b: Signer<'info>,
+                #[account(validate_after = a)]
+                pub c: Signer<'info>,
+            }
+        });
+        assert_eq!(
+            cycle.unwrap_err().to_string(),
+            "validate_after forms a cycle: a -> b -> c -> a"
+        );
+        let unknown = syn::parse2::<AccountsStruct>(quote! {
+            pub struct Unknown<'info> {
+                #[account(validate_after = b)]
+                pub a: Signer<'info>,
+            }
+        });
+        assert_eq!(
+            unknown.unwrap_err().to_string(),
+            "validate_after must name another field of the struct"
+        );
+    }
+
+    #[test]
+    fn init_cycles_are_rejected() {
+        let cycle = syn::parse2::<AccountsStruct>(quote! {
+            pub struct Cycle<'info> {
+                #[account(init, payer = payer, mint::decimals = 6, mint::authority = token)]
+                pub mint: Account<'info, Mint>,
+                #[account(init, payer = payer, token::mint = mint, token::authority = payer)]
+                pub token: Account<'info, TokenAccount>,
+                #[account(mut)]
+                pub payer: Signer<'info>,
+            }
+        });
+        assert_eq!(
+            cycle.unwrap_err().to_string(),
+            "init constraints depend on each other in a cycle: mint -> token -> mint"
+        );
+        let with_validate_after = syn::parse2::<AccountsStruct>(quote! {
+            pub struct Cycle<'info> {
+                #[account(init, payer = payer, mint::decimals = 6, mint::authority = payer, validate_after = token)]
+                pub mint: Account<'info, Mint>,
+                #[account(init, payer = payer, token::mint = mint, token::authority = payer)]
+                pub token: Account<'info, TokenAccount>,
+                #[account(mut)]
+                pub payer: Signer<'info>,
+            }
+        });
+        assert_eq!(
+            with_validate_after.unwrap_err().to_string(),
+            "init constraints depend on each other in a cycle: mint -> token -> mint"
+        );
+    }
+}
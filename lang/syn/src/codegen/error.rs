@@ -1,19 +1,59 @@
 use crate::Error;
-use quote::quote;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
 
 pub fn generate(error: Error) -> proc_macro2::TokenStream {
     let error_enum = &error.raw_enum;
     let enum_name = &error.ident;
+    // Enums with data carrying variants can't be `#[repr(u32)]` casts, so
+    // codes are always resolved by matching on the variant.
+    let has_fields = error.codes.iter().any(|code| !code.fields.is_empty());
+    let derives: &[syn::Path] = error
+        .args
+        .as_ref()
+        .map(|args| args.derives.as_slice())
+        .unwrap_or_default();
+    // Skip the traits the enum derives regardless.
+    let enum_derives = derives.iter().filter(|path| {
+        let ident = &path.segments.last().unwrap().ident;
+        !(ident == "Debug" || ident == "Clone" || (!has_fields && ident == "Copy"))
+    });
+    let error_enum = match has_fields {
+        false => quote! {
+            #[derive(std::fmt::Debug, Clone, Copy #(, #enum_derives)*)]
+            #[repr(u32)]
+            #error_enum
+        },
+        true => {
+            let mut error_enum = error_enum.clone();
+            for variant in error_enum.variants.iter_mut() {
+                variant.discriminant = None;
+            }
+            quote! {
+                #[derive(std::fmt::Debug, Clone #(, #enum_derives)*)]
+                #error_enum
+            }
+        }
+    };
+    let error_derives: Vec<&syn::Path> = derives
+        .iter()
+        .filter(|path| {
+            let ident = &path.segments.last().unwrap().ident;
+            ident != "Debug"
+        })
+        .collect();
     // Each arm of the `match` statement for implementing `std::fmt::Display`
     // on the user defined error code.
     let variant_dispatch: Vec<proc_macro2::TokenStream> = error
-        .raw_enum
-        .variants
+        .codes
         .iter()
-        .enumerate()
-        .map(|(idx, variant)| {
-            let ident = &variant.ident;
-            let error_code = &error.codes[idx];
+        .map(|error_code| {
+            let ident = &error_code.ident;
+            let field_idents: Vec<&syn::Ident> = error_code
+                .fields
+                .iter()
+                .map(|f| f.ident.as_ref().unwrap())
+                .collect();
             let msg = match &error_code.msg {
                 None => {
                     quote! {
@@ -21,69 +61,281 @@ pub fn generate(error: Error) -> proc_macro2::TokenStream {
                     }
                 }
                 Some(msg) => {
+                    // Only pass the fields referenced by the message, since
+                    // unused named arguments are rejected by `write!`.
+                    let msg_args: Vec<&&syn::Ident> = field_idents
+                        .iter()
+                        .filter(|f| {
+                            msg.contains(&format!("{{{}}}", f))
+                                || msg.contains(&format!("{{{}:", f))
+                        })
+                        .collect();
                     quote! {
-                        write!(fmt, #msg)
+                        write!(fmt, #msg, #(#msg_args = #msg_args),*)
                     }
                 }
             };
             quote! {
-                #enum_name::#ident => #msg
+                #[allow(unused_variables)]
+                #enum_name::#ident { #(#field_idents),* } => #msg
+            }
+        })
+        .collect();
+    // Each arm of the `match` statement mapping a variant to its code and
+    // serialized fields.
+    let code_dispatch: Vec<proc_macro2::TokenStream> = error
+        .codes
+        .iter()
+        .map(|error_code| {
+            let ident = &error_code.ident;
+            let id = error_code.id;
+            let field_idents: Vec<&syn::Ident> = error_code
+                .fields
+                .iter()
+                .map(|f| f.ident.as_ref().unwrap())
+                .collect();
+            match field_idents.is_empty() {
+                true => quote! {
+                    #enum_name::#ident { .. } => #id
+                },
+                false => quote! {
+                    #enum_name::#ident { #(#field_idents),* } => {
+                        let mut data = vec![];
+                        #(
+                            anchor_lang::AnchorSerialize::serialize(#field_idents, &mut data)
+                                .expect("Error data must serialize");
+                        )*
+                        anchor_lang::solana_program::msg!(
+                            "Error data: {}",
+                            anchor_lang::__private::base64::encode(&data)
+                        );
+                        #id
+                    }
+                },
             }
         })
         .collect();
 
-    let offset = match error.args {
+    // Each arm of the `match` statement mapping a variant to its name.
+    let name_dispatch: Vec<proc_macro2::TokenStream> = error
+        .codes
+        .iter()
+        .map(|error_code| {
+            let ident = &error_code.ident;
+            let name = ident.to_string();
+            quote! {
+                #enum_name::#ident { .. } => #name
+            }
+        })
+        .collect();
+
+    let offset = match error.args.as_ref().and_then(|args| args.offset.as_ref()) {
         None => quote! { anchor_lang::__private::ERROR_CODE_OFFSET},
-        Some(args) => {
-            let offset = &args.offset;
-            quote! { #offset }
+        Some(offset) => quote! { #offset },
+    };
+
+    // The code, name and message of each variant.
+    let error_codes: Vec<proc_macro2::TokenStream> = error
+        .codes
+        .iter()
+        .map(|error_code| {
+            let id = error_code.id;
+            let name = error_code.ident.to_string();
+            let msg = error_code.msg.clone().unwrap_or_else(|| name.clone());
+            quote! { (#offset + #id, #name, #msg) }
+        })
+        .collect();
+
+    // The range of the enum's codes, with an exclusive end, against which
+    // extending enums check their own.
+    let code_range = match (
+        error.codes.iter().map(|code| code.id).min(),
+        error.codes.iter().map(|code| code.id).max(),
+    ) {
+        (Some(min), Some(max)) => {
+            let end = max + 1;
+            quote! { (#offset + #min, #offset + #end) }
         }
+        _ => quote! { (#offset, #offset) },
+    };
+
+    // Each shared error enum the program's errors extend. The array fails to
+    // type check if the shared codes overlap with the enum's own.
+    let extended = error
+        .args
+        .as_ref()
+        .map(|args| args.extends.as_slice())
+        .unwrap_or_default();
+    let extends: Vec<proc_macro2::TokenStream> = extended
+        .iter()
+        .map(|path| {
+            quote_spanned! {path.span()=>
+                const _: [(); 0] = [(); (<#path>::__ANCHOR_ERROR_CODES.0 < #enum_name::__ANCHOR_ERROR_CODES.1
+                    && #enum_name::__ANCHOR_ERROR_CODES.0 < <#path>::__ANCHOR_ERROR_CODES.1)
+                    as usize];
+
+                impl std::convert::From<#path> for Error {
+                    fn from(e: #path) -> Error {
+                        Error::ProgramError(e.into())
+                    }
+                }
+            }
+        })
+        .collect();
+
+    // The codes of the extended enums are decoded along with the enum's own.
+    let extended_tables = match extended.is_empty() {
+        true => quote! {},
+        false => quote! {
+            fn error_tables() -> Vec<&'static [(u32, &'static str, &'static str)]> {
+                let mut tables = vec![<Self as anchor_lang::error::ErrorCodes>::ERROR_CODES];
+                #(tables.extend(<#extended as anchor_lang::error::ErrorCodes>::error_tables());)*
+                tables
+            }
+        },
+    };
+
+    let codes_only = error
+        .args
+        .as_ref()
+        .map(|args| args.codes_only)
+        .unwrap_or(false);
+    let error_types = match codes_only {
+        true => quote! {},
+        false => generate_error_types(enum_name, &error_derives, &extends),
     };
 
+    quote! {
+        #error_types
+
+        #error_enum
+
+        impl #enum_name {
+            #[doc(hidden)]
+            pub const __ANCHOR_ERROR_CODES: (u32, u32) = #code_range;
+
+            /// The name of the error's variant.
+            pub fn error_name(&self) -> &'static str {
+                match self {
+                    #(#name_dispatch),*
+                }
+            }
+        }
+
+        impl anchor_lang::error::ErrorCodes for #enum_name {
+            const ERROR_CODES: &'static [(u32, &'static str, &'static str)] = &[
+                #(#error_codes),*
+            ];
+
+            #extended_tables
+        }
+
+        impl std::fmt::Display for #enum_name {
+            fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+                match self {
+                    #(#variant_dispatch),*
+                }
+            }
+        }
+
+        impl std::error::Error for #enum_name {}
+
+        impl std::convert::From<#enum_name> for anchor_lang::solana_program::program_error::ProgramError {
+            fn from(e: #enum_name) -> anchor_lang::solana_program::program_error::ProgramError {
+                let code: u32 = match &e {
+                    #(#code_dispatch),*
+                };
+                anchor_lang::solana_program::program_error::ProgramError::Custom(code + #offset)
+            }
+        }
+
+        impl std::convert::From<#enum_name> for anchor_lang::error::Error {
+            fn from(e: #enum_name) -> anchor_lang::error::Error {
+                let error_name = e.error_name();
+                let error_msg = e.to_string();
+                let error_code_number = match anchor_lang::solana_program::program_error::ProgramError::from(e) {
+                    anchor_lang::solana_program::program_error::ProgramError::Custom(code) => code,
+                    _ => unreachable!(),
+                };
+                anchor_lang::error::Error::AnchorError(anchor_lang::error::AnchorError {
+                    error_name: error_name.to_string(),
+                    error_code_number,
+                    error_msg,
+                })
+            }
+        }
+    }
+}
+
+// The program's `Error`, holding either one of its error codes or any
+// `ProgramError`, and its `Result`, returning `anchor_lang::error::Error`.
+fn generate_error_types(
+    enum_name: &syn::Ident,
+    error_derives: &[&syn::Path],
+    extends: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
     quote! {
         /// Anchor generated Result to be used as the return type for the
-        /// program.
-        pub type Result<T> = std::result::Result<T, Error>;
+        /// program, returning any of its errors, anchor's or a `ProgramError`.
+        pub type Result<T> = std::result::Result<T, anchor_lang::error::Error>;
 
         /// Anchor generated error allowing one to easily return a
         /// `ProgramError` or a custom, user defined error code by utilizing
         /// its `From` implementation.
         #[doc(hidden)]
-        #[derive(thiserror::Error, Debug)]
+        #[derive(Debug #(, #error_derives)*)]
         pub enum Error {
-            #[error(transparent)]
-            ProgramError(#[from] anchor_lang::solana_program::program_error::ProgramError),
-            #[error(transparent)]
-            ErrorCode(#[from] #enum_name),
+            ProgramError(anchor_lang::solana_program::program_error::ProgramError),
+            ErrorCode(#enum_name),
         }
 
-        #[derive(std::fmt::Debug, Clone, Copy)]
-        #[repr(u32)]
-        #error_enum
-
-        impl std::fmt::Display for #enum_name {
+        impl std::fmt::Display for Error {
             fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
                 match self {
-                    #(#variant_dispatch),*
+                    Error::ProgramError(e) => std::fmt::Display::fmt(e, fmt),
+                    Error::ErrorCode(e) => std::fmt::Display::fmt(e, fmt),
                 }
             }
         }
 
-        impl std::error::Error for #enum_name {}
+        impl std::error::Error for Error {
+            fn source(&self) -> std::option::Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    Error::ProgramError(e) => std::error::Error::source(e),
+                    Error::ErrorCode(e) => std::error::Error::source(e),
+                }
+            }
+        }
+
+        impl std::convert::From<anchor_lang::solana_program::program_error::ProgramError> for Error {
+            fn from(e: anchor_lang::solana_program::program_error::ProgramError) -> Error {
+                Error::ProgramError(e)
+            }
+        }
+
+        impl std::convert::From<#enum_name> for Error {
+            fn from(e: #enum_name) -> Error {
+                Error::ErrorCode(e)
+            }
+        }
+
+        #(#extends)*
 
         impl std::convert::From<Error> for anchor_lang::solana_program::program_error::ProgramError {
             fn from(e: Error) -> anchor_lang::solana_program::program_error::ProgramError {
                 match e {
                     Error::ProgramError(e) => e,
-                    Error::ErrorCode(c) => anchor_lang::solana_program::program_error::ProgramError::Custom(c as u32 + #offset),
+                    Error::ErrorCode(c) => c.into(),
                 }
             }
         }
 
-        impl std::convert::From<#enum_name> for anchor_lang::solana_program::program_error::ProgramError {
-            fn from(e: #enum_name) -> anchor_lang::solana_program::program_error::ProgramError {
-                let err: Error = e.into();
-                err.into()
+        impl std::convert::From<Error> for anchor_lang::error::Error {
+            fn from(e: Error) -> anchor_lang::error::Error {
+                match e {
+                    Error::ProgramError(e) => e.into(),
+                    Error::ErrorCode(c) => c.into(),
+                }
             }
         }
     }
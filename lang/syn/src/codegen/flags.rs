@@ -0,0 +1,233 @@
+use crate::Flags;
+use heck::ShoutySnakeCase;
+use quote::quote;
+
+pub fn generate(flags: Flags) -> proc_macro2::TokenStream {
+    let ident = &flags.ident;
+    let vis = &flags.raw_enum.vis;
+    let repr = &flags.repr;
+    let docs = flags
+        .raw_enum
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"));
+
+    let all_bits = flags.flags.iter().fold(0u64, |acc, f| acc | f.value);
+    let all_bits: proc_macro2::TokenStream = all_bits.to_string().parse().unwrap();
+
+    let consts: Vec<proc_macro2::TokenStream> = flags
+        .raw_enum
+        .variants
+        .iter()
+        .zip(flags.flags.iter())
+        .map(|(variant, flag)| {
+            let docs = variant
+                .attrs
+                .iter()
+                .filter(|attr| attr.path.is_ident("doc"));
+            let name: proc_macro2::TokenStream = flag
+                .ident
+                .to_string()
+                .to_shouty_snake_case()
+                .parse()
+                .unwrap();
+            let value: proc_macro2::TokenStream = flag.value.to_string().parse().unwrap();
+            quote! {
+                #(#docs)*
+                pub const #name: Self = Self(#value);
+            }
+        })
+        .collect();
+
+    let debug_names: Vec<proc_macro2::TokenStream> = flags
+        .flags
+        .iter()
+        .map(|flag| {
+            let name = flag.ident.to_string().to_shouty_snake_case();
+            let value: proc_macro2::TokenStream = flag.value.to_string().parse().unwrap();
+            quote! {
+                (#name, #value)
+            }
+        })
+        .collect();
+
+    quote! {
+        #(#docs)*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+        #[repr(transparent)]
+        #vis struct #ident(#repr);
+
+        #[automatically_derived]
+        impl #ident {
+            #(#consts)*
+
+            /// Returns a value with no flags set.
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// Returns a value with all defined flags set.
+            pub const fn all() -> Self {
+                Self(#all_bits)
+            }
+
+            /// Returns the raw value of the flags.
+            pub const fn bits(&self) -> #repr {
+                self.0
+            }
+
+            /// Converts from raw bits, returning `None` if any bit doesn't
+            /// correspond to a defined flag.
+            pub const fn from_bits(bits: #repr) -> Option<Self> {
+                if bits & !#all_bits == 0 {
+                    Some(Self(bits))
+                } else {
+                    None
+                }
+            }
+
+            /// Converts from raw bits, dropping any bit that doesn't
+            /// correspond to a defined flag.
+            pub const fn from_bits_truncate(bits: #repr) -> Self {
+                Self(bits & #all_bits)
+            }
+
+            /// Converts from raw bits, keeping any bit that doesn't
+            /// correspond to a defined flag, as deserialization does.
+            pub const fn from_bits_retain(bits: #repr) -> Self {
+                Self(bits)
+            }
+
+            /// Returns true if any bit that doesn't correspond to a defined
+            /// flag is set.
+            pub const fn has_undefined_bits(&self) -> bool {
+                self.0 & !#all_bits != 0
+            }
+
+            pub const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            /// Returns true if all flags in `other` are set.
+            pub const fn contains(&self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Returns true if any flag in `other` is set.
+            pub const fn intersects(&self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            pub fn insert(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+
+            pub fn remove(&mut self, other: Self) {
+                self.0 &= !other.0;
+            }
+
+            pub fn toggle(&mut self, other: Self) {
+                self.0 ^= other.0;
+            }
+
+            /// Inserts or removes the flags in `other` depending on `value`.
+            pub fn set(&mut self, other: Self, value: bool) {
+                if value {
+                    self.insert(other);
+                } else {
+                    self.remove(other);
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl std::ops::BitOr for #ident {
+            type Output = Self;
+            fn bitor(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+        }
+
+        #[automatically_derived]
+        impl std::ops::BitOrAssign for #ident {
+            fn bitor_assign(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+        }
+
+        #[automatically_derived]
+        impl std::ops::BitAnd for #ident {
+            type Output = Self;
+            fn bitand(self, other: Self) -> Self {
+                Self(self.0 & other.0)
+            }
+        }
+
+        #[automatically_derived]
+        impl std::ops::BitAndAssign for #ident {
+            fn bitand_assign(&mut self, other: Self) {
+                self.0 &= other.0;
+            }
+        }
+
+        #[automatically_derived]
+        impl std::ops::BitXor for #ident {
+            type Output = Self;
+            fn bitxor(self, other: Self) -> Self {
+                Self(self.0 ^ other.0)
+            }
+        }
+
+        #[automatically_derived]
+        impl std::ops::BitXorAssign for #ident {
+            fn bitxor_assign(&mut self, other: Self) {
+                self.0 ^= other.0;
+            }
+        }
+
+        #[automatically_derived]
+        impl std::ops::Not for #ident {
+            type Output = Self;
+            fn not(self) -> Self {
+                Self::from_bits_truncate(!self.0)
+            }
+        }
+
+        #[automatically_derived]
+        impl std::fmt::Debug for #ident {
+            fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let mut names: Vec<String> = [#(#debug_names),*]
+                    .iter()
+                    .filter(|(_, value)| self.0 & value == *value)
+                    .map(|(name, _)| name.to_string())
+                    .collect();
+                if self.has_undefined_bits() {
+                    names.push(format!("{:#x}", self.0 & !#all_bits));
+                }
+                write!(fmt, "{}({})", stringify!(#ident), names.join(" | "))
+            }
+        }
+
+        #[automatically_derived]
+        impl anchor_lang::AnchorSerialize for #ident {
+            fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+                anchor_lang::AnchorSerialize::serialize(&self.0, writer)
+            }
+        }
+
+        #[automatically_derived]
+        impl anchor_lang::AnchorDeserialize for #ident {
+            fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+                // Undefined bits are kept, like when the flags are read from a
+                // zero copy account, where any bit pattern is a valid value.
+                let bits: #repr = anchor_lang::AnchorDeserialize::deserialize(buf)?;
+                Ok(Self::from_bits_retain(bits))
+            }
+        }
+
+        #[automatically_derived]
+        unsafe impl anchor_lang::__private::bytemuck::Pod for #ident {}
+        #[automatically_derived]
+        unsafe impl anchor_lang::__private::bytemuck::Zeroable for #ident {}
+    }
+}
@@ -51,9 +51,6 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         })
         .collect();
 
-    // TODO: calculate the account size and add it as a constant field to
-    //       each struct here. This is convenient for Rust clients.
-
     quote! {
         /// An Anchor generated module, providing a set of structs
         /// mirroring the structs deriving `Accounts`, where each field is
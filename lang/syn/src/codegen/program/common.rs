@@ -1,5 +1,5 @@
 use crate::parser;
-use crate::{IxArg, State};
+use crate::{Ix, IxArg, State};
 use heck::CamelCase;
 use quote::quote;
 
@@ -10,6 +10,10 @@ pub const SIGHASH_STATE_NAMESPACE: &str = "state";
 // not affecting program state.
 pub const SIGHASH_GLOBAL_NAMESPACE: &str = "global";
 
+// Namespace of the SPL transfer hook interface, whose `execute` instruction
+// is invoked by the token program on every transfer of a hooked mint.
+pub const SIGHASH_TRANSFER_HOOK_NAMESPACE: &str = "spl-transfer-hook-interface";
+
 // We don't technically use sighash, because the input arguments aren't given.
 // Rust doesn't have method overloading so no need to use the arguments.
 // However, we do namespace methods in the preeimage so that we can use
@@ -22,10 +26,73 @@ pub fn sighash(namespace: &str, name: &str) -> [u8; 8] {
     sighash
 }
 
+// Sighash of a global instruction, which is the transfer hook interface's
+// `execute` for `#[transfer_hook]` handlers.
+pub fn ix_sighash(ix: &Ix) -> [u8; 8] {
+    match ix.transfer_hook {
+        true => sighash(SIGHASH_TRANSFER_HOOK_NAMESPACE, "execute"),
+        false => sighash(
+            SIGHASH_GLOBAL_NAMESPACE,
+            &ix.raw_method.sig.ident.to_string(),
+        ),
+    }
+}
+
+// Sighash of the `close_replay` instruction generated for programs with
+// `#[idempotent]` instructions.
+pub fn sighash_close_replay() -> [u8; 8] {
+    sighash(SIGHASH_GLOBAL_NAMESPACE, "close_replay")
+}
+
+// Sighashes of the instructions managing timelocks, generated for programs
+// with `#[program(timelock)]`.
+pub fn sighash_timelock_queue() -> [u8; 8] {
+    sighash(SIGHASH_GLOBAL_NAMESPACE, "timelock_queue")
+}
+
+pub fn sighash_timelock_cancel() -> [u8; 8] {
+    sighash(SIGHASH_GLOBAL_NAMESPACE, "timelock_cancel")
+}
+
+// Sighashes of the instructions managing interface registrations, generated
+// for programs with `#[program(interface_registry)]`.
+pub fn sighash_interface_register() -> [u8; 8] {
+    sighash(SIGHASH_GLOBAL_NAMESPACE, "interface_register")
+}
+
+pub fn sighash_interface_unregister() -> [u8; 8] {
+    sighash(SIGHASH_GLOBAL_NAMESPACE, "interface_unregister")
+}
+
+// Sighash of the instruction publishing the program's schema, generated for
+// programs with `#[program(schema_registry)]`.
+pub fn sighash_schema_publish() -> [u8; 8] {
+    sighash(SIGHASH_GLOBAL_NAMESPACE, "schema_publish")
+}
+
+// Sighash of the instruction checking the program's dependencies, generated
+// for programs with `#[program(dependencies(..))]`.
+pub fn sighash_check_dependencies() -> [u8; 8] {
+    sighash(SIGHASH_GLOBAL_NAMESPACE, "check_dependencies")
+}
+
 pub fn sighash_ctor() -> [u8; 8] {
     sighash(SIGHASH_STATE_NAMESPACE, "new")
 }
 
+// Discriminator of an `#[account]` struct, under the given namespace if not
+// empty.
+pub fn account_discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    match namespace.is_empty() {
+        true => sighash("account", name),
+        false => sighash(namespace, name),
+    }
+}
+
+pub fn event_discriminator(name: &str) -> [u8; 8] {
+    sighash("event", name)
+}
+
 pub fn generate_ix_variant(name: String, args: &[IxArg]) -> proc_macro2::TokenStream {
     let ix_arg_names: Vec<&syn::Ident> = args.iter().map(|arg| &arg.name).collect();
     let ix_name_camel: proc_macro2::TokenStream = {
@@ -46,6 +113,58 @@ pub fn generate_ix_variant(name: String, args: &[IxArg]) -> proc_macro2::TokenSt
     }
 }
 
+// Returns the minimum and, if bounded, maximum borsh serialized size of an
+// instruction argument, as far as can be told from its type alone. Types
+// defined elsewhere are assumed to be unbounded with no minimum size.
+pub fn arg_serialized_len(ty: &syn::Type) -> (usize, Option<usize>) {
+    match ty {
+        syn::Type::Array(arr) => {
+            let len = match &arr.len {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(len),
+                    ..
+                }) => len.base10_parse::<usize>().ok(),
+                _ => None,
+            };
+            let (min, max) = arg_serialized_len(&arr.elem);
+            match len {
+                Some(len) => (min * len, max.map(|max| max * len)),
+                None => (0, None),
+            }
+        }
+        syn::Type::Path(path) => {
+            let segment = match path.path.segments.last() {
+                Some(segment) => segment,
+                None => return (0, None),
+            };
+            let size = match segment.ident.to_string().as_str() {
+                "bool" | "u8" | "i8" => 1,
+                "u16" | "i16" => 2,
+                "u32" | "i32" | "f32" => 4,
+                "u64" | "i64" | "f64" => 8,
+                "u128" | "i128" => 16,
+                "Pubkey" => 32,
+                "String" | "Vec" => return (4, None),
+                "Option" => {
+                    let inner = match &segment.arguments {
+                        syn::PathArguments::AngleBracketed(args) => args.args.first(),
+                        _ => None,
+                    };
+                    return match inner {
+                        Some(syn::GenericArgument::Type(ty)) => {
+                            (1, arg_serialized_len(ty).1.map(|max| 1 + max))
+                        }
+                        _ => (1, None),
+                    };
+                }
+                _ => return (0, None),
+            };
+            (size, Some(size))
+        }
+        _ => (0, None),
+    }
+}
+
 pub fn generate_ctor_args(state: &State) -> Vec<syn::Pat> {
     generate_ctor_typed_args(state)
         .iter()
@@ -1,4 +1,4 @@
-use crate::codegen::program::common::{generate_ix_variant, sighash, SIGHASH_GLOBAL_NAMESPACE};
+use crate::codegen::program::common::{generate_ix_variant, ix_sighash};
 use crate::Program;
 use crate::StateIx;
 use heck::SnakeCase;
@@ -24,6 +24,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                 &method.args,
                             );
                             let method_name = &method.ident;
+                            let ix_name = format!("state::{}", method_name);
                             let args: Vec<&syn::PatType> =
                                 method.args.iter().map(|arg| &arg.raw_arg).collect();
 
@@ -31,19 +32,21 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                 pub fn #method_name<'a, 'b, 'c, 'info>(
                                     ctx: CpiStateContext<'a, 'b, 'c, 'info, #accounts_ident<'info>>,
                                     #(#args),*
-                                ) -> ProgramResult {
+                                ) -> anchor_lang::solana_program::entrypoint::ProgramResult {
                                     let ix = {
                                         let ix = instruction::state::#ix_variant;
                                         let data = anchor_lang::InstructionData::data(&ix);
                                         let accounts = ctx.to_account_metas(None);
+                                        let program_id = *ctx.program().key;
                                         anchor_lang::solana_program::instruction::Instruction {
-                                            program_id: crate::ID,
+                                            program_id,
                                             accounts,
                                             data,
                                         }
                                     };
                                     let mut acc_infos = ctx.to_account_infos();
-                                    anchor_lang::solana_program::program::invoke_signed(
+                                    anchor_lang::cpi_trace::invoke_signed(
+                                        #ix_name,
                                         &ix,
                                         &acc_infos,
                                         ctx.signer_seeds(),
@@ -65,31 +68,34 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             let cpi_method = {
                 let ix_variant = generate_ix_variant(ix.raw_method.sig.ident.to_string(), &ix.args);
                 let method_name = &ix.ident;
+                let ix_name = method_name.to_string();
                 let args: Vec<&syn::PatType> = ix.args.iter().map(|arg| &arg.raw_arg).collect();
-                let name = &ix.raw_method.sig.ident.to_string();
-                let sighash_arr = sighash(SIGHASH_GLOBAL_NAMESPACE, name);
+                let sighash_arr = ix_sighash(ix);
                 let sighash_tts: proc_macro2::TokenStream =
                     format!("{:?}", sighash_arr).parse().unwrap();
                 quote! {
                     pub fn #method_name<'a, 'b, 'c, 'info>(
-                        ctx: CpiContext<'a, 'b, 'c, 'info, #accounts_ident<'info>>,
+                        ctx: anchor_lang::CpiContext<'a, 'b, 'c, 'info, #accounts_ident<'info>>,
                         #(#args),*
-                    ) -> ProgramResult {
+                    ) -> anchor_lang::solana_program::entrypoint::ProgramResult {
                         let ix = {
                             let ix = instruction::#ix_variant;
-                            let mut ix_data = AnchorSerialize::try_to_vec(&ix)
+                            let mut ix_data = anchor_lang::AnchorSerialize::try_to_vec(&ix)
                                 .map_err(|_| anchor_lang::__private::ErrorCode::InstructionDidNotSerialize)?;
                             let mut data = #sighash_tts.to_vec();
                             data.append(&mut ix_data);
                             let accounts = ctx.to_account_metas(None);
+                            let program_id = *ctx.program.key;
                             anchor_lang::solana_program::instruction::Instruction {
-                                program_id: crate::ID,
+                                program_id,
                                 accounts,
                                 data,
                             }
                         };
+                        ctx.check_compute_budget()?;
                         let mut acc_infos = ctx.to_account_infos();
-                        anchor_lang::solana_program::program::invoke_signed(
+                        anchor_lang::cpi_trace::invoke_signed(
+                            #ix_name,
                             &ix,
                             &acc_infos,
                             ctx.signer_seeds,
@@ -108,9 +114,11 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         #[cfg(feature = "cpi")]
         pub mod cpi {
             use super::*;
+            use anchor_lang::__private::traits::*;
 
             pub mod state {
                 use super::*;
+                use anchor_lang::__private::traits::*;
 
                 #(#state_cpi_methods)*
             }
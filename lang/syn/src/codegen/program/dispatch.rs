@@ -99,23 +99,128 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         .iter()
         .map(|ix| {
             let ix_method_name = &ix.raw_method.sig.ident;
-            let sighash_arr = sighash(SIGHASH_GLOBAL_NAMESPACE, &ix_method_name.to_string());
+            let sighash_arr = ix_sighash(ix);
             let sighash_tts: proc_macro2::TokenStream =
                 format!("{:?}", sighash_arr).parse().unwrap();
+            if ix.simulation_only {
+                // Only dispatched by builds with the `simulation` feature,
+                // e.g. for a local validator used to simulate transactions.
+                quote! {
+                    #sighash_tts => {
+                        #[cfg(feature = "simulation")]
+                        {
+                            __private::__global::#ix_method_name(
+                                program_id,
+                                accounts,
+                                ix_data,
+                            )
+                        }
+                        #[cfg(not(feature = "simulation"))]
+                        {
+                            Err(anchor_lang::__private::ErrorCode::InstructionSimulationOnly.into())
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    #sighash_tts => {
+                        __private::__global::#ix_method_name(
+                            program_id,
+                            accounts,
+                            ix_data,
+                        )
+                    }
+                }
+            }
+        })
+        .collect();
+    // Expired replay accounts of idempotent instructions can be closed by
+    // anyone.
+    let close_replay_dispatch_arm = match program.ixs.iter().any(|ix| ix.idempotent.is_some()) {
+        false => quote! {},
+        true => {
+            let sighash_tts: proc_macro2::TokenStream =
+                format!("{:?}", sighash_close_replay()).parse().unwrap();
             quote! {
                 #sighash_tts => {
-                    __private::__global::#ix_method_name(
-                        program_id,
+                    Ok(anchor_lang::idempotent::close_replay(program_id, accounts, ix_data)?)
+                }
+            }
+        }
+    };
+    let timelock_dispatch_arms = match program.timelock {
+        false => quote! {},
+        true => {
+            let queue_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_timelock_queue()).parse().unwrap();
+            let cancel_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_timelock_cancel()).parse().unwrap();
+            quote! {
+                #queue_sighash => {
+                    Ok(anchor_lang::timelock::queue(program_id, accounts, ix_data)?)
+                }
+                #cancel_sighash => {
+                    Ok(anchor_lang::timelock::cancel(program_id, accounts, ix_data)?)
+                }
+            }
+        }
+    };
+    let interface_registry_dispatch_arms = match program.interface_registry {
+        false => quote! {},
+        true => {
+            let register_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_interface_register())
+                    .parse()
+                    .unwrap();
+            let unregister_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_interface_unregister())
+                    .parse()
+                    .unwrap();
+            quote! {
+                #register_sighash => {
+                    Ok(anchor_lang::interface_registry::register(program_id, accounts, ix_data)?)
+                }
+                #unregister_sighash => {
+                    Ok(anchor_lang::interface_registry::unregister(program_id, accounts, ix_data)?)
+                }
+            }
+        }
+    };
+    let check_dependencies_dispatch_arm = match program.dependencies.is_empty() {
+        true => quote! {},
+        false => {
+            let check_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_check_dependencies())
+                    .parse()
+                    .unwrap();
+            let dependencies = &program.dependencies;
+            quote! {
+                #check_sighash => {
+                    Ok(anchor_lang::dependency::check(
+                        &[#(#dependencies::DEPENDENCY),*],
                         accounts,
-                        ix_data,
-                    )
+                    )?)
                 }
             }
-        })
-        .collect();
-    let fallback_fn = gen_fallback(program).unwrap_or(quote! {
-        Err(anchor_lang::__private::ErrorCode::InstructionFallbackNotFound.into())
-    });
+        }
+    };
+    let schema_registry_dispatch_arm = match program.schema_registry {
+        false => quote! {},
+        true => {
+            let publish_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_schema_publish()).parse().unwrap();
+            quote! {
+                #publish_sighash => {
+                    Ok(anchor_lang::schema_registry::publish(program_id, accounts, ix_data)?)
+                }
+            }
+        }
+    };
+    let fallback_fn = gen_fallback(program)
+        .map(|fallback| quote! { Ok(#fallback?) })
+        .unwrap_or(quote! {
+            Err(anchor_lang::__private::ErrorCode::InstructionFallbackNotFound.into())
+        });
     quote! {
         /// Performs method dispatch.
         ///
@@ -136,10 +241,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         /// matching the given 8 byte identifier to the associated method
         /// handler, which leads to user defined code being eventually invoked.
         fn dispatch(
-            program_id: &Pubkey,
-            accounts: &[AccountInfo],
+            program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+            accounts: &[anchor_lang::solana_program::account_info::AccountInfo],
             data: &[u8],
-        ) -> ProgramResult {
+        ) -> anchor_lang::Result<()> {
             // Split the instruction data into the first 8 byte method
             // identifier (sighash) and the serialized instruction data.
             let mut ix_data: &[u8] = data;
@@ -167,6 +272,11 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                 #(#state_dispatch_arms)*
                 #(#trait_dispatch_arms)*
                 #(#global_dispatch_arms)*
+                #close_replay_dispatch_arm
+                #timelock_dispatch_arms
+                #interface_registry_dispatch_arms
+                #schema_registry_dispatch_arm
+                #check_dependencies_dispatch_arm
                 _ => {
                     #fallback_fn
                 }
@@ -8,9 +8,63 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
     let fallback_maybe = dispatch::gen_fallback(program).unwrap_or(quote! {
         Err(anchor_lang::__private::ErrorCode::InstructionMissing.into());
     });
+    // Instructions of other layouts, routed by their leading bytes before
+    // anything else, since they may be shorter than a sighash.
+    let prefix_dispatch = program.prefix_fns.iter().map(|prefix_fn| {
+        let program_name = &program.name;
+        let fn_name = &prefix_fn.raw_method.sig.ident;
+        let prefix = &prefix_fn.prefix;
+        quote! {
+            if data.starts_with(&[#(#prefix),*]) {
+                return #program_name::#fn_name(program_id, accounts, data);
+            }
+        }
+    });
+    let entrypoint = match &program.panic_handler {
+        None => quote! {
+            #[cfg(not(feature = "no-entrypoint"))]
+            anchor_lang::solana_program::entrypoint!(entry);
+        },
+        // Expands `entrypoint!` with the custom panic handler in place of
+        // the default one, which `entrypoint!` always defines unless the
+        // program has a `custom-panic` feature. As in `entrypoint!`, the
+        // exported symbols only exist on chain, so that programs can be
+        // linked together in host tests.
+        Some(panic_handler) => quote! {
+            /// # Safety
+            #[cfg(all(not(feature = "no-entrypoint"), target_arch = "bpf"))]
+            #[no_mangle]
+            pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
+                let (program_id, accounts, instruction_data) =
+                    anchor_lang::solana_program::entrypoint::deserialize(input);
+                match entry(program_id, &accounts, instruction_data) {
+                    Ok(()) => anchor_lang::solana_program::entrypoint::SUCCESS,
+                    Err(error) => error.into(),
+                }
+            }
+            #[cfg(not(feature = "no-entrypoint"))]
+            anchor_lang::solana_program::custom_heap_default!();
+            #[cfg(all(not(feature = "no-entrypoint"), target_arch = "bpf"))]
+            #[no_mangle]
+            fn custom_panic(info: &core::panic::PanicInfo<'_>) {
+                #panic_handler(info);
+            }
+        },
+    };
+    // Programs not allowed to be invoked are refused up front, as they can
+    // only be invoked if given to the instruction.
+    let cpi_allowlist_check = program.cpi_allowlist.as_ref().map(|allowlist| {
+        let programs = &allowlist.programs;
+        let hook = match &allowlist.hook {
+            Some(hook) => quote! { Some(#hook) },
+            None => quote! { None },
+        };
+        quote! {
+            anchor_lang::cpi_allowlist::check(program_id, accounts, &[#(#programs),*], #hook)?;
+        }
+    });
     quote! {
-        #[cfg(not(feature = "no-entrypoint"))]
-        anchor_lang::solana_program::entrypoint!(entry);
+        #entrypoint
         /// The Anchor codegen exposes a programming model where a user defines
         /// a set of methods inside of a `#[program]` module in a way similar
         /// to writing RPC request handlers. The macro then generates a bunch of
@@ -52,11 +106,13 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         /// The `entry` function here, defines the standard entry to a Solana
         /// program, where execution begins.
         #[cfg(not(feature = "no-entrypoint"))]
-        pub fn entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        pub fn entry(program_id: &anchor_lang::solana_program::pubkey::Pubkey, accounts: &[anchor_lang::solana_program::account_info::AccountInfo], data: &[u8]) -> anchor_lang::solana_program::entrypoint::ProgramResult {
             #[cfg(feature = "anchor-debug")]
             {
-                msg!("anchor-debug is active");
+                anchor_lang::prelude::msg!("anchor-debug is active");
             }
+            #cpi_allowlist_check
+            #(#prefix_dispatch)*
             if data.len() < 8 {
                 return #fallback_maybe
             }
@@ -64,7 +120,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             dispatch(program_id, accounts, data)
                 .map_err(|e| {
                     anchor_lang::solana_program::msg!(&e.to_string());
-                    e
+                    e.into()
                 })
         }
 
@@ -86,8 +142,13 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             }
 
             impl anchor_lang::Id for #name {
-                fn id() -> Pubkey {
-                    ID
+                fn id() -> anchor_lang::solana_program::pubkey::Pubkey {
+                    id()
+                }
+
+                #[cfg(feature = "dynamic-program-id")]
+                fn id_for(program_id: &anchor_lang::solana_program::pubkey::Pubkey) -> anchor_lang::solana_program::pubkey::Pubkey {
+                    *program_id
                 }
             }
         }
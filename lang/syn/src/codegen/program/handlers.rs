@@ -16,7 +16,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             // on chain.
             #[inline(never)]
             #[cfg(not(feature = "no-idl"))]
-            pub fn __idl_dispatch(program_id: &Pubkey, accounts: &[AccountInfo], idl_ix_data: &[u8]) -> ProgramResult {
+            pub fn __idl_dispatch(program_id: &anchor_lang::solana_program::pubkey::Pubkey, accounts: &[anchor_lang::solana_program::account_info::AccountInfo], idl_ix_data: &[u8]) -> anchor_lang::Result<()> {
                 let mut accounts = accounts;
                 let mut data: &[u8] = idl_ix_data;
 
@@ -60,7 +60,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
             #[inline(never)]
             #[cfg(feature = "no-idl")]
-            pub fn __idl_dispatch(program_id: &Pubkey, accounts: &[AccountInfo], idl_ix_data: &[u8]) -> ProgramResult {
+            pub fn __idl_dispatch(program_id: &anchor_lang::solana_program::pubkey::Pubkey, accounts: &[anchor_lang::solana_program::account_info::AccountInfo], idl_ix_data: &[u8]) -> anchor_lang::Result<()> {
                 Err(anchor_lang::__private::ErrorCode::IdlInstructionStub.into())
             }
 
@@ -68,10 +68,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             // invocations.
             #[inline(never)]
             pub fn __idl_create_account(
-                program_id: &Pubkey,
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
                 accounts: &mut anchor_lang::idl::IdlCreateAccounts,
                 data_len: u64,
-            ) -> ProgramResult {
+            ) -> anchor_lang::Result<()> {
                 #[cfg(not(feature = "no-log-ix-name"))]
                 anchor_lang::prelude::msg!("Instruction: IdlCreateAccount");
 
@@ -80,13 +80,13 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                 }
                 // Create the IDL's account.
                 let from = accounts.from.key;
-                let (base, nonce) = Pubkey::find_program_address(&[], program_id);
+                let (base, nonce) = anchor_lang::solana_program::pubkey::Pubkey::find_program_address(&[], program_id);
                 let seed = anchor_lang::idl::IdlAccount::seed();
                 let owner = accounts.program.key;
-                let to = Pubkey::create_with_seed(&base, seed, owner).unwrap();
+                let to = anchor_lang::solana_program::pubkey::Pubkey::create_with_seed(&base, seed, owner).unwrap();
                 // Space: account discriminator || authority pubkey || vec len || vec data
                 let space = 8 + 32 + 4 + data_len as usize;
-                let rent = Rent::get()?;
+                let rent = <anchor_lang::solana_program::sysvar::rent::Rent as anchor_lang::compat::GetSysvar>::get_sysvar()?;
                 let lamports = rent.minimum_balance(space);
                 let seeds = &[&[nonce][..]];
                 let ix = anchor_lang::solana_program::system_instruction::create_account_with_seed(
@@ -98,7 +98,8 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                     space as u64,
                     owner,
                 );
-                anchor_lang::solana_program::program::invoke_signed(
+                anchor_lang::cpi_trace::invoke_signed(
+                    "create_account_with_seed",
                     &ix,
                     &[
                         accounts.from.clone(),
@@ -132,9 +133,9 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
             #[inline(never)]
             pub fn __idl_create_buffer(
-                program_id: &Pubkey,
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
                 accounts: &mut anchor_lang::idl::IdlCreateBuffer,
-            ) -> ProgramResult {
+            ) -> anchor_lang::Result<()> {
                 #[cfg(not(feature = "no-log-ix-name"))]
                 anchor_lang::prelude::msg!("Instruction: IdlCreateBuffer");
 
@@ -145,10 +146,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
             #[inline(never)]
             pub fn __idl_write(
-                program_id: &Pubkey,
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
                 accounts: &mut anchor_lang::idl::IdlAccounts,
                 idl_data: Vec<u8>,
-            ) -> ProgramResult {
+            ) -> anchor_lang::Result<()> {
                 #[cfg(not(feature = "no-log-ix-name"))]
                 anchor_lang::prelude::msg!("Instruction: IdlWrite");
 
@@ -159,10 +160,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
             #[inline(never)]
             pub fn __idl_set_authority(
-                program_id: &Pubkey,
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
                 accounts: &mut anchor_lang::idl::IdlAccounts,
-                new_authority: Pubkey,
-            ) -> ProgramResult {
+                new_authority: anchor_lang::solana_program::pubkey::Pubkey,
+            ) -> anchor_lang::Result<()> {
                 #[cfg(not(feature = "no-log-ix-name"))]
                 anchor_lang::prelude::msg!("Instruction: IdlSetAuthority");
 
@@ -172,9 +173,9 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
             #[inline(never)]
             pub fn __idl_set_buffer(
-                program_id: &Pubkey,
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
                 accounts: &mut anchor_lang::idl::IdlSetBuffer,
-            ) -> ProgramResult {
+            ) -> anchor_lang::Result<()> {
                 #[cfg(not(feature = "no-log-ix-name"))]
                 anchor_lang::prelude::msg!("Instruction: IdlSetBuffer");
 
@@ -201,7 +202,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                         // One time state account initializer. Will faill on subsequent
                         // invocations.
                         #[inline(never)]
-                        pub fn __ctor(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+                        pub fn __ctor(program_id: &anchor_lang::solana_program::pubkey::Pubkey, accounts: &[anchor_lang::solana_program::account_info::AccountInfo], ix_data: &[u8]) -> anchor_lang::Result<()> {
                             #[cfg(not(feature = "no-log-ix-name"))]
                             anchor_lang::prelude::msg!(#ix_name_log);
 
@@ -211,18 +212,18 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                             let instruction::state::#variant_arm = ix;
 
                             // Deserialize accounts.
-                            let mut remaining_accounts: &[AccountInfo] = accounts;
+                            let mut remaining_accounts: &[anchor_lang::solana_program::account_info::AccountInfo] = accounts;
                             let ctor_accounts = anchor_lang::__private::Ctor::try_accounts(program_id, &mut remaining_accounts, &[])?;
                             let mut ctor_user_def_accounts = #anchor_ident::try_accounts(program_id, &mut remaining_accounts, ix_data)?;
 
                             // Create the solana account for the ctor data.
                             let from = ctor_accounts.from.key;
-                            let (base, nonce) = Pubkey::find_program_address(&[], ctor_accounts.program.key);
+                            let (base, nonce) = anchor_lang::solana_program::pubkey::Pubkey::find_program_address(&[], ctor_accounts.program.key);
                             let seed = anchor_lang::__private::PROGRAM_STATE_SEED;
                             let owner = ctor_accounts.program.key;
-                            let to = Pubkey::create_with_seed(&base, seed, owner).unwrap();
+                            let to = anchor_lang::solana_program::pubkey::Pubkey::create_with_seed(&base, seed, owner).unwrap();
                             let space = 8 + std::mem::size_of::<#name>();
-                            let rent = Rent::get()?;
+                            let rent = <anchor_lang::solana_program::sysvar::rent::Rent as anchor_lang::compat::GetSysvar>::get_sysvar()?;
                             let lamports = rent.minimum_balance(std::convert::TryInto::try_into(space).unwrap());
                             let seeds = &[&[nonce][..]];
                             let ix = anchor_lang::solana_program::system_instruction::create_account_with_seed(
@@ -234,7 +235,8 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                 space as u64,
                                 owner,
                             );
-                            anchor_lang::solana_program::program::invoke_signed(
+                            anchor_lang::cpi_trace::invoke_signed(
+                                "create_account_with_seed",
                                 &ix,
                                 &[
                                     ctor_accounts.from.clone(),
@@ -275,7 +277,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                         // One time state account initializer. Will faill on subsequent
                         // invocations.
                         #[inline(never)]
-                        pub fn __ctor(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+                        pub fn __ctor(program_id: &anchor_lang::solana_program::pubkey::Pubkey, accounts: &[anchor_lang::solana_program::account_info::AccountInfo], ix_data: &[u8]) -> anchor_lang::Result<()> {
                             #[cfg(not(feature = "no-log-ix-name"))]
                             anchor_lang::prelude::msg!(#ix_name_log);
 
@@ -285,7 +287,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                             let instruction::state::#variant_arm = ix;
 
                             // Deserialize accounts.
-                            let mut remaining_accounts: &[AccountInfo] = accounts;
+                            let mut remaining_accounts: &[anchor_lang::solana_program::account_info::AccountInfo] = accounts;
                             let ctor_accounts = anchor_lang::__private::Ctor::try_accounts(program_id, &mut remaining_accounts, &[])?;
                             let mut ctor_user_def_accounts = #anchor_ident::try_accounts(program_id, &mut remaining_accounts, ix_data)?;
 
@@ -301,12 +303,12 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
                             // Create the solana account for the ctor data.
                             let from = ctor_accounts.from.key;
-                            let (base, nonce) = Pubkey::find_program_address(&[], ctor_accounts.program.key);
+                            let (base, nonce) = anchor_lang::solana_program::pubkey::Pubkey::find_program_address(&[], ctor_accounts.program.key);
                             let seed = anchor_lang::ProgramState::<#name>::seed();
                             let owner = ctor_accounts.program.key;
-                            let to = Pubkey::create_with_seed(&base, seed, owner).unwrap();
+                            let to = anchor_lang::solana_program::pubkey::Pubkey::create_with_seed(&base, seed, owner).unwrap();
                             let space = anchor_lang::__private::AccountSize::size(&instance)?;
-                            let rent = Rent::get()?;
+                            let rent = <anchor_lang::solana_program::sysvar::rent::Rent as anchor_lang::compat::GetSysvar>::get_sysvar()?;
                             let lamports = rent.minimum_balance(std::convert::TryInto::try_into(space).unwrap());
                             let seeds = &[&[nonce][..]];
                             let ix = anchor_lang::solana_program::system_instruction::create_account_with_seed(
@@ -318,7 +320,8 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                 space,
                                 owner,
                             );
-                            anchor_lang::solana_program::program::invoke_signed(
+                            anchor_lang::cpi_trace::invoke_signed(
+                                "create_account_with_seed",
                                 &ix,
                                 &[
                                     ctor_accounts.from.clone(),
@@ -375,10 +378,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                             quote! {
                                 #[inline(never)]
                                 pub fn #private_ix_method_name(
-                                    program_id: &Pubkey,
-                                    accounts: &[AccountInfo],
+                                    program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                                    accounts: &[anchor_lang::solana_program::account_info::AccountInfo],
                                     ix_data: &[u8],
-                                ) -> ProgramResult {
+                                ) -> anchor_lang::Result<()> {
                                     #[cfg(not(feature = "no-log-ix-name"))]
                                     anchor_lang::prelude::msg!(#ix_name_log);
 
@@ -388,7 +391,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                     let instruction::state::#variant_arm = ix;
 
                                     // Load state.
-                                    let mut remaining_accounts: &[AccountInfo] = accounts;
+                                    let mut remaining_accounts: &[anchor_lang::solana_program::account_info::AccountInfo] = accounts;
                                     if remaining_accounts.is_empty() {
                                         return Err(anchor_lang::__private::ErrorCode::AccountNotEnoughKeys.into());
                                     }
@@ -421,10 +424,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                             quote! {
                                 #[inline(never)]
                                 pub fn #private_ix_method_name(
-                                    program_id: &Pubkey,
-                                    accounts: &[AccountInfo],
+                                    program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                                    accounts: &[anchor_lang::solana_program::account_info::AccountInfo],
                                     ix_data: &[u8],
-                                ) -> ProgramResult {
+                                ) -> anchor_lang::Result<()> {
                                     #[cfg(not(feature = "no-log-ix-name"))]
                                     anchor_lang::prelude::msg!(#ix_name_log);
 
@@ -434,7 +437,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                     let instruction::state::#variant_arm = ix;
 
                                     // Load state.
-                                    let mut remaining_accounts: &[AccountInfo] = accounts;
+                                    let mut remaining_accounts: &[anchor_lang::solana_program::account_info::AccountInfo] = accounts;
                                     if remaining_accounts.is_empty() {
                                         return Err(anchor_lang::__private::ErrorCode::AccountNotEnoughKeys.into());
                                     }
@@ -536,10 +539,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                     quote! {
                                         #[inline(never)]
                                         pub fn #private_ix_method_name(
-                                            program_id: &Pubkey,
-                                            accounts: &[AccountInfo],
+                                            program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                                            accounts: &[anchor_lang::solana_program::account_info::AccountInfo],
                                             ix_data: &[u8],
-                                        ) -> ProgramResult {
+                                        ) -> anchor_lang::Result<()> {
                                             #[cfg(not(feature = "no-log-ix-name"))]
                                             anchor_lang::prelude::msg!(#ix_name_log);
 
@@ -547,7 +550,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                             #deserialize_instruction
 
                                             // Deserialize the program state account.
-                                            let mut remaining_accounts: &[AccountInfo] = accounts;
+                                            let mut remaining_accounts: &[anchor_lang::solana_program::account_info::AccountInfo] = accounts;
                                             if remaining_accounts.is_empty() {
                                                 return Err(anchor_lang::__private::ErrorCode::AccountNotEnoughKeys.into());
                                             }
@@ -583,10 +586,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                     quote! {
                                         #[inline(never)]
                                         pub fn #private_ix_method_name(
-                                            program_id: &Pubkey,
-                                            accounts: &[AccountInfo],
+                                            program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                                            accounts: &[anchor_lang::solana_program::account_info::AccountInfo],
                                             ix_data: &[u8],
-                                        ) -> ProgramResult {
+                                        ) -> anchor_lang::Result<()> {
                                             #[cfg(not(feature = "no-log-ix-name"))]
                                             anchor_lang::prelude::msg!(#ix_name_log);
 
@@ -594,7 +597,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                                             #deserialize_instruction
 
                                             // Deserialize accounts.
-                                            let mut remaining_accounts: &[AccountInfo] = accounts;
+                                            let mut remaining_accounts: &[anchor_lang::solana_program::account_info::AccountInfo] = accounts;
                                             let mut accounts = #anchor_ident::try_accounts(
                                                 program_id,
                                                 &mut remaining_accounts,
@@ -627,39 +630,253 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             let ix_name = generate_ix_variant_name(ix.raw_method.sig.ident.to_string());
             let ix_method_name = &ix.raw_method.sig.ident;
             let anchor = &ix.anchor_ident;
-            let variant_arm = generate_ix_variant(ix.raw_method.sig.ident.to_string(), &ix.args);
+            let sighash_arr = ix_sighash(ix);
+            let sighash_tts: proc_macro2::TokenStream =
+                format!("{:?}", sighash_arr).parse().unwrap();
             let ix_name_log = format!("Instruction: {}", ix_name);
+            // Refers to the account struct's mut markers, which are
+            // deprecated for fields that aren't `mut`, so that modifying one
+            // of those in the handler warns at the modification.
+            let mut_checks: Vec<syn::Ident> = ix
+                .mutated_accounts
+                .iter()
+                .map(|field| quote::format_ident!("__anchor_mut_{}", field, span = field.span()))
+                .collect();
+            // Deprecated instructions warn when invoked and, once past their
+            // sunset slot, are refused.
+            let deprecation = ix.deprecated.as_ref().map(|d| {
+                let mut warning = format!("Warning: instruction {} is deprecated", ix_name);
+                if let Some(since) = &d.since {
+                    warning.push_str(&format!(" since {}", since));
+                }
+                if let Some(note) = &d.note {
+                    warning.push_str(&format!(": {}", note));
+                }
+                let sunset = d.sunset_slot.map(|slot| {
+                    quote! {
+                        let clock = anchor_lang::clock::get()?;
+                        if clock.slot >= #slot {
+                            return Err(anchor_lang::__private::ErrorCode::InstructionSunset.into());
+                        }
+                    }
+                });
+                quote! {
+                    anchor_lang::prelude::msg!(#warning);
+                    #sunset
+                }
+            });
+            // Idempotent instructions record their nonce, taking the replay
+            // accounts off the remaining accounts, before the handler runs.
+            // The nonce is the last arg and isn't passed to the handler.
+            let (replay_protection, handler_arg_names) = match &ix.idempotent {
+                None => (None, &ix_arg_names[..]),
+                Some(idempotent) => {
+                    let expiry = match idempotent.expiry {
+                        Some(expiry) => quote! { #expiry },
+                        None => quote! { anchor_lang::idempotent::DEFAULT_EXPIRY },
+                    };
+                    let replay_protection = quote! {
+                        anchor_lang::idempotent::record(
+                            program_id,
+                            &nonce,
+                            #expiry,
+                            &mut remaining_accounts,
+                        )?;
+                    };
+                    (Some(replay_protection), &ix_arg_names[..ix_arg_names.len() - 1])
+                }
+            };
+            // Read only handlers skip the exit routine, which would have
+            // nothing to persist.
+            let exit = match ix.skip_exit {
+                false => quote! {
+                    // Exit routine.
+                    accounts.exit(program_id)?;
+                },
+                true => quote! {
+                    const _: fn() = || {
+                        fn assert_read_only<T: anchor_lang::ReadOnlyAccounts>() {}
+                        assert_read_only::<#anchor>();
+                    };
+                },
+            };
+            // The instruction's arena, if the program has one, from which
+            // the event buffer and the batch chunks are allocated. Its
+            // region is only reserved by the first allocation.
+            let (new_arena, arena, with_arena) = match &program.arena {
+                None => (quote! {}, quote! { None }, quote! {}),
+                Some(capacity) => (
+                    quote! {
+                        let arena = anchor_lang::arena::Arena::new(#capacity);
+                    },
+                    quote! { Some(&arena) },
+                    quote! { .with_arena(&arena) },
+                ),
+            };
+            // Batch handlers are invoked once per item, each with its own
+            // chunk of accounts taken off the remaining accounts. All chunks
+            // are validated before the first item is handled.
+            let invoke = match &ix.batch {
+                None => quote! {
+                    // Invoke user defined handler.
+                    #program_name::#ix_method_name(
+                        Context::new(program_id, &mut accounts, remaining_accounts)
+                            .with_event_buffer(&events)
+                            #with_arena
+                            .with_bumps(bumps),
+                        #(#handler_arg_names),*
+                    )?;
+                },
+                Some(batch) => {
+                    let chunk = &batch.accounts;
+                    let exit_chunks = match ix.skip_exit {
+                        false => quote! {
+                            for chunk in chunks.iter() {
+                                anchor_lang::AccountsExit::exit(chunk, program_id)?;
+                            }
+                        },
+                        true => quote! {
+                            const _: fn() = || {
+                                fn assert_read_only<T: anchor_lang::ReadOnlyAccounts>() {}
+                                assert_read_only::<#chunk>();
+                            };
+                        },
+                    };
+                    quote! {
+                        // Deserialize each item's accounts.
+                        let mut chunks =
+                            anchor_lang::arena::ArenaVec::with_capacity_in(items.len(), #arena)?;
+                        for _ in 0..items.len() {
+                            chunks.push(#chunk::try_accounts_for_instruction(
+                                program_id,
+                                &mut remaining_accounts,
+                                Some(&#sighash_tts),
+                                ix_data,
+                                &mut Default::default(),
+                            )?)?;
+                        }
+
+                        // Invoke user defined handler for each item.
+                        for (chunk, item) in chunks.iter_mut().zip(items) {
+                            #program_name::#ix_method_name(
+                                Context::new(program_id, &mut accounts, remaining_accounts)
+                                    .with_event_buffer(&events)
+                                    #with_arena
+                                    .with_bumps(bumps.clone()),
+                                chunk,
+                                item,
+                            )?;
+                        }
+
+                        #exit_chunks
+                    }
+                }
+            };
+            // Args are deserialized one at a time, so that the one failing
+            // is logged. If the program denies it, data past the last arg is
+            // refused.
+            let check_args_end = |rest: proc_macro2::TokenStream| match program.deny_trailing_args {
+                true => quote! {
+                    anchor_lang::__private::check_ix_args_end(#rest, ix_data.len())?;
+                },
+                false => quote! {},
+            };
+            let deserialize_instruction = match ix.zero_copy {
+                false => {
+                    let deserialize_args = ix.args.iter().map(|arg| {
+                        let name = &arg.name;
+                        let ty = &arg.raw_arg.ty;
+                        let ty_str = quote!(#ty).to_string().replace(' ', "");
+                        quote! {
+                            let #name: #ty = anchor_lang::__private::deserialize_ix_arg(
+                                &mut args_data,
+                                ix_data.len(),
+                                stringify!(#name),
+                                #ty_str,
+                            )?;
+                        }
+                    });
+                    match ix.args.is_empty() {
+                        true => check_args_end(quote! { ix_data }),
+                        false => {
+                            let check_args_end = check_args_end(quote! { args_data });
+                            quote! {
+                                let mut args_data: &[u8] = ix_data;
+                                #(#deserialize_args)*
+                                #check_args_end
+                            }
+                        }
+                    }
+                }
+                true => {
+                    // The packed args struct has the same layout as the borsh
+                    // encoding of its fields, so the data is cast in place.
+                    let arg_tys: Vec<&syn::Type> = ix.args.iter().map(|arg| &*arg.raw_arg.ty).collect();
+                    let check_args_end =
+                        check_args_end(quote! { &ix_data[std::mem::size_of::<Args>()..] });
+                    quote! {
+                        #[derive(Clone, Copy)]
+                        #[repr(C, packed)]
+                        struct Args {
+                            #(#ix_arg_names: #arg_tys),*
+                        }
+                        unsafe impl anchor_lang::__private::bytemuck::Pod for Args {}
+                        unsafe impl anchor_lang::__private::bytemuck::Zeroable for Args {}
+                        const _: fn() = || {
+                            fn assert_pod<T: anchor_lang::__private::bytemuck::Pod>() {}
+                            #(assert_pod::<#arg_tys>();)*
+                        };
+
+                        let ix: &Args = ix_data
+                            .get(..std::mem::size_of::<Args>())
+                            .and_then(|data| anchor_lang::__private::bytemuck::try_from_bytes(data).ok())
+                            .ok_or(anchor_lang::__private::ErrorCode::InstructionDidNotDeserialize)?;
+                        let Args { #(#ix_arg_names),* } = *ix;
+                        #check_args_end
+                    }
+                }
+            };
             quote! {
                 #[inline(never)]
                 pub fn #ix_method_name(
-                    program_id: &Pubkey,
-                    accounts: &[AccountInfo],
+                    program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                    accounts: &[anchor_lang::solana_program::account_info::AccountInfo],
                     ix_data: &[u8],
-                ) -> ProgramResult {
+                ) -> anchor_lang::Result<()> {
                     #[cfg(not(feature = "no-log-ix-name"))]
                     anchor_lang::prelude::msg!(#ix_name_log);
 
+                    #deprecation
+
+                    #(let _ = #anchor::#mut_checks;)*
+
                     // Deserialize data.
-                    let ix = instruction::#ix_name::deserialize(&mut &ix_data[..])
-                        .map_err(|_| anchor_lang::__private::ErrorCode::InstructionDidNotDeserialize)?;
-                    let instruction::#variant_arm = ix;
+                    #deserialize_instruction
 
                     // Deserialize accounts.
-                    let mut remaining_accounts: &[AccountInfo] = accounts;
-                    let mut accounts = #anchor::try_accounts(
+                    let mut remaining_accounts: &[anchor_lang::solana_program::account_info::AccountInfo] = accounts;
+                    let mut bumps = <#anchor as anchor_lang::Bumps>::Bumps::default();
+                    let mut accounts = #anchor::try_accounts_for_instruction(
                         program_id,
                         &mut remaining_accounts,
+                        Some(&#sighash_tts),
                         ix_data,
+                        &mut bumps,
                     )?;
 
-                    // Invoke user defined handler.
-                    #program_name::#ix_method_name(
-                        Context::new(program_id, &mut accounts, remaining_accounts),
-                        #(#ix_arg_names),*
-                    )?;
+                    #replay_protection
 
-                    // Exit routine.
-                    accounts.exit(program_id)
+                    #new_arena
+
+                    // Events pushed by the handler, logged once it succeeded.
+                    let events = anchor_lang::EventBuffer::new_in(#arena);
+
+                    #invoke
+
+                    #exit
+
+                    events.flush();
+                    Ok(())
                 }
             }
         })
@@ -671,9 +888,11 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         /// wrapper.
         mod __private {
             use super::*;
+            use anchor_lang::__private::traits::*;
             /// __idl mod defines handlers for injected Anchor IDL instructions.
             pub mod __idl {
                 use super::*;
+                use anchor_lang::__private::traits::*;
 
                 #non_inlined_idl
             }
@@ -681,6 +900,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             /// __state mod defines wrapped handlers for state instructions.
             pub mod __state {
                 use super::*;
+                use anchor_lang::__private::traits::*;
 
                 #non_inlined_ctor
                 #(#non_inlined_state_handlers)*
@@ -690,6 +910,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             /// implementations.
             pub mod __interface {
                 use super::*;
+                use anchor_lang::__private::traits::*;
 
                 #(#non_inlined_state_trait_handlers)*
             }
@@ -697,6 +918,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             /// __global mod defines wrapped handlers for global instructions.
             pub mod __global {
                 use super::*;
+                use anchor_lang::__private::traits::*;
 
                 #(#non_inlined_handlers)*
             }
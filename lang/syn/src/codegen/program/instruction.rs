@@ -1,10 +1,11 @@
 use crate::codegen::program::common::*;
 use crate::parser;
-use crate::Program;
+use crate::{Ix, Program};
 use heck::CamelCase;
-use quote::quote;
+use quote::{quote, quote_spanned};
 
 pub fn generate(program: &Program) -> proc_macro2::TokenStream {
+    let derives = &program.derives;
     let ctor_variant = match &program.state {
         None => quote! {},
         Some(state) => {
@@ -19,12 +20,12 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             let strct = {
                 if ctor_args.is_empty() {
                     quote! {
-                        #[derive(AnchorSerialize, AnchorDeserialize)]
+                        #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
                         pub struct New;
                     }
                 } else {
                     quote! {
-                        #[derive(AnchorSerialize, AnchorDeserialize)]
+                        #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
                         pub struct New {
                             #(#ctor_args),*
                         }
@@ -96,7 +97,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                         if method.args.is_empty() {
                             quote! {
                                 /// Anchor generated instruction.
-                                #[derive(AnchorSerialize, AnchorDeserialize)]
+                                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
                                 pub struct #ix_name_camel;
 
                                 #ix_data_trait
@@ -104,7 +105,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                         } else {
                             quote! {
                                 /// Anchor generated instruction.
-                                #[derive(AnchorSerialize, AnchorDeserialize)]
+                                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
                                 pub struct #ix_name_camel {
                                     #(#raw_args),*
                                 }
@@ -134,7 +135,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                 })
                 .collect();
             let ix_data_trait = {
-                let sighash_arr = sighash(SIGHASH_GLOBAL_NAMESPACE, name);
+                let sighash_arr = ix_sighash(ix);
                 let sighash_tts: proc_macro2::TokenStream =
                     format!("{:?}", sighash_arr).parse().unwrap();
                 quote! {
@@ -147,28 +148,195 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                     }
                 }
             };
+            let ix_sizes = generate_ix_sizes(ix, &ix_name_camel);
             // If no args, output a "unit" variant instead of a struct variant.
             if ix.args.is_empty() {
                 quote! {
                     /// Instruction.
-                    #[derive(AnchorSerialize, AnchorDeserialize)]
+                    #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
                     pub struct #ix_name_camel;
 
                     #ix_data_trait
+                    #ix_sizes
                 }
             } else {
                 quote! {
                     /// Instruction.
-                    #[derive(AnchorSerialize, AnchorDeserialize)]
+                    #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
                     pub struct #ix_name_camel {
                         #(#raw_args),*
                     }
 
                     #ix_data_trait
+                    #ix_sizes
                 }
             }
         })
         .collect();
+    let close_replay_variant = match program.ixs.iter().any(|ix| ix.idempotent.is_some()) {
+        false => quote! {},
+        true => {
+            let sighash_tts: proc_macro2::TokenStream =
+                format!("{:?}", sighash_close_replay()).parse().unwrap();
+            quote! {
+                /// Instruction closing the expired replay account of an
+                /// `#[idempotent]` instruction's nonce.
+                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
+                pub struct CloseReplay {
+                    pub nonce: [u8; 16],
+                }
+
+                impl anchor_lang::InstructionData for CloseReplay {
+                    fn data(&self) -> Vec<u8> {
+                        let mut d = #sighash_tts.to_vec();
+                        d.append(&mut self.try_to_vec().expect("Should always serialize"));
+                        d
+                    }
+                }
+            }
+        }
+    };
+    let timelock_variants = match program.timelock {
+        false => quote! {},
+        true => {
+            let queue_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_timelock_queue()).parse().unwrap();
+            let cancel_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_timelock_cancel()).parse().unwrap();
+            quote! {
+                /// Instruction queueing an action on the authority's timelock,
+                /// see `anchor_lang::timelock::action_hash`.
+                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
+                pub struct TimelockQueue {
+                    pub action: [u8; 32],
+                }
+
+                impl anchor_lang::InstructionData for TimelockQueue {
+                    fn data(&self) -> Vec<u8> {
+                        let mut d = #queue_sighash.to_vec();
+                        d.append(&mut self.try_to_vec().expect("Should always serialize"));
+                        d
+                    }
+                }
+
+                /// Instruction dropping the action queued on the authority's
+                /// timelock.
+                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
+                pub struct TimelockCancel;
+
+                impl anchor_lang::InstructionData for TimelockCancel {
+                    fn data(&self) -> Vec<u8> {
+                        #cancel_sighash.to_vec()
+                    }
+                }
+            }
+        }
+    };
+
+    let interface_registry_variants = match program.interface_registry {
+        false => quote! {},
+        true => {
+            let register_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_interface_register())
+                    .parse()
+                    .unwrap();
+            let unregister_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_interface_unregister())
+                    .parse()
+                    .unwrap();
+            quote! {
+                /// Instruction registering an implementation of an interface
+                /// by the signing authority, see
+                /// `anchor_lang::interface_registry`.
+                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
+                pub struct InterfaceRegister {
+                    pub interface: [u8; 8],
+                    pub implementation: anchor_lang::solana_program::pubkey::Pubkey,
+                }
+
+                impl anchor_lang::InstructionData for InterfaceRegister {
+                    fn data(&self) -> Vec<u8> {
+                        let mut d = #register_sighash.to_vec();
+                        d.append(&mut self.try_to_vec().expect("Should always serialize"));
+                        d
+                    }
+                }
+
+                /// Instruction closing the signing authority's registration
+                /// of an implementation of an interface.
+                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
+                pub struct InterfaceUnregister {
+                    pub interface: [u8; 8],
+                    pub implementation: anchor_lang::solana_program::pubkey::Pubkey,
+                }
+
+                impl anchor_lang::InstructionData for InterfaceUnregister {
+                    fn data(&self) -> Vec<u8> {
+                        let mut d = #unregister_sighash.to_vec();
+                        d.append(&mut self.try_to_vec().expect("Should always serialize"));
+                        d
+                    }
+                }
+            }
+        }
+    };
+
+    let schema_registry_variant = match program.schema_registry {
+        false => quote! {},
+        true => {
+            let publish_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_schema_publish()).parse().unwrap();
+            quote! {
+                /// Instruction publishing the program's schema, signed by its
+                /// upgrade authority, see `anchor_lang::schema_registry`.
+                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
+                pub struct SchemaPublish {
+                    pub idl_hash: [u8; 32],
+                    pub event_schema_version: u32,
+                }
+
+                impl anchor_lang::InstructionData for SchemaPublish {
+                    fn data(&self) -> Vec<u8> {
+                        let mut d = #publish_sighash.to_vec();
+                        d.append(&mut self.try_to_vec().expect("Should always serialize"));
+                        d
+                    }
+                }
+            }
+        }
+    };
+
+    let check_dependencies_variant = match program.dependencies.is_empty() {
+        true => quote! {},
+        false => {
+            let check_sighash: proc_macro2::TokenStream =
+                format!("{:?}", sighash_check_dependencies())
+                    .parse()
+                    .unwrap();
+            let dependencies = &program.dependencies;
+            quote! {
+                /// Instruction checking the program's deployed dependencies,
+                /// see `anchor_lang::dependency`.
+                #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize #(, #derives)*)]
+                pub struct CheckDependencies;
+
+                impl CheckDependencies {
+                    /// The program and IDL accounts of the dependencies.
+                    pub fn accounts() -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+                        anchor_lang::dependency::account_metas(&[#(#dependencies::DEPENDENCY),*])
+                    }
+                }
+
+                impl anchor_lang::InstructionData for CheckDependencies {
+                    fn data(&self) -> Vec<u8> {
+                        #check_sighash.to_vec()
+                    }
+                }
+            }
+        }
+    };
+
+    let parser = generate_parser(program);
 
     quote! {
         /// An Anchor generated module containing the program's set of
@@ -179,16 +347,268 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         /// instructions on a client.
         pub mod instruction {
             use super::*;
+            use anchor_lang::__private::traits::*;
 
             /// Instruction struct definitions for `#[state]` methods.
             pub mod state {
                 use super::*;
+                use anchor_lang::__private::traits::*;
 
                 #ctor_variant
                 #(#state_method_variants)*
             }
 
             #(#variants)*
+            #close_replay_variant
+            #timelock_variants
+            #interface_registry_variants
+            #schema_registry_variant
+            #check_dependencies_variant
+
+            #parser
+
+            #[cfg(feature = "instruction-parser")]
+            pub use __parser::{
+                try_parse_compiled_instruction, try_parse_compiled_instruction_for,
+                try_parse_instruction, try_parse_instruction_with_accounts, ParsedInstruction,
+                ProgramInstruction,
+            };
         }
     }
 }
+
+// Generates the `ProgramInstruction` enum of the program's global
+// instructions, and `try_parse_instruction` decoding one from its data, for
+// programs introspecting this program's instructions. `ParsedInstruction`
+// adds the instruction's accounts, decoded from a transaction's instruction
+// with `try_parse_compiled_instruction`, e.g. by indexers. They're exported
+// from the `instruction` module with the `instruction-parser` feature.
+fn generate_parser(program: &Program) -> proc_macro2::TokenStream {
+    let derives = &program.derives;
+    let mut variants: Vec<(proc_macro2::Ident, [u8; 8])> = program
+        .ixs
+        .iter()
+        .map(|ix| {
+            let name = ix.raw_method.sig.ident.to_string().to_camel_case();
+            (
+                proc_macro2::Ident::new(&name, ix.raw_method.sig.ident.span()),
+                ix_sighash(ix),
+            )
+        })
+        .collect();
+    if program.ixs.iter().any(|ix| ix.idempotent.is_some()) {
+        variants.push((
+            proc_macro2::Ident::new("CloseReplay", proc_macro2::Span::call_site()),
+            sighash_close_replay(),
+        ));
+    }
+    if program.timelock {
+        variants.push((
+            proc_macro2::Ident::new("TimelockQueue", proc_macro2::Span::call_site()),
+            sighash_timelock_queue(),
+        ));
+        variants.push((
+            proc_macro2::Ident::new("TimelockCancel", proc_macro2::Span::call_site()),
+            sighash_timelock_cancel(),
+        ));
+    }
+    if program.interface_registry {
+        variants.push((
+            proc_macro2::Ident::new("InterfaceRegister", proc_macro2::Span::call_site()),
+            sighash_interface_register(),
+        ));
+        variants.push((
+            proc_macro2::Ident::new("InterfaceUnregister", proc_macro2::Span::call_site()),
+            sighash_interface_unregister(),
+        ));
+    }
+    if program.schema_registry {
+        variants.push((
+            proc_macro2::Ident::new("SchemaPublish", proc_macro2::Span::call_site()),
+            sighash_schema_publish(),
+        ));
+    }
+    if !program.dependencies.is_empty() {
+        variants.push((
+            proc_macro2::Ident::new("CheckDependencies", proc_macro2::Span::call_site()),
+            sighash_check_dependencies(),
+        ));
+    }
+    let names: Vec<&proc_macro2::Ident> = variants.iter().map(|(name, _)| name).collect();
+    let sighashes: Vec<proc_macro2::TokenStream> = variants
+        .iter()
+        .map(|(_, sighash)| format!("{:?}", sighash).parse().unwrap())
+        .collect();
+    // The program's instructions come with their client accounts struct,
+    // while anchor's own instructions come with their account keys.
+    let ix_names = &names[..program.ixs.len()];
+    let ix_accounts: Vec<&proc_macro2::Ident> =
+        program.ixs.iter().map(|ix| &ix.anchor_ident).collect();
+    let builtin_names = &names[program.ixs.len()..];
+    quote! {
+        #[doc(hidden)]
+        pub mod __parser {
+            use super::*;
+
+            /// A decoded instruction of the program.
+            #[derive(#(#derives),*)]
+            pub enum ProgramInstruction {
+                #(#names(#names),)*
+            }
+
+            /// Decodes the instruction with the given data, i.e. its sighash
+            /// followed by its args, returning `None` if it isn't one of the
+            /// program's global instructions or its args don't deserialize.
+            #[allow(unused_mut, unused_variables)]
+            pub fn try_parse_instruction(data: &[u8]) -> Option<ProgramInstruction> {
+                if data.len() < 8 {
+                    return None;
+                }
+                let (sighash, mut args) = data.split_at(8);
+                match sighash {
+                    #(
+                        #sighashes => anchor_lang::AnchorDeserialize::deserialize(&mut args)
+                            .ok()
+                            .map(ProgramInstruction::#names),
+                    )*
+                    _ => None,
+                }
+            }
+
+            /// A decoded instruction of the program, along with its
+            /// accounts.
+            #[derive(#(#derives),*)]
+            pub enum ParsedInstruction {
+                #(
+                    #ix_names {
+                        args: #ix_names,
+                        accounts: super::super::accounts::#ix_accounts,
+                        remaining_accounts: Vec<anchor_lang::solana_program::pubkey::Pubkey>,
+                    },
+                )*
+                #(
+                    #builtin_names {
+                        args: #builtin_names,
+                        accounts: Vec<anchor_lang::solana_program::pubkey::Pubkey>,
+                    },
+                )*
+            }
+
+            /// Decodes the instruction with the given data and account keys,
+            /// returning `None` if it isn't one of the program's global
+            /// instructions, its args don't deserialize or it's missing
+            /// accounts.
+            #[allow(unused_mut)]
+            pub fn try_parse_instruction_with_accounts(
+                data: &[u8],
+                keys: &[anchor_lang::solana_program::pubkey::Pubkey],
+            ) -> Option<ParsedInstruction> {
+                let mut remaining_accounts = keys;
+                match try_parse_instruction(data)? {
+                    #(
+                        ProgramInstruction::#ix_names(args) => Some(ParsedInstruction::#ix_names {
+                            args,
+                            accounts: anchor_lang::FromAccountKeys::from_account_keys(&mut remaining_accounts)?,
+                            remaining_accounts: remaining_accounts.to_vec(),
+                        }),
+                    )*
+                    #(
+                        ProgramInstruction::#builtin_names(args) => Some(ParsedInstruction::#builtin_names {
+                            args,
+                            accounts: remaining_accounts.to_vec(),
+                        }),
+                    )*
+                }
+            }
+
+            /// Decodes an instruction of a transaction's message, given the
+            /// message's account keys, returning `None` if it isn't an
+            /// instruction of this program, see
+            /// [`try_parse_instruction_with_accounts`].
+            pub fn try_parse_compiled_instruction(
+                ix: &anchor_lang::solana_program::instruction::CompiledInstruction,
+                account_keys: &[anchor_lang::solana_program::pubkey::Pubkey],
+            ) -> Option<ParsedInstruction> {
+                try_parse_compiled_instruction_for(&crate::ID, ix, account_keys)
+            }
+
+            /// Same as [`try_parse_compiled_instruction`], for this program
+            /// deployed as `program_id`, e.g. one of the deployments of a
+            /// program built with the `dynamic-program-id` feature.
+            pub fn try_parse_compiled_instruction_for(
+                program_id: &anchor_lang::solana_program::pubkey::Pubkey,
+                ix: &anchor_lang::solana_program::instruction::CompiledInstruction,
+                account_keys: &[anchor_lang::solana_program::pubkey::Pubkey],
+            ) -> Option<ParsedInstruction> {
+                if account_keys.get(ix.program_id_index as usize) != Some(program_id) {
+                    return None;
+                }
+                let keys = ix
+                    .accounts
+                    .iter()
+                    .map(|idx| account_keys.get(*idx as usize).copied())
+                    .collect::<Option<Vec<_>>>()?;
+                try_parse_instruction_with_accounts(&ix.data, &keys)
+            }
+        }
+    }
+}
+
+// Generates the `DATA_LEN` and `ACCOUNTS_LEN` constants of an instruction
+// struct, along with a check that warns at compile time if the instruction
+// can't fit in a legacy transaction even at its smallest.
+fn generate_ix_sizes(ix: &Ix, ix_name_camel: &proc_macro2::Ident) -> proc_macro2::TokenStream {
+    let anchor_ident = &ix.anchor_ident;
+    // The replay account, its payer and the system program.
+    let replay_accounts_len: usize = match ix.idempotent {
+        Some(_) => 3,
+        None => 0,
+    };
+    let (min_data_len, max_data_len) =
+        ix.args
+            .iter()
+            .fold((8, Some(8)), |(min, max): (usize, Option<usize>), arg| {
+                let (arg_min, arg_max) = arg_serialized_len(&arg.raw_arg.ty);
+                (
+                    min + arg_min,
+                    max.and_then(|max| arg_max.map(|arg_max| max + arg_max)),
+                )
+            });
+    let max_data_len = match max_data_len {
+        Some(len) => quote! { Some(#len) },
+        None => quote! { None },
+    };
+    let warning = format!(
+        "instruction `{}` doesn't fit in a legacy transaction with its accounts",
+        ix.raw_method.sig.ident
+    );
+    let check = quote_spanned! { ix.raw_method.sig.ident.span() =>
+        const _: () = {
+            struct Check<const FITS: bool>;
+            #[allow(dead_code)]
+            impl Check<false> {
+                #[deprecated(note = #warning)]
+                const fn check() {}
+            }
+            #[allow(dead_code)]
+            impl Check<true> {
+                const fn check() {}
+            }
+            Check::<{
+                anchor_lang::__private::min_transaction_size(#ix_name_camel::ACCOUNTS_LEN, #min_data_len)
+                    <= anchor_lang::__private::PACKET_DATA_SIZE
+            }>::check()
+        };
+    };
+    quote! {
+        impl #ix_name_camel {
+            /// Size of the serialized instruction data, including the
+            /// discriminator, if it's bounded.
+            pub const DATA_LEN: Option<usize> = #max_data_len;
+            /// Number of account metas the instruction takes.
+            pub const ACCOUNTS_LEN: usize = super::accounts::#anchor_ident::ACCOUNTS_LEN + #replay_accounts_len;
+        }
+
+        #check
+    }
+}
@@ -0,0 +1,319 @@
+// Generates a C header of a program's instructions from its IDL, for C and
+// C++ clients calling the program without reimplementing its borsh layouts.
+//
+// For each instruction, the header has its discriminator, a packed struct of
+// its data if its args are fixed size, i.e. don't contain strings, vectors,
+// options or enums with fields, and the indices of its accounts, nested
+// accounts structs being flattened. Fixed size structs and fieldless enums
+// of the IDL's types are declared first, for the args to refer to.
+
+use crate::idl::*;
+use heck::{ShoutySnakeCase, SnakeCase};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+pub fn generate(idl: &Idl) -> String {
+    let prefix = idl.name.to_shouty_snake_case();
+    let fixed_defs = fixed_types(idl);
+    let fixed: HashSet<String> = fixed_defs
+        .iter()
+        .map(|ty_def| ty_def.name.clone())
+        .collect();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "/* Generated by anchor from the `{}` IDL. */",
+        idl.name
+    )
+    .unwrap();
+    writeln!(out, "#ifndef {}_H", prefix).unwrap();
+    writeln!(out, "#define {}_H", prefix).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "#include <stdint.h>").unwrap();
+
+    for ty_def in fixed_defs {
+        let c_name = type_name(idl, &ty_def.name);
+        writeln!(out).unwrap();
+        match &ty_def.ty {
+            IdlTypeDefinitionTy::Struct { fields } => {
+                writeln!(out, "typedef struct __attribute__((packed)) {{").unwrap();
+                for field in fields {
+                    writeln!(out, "    {};", c_decl(idl, &field.ty, &field.name)).unwrap();
+                }
+                writeln!(out, "}} {};", c_name).unwrap();
+            }
+            IdlTypeDefinitionTy::Enum { variants } => {
+                let enum_prefix = format!("{}_{}", prefix, ty_def.name.to_shouty_snake_case());
+                writeln!(out, "typedef uint8_t {};", c_name).unwrap();
+                writeln!(out, "enum {{").unwrap();
+                for (idx, variant) in variants.iter().enumerate() {
+                    let name = variant.name.to_shouty_snake_case();
+                    writeln!(out, "    {}_{} = {},", enum_prefix, name, idx).unwrap();
+                }
+                writeln!(out, "}};").unwrap();
+            }
+            IdlTypeDefinitionTy::Flags { repr, .. } => {
+                writeln!(out, "typedef {} {};", c_type(idl, repr).0, c_name).unwrap();
+            }
+        }
+    }
+
+    for ix in &idl.instructions {
+        let discriminator = match ix.discriminator {
+            Some(discriminator) => discriminator,
+            None => continue,
+        };
+        let ix_name = ix.name.to_snake_case();
+        let ix_prefix = format!("{}_{}", prefix, ix_name.to_shouty_snake_case());
+        writeln!(out).unwrap();
+        writeln!(out, "/* {} */", ix.name).unwrap();
+        let bytes: Vec<String> = discriminator
+            .iter()
+            .map(|b| format!("0x{:02x}", b))
+            .collect();
+        writeln!(
+            out,
+            "static const uint8_t {}_DISCRIMINATOR[8] = {{{}}};",
+            ix_prefix,
+            bytes.join(", ")
+        )
+        .unwrap();
+        if ix.args.iter().all(|arg| is_fixed(&arg.ty, &fixed)) {
+            writeln!(out, "typedef struct __attribute__((packed)) {{").unwrap();
+            writeln!(out, "    uint8_t discriminator[8];").unwrap();
+            for arg in &ix.args {
+                writeln!(out, "    {};", c_decl(idl, &arg.ty, &arg.name)).unwrap();
+            }
+            writeln!(out, "}} {}_{}_data_t;", idl.name.to_snake_case(), ix_name).unwrap();
+        } else {
+            writeln!(
+                out,
+                "/* The args aren't fixed size, so they're borsh encoded after the discriminator. */"
+            )
+            .unwrap();
+        }
+        let mut accounts = Vec::new();
+        flatten_accounts(&ix.accounts, "", &mut accounts);
+        writeln!(out, "enum {{").unwrap();
+        for (idx, (name, account)) in accounts.iter().enumerate() {
+            let flags: Vec<&str> = [(account.is_mut, "writable"), (account.is_signer, "signer")]
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, flag)| *flag)
+                .collect();
+            let comment = match flags.is_empty() {
+                true => String::new(),
+                false => format!(" /* {} */", flags.join(", ")),
+            };
+            writeln!(
+                out,
+                "    {}_ACCOUNT_{} = {},{}",
+                ix_prefix, name, idx, comment
+            )
+            .unwrap();
+        }
+        writeln!(out, "    {}_ACCOUNTS_LEN = {},", ix_prefix, accounts.len()).unwrap();
+        writeln!(out, "}};").unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "#endif /* {}_H */", prefix).unwrap();
+    out
+}
+
+// The accounts in order, named by their path through nested accounts
+// structs.
+fn flatten_accounts<'a>(
+    items: &'a [IdlAccountItem],
+    prefix: &str,
+    out: &mut Vec<(String, &'a IdlAccount)>,
+) {
+    for item in items {
+        match item {
+            IdlAccountItem::IdlAccount(account) => {
+                let name = format!("{}{}", prefix, account.name.to_shouty_snake_case());
+                out.push((name, account));
+            }
+            IdlAccountItem::IdlAccounts(accounts) => {
+                let prefix = format!("{}{}_", prefix, accounts.name.to_shouty_snake_case());
+                flatten_accounts(&accounts.accounts, &prefix, out);
+            }
+        }
+    }
+}
+
+// The IDL's types with a fixed size encoding, each after the types it
+// refers to.
+fn fixed_types(idl: &Idl) -> Vec<&IdlTypeDefinition> {
+    let mut fixed: Vec<&IdlTypeDefinition> = Vec::new();
+    loop {
+        let names: HashSet<String> = fixed.iter().map(|ty_def| ty_def.name.clone()).collect();
+        let found: Vec<&IdlTypeDefinition> = idl
+            .types
+            .iter()
+            .filter(|ty_def| !names.contains(&ty_def.name))
+            .filter(|ty_def| match &ty_def.ty {
+                IdlTypeDefinitionTy::Struct { fields } => {
+                    fields.iter().all(|field| is_fixed(&field.ty, &names))
+                }
+                IdlTypeDefinitionTy::Enum { variants } => {
+                    variants.iter().all(|variant| variant.fields.is_none())
+                }
+                IdlTypeDefinitionTy::Flags { .. } => true,
+            })
+            .collect();
+        if found.is_empty() {
+            return fixed;
+        }
+        fixed.extend(found);
+    }
+}
+
+fn is_fixed(ty: &IdlType, fixed: &HashSet<String>) -> bool {
+    match ty {
+        IdlType::Bytes | IdlType::String | IdlType::Option(_) | IdlType::Vec(_) => false,
+        IdlType::Defined(name) => fixed.contains(name),
+        IdlType::Array(ty, _) => is_fixed(ty, fixed),
+        _ => true,
+    }
+}
+
+fn type_name(idl: &Idl, name: &str) -> String {
+    format!("{}_{}_t", idl.name.to_snake_case(), name.to_snake_case())
+}
+
+// The C declaration of `name` with the fixed size type `ty`.
+fn c_decl(idl: &Idl, ty: &IdlType, name: &str) -> String {
+    let (c_ty, dims) = c_type(idl, ty);
+    let dims: String = dims.iter().map(|len| format!("[{}]", len)).collect();
+    format!("{} {}{}", c_ty, name.to_snake_case(), dims)
+}
+
+// The C element type of the fixed size type `ty`, and its array dimensions.
+fn c_type(idl: &Idl, ty: &IdlType) -> (String, Vec<usize>) {
+    let c_ty = match ty {
+        IdlType::Bool | IdlType::U8 => "uint8_t",
+        IdlType::I8 => "int8_t",
+        IdlType::U16 => "uint16_t",
+        IdlType::I16 => "int16_t",
+        IdlType::U32 => "uint32_t",
+        IdlType::I32 => "int32_t",
+        IdlType::U64 => "uint64_t",
+        IdlType::I64 => "int64_t",
+        // Little endian, as C has no standard 128 bit integers.
+        IdlType::U128 | IdlType::I128 => return ("uint8_t".to_string(), vec![16]),
+        IdlType::PublicKey => return ("uint8_t".to_string(), vec![32]),
+        IdlType::FixedString(len) => return ("char".to_string(), vec![*len]),
+        IdlType::Defined(name) => return (type_name(idl, name), vec![]),
+        IdlType::Array(ty, len) => {
+            let (c_ty, mut dims) = c_type(idl, ty);
+            dims.insert(0, *len);
+            return (c_ty, dims);
+        }
+        IdlType::Bytes | IdlType::String | IdlType::Option(_) | IdlType::Vec(_) => {
+            unreachable!("not fixed size")
+        }
+    };
+    (c_ty.to_string(), vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate;
+    use crate::idl::Idl;
+
+    #[test]
+    fn header_of_fixed_and_variable_instructions() {
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "version": "0.1.0",
+            "name": "vault",
+            "instructions": [
+                {
+                    "name": "deposit",
+                    "accounts": [
+                        { "name": "vault", "isMut": true, "isSigner": false },
+                        {
+                            "name": "owner",
+                            "accounts": [
+                                { "name": "authority", "isMut": false, "isSigner": true }
+                            ]
+                        }
+                    ],
+                    "args": [
+                        { "name": "amount", "type": "u64" },
+                        { "name": "side", "type": { "defined": "Side" } },
+                        { "name": "limits", "type": { "array": [{ "defined": "Limit" }, 2] } }
+                    ],
+                    "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]
+                },
+                {
+                    "name": "setName",
+                    "accounts": [],
+                    "args": [{ "name": "name", "type": "string" }],
+                    "discriminator": [8, 7, 6, 5, 4, 3, 2, 1]
+                }
+            ],
+            "types": [
+                {
+                    "name": "Limit",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "maxAmount", "type": "u128" },
+                            { "name": "side", "type": { "defined": "Side" } }
+                        ]
+                    }
+                },
+                {
+                    "name": "Side",
+                    "type": { "kind": "enum", "variants": [{ "name": "Bid" }, { "name": "Ask" }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let header = generate(&idl);
+        let expected = "\
+/* Generated by anchor from the `vault` IDL. */
+#ifndef VAULT_H
+#define VAULT_H
+
+#include <stdint.h>
+
+typedef uint8_t vault_side_t;
+enum {
+    VAULT_SIDE_BID = 0,
+    VAULT_SIDE_ASK = 1,
+};
+
+typedef struct __attribute__((packed)) {
+    uint8_t max_amount[16];
+    vault_side_t side;
+} vault_limit_t;
+
+/* deposit */
+static const uint8_t VAULT_DEPOSIT_DISCRIMINATOR[8] = {0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08};
+typedef struct __attribute__((packed)) {
+    uint8_t discriminator[8];
+    uint64_t amount;
+    vault_side_t side;
+    vault_limit_t limits[2];
+} vault_deposit_data_t;
+enum {
+    VAULT_DEPOSIT_ACCOUNT_VAULT = 0, /* writable */
+    VAULT_DEPOSIT_ACCOUNT_OWNER_AUTHORITY = 1, /* signer */
+    VAULT_DEPOSIT_ACCOUNTS_LEN = 2,
+};
+
+/* setName */
+static const uint8_t VAULT_SET_NAME_DISCRIMINATOR[8] = {0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01};
+/* The args aren't fixed size, so they're borsh encoded after the discriminator. */
+enum {
+    VAULT_SET_NAME_ACCOUNTS_LEN = 0,
+};
+
+#endif /* VAULT_H */
+";
+        assert_eq!(header, expected);
+    }
+}
@@ -1,13 +1,18 @@
+use crate::codegen::program::common;
 use crate::idl::*;
 use crate::parser::context::CrateContext;
 use crate::parser::{self, accounts, error, program};
 use crate::Ty;
-use crate::{AccountField, AccountsStruct, StateIx};
-use anyhow::Result;
+use crate::{
+    AccountField, AccountsStruct, ConstraintSeedsGroup, Dependency, Error, ErrorArgs, EventArgs,
+    FlagsArgs, ProgramArgs, SeedEndian, StateIx,
+};
+use anyhow::{anyhow, Result};
 use heck::MixedCase;
 use quote::ToTokens;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::path::Path;
+use syn::punctuated::Punctuated;
 
 const DERIVE_NAME: &str = "Accounts";
 // TODO: sharee this with `anchor_lang` crate.
@@ -47,6 +52,7 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                                         IdlField {
                                             name: arg.name.to_string().to_mixed_case(),
                                             ty,
+                                            aliases: vec![],
                                         }
                                     })
                                     .collect::<Vec<_>>();
@@ -57,6 +63,12 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                                     name,
                                     accounts,
                                     args,
+                                    simulation_only: None,
+                                    deprecated: None,
+                                    discriminator: Some(common::sighash(
+                                        common::SIGHASH_STATE_NAMESPACE,
+                                        &method.ident.to_string(),
+                                    )),
                                 }
                             })
                             .collect::<Vec<_>>()
@@ -85,6 +97,7 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                                 IdlField {
                                     name: parser::tts_to_string(&arg_typed.pat).to_mixed_case(),
                                     ty,
+                                    aliases: vec![],
                                 }
                             }
                             _ => panic!("Invalid syntax"),
@@ -96,6 +109,9 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                         name,
                         accounts,
                         args,
+                        simulation_only: None,
+                        deprecated: None,
+                        discriminator: Some(common::sighash_ctor()),
                     }
                 };
 
@@ -113,6 +129,7 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                                 IdlField {
                                     name: f.ident.as_ref().unwrap().to_string().to_mixed_case(),
                                     ty,
+                                    aliases: vec![],
                                 }
                             })
                             .collect::<Vec<IdlField>>(),
@@ -121,6 +138,9 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                     IdlTypeDefinition {
                         name: state.name,
                         ty: IdlTypeDefinitionTy::Struct { fields },
+                        codec: None,
+                        discriminator: None,
+                        aliases: vec![],
                     }
                 };
 
@@ -128,19 +148,16 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
             }
         },
     };
-    let error = parse_error_enum(&ctx).map(|mut e| error::parse(&mut e, None));
-    let error_codes = error.as_ref().map(|e| {
-        e.codes
-            .iter()
-            .map(|code| IdlErrorCode {
-                code: ERROR_CODE_OFFSET + code.id,
-                name: code.ident.to_string(),
-                msg: code.msg.clone(),
-            })
-            .collect::<Vec<IdlErrorCode>>()
-    });
+    let error = match parse_error_enum(&ctx) {
+        None => None,
+        Some(mut e) => {
+            let args = error_args(&e)?;
+            Some(error::parse(&mut e, Some(args)))
+        }
+    };
+    let error_codes = error.as_ref().map(idl_error_codes);
 
-    let instructions = p
+    let mut instructions = p
         .ixs
         .iter()
         .map(|ix| {
@@ -154,23 +171,45 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                     IdlField {
                         name: arg.name.to_string().to_mixed_case(),
                         ty,
+                        aliases: vec![],
                     }
                 })
                 .collect::<Vec<_>>();
             // todo: don't unwrap
             let accounts_strct = accs.get(&ix.anchor_ident.to_string()).unwrap();
-            let accounts = idl_accounts(accounts_strct, &accs);
+            let mut accounts = idl_accounts(accounts_strct, &accs);
+            if ix.idempotent.is_some() {
+                accounts.extend(idl_replay_accounts(true));
+            }
             IdlInstruction {
                 name: ix.ident.to_string().to_mixed_case(),
                 accounts,
                 args,
+                simulation_only: if ix.simulation_only { Some(true) } else { None },
+                deprecated: ix.deprecated.as_ref().map(|d| IdlDeprecation {
+                    since: d.since.clone(),
+                    note: d.note.clone(),
+                    sunset_slot: d.sunset_slot,
+                }),
+                discriminator: Some(common::ix_sighash(ix)),
             }
         })
         .collect::<Vec<_>>();
+    if p.ixs.iter().any(|ix| ix.idempotent.is_some()) {
+        instructions.push(IdlInstruction {
+            name: "closeReplay".to_string(),
+            accounts: idl_replay_accounts(false),
+            args: vec![idl_nonce()],
+            simulation_only: None,
+            deprecated: None,
+            discriminator: Some(common::sighash_close_replay()),
+        });
+    }
 
     let events = parse_events(&ctx)
         .iter()
         .map(|e: &&syn::ItemStruct| {
+            let anonymous = event_args(e)?.anonymous;
             let fields = match &e.fields {
                 syn::Fields::Named(n) => n,
                 _ => panic!("Event fields must be named"),
@@ -191,12 +230,17 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
                 })
                 .collect::<Vec<IdlEventField>>();
 
-            IdlEvent {
+            Ok(IdlEvent {
                 name: e.ident.to_string(),
                 fields,
-            }
+                discriminator: match anonymous {
+                    true => None,
+                    false => Some(common::event_discriminator(&e.ident.to_string())),
+                },
+                anonymous: if anonymous { Some(true) } else { None },
+            })
         })
-        .collect::<Vec<IdlEvent>>();
+        .collect::<Result<Vec<IdlEvent>>>()?;
 
     // All user defined types.
     let mut accounts = vec![];
@@ -204,10 +248,15 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
     let ty_defs = parse_ty_defs(&ctx)?;
 
     let account_structs = parse_accounts(&ctx);
-    let account_names: HashSet<String> = account_structs
+    let account_discriminators: HashMap<String, [u8; 8]> = account_structs
         .iter()
-        .map(|a| a.ident.to_string())
-        .collect::<HashSet<_>>();
+        .map(|a| {
+            Ok((
+                idl_names(a.ident.to_string(), &a.attrs)?.0,
+                account_discriminator(a),
+            ))
+        })
+        .collect::<Result<_>>()?;
 
     let error_name = error.map(|e| e.name).unwrap_or_else(|| "".to_string());
 
@@ -215,8 +264,11 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
     for ty_def in ty_defs {
         // Don't add the error type to the types or accounts sections.
         if ty_def.name != error_name {
-            if account_names.contains(&ty_def.name) {
-                accounts.push(ty_def);
+            if let Some(discriminator) = account_discriminators.get(&ty_def.name) {
+                accounts.push(IdlTypeDefinition {
+                    discriminator: Some(*discriminator),
+                    ..ty_def
+                });
             } else if !events.iter().any(|e| e.name == ty_def.name) {
                 types.push(ty_def);
             }
@@ -232,7 +284,9 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
         })
         .collect::<Vec<IdlConst>>();
 
-    Ok(Some(Idl {
+    let dependencies = parse_dependencies(&ctx)?;
+
+    let mut idl = Idl {
         version,
         name: p.name.to_string(),
         state,
@@ -245,12 +299,155 @@ pub fn parse(filename: impl AsRef<Path>, version: String) -> Result<Option<Idl>>
             Some(events)
         },
         errors: error_codes,
+        dependencies,
         metadata: None,
         constants,
-    }))
+    };
+    rename_defined(&mut idl);
+    Ok(Some(idl))
+}
+
+// Points the references to types renamed with `#[idl(name = ..)]`, which
+// are by their Rust name, to their IDL name.
+fn rename_defined(idl: &mut Idl) {
+    let renames: HashMap<String, String> = idl
+        .accounts
+        .iter()
+        .chain(idl.types.iter())
+        .flat_map(|def| {
+            def.aliases
+                .iter()
+                .map(move |alias| (alias.clone(), def.name.clone()))
+        })
+        .collect();
+    if renames.is_empty() {
+        return;
+    }
+    let rename_fields = |fields: &mut Vec<IdlField>| {
+        for f in fields {
+            rename_ty(&mut f.ty, &renames);
+        }
+    };
+    let rename_def = |def: &mut IdlTypeDefinition| match &mut def.ty {
+        IdlTypeDefinitionTy::Struct { fields } => rename_fields(fields),
+        IdlTypeDefinitionTy::Enum { variants } => {
+            for variant in variants {
+                match &mut variant.fields {
+                    Some(EnumFields::Named(fields)) => rename_fields(fields),
+                    Some(EnumFields::Tuple(tys)) => {
+                        tys.iter_mut().for_each(|ty| rename_ty(ty, &renames))
+                    }
+                    None => {}
+                }
+            }
+        }
+        IdlTypeDefinitionTy::Flags { .. } => {}
+    };
+    idl.accounts.iter_mut().for_each(rename_def);
+    idl.types.iter_mut().for_each(rename_def);
+    for ix in idl.instructions.iter_mut() {
+        rename_fields(&mut ix.args);
+    }
+    if let Some(state) = &mut idl.state {
+        rename_def(&mut state.strct);
+        for method in state.methods.iter_mut() {
+            rename_fields(&mut method.args);
+        }
+    }
+    for event in idl.events.iter_mut().flatten() {
+        for f in event.fields.iter_mut() {
+            rename_ty(&mut f.ty, &renames);
+        }
+    }
+    for code in idl.errors.iter_mut().flatten() {
+        if let Some(fields) = &mut code.fields {
+            rename_fields(fields);
+        }
+    }
+}
+
+fn rename_ty(ty: &mut IdlType, renames: &HashMap<String, String>) {
+    match ty {
+        IdlType::Defined(name) => {
+            if let Some(renamed) = renames.get(name) {
+                *name = renamed.clone();
+            }
+        }
+        IdlType::Option(ty) | IdlType::Vec(ty) | IdlType::Array(ty, _) => rename_ty(ty, renames),
+        _ => {}
+    }
 }
 
 // Parse the main program mod.
+// Parse the `#[program]` attribute's arguments of an entire interface file.
+pub fn parse_program_args(filename: impl AsRef<Path>) -> Result<Option<ProgramArgs>> {
+    let ctx = CrateContext::parse(filename)?;
+    let program_mod = match parse_program_mod(&ctx) {
+        None => return Ok(None),
+        Some(m) => m,
+    };
+    let attr = program_mod
+        .attrs
+        .iter()
+        .find(|attr| attr.path.segments.last().unwrap().ident == "program")
+        .unwrap();
+    if attr.tokens.is_empty() {
+        return Ok(Some(ProgramArgs::default()));
+    }
+    Ok(Some(attr.parse_args_with(program::parse_args)?))
+}
+
+/// The shared error enums extended by the program's `#[error]` enum, with
+/// `#[error(extends(<path>, ..))]`.
+pub fn parse_extended_errors(filename: impl AsRef<Path>) -> Result<Vec<syn::Path>> {
+    let ctx = CrateContext::parse(filename)?;
+    match parse_error_enum(&ctx) {
+        None => Ok(vec![]),
+        Some(item_enum) => Ok(error_args(&item_enum)?.extends),
+    }
+}
+
+/// The error codes of the `#[error]` enum named `name` in the crate at
+/// `filename`, e.g. of a shared error crate, at the enum's offset.
+pub fn parse_error_codes(filename: impl AsRef<Path>, name: &str) -> Result<Vec<IdlErrorCode>> {
+    let ctx = CrateContext::parse(filename)?;
+    let mut item_enum = ctx
+        .enums()
+        .find(|item_enum| {
+            item_enum.ident == name
+                && item_enum
+                    .attrs
+                    .iter()
+                    .any(|attr| attr.path.segments.last().unwrap().ident == "error")
+        })
+        .cloned()
+        .ok_or_else(|| anyhow!("#[error] enum {} not found", name))?;
+    let args = error_args(&item_enum)?;
+    Ok(idl_error_codes(&error::parse(&mut item_enum, Some(args))))
+}
+
+/// Adds the codes of shared error enums to the IDL's errors, ahead of the
+/// program's own. Fails if any two errors have the same code.
+pub fn extend_errors(idl: &mut Idl, shared: Vec<IdlErrorCode>) -> Result<()> {
+    let mut errors = shared;
+    errors.extend(idl.errors.take().unwrap_or_default());
+    let mut codes: HashMap<u32, &str> = HashMap::new();
+    for error in &errors {
+        if let Some(other) = codes.insert(error.code, &error.name) {
+            return Err(anyhow!(
+                "Errors {} and {} have the same code {}",
+                other,
+                error.name,
+                error.code
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        idl.errors = Some(errors);
+    }
+    Ok(())
+}
+
 fn parse_program_mod(ctx: &CrateContext) -> Option<syn::ItemMod> {
     let root = ctx.root_module();
     let mods = root
@@ -317,6 +514,66 @@ fn parse_events(ctx: &CrateContext) -> Vec<&syn::ItemStruct> {
         .collect()
 }
 
+// The arguments of the enum's `#[error]` attribute.
+fn error_args(item_enum: &syn::ItemEnum) -> Result<ErrorArgs> {
+    let attr = item_enum
+        .attrs
+        .iter()
+        .find(|attr| attr.path.segments.last().unwrap().ident == "error")
+        .unwrap();
+    if attr.tokens.is_empty() {
+        return Ok(ErrorArgs::default());
+    }
+    Ok(attr.parse_args::<ErrorArgs>()?)
+}
+
+// The IDL error codes of the enum, at its offset.
+fn idl_error_codes(error: &Error) -> Vec<IdlErrorCode> {
+    let offset = match error.args.as_ref().and_then(|args| args.offset.as_ref()) {
+        None => ERROR_CODE_OFFSET,
+        Some(offset) => offset.base10_parse().expect("Must be a base 10 number"),
+    };
+    error
+        .codes
+        .iter()
+        .map(|code| IdlErrorCode {
+            code: offset + code.id,
+            name: code.ident.to_string(),
+            msg: code.msg.clone(),
+            fields: match code.fields.is_empty() {
+                true => None,
+                false => Some(
+                    code.fields
+                        .iter()
+                        .map(|f: &syn::Field| {
+                            let mut tts = proc_macro2::TokenStream::new();
+                            f.ty.to_tokens(&mut tts);
+                            IdlField {
+                                name: f.ident.as_ref().unwrap().to_string().to_mixed_case(),
+                                ty: tts.to_string().parse().unwrap(),
+                                aliases: vec![],
+                            }
+                        })
+                        .collect(),
+                ),
+            },
+        })
+        .collect()
+}
+
+// The arguments of the event's `#[event]` attribute.
+fn event_args(strct: &syn::ItemStruct) -> Result<EventArgs> {
+    let attr = strct
+        .attrs
+        .iter()
+        .find(|attr| attr.path.segments.last().unwrap().ident == "event")
+        .unwrap();
+    if attr.tokens.is_empty() {
+        return Ok(EventArgs::default());
+    }
+    Ok(attr.parse_args::<EventArgs>()?)
+}
+
 fn parse_accounts(ctx: &CrateContext) -> Vec<&syn::ItemStruct> {
     ctx.structs()
         .filter_map(|item_strct| {
@@ -337,6 +594,30 @@ fn parse_accounts(ctx: &CrateContext) -> Vec<&syn::ItemStruct> {
         .collect()
 }
 
+// Discriminator of an `#[account]` struct, namespaced by its string argument,
+// if any.
+fn account_discriminator(strct: &syn::ItemStruct) -> [u8; 8] {
+    let namespace = strct
+        .attrs
+        .iter()
+        .find(|attr| attr.path.segments.last().unwrap().ident == "account")
+        .and_then(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated)
+                .ok()
+        })
+        .and_then(|args| {
+            args.into_iter().find_map(|arg| match arg {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(namespace),
+                    ..
+                }) => Some(namespace.value()),
+                _ => None,
+            })
+        })
+        .unwrap_or_default();
+    common::account_discriminator(&namespace, &strct.ident.to_string())
+}
+
 // Parse all structs implementing the `Accounts` trait.
 fn parse_account_derives(ctx: &CrateContext) -> HashMap<String, AccountsStruct> {
     // TODO: parse manual implementations. Currently we only look
@@ -367,6 +648,28 @@ fn parse_consts(ctx: &CrateContext) -> Vec<&syn::ItemConst> {
         .collect()
 }
 
+// Parse all `declare_dependency!` invocations.
+fn parse_dependencies(ctx: &CrateContext) -> Result<Vec<IdlDependency>> {
+    ctx.macros()
+        .filter(|m| {
+            m.mac
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident == "declare_dependency")
+                .unwrap_or(false)
+        })
+        .map(|m| {
+            let dependency: Dependency = m.mac.parse_body()?;
+            Ok(IdlDependency {
+                name: dependency.name.to_string(),
+                program_id: dependency.program_id.value(),
+                idl_hash: dependency.idl_hash.map(|h| h.value().to_lowercase()),
+            })
+        })
+        .collect()
+}
+
 // Parse all user defined types in the file.
 fn parse_ty_defs(ctx: &CrateContext) -> Result<Vec<IdlTypeDefinition>> {
     ctx.structs()
@@ -394,17 +697,26 @@ fn parse_ty_defs(ctx: &CrateContext) -> Result<Vec<IdlTypeDefinition>> {
                 _ => return None,
             }
 
-            let name = item_strct.ident.to_string();
+            let (name, aliases) = match idl_names(item_strct.ident.to_string(), &item_strct.attrs) {
+                Ok(names) => names,
+                Err(err) => return Some(Err(err)),
+            };
             let fields = match &item_strct.fields {
                 syn::Fields::Named(fields) => fields
                     .named
                     .iter()
+                    .filter(|f| !f.attrs.iter().any(|attr| attr.path.is_ident("transient")))
                     .map(|f: &syn::Field| {
                         let mut tts = proc_macro2::TokenStream::new();
                         f.ty.to_tokens(&mut tts);
+                        let (name, aliases) = idl_names(
+                            f.ident.as_ref().unwrap().to_string().to_mixed_case(),
+                            &f.attrs,
+                        )?;
                         Ok(IdlField {
-                            name: f.ident.as_ref().unwrap().to_string().to_mixed_case(),
+                            name,
                             ty: tts.to_string().parse()?,
+                            aliases,
                         })
                     })
                     .collect::<Result<Vec<IdlField>>>(),
@@ -415,10 +727,41 @@ fn parse_ty_defs(ctx: &CrateContext) -> Result<Vec<IdlTypeDefinition>> {
             Some(fields.map(|fields| IdlTypeDefinition {
                 name,
                 ty: IdlTypeDefinitionTy::Struct { fields },
+                codec: parse_account_codec(item_strct),
+                discriminator: None,
+                aliases,
             }))
         })
         .chain(ctx.enums().map(|enm| {
-            let name = enm.ident.to_string();
+            let (name, aliases) = idl_names(enm.ident.to_string(), &enm.attrs)?;
+            if let Some(flags_attr) = enm
+                .attrs
+                .iter()
+                .find(|attr| attr.path.segments.last().unwrap().ident == "flags")
+            {
+                let args = match flags_attr.tokens.is_empty() {
+                    true => None,
+                    false => Some(flags_attr.parse_args::<FlagsArgs>()?),
+                };
+                let flags = parser::flags::parse(enm, args)?;
+                return Ok(IdlTypeDefinition {
+                    name,
+                    ty: IdlTypeDefinitionTy::Flags {
+                        repr: flags.repr.to_string().parse()?,
+                        flags: flags
+                            .flags
+                            .iter()
+                            .map(|f| IdlFlag {
+                                name: f.ident.to_string(),
+                                value: f.value,
+                            })
+                            .collect(),
+                    },
+                    codec: None,
+                    discriminator: None,
+                    aliases: aliases.clone(),
+                });
+            }
             let variants = enm
                 .variants
                 .iter()
@@ -436,25 +779,63 @@ fn parse_ty_defs(ctx: &CrateContext) -> Result<Vec<IdlTypeDefinition>> {
                                 .named
                                 .iter()
                                 .map(|f: &syn::Field| {
-                                    let name = f.ident.as_ref().unwrap().to_string();
+                                    let (name, aliases) =
+                                        idl_names(f.ident.as_ref().unwrap().to_string(), &f.attrs)?;
                                     let ty = to_idl_type(f);
-                                    IdlField { name, ty }
+                                    Ok(IdlField { name, ty, aliases })
                                 })
-                                .collect();
+                                .collect::<Result<_>>()?;
                             Some(EnumFields::Named(fields))
                         }
                     };
-                    IdlEnumVariant { name, fields }
+                    Ok(IdlEnumVariant { name, fields })
                 })
-                .collect::<Vec<IdlEnumVariant>>();
+                .collect::<Result<Vec<IdlEnumVariant>>>()?;
             Ok(IdlTypeDefinition {
                 name,
                 ty: IdlTypeDefinitionTy::Enum { variants },
+                codec: None,
+                discriminator: None,
+                aliases,
             })
         }))
         .collect()
 }
 
+// The IDL name of a type or field, given by its `#[idl(name = ..)]`
+// attribute or else its Rust name, and its aliases, i.e. the Rust name if
+// it's renamed.
+fn idl_names(rust_name: String, attrs: &[syn::Attribute]) -> Result<(String, Vec<String>)> {
+    Ok(match parser::idl_name(attrs)? {
+        Some(name) if name != rust_name => (name, vec![rust_name]),
+        _ => (rust_name, vec![]),
+    })
+}
+
+// Parse the `codec` argument of an `#[account]` attribute, or `compressed`.
+// Borsh, the default, isn't recorded.
+fn parse_account_codec(item_strct: &syn::ItemStruct) -> Option<String> {
+    item_strct
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.segments.last().unwrap().ident == "account")
+        .flat_map(|attr| {
+            let args: String = attr
+                .tokens
+                .to_string()
+                .chars()
+                .filter(|c| !c.is_whitespace() && !matches!(c, '(' | ')' | '"'))
+                .collect();
+            args.split(',')
+                .filter_map(|arg| match arg {
+                    "compressed" => Some(arg.to_string()),
+                    _ => arg.strip_prefix("codec=").map(|c| c.to_string()),
+                })
+                .collect::<Vec<_>>()
+        })
+        .find(|codec| codec != "borsh")
+}
+
 fn to_idl_type(f: &syn::Field) -> IdlType {
     let mut tts = proc_macro2::TokenStream::new();
     f.ty.to_tokens(&mut tts);
@@ -479,14 +860,330 @@ fn idl_accounts(
                     accounts,
                 })
             }
-            AccountField::Field(acc) => IdlAccountItem::IdlAccount(IdlAccount {
-                name: acc.ident.to_string().to_mixed_case(),
-                is_mut: acc.constraints.is_mutable(),
-                is_signer: match acc.ty {
-                    Ty::Signer => true,
-                    _ => acc.constraints.is_signer(),
-                },
-            }),
+            AccountField::Field(acc) => {
+                IdlAccountItem::IdlAccount(IdlAccount {
+                    name: acc.ident.to_string().to_mixed_case(),
+                    is_mut: acc.constraints.is_mutable(),
+                    is_signer: match acc.ty {
+                        Ty::Signer => true,
+                        _ => acc.constraints.is_signer(),
+                    },
+                    pda: acc.constraints.seeds.as_ref().map(|c| IdlPda {
+                        seeds: c
+                            .seeds
+                            .iter()
+                            .map(|seed| idl_seed(accounts, seed))
+                            .collect(),
+                    }),
+                    relations: idl_relations(accounts, &acc.ident),
+                    active_window: acc.constraints.active_window.as_ref().map(|c| {
+                        IdlActiveWindow {
+                            start: expr_to_string(&c.start),
+                            end: expr_to_string(&c.end),
+                        }
+                    }),
+                })
+            }
         })
         .collect::<Vec<_>>()
 }
+
+// Accounts appended to `#[idempotent]` instructions, i.e. the replay account
+// derived from the nonce arg, its payer and, when it's created, the system
+// program. See `anchor_lang::idempotent`.
+fn idl_replay_accounts(create: bool) -> Vec<IdlAccountItem> {
+    let account = |name: &str, is_mut: bool, is_signer: bool| IdlAccount {
+        name: name.to_string(),
+        is_mut,
+        is_signer,
+        pda: None,
+        relations: vec![],
+        active_window: None,
+    };
+    let mut accounts = vec![
+        IdlAccount {
+            pda: Some(IdlPda {
+                seeds: vec![
+                    IdlSeed::Const {
+                        value: b"anchor:replay".to_vec(),
+                    },
+                    IdlSeed::Expr {
+                        value: "nonce".to_string(),
+                    },
+                ],
+            }),
+            ..account("replay", true, false)
+        },
+        account("replayPayer", true, create),
+    ];
+    if create {
+        accounts.push(account("systemProgram", false, false));
+    }
+    accounts
+        .into_iter()
+        .map(IdlAccountItem::IdlAccount)
+        .collect()
+}
+
+fn idl_nonce() -> IdlField {
+    IdlField {
+        name: "nonce".to_string(),
+        ty: "[u8; 16]".parse().unwrap(),
+        aliases: vec![],
+    }
+}
+
+fn idl_relations(accounts: &AccountsStruct, target: &syn::Ident) -> Vec<String> {
+    accounts
+        .fields
+        .iter()
+        .filter_map(|f| match f {
+            AccountField::Field(f) if f.has_one_targets().contains(&target) => {
+                Some(f.ident.to_string().to_mixed_case())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn idl_seed(accounts: &AccountsStruct, seed: &syn::Expr) -> IdlSeed {
+    if let Some(seeds) = ConstraintSeedsGroup::hash_seed(seed) {
+        return IdlSeed::Hash {
+            seeds: seeds.iter().map(|seed| idl_seed(accounts, seed)).collect(),
+        };
+    }
+    if let Some((value, endian)) = ConstraintSeedsGroup::int_seed(seed) {
+        let (value, ty) = match value {
+            syn::Expr::Cast(cast) => (&*cast.expr, Some(&*cast.ty)),
+            _ => (value, None),
+        };
+        let ty = ty
+            .or_else(|| accounts.instruction_arg_ty(&expr_to_string(value)))
+            .and_then(|ty| ty.to_token_stream().to_string().parse().ok());
+        return IdlSeed::Int {
+            value: expr_to_string(value),
+            ty,
+            endian: match endian {
+                SeedEndian::Little => IdlSeedEndian::Le,
+                SeedEndian::Big => IdlSeedEndian::Be,
+            },
+        };
+    }
+    // Byte string literals, optionally followed by `.as_ref()`.
+    let lit = match seed {
+        syn::Expr::MethodCall(call) if call.method == "as_ref" && call.args.is_empty() => {
+            &*call.receiver
+        }
+        _ => seed,
+    };
+    match lit {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::ByteStr(bytes),
+            ..
+        }) => IdlSeed::Const {
+            value: bytes.value(),
+        },
+        _ => IdlSeed::Expr {
+            value: expr_to_string(seed),
+        },
+    }
+}
+
+fn expr_to_string(expr: &syn::Expr) -> String {
+    expr.to_token_stream()
+        .to_string()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_discriminators() {
+        let plain: syn::ItemStruct = syn::parse_quote! {
+            #[account(zero_copy)]
+            pub struct Counter {}
+        };
+        assert_eq!(
+            account_discriminator(&plain),
+            common::sighash("account", "Counter")
+        );
+        let namespaced: syn::ItemStruct = syn::parse_quote! {
+            #[account("state")]
+            pub struct Counter {}
+        };
+        assert_eq!(
+            account_discriminator(&namespaced),
+            common::sighash("state", "Counter")
+        );
+    }
+
+    #[test]
+    fn anonymous_events() {
+        let plain: syn::ItemStruct = syn::parse_quote! {
+            #[event(derive(Clone))]
+            pub struct Trade {}
+        };
+        assert!(!event_args(&plain).unwrap().anonymous);
+        let anonymous: syn::ItemStruct = syn::parse_quote! {
+            #[event(anonymous, derive(Clone))]
+            pub struct Tick {}
+        };
+        let args = event_args(&anonymous).unwrap();
+        assert!(args.anonymous);
+        assert_eq!(args.derives.len(), 1);
+    }
+
+    #[test]
+    fn shared_error_codes() {
+        let dir = std::env::temp_dir().join(format!("anchor-idl-errors-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let shared = dir.join("shared.rs");
+        std::fs::write(
+            &shared,
+            r#"
+                #[error(offset = 9000)]
+                pub enum ProtocolError {
+                    #[msg("The protocol is paused")]
+                    Paused,
+                    Halted = 5,
+                }
+            "#,
+        )
+        .unwrap();
+        let program = dir.join("program.rs");
+        std::fs::write(
+            &program,
+            r#"
+                #[error(extends(protocol_errors::ProtocolError))]
+                pub enum ErrorCode {
+                    Unauthorized,
+                }
+            "#,
+        )
+        .unwrap();
+        let extends = parse_extended_errors(&program).unwrap();
+        let shared_codes = parse_error_codes(&shared, "ProtocolError").unwrap();
+        assert!(parse_error_codes(&shared, "ErrorCode").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(extends.len(), 1);
+        assert_eq!(
+            extends[0].segments.last().unwrap().ident.to_string(),
+            "ProtocolError"
+        );
+        let codes: Vec<(u32, &str)> = shared_codes
+            .iter()
+            .map(|code| (code.code, code.name.as_str()))
+            .collect();
+        assert_eq!(codes, vec![(9000, "Paused"), (9005, "Halted")]);
+
+        let mut idl: Idl = serde_json::from_value(serde_json::json!({
+            "version": "0.1.0",
+            "name": "vault",
+            "instructions": [],
+            "errors": [{ "code": 6000, "name": "Unauthorized" }]
+        }))
+        .unwrap();
+        extend_errors(&mut idl, shared_codes.clone()).unwrap();
+        let names: Vec<&str> = idl
+            .errors
+            .iter()
+            .flatten()
+            .map(|code| code.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Paused", "Halted", "Unauthorized"]);
+        assert!(extend_errors(&mut idl, shared_codes).is_err());
+    }
+
+    #[test]
+    fn active_windows() {
+        let strct: syn::ItemStruct = syn::parse_quote! {
+            pub struct Buy<'info> {
+                #[account(active_window = launch.start..launch.end)]
+                pub launch: Account<'info, Launch>,
+            }
+        };
+        let accounts = accounts::parse(&strct).unwrap();
+        match &idl_accounts(&accounts, &HashMap::new())[0] {
+            IdlAccountItem::IdlAccount(account) => assert_eq!(
+                account.active_window,
+                Some(IdlActiveWindow {
+                    start: "launch.start".to_string(),
+                    end: "launch.end".to_string(),
+                })
+            ),
+            _ => panic!("launch must be an account"),
+        }
+    }
+
+    #[test]
+    fn renamed_types_and_fields() {
+        let dir = std::env::temp_dir().join(format!("anchor-idl-rename-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        std::fs::write(
+            &path,
+            r#"
+                #[program]
+                pub mod pools {
+                    use super::*;
+                    pub fn update(ctx: Context<Update>, fees: FeeSchedule) -> ProgramResult {
+                        Ok(())
+                    }
+                }
+
+                #[derive(Accounts)]
+                pub struct Update<'info> {
+                    pub pool: Account<'info, LiquidityPool>,
+                }
+
+                #[account]
+                #[idl(name = "Pool")]
+                pub struct LiquidityPool {
+                    #[idl(name = "feeBps")]
+                    pub fee_rate_bps: u16,
+                    pub fees: Vec<FeeSchedule>,
+                }
+
+                #[idl(name = "Fees")]
+                #[derive(AnchorSerialize, AnchorDeserialize)]
+                pub struct FeeSchedule {
+                    pub maker: u16,
+                }
+            "#,
+        )
+        .unwrap();
+        let idl = parse(&path, "0.1.0".to_string()).unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let pool = &idl.accounts[0];
+        assert_eq!(pool.name, "Pool");
+        assert_eq!(pool.aliases, vec!["LiquidityPool".to_string()]);
+        assert_eq!(
+            pool.discriminator,
+            Some(common::sighash("account", "LiquidityPool"))
+        );
+        let fields = match &pool.ty {
+            IdlTypeDefinitionTy::Struct { fields } => fields,
+            _ => panic!("Pool must be a struct"),
+        };
+        assert_eq!(fields[0].name, "feeBps");
+        assert_eq!(fields[0].aliases, vec!["feeRateBps".to_string()]);
+        assert_eq!(
+            fields[1].ty,
+            IdlType::Vec(Box::new(IdlType::Defined("Fees".to_string())))
+        );
+        assert!(fields[1].aliases.is_empty());
+
+        assert_eq!(idl.types[0].name, "Fees");
+        assert_eq!(idl.types[0].aliases, vec!["FeeSchedule".to_string()]);
+        assert_eq!(
+            idl.instructions[0].args[0].ty,
+            IdlType::Defined("Fees".to_string())
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
+pub mod c_header;
 pub mod file;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,10 +21,21 @@ pub struct Idl {
     pub events: Option<Vec<IdlEvent>>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub errors: Option<Vec<IdlErrorCode>>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub dependencies: Vec<IdlDependency>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub metadata: Option<JsonValue>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlDependency {
+    pub name: String,
+    pub program_id: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub idl_hash: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IdlConst {
     pub name: String,
@@ -40,10 +52,29 @@ pub struct IdlState {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct IdlInstruction {
     pub name: String,
     pub accounts: Vec<IdlAccountItem>,
     pub args: Vec<IdlField>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub simulation_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub deprecated: Option<IdlDeprecation>,
+    // The sighash the instruction is dispatched by.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub discriminator: Option<[u8; 8]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlDeprecation {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sunset_slot: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -66,6 +97,62 @@ pub struct IdlAccount {
     pub name: String,
     pub is_mut: bool,
     pub is_signer: bool,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pda: Option<IdlPda>,
+    // Accounts storing this account's address, as given by their `has_one`
+    // constraints, from which clients can resolve it.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub relations: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub active_window: Option<IdlActiveWindow>,
+}
+
+// The unix timestamps between which the instruction can be invoked, as given
+// by the account's `active_window` constraint, e.g. `config.start`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlActiveWindow {
+    pub start: String,
+    pub end: String,
+}
+
+// Seeds the account's address is derived from, as given by its `seeds`
+// constraint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlPda {
+    pub seeds: Vec<IdlSeed>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum IdlSeed {
+    // Constant bytes, e.g. `b"vault"`.
+    Const {
+        value: Vec<u8>,
+    },
+    // An integer, given as `le(<expr>)` or `be(<expr>)`. The type is known if
+    // the integer is cast or is an `#[instruction(...)]` argument.
+    Int {
+        value: String,
+        #[serde(rename = "type", skip_serializing_if = "Option::is_none", default)]
+        ty: Option<IdlType>,
+        endian: IdlSeedEndian,
+    },
+    // The hash of other seeds, given as `hash(<seed>, ..)`, see
+    // `anchor_lang::seeds::hash`.
+    Hash {
+        seeds: Vec<IdlSeed>,
+    },
+    // Any other expression, as written.
+    Expr {
+        value: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdlSeedEndian {
+    Le,
+    Be,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -73,12 +160,22 @@ pub struct IdlField {
     pub name: String,
     #[serde(rename = "type")]
     pub ty: IdlType,
+    // Other names of the field accepted by clients, i.e. its Rust name if
+    // it's renamed with `#[idl(name = ..)]`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IdlEvent {
     pub name: String,
     pub fields: Vec<IdlEventField>,
+    // Prefixing the event's logs, unless it's anonymous.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub discriminator: Option<[u8; 8]>,
+    // Set for events logged without a discriminator.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub anonymous: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -94,6 +191,17 @@ pub struct IdlTypeDefinition {
     pub name: String,
     #[serde(rename = "type")]
     pub ty: IdlTypeDefinitionTy,
+    // Serialization scheme of an account, if not borsh.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub codec: Option<String>,
+    // Discriminator prefixing the data of accounts of this type, only set
+    // for accounts.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub discriminator: Option<[u8; 8]>,
+    // Other names of the type accepted by clients, i.e. its Rust name if
+    // it's renamed with `#[idl(name = ..)]`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -101,6 +209,13 @@ pub struct IdlTypeDefinition {
 pub enum IdlTypeDefinitionTy {
     Struct { fields: Vec<IdlField> },
     Enum { variants: Vec<IdlEnumVariant> },
+    Flags { repr: IdlType, flags: Vec<IdlFlag> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlFlag {
+    pub name: String,
+    pub value: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -138,6 +253,7 @@ pub enum IdlType {
     Option(Box<IdlType>),
     Vec(Box<IdlType>),
     Array(Box<IdlType>, usize),
+    FixedString(usize),
 }
 
 impl std::str::FromStr for IdlType {
@@ -145,18 +261,37 @@ impl std::str::FromStr for IdlType {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut s = s.to_string();
-        fn array_from_str(inner: &str) -> IdlType {
+        fn array_from_str(inner: &str) -> Result<IdlType, anyhow::Error> {
             match inner.strip_suffix(']') {
                 None => {
-                    let (raw_type, raw_length) = inner.rsplit_once(';').unwrap();
-                    let ty = IdlType::from_str(raw_type).unwrap();
-                    let len = raw_length.replace("_", "").parse::<usize>().unwrap();
-                    IdlType::Array(Box::new(ty), len)
+                    let (raw_type, raw_length) = inner
+                        .rsplit_once(';')
+                        .ok_or_else(|| anyhow::anyhow!("Invalid array: {}", inner))?;
+                    let ty = IdlType::from_str(raw_type)?;
+                    let len = raw_length
+                        .replace("_", "")
+                        .parse::<usize>()
+                        .map_err(|_| anyhow::anyhow!("Invalid array length: {}", raw_length))?;
+                    Ok(IdlType::Array(Box::new(ty), len))
                 }
                 Some(nested_inner) => array_from_str(&nested_inner[1..]),
             }
         }
         s.retain(|c| !c.is_whitespace());
+        // Fully qualified public keys, e.g. `solana_program::pubkey::Pubkey`.
+        if s.ends_with("::Pubkey") && !s.contains(&['<', '['][..]) {
+            return Ok(IdlType::PublicKey);
+        }
+        // Fixed strings, e.g. `FixedStr<32>` or `anchor_lang::FixedStr<32>`.
+        if let Some((path, len)) = s.strip_suffix('>').and_then(|s| s.split_once('<')) {
+            if path == "FixedStr" || path.ends_with("::FixedStr") {
+                let len = len
+                    .replace("_", "")
+                    .parse::<usize>()
+                    .map_err(|_| anyhow::anyhow!("Invalid fixed string length: {}", len))?;
+                return Ok(IdlType::FixedString(len));
+            }
+        }
         let r = match s.as_str() {
             "bool" => IdlType::Bool,
             "u8" => IdlType::U8,
@@ -176,7 +311,7 @@ impl std::str::FromStr for IdlType {
                 None => match s.to_string().strip_prefix("Vec<") {
                     None => {
                         if s.to_string().starts_with('[') {
-                            array_from_str(&s)
+                            array_from_str(&s)?
                         } else {
                             IdlType::Defined(s.to_string())
                         }
@@ -210,6 +345,8 @@ pub struct IdlErrorCode {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub msg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fields: Option<Vec<IdlField>>,
 }
 
 #[cfg(test)]
@@ -249,6 +386,55 @@ mod tests {
         )
     }
 
+    #[test]
+    fn qualified_pubkey() {
+        assert_eq!(
+            IdlType::from_str("solana_program::pubkey::Pubkey").unwrap(),
+            IdlType::PublicKey
+        );
+        assert_eq!(
+            IdlType::from_str("Option<anchor_lang::prelude::Pubkey>").unwrap(),
+            IdlType::Option(Box::new(IdlType::PublicKey))
+        );
+    }
+
+    #[test]
+    fn option_array() {
+        assert_eq!(
+            IdlType::from_str("Option<[u8; 32]>").unwrap(),
+            IdlType::Option(Box::new(IdlType::Array(Box::new(IdlType::U8), 32)))
+        );
+        assert_eq!(
+            IdlType::from_str("[Option<Pubkey>; 4]").unwrap(),
+            IdlType::Array(Box::new(IdlType::Option(Box::new(IdlType::PublicKey))), 4)
+        );
+    }
+
+    #[test]
+    fn fixed_string() {
+        assert_eq!(
+            IdlType::from_str("FixedStr<32>").unwrap(),
+            IdlType::FixedString(32)
+        );
+        assert_eq!(
+            IdlType::from_str("Option<anchor_lang::FixedStr<1_024>>").unwrap(),
+            IdlType::Option(Box::new(IdlType::FixedString(1024)))
+        );
+    }
+
+    #[test]
+    fn nested_option() {
+        assert_eq!(
+            IdlType::from_str("Option<Option<Pubkey>>").unwrap(),
+            IdlType::Option(Box::new(IdlType::Option(Box::new(IdlType::PublicKey))))
+        );
+    }
+
+    #[test]
+    fn array_with_non_literal_length() {
+        assert!(IdlType::from_str("[u8; LEN]").is_err());
+    }
+
     #[test]
     fn vector() {
         assert_eq!(
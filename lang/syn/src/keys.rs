@@ -0,0 +1,282 @@
+//! Program key management, keeping the ids declared with `declare_id!` in
+//! sync with the program keypairs in a workspace's `target/deploy` directory.
+//!
+//! This backs the CLI's `anchor keys sync`, and the build time check done by
+//! `declare_id!` with anchor-lang's `check-program-id` feature, which also
+//! compares the declared ids with the `[programs.<cluster>]` addresses of the
+//! workspace's `Anchor.toml`.
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DECLARE_ID: &str = "declare_id!(";
+
+/// Environment variable naming the cluster programs are built for, set by
+/// `anchor build`. Defaults to the `[provider]` cluster of `Anchor.toml`.
+pub const CLUSTER_ENV: &str = "ANCHOR_CLUSTER";
+
+/// A literal `declare_id!("<id>")` invocation in a program's sources.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclaredId {
+    pub path: PathBuf,
+    // 1-based line of the invocation.
+    pub line: usize,
+    pub id: String,
+}
+
+/// A declared id not matching the program's keypair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdMismatch {
+    pub declared: DeclaredId,
+    pub expected: String,
+}
+
+/// Path of the keypair the CLI deploys the program `lib_name` with.
+pub fn keypair_path(workspace_root: &Path, lib_name: &str) -> PathBuf {
+    workspace_root
+        .join("target")
+        .join("deploy")
+        .join(format!("{}-keypair.json", lib_name))
+}
+
+/// Returns the base58 encoded public key of the JSON keypair file at `path`,
+/// as written by `solana-keygen`.
+pub fn read_keypair_pubkey(path: &Path) -> Result<String> {
+    let bytes: Vec<u8> = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| anyhow!("invalid keypair file {}: {}", path.display(), e))?;
+    if bytes.len() != 64 {
+        return Err(anyhow!("invalid keypair file {}", path.display()));
+    }
+    // The public key follows the 32 byte secret key.
+    Ok(bs58::encode(&bytes[32..]).into_string())
+}
+
+/// Returns the cluster and address of the program `lib_name` in the
+/// `[programs.<cluster>]` section of the workspace's `Anchor.toml`, if it's
+/// listed there. `cluster` defaults to the `[provider]`'s.
+pub fn configured_program_id(
+    workspace_root: &Path,
+    cluster: Option<&str>,
+    lib_name: &str,
+) -> Result<Option<(String, String)>> {
+    let path = workspace_root.join("Anchor.toml");
+    let cfg: toml::Value = fs::read_to_string(&path)?
+        .parse()
+        .map_err(|e| anyhow!("invalid {}: {}", path.display(), e))?;
+    let cluster = match cluster {
+        Some(cluster) => cluster.to_string(),
+        None => match cfg
+            .get("provider")
+            .and_then(|provider| provider.get("cluster"))
+            .and_then(|cluster| cluster.as_str())
+        {
+            Some(cluster) => cluster.to_string(),
+            None => return Ok(None),
+        },
+    };
+    let cluster = cluster_name(&cluster);
+    let program = match cfg
+        .get("programs")
+        .and_then(|programs| programs.get(&cluster))
+        .and_then(|programs| programs.get(lib_name))
+    {
+        Some(program) => program,
+        None => return Ok(None),
+    };
+    // Either the address, or a table with the address and e.g. the path of
+    // the program to deploy.
+    let address = match program {
+        toml::Value::String(address) => Some(address.as_str()),
+        toml::Value::Table(table) => table.get("address").and_then(|a| a.as_str()),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow!("invalid address of {} in [programs.{}]", lib_name, cluster))?;
+    Ok(Some((cluster, address.to_string())))
+}
+
+// The name of the cluster as written by the CLI, expanding its aliases.
+fn cluster_name(cluster: &str) -> String {
+    match cluster.to_lowercase().as_str() {
+        "t" | "testnet" => "testnet".to_string(),
+        "m" | "mainnet" => "mainnet".to_string(),
+        "d" | "devnet" => "devnet".to_string(),
+        "l" | "localnet" => "localnet".to_string(),
+        "g" | "debug" => "debug".to_string(),
+        _ => cluster.to_string(),
+    }
+}
+
+/// Finds the literal `declare_id!` invocations in the `src` directory of the
+/// program at `program_dir`.
+pub fn find_declared_ids(program_dir: &Path) -> Result<Vec<DeclaredId>> {
+    let mut ids = vec![];
+    for path in rust_files(&program_dir.join("src"))? {
+        let source = fs::read_to_string(&path)?;
+        for (idx, line) in source.lines().enumerate() {
+            if let Some(id) = parse_declared_id(line) {
+                ids.push(DeclaredId {
+                    path: path.clone(),
+                    line: idx + 1,
+                    id: id.to_string(),
+                });
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Returns the ids declared by the program at `program_dir` that don't
+/// match `expected`.
+pub fn check_declared_ids(program_dir: &Path, expected: &str) -> Result<Vec<IdMismatch>> {
+    Ok(find_declared_ids(program_dir)?
+        .into_iter()
+        .filter(|declared| declared.id != expected)
+        .map(|declared| IdMismatch {
+            declared,
+            expected: expected.to_string(),
+        })
+        .collect())
+}
+
+/// Rewrites the ids declared by the program at `program_dir` that don't
+/// match `expected`, returning the ids that were replaced.
+pub fn sync_declared_ids(program_dir: &Path, expected: &str) -> Result<Vec<IdMismatch>> {
+    let mismatches = check_declared_ids(program_dir, expected)?;
+    let mut paths: Vec<&PathBuf> = mismatches.iter().map(|m| &m.declared.path).collect();
+    paths.dedup();
+    for path in paths {
+        let source = fs::read_to_string(path)?;
+        let mut synced: String = source
+            .lines()
+            .map(|line| match parse_declared_id(line) {
+                Some(id) if id != expected => line.replacen(id, expected, 1),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        if source.ends_with('\n') {
+            synced.push('\n');
+        }
+        fs::write(path, synced)?;
+    }
+    Ok(mismatches)
+}
+
+// Returns the id of a `declare_id!("<id>")` invocation on the given line,
+// ignoring commented out lines and non literal ids.
+fn parse_declared_id(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("//") {
+        return None;
+    }
+    let start = line.find(DECLARE_ID)? + DECLARE_ID.len();
+    let rest = line[start..].trim_start().strip_prefix('"')?;
+    let end = rest.find('"')?;
+    Some(&rest[..end])
+}
+
+fn rust_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<PathBuf>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            files.extend(rust_files(&path)?);
+        } else if path.extension().map_or(false, |ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_declared_ids() {
+        assert_eq!(
+            parse_declared_id("declare_id!(\"Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS\");"),
+            Some("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS")
+        );
+        assert_eq!(
+            parse_declared_id("    anchor_lang::declare_id!( \"abc\" );"),
+            Some("abc")
+        );
+        assert_eq!(parse_declared_id("// declare_id!(\"abc\");"), None);
+        assert_eq!(parse_declared_id("declare_id!(MY_ID);"), None);
+    }
+
+    #[test]
+    fn reads_configured_program_ids() {
+        let dir = std::env::temp_dir().join(format!("anchor-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("Anchor.toml"),
+            r#"
+[provider]
+cluster = "l"
+wallet = "~/.config/solana/id.json"
+
+[programs.localnet]
+vault = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS"
+
+[programs.devnet]
+vault = { address = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin", idl = "vault.json" }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            configured_program_id(&dir, None, "vault").unwrap(),
+            Some((
+                "localnet".to_string(),
+                "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS".to_string()
+            ))
+        );
+        assert_eq!(
+            configured_program_id(&dir, Some("devnet"), "vault").unwrap(),
+            Some((
+                "devnet".to_string(),
+                "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string()
+            ))
+        );
+        assert_eq!(
+            configured_program_id(&dir, Some("mainnet"), "vault").unwrap(),
+            None
+        );
+        assert_eq!(configured_program_id(&dir, None, "other").unwrap(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn syncs_declared_ids() {
+        let dir = std::env::temp_dir().join(format!("anchor-keys-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let lib = dir.join("src").join("lib.rs");
+        fs::write(
+            &lib,
+            "use anchor_lang::prelude::*;\n\ndeclare_id!(\"old\");\n",
+        )
+        .unwrap();
+
+        let mismatches = sync_declared_ids(&dir, "new").unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].declared.line, 3);
+        assert_eq!(mismatches[0].declared.id, "old");
+        assert_eq!(
+            fs::read_to_string(&lib).unwrap(),
+            "use anchor_lang::prelude::*;\n\ndeclare_id!(\"new\");\n"
+        );
+        assert!(check_declared_ids(&dir, "new").unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -13,7 +13,7 @@ use syn::spanned::Spanned;
 use syn::token::Comma;
 use syn::{
     Expr, Generics, Ident, ImplItemMethod, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStruct, LitInt,
-    LitStr, PatType, Token, TypePath,
+    LitStr, PatType, Token, TypePath, Visibility,
 };
 
 pub mod codegen;
@@ -23,7 +23,9 @@ pub mod hash;
 pub(crate) mod hash;
 #[cfg(feature = "idl")]
 pub mod idl;
+pub mod keys;
 pub mod parser;
+pub mod visit;
 
 #[derive(Debug)]
 pub struct Program {
@@ -32,6 +34,34 @@ pub struct Program {
     pub name: Ident,
     pub program_mod: ItemMod,
     pub fallback_fn: Option<FallbackFn>,
+    // Raw handlers of the instructions starting with a given prefix, see
+    // `#[instruction_prefix]`.
+    pub prefix_fns: Vec<PrefixFn>,
+    // Function installed as the program's panic handler, if any.
+    pub panic_handler: Option<syn::Path>,
+    // Additional traits derived for the generated instruction structs.
+    pub derives: Vec<syn::Path>,
+    // Programs the program may invoke, if restricted.
+    pub cpi_allowlist: Option<CpiAllowlist>,
+    // True if the program has the instructions managing timelocks, see
+    // `anchor_lang::timelock`.
+    pub timelock: bool,
+    // True if the program has the instructions managing interface
+    // registrations, see `anchor_lang::interface_registry`.
+    pub interface_registry: bool,
+    // True if the program has the instruction publishing its schema, see
+    // `anchor_lang::schema_registry`.
+    pub schema_registry: bool,
+    // Modules declared with `declare_dependency!` that the program's
+    // `check_dependencies` instruction verifies, see
+    // `anchor_lang::dependency`.
+    pub dependencies: Vec<syn::Path>,
+    // Capacity in bytes of the arena given to each instruction, if any, see
+    // `anchor_lang::arena`.
+    pub arena: Option<syn::Expr>,
+    // True if instructions refuse data past their last argument, which is
+    // ignored otherwise.
+    pub deny_trailing_args: bool,
 }
 
 impl Parse for Program {
@@ -41,6 +71,37 @@ impl Parse for Program {
     }
 }
 
+// Arguments of the `#[program]` attribute.
+#[derive(Debug, Default)]
+pub struct ProgramArgs {
+    pub panic_handler: Option<syn::Path>,
+    pub derives: Vec<syn::Path>,
+    pub cpi_allowlist: Option<CpiAllowlist>,
+    pub timelock: bool,
+    pub interface_registry: bool,
+    pub schema_registry: bool,
+    pub dependencies: Vec<syn::Path>,
+    pub arena: Option<syn::Expr>,
+    pub deny_trailing_args: bool,
+    // True if `anchor build` writes a C header of the program's instructions,
+    // see `idl::c_header`. Doesn't change the program's code.
+    pub c_header: bool,
+}
+
+// Programs given by `#[program(cpi_allowlist(..))]`, and the runtime hook
+// given by `cpi_allowlist_hook`, see `anchor_lang::cpi_allowlist`.
+#[derive(Debug, Default)]
+pub struct CpiAllowlist {
+    pub programs: Vec<syn::Expr>,
+    pub hook: Option<syn::Path>,
+}
+
+impl Parse for ProgramArgs {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        program_parser::parse_args(input)
+    }
+}
+
 impl From<&Program> for TokenStream {
     fn from(program: &Program) -> Self {
         program_codegen::generate(program)
@@ -86,6 +147,50 @@ pub struct Ix {
     pub args: Vec<IxArg>,
     // The ident for the struct deriving Accounts.
     pub anchor_ident: Ident,
+    // True if the handler is marked `#[simulation_only]`.
+    pub simulation_only: bool,
+    // True if the handler is marked `#[instruction(zero_copy)]`, i.e. its
+    // args are cast from the instruction data instead of borsh deserialized.
+    pub zero_copy: bool,
+    // True if the handler is marked `#[skip_exit]`, i.e. its accounts, which
+    // must implement `ReadOnlyAccounts`, aren't exited after it returns.
+    pub skip_exit: bool,
+    // Accounts the handler appears to modify in place, e.g.
+    // `ctx.accounts.counter.count += 1`, used to warn if they aren't `mut`.
+    pub mutated_accounts: Vec<Ident>,
+    // True if the handler is marked `#[transfer_hook]`, i.e. it's dispatched
+    // by the SPL transfer hook interface's `Execute` discriminator.
+    pub transfer_hook: bool,
+    // Set if the handler is marked `#[deprecated_instruction]`.
+    pub deprecated: Option<IxDeprecation>,
+    // Set if the handler is marked `#[idempotent]`, in which case its last
+    // arg is the injected nonce.
+    pub idempotent: Option<IxIdempotent>,
+    // Set if the handler is marked `#[batch]`, in which case its first arg
+    // is the `items` vec, each of which is handled with its own chunk of
+    // accounts.
+    pub batch: Option<IxBatch>,
+}
+
+#[derive(Debug)]
+pub struct IxBatch {
+    // The accounts struct validating each item's chunk of accounts, without
+    // its generic args.
+    pub accounts: syn::Path,
+}
+
+#[derive(Debug, Default)]
+pub struct IxIdempotent {
+    // Seconds after which the replay account can be closed.
+    pub expiry: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct IxDeprecation {
+    pub since: Option<String>,
+    pub note: Option<String>,
+    // Slot from which the instruction is refused.
+    pub sunset_slot: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -99,14 +204,32 @@ pub struct FallbackFn {
     raw_method: ItemFn,
 }
 
+#[derive(Debug)]
+pub struct PrefixFn {
+    pub raw_method: ItemFn,
+    // Leading instruction data bytes routed to the handler.
+    pub prefix: Vec<u8>,
+}
+
 #[derive(Debug)]
 pub struct AccountsStruct {
     // Name of the accounts struct.
     pub ident: Ident,
+    // Visibility of the accounts struct.
+    pub vis: Visibility,
     // Generics + lifetimes on the accounts struct.
     pub generics: Generics,
     // Fields on the accounts struct.
     pub fields: Vec<AccountField>,
+    // Constraints given with `#[accounts(..)]` on the struct, checked after
+    // all the fields' constraints.
+    pub struct_constraints: Vec<ConstraintRaw>,
+    // True if the struct is marked `#[accounts(snapshot)]`, generating its
+    // `<Name>Snapshot` struct.
+    pub snapshot: bool,
+    // Checks on the accounts left after the struct's fields, given with
+    // `#[remaining_accounts(..)]`.
+    pub remaining_accounts: Option<ConstraintRemainingAccounts>,
     // Instruction data api expression.
     instruction_api: Option<Punctuated<Expr, Comma>>,
 }
@@ -134,17 +257,38 @@ impl AccountsStruct {
     pub fn new(
         strct: ItemStruct,
         fields: Vec<AccountField>,
+        struct_constraints: Vec<ConstraintRaw>,
         instruction_api: Option<Punctuated<Expr, Comma>>,
     ) -> Self {
         let ident = strct.ident.clone();
+        let vis = strct.vis;
         let generics = strct.generics;
         Self {
             ident,
+            vis,
             generics,
             fields,
+            struct_constraints,
+            snapshot: false,
+            remaining_accounts: None,
             instruction_api,
         }
     }
+
+    // Returns the type of the instruction argument `name`, as declared with
+    // `#[instruction(...)]`.
+    pub fn instruction_arg_ty(&self, name: &str) -> Option<&syn::Type> {
+        self.instruction_api
+            .as_ref()?
+            .iter()
+            .find_map(|arg| match arg {
+                Expr::Type(ty) => match &*ty.expr {
+                    Expr::Path(p) if p.path.is_ident(name) => Some(&*ty.ty),
+                    _ => None,
+                },
+                _ => None,
+            })
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -154,6 +298,22 @@ pub enum AccountField {
     CompositeField(CompositeField),
 }
 
+impl AccountField {
+    pub fn ident(&self) -> &Ident {
+        match self {
+            AccountField::Field(f) => &f.ident,
+            AccountField::CompositeField(s) => &s.ident,
+        }
+    }
+
+    pub fn constraints(&self) -> &ConstraintGroup {
+        match self {
+            AccountField::Field(f) => &f.constraints,
+            AccountField::CompositeField(s) => &s.constraints,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Field {
     pub ident: Ident,
@@ -163,6 +323,19 @@ pub struct Field {
 }
 
 impl Field {
+    // Sibling accounts joined by the field's `has_one` constraints, i.e. the
+    // accounts whose addresses are stored in this account.
+    pub fn has_one_targets(&self) -> Vec<&Ident> {
+        self.constraints
+            .has_one
+            .iter()
+            .filter_map(|c| match &c.join_target {
+                Expr::Path(p) => p.path.get_ident(),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn typed_ident(&self) -> proc_macro2::TokenStream {
         let name = &self.ident;
         let ty_decl = self.ty_decl();
@@ -176,19 +349,25 @@ impl Field {
         let container_ty = self.container_ty();
         match &self.ty {
             Ty::AccountInfo => quote! {
-                AccountInfo
+                anchor_lang::solana_program::account_info::AccountInfo
             },
             Ty::UncheckedAccount => quote! {
-                UncheckedAccount
+                anchor_lang::UncheckedAccount
             },
             Ty::Signer => quote! {
-                Signer
+                anchor_lang::Signer
             },
             Ty::ProgramData => quote! {
-                ProgramData
+                anchor_lang::ProgramData
             },
             Ty::SystemAccount => quote! {
-                SystemAccount
+                anchor_lang::SystemAccount
+            },
+            Ty::NativeAccount => quote! {
+                anchor_lang::NativeAccount
+            },
+            Ty::SysvarAccount => quote! {
+                anchor_lang::SysvarAccount
             },
             Ty::Account(AccountTy { boxed, .. }) => {
                 if *boxed {
@@ -201,23 +380,9 @@ impl Field {
                     }
                 }
             }
-            Ty::Sysvar(ty) => {
-                let account = match ty {
-                    SysvarTy::Clock => quote! {Clock},
-                    SysvarTy::Rent => quote! {Rent},
-                    SysvarTy::EpochSchedule => quote! {EpochSchedule},
-                    SysvarTy::Fees => quote! {Fees},
-                    SysvarTy::RecentBlockhashes => quote! {RecentBlockhashes},
-                    SysvarTy::SlotHashes => quote! {SlotHashes},
-                    SysvarTy::SlotHistory => quote! {SlotHistory},
-                    SysvarTy::StakeHistory => quote! {StakeHistory},
-                    SysvarTy::Instructions => quote! {Instructions},
-                    SysvarTy::Rewards => quote! {Rewards},
-                };
-                quote! {
-                    Sysvar<#account>
-                }
-            }
+            Ty::Sysvar(_) => quote! {
+                #container_ty<#account_ty>
+            },
             _ => quote! {
                 #container_ty<#account_ty>
             },
@@ -232,18 +397,20 @@ impl Field {
         match &self.ty {
             Ty::AccountInfo => quote! { #field.to_account_info() },
             Ty::UncheckedAccount => {
-                quote! { UncheckedAccount::try_from(#field.to_account_info()) }
+                quote! { anchor_lang::UncheckedAccount::try_from(#field.to_account_info()) }
             }
             Ty::Account(AccountTy { boxed, .. }) => {
                 if *boxed {
                     quote! {
-                        Box::new(#container_ty::try_from_unchecked(
+                        Box::new(#container_ty::try_from_unchecked_for(
+                            program_id,
                             &#field,
                         )?)
                     }
                 } else {
                     quote! {
-                        #container_ty::try_from_unchecked(
+                        #container_ty::try_from_unchecked_for(
+                            program_id,
                             &#field,
                         )?
                     }
@@ -301,6 +468,8 @@ impl Field {
             Ty::UncheckedAccount => quote! {},
             Ty::Signer => quote! {},
             Ty::SystemAccount => quote! {},
+            Ty::NativeAccount => quote! {},
+            Ty::SysvarAccount => quote! {},
             Ty::ProgramData => quote! {},
         }
     }
@@ -309,19 +478,25 @@ impl Field {
     pub fn account_ty(&self) -> proc_macro2::TokenStream {
         match &self.ty {
             Ty::AccountInfo => quote! {
-                AccountInfo
+                anchor_lang::solana_program::account_info::AccountInfo
             },
             Ty::UncheckedAccount => quote! {
-                UncheckedAccount
+                anchor_lang::UncheckedAccount
             },
             Ty::Signer => quote! {
-                Signer
+                anchor_lang::Signer
             },
             Ty::SystemAccount => quote! {
-                SystemAccount
+                anchor_lang::SystemAccount
+            },
+            Ty::NativeAccount => quote! {
+                anchor_lang::NativeAccount
+            },
+            Ty::SysvarAccount => quote! {
+                anchor_lang::SysvarAccount
             },
             Ty::ProgramData => quote! {
-                ProgramData
+                anchor_lang::ProgramData
             },
             Ty::ProgramAccount(ty) => {
                 let ident = &ty.account_type_path;
@@ -366,16 +541,28 @@ impl Field {
                 }
             }
             Ty::Sysvar(ty) => match ty {
-                SysvarTy::Clock => quote! {Clock},
-                SysvarTy::Rent => quote! {Rent},
-                SysvarTy::EpochSchedule => quote! {EpochSchedule},
-                SysvarTy::Fees => quote! {Fees},
-                SysvarTy::RecentBlockhashes => quote! {RecentBlockhashes},
-                SysvarTy::SlotHashes => quote! {SlotHashes},
-                SysvarTy::SlotHistory => quote! {SlotHistory},
-                SysvarTy::StakeHistory => quote! {StakeHistory},
-                SysvarTy::Instructions => quote! {Instructions},
-                SysvarTy::Rewards => quote! {Rewards},
+                SysvarTy::Clock => quote! {anchor_lang::solana_program::sysvar::clock::Clock},
+                SysvarTy::Rent => quote! {anchor_lang::solana_program::sysvar::rent::Rent},
+                SysvarTy::EpochSchedule => {
+                    quote! {anchor_lang::solana_program::sysvar::epoch_schedule::EpochSchedule}
+                }
+                SysvarTy::Fees => quote! {anchor_lang::solana_program::sysvar::fees::Fees},
+                SysvarTy::RecentBlockhashes => quote! {
+                    anchor_lang::solana_program::sysvar::recent_blockhashes::RecentBlockhashes
+                },
+                SysvarTy::SlotHashes => {
+                    quote! {anchor_lang::solana_program::sysvar::slot_hashes::SlotHashes}
+                }
+                SysvarTy::SlotHistory => {
+                    quote! {anchor_lang::solana_program::sysvar::slot_history::SlotHistory}
+                }
+                SysvarTy::StakeHistory => {
+                    quote! {anchor_lang::solana_program::sysvar::stake_history::StakeHistory}
+                }
+                SysvarTy::Instructions => {
+                    quote! {anchor_lang::solana_program::sysvar::instructions::Instructions}
+                }
+                SysvarTy::Rewards => quote! {anchor_lang::solana_program::sysvar::rewards::Rewards},
             },
             Ty::Program(ty) => {
                 let program = &ty.account_type_path;
@@ -412,6 +599,8 @@ pub enum Ty {
     Program(ProgramTy),
     Signer,
     SystemAccount,
+    NativeAccount,
+    SysvarAccount,
     ProgramData,
 }
 
@@ -486,22 +675,80 @@ pub struct Error {
     pub args: Option<ErrorArgs>,
 }
 
-#[derive(Debug)]
+// Arguments of the `#[error]` attribute, a comma separated list of
+// `offset = <int>`, `derive(<path>, ..)` and `extends(<path>, ..)`.
+#[derive(Debug, Default)]
 pub struct ErrorArgs {
-    pub offset: LitInt,
+    pub offset: Option<LitInt>,
+    // Additional traits derived for the error enum and the generated
+    // `Error` type.
+    pub derives: Vec<syn::Path>,
+    // Shared error enums, defined with `#[error]` in other crates, whose
+    // codes the program returns along with its own.
+    pub extends: Vec<syn::Path>,
+    // Only generate the error codes, without the `Error` and `Result` types,
+    // as for anchor's own `ErrorCode`.
+    pub codes_only: bool,
 }
 
 impl Parse for ErrorArgs {
     fn parse(stream: ParseStream) -> ParseResult<Self> {
-        let offset_span = stream.span();
-        let offset = stream.call(Ident::parse_any)?;
-        if offset.to_string().as_str() != "offset" {
-            return Err(ParseError::new(offset_span, "expected keyword offset"));
+        let mut args = ErrorArgs::default();
+        while !stream.is_empty() {
+            let ident = stream.call(Ident::parse_any)?;
+            match ident.to_string().as_str() {
+                "offset" => {
+                    stream.parse::<Token![=]>()?;
+                    args.offset = Some(stream.parse()?);
+                }
+                "derive" => args.derives.extend(parser::parse_derives(stream)?),
+                "extends" => args.extends.extend(parser::parse_derives(stream)?),
+                "codes_only" => args.codes_only = true,
+                _ => {
+                    return Err(ParseError::new(
+                        ident.span(),
+                        "expected keyword offset, derive or extends",
+                    ))
+                }
+            }
+            if !stream.is_empty() {
+                stream.parse::<Token![,]>()?;
+            }
         }
-        stream.parse::<Token![=]>()?;
-        Ok(ErrorArgs {
-            offset: stream.parse()?,
-        })
+        Ok(args)
+    }
+}
+
+// Arguments of the `#[event]` attribute, an optional `anonymous` and
+// `derive(<path>, ..)`.
+#[derive(Debug, Default)]
+pub struct EventArgs {
+    // Whether the event is logged without its discriminator.
+    pub anonymous: bool,
+    // Additional traits derived for the event struct.
+    pub derives: Vec<syn::Path>,
+}
+
+impl Parse for EventArgs {
+    fn parse(stream: ParseStream) -> ParseResult<Self> {
+        let mut args = EventArgs::default();
+        while !stream.is_empty() {
+            let ident = stream.call(Ident::parse_any)?;
+            match ident.to_string().as_str() {
+                "anonymous" => args.anonymous = true,
+                "derive" => args.derives = parser::parse_derives(stream)?,
+                _ => {
+                    return Err(ParseError::new(
+                        ident.span(),
+                        "expected keyword anonymous or derive",
+                    ))
+                }
+            }
+            if !stream.is_empty() {
+                stream.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
     }
 }
 
@@ -510,6 +757,84 @@ pub struct ErrorCode {
     pub id: u32,
     pub ident: Ident,
     pub msg: Option<String>,
+    // Named fields carried by the variant, empty for unit variants.
+    pub fields: Vec<syn::Field>,
+}
+
+// Arguments to `declare_dependency!`.
+#[derive(Debug)]
+pub struct Dependency {
+    pub name: Ident,
+    pub program_id: LitStr,
+    // Hex encoded SHA256 of the dependency's IDL.
+    pub idl_hash: Option<LitStr>,
+}
+
+impl Parse for Dependency {
+    fn parse(stream: ParseStream) -> ParseResult<Self> {
+        let name = stream.parse()?;
+        stream.parse::<Token![,]>()?;
+        let program_id = stream.parse()?;
+        let mut idl_hash: Option<LitStr> = None;
+        if stream.peek(Token![,]) {
+            stream.parse::<Token![,]>()?;
+            if !stream.is_empty() {
+                let kw = stream.call(Ident::parse_any)?;
+                if kw != "idl_hash" {
+                    return Err(ParseError::new(kw.span(), "expected keyword idl_hash"));
+                }
+                stream.parse::<Token![=]>()?;
+                let hash: LitStr = stream.parse()?;
+                let value = hash.value();
+                if value.len() != 64 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(ParseError::new(
+                        hash.span(),
+                        "idl_hash must be a hex encoded SHA256 hash",
+                    ));
+                }
+                idl_hash = Some(hash);
+                if stream.peek(Token![,]) {
+                    stream.parse::<Token![,]>()?;
+                }
+            }
+        }
+        Ok(Dependency {
+            name,
+            program_id,
+            idl_hash,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Flags {
+    pub raw_enum: ItemEnum,
+    pub ident: Ident,
+    // The integer type backing the flags, `u32` or `u64`.
+    pub repr: Ident,
+    pub flags: Vec<Flag>,
+}
+
+#[derive(Debug)]
+pub struct Flag {
+    pub ident: Ident,
+    // Bitmask of the flag.
+    pub value: u64,
+}
+
+#[derive(Debug)]
+pub struct FlagsArgs {
+    pub repr: Ident,
+}
+
+impl Parse for FlagsArgs {
+    fn parse(stream: ParseStream) -> ParseResult<Self> {
+        let repr: Ident = stream.parse()?;
+        if repr != "u32" && repr != "u64" {
+            return Err(ParseError::new(repr.span(), "expected u32 or u64"));
+        }
+        Ok(FlagsArgs { repr })
+    }
 }
 
 // All well formed constraints on a single `Accounts` field.
@@ -530,6 +855,13 @@ pub struct ConstraintGroup {
     close: Option<ConstraintClose>,
     address: Option<ConstraintAddress>,
     associated_token: Option<ConstraintAssociatedToken>,
+    price_feed_max_age: Option<ConstraintPriceFeedMaxAge>,
+    program_stable: Option<ConstraintProgramStable>,
+    timelock: Option<ConstraintTimelock>,
+    active_window: Option<ConstraintActiveWindow>,
+    assign_to: Option<ConstraintAssignTo>,
+    validate_after: Vec<ConstraintValidateAfter>,
+    when: Option<ConstraintWhen>,
 }
 
 impl ConstraintGroup {
@@ -548,6 +880,24 @@ impl ConstraintGroup {
     pub fn is_close(&self) -> bool {
         self.close.is_some()
     }
+
+    pub fn is_timelock(&self) -> bool {
+        self.timelock.is_some()
+    }
+
+    pub fn assign_to(&self) -> Option<&ConstraintAssignTo> {
+        self.assign_to.as_ref()
+    }
+
+    // The fields whose constraints must be checked before this one's.
+    pub fn validate_after(&self) -> Vec<&Ident> {
+        self.validate_after.iter().map(|c| &c.target).collect()
+    }
+
+    // The condition the field's constraints are checked under, if any.
+    pub fn when(&self) -> Option<&ConstraintWhen> {
+        self.when.as_ref()
+    }
 }
 
 // A single account constraint *after* merging all tokens into a well formed
@@ -571,6 +921,10 @@ pub enum Constraint {
     State(ConstraintState),
     Close(ConstraintClose),
     Address(ConstraintAddress),
+    PriceFeedMaxAge(ConstraintPriceFeedMaxAge),
+    ProgramStable(ConstraintProgramStable),
+    Timelock(ConstraintTimelock),
+    ActiveWindow(ConstraintActiveWindow),
 }
 
 // Constraint token is a single keyword in a `#[account(<TOKEN>)]` attribute.
@@ -601,6 +955,14 @@ pub enum ConstraintToken {
     MintFreezeAuthority(Context<ConstraintMintFreezeAuthority>),
     MintDecimals(Context<ConstraintMintDecimals>),
     Bump(Context<ConstraintTokenBump>),
+    PriceFeedMaxAge(Context<ConstraintPriceFeedMaxAge>),
+    ProgramStable(Context<ConstraintProgramStable>),
+    Timelock(Context<ConstraintTimelockAddress>),
+    MinDelay(Context<ConstraintMinDelay>),
+    ActiveWindow(Context<ConstraintActiveWindow>),
+    AssignTo(Context<ConstraintAssignTo>),
+    ValidateAfter(Context<ConstraintValidateAfter>),
+    When(Context<ConstraintWhen>),
 }
 
 impl Parse for ConstraintToken {
@@ -633,6 +995,8 @@ pub struct ConstraintSigner {
 #[derive(Debug, Clone)]
 pub struct ConstraintHasOne {
     pub join_target: Expr,
+    // Whether an `Option<Pubkey>` field set to `None` passes the check.
+    pub allow_none: bool,
     pub error: Option<Expr>,
 }
 
@@ -647,6 +1011,14 @@ pub struct ConstraintRaw {
     pub error: Option<Expr>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct ConstraintRemainingAccounts {
+    pub min: Option<Expr>,
+    pub max: Option<Expr>,
+    pub owner: Option<Expr>,
+    pub writable: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintOwner {
     pub owner_address: Expr,
@@ -657,12 +1029,18 @@ pub struct ConstraintOwner {
 pub struct ConstraintAddress {
     pub address: Expr,
     pub error: Option<Expr>,
+    // True if the address is a field of a zero-copy account of the same
+    // struct, e.g. `config.oracle`, which must be loaded to be read.
+    pub zero_copy_base: bool,
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum ConstraintRentExempt {
     Enforce,
     Skip,
+    // Transfers the account's shortfall from `payer` instead of failing.
+    TopUp { payer: Expr },
 }
 
 #[derive(Debug, Clone)]
@@ -674,6 +1052,61 @@ pub struct ConstraintInitGroup {
     pub kind: InitKind,
 }
 
+impl ConstraintInitGroup {
+    // `payer = self`, i.e., the account is pre-funded and pays for its own
+    // rent.
+    pub fn is_self_funded(&self) -> bool {
+        self.payer
+            .as_ref()
+            .map(ConstraintPayer::is_self_target)
+            .unwrap_or(false)
+    }
+
+    // `payer = [<payers>]`, i.e., the rent is split between several payers.
+    pub fn split_payers(&self) -> Option<Vec<Expr>> {
+        self.payer.as_ref().and_then(ConstraintPayer::split_targets)
+    }
+
+    // Identifiers in the expressions the constraint is generated from.
+    pub fn references(&self) -> Vec<Ident> {
+        let mut tokens = TokenStream::new();
+        self.payer.to_tokens(&mut tokens);
+        self.space.to_tokens(&mut tokens);
+        if let Some(seeds) = &self.seeds {
+            seeds.seeds.to_tokens(&mut tokens);
+            seeds.bump.to_tokens(&mut tokens);
+        }
+        match &self.kind {
+            InitKind::Program { owner } => owner.to_tokens(&mut tokens),
+            InitKind::Token { owner, mint } | InitKind::AssociatedToken { owner, mint } => {
+                owner.to_tokens(&mut tokens);
+                mint.to_tokens(&mut tokens);
+            }
+            InitKind::Mint {
+                owner,
+                freeze_authority,
+                decimals,
+            } => {
+                owner.to_tokens(&mut tokens);
+                freeze_authority.to_tokens(&mut tokens);
+                decimals.to_tokens(&mut tokens);
+            }
+        }
+        fn collect_idents(tokens: TokenStream, idents: &mut Vec<Ident>) {
+            for token in tokens {
+                match token {
+                    proc_macro2::TokenTree::Ident(ident) => idents.push(ident),
+                    proc_macro2::TokenTree::Group(group) => collect_idents(group.stream(), idents),
+                    _ => {}
+                }
+            }
+        }
+        let mut idents = Vec::new();
+        collect_idents(tokens, &mut idents);
+        idents
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintSeedsGroup {
     pub is_init: bool,
@@ -681,6 +1114,64 @@ pub struct ConstraintSeedsGroup {
     pub bump: Option<Expr>, // None => bump was given without a target.
 }
 
+impl ConstraintSeedsGroup {
+    // Returns the seeds as byte slice expressions, with integer seeds
+    // converted, and without a trailing comma.
+    pub fn seed_bytes(&self) -> Punctuated<Expr, Token![,]> {
+        self.seeds.iter().map(Self::bytes).collect()
+    }
+
+    // Converts a seed to a byte slice expression.
+    fn bytes(seed: &Expr) -> Expr {
+        if let Some(seeds) = Self::hash_seed(seed) {
+            let seeds = seeds.iter().map(Self::bytes);
+            return syn::parse_quote! {
+                &anchor_lang::seeds::hash(&[#(#seeds),*])[..]
+            };
+        }
+        match Self::int_seed(seed) {
+            Some((value, SeedEndian::Little)) => {
+                syn::parse_quote! { &(#value).to_le_bytes()[..] }
+            }
+            Some((value, SeedEndian::Big)) => {
+                syn::parse_quote! { &(#value).to_be_bytes()[..] }
+            }
+            None => seed.clone(),
+        }
+    }
+
+    // Returns the hashed seeds if `seed` is `hash(<seed>, ..)`.
+    pub fn hash_seed(seed: &Expr) -> Option<&Punctuated<Expr, Token![,]>> {
+        match seed {
+            Expr::Call(call) if matches!(&*call.func, Expr::Path(p) if p.path.is_ident("hash")) => {
+                Some(&call.args)
+            }
+            _ => None,
+        }
+    }
+
+    // Returns the integer and its endianness if `seed` is an integer seed,
+    // i.e. `le(<expr>)` or `be(<expr>)`.
+    pub fn int_seed(seed: &Expr) -> Option<(&Expr, SeedEndian)> {
+        let call = match seed {
+            Expr::Call(call) if call.args.len() == 1 => call,
+            _ => return None,
+        };
+        let endian = match &*call.func {
+            Expr::Path(p) if p.path.is_ident("le") => SeedEndian::Little,
+            Expr::Path(p) if p.path.is_ident("be") => SeedEndian::Big,
+            _ => return None,
+        };
+        Some((&call.args[0], endian))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeedEndian {
+    Little,
+    Big,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintSeeds {
     pub seeds: Punctuated<Expr, Token![,]>,
@@ -699,6 +1190,19 @@ pub struct ConstraintPayer {
     pub target: Expr,
 }
 
+impl ConstraintPayer {
+    pub fn is_self_target(target: &Expr) -> bool {
+        matches!(target, Expr::Path(p) if p.path.is_ident("self"))
+    }
+
+    pub fn split_targets(target: &Expr) -> Option<Vec<Expr>> {
+        match target {
+            Expr::Array(payers) => Some(payers.elems.iter().cloned().collect()),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintSpace {
     pub space: Expr,
@@ -727,6 +1231,66 @@ pub enum InitKind {
     },
 }
 
+#[derive(Debug, Clone)]
+pub struct ConstraintPriceFeedMaxAge {
+    pub max_age: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintProgramStable {
+    pub programdata: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintTimelock {
+    // The expected timelock account's address.
+    pub address: Expr,
+    // Seconds the action must have been queued for.
+    pub min_delay: Expr,
+}
+
+// The unix timestamps, e.g. read from a config account, between which the
+// instruction can be invoked, the end being excluded.
+#[derive(Debug, Clone)]
+pub struct ConstraintActiveWindow {
+    pub start: Expr,
+    pub end: Expr,
+    // True if the bound is a field of a zero-copy account of the same
+    // struct, see `ConstraintAddress::zero_copy_base`.
+    pub zero_copy_start: bool,
+    pub zero_copy_end: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintValidateAfter {
+    pub target: Ident,
+}
+
+// Gates the field's other constraints, except `mut`, which the exit code
+// relies on.
+#[derive(Debug, Clone)]
+pub struct ConstraintWhen {
+    pub cond: Expr,
+}
+
+// Checked on exit rather than with the other constraints, as the owner is
+// expected to change during the instruction.
+#[derive(Debug, Clone)]
+pub struct ConstraintAssignTo {
+    // The account's expected owner after the instruction.
+    pub owner: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintTimelockAddress {
+    pub address: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintMinDelay {
+    pub min_delay: Expr,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintClose {
     pub sol_dest: Ident,
@@ -52,8 +52,19 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
     let is_lit = stream.peek(LitStr);
     if is_lit {
         let lit: LitStr = stream.parse()?;
-        let c = ConstraintToken::Literal(Context::new(lit.span(), ConstraintLiteral { lit }));
-        return Ok(c);
+        // Literal constraints are only accepted during the transition to
+        // `constraint = <expr>`.
+        if cfg!(feature = "deprecated-literal-constraint") {
+            let c = ConstraintToken::Literal(Context::new(lit.span(), ConstraintLiteral { lit }));
+            return Ok(c);
+        }
+        return Err(ParseError::new(
+            lit.span(),
+            format!(
+                "string literal constraints are no longer supported, use #[account(constraint = {})]",
+                lit.value()
+            ),
+        ));
     }
 
     let ident = stream.call(Ident::parse_any)?;
@@ -171,6 +182,70 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                 _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
             }
         }
+        "price_feed" => {
+            stream.parse::<Token![:]>()?;
+            stream.parse::<Token![:]>()?;
+            let kw = stream.call(Ident::parse_any)?.to_string();
+            stream.parse::<Token![=]>()?;
+
+            let span = ident
+                .span()
+                .join(stream.span())
+                .unwrap_or_else(|| ident.span());
+
+            match kw.as_str() {
+                "max_age" => ConstraintToken::PriceFeedMaxAge(Context::new(
+                    span,
+                    ConstraintPriceFeedMaxAge {
+                        max_age: stream.parse()?,
+                    },
+                )),
+                _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
+            }
+        }
+        "program" => {
+            stream.parse::<Token![:]>()?;
+            stream.parse::<Token![:]>()?;
+            let kw = stream.call(Ident::parse_any)?.to_string();
+            stream.parse::<Token![=]>()?;
+
+            let span = ident
+                .span()
+                .join(stream.span())
+                .unwrap_or_else(|| ident.span());
+
+            match kw.as_str() {
+                "stable" => ConstraintToken::ProgramStable(Context::new(
+                    span,
+                    ConstraintProgramStable {
+                        programdata: stream.parse()?,
+                    },
+                )),
+                _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
+            }
+        }
+        "has_one" if stream.peek(Token![::]) => {
+            stream.parse::<Token![::]>()?;
+            let kw = stream.call(Ident::parse_any)?;
+            if kw != "allow_none" {
+                return Err(ParseError::new(kw.span(), "Invalid attribute"));
+            }
+            stream.parse::<Token![=]>()?;
+
+            let span = ident
+                .span()
+                .join(stream.span())
+                .unwrap_or_else(|| ident.span());
+
+            ConstraintToken::HasOne(Context::new(
+                span,
+                ConstraintHasOne {
+                    join_target: stream.parse()?,
+                    allow_none: true,
+                    error: parse_optional_custom_error(&stream)?,
+                },
+            ))
+        }
         "bump" => {
             let bump = {
                 if stream.peek(Token![=]) {
@@ -193,6 +268,7 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                     span,
                     ConstraintHasOne {
                         join_target: stream.parse()?,
+                        allow_none: false,
                         error: parse_optional_custom_error(&stream)?,
                     },
                 )),
@@ -208,10 +284,17 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                     match stream.parse::<Ident>()?.to_string().as_str() {
                         "skip" => ConstraintRentExempt::Skip,
                         "enforce" => ConstraintRentExempt::Enforce,
+                        "top_up" => {
+                            let content;
+                            syn::parenthesized!(content in stream);
+                            ConstraintRentExempt::TopUp {
+                                payer: content.parse()?,
+                            }
+                        }
                         _ => {
                             return Err(ParseError::new(
                                 span,
-                                "rent_exempt must be either skip or enforce",
+                                "rent_exempt must be either skip, enforce or top_up(<payer>)",
                             ))
                         }
                     },
@@ -257,11 +340,67 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                         sol_dest: stream.parse()?,
                     },
                 )),
+                "timelock" => ConstraintToken::Timelock(Context::new(
+                    span,
+                    ConstraintTimelockAddress {
+                        address: stream.parse()?,
+                    },
+                )),
+                "min_delay" => ConstraintToken::MinDelay(Context::new(
+                    span,
+                    ConstraintMinDelay {
+                        min_delay: stream.parse()?,
+                    },
+                )),
+                "active_window" => {
+                    let (start, end) = match stream.parse()? {
+                        Expr::Range(syn::ExprRange {
+                            from: Some(start),
+                            limits: syn::RangeLimits::HalfOpen(_),
+                            to: Some(end),
+                            ..
+                        }) => (*start, *end),
+                        range => {
+                            return Err(ParseError::new(
+                                range.span(),
+                                "active_window must be a range `start..end`",
+                            ))
+                        }
+                    };
+                    ConstraintToken::ActiveWindow(Context::new(
+                        span,
+                        ConstraintActiveWindow {
+                            start,
+                            end,
+                            zero_copy_start: false,
+                            zero_copy_end: false,
+                        },
+                    ))
+                }
+                "validate_after" => ConstraintToken::ValidateAfter(Context::new(
+                    span,
+                    ConstraintValidateAfter {
+                        target: stream.parse()?,
+                    },
+                )),
+                "when" => ConstraintToken::When(Context::new(
+                    span,
+                    ConstraintWhen {
+                        cond: stream.parse()?,
+                    },
+                )),
+                "assign_to" => ConstraintToken::AssignTo(Context::new(
+                    span,
+                    ConstraintAssignTo {
+                        owner: stream.parse()?,
+                    },
+                )),
                 "address" => ConstraintToken::Address(Context::new(
                     span,
                     ConstraintAddress {
                         address: stream.parse()?,
                         error: parse_optional_custom_error(&stream)?,
+                        zero_copy_base: false,
                     },
                 )),
                 _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
@@ -272,6 +411,92 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
     Ok(c)
 }
 
+// Parses the `#[accounts(..)]` attributes of the struct, returning its
+// constraints and whether `snapshot` was given.
+pub fn parse_struct_attrs(strct: &syn::ItemStruct) -> ParseResult<(Vec<ConstraintRaw>, bool)> {
+    let mut constraints = Vec::new();
+    let mut snapshot = false;
+    for attr in strct.attrs.iter().filter(|a| a.path.is_ident("accounts")) {
+        for arg in attr.parse_args_with(Punctuated::<StructAttr, Comma>::parse_terminated)? {
+            match arg {
+                StructAttr::Constraint(c) => constraints.push(c),
+                StructAttr::Snapshot => snapshot = true,
+            }
+        }
+    }
+    Ok((constraints, snapshot))
+}
+
+pub fn parse_remaining_accounts(
+    strct: &syn::ItemStruct,
+) -> ParseResult<Option<ConstraintRemainingAccounts>> {
+    let attr = match strct
+        .attrs
+        .iter()
+        .find(|a| a.path.is_ident("remaining_accounts"))
+    {
+        None => return Ok(None),
+        Some(attr) => attr,
+    };
+    let mut c = ConstraintRemainingAccounts::default();
+    attr.parse_args_with(|stream: ParseStream| {
+        while !stream.is_empty() {
+            let ident = stream.call(Ident::parse_any)?;
+            if ident == "writable" {
+                c.writable = true;
+            } else {
+                let value = match ident.to_string().as_str() {
+                    "min" => &mut c.min,
+                    "max" => &mut c.max,
+                    "owner" => &mut c.owner,
+                    _ => {
+                        return Err(ParseError::new(
+                            ident.span(),
+                            "only `min`, `max`, `owner` and `writable` are allowed on the remaining accounts",
+                        ))
+                    }
+                };
+                if value.is_some() {
+                    return Err(ParseError::new(ident.span(), "duplicate check"));
+                }
+                stream.parse::<Token![=]>()?;
+                *value = Some(stream.parse()?);
+            }
+            if !stream.is_empty() {
+                stream.parse::<Comma>()?;
+            }
+        }
+        Ok(())
+    })?;
+    Ok(Some(c))
+}
+
+#[allow(clippy::large_enum_variant)]
+enum StructAttr {
+    Constraint(ConstraintRaw),
+    Snapshot,
+}
+
+impl Parse for StructAttr {
+    fn parse(stream: ParseStream) -> ParseResult<Self> {
+        let ident = stream.call(Ident::parse_any)?;
+        if ident == "snapshot" {
+            return Ok(StructAttr::Snapshot);
+        }
+        if ident != "constraint" {
+            return Err(ParseError::new(
+                ident.span(),
+                "only `constraint = <expr>` and `snapshot` are allowed on the accounts struct",
+            ));
+        }
+        stream.parse::<Token![=]>()?;
+        Ok(StructAttr::Constraint(ConstraintRaw {
+            raw: stream.parse()?,
+            error: parse_optional_custom_error(&stream)?,
+        }))
+    }
+}
+
 fn parse_optional_custom_error(stream: &ParseStream) -> ParseResult<Option<Expr>> {
     if stream.peek(Token![@]) {
         stream.parse::<Token![@]>()?;
@@ -308,6 +533,14 @@ pub struct ConstraintGroupBuilder<'ty> {
     pub mint_freeze_authority: Option<Context<ConstraintMintFreezeAuthority>>,
     pub mint_decimals: Option<Context<ConstraintMintDecimals>>,
     pub bump: Option<Context<ConstraintTokenBump>>,
+    pub price_feed_max_age: Option<Context<ConstraintPriceFeedMaxAge>>,
+    pub program_stable: Option<Context<ConstraintProgramStable>>,
+    pub timelock: Option<Context<ConstraintTimelockAddress>>,
+    pub min_delay: Option<Context<ConstraintMinDelay>>,
+    pub active_window: Option<Context<ConstraintActiveWindow>>,
+    pub assign_to: Option<Context<ConstraintAssignTo>>,
+    pub validate_after: Vec<Context<ConstraintValidateAfter>>,
+    pub when: Option<Context<ConstraintWhen>>,
 }
 
 impl<'ty> ConstraintGroupBuilder<'ty> {
@@ -338,6 +571,14 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             mint_freeze_authority: None,
             mint_decimals: None,
             bump: None,
+            price_feed_max_age: None,
+            program_stable: None,
+            timelock: None,
+            min_delay: None,
+            active_window: None,
+            assign_to: None,
+            validate_after: Vec::new(),
+            when: None,
         }
     }
 
@@ -366,6 +607,33 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
                     "payer must be provided when initializing an account",
                 ));
             }
+            if let Some(p) = &self.payer {
+                if ConstraintPayer::is_self_target(&p.target)
+                    && self.associated_token_mint.is_some()
+                {
+                    return Err(ParseError::new(
+                        p.span(),
+                        "payer = self is not supported for associated token accounts",
+                    ));
+                }
+                if let Some(payers) = ConstraintPayer::split_targets(&p.target) {
+                    if payers.is_empty() {
+                        return Err(ParseError::new(p.span(), "payer list cannot be empty"));
+                    }
+                    if payers.iter().any(ConstraintPayer::is_self_target) {
+                        return Err(ParseError::new(
+                            p.span(),
+                            "payer = self cannot be split with other payers",
+                        ));
+                    }
+                    if self.associated_token_mint.is_some() {
+                        return Err(ParseError::new(
+                            p.span(),
+                            "multiple payers are not supported for associated token accounts",
+                        ));
+                    }
+                }
+            }
             // When initializing a non-PDA account, the account being
             // initialized must sign to invoke the system program's create
             // account instruction.
@@ -494,6 +762,14 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             mint_freeze_authority,
             mint_decimals,
             bump,
+            price_feed_max_age,
+            program_stable,
+            timelock,
+            min_delay,
+            active_window,
+            assign_to,
+            validate_after,
+            when,
         } = self;
 
         // Converts Option<Context<T>> -> Option<T>.
@@ -512,6 +788,67 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             };
         }
 
+        let timelock = match (timelock, min_delay) {
+            (None, None) => None,
+            (Some(t), None) => {
+                return Err(ParseError::new(
+                    t.span(),
+                    "min_delay must be provided with timelock",
+                ))
+            }
+            (None, Some(d)) => {
+                return Err(ParseError::new(
+                    d.span(),
+                    "timelock must be provided with min_delay",
+                ))
+            }
+            (Some(t), Some(d)) => {
+                if mutable.is_none() {
+                    return Err(ParseError::new(
+                        t.span(),
+                        "mut must be provided with timelock",
+                    ));
+                }
+                Some(ConstraintTimelock {
+                    address: t.into_inner().address,
+                    min_delay: d.into_inner().min_delay,
+                })
+            }
+        };
+
+        if let Some(r) = &rent_exempt {
+            if matches!(**r, ConstraintRentExempt::TopUp { .. }) && mutable.is_none() {
+                return Err(ParseError::new(
+                    r.span(),
+                    "mut must be provided with rent_exempt = top_up",
+                ));
+            }
+        }
+
+        if let Some(a) = &assign_to {
+            if mutable.is_none() {
+                return Err(ParseError::new(
+                    a.span(),
+                    "mut must be provided with assign_to",
+                ));
+            }
+        }
+
+        if let Some(w) = &when {
+            let unconditional = [
+                (init.is_some(), "init"),
+                (zeroed.is_some(), "zero"),
+                (close.is_some(), "close"),
+                (assign_to.is_some(), "assign_to"),
+            ];
+            if let Some((_, name)) = unconditional.iter().find(|(set, _)| *set) {
+                return Err(ParseError::new(
+                    w.span(),
+                    format!("when cannot be provided with {}", name),
+                ));
+            }
+        }
+
         let is_init = init.is_some();
         let seeds = seeds.map(|c| ConstraintSeedsGroup {
             is_init,
@@ -590,6 +927,13 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             close: into_inner!(close),
             address: into_inner!(address),
             associated_token: if !is_init { associated_token } else { None },
+            price_feed_max_age: into_inner!(price_feed_max_age),
+            program_stable: into_inner!(program_stable),
+            timelock,
+            active_window: into_inner!(active_window),
+            assign_to: into_inner!(assign_to),
+            validate_after: into_inner_vec!(validate_after),
+            when: into_inner!(when),
             seeds,
         })
     }
@@ -620,6 +964,14 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             ConstraintToken::MintFreezeAuthority(c) => self.add_mint_freeze_authority(c),
             ConstraintToken::MintDecimals(c) => self.add_mint_decimals(c),
             ConstraintToken::Bump(c) => self.add_bump(c),
+            ConstraintToken::PriceFeedMaxAge(c) => self.add_price_feed_max_age(c),
+            ConstraintToken::ProgramStable(c) => self.add_program_stable(c),
+            ConstraintToken::Timelock(c) => self.add_timelock(c),
+            ConstraintToken::MinDelay(c) => self.add_min_delay(c),
+            ConstraintToken::ActiveWindow(c) => self.add_active_window(c),
+            ConstraintToken::AssignTo(c) => self.add_assign_to(c),
+            ConstraintToken::ValidateAfter(c) => self.add_validate_after(c),
+            ConstraintToken::When(c) => self.add_when(c),
         }
     }
 
@@ -665,10 +1017,106 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         if self.close.is_some() {
             return Err(ParseError::new(c.span(), "close already provided"));
         }
+        if self.assign_to.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "close cannot be provided with assign_to",
+            ));
+        }
         self.close.replace(c);
         Ok(())
     }
 
+    fn add_active_window(&mut self, c: Context<ConstraintActiveWindow>) -> ParseResult<()> {
+        if self.active_window.is_some() {
+            return Err(ParseError::new(c.span(), "active_window already provided"));
+        }
+        self.active_window.replace(c);
+        Ok(())
+    }
+
+    fn add_assign_to(&mut self, c: Context<ConstraintAssignTo>) -> ParseResult<()> {
+        if self.close.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "assign_to cannot be provided with close",
+            ));
+        }
+        if self.assign_to.is_some() {
+            return Err(ParseError::new(c.span(), "assign_to already provided"));
+        }
+        self.assign_to.replace(c);
+        Ok(())
+    }
+
+    fn add_price_feed_max_age(&mut self, c: Context<ConstraintPriceFeedMaxAge>) -> ParseResult<()> {
+        if !matches!(self.f_ty, Some(Ty::Account(_))) {
+            return Err(ParseError::new(
+                c.span(),
+                "price_feed::max_age must be on an Account<'info, PriceFeed>",
+            ));
+        }
+        if self.init.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "price_feed::max_age cannot be provided with init",
+            ));
+        }
+        if self.price_feed_max_age.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "price_feed max_age already provided",
+            ));
+        }
+        self.price_feed_max_age.replace(c);
+        Ok(())
+    }
+
+    fn add_program_stable(&mut self, c: Context<ConstraintProgramStable>) -> ParseResult<()> {
+        if !matches!(self.f_ty, Some(Ty::Program(_))) {
+            return Err(ParseError::new(
+                c.span(),
+                "program::stable must be on a Program",
+            ));
+        }
+        if self.program_stable.is_some() {
+            return Err(ParseError::new(c.span(), "program stable already provided"));
+        }
+        self.program_stable.replace(c);
+        Ok(())
+    }
+
+    fn add_timelock(&mut self, c: Context<ConstraintTimelockAddress>) -> ParseResult<()> {
+        if !matches!(
+            self.f_ty,
+            Some(Ty::AccountInfo) | Some(Ty::UncheckedAccount)
+        ) {
+            return Err(ParseError::new(
+                c.span(),
+                "timelock must be on an AccountInfo or UncheckedAccount",
+            ));
+        }
+        if self.init.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "timelock cannot be provided with init",
+            ));
+        }
+        if self.timelock.is_some() {
+            return Err(ParseError::new(c.span(), "timelock already provided"));
+        }
+        self.timelock.replace(c);
+        Ok(())
+    }
+
+    fn add_min_delay(&mut self, c: Context<ConstraintMinDelay>) -> ParseResult<()> {
+        if self.min_delay.is_some() {
+            return Err(ParseError::new(c.span(), "min_delay already provided"));
+        }
+        self.min_delay.replace(c);
+        Ok(())
+    }
+
     fn add_address(&mut self, c: Context<ConstraintAddress>) -> ParseResult<()> {
         if self.address.is_some() {
             return Err(ParseError::new(c.span(), "address already provided"));
@@ -840,6 +1288,29 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    fn add_validate_after(&mut self, c: Context<ConstraintValidateAfter>) -> ParseResult<()> {
+        if self
+            .validate_after
+            .iter()
+            .any(|item| item.target == c.target)
+        {
+            return Err(ParseError::new(
+                c.span(),
+                "validate_after target already provided",
+            ));
+        }
+        self.validate_after.push(c);
+        Ok(())
+    }
+
+    fn add_when(&mut self, c: Context<ConstraintWhen>) -> ParseResult<()> {
+        if self.when.is_some() {
+            return Err(ParseError::new(c.span(), "when already provided"));
+        }
+        self.when.replace(c);
+        Ok(())
+    }
+
     fn add_literal(&mut self, c: Context<ConstraintLiteral>) -> ParseResult<()> {
         self.literal.push(c);
         Ok(())
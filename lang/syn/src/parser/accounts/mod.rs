@@ -18,7 +18,7 @@ pub fn parse(strct: &syn::ItemStruct) -> ParseResult<AccountsStruct> {
         })
         .map(|ix_attr| ix_attr.parse_args_with(Punctuated::<Expr, Comma>::parse_terminated))
         .transpose()?;
-    let fields = match &strct.fields {
+    let mut fields = match &strct.fields {
         syn::Fields::Named(fields) => fields
             .named
             .iter()
@@ -31,7 +31,178 @@ pub fn parse(strct: &syn::ItemStruct) -> ParseResult<AccountsStruct> {
             ))
         }
     };
-    Ok(AccountsStruct::new(strct.clone(), fields, instruction_api))
+    check_validation_order(&fields)?;
+    resolve_zero_copy_addresses(&mut fields);
+    let (struct_constraints, snapshot) = constraints::parse_struct_attrs(strct)?;
+    if snapshot {
+        if let Some(param) = strct
+            .generics
+            .params
+            .iter()
+            .find(|param| !matches!(param, syn::GenericParam::Lifetime(_)))
+        {
+            return Err(ParseError::new(
+                param.span(),
+                "#[accounts(snapshot)] structs can only be generic over lifetimes",
+            ));
+        }
+    }
+    let mut accounts =
+        AccountsStruct::new(strct.clone(), fields, struct_constraints, instruction_api);
+    accounts.snapshot = snapshot;
+    accounts.remaining_accounts = constraints::parse_remaining_accounts(strct)?;
+    Ok(accounts)
+}
+
+// Checks that the `validate_after` targets are other fields of the struct,
+// and that neither they nor the other `init` fields an `init` constraint
+// refers to depend on each other in a cycle.
+fn check_validation_order(fields: &[AccountField]) -> ParseResult<()> {
+    let idents: Vec<&syn::Ident> = fields.iter().map(AccountField::ident).collect();
+    for af in fields {
+        for target in af.constraints().validate_after() {
+            if target == af.ident() || !idents.contains(&target) {
+                return Err(ParseError::new(
+                    target.span(),
+                    "validate_after must name another field of the struct",
+                ));
+            }
+        }
+    }
+    let position = |ident: &syn::Ident| idents.iter().position(|i| *i == ident);
+    let explicit: Vec<Vec<usize>> = fields
+        .iter()
+        .map(|af| {
+            af.constraints()
+                .validate_after()
+                .into_iter()
+                .filter_map(position)
+                .collect()
+        })
+        .collect();
+    if let Some(cycle) = find_cycle(&explicit) {
+        return Err(cycle_error(fields, &cycle, "validate_after forms a cycle"));
+    }
+
+    let is_init =
+        |af: &AccountField| matches!(af, AccountField::Field(f) if f.constraints.init.is_some());
+    let implicit = fields.iter().enumerate().map(|(idx, af)| match af {
+        AccountField::Field(f) if is_init(af) => f
+            .constraints
+            .init
+            .as_ref()
+            .unwrap()
+            .references()
+            .iter()
+            .filter_map(position)
+            .filter(|dep| *dep != idx && is_init(&fields[*dep]))
+            .collect(),
+        _ => vec![],
+    });
+    let deps: Vec<Vec<usize>> = explicit
+        .into_iter()
+        .zip(implicit)
+        .map(|(explicit, implicit): (Vec<usize>, Vec<usize>)| {
+            explicit.into_iter().chain(implicit).collect()
+        })
+        .collect();
+    if let Some(cycle) = find_cycle(&deps) {
+        return Err(cycle_error(
+            fields,
+            &cycle,
+            "init constraints depend on each other in a cycle",
+        ));
+    }
+    Ok(())
+}
+
+// Finds a cycle in the graph of the fields' dependencies, given by index,
+// returning the path around it.
+fn find_cycle(deps: &[Vec<usize>]) -> Option<Vec<usize>> {
+    // Depth first search from each field, following its dependencies. A
+    // field reached again while it's on the path closes a cycle.
+    fn visit(
+        idx: usize,
+        deps: &[Vec<usize>],
+        path: &mut Vec<usize>,
+        done: &mut Vec<bool>,
+    ) -> Option<Vec<usize>> {
+        if done[idx] {
+            return None;
+        }
+        if let Some(start) = path.iter().position(|p| *p == idx) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(idx);
+            return Some(cycle);
+        }
+        path.push(idx);
+        for dep in &deps[idx] {
+            if let Some(cycle) = visit(*dep, deps, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done[idx] = true;
+        None
+    }
+    let mut done = vec![false; deps.len()];
+    (0..deps.len()).find_map(|idx| visit(idx, deps, &mut Vec::new(), &mut done))
+}
+
+fn cycle_error(fields: &[AccountField], cycle: &[usize], msg: &str) -> ParseError {
+    let names: Vec<String> = cycle
+        .iter()
+        .map(|idx| fields[*idx].ident().to_string())
+        .collect();
+    ParseError::new(
+        fields[*cycle.last().unwrap()].ident().span(),
+        format!("{}: {}", msg, names.join(" -> ")),
+    )
+}
+
+// Marks the `address` and `active_window` constraints reading a field of one
+// of the struct's zero-copy accounts, e.g. `address = config.oracle` with an
+// `AccountLoader` config, which can't be dereferenced like other accounts.
+fn resolve_zero_copy_addresses(fields: &mut [AccountField]) {
+    let zero_copy: Vec<syn::Ident> = fields
+        .iter()
+        .filter_map(|af| match af {
+            AccountField::Field(f) if matches!(f.ty, Ty::Loader(_) | Ty::AccountLoader(_)) => {
+                Some(f.ident.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    let is_zero_copy =
+        |expr: &Expr| address_base(expr).map_or(false, |base| zero_copy.contains(base));
+    for af in fields.iter_mut() {
+        let constraints = match af {
+            AccountField::Field(f) => &mut f.constraints,
+            AccountField::CompositeField(f) => &mut f.constraints,
+        };
+        if let Some(c) = &mut constraints.address {
+            c.zero_copy_base = is_zero_copy(&c.address);
+        }
+        if let Some(c) = &mut constraints.active_window {
+            c.zero_copy_start = is_zero_copy(&c.start);
+            c.zero_copy_end = is_zero_copy(&c.end);
+        }
+    }
+}
+
+// The account an address of the form `<account>.<field>..` is read from.
+fn address_base(address: &Expr) -> Option<&syn::Ident> {
+    let mut base = match address {
+        Expr::Field(field) => &*field.base,
+        _ => return None,
+    };
+    while let Expr::Field(field) = base {
+        base = &*field.base;
+    }
+    match base {
+        Expr::Path(path) => path.path.get_ident(),
+        _ => None,
+    }
 }
 
 pub fn parse_account_field(f: &syn::Field, has_instruction_api: bool) -> ParseResult<AccountField> {
@@ -79,6 +250,8 @@ fn is_field_primitive(f: &syn::Field) -> ParseResult<bool> {
             | "Program"
             | "Signer"
             | "SystemAccount"
+            | "NativeAccount"
+            | "SysvarAccount"
             | "ProgramData"
     );
     Ok(r)
@@ -103,6 +276,8 @@ fn parse_ty(f: &syn::Field) -> ParseResult<Ty> {
         "Program" => Ty::Program(parse_program_ty(&path)?),
         "Signer" => Ty::Signer,
         "SystemAccount" => Ty::SystemAccount,
+        "NativeAccount" => Ty::NativeAccount,
+        "SysvarAccount" => Ty::SysvarAccount,
         "ProgramData" => Ty::ProgramData,
         _ => return Err(ParseError::new(f.ty.span(), "invalid account type given")),
     };
@@ -23,6 +23,10 @@ impl CrateContext {
         self.modules.iter().flat_map(|(_, ctx)| ctx.enums())
     }
 
+    pub fn macros(&self) -> impl Iterator<Item = &syn::ItemMacro> {
+        self.modules.iter().flat_map(|(_, ctx)| ctx.macros())
+    }
+
     pub fn modules(&self) -> impl Iterator<Item = ModuleContext> {
         self.modules
             .iter()
@@ -194,4 +198,11 @@ impl ParsedModule {
             _ => None,
         })
     }
+
+    fn macros(&self) -> impl Iterator<Item = &syn::ItemMacro> {
+        self.items.iter().filter_map(|i| match i {
+            syn::Item::Macro(item) => Some(item),
+            _ => None,
+        })
+    }
 }
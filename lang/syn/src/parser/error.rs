@@ -10,6 +10,11 @@ pub fn parse(error_enum: &mut syn::ItemEnum, args: Option<ErrorArgs>) -> Error {
         .map(|variant: &mut syn::Variant| {
             let msg = parse_error_attribute(variant);
             let ident = variant.ident.clone();
+            let fields = match &variant.fields {
+                syn::Fields::Unit => vec![],
+                syn::Fields::Named(fields) => fields.named.iter().cloned().collect(),
+                syn::Fields::Unnamed(_) => panic!("Error variant fields must be named"),
+            };
             let id = match &variant.discriminant {
                 None => last_discriminant,
                 Some((_, disc)) => match disc {
@@ -27,7 +32,12 @@ pub fn parse(error_enum: &mut syn::ItemEnum, args: Option<ErrorArgs>) -> Error {
             // Remove any attributes on the error variant.
             variant.attrs = vec![];
 
-            ErrorCode { id, ident, msg }
+            ErrorCode {
+                id,
+                ident,
+                msg,
+                fields,
+            }
         })
         .collect();
     Error {
@@ -0,0 +1,78 @@
+use crate::{Flag, Flags, FlagsArgs};
+use syn::parse::{Error as ParseError, Result as ParseResult};
+use syn::spanned::Spanned;
+
+// Variants without a discriminant take the next bit, e.g. `A, B, C` are
+// `1 << 0`, `1 << 1` and `1 << 2`. Explicit discriminants must be integer
+// literals or shifts of integer literals, so that the IDL can evaluate them.
+pub fn parse(flags_enum: &syn::ItemEnum, args: Option<FlagsArgs>) -> ParseResult<Flags> {
+    let repr = args
+        .map(|a| a.repr)
+        .unwrap_or_else(|| syn::Ident::new("u32", proc_macro2::Span::call_site()));
+    let bits = if repr == "u64" { 64 } else { 32 };
+    let flags = flags_enum
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(idx, variant)| {
+            if !matches!(variant.fields, syn::Fields::Unit) {
+                return Err(ParseError::new(
+                    variant.fields.span(),
+                    "flags can't have fields",
+                ));
+            }
+            let value = match &variant.discriminant {
+                None if idx < bits => 1u64 << idx,
+                None => {
+                    return Err(ParseError::new(
+                        variant.span(),
+                        format!("too many flags for {}", repr),
+                    ))
+                }
+                Some((_, expr)) => parse_flag_value(expr)?,
+            };
+            if value == 0 || (bits == 32 && value > u32::MAX as u64) {
+                return Err(ParseError::new(
+                    variant.span(),
+                    format!("flag value must be a non-zero {}", repr),
+                ));
+            }
+            Ok(Flag {
+                ident: variant.ident.clone(),
+                value,
+            })
+        })
+        .collect::<ParseResult<Vec<Flag>>>()?;
+    Ok(Flags {
+        raw_enum: flags_enum.clone(),
+        ident: flags_enum.ident.clone(),
+        repr,
+        flags,
+    })
+}
+
+fn parse_flag_value(expr: &syn::Expr) -> ParseResult<u64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        }) => int.base10_parse::<u64>(),
+        syn::Expr::Binary(syn::ExprBinary {
+            left,
+            op: syn::BinOp::Shl(_),
+            right,
+            ..
+        }) => {
+            let shift = parse_flag_value(right)?;
+            parse_flag_value(left)?
+                .checked_shl(shift as u32)
+                .filter(|_| shift < 64)
+                .ok_or_else(|| ParseError::new(expr.span(), "flag value overflows u64"))
+        }
+        syn::Expr::Paren(paren) => parse_flag_value(&paren.expr),
+        _ => Err(ParseError::new(
+            expr.span(),
+            "expected an integer literal or a shift, e.g. `1 << 3`",
+        )),
+    }
+}
@@ -1,6 +1,7 @@
 pub mod accounts;
 pub mod context;
 pub mod error;
+pub mod flags;
 pub mod program;
 
 pub fn tts_to_string<T: quote::ToTokens>(item: T) -> String {
@@ -8,3 +9,45 @@ pub fn tts_to_string<T: quote::ToTokens>(item: T) -> String {
     item.to_tokens(&mut tts);
     tts.to_string()
 }
+
+// Parses the `(<path>, ..)` list following the `derive` keyword in the
+// `#[program]`, `#[event]` and `#[error]` attribute arguments, giving
+// additional traits to derive for the generated types.
+pub fn parse_derives(input: syn::parse::ParseStream) -> syn::parse::Result<Vec<syn::Path>> {
+    let content;
+    syn::parenthesized!(content in input);
+    let derives = content.parse_terminated::<_, syn::Token![,]>(syn::Path::parse_mod_style)?;
+    Ok(derives.into_iter().collect())
+}
+
+// Parses the name given by an `#[idl(name = "<name>")]` attribute, under
+// which a type or field appears in the IDL instead of its Rust name. A bare
+// `#[idl]` gives no name.
+pub fn parse_idl_name(attr: &syn::Attribute) -> syn::parse::Result<Option<String>> {
+    match attr.parse_meta()? {
+        syn::Meta::Path(_) => Ok(None),
+        syn::Meta::List(list) => match list.nested.iter().collect::<Vec<_>>().as_slice() {
+            [syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(name),
+                ..
+            }))] if path.is_ident("name") => Ok(Some(name.value())),
+            _ => Err(syn::parse::Error::new_spanned(
+                list,
+                "expected #[idl(name = \"<name>\")]",
+            )),
+        },
+        meta => Err(syn::parse::Error::new_spanned(
+            meta,
+            "expected #[idl(name = \"<name>\")]",
+        )),
+    }
+}
+
+// The IDL name given by the first `#[idl(..)]` of `attrs`, if any.
+pub fn idl_name(attrs: &[syn::Attribute]) -> syn::parse::Result<Option<String>> {
+    match attrs.iter().find(|attr| attr.path.is_ident("idl")) {
+        None => Ok(None),
+        Some(attr) => parse_idl_name(attr),
+    }
+}
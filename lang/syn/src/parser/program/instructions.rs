@@ -1,10 +1,15 @@
 use crate::parser::program::ctx_accounts_ident;
-use crate::{FallbackFn, Ix, IxArg};
+use crate::{FallbackFn, Ix, IxArg, IxBatch, IxDeprecation, IxIdempotent, PrefixFn};
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use quote::ToTokens;
 use syn::parse::{Error as ParseError, Result as ParseResult};
 use syn::spanned::Spanned;
+use syn::Ident;
 
 // Parse all non-state ix handlers from the program mod definition.
-pub fn parse(program_mod: &syn::ItemMod) -> ParseResult<(Vec<Ix>, Option<FallbackFn>)> {
+pub fn parse(
+    program_mod: &syn::ItemMod,
+) -> ParseResult<(Vec<Ix>, Option<FallbackFn>, Vec<PrefixFn>)> {
     let mod_content = &program_mod
         .content
         .as_ref()
@@ -22,22 +27,90 @@ pub fn parse(program_mod: &syn::ItemMod) -> ParseResult<(Vec<Ix>, Option<Fallbac
             _ => None,
         })
         .map(|method: &syn::ItemFn| {
-            let (ctx, args) = parse_args(method)?;
+            let (ctx, mut args) = parse_args(method)?;
             let anchor_ident = ctx_accounts_ident(&ctx.raw_arg)?;
+            let batch = parse_batch(method, &mut args)?;
+            let idempotent = parse_idempotent(method)?;
+            if idempotent.is_some() {
+                if let Some(arg) = args.iter().find(|arg| arg.name == "nonce") {
+                    return Err(ParseError::new(
+                        arg.name.span(),
+                        "#[idempotent] instructions are given a `nonce` arg by the program macro",
+                    ));
+                }
+                let raw_arg = match syn::parse_quote! { nonce: [u8; 16] } {
+                    syn::FnArg::Typed(arg) => arg,
+                    syn::FnArg::Receiver(_) => unreachable!(),
+                };
+                args.push(IxArg {
+                    name: syn::parse_quote! { nonce },
+                    raw_arg,
+                });
+            }
             Ok(Ix {
                 raw_method: method.clone(),
                 ident: method.sig.ident.clone(),
                 args,
                 anchor_ident,
+                simulation_only: is_simulation_only(method),
+                zero_copy: is_zero_copy(method),
+                skip_exit: method.attrs.iter().any(is_skip_exit_attr),
+                transfer_hook: method.attrs.iter().any(is_transfer_hook_attr),
+                mutated_accounts: mutated_accounts(&ctx.name, &method.block),
+                deprecated: parse_deprecation(method)?,
+                idempotent,
+                batch,
             })
         })
         .collect::<ParseResult<Vec<Ix>>>()?;
+    if ixs.iter().any(|ix| ix.idempotent.is_some()) {
+        if let Some(ix) = ixs.iter().find(|ix| ix.ident == "close_replay") {
+            return Err(ParseError::new(
+                ix.ident.span(),
+                "`close_replay` is generated for programs with #[idempotent] instructions",
+            ));
+        }
+    }
+    if let Some(ix) = ixs.iter().filter(|ix| ix.transfer_hook).nth(1) {
+        return Err(ParseError::new(
+            ix.raw_method.span(),
+            "Only one instruction can be marked #[transfer_hook]",
+        ));
+    }
+
+    let prefix_fns = mod_content
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Fn(item_fn) => {
+                let attr = item_fn.attrs.iter().find(|attr| is_prefix_attr(attr))?;
+                Some((item_fn, attr))
+            }
+            _ => None,
+        })
+        .map(|(method, attr)| {
+            if let Ok((ctx, _)) = parse_args(method) {
+                if ctx_accounts_ident(&ctx.raw_arg).is_ok() {
+                    return Err(ParseError::new(
+                        method.sig.span(),
+                        "#[instruction_prefix] handlers take the raw program id, accounts and instruction data",
+                    ));
+                }
+            }
+            Ok(PrefixFn {
+                raw_method: method.clone(),
+                prefix: parse_prefix(attr)?,
+            })
+        })
+        .collect::<ParseResult<Vec<PrefixFn>>>()?;
 
     let fallback_fn = {
         let fallback_fns = mod_content
             .iter()
             .filter_map(|item| match item {
                 syn::Item::Fn(item_fn) => {
+                    if item_fn.attrs.iter().any(is_prefix_attr) {
+                        return None;
+                    }
                     let (ctx, _args) = parse_args(item_fn).ok()?;
                     if ctx_accounts_ident(&ctx.raw_arg).is_ok() {
                         return None;
@@ -60,7 +133,268 @@ pub fn parse(program_mod: &syn::ItemMod) -> ParseResult<(Vec<Ix>, Option<Fallbac
             })
     };
 
-    Ok((ixs, fallback_fn))
+    Ok((ixs, fallback_fn, prefix_fns))
+}
+
+pub fn is_simulation_only_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("simulation_only")
+}
+
+fn is_simulation_only(method: &syn::ItemFn) -> bool {
+    method.attrs.iter().any(is_simulation_only_attr)
+}
+
+pub fn is_zero_copy_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("instruction")
+        && attr
+            .parse_args::<syn::Ident>()
+            .map_or(false, |ident| ident == "zero_copy")
+}
+
+fn is_zero_copy(method: &syn::ItemFn) -> bool {
+    method.attrs.iter().any(is_zero_copy_attr)
+}
+
+pub fn is_skip_exit_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("skip_exit")
+}
+
+pub fn is_transfer_hook_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("transfer_hook")
+}
+
+pub fn is_prefix_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("instruction_prefix")
+}
+
+// Parses `#[instruction_prefix(<byte>, ..)]`.
+fn parse_prefix(attr: &syn::Attribute) -> ParseResult<Vec<u8>> {
+    let bytes = attr.parse_args_with(
+        syn::punctuated::Punctuated::<syn::LitInt, syn::Token![,]>::parse_terminated,
+    )?;
+    if bytes.is_empty() {
+        return Err(ParseError::new(
+            attr.span(),
+            "#[instruction_prefix] requires at least one byte",
+        ));
+    }
+    bytes.iter().map(|byte| byte.base10_parse::<u8>()).collect()
+}
+
+pub fn is_deprecated_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("deprecated_instruction")
+}
+
+// Parses `#[deprecated_instruction(since = "..", note = "..", sunset_slot = ..)]`,
+// where all args are optional.
+fn parse_deprecation(method: &syn::ItemFn) -> ParseResult<Option<IxDeprecation>> {
+    let attr = match method.attrs.iter().find(|attr| is_deprecated_attr(attr)) {
+        None => return Ok(None),
+        Some(attr) => attr,
+    };
+    let mut deprecation = IxDeprecation::default();
+    let nested = match attr.parse_meta()? {
+        syn::Meta::Path(_) => return Ok(Some(deprecation)),
+        syn::Meta::List(list) => list.nested,
+        syn::Meta::NameValue(nv) => {
+            return Err(ParseError::new(nv.span(), "expected a list of arguments"))
+        }
+    };
+    for meta in nested {
+        let nv = match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) => nv,
+            _ => return Err(ParseError::new(meta.span(), "expected `name = value`")),
+        };
+        match (
+            nv.path.get_ident().map(|i| i.to_string()).as_deref(),
+            &nv.lit,
+        ) {
+            (Some("since"), syn::Lit::Str(s)) => deprecation.since = Some(s.value()),
+            (Some("note"), syn::Lit::Str(s)) => deprecation.note = Some(s.value()),
+            (Some("sunset_slot"), syn::Lit::Int(i)) => {
+                deprecation.sunset_slot = Some(i.base10_parse()?)
+            }
+            _ => {
+                return Err(ParseError::new(
+                    nv.span(),
+                    "expected `since = \"..\"`, `note = \"..\"` or `sunset_slot = <slot>`",
+                ))
+            }
+        }
+    }
+    Ok(Some(deprecation))
+}
+
+pub fn is_batch_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("batch")
+}
+
+// Parses the args of a `#[batch]` handler, `(ctx, chunk: &mut <Accounts>,
+// item: <Item>)`, replacing them with the instruction's `items: Vec<<Item>>`.
+fn parse_batch(method: &syn::ItemFn, args: &mut Vec<IxArg>) -> ParseResult<Option<IxBatch>> {
+    if !method.attrs.iter().any(is_batch_attr) {
+        return Ok(None);
+    }
+    if is_zero_copy(method) {
+        return Err(ParseError::new(
+            method.sig.span(),
+            "#[batch] instructions can't be #[instruction(zero_copy)]",
+        ));
+    }
+    let (chunk, item) = match &args[..] {
+        [chunk, item] => (chunk, item),
+        _ => {
+            return Err(ParseError::new(
+                method.sig.inputs.span(),
+                "#[batch] handlers take the context, a `&mut` accounts chunk and an item",
+            ))
+        }
+    };
+    let mut accounts = match &*chunk.raw_arg.ty {
+        syn::Type::Reference(syn::TypeReference {
+            mutability: Some(_),
+            elem,
+            ..
+        }) => match &**elem {
+            syn::Type::Path(ty) => ty.path.clone(),
+            _ => return Err(ParseError::new(elem.span(), "expected an accounts struct")),
+        },
+        ty => {
+            return Err(ParseError::new(
+                ty.span(),
+                "expected a `&mut` reference to an accounts struct",
+            ))
+        }
+    };
+    if let Some(segment) = accounts.segments.last_mut() {
+        segment.arguments = syn::PathArguments::None;
+    }
+    let item_ty = &item.raw_arg.ty;
+    let raw_arg = match syn::parse_quote! { items: Vec<#item_ty> } {
+        syn::FnArg::Typed(arg) => arg,
+        syn::FnArg::Receiver(_) => unreachable!(),
+    };
+    *args = vec![IxArg {
+        name: syn::parse_quote! { items },
+        raw_arg,
+    }];
+    Ok(Some(IxBatch { accounts }))
+}
+
+pub fn is_idempotent_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("idempotent")
+}
+
+// Parses `#[idempotent]` or `#[idempotent(expiry = <seconds>)]`.
+fn parse_idempotent(method: &syn::ItemFn) -> ParseResult<Option<IxIdempotent>> {
+    let attr = match method.attrs.iter().find(|attr| is_idempotent_attr(attr)) {
+        None => return Ok(None),
+        Some(attr) => attr,
+    };
+    let mut idempotent = IxIdempotent::default();
+    let nested = match attr.parse_meta()? {
+        syn::Meta::Path(_) => return Ok(Some(idempotent)),
+        syn::Meta::List(list) => list.nested,
+        syn::Meta::NameValue(nv) => {
+            return Err(ParseError::new(nv.span(), "expected a list of arguments"))
+        }
+    };
+    for meta in nested {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("expiry") => {
+                let expiry = match &nv.lit {
+                    syn::Lit::Int(i) => i.base10_parse::<i64>()?,
+                    _ => return Err(ParseError::new(nv.lit.span(), "expected seconds")),
+                };
+                if expiry < 0 {
+                    return Err(ParseError::new(
+                        nv.lit.span(),
+                        "expected a non-negative number of seconds",
+                    ));
+                }
+                idempotent.expiry = Some(expiry);
+            }
+            _ => {
+                return Err(ParseError::new(
+                    meta.span(),
+                    "expected `expiry = <seconds>`",
+                ))
+            }
+        }
+    }
+    Ok(Some(idempotent))
+}
+
+// Heuristically finds the accounts a handler modifies in place, i.e.
+// `ctx.accounts.<field>`, possibly followed by further field accesses, that's
+// assigned to or mutably borrowed. Changes through method calls aren't seen.
+fn mutated_accounts(ctx: &Ident, block: &syn::Block) -> Vec<Ident> {
+    let mut mutated = Vec::new();
+    find_mutated_accounts(ctx, block.to_token_stream(), &mut mutated);
+    mutated
+}
+
+fn find_mutated_accounts(ctx: &Ident, stream: TokenStream, mutated: &mut Vec<Ident>) {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    for (idx, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(group) => find_mutated_accounts(ctx, group.stream(), mutated),
+            TokenTree::Ident(ident) if ident == ctx => {
+                let field = match &tokens[idx + 1..] {
+                    [TokenTree::Punct(dot_1), TokenTree::Ident(accounts), TokenTree::Punct(dot_2), TokenTree::Ident(field), ..]
+                        if dot_1.as_char() == '.'
+                            && accounts == "accounts"
+                            && dot_2.as_char() == '.' =>
+                    {
+                        field
+                    }
+                    _ => continue,
+                };
+                let borrowed_mut = matches!(
+                    &tokens[..idx],
+                    [.., TokenTree::Punct(amp), TokenTree::Ident(mut_token)]
+                        if amp.as_char() == '&' && mut_token == "mut"
+                );
+                if (borrowed_mut || is_assigned(&tokens[idx + 5..]))
+                    && !mutated.iter().any(|m| m == field)
+                {
+                    mutated.push(field.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Skips field accesses and indexing, then checks for an assignment operator.
+fn is_assigned(mut rest: &[TokenTree]) -> bool {
+    loop {
+        match rest {
+            [TokenTree::Punct(dot), TokenTree::Ident(_) | TokenTree::Literal(_), tail @ ..]
+                if dot.as_char() == '.' =>
+            {
+                rest = tail
+            }
+            [TokenTree::Group(group), tail @ ..] if group.delimiter() == Delimiter::Bracket => {
+                rest = tail
+            }
+            _ => break,
+        }
+    }
+    match rest {
+        [TokenTree::Punct(eq), ..] if eq.as_char() == '=' => eq.spacing() == Spacing::Alone,
+        [TokenTree::Punct(op), TokenTree::Punct(eq), ..]
+            if "+-*/%^&|".contains(op.as_char()) && op.spacing() == Spacing::Joint =>
+        {
+            eq.as_char() == '='
+        }
+        [TokenTree::Punct(op_1), TokenTree::Punct(op_2), TokenTree::Punct(eq), ..]
+            if "<>".contains(op_1.as_char()) && op_1.as_char() == op_2.as_char() =>
+        {
+            eq.as_char() == '='
+        }
+        _ => false,
+    }
 }
 
 pub fn parse_args(method: &syn::ItemFn) -> ParseResult<(IxArg, Vec<IxArg>)> {
@@ -91,3 +425,23 @@ pub fn parse_args(method: &syn::ItemFn) -> ParseResult<(IxArg, Vec<IxArg>)> {
 
     Ok((ctx, args))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idempotent_expiry() {
+        let method: syn::ItemFn = syn::parse_quote! {
+            #[idempotent(expiry = 60)]
+            pub fn pay(ctx: Context<Pay>) -> ProgramResult { Ok(()) }
+        };
+        assert_eq!(parse_idempotent(&method).unwrap().unwrap().expiry, Some(60));
+
+        let method: syn::ItemFn = syn::parse_quote! {
+            #[idempotent(expiry = -60)]
+            pub fn pay(ctx: Context<Pay>) -> ProgramResult { Ok(()) }
+        };
+        assert!(parse_idempotent(&method).is_err());
+    }
+}
@@ -1,22 +1,197 @@
-use crate::Program;
+use crate::codegen::program::common;
+use crate::{CpiAllowlist, Ix, PrefixFn, Program, ProgramArgs, State};
 use syn::parse::{Error as ParseError, Result as ParseResult};
 use syn::spanned::Spanned;
 
 mod instructions;
 mod state;
 
-pub fn parse(program_mod: syn::ItemMod) -> ParseResult<Program> {
+// Mirrors `anchor_lang::idl::IDL_IX_TAG`.
+const IDL_IX_TAG: u64 = 0x0a69e9a778bcf440;
+
+pub fn parse(mut program_mod: syn::ItemMod) -> ParseResult<Program> {
     let state = state::parse(&program_mod)?;
-    let (ixs, fallback_fn) = instructions::parse(&program_mod)?;
+    let (ixs, fallback_fn, prefix_fns) = instructions::parse(&program_mod)?;
+    check_prefixes(&ixs, state.as_ref(), &prefix_fns)?;
+    strip_marker_attrs(&mut program_mod);
     Ok(Program {
         state,
         ixs,
         name: program_mod.ident.clone(),
         program_mod,
         fallback_fn,
+        prefix_fns,
+        panic_handler: None,
+        cpi_allowlist: None,
+        timelock: false,
+        interface_registry: false,
+        schema_registry: false,
+        dependencies: Vec::new(),
+        arena: None,
+        deny_trailing_args: false,
+        derives: Vec::new(),
     })
 }
 
+// Instruction prefixes are matched before sighash dispatch, so they must not
+// shadow any of the program's instructions, including the IDL instructions,
+// or each other.
+fn check_prefixes(ixs: &[Ix], state: Option<&State>, prefix_fns: &[PrefixFn]) -> ParseResult<()> {
+    let mut sighashes: Vec<[u8; 8]> = ixs.iter().map(common::ix_sighash).collect();
+    sighashes.push(IDL_IX_TAG.to_le_bytes());
+    if ixs.iter().any(|ix| ix.idempotent.is_some()) {
+        sighashes.push(common::sighash_close_replay());
+    }
+    if let Some(state) = state {
+        sighashes.push(common::sighash_ctor());
+        if let Some((_, methods)) = &state.impl_block_and_methods {
+            sighashes.extend(
+                methods.iter().map(|m| {
+                    common::sighash(common::SIGHASH_STATE_NAMESPACE, &m.ident.to_string())
+                }),
+            );
+        }
+        for iface in state.interfaces.iter().flatten() {
+            sighashes.extend(
+                iface
+                    .methods
+                    .iter()
+                    .map(|m| common::sighash(&iface.trait_name, &m.ident.to_string())),
+            );
+        }
+    }
+    for (idx, prefix_fn) in prefix_fns.iter().enumerate() {
+        let prefix = &prefix_fn.prefix;
+        if sighashes.iter().any(|sighash| sighash.starts_with(prefix)) {
+            return Err(ParseError::new(
+                prefix_fn.raw_method.sig.ident.span(),
+                "instruction prefix collides with an instruction's sighash",
+            ));
+        }
+        let overlaps = prefix_fns[..idx]
+            .iter()
+            .any(|other| other.prefix.starts_with(prefix) || prefix.starts_with(&other.prefix));
+        if overlaps {
+            return Err(ParseError::new(
+                prefix_fn.raw_method.sig.ident.span(),
+                "instruction prefix overlaps with another instruction prefix",
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Parses the `#[program]` attribute's arguments, a comma separated list of
+// - `panic_handler`, installing either anchor's default handler or, with
+//   `panic_handler = <path>`, the given function.
+// - `derive(<path>, ..)`, additional traits to derive for the generated
+//   instruction structs.
+// - `cpi_allowlist(<program id>, ..)`, the programs the program may invoke,
+//   and `cpi_allowlist_hook = <path>`, accepting other programs at runtime.
+// - `timelock`, adding the instructions managing timelocks.
+// - `interface_registry`, adding the instructions managing interface
+//   registrations.
+// - `schema_registry`, adding the instruction publishing the program's schema.
+// - `dependencies(<module>, ..)`, adding the instruction checking the
+//   dependencies declared by the given `declare_dependency!` modules.
+// - `arena`, giving each instruction an arena of the default capacity or,
+//   with `arena = <bytes>`, of the given one.
+// - `deny_trailing_args`, refusing instruction data past the last argument.
+// - `c_header`, having `anchor build` write a C header of the instructions.
+pub fn parse_args(input: syn::parse::ParseStream) -> ParseResult<ProgramArgs> {
+    let mut args = ProgramArgs::default();
+    let mut cpi_allowlist = None;
+    let mut cpi_allowlist_hook = None;
+    while !input.is_empty() {
+        let ident: syn::Ident = input.parse()?;
+        if ident == "panic_handler" {
+            let handler = match input.parse::<Option<syn::Token![=]>>()? {
+                Some(_) => input.parse()?,
+                None => syn::parse_quote! { anchor_lang::panic::log_panic },
+            };
+            args.panic_handler = Some(handler);
+        } else if ident == "derive" {
+            args.derives.extend(crate::parser::parse_derives(input)?);
+        } else if ident == "cpi_allowlist" {
+            let content;
+            syn::parenthesized!(content in input);
+            let programs =
+                content.parse_terminated::<syn::Expr, syn::Token![,]>(syn::parse::Parse::parse)?;
+            cpi_allowlist = Some(programs.into_iter().collect());
+        } else if ident == "cpi_allowlist_hook" {
+            input.parse::<syn::Token![=]>()?;
+            cpi_allowlist_hook = Some((ident, input.parse()?));
+        } else if ident == "timelock" {
+            args.timelock = true;
+        } else if ident == "interface_registry" {
+            args.interface_registry = true;
+        } else if ident == "schema_registry" {
+            args.schema_registry = true;
+        } else if ident == "dependencies" {
+            let content;
+            syn::parenthesized!(content in input);
+            let modules =
+                content.parse_terminated::<syn::Path, syn::Token![,]>(syn::parse::Parse::parse)?;
+            args.dependencies = modules.into_iter().collect();
+        } else if ident == "arena" {
+            let capacity = match input.parse::<Option<syn::Token![=]>>()? {
+                Some(_) => input.parse()?,
+                None => syn::parse_quote! { anchor_lang::arena::DEFAULT_CAPACITY },
+            };
+            args.arena = Some(capacity);
+        } else if ident == "deny_trailing_args" {
+            args.deny_trailing_args = true;
+        } else if ident == "c_header" {
+            args.c_header = true;
+        } else {
+            return Err(ParseError::new(
+                ident.span(),
+                "expected `panic_handler`, `derive`, `cpi_allowlist`, `cpi_allowlist_hook`, `timelock`, `interface_registry`, `schema_registry`, `dependencies`, `arena`, `deny_trailing_args` or `c_header`",
+            ));
+        }
+        if !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+        }
+    }
+    args.cpi_allowlist = match (cpi_allowlist, cpi_allowlist_hook) {
+        (None, None) => None,
+        (None, Some((ident, _))) => {
+            return Err(ParseError::new(
+                ident.span(),
+                "`cpi_allowlist_hook` requires `cpi_allowlist`",
+            ))
+        }
+        (Some(programs), hook) => Some(CpiAllowlist {
+            programs,
+            hook: hook.map(|(_, hook)| hook),
+        }),
+    };
+    Ok(args)
+}
+
+// `#[simulation_only]`, `#[deprecated_instruction]`, `#[transfer_hook]`,
+// `#[instruction_prefix]`, `#[idempotent]`, `#[skip_exit]` and
+// `#[instruction(zero_copy)]` are inert markers consumed by the program macro,
+// so they're removed before the user's module is emitted.
+fn strip_marker_attrs(program_mod: &mut syn::ItemMod) {
+    if let Some((_, items)) = program_mod.content.as_mut() {
+        for item in items.iter_mut() {
+            if let syn::Item::Fn(item_fn) = item {
+                item_fn.attrs.retain(|attr| {
+                    !instructions::is_simulation_only_attr(attr)
+                        && !instructions::is_deprecated_attr(attr)
+                        && !instructions::is_transfer_hook_attr(attr)
+                        && !instructions::is_zero_copy_attr(attr)
+                        && !instructions::is_prefix_attr(attr)
+                        && !instructions::is_idempotent_attr(attr)
+                        && !instructions::is_skip_exit_attr(attr)
+                        && !instructions::is_batch_attr(attr)
+                });
+            }
+        }
+    }
+}
+
 fn ctx_accounts_ident(path_ty: &syn::PatType) -> ParseResult<proc_macro2::Ident> {
     let p = match &*path_ty.ty {
         syn::Type::Path(p) => &p.path,
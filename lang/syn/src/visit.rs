@@ -0,0 +1,236 @@
+//! Traversal of parsed programs, accounts structs and errors, for tools
+//! analyzing Anchor programs, e.g. linters or documentation generators, that
+//! reuse the parser instead of reimplementing Anchor's attributes.
+//!
+//! Implement [`Visit`], overriding the methods of the nodes of interest, and
+//! call a `visit_*` method on a parsed node. Like `syn::visit`, the default
+//! implementation of each method visits the node's children through the free
+//! function of the same name, which an overriding method should call to keep
+//! traversing them.
+//!
+//! ```ignore
+//! struct Signers(Vec<String>);
+//!
+//! impl<'ast> Visit<'ast> for Signers {
+//!     fn visit_field(&mut self, field: &'ast Field) {
+//!         if field.constraints.is_signer() {
+//!             self.0.push(field.ident.to_string());
+//!         }
+//!         visit::visit_field(self, field);
+//!     }
+//! }
+//!
+//! let accounts: AccountsStruct = syn::parse_str(source)?;
+//! let mut signers = Signers(vec![]);
+//! signers.visit_accounts_struct(&accounts);
+//! ```
+
+use crate::codegen::accounts::constraints::linearize;
+use crate::{
+    AccountField, AccountsStruct, CompositeField, Constraint, ConstraintRaw, Error, ErrorCode,
+    Field, Ix, IxArg, Program, State, StateIx,
+};
+
+pub trait Visit<'ast> {
+    fn visit_program(&mut self, node: &'ast Program) {
+        visit_program(self, node)
+    }
+
+    fn visit_state(&mut self, node: &'ast State) {
+        visit_state(self, node)
+    }
+
+    fn visit_state_ix(&mut self, node: &'ast StateIx) {
+        visit_state_ix(self, node)
+    }
+
+    fn visit_ix(&mut self, node: &'ast Ix) {
+        visit_ix(self, node)
+    }
+
+    fn visit_ix_arg(&mut self, _node: &'ast IxArg) {}
+
+    fn visit_accounts_struct(&mut self, node: &'ast AccountsStruct) {
+        visit_accounts_struct(self, node)
+    }
+
+    fn visit_account_field(&mut self, node: &'ast AccountField) {
+        visit_account_field(self, node)
+    }
+
+    fn visit_field(&mut self, node: &'ast Field) {
+        visit_field(self, node)
+    }
+
+    fn visit_composite_field(&mut self, node: &'ast CompositeField) {
+        visit_composite_field(self, node)
+    }
+
+    /// A constraint of the field or composite field visited last, in the
+    /// order they're checked.
+    fn visit_constraint(&mut self, _node: &Constraint) {}
+
+    /// A constraint given with `#[accounts(..)]` on an accounts struct.
+    fn visit_struct_constraint(&mut self, _node: &'ast ConstraintRaw) {}
+
+    fn visit_error(&mut self, node: &'ast Error) {
+        visit_error(self, node)
+    }
+
+    fn visit_error_code(&mut self, _node: &'ast ErrorCode) {}
+}
+
+pub fn visit_program<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast Program) {
+    if let Some(state) = &node.state {
+        v.visit_state(state);
+    }
+    for ix in &node.ixs {
+        v.visit_ix(ix);
+    }
+}
+
+pub fn visit_state<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast State) {
+    if let Some((_, methods)) = &node.impl_block_and_methods {
+        for method in methods {
+            v.visit_state_ix(method);
+        }
+    }
+    for interface in node.interfaces.iter().flatten() {
+        for method in &interface.methods {
+            v.visit_state_ix(method);
+        }
+    }
+}
+
+pub fn visit_state_ix<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast StateIx) {
+    for arg in &node.args {
+        v.visit_ix_arg(arg);
+    }
+}
+
+pub fn visit_ix<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast Ix) {
+    for arg in &node.args {
+        v.visit_ix_arg(arg);
+    }
+}
+
+pub fn visit_accounts_struct<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast AccountsStruct) {
+    for field in &node.fields {
+        v.visit_account_field(field);
+    }
+    for constraint in &node.struct_constraints {
+        v.visit_struct_constraint(constraint);
+    }
+}
+
+pub fn visit_account_field<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast AccountField) {
+    match node {
+        AccountField::Field(field) => v.visit_field(field),
+        AccountField::CompositeField(field) => v.visit_composite_field(field),
+    }
+}
+
+pub fn visit_field<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast Field) {
+    for constraint in linearize(&node.constraints) {
+        v.visit_constraint(&constraint);
+    }
+}
+
+pub fn visit_composite_field<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast CompositeField) {
+    for constraint in linearize(&node.constraints) {
+        v.visit_constraint(&constraint);
+    }
+}
+
+pub fn visit_error<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, node: &'ast Error) {
+    for code in &node.codes {
+        v.visit_error_code(code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Collect(Vec<String>);
+
+    impl<'ast> Visit<'ast> for Collect {
+        fn visit_ix(&mut self, node: &'ast Ix) {
+            self.0.push(format!("ix {}", node.ident));
+            visit_ix(self, node);
+        }
+
+        fn visit_ix_arg(&mut self, node: &'ast IxArg) {
+            self.0.push(format!("arg {}", node.name));
+        }
+
+        fn visit_field(&mut self, node: &'ast Field) {
+            self.0.push(format!("field {}", node.ident));
+            visit_field(self, node);
+        }
+
+        fn visit_composite_field(&mut self, node: &'ast CompositeField) {
+            self.0.push(format!("composite {}", node.ident));
+        }
+
+        fn visit_constraint(&mut self, node: &Constraint) {
+            let kind = match node {
+                Constraint::Mut(_) => "mut",
+                Constraint::Signer(_) => "signer",
+                Constraint::HasOne(_) => "has_one",
+                _ => "other",
+            };
+            self.0.push(format!("constraint {}", kind));
+        }
+
+        fn visit_struct_constraint(&mut self, _node: &'ast ConstraintRaw) {
+            self.0.push("struct constraint".to_string());
+        }
+    }
+
+    #[test]
+    fn visits_programs() {
+        let program: Program = syn::parse_str(
+            "mod counter {
+                use super::*;
+                pub fn increment(ctx: Context<Increment>, by: u64) -> ProgramResult {
+                    Ok(())
+                }
+            }",
+        )
+        .unwrap();
+        let mut collect = Collect::default();
+        collect.visit_program(&program);
+        assert_eq!(collect.0, vec!["ix increment", "arg by"]);
+    }
+
+    #[test]
+    fn visits_accounts_structs() {
+        let accounts: AccountsStruct = syn::parse_str(
+            "#[accounts(constraint = counter.count < 10)]
+            pub struct Increment<'info> {
+                #[account(mut, has_one = authority)]
+                pub counter: Account<'info, Counter>,
+                #[account(signer)]
+                pub authority: AccountInfo<'info>,
+                pub nested: Nested<'info>,
+            }",
+        )
+        .unwrap();
+        let mut collect = Collect::default();
+        collect.visit_accounts_struct(&accounts);
+        assert_eq!(
+            collect.0,
+            vec![
+                "field counter",
+                "constraint mut",
+                "constraint has_one",
+                "field authority",
+                "constraint signer",
+                "composite nested",
+                "struct constraint",
+            ]
+        );
+    }
+}
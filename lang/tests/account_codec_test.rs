@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+use std::io::Write;
+
+mod common;
+
+#[account(codec = "bytemuck")]
+pub struct PodAccount {
+    pub authority: Pubkey,
+    pub count: u64,
+}
+
+// Fields of mixed widths, ordered so that `repr(C)` adds no padding.
+#[account(codec = "bytemuck")]
+pub struct MixedPodAccount {
+    pub amount: u64,
+    pub index: u32,
+    pub kind: u16,
+    pub flags: [u8; 2],
+}
+
+#[account(codec = "custom")]
+pub struct CustomAccount {
+    pub value: u32,
+}
+
+// Big endian, to differ from borsh.
+impl AccountCodec for CustomAccount {
+    fn encode<W: Write>(&self, writer: &mut W) -> std::result::Result<(), ProgramError> {
+        writer
+            .write_all(&self.value.to_be_bytes())
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    fn decode(buf: &mut &[u8]) -> std::result::Result<Self, ProgramError> {
+        if buf.len() < 4 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut value = [0u8; 4];
+        value.copy_from_slice(&buf[..4]);
+        *buf = &buf[4..];
+        Ok(CustomAccount {
+            value: u32::from_be_bytes(value),
+        })
+    }
+}
+
+#[test]
+fn bytemuck_codec_round_trip() {
+    let account = PodAccount {
+        authority: Pubkey::new_unique(),
+        count: 42,
+    };
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    assert_eq!(data.len(), 8 + 32 + 8);
+    assert_eq!(&data[..8], &PodAccount::discriminator());
+    assert_eq!(&data[40..], &42u64.to_le_bytes());
+
+    let decoded = PodAccount::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(decoded.authority, account.authority);
+    assert_eq!(decoded.count, 42);
+
+    assert!(PodAccount::try_deserialize(&mut &data[..20]).is_err());
+}
+
+#[test]
+fn bytemuck_codec_mixed_widths() {
+    let account = MixedPodAccount {
+        amount: u64::MAX - 1,
+        index: 7,
+        kind: 3,
+        flags: [1, 2],
+    };
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    assert_eq!(data.len(), 8 + 16);
+
+    let decoded = MixedPodAccount::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(decoded.amount, u64::MAX - 1);
+    assert_eq!(decoded.index, 7);
+    assert_eq!(decoded.kind, 3);
+    assert_eq!(decoded.flags, [1, 2]);
+}
+
+#[test]
+fn custom_codec_round_trip() {
+    let mut data = Vec::new();
+    CustomAccount { value: 7 }.try_serialize(&mut data).unwrap();
+    assert_eq!(&data[..8], &CustomAccount::discriminator());
+    assert_eq!(&data[8..], &[0, 0, 0, 7]);
+
+    let decoded = CustomAccount::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(decoded.value, 7);
+}
+
+#[cfg(feature = "compression")]
+mod compressed {
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+
+    #[account(compressed)]
+    pub struct Archive {
+        pub entries: Vec<u64>,
+    }
+
+    #[test]
+    fn compressed_round_trip() {
+        let account = Archive {
+            entries: vec![1; 512],
+        };
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        assert_eq!(&data[..8], &Archive::discriminator());
+        assert_eq!(&data[8..12], &(4 + 8 * 512u32).to_le_bytes());
+        assert!(data.len() < 4 + 8 * 512);
+
+        // Unused trailing space is ignored.
+        data.extend_from_slice(&[0; 64]);
+        let decoded = Archive::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.entries, account.entries);
+
+        assert!(Archive::try_deserialize(&mut &data[..20]).is_err());
+    }
+
+    #[test]
+    fn compressed_length_is_checked() {
+        let account = Archive {
+            entries: vec![1; 512],
+        };
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        let uncompressed_len = 4 + 8 * 512u32;
+
+        // Data inflating past the length in the header isn't decompressed.
+        data[8..12].copy_from_slice(&64u32.to_le_bytes());
+        assert_eq!(
+            Archive::try_deserialize(&mut data.as_slice()).err(),
+            Some(anchor_lang::__private::ErrorCode::AccountDidNotDeserialize.into())
+        );
+
+        // Nor is data inflating to less.
+        data[8..12].copy_from_slice(&(uncompressed_len + 1).to_le_bytes());
+        assert_eq!(
+            Archive::try_deserialize(&mut data.as_slice()).err(),
+            Some(anchor_lang::__private::ErrorCode::AccountDidNotDeserialize.into())
+        );
+
+        data[8..12].copy_from_slice(&uncompressed_len.to_le_bytes());
+        assert!(Archive::try_deserialize(&mut data.as_slice()).is_ok());
+    }
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[account]
+pub struct Counter {
+    pub count: u64,
+}
+
+#[derive(Accounts)]
+pub struct Read<'info> {
+    pub counter: Account<'info, Counter>,
+}
+
+#[test]
+fn modified_account_is_dirty() {
+    let mut data = vec![];
+    Counter { count: 1 }.try_serialize(&mut data).unwrap();
+    let mut counter = TestAccount::new(ID, data).writable().lamports(1);
+    let info = counter.info();
+
+    let accounts = [info];
+    let mut read = Read::try_accounts(&ID, &mut &accounts[..], &[]).unwrap();
+    assert!(!read.counter.is_dirty().unwrap());
+    assert!(read.exit(&ID).is_ok());
+
+    read.counter.count += 1;
+    assert!(read.counter.is_dirty().unwrap());
+    #[cfg(feature = "anchor-debug")]
+    assert_eq!(
+        read.exit(&ID),
+        Err(anchor_lang::__private::ErrorCode::ConstraintMut.into())
+    );
+}
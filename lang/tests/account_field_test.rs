@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[account]
+pub struct Pool {
+    pub authority: Pubkey,
+    #[transient]
+    pub cached: Option<u64>,
+    pub fees: [u16; 2],
+    pub name: String,
+    pub bump: u8,
+}
+
+#[account(zero_copy)]
+pub struct Book {
+    pub seq: u64,
+    pub market: Pubkey,
+}
+
+#[test]
+fn borsh_fields_at_fixed_offsets() {
+    let authority = Pubkey::new_unique();
+    let pool = Pool {
+        authority,
+        cached: Some(1),
+        fees: [3, 4],
+        name: "pool".to_string(),
+        bump: 5,
+    };
+    let mut data = vec![];
+    pool.try_serialize(&mut data).unwrap();
+
+    let field = account_field!(Pool, authority);
+    assert_eq!((field.offset(), field.size()), (8, 32));
+    assert_eq!(field.read(&data), Ok(authority));
+    let fees = account_field!(Pool, fees);
+    assert_eq!((fees.offset(), fees.end()), (40, 44));
+    assert_eq!(fees.read(&data), Ok([3, 4]));
+    assert!(fees.read(&data[..43]).is_err());
+}
+
+#[test]
+fn zero_copy_fields() {
+    let market = account_field!(Book, market);
+    assert_eq!((market.offset(), market.size()), (16, 32));
+    let mut data = vec![0; 48];
+    data[16..].copy_from_slice(ID.as_ref());
+    assert_eq!(market.read(&data), Ok(ID));
+}
+
+#[test]
+fn offsets_modules() {
+    assert_eq!(
+        (pool_offsets::AUTHORITY, pool_offsets::AUTHORITY_LEN),
+        (8, 32)
+    );
+    assert_eq!((pool_offsets::FEES, pool_offsets::FEES_LEN), (40, 4));
+    assert_eq!((book_offsets::SEQ, book_offsets::SEQ_LEN), (8, 8));
+    assert_eq!((book_offsets::MARKET, book_offsets::MARKET_LEN), (16, 32));
+    assert_eq!(account_field!(Book, market).offset(), book_offsets::MARKET);
+}
@@ -0,0 +1,227 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+use solana_program::instruction::Instruction;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::system_instruction::SystemInstruction;
+use solana_program::system_program;
+
+mod common;
+
+#[account(zero_copy)]
+pub struct Counter {
+    pub count: u64,
+}
+
+fn counter_data() -> Vec<u8> {
+    let mut data = Counter::discriminator().to_vec();
+    data.extend_from_slice(&7u64.to_le_bytes());
+    data
+}
+
+#[test]
+fn multiple_shared_loads() {
+    let mut account = TestAccount::new(ID, counter_data()).writable();
+    let info = account.info();
+    let loader = AccountLoader::<Counter>::try_from(&info).unwrap();
+
+    let first = loader.load().unwrap();
+    let second = loader.load().unwrap();
+    assert_eq!({ first.count }, { second.count });
+}
+
+#[test]
+fn conflicting_loads_return_errors() {
+    let mut account = TestAccount::new(ID, counter_data()).writable();
+    let info = account.info();
+    let loader = AccountLoader::<Counter>::try_from(&info).unwrap();
+
+    {
+        let _shared = loader.load().unwrap();
+        assert_eq!(
+            loader.load_mut().err(),
+            Some(anchor_lang::__private::ErrorCode::AccountAlreadyLoaded.into()),
+        );
+    }
+    {
+        let _exclusive = loader.load_mut().unwrap();
+        assert_eq!(
+            loader.load().err(),
+            Some(anchor_lang::__private::ErrorCode::AccountBorrowedMutably.into()),
+        );
+    }
+    assert!(loader.load_mut().is_ok());
+}
+
+#[account(zero_copy)]
+pub struct Vault {
+    pub count: u64,
+    pub authority: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct CheckAuthority<'info> {
+    #[account(has_one = authority)]
+    pub vault: AccountLoader<'info, Vault>,
+    pub authority: AccountInfo<'info>,
+}
+
+#[test]
+fn zero_copy_field_offsets() {
+    assert_eq!(Vault::__anchor_offset_count, 0);
+    assert_eq!(Vault::__anchor_offset_authority, 8);
+}
+
+#[test]
+fn zero_copy_has_one_reads_field_in_place() {
+    let mut authority = TestAccount::new(ID, vec![]);
+    let mut other = TestAccount::new(ID, vec![]);
+    let mut vault_data = Vault::discriminator().to_vec();
+    vault_data.extend_from_slice(&7u64.to_le_bytes());
+    vault_data.extend_from_slice(authority.key.as_ref());
+    let mut vault = TestAccount::new(ID, vault_data).writable();
+    let (vault, authority, other) = (vault.info(), authority.info(), other.info());
+
+    let accounts = [vault.clone(), authority];
+    assert!(CheckAuthority::try_accounts(&ID, &mut &accounts[..], &[]).is_ok());
+
+    let accounts = [vault, other];
+    assert_eq!(
+        CheckAuthority::try_accounts(&ID, &mut &accounts[..], &[]).err(),
+        Some(anchor_lang::__private::ErrorCode::ConstraintHasOne.into()),
+    );
+}
+
+#[derive(Accounts)]
+pub struct InitCounter<'info> {
+    #[account(init_if_needed, payer = payer, space = 16)]
+    pub counter: AccountLoader<'info, Counter>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Provides the rent sysvar and the system program's `create_account` off
+// chain.
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let info = |i: usize| {
+            account_infos
+                .iter()
+                .find(|info| info.key == &instruction.accounts[i].pubkey)
+                .unwrap()
+        };
+        match bincode::deserialize(&instruction.data) {
+            Ok(SystemInstruction::CreateAccount {
+                lamports,
+                space,
+                owner,
+            }) => {
+                let (from, to) = (info(0), info(1));
+                **from.try_borrow_mut_lamports()? -= lamports;
+                **to.try_borrow_mut_lamports()? += lamports;
+                *to.try_borrow_mut_data()? = Box::leak(vec![0; space as usize].into_boxed_slice());
+                // Assigns the account in place, as solana-program-test does.
+                to.assign(&owner);
+                Ok(())
+            }
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+// Runs `InitCounter` against a counter account owned by `owner`, holding
+// `lamports` and `data`, returning its owner and count afterwards.
+fn init_counter(
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+) -> std::result::Result<(Pubkey, u64), ProgramError> {
+    let mut accounts = [
+        TestAccount::new(owner, data)
+            .signer()
+            .writable()
+            .lamports(lamports),
+        TestAccount::new(system_program::ID, vec![])
+            .signer()
+            .writable()
+            .lamports(1_000_000_000),
+        TestAccount::native_program(system_program::ID),
+    ];
+    let accounts = infos(&mut accounts);
+    let init = InitCounter::try_accounts(&ID, &mut &accounts[..], &[])?;
+    let count = init.counter.load_init_if_needed()?.count;
+    init.counter.exit(&ID)?;
+    Ok((*accounts[0].owner, count))
+}
+
+#[test]
+fn init_if_needed_checks_existing_discriminator() {
+    set_syscall_stubs(Box::new(Stubs));
+    let mut data = counter_data();
+    assert_eq!(init_counter(ID, 1_000_000_000, data.clone()), Ok((ID, 7)));
+
+    data[0] ^= 1;
+    assert_eq!(
+        init_counter(ID, 1_000_000_000, data),
+        Err(anchor_lang::__private::ErrorCode::AccountDiscriminatorMismatch.into()),
+    );
+}
+
+#[test]
+fn init_if_needed_creates_missing_account() {
+    set_syscall_stubs(Box::new(Stubs));
+    assert_eq!(init_counter(system_program::ID, 0, vec![]), Ok((ID, 0)));
+}
+
+#[test]
+fn load_init_if_needed_initializes_zeroed_account() {
+    let mut account = TestAccount::new(ID, vec![0; 16]).writable();
+    let info = account.info();
+    let loader = AccountLoader::<Counter>::try_from_unchecked(&ID, &info).unwrap();
+    loader.load_init_if_needed().unwrap().count = 3;
+    loader.exit(&ID).unwrap();
+    assert_eq!({ loader.load_init_if_needed().unwrap().count }, 3);
+    assert!(loader.load_init().is_err());
+}
+
+// Too large to derive `Default`, so its space is inferred from its size.
+#[account(zero_copy)]
+pub struct Book {
+    pub orders: [u64; 64],
+}
+
+#[derive(Accounts)]
+pub struct InitBook<'info> {
+    #[account(init, payer = payer)]
+    pub book: AccountLoader<'info, Book>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[test]
+fn load_init_checks_size() {
+    assert_eq!(Book::SIZE, 512);
+    assert_eq!(Counter::SIZE, 8);
+
+    let mut account = TestAccount::new(ID, vec![0; 8 + 256]).writable();
+    let info = account.info();
+    let loader = AccountLoader::<Book>::try_from_unchecked(&ID, &info).unwrap();
+    assert_eq!(
+        loader.load_init().err(),
+        Some(anchor_lang::__private::ErrorCode::ConstraintSpace.into()),
+    );
+}
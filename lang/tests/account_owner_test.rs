@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+pub mod pool_program {
+    anchor_lang::declare_id!("22Y43yTVxuUkoRKdm9thyRhQ3SdgQS7c7kB6UNCiaczD");
+}
+
+// As if declared in a library crate shared by several programs.
+pub mod shared {
+    use anchor_lang::prelude::*;
+
+    #[account(owner_crate = crate::pool_program)]
+    pub struct Pool {
+        pub authority: Pubkey,
+    }
+
+    #[account(zero_copy, owner_crate = crate::pool_program)]
+    pub struct PoolHistory {
+        pub entries: [u64; 4],
+    }
+}
+
+#[account]
+pub struct Local {
+    pub authority: Pubkey,
+}
+
+#[test]
+fn owner_crate() {
+    assert_eq!(<shared::Pool as Owner>::owner(), pool_program::ID);
+    assert_eq!(<shared::PoolHistory as Owner>::owner(), pool_program::ID);
+    assert_eq!(<Local as Owner>::owner(), ID);
+}
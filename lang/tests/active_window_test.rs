@@ -0,0 +1,79 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::clock::MockClock;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+
+mod common;
+
+#[account]
+pub struct Launch {
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    #[account(active_window = launch.start..launch.end)]
+    pub launch: Account<'info, Launch>,
+}
+
+#[account(zero_copy)]
+pub struct ZeroCopyLaunch {
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Accounts)]
+pub struct ZeroCopyBuy<'info> {
+    #[account(active_window = launch.start..launch.end)]
+    pub launch: AccountLoader<'info, ZeroCopyLaunch>,
+}
+
+fn try_buy() -> anchor_lang::Result<()> {
+    let mut data = vec![];
+    Launch {
+        start: 100,
+        end: 200,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    let mut launch = TestAccount::new(ID, data).lamports(1);
+    let accounts = [launch.info()];
+    Buy::try_accounts(&ID, &mut &accounts[..], &[]).map(|_| ())
+}
+
+fn try_zero_copy_buy() -> anchor_lang::Result<()> {
+    let mut data = ZeroCopyLaunch::discriminator().to_vec();
+    data.extend_from_slice(&100i64.to_le_bytes());
+    data.extend_from_slice(&200i64.to_le_bytes());
+    let mut launch = TestAccount::new(ID, data).lamports(1);
+    let accounts = [launch.info()];
+    ZeroCopyBuy::try_accounts(&ID, &mut &accounts[..], &[]).map(|_| ())
+}
+
+#[test]
+fn instructions_are_gated_by_the_active_window() {
+    let clock = MockClock::new(Clock::default());
+    clock.install();
+    for try_buy in [try_buy, try_zero_copy_buy] {
+        clock.set(Clock {
+            unix_timestamp: 99,
+            ..Clock::default()
+        });
+        assert_eq!(
+            try_buy(),
+            Err(ErrorCode::ConstraintActiveWindowNotStarted.into())
+        );
+        clock.advance(1);
+        assert_eq!(try_buy(), Ok(()));
+        clock.advance(99);
+        assert_eq!(try_buy(), Ok(()));
+        clock.advance(1);
+        assert_eq!(
+            try_buy(),
+            Err(ErrorCode::ConstraintActiveWindowEnded.into())
+        );
+    }
+    anchor_lang::clock::clear_provider();
+}
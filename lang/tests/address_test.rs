@@ -0,0 +1,81 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+
+mod common;
+
+#[account]
+pub struct Config {
+    pub oracle: Pubkey,
+}
+
+#[account(zero_copy)]
+pub struct ZeroCopyConfig {
+    pub oracle: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct ReadOracle<'info> {
+    pub config: Account<'info, Config>,
+    #[account(address = config.oracle)]
+    pub oracle: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadZeroCopyOracle<'info> {
+    // The address can be read from a field declared after it.
+    #[account(address = config.oracle @ ErrorCode::ConstraintOwner)]
+    pub oracle: UncheckedAccount<'info>,
+    pub config: AccountLoader<'info, ZeroCopyConfig>,
+}
+
+fn config_data(oracle: Pubkey) -> Vec<u8> {
+    let mut data = vec![];
+    Config { oracle }.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn zero_copy_config_data(oracle: Pubkey) -> Vec<u8> {
+    let mut data = ZeroCopyConfig::discriminator().to_vec();
+    data.extend_from_slice(oracle.as_ref());
+    data
+}
+
+#[test]
+fn address_is_read_from_the_config() {
+    let oracle = TestAccount::new(ID, vec![]).lamports(1);
+    let config = TestAccount::new(ID, config_data(oracle.key)).lamports(1);
+    let mut accounts = [config, oracle];
+    let accounts = infos(&mut accounts);
+    assert!(ReadOracle::try_accounts(&ID, &mut &accounts[..], &[]).is_ok());
+
+    // Rotating the oracle rejects the old one.
+    let mut rotated = vec![];
+    Config {
+        oracle: Pubkey::new_unique(),
+    }
+    .try_serialize(&mut rotated)
+    .unwrap();
+    accounts[0].try_borrow_mut_data().unwrap()[..].copy_from_slice(&rotated);
+    assert_eq!(
+        ReadOracle::try_accounts(&ID, &mut &accounts[..], &[]).err(),
+        Some(ErrorCode::ConstraintAddress.into())
+    );
+}
+
+#[test]
+fn address_is_read_from_the_zero_copy_config() {
+    let oracle = TestAccount::new(ID, vec![]).lamports(1);
+    let config = TestAccount::new(ID, zero_copy_config_data(oracle.key)).lamports(1);
+    let mut accounts = [oracle, config];
+    let accounts = infos(&mut accounts);
+    assert!(ReadZeroCopyOracle::try_accounts(&ID, &mut &accounts[..], &[]).is_ok());
+
+    let rotated = zero_copy_config_data(Pubkey::new_unique());
+    accounts[1].try_borrow_mut_data().unwrap()[..].copy_from_slice(&rotated);
+    assert_eq!(
+        ReadZeroCopyOracle::try_accounts(&ID, &mut &accounts[..], &[]).err(),
+        Some(ErrorCode::ConstraintOwner.into())
+    );
+}
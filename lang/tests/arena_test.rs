@@ -0,0 +1,121 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::arena::{Arena, ArenaString, ArenaVec};
+use anchor_lang::prelude::*;
+use anchor_lang::{EventBuffer, InstructionData};
+use common::*;
+use std::fmt::Write;
+
+mod common;
+
+#[program(arena = 64)]
+pub mod arena {
+    use super::*;
+
+    pub fn greet(ctx: Context<Empty>, name: String) -> ProgramResult {
+        let mut greeting = ArenaString::new_in(ctx.arena());
+        write!(greeting, "Hello, {}!", name).map_err(|_| ErrorCode::ArenaExhausted)?;
+        msg!(&greeting);
+        ctx.events().push(Greeted {
+            len: greeting.len() as u64,
+        })?;
+        Ok(())
+    }
+
+    #[batch]
+    pub fn tally(ctx: Context<Empty>, _chunk: &mut Empty, args: TallyArgs) -> ProgramResult {
+        // The items share the instruction's arena.
+        let mut counts = ArenaVec::new_in(ctx.arena());
+        for count in 0..args.len {
+            counts.push(count)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TallyArgs {
+    pub len: u64,
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[event]
+pub struct Greeted {
+    pub len: u64,
+}
+
+#[test]
+fn vectors_are_allocated_from_the_arena() {
+    let mut arena = Arena::new(64);
+    assert!(!arena.is_reserved());
+    {
+        let mut vec = ArenaVec::new_in(Some(&arena));
+        for i in 0..8u64 {
+            vec.push(i).unwrap();
+        }
+        // Growing the last allocation doesn't move it.
+        assert_eq!(arena.used(), 64);
+        assert_eq!(&vec[..], &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(vec.push(8), Err(ErrorCode::ArenaExhausted.into()));
+        assert_eq!(vec.pop(), Some(7));
+        vec.clear();
+        assert!(vec.is_empty());
+    }
+    arena.reset();
+    assert_eq!(arena.used(), 0);
+
+    let mut s = ArenaString::new_in(Some(&arena));
+    write!(s, "a-{}", 1).unwrap();
+    s.push('é').unwrap();
+    assert_eq!(s.as_str(), "a-1é");
+
+    // Without an arena, they're allocated on the heap.
+    let mut vec = ArenaVec::new_in(None);
+    vec.try_extend_from_slice(&vec!["a".to_string(); 100])
+        .unwrap();
+    assert_eq!(vec.len(), 100);
+}
+
+#[test]
+fn events_are_buffered_in_the_arena() {
+    let arena = Arena::new(64);
+    let buffer = EventBuffer::new_in(Some(&arena));
+    assert!(!arena.is_reserved());
+    buffer.push(&Greeted { len: 1 }).unwrap();
+    assert!(arena.is_reserved());
+    // The batch discriminator, then each event's length, discriminator and
+    // data.
+    buffer.push(&Greeted { len: 2 }).unwrap();
+    assert_eq!(
+        buffer.push(&Greeted { len: 3 }),
+        Err(ErrorCode::ArenaExhausted.into())
+    );
+}
+
+#[test]
+fn instructions_get_an_arena() {
+    let data = instruction::Greet {
+        name: "arena".to_string(),
+    }
+    .data();
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+    let data = instruction::Greet {
+        name: "a".repeat(64),
+    }
+    .data();
+    assert_eq!(
+        entry(&ID, &[], &data),
+        Err(ErrorCode::ArenaExhausted.into())
+    );
+
+    // The items of a batch share the arena.
+    let tally = |len| instruction::Tally {
+        items: vec![TallyArgs { len }, TallyArgs { len }],
+    };
+    assert_eq!(entry(&ID, &[], &tally(4).data()), Ok(()));
+    assert_eq!(
+        entry(&ID, &[], &tally(5).data()),
+        Err(ErrorCode::ArenaExhausted.into())
+    );
+}
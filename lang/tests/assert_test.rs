@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[error]
+pub enum ErrorCode {
+    #[msg("Wrong authority")]
+    WrongAuthority,
+}
+
+// Validates the remaining accounts by hand: an authority with a given key
+// followed by accounts owned by the program.
+fn validate(authority: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let (first, rest) = accounts.split_first().unwrap();
+    assert_keys_eq!(first.key, authority);
+    assert_keys_eq!(*first.key, *authority, ErrorCode::WrongAuthority);
+    for account in rest {
+        assert_owner!(account, ID);
+    }
+    Ok(())
+}
+
+#[test]
+fn assertions_fail_like_constraints() {
+    let mut accounts = [
+        TestAccount::new(solana_program::system_program::ID, vec![]).lamports(1),
+        TestAccount::new(ID, vec![]).lamports(1),
+    ];
+    let (authority, owned) = (accounts[0].key, accounts[1].key);
+    let accounts = infos(&mut accounts);
+
+    assert_eq!(validate(&authority, &accounts), Ok(()));
+    assert_eq!(
+        validate(&owned, &accounts),
+        Err(anchor_lang::__private::ErrorCode::ConstraintAddress.into())
+    );
+    assert_eq!(
+        validate(&authority, &[accounts[0].clone(), accounts[0].clone()]),
+        Err(anchor_lang::__private::ErrorCode::ConstraintOwner.into())
+    );
+}
+
+#[test]
+fn assertions_fail_with_custom_errors() {
+    let result: ProgramResult = (|| {
+        assert_keys_eq!(Pubkey::new_unique(), ID, ErrorCode::WrongAuthority);
+        Ok(())
+    })();
+    assert_eq!(result, Err(ErrorCode::WrongAuthority.into()));
+}
@@ -0,0 +1,63 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+const SYSTEM_PROGRAM: Pubkey = solana_program::system_program::ID;
+
+#[derive(Accounts)]
+pub struct Assign<'info> {
+    #[account(mut, assign_to = program_id)]
+    pub account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AssignFromSystem<'info> {
+    #[account(mut, owner = SYSTEM_PROGRAM, assign_to = ID)]
+    pub account: UncheckedAccount<'info>,
+}
+
+// Gives an account with the given owner to `run`.
+fn with_account(
+    owner: Pubkey,
+    run: impl FnOnce(&[AccountInfo]) -> anchor_lang::Result<()>,
+) -> anchor_lang::Result<()> {
+    let mut account = TestAccount::new(owner, vec![]).writable().lamports(1);
+    run(&[account.info()])
+}
+
+// Validates the accounts and exits right away, as if the instruction had
+// left the account with its current owner.
+fn assign(owner: Pubkey) -> anchor_lang::Result<()> {
+    with_account(owner, |accounts| {
+        Assign::try_accounts(&ID, &mut &accounts[..], &[])?.exit(&ID)
+    })
+}
+
+fn assign_from_system(owner: Pubkey) -> anchor_lang::Result<()> {
+    with_account(owner, |accounts| {
+        AssignFromSystem::try_accounts(&ID, &mut &accounts[..], &[])?.exit(&ID)
+    })
+}
+
+#[test]
+fn owner_is_checked_on_exit() {
+    assert_eq!(assign(ID), Ok(()));
+    assert_eq!(
+        assign(SYSTEM_PROGRAM),
+        Err(ErrorCode::ConstraintAssignTo.into())
+    );
+}
+
+#[test]
+fn owner_is_checked_before_and_after() {
+    assert_eq!(
+        assign_from_system(ID),
+        Err(ErrorCode::ConstraintOwner.into())
+    );
+    assert_eq!(
+        assign_from_system(SYSTEM_PROGRAM),
+        Err(ErrorCode::ConstraintAssignTo.into())
+    );
+}
@@ -0,0 +1,92 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod batch {
+    use super::*;
+
+    #[batch]
+    pub fn settle(
+        ctx: Context<Settle>,
+        chunk: &mut SettlePosition,
+        args: SettleArgs,
+    ) -> ProgramResult {
+        if ctx.accounts.authority.key() != chunk.position.authority {
+            return Err(ProgramError::InvalidArgument);
+        }
+        chunk.position.settled += args.amount;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Position {
+    pub authority: Pubkey,
+    pub settled: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SettleArgs {
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettlePosition<'info> {
+    #[account(mut)]
+    pub position: Account<'info, Position>,
+}
+
+fn position_data(authority: Pubkey, settled: u64) -> Vec<u8> {
+    let mut data = vec![];
+    Position { authority, settled }
+        .try_serialize(&mut data)
+        .unwrap();
+    data
+}
+
+fn settled(data: &[u8]) -> u64 {
+    Position::try_deserialize(&mut &data[..]).unwrap().settled
+}
+
+fn settle_data(amounts: &[u64]) -> Vec<u8> {
+    instruction::Settle {
+        items: amounts
+            .iter()
+            .map(|amount| SettleArgs { amount: *amount })
+            .collect(),
+    }
+    .data()
+}
+
+#[test]
+fn handles_each_item_with_its_chunk() {
+    let authority = TestAccount::new(ID, vec![]).signer().lamports(1);
+    let authority_key = authority.key;
+    let position = |amount| {
+        TestAccount::new(ID, position_data(authority_key, amount))
+            .writable()
+            .lamports(1)
+    };
+    let mut accounts = [authority, position(1), position(2)];
+    let accounts = infos(&mut accounts);
+
+    assert_eq!(entry(&ID, &accounts, &settle_data(&[10, 20])), Ok(()));
+    assert_eq!(settled(&accounts[1].try_borrow_data().unwrap()), 11);
+    assert_eq!(settled(&accounts[2].try_borrow_data().unwrap()), 22);
+
+    // Every item needs its chunk of accounts.
+    assert_eq!(
+        entry(&ID, &accounts, &settle_data(&[1, 1, 1])),
+        Err(ErrorCode::AccountNotEnoughKeys.into())
+    );
+    assert_eq!(settled(&accounts[1].try_borrow_data().unwrap()), 11);
+}
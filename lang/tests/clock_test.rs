@@ -0,0 +1,70 @@
+use anchor_lang::clock::MockClock;
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+use solana_program::epoch_schedule::EpochSchedule;
+
+mod common;
+
+const VESTING_END: i64 = 1_000;
+
+#[program]
+pub mod clock {
+    use super::*;
+
+    pub fn claim(ctx: Context<Empty>) -> ProgramResult {
+        if ctx.clock()?.unix_timestamp < VESTING_END {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    #[deprecated_instruction(sunset_slot = 100)]
+    pub fn legacy_claim(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn handlers_read_the_mock_clock() {
+    let clock = MockClock::new(Clock {
+        unix_timestamp: VESTING_END - 10,
+        ..Clock::default()
+    });
+    clock.install();
+    assert_eq!(
+        entry(&ID, &[], &instruction::Claim.data()),
+        Err(ProgramError::InvalidArgument)
+    );
+    clock.advance(10);
+    assert_eq!(entry(&ID, &[], &instruction::Claim.data()), Ok(()));
+    anchor_lang::clock::clear_provider();
+}
+
+#[test]
+fn constraints_read_the_mock_clock() {
+    let clock = MockClock::default();
+    clock.install();
+    assert_eq!(entry(&ID, &[], &instruction::LegacyClaim.data()), Ok(()));
+    clock.warp_to_slot(100);
+    assert_eq!(
+        entry(&ID, &[], &instruction::LegacyClaim.data()),
+        Err(anchor_lang::__private::ErrorCode::InstructionSunset.into())
+    );
+    anchor_lang::clock::clear_provider();
+}
+
+#[test]
+fn mock_clock_warps_to_epochs() {
+    let clock = MockClock::default();
+    clock.install();
+    let schedule = EpochSchedule::without_warmup();
+    clock.warp_to_epoch(3, &schedule);
+    let now = anchor_lang::clock::get().unwrap();
+    assert_eq!(now.epoch, 3);
+    assert_eq!(now.slot, schedule.get_first_slot_in_epoch(3));
+    anchor_lang::clock::clear_provider();
+}
@@ -0,0 +1,102 @@
+//! Fixtures shared by the tests, declaring the test program's ID and building
+//! the accounts passed to it, e.g.
+//!
+//! ```ignore
+//! mod common;
+//! use common::*;
+//!
+//! let mut payer = TestAccount::new(system_program::ID, vec![]).signer().writable();
+//! let mut counter = TestAccount::new(ID, counter_data()).writable();
+//! let result = entry(&ID, &[payer.info(), counter.info()], &data);
+//! ```
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use solana_program::clock::Epoch;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+/// An account owned by the test, lent to programs as an [`AccountInfo`].
+#[derive(Clone, Debug)]
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    /// A read-only account at a unique key, owned by `owner` and holding
+    /// `data`, without lamports.
+    pub fn new(owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key: Pubkey::new_unique(),
+            owner,
+            lamports: 0,
+            data,
+            is_signer: false,
+            is_writable: false,
+            executable: false,
+        }
+    }
+
+    /// The executable account of the program `program_id`, owned by
+    /// `loader`.
+    pub fn program(program_id: Pubkey, loader: Pubkey) -> Self {
+        Self::new(loader, vec![]).at(program_id).executable()
+    }
+
+    /// The account of the native program `program_id`, e.g. the system
+    /// program.
+    pub fn native_program(program_id: Pubkey) -> Self {
+        Self::program(program_id, program_id)
+    }
+
+    pub fn at(mut self, key: Pubkey) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn lamports(mut self, lamports: u64) -> Self {
+        self.lamports = lamports;
+        self
+    }
+
+    pub fn signer(mut self) -> Self {
+        self.is_signer = true;
+        self
+    }
+
+    pub fn writable(mut self) -> Self {
+        self.is_writable = true;
+        self
+    }
+
+    pub fn executable(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    /// Lends the account, borrowing its lamports and data.
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            Epoch::default(),
+        )
+    }
+}
+
+/// Lends all `accounts`, in order.
+pub fn infos(accounts: &mut [TestAccount]) -> Vec<AccountInfo<'_>> {
+    accounts.iter_mut().map(TestAccount::info).collect()
+}
@@ -0,0 +1,163 @@
+use anchor_lang::compat::{self, GetSysvar};
+use anchor_lang::prelude::*;
+use common::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::sysvar;
+#[cfg(feature = "solana-1-9")]
+use std::sync::Mutex;
+use std::sync::Once;
+
+mod common;
+
+const SLOT: u64 = 42;
+
+#[cfg(feature = "solana-1-9")]
+static RETURN_DATA: Mutex<Option<(Pubkey, Vec<u8>)>> = Mutex::new(None);
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT,
+            ..Clock::default()
+        };
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Clock, clock) };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    #[cfg(feature = "solana-1-9")]
+    fn sol_set_return_data(&self, data: &[u8]) {
+        *RETURN_DATA.lock().unwrap() = Some((ID, data.to_vec()));
+    }
+
+    #[cfg(feature = "solana-1-9")]
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RETURN_DATA.lock().unwrap().clone()
+    }
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(Stubs));
+    });
+}
+
+// Serializes the instructions sysvar account data, as done by the runtime.
+fn instructions_sysvar_data(ixs: &[Instruction], current_index: u16) -> Vec<u8> {
+    let mut data = (ixs.len() as u16).to_le_bytes().to_vec();
+    data.resize(2 + 2 * ixs.len(), 0);
+    for (i, ix) in ixs.iter().enumerate() {
+        let offset = data.len() as u16;
+        data[2 + 2 * i..4 + 2 * i].copy_from_slice(&offset.to_le_bytes());
+        data.extend_from_slice(&(ix.accounts.len() as u16).to_le_bytes());
+        for meta in &ix.accounts {
+            data.push(meta.is_signer as u8 | (meta.is_writable as u8) << 1);
+            data.extend_from_slice(meta.pubkey.as_ref());
+        }
+        data.extend_from_slice(ix.program_id.as_ref());
+        data.extend_from_slice(&(ix.data.len() as u16).to_le_bytes());
+        data.extend_from_slice(&ix.data);
+    }
+    data.extend_from_slice(&current_index.to_le_bytes());
+    data
+}
+
+fn transaction() -> Vec<Instruction> {
+    vec![
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(Pubkey::new_unique(), true)],
+        ),
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[4],
+            vec![AccountMeta::new_readonly(Pubkey::new_unique(), false)],
+        ),
+    ]
+}
+
+fn with_instructions_sysvar<R>(
+    key: Pubkey,
+    ixs: &[Instruction],
+    current_index: u16,
+    f: impl FnOnce(&AccountInfo) -> R,
+) -> R {
+    let mut account =
+        TestAccount::new(sysvar::ID, instructions_sysvar_data(ixs, current_index)).at(key);
+    let info = account.info();
+    f(&info)
+}
+
+#[test]
+fn gets_sysvars_through_the_syscall() {
+    install_stubs();
+    assert_eq!(Clock::get_sysvar().unwrap().slot, SLOT);
+}
+
+#[test]
+fn loads_instructions() {
+    let ixs = transaction();
+    with_instructions_sysvar(sysvar::instructions::ID, &ixs, 1, |info| {
+        assert_eq!(compat::load_current_index(info), Ok(1));
+        assert_eq!(compat::load_instruction_at(0, info), Ok(ixs[0].clone()));
+        assert_eq!(compat::load_instruction_at(1, info), Ok(ixs[1].clone()));
+        assert_eq!(
+            compat::load_instruction_at(2, info),
+            Err(ProgramError::InvalidArgument)
+        );
+    });
+}
+
+#[test]
+fn gets_instructions_relative_to_the_current_one() {
+    let ixs = transaction();
+    with_instructions_sysvar(sysvar::instructions::ID, &ixs, 1, |info| {
+        assert_eq!(
+            compat::get_instruction_relative(0, info),
+            Ok(ixs[1].clone())
+        );
+        assert_eq!(
+            compat::get_instruction_relative(-1, info),
+            Ok(ixs[0].clone())
+        );
+        assert_eq!(
+            compat::get_instruction_relative(-2, info),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            compat::get_instruction_relative(1, info),
+            Err(ProgramError::InvalidArgument)
+        );
+    });
+}
+
+#[test]
+fn rejects_other_accounts_as_the_instructions_sysvar() {
+    let ixs = transaction();
+    with_instructions_sysvar(Pubkey::new_unique(), &ixs, 0, |info| {
+        assert_eq!(
+            compat::load_current_index(info),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+        assert_eq!(
+            compat::load_instruction_at(0, info),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+        assert_eq!(
+            compat::get_instruction_relative(0, info),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+    });
+}
+
+#[cfg(feature = "solana-1-9")]
+#[test]
+fn sets_and_gets_return_data() {
+    install_stubs();
+    compat::set_return_data(&[7, 8]);
+    assert_eq!(compat::get_return_data(), Some((ID, vec![7, 8])));
+}
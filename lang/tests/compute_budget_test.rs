@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[derive(Accounts)]
+pub struct Noop {}
+
+fn with_program<R>(f: impl FnOnce(AccountInfo) -> R) -> R {
+    let mut program = TestAccount::program(Pubkey::new_unique(), solana_program::bpf_loader::ID);
+    let program = program.info();
+    f(program)
+}
+
+#[test]
+fn no_check_requested() {
+    with_program(|program| {
+        let ctx = CpiContext::new(program, Noop {});
+        assert!(ctx.check_compute_budget().is_ok());
+    });
+}
+
+#[cfg(feature = "remaining-compute-units")]
+#[test]
+fn check_fails_when_remaining_units_are_unknown() {
+    with_program(|program| {
+        let ctx = CpiContext::new(program, Noop {}).with_compute_budget_check(1);
+        assert_eq!(
+            ctx.check_compute_budget().unwrap_err(),
+            anchor_lang::error::ErrorCode::InsufficientComputeUnits.into()
+        );
+    });
+}
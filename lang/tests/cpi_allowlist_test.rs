@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+const TOKEN_PROGRAM: Pubkey = Pubkey::new_from_array([1; 32]);
+const ALLOWLIST_SEED: &[u8] = b"allowlist";
+
+#[program(cpi_allowlist(TOKEN_PROGRAM), cpi_allowlist_hook = allowlist_hook)]
+pub mod cpi_allowlist {
+    use super::*;
+
+    pub fn run(_ctx: Context<Run>) -> ProgramResult {
+        Ok(())
+    }
+
+    pub fn read(ctx: Context<Read>) -> ProgramResult {
+        msg!("{}", ctx.accounts.program.key);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Run {}
+
+#[derive(Accounts)]
+pub struct Read<'info> {
+    pub program: UncheckedAccount<'info>,
+}
+
+#[account]
+pub struct Allowlist {
+    pub programs: Vec<Pubkey>,
+}
+
+// Accepts the programs listed in the allowlist account, if given.
+fn allowlist_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    program: &AccountInfo,
+) -> ProgramResult {
+    let address = Pubkey::find_program_address(&[ALLOWLIST_SEED], program_id).0;
+    let allowlist = accounts
+        .iter()
+        .find(|account| account.key == &address)
+        .ok_or(anchor_lang::__private::ErrorCode::CpiProgramNotAllowed)?;
+    let allowlist: Account<Allowlist> = Account::try_from(allowlist)?;
+    if !allowlist.programs.contains(program.key) {
+        return Err(anchor_lang::__private::ErrorCode::CpiProgramNotAllowed.into());
+    }
+    Ok(())
+}
+
+// Runs the instruction with an executable account at each of `programs` and,
+// if given, an allowlist account listing `allowed`.
+fn run(programs: &[Pubkey], allowed: Option<Vec<Pubkey>>) -> ProgramResult {
+    execute(&instruction::Run.data(), programs, allowed)
+}
+
+fn execute(ix_data: &[u8], programs: &[Pubkey], allowed: Option<Vec<Pubkey>>) -> ProgramResult {
+    let mut accounts: Vec<TestAccount> = programs
+        .iter()
+        .map(|key| TestAccount::program(*key, solana_program::bpf_loader::ID).lamports(1))
+        .collect();
+    if let Some(programs) = allowed {
+        let mut data = vec![];
+        Allowlist { programs }.try_serialize(&mut data).unwrap();
+        let allowlist_key = Pubkey::find_program_address(&[ALLOWLIST_SEED], &ID).0;
+        accounts.push(TestAccount::new(ID, data).at(allowlist_key).lamports(1));
+    }
+    let accounts = infos(&mut accounts);
+    entry(&ID, &accounts, ix_data)
+}
+
+#[test]
+fn allowed_programs_are_accepted() {
+    assert_eq!(run(&[], None), Ok(()));
+    assert_eq!(
+        run(
+            &[TOKEN_PROGRAM, solana_program::system_program::ID, ID],
+            None
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn other_programs_are_refused() {
+    assert_eq!(
+        run(&[TOKEN_PROGRAM, Pubkey::new_unique()], None),
+        Err(anchor_lang::__private::ErrorCode::CpiProgramNotAllowed.into())
+    );
+}
+
+#[test]
+fn hook_accepts_programs_at_runtime() {
+    let program = Pubkey::new_unique();
+    assert_eq!(run(&[program], Some(vec![program])), Ok(()));
+    assert_eq!(
+        run(&[program], Some(vec![Pubkey::new_unique()])),
+        Err(anchor_lang::__private::ErrorCode::CpiProgramNotAllowed.into())
+    );
+}
+
+#[test]
+fn programs_only_read_are_refused() {
+    // The handler never invokes the program, but the check runs before it.
+    let program = Pubkey::new_unique();
+    let data = instruction::Read.data();
+    assert_eq!(
+        execute(&data, &[program], None),
+        Err(anchor_lang::__private::ErrorCode::CpiProgramNotAllowed.into())
+    );
+    assert_eq!(execute(&data, &[program], Some(vec![program])), Ok(()));
+    assert_eq!(execute(&data, &[TOKEN_PROGRAM], None), Ok(()));
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use common::*;
+use solana_program::instruction::Instruction;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::system_instruction;
+use std::cell::RefCell;
+
+mod common;
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Pubkey>> = const { RefCell::new(Vec::new()) };
+    static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.program_id));
+        Ok(())
+    }
+}
+
+#[test]
+fn invokes_the_instruction() {
+    set_syscall_stubs(Box::new(Stubs));
+    let system_program = solana_program::system_program::ID;
+    let mut accounts = [
+        TestAccount::new(system_program, vec![])
+            .signer()
+            .writable()
+            .lamports(10),
+        TestAccount::new(system_program, vec![]).writable(),
+    ];
+    let (from_key, to_key) = (accounts[0].key, accounts[1].key);
+    let accounts = infos(&mut accounts);
+    let ix = system_instruction::transfer(&from_key, &to_key, 1);
+
+    assert_eq!(
+        anchor_lang::cpi_trace::invoke("transfer", &ix, &accounts),
+        Ok(())
+    );
+    assert_eq!(INVOKED.with(|invoked| invoked.take()), vec![system_program]);
+
+    let logs = LOGS.with(|logs| logs.take());
+    let traced = logs
+        .iter()
+        .any(|log| log == &format!("CPI {} transfer accounts=2", system_program));
+    assert_eq!(traced, cfg!(feature = "cpi-trace"));
+    if cfg!(feature = "cpi-trace") {
+        assert!(logs
+            .iter()
+            .any(|log| log == &format!("CPI {} transfer ok", system_program)));
+    }
+}
@@ -0,0 +1,102 @@
+use anchor_lang::idl::IdlAccount;
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+// SHA256 of an empty IDL.
+declare_dependency!(
+    swap,
+    "22Y43yTVxuUkoRKdm9thyRhQ3SdgQS7c7kB6UNCiaczD",
+    idl_hash = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+);
+
+declare_dependency!(lending, "6ZS9Ct2uC5Gg4TyuHSJeXVSQ7gKUXAoZoTuKVUnEXQAR");
+
+#[test]
+fn dependency_ids() {
+    assert_eq!(
+        swap::ID.to_string(),
+        "22Y43yTVxuUkoRKdm9thyRhQ3SdgQS7c7kB6UNCiaczD"
+    );
+    assert_eq!(swap::DEPENDENCY.program_id, swap::id());
+    assert_eq!(swap::DEPENDENCY.name, "swap");
+    assert_eq!(lending::DEPENDENCY.idl_hash, None);
+}
+
+#[test]
+fn check_program() {
+    let mut program = TestAccount::program(swap::ID, Pubkey::default());
+    let program = program.info();
+    assert!(swap::DEPENDENCY.check_program(&program).is_ok());
+    assert_eq!(
+        lending::DEPENDENCY.check_program(&program),
+        Err(anchor_lang::__private::ErrorCode::DependencyProgramMismatch.into())
+    );
+}
+
+fn idl_account_data(idl: &[u8]) -> Vec<u8> {
+    let mut data = vec![];
+    IdlAccount {
+        authority: Pubkey::default(),
+        data: idl.to_vec(),
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    data
+}
+
+#[test]
+fn check_idl() {
+    let idl_address = IdlAccount::address(&swap::ID);
+    for (idl, expected) in [
+        (&b""[..], Ok(())),
+        (
+            &b"changed"[..],
+            Err(anchor_lang::__private::ErrorCode::DependencyIdlMismatch.into()),
+        ),
+    ] {
+        let mut idl = TestAccount::new(swap::ID, idl_account_data(idl)).at(idl_address);
+        assert_eq!(swap::DEPENDENCY.check_idl(&idl.info()), expected);
+    }
+}
+
+#[program(dependencies(swap, lending))]
+pub mod dependent {
+    use super::*;
+
+    pub fn noop(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn check_dependencies_instruction() {
+    let metas = instruction::CheckDependencies::accounts();
+    assert_eq!(
+        metas.iter().map(|meta| meta.pubkey).collect::<Vec<_>>(),
+        vec![swap::ID, IdlAccount::address(&swap::ID), lending::ID]
+    );
+
+    let mut swap_program = TestAccount::program(swap::ID, Pubkey::default());
+    let mut swap_idl = TestAccount::new(swap::ID, idl_account_data(b"")).at(metas[1].pubkey);
+    let mut lending_program = TestAccount::program(lending::ID, Pubkey::default());
+    let accounts = [swap_program.info(), swap_idl.info(), lending_program.info()];
+    let data = instruction::CheckDependencies.data();
+    assert_eq!(entry(&ID, &accounts, &data), Ok(()));
+
+    // The IDL account of `swap` must be given before `lending`'s program.
+    let accounts = [swap_program.info(), lending_program.info()];
+    assert_eq!(
+        entry(&ID, &accounts, &data),
+        Err(anchor_lang::__private::ErrorCode::DependencyIdlMismatch.into())
+    );
+    assert_eq!(
+        entry(&ID, &accounts[..1], &data),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+mod common;
+
+#[program]
+pub mod deprecated_instruction {
+    use super::*;
+
+    #[deprecated_instruction(since = "0.3", note = "use swap_v2")]
+    pub fn swap(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+
+    #[deprecated_instruction(since = "0.2", sunset_slot = 100)]
+    pub fn legacy_swap(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+// Provides the clock sysvar off chain.
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: 100,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        solana_program::entrypoint::SUCCESS
+    }
+}
+
+#[test]
+fn deprecated_ix_is_refused_after_sunset() {
+    set_syscall_stubs(Box::new(Stubs));
+    assert_eq!(entry(&ID, &[], &instruction::Swap.data()), Ok(()));
+    assert_eq!(
+        entry(&ID, &[], &instruction::LegacySwap.data()),
+        Err(anchor_lang::__private::ErrorCode::InstructionSunset.into())
+    );
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[program(derive(Clone, Debug, PartialEq, Eq))]
+pub mod derives {
+    use super::*;
+
+    pub fn transfer(_ctx: Context<Transfer>, amount: u64) -> ProgramResult {
+        msg!("{}", amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Transfer<'info> {
+    #[account(mut)]
+    pub from: AccountInfo<'info>,
+    pub to: AccountInfo<'info>,
+}
+
+#[event(derive(Clone, Debug, PartialEq))]
+pub struct Transferred {
+    pub amount: u64,
+}
+
+#[error(offset = 6000, derive(Clone, PartialEq, Eq))]
+pub enum TransferError {
+    #[msg("Insufficient funds")]
+    InsufficientFunds,
+}
+
+#[test]
+fn instructions_derive_traits() {
+    let ix = instruction::Transfer { amount: 1 };
+    assert_eq!(ix.clone(), instruction::Transfer { amount: 1 });
+    assert_ne!(ix, instruction::Transfer { amount: 2 });
+}
+
+#[test]
+fn events_derive_traits() {
+    let event = Transferred { amount: 1 };
+    assert_eq!(event.clone(), Transferred { amount: 1 });
+}
+
+#[test]
+fn errors_derive_traits() {
+    let err: Error = TransferError::InsufficientFunds.into();
+    assert_eq!(
+        err.clone(),
+        Error::ErrorCode(TransferError::InsufficientFunds)
+    );
+    assert_eq!(ProgramError::from(err), ProgramError::Custom(6000),);
+}
+
+#[test]
+fn cpi_accounts_compare_keys() {
+    let mut from = TestAccount::new(ID, vec![]).writable();
+    let mut to = TestAccount::new(ID, vec![]);
+    let (from, to) = (from.info(), to.info());
+
+    // Re-exported as `cpi::accounts::Transfer` with the `cpi` feature.
+    use __cpi_client_accounts_transfer::Transfer;
+    let accounts = Transfer {
+        from: from.clone(),
+        to: to.clone(),
+    };
+    assert_eq!(accounts.clone(), accounts);
+    assert_ne!(accounts, Transfer { from: to, to: from });
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[account]
+pub struct Counter {
+    pub count: u64,
+}
+
+// Owned by the executing program id, like the `#[account]`s of a program
+// built with `dynamic-program-id`.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct Tenant {
+    pub count: u64,
+}
+
+impl AccountSerialize for Tenant {
+    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<(), ProgramError> {
+        AnchorSerialize::serialize(self, writer).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl AccountDeserialize for Tenant {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        AnchorDeserialize::deserialize(buf).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl Owner for Tenant {
+    fn owner() -> Pubkey {
+        ID
+    }
+
+    fn owner_for(program_id: &Pubkey) -> Pubkey {
+        *program_id
+    }
+}
+
+// The program type of a program built with `dynamic-program-id`.
+#[derive(Clone)]
+pub struct Tenancy;
+
+impl AccountDeserialize for Tenancy {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(_buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        Ok(Tenancy)
+    }
+}
+
+impl Id for Tenancy {
+    fn id() -> Pubkey {
+        ID
+    }
+
+    fn id_for(program_id: &Pubkey) -> Pubkey {
+        *program_id
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReadSelf<'info> {
+    pub program: Program<'info, Tenancy>,
+}
+
+#[derive(Accounts)]
+pub struct ReadCounter<'info> {
+    pub counter: Account<'info, Counter>,
+}
+
+#[derive(Accounts)]
+pub struct ReadTenant<'info> {
+    #[account(mut)]
+    pub tenant: Account<'info, Tenant>,
+}
+
+#[test]
+fn owner_for_defaults_to_owner() {
+    assert_eq!(Counter::owner_for(&Pubkey::new_unique()), ID);
+    assert_eq!(System::id_for(&Pubkey::new_unique()), System::id());
+}
+
+#[test]
+fn the_program_is_checked_against_the_executing_program_id() {
+    let program_id = Pubkey::new_unique();
+    let mut program = TestAccount::program(program_id, solana_program::bpf_loader::ID);
+    let accounts = [program.info()];
+    assert!(ReadSelf::try_accounts(&program_id, &mut &accounts[..], &[]).is_ok());
+    assert_eq!(
+        ReadSelf::try_accounts(&ID, &mut &accounts[..], &[]).err(),
+        Some(anchor_lang::__private::ErrorCode::InvalidProgramId.into())
+    );
+    assert_eq!(
+        Program::<Tenancy>::try_from(&accounts[0]).err(),
+        Some(anchor_lang::__private::ErrorCode::InvalidProgramId.into())
+    );
+}
+
+#[test]
+fn accounts_are_checked_against_the_executing_program_id() {
+    let program_id = Pubkey::new_unique();
+    let mut account = TestAccount::new(program_id, vec![0; 8])
+        .writable()
+        .lamports(1);
+    let accounts = [account.info()];
+
+    assert_eq!(
+        ReadCounter::try_accounts(&program_id, &mut &accounts[..], &[]).err(),
+        Some(anchor_lang::__private::ErrorCode::AccountNotProgramOwned.into())
+    );
+    let read = ReadTenant::try_accounts(&program_id, &mut &accounts[..], &[]).unwrap();
+    assert!(read.exit(&program_id).is_ok());
+    assert!(Account::<Tenant>::try_from_for(&program_id, &accounts[0]).is_ok());
+    assert_eq!(
+        Account::<Tenant>::try_from(&accounts[0]).err(),
+        Some(anchor_lang::__private::ErrorCode::AccountNotProgramOwned.into())
+    );
+    assert_eq!(
+        ReadTenant::try_accounts(&ID, &mut &accounts[..], &[]).err(),
+        Some(anchor_lang::__private::ErrorCode::AccountNotProgramOwned.into())
+    );
+}
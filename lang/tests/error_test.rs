@@ -0,0 +1,203 @@
+use anchor_lang::solana_program::program_error::ProgramError;
+
+mod unit {
+    use anchor_lang::prelude::*;
+
+    #[error]
+    pub enum UnitError {
+        #[msg("Something went wrong")]
+        Failed,
+        Other = 10,
+    }
+}
+
+mod data {
+    use anchor_lang::prelude::*;
+
+    #[error]
+    pub enum DataError {
+        #[msg("Insufficient funds: needed {needed}, available {available}")]
+        InsufficientFunds {
+            needed: u64,
+            available: u64,
+        },
+        #[msg("Limit exceeded")]
+        LimitExceeded {
+            limit: u8,
+        },
+        Closed = 10,
+    }
+}
+
+mod protocol {
+    use anchor_lang::prelude::*;
+
+    #[error(offset = 9000)]
+    pub enum ProtocolError {
+        #[msg("The protocol is paused")]
+        Paused,
+    }
+}
+
+mod extending {
+    use anchor_lang::prelude::*;
+
+    #[error(extends(super::protocol::ProtocolError))]
+    pub enum ProgramErrorCode {
+        Unauthorized,
+    }
+
+    pub fn check(paused: bool) -> std::result::Result<(), Error> {
+        if paused {
+            return Err(super::protocol::ProtocolError::Paused.into());
+        }
+        Err(ProgramErrorCode::Unauthorized.into())
+    }
+}
+
+use data::DataError;
+use unit::UnitError;
+
+#[test]
+fn unit_error_codes() {
+    assert_eq!(UnitError::Other as u32, 10);
+    assert_eq!(
+        ProgramError::from(UnitError::Failed),
+        ProgramError::Custom(6000)
+    );
+    assert_eq!(
+        ProgramError::from(UnitError::Other),
+        ProgramError::Custom(6010)
+    );
+}
+
+#[test]
+fn data_error_codes() {
+    let err = DataError::InsufficientFunds {
+        needed: 10,
+        available: 5,
+    };
+    assert_eq!(ProgramError::from(err), ProgramError::Custom(6000));
+    assert_eq!(
+        ProgramError::from(DataError::LimitExceeded { limit: 3 }),
+        ProgramError::Custom(6001)
+    );
+    assert_eq!(
+        ProgramError::from(DataError::Closed),
+        ProgramError::Custom(6010)
+    );
+}
+
+#[test]
+fn data_error_messages() {
+    let err = DataError::InsufficientFunds {
+        needed: 10,
+        available: 5,
+    };
+    assert_eq!(
+        err.to_string(),
+        "Insufficient funds: needed 10, available 5"
+    );
+    assert_eq!(
+        DataError::LimitExceeded { limit: 3 }.to_string(),
+        "Limit exceeded"
+    );
+    assert_eq!(DataError::Closed.to_string(), "Closed");
+}
+
+#[test]
+fn errors_unify() {
+    let err = anchor_lang::error::Error::from(DataError::InsufficientFunds {
+        needed: 10,
+        available: 5,
+    });
+    assert_eq!(err.error_code(), 6000);
+    assert_eq!(err.error_name(), "InsufficientFunds");
+    assert_eq!(DataError::Closed.error_name(), "Closed");
+    assert_eq!(
+        err.to_string(),
+        "Insufficient funds: needed 10, available 5"
+    );
+    assert_eq!(ProgramError::from(err), ProgramError::Custom(6000));
+
+    let err = anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintMut);
+    assert_eq!(err.error_code(), 2000);
+    assert_eq!(err.error_name(), "ConstraintMut");
+
+    let err = anchor_lang::error::Error::from(ProgramError::InvalidArgument);
+    assert_eq!(err.error_code(), u64::from(ProgramError::InvalidArgument));
+    assert_eq!(err.error_name(), "InvalidArgument");
+    assert_eq!(
+        anchor_lang::error::Error::from(ProgramError::Custom(7)).error_name(),
+        "Custom"
+    );
+    assert_eq!(
+        anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintMut),
+        anchor_lang::error::Error::from(ProgramError::Custom(2000))
+    );
+}
+
+#[test]
+fn question_mark_converts_any_error() {
+    fn check(step: u8) -> data::Result<()> {
+        match step {
+            0 => Err(ProgramError::InvalidArgument)?,
+            1 => Err(UnitError::Failed)?,
+            2 => Err(DataError::Closed)?,
+            _ => Err(anchor_lang::error::ErrorCode::ConstraintMut)?,
+        }
+    }
+    let codes: Vec<u64> = (0..4)
+        .map(|step| check(step).unwrap_err().error_code())
+        .collect();
+    assert_eq!(
+        codes,
+        vec![u64::from(ProgramError::InvalidArgument), 6000, 6010, 2000]
+    );
+}
+
+#[test]
+fn extended_error_codes() {
+    assert_eq!(protocol::ProtocolError::__ANCHOR_ERROR_CODES, (9000, 9001));
+    assert_eq!(
+        ProgramError::from(extending::check(true).unwrap_err()),
+        ProgramError::Custom(9000)
+    );
+    assert_eq!(
+        ProgramError::from(extending::check(false).unwrap_err()),
+        ProgramError::Custom(6000)
+    );
+}
+
+#[test]
+fn error_code_tables() {
+    use anchor_lang::error::ErrorCodes;
+    assert_eq!(
+        UnitError::ERROR_CODES,
+        &[
+            (6000, "Failed", "Something went wrong"),
+            (6010, "Other", "Other")
+        ]
+    );
+    assert_eq!(
+        DataError::ERROR_CODES[0],
+        (
+            6000,
+            "InsufficientFunds",
+            "Insufficient funds: needed {needed}, available {available}"
+        )
+    );
+    assert_eq!(
+        protocol::ProtocolError::ERROR_CODES,
+        &[(9000, "Paused", "The protocol is paused")]
+    );
+    // Extending enums list the extended codes after their own.
+    assert_eq!(
+        extending::ProgramErrorCode::error_tables(),
+        vec![
+            &[(6000, "Unauthorized", "Unauthorized")][..],
+            protocol::ProtocolError::ERROR_CODES,
+        ]
+    );
+    assert_eq!(UnitError::error_tables(), vec![UnitError::ERROR_CODES]);
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{Event, EventBuffer, InstructionData};
+use common::*;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use std::cell::RefCell;
+
+mod common;
+
+thread_local! {
+    static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+    }
+}
+
+#[program]
+pub mod event_buffer {
+    use super::*;
+
+    pub fn notify(ctx: Context<Notify>, fail: bool) -> ProgramResult {
+        ctx.events().push(Deposited { amount: 1 })?;
+        ctx.events().push(Withdrawn { amount: 2 })?;
+        if fail {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Notify<'info> {
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct Deposited {
+    pub amount: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub amount: u64,
+}
+
+fn batch(events: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = EventBuffer::DISCRIMINATOR.to_vec();
+    for event in events {
+        data.extend_from_slice(&(event.len() as u32).to_le_bytes());
+        data.extend_from_slice(event);
+    }
+    data
+}
+
+#[test]
+fn events_are_buffered() {
+    let buffer = EventBuffer::new();
+    assert!(buffer.is_empty());
+    buffer.push(&Deposited { amount: 1 }).unwrap();
+    buffer.push(&Withdrawn { amount: 2 }).unwrap();
+    assert!(!buffer.is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "event-log-data"))]
+fn events_are_logged_once_the_instruction_succeeded() {
+    set_syscall_stubs(Box::new(Stubs));
+    let mut authority = TestAccount::new(solana_program::system_program::ID, vec![])
+        .signer()
+        .lamports(1);
+    let accounts = [authority.info()];
+    let batch = anchor_lang::__private::base64::encode(batch(&[
+        Deposited { amount: 1 }.data(),
+        Withdrawn { amount: 2 }.data(),
+    ]));
+
+    assert_eq!(
+        entry(&ID, &accounts, &instruction::Notify { fail: true }.data()),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert!(!LOGS.with(|logs| logs.take()).contains(&batch));
+
+    assert_eq!(
+        entry(&ID, &accounts, &instruction::Notify { fail: false }.data()),
+        Ok(())
+    );
+    let logs = LOGS.with(|logs| logs.take());
+    assert_eq!(logs.last(), Some(&batch));
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Event;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use std::sync::{Arc, Mutex};
+
+#[event]
+pub struct Small {
+    pub value: u64,
+}
+
+#[event]
+pub struct Large {
+    pub values: Vec<u64>,
+}
+
+#[event(anonymous)]
+pub struct Tick {
+    pub price: u64,
+}
+
+struct Stubs(Arc<Mutex<Vec<String>>>);
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        self.0.lock().unwrap().push(message.to_string());
+    }
+}
+
+#[test]
+#[cfg(not(feature = "event-log-data"))]
+fn events_are_logged_base64_encoded() {
+    let logs = Arc::new(Mutex::new(vec![]));
+    set_syscall_stubs(Box::new(Stubs(logs.clone())));
+    let small = Small { value: 1 };
+    let large = Large {
+        values: (0..100).collect(),
+    };
+    let tick = Tick { price: 7 };
+    emit!(small);
+    emit!(large);
+    emit!(tick);
+
+    let logs = logs.lock().unwrap();
+    assert_eq!(
+        *logs,
+        [
+            anchor_lang::__private::base64::encode(small.data()),
+            anchor_lang::__private::base64::encode(large.data()),
+            anchor_lang::__private::base64::encode(7u64.to_le_bytes()),
+        ]
+    );
+}
+
+#[test]
+fn anonymous_events_have_no_discriminator() {
+    assert_eq!(Small { value: 7 }.data()[8..], 7u64.to_le_bytes());
+    assert_eq!(Tick { price: 7 }.data(), 7u64.to_le_bytes());
+}
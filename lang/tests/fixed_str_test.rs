@@ -0,0 +1,85 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+use std::convert::TryFrom;
+
+mod common;
+
+#[account(zero_copy)]
+pub struct Profile {
+    pub name: FixedStr<16>,
+    pub score: u64,
+}
+
+#[account]
+pub struct Listing {
+    pub title: FixedStr<8>,
+    pub price: u64,
+}
+
+#[test]
+fn fixed_str_is_padded() {
+    let name = FixedStr::<16>::new("héllo").unwrap();
+    assert_eq!(name, "héllo");
+    assert_eq!(name.len(), 6);
+    assert_eq!(FixedStr::<16>::capacity(), 16);
+    assert_eq!(name.to_string(), "héllo");
+    assert!(FixedStr::<16>::default().is_empty());
+
+    let bytes: &[u8] = anchor_lang::__private::bytemuck::bytes_of(&name);
+    assert_eq!(&bytes[..6], "héllo".as_bytes());
+    assert!(bytes[6..].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn fixed_str_is_validated() {
+    assert_eq!(
+        FixedStr::<4>::new("hello").err(),
+        Some(ErrorCode::FixedStrTooLong.into())
+    );
+    assert_eq!(
+        FixedStr::<4>::try_from("a\0b").err(),
+        Some(ErrorCode::FixedStrInvalidUtf8.into())
+    );
+    assert_eq!(
+        FixedStr::<4>::from_bytes(&[0xff, 0, 0, 0]).err(),
+        Some(ErrorCode::FixedStrInvalidUtf8.into())
+    );
+    assert_eq!(FixedStr::<4>::from_bytes(b"ab\0\0").unwrap(), "ab");
+
+    let mut name = FixedStr::<4>::new("abcd").unwrap();
+    name.set("x").unwrap();
+    assert_eq!(name, "x");
+    assert!(name.set("toolong").is_err());
+    assert_eq!(name, "x");
+}
+
+#[test]
+fn fixed_str_is_encoded_without_length_prefix() {
+    let listing = Listing {
+        title: FixedStr::new("lamp").unwrap(),
+        price: 3,
+    };
+    let data = listing.try_to_vec().unwrap();
+    assert_eq!(
+        data,
+        [b'l', b'a', b'm', b'p', 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0]
+    );
+    let decoded = Listing::try_from_slice(&data).unwrap();
+    assert_eq!(decoded.title, "lamp");
+
+    let mut invalid = data;
+    invalid[0] = 0xff;
+    assert!(Listing::try_from_slice(&invalid).is_err());
+}
+
+#[test]
+fn fixed_str_is_zero_copy() {
+    let mut profile = Profile {
+        name: FixedStr::new("alice").unwrap(),
+        score: 7,
+    };
+    profile.name.set("bob").unwrap();
+    assert_eq!({ profile.name }, "bob");
+    assert_eq!(std::mem::size_of::<Profile>(), 24);
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+#[flags]
+pub enum Permissions {
+    Mint,
+    Burn,
+    Freeze = 1 << 4,
+}
+
+#[flags(u64)]
+pub enum Wide {
+    Low,
+    High = 1 << 63,
+}
+
+#[test]
+fn flag_values() {
+    assert_eq!(Permissions::MINT.bits(), 1);
+    assert_eq!(Permissions::BURN.bits(), 2);
+    assert_eq!(Permissions::FREEZE.bits(), 16);
+    assert_eq!(Permissions::all().bits(), 19);
+    assert_eq!(Wide::HIGH.bits(), 1u64 << 63);
+    assert_eq!(std::mem::size_of::<Wide>(), 8);
+}
+
+#[test]
+fn flag_operations() {
+    let mut perms = Permissions::MINT | Permissions::FREEZE;
+    assert!(perms.contains(Permissions::MINT));
+    assert!(!perms.contains(Permissions::MINT | Permissions::BURN));
+    assert!(perms.intersects(Permissions::MINT | Permissions::BURN));
+
+    perms.remove(Permissions::MINT);
+    assert_eq!(perms, Permissions::FREEZE);
+    perms.set(Permissions::BURN, true);
+    assert_eq!(perms, Permissions::BURN | Permissions::FREEZE);
+    assert_eq!(!perms, Permissions::MINT);
+    assert_eq!(format!("{:?}", perms), "Permissions(BURN | FREEZE)");
+
+    assert_eq!(Permissions::from_bits(4), None);
+    assert_eq!(Permissions::from_bits_truncate(5), Permissions::MINT);
+}
+
+#[test]
+fn flags_serialization() {
+    let perms = Permissions::MINT | Permissions::BURN;
+    let data = perms.try_to_vec().unwrap();
+    assert_eq!(data, 3u32.to_le_bytes());
+    assert_eq!(Permissions::try_from_slice(&data).unwrap(), perms);
+    assert_eq!(
+        anchor_lang::__private::bytemuck::bytes_of(&perms),
+        &data[..]
+    );
+
+    // Undefined bits are kept, as for zero copy accounts.
+    let undefined = Permissions::try_from_slice(&5u32.to_le_bytes()).unwrap();
+    assert_eq!(undefined, Permissions::from_bits_retain(5));
+    assert!(undefined.has_undefined_bits());
+    assert!(!perms.has_undefined_bits());
+    assert_eq!(format!("{:?}", undefined), "Permissions(MINT | 0x4)");
+    assert_eq!(
+        anchor_lang::__private::bytemuck::cast::<u32, Permissions>(5),
+        undefined
+    );
+}
@@ -3,10 +3,11 @@
 use anchor_lang::prelude::borsh::maybestd::io::Write;
 use anchor_lang::prelude::*;
 use borsh::{BorshDeserialize, BorshSerialize};
+use common::*;
 use solana_program::pubkey::Pubkey;
 
 // Needed to declare accounts.
-declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+mod common;
 
 #[derive(Accounts)]
 pub struct GenericsTest<'info, T, U, const N: usize>
@@ -0,0 +1,190 @@
+use anchor_lang::idempotent::{replay_address, REPLAY_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+use solana_program::instruction::Instruction;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::system_instruction;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod common;
+
+const NOW: i64 = 1_000;
+const NONCE: [u8; 16] = [7; 16];
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+#[program]
+pub mod idempotent {
+    use super::*;
+
+    #[idempotent(expiry = 60)]
+    pub fn pay(ctx: Context<Pay>, amount: u64) -> ProgramResult {
+        **ctx.accounts.payee.lamports.borrow_mut() += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Pay<'info> {
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub payee: AccountInfo<'info>,
+}
+
+// Provides the sysvars off chain and, instead of executing cross-program
+// invocations, checks that they create the replay account.
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let payer = instruction.accounts[0].pubkey;
+        let replay_key = replay_address(&ID, &NONCE).0;
+        assert_eq!(
+            instruction,
+            &system_instruction::create_account(
+                &payer,
+                &replay_key,
+                Rent::default().minimum_balance(40),
+                40,
+                &ID,
+            )
+        );
+        CREATED.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn replay_record(payer: &Pubkey, expires_at: i64) -> Vec<u8> {
+    let mut data = payer.to_bytes().to_vec();
+    data.extend_from_slice(&expires_at.to_le_bytes());
+    data
+}
+
+// Runs `pay` with the replay account at `replay_key`, owned by `replay_owner`,
+// returning the result, the payee's lamports and the replay account's data.
+fn pay(replay_key: Pubkey, replay_owner: Pubkey) -> (ProgramResult, u64, Vec<u8>) {
+    let system_program = solana_program::system_program::ID;
+    let mut accounts = [
+        TestAccount::new(system_program, vec![])
+            .signer()
+            .writable()
+            .lamports(1_000_000_000),
+        TestAccount::new(system_program, vec![]).writable(),
+        TestAccount::new(replay_owner, vec![0; 40])
+            .at(replay_key)
+            .writable(),
+        TestAccount::native_program(system_program),
+    ];
+    let accounts = infos(&mut accounts);
+    let accounts = [
+        accounts[0].clone(),
+        accounts[1].clone(),
+        accounts[2].clone(),
+        accounts[0].clone(),
+        accounts[3].clone(),
+    ];
+    let data = instruction::Pay {
+        amount: 5,
+        nonce: NONCE,
+    }
+    .data();
+    let result = entry(&ID, &accounts, &data);
+    let replay = accounts[2].try_borrow_data().unwrap().to_vec();
+    (result, accounts[1].lamports(), replay)
+}
+
+#[test]
+fn first_execution_creates_replay_account() {
+    set_syscall_stubs(Box::new(Stubs));
+    let replay_key = replay_address(&ID, &NONCE).0;
+    let (result, paid, data) = pay(replay_key, solana_program::system_program::ID);
+    assert_eq!(result, Ok(()));
+    assert_eq!(paid, 5);
+    assert_eq!(&data[32..], &(NOW + 60).to_le_bytes());
+
+    assert_eq!(CREATED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn duplicate_execution_is_rejected() {
+    set_syscall_stubs(Box::new(Stubs));
+    let replay_key = replay_address(&ID, &NONCE).0;
+    let (result, paid, _) = pay(replay_key, ID);
+    assert_eq!(
+        result,
+        Err(anchor_lang::__private::ErrorCode::InstructionAlreadyExecuted.into())
+    );
+    assert_eq!(paid, 0);
+}
+
+#[test]
+fn replay_account_must_be_derived_from_nonce() {
+    set_syscall_stubs(Box::new(Stubs));
+    let other = Pubkey::find_program_address(&[REPLAY_SEED, &[8; 16]], &ID).0;
+    assert_eq!(
+        pay(other, solana_program::system_program::ID).0,
+        Err(anchor_lang::__private::ErrorCode::ConstraintSeeds.into())
+    );
+}
+
+// Runs `close_replay` against a replay account funded by `funder` that
+// expires at `expires_at`, returning the result and the payer's lamports.
+fn close_replay(funder: Option<Pubkey>, expires_at: i64) -> (ProgramResult, u64) {
+    let replay_key = replay_address(&ID, &NONCE).0;
+    let payer = TestAccount::new(solana_program::system_program::ID, vec![])
+        .writable()
+        .lamports(1);
+    let replay_data = replay_record(&funder.unwrap_or(payer.key), expires_at);
+    let mut accounts = [
+        TestAccount::new(ID, replay_data)
+            .at(replay_key)
+            .writable()
+            .lamports(10),
+        payer,
+    ];
+    let accounts = infos(&mut accounts);
+    let data = instruction::CloseReplay { nonce: NONCE }.data();
+    let result = entry(&ID, &accounts, &data);
+    let lamports = accounts[1].lamports();
+    (result, lamports)
+}
+
+#[test]
+fn expired_replay_account_is_closed() {
+    set_syscall_stubs(Box::new(Stubs));
+    assert_eq!(close_replay(None, NOW), (Ok(()), 11));
+    assert_eq!(
+        close_replay(None, NOW + 1),
+        (
+            Err(anchor_lang::__private::ErrorCode::ReplayNotExpired.into()),
+            1
+        )
+    );
+    assert_eq!(
+        close_replay(Some(Pubkey::new_unique()), NOW),
+        (
+            Err(anchor_lang::__private::ErrorCode::ReplayPayerMismatch.into()),
+            1
+        )
+    );
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+
+mod common;
+
+// The `#[idl]` attributes only affect the IDL, so the renamed items keep
+// their Rust names and encoding.
+#[account]
+#[idl(name = "Pool")]
+pub struct LiquidityPool {
+    #[idl(name = "feeBps")]
+    pub fee_rate_bps: u16,
+    pub fees: FeeSchedule,
+}
+
+#[idl(name = "Fees")]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct FeeSchedule {
+    #[idl(name = "makerBps")]
+    pub maker: u16,
+}
+
+#[idl]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum Side {
+    Bid {
+        #[idl(name = "px")]
+        price: u64,
+    },
+    Ask,
+}
+
+#[account(zero_copy)]
+pub struct Oracle {
+    #[idl(name = "px")]
+    pub price: u64,
+}
+
+#[test]
+fn renamed_items_keep_their_encoding() {
+    let pool = LiquidityPool {
+        fee_rate_bps: 30,
+        fees: FeeSchedule { maker: 5 },
+    };
+    let mut data = vec![];
+    pool.try_serialize(&mut data).unwrap();
+    assert_eq!(&data[..8], &LiquidityPool::discriminator());
+    assert_eq!(data[8..], [30, 0, 5, 0]);
+
+    let side = Side::Bid { price: 1 };
+    assert_eq!(
+        Side::try_from_slice(&side.try_to_vec().unwrap()).unwrap(),
+        side
+    );
+    assert_eq!({ Oracle { price: 2 }.price }, 2);
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod instruction_args {
+    use super::*;
+
+    pub fn keys(
+        _ctx: Context<Empty>,
+        _key_array: [Pubkey; 3],
+        _maybe_key: Option<Pubkey>,
+        _maybe_bytes: Option<[u8; 32]>,
+        _nested: Option<Option<Pubkey>>,
+        _key_options: [Option<Pubkey>; 2],
+    ) -> ProgramResult {
+        Ok(())
+    }
+
+    pub fn memo(_ctx: Context<Memo>, _memo: String) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[derive(Accounts)]
+pub struct Authority<'info> {
+    pub authority: Signer<'info>,
+    pub delegate: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Memo<'info> {
+    pub data: AccountInfo<'info>,
+    pub authority: Authority<'info>,
+}
+
+fn round_trip(ix: instruction::Keys) -> instruction::Keys {
+    let data = ix.data();
+    let sighash = anchor_lang::solana_program::hash::hash(b"global:keys");
+    assert_eq!(&data[..8], &sighash.to_bytes()[..8]);
+    AnchorDeserialize::try_from_slice(&data[8..]).unwrap()
+}
+
+#[test]
+fn pubkey_arrays_and_options_round_trip() {
+    let key = Pubkey::new_unique();
+    let ix = round_trip(instruction::Keys {
+        _key_array: [key, Pubkey::default(), key],
+        _maybe_key: Some(key),
+        _maybe_bytes: Some(key.to_bytes()),
+        _nested: Some(Some(key)),
+        _key_options: [None, Some(key)],
+    });
+    assert_eq!(ix._key_array, [key, Pubkey::default(), key]);
+    assert_eq!(ix._maybe_key, Some(key));
+    assert_eq!(ix._maybe_bytes, Some(key.to_bytes()));
+    assert_eq!(ix._nested, Some(Some(key)));
+    assert_eq!(ix._key_options, [None, Some(key)]);
+}
+
+#[test]
+fn empty_options_round_trip() {
+    let ix = round_trip(instruction::Keys {
+        _key_array: [Pubkey::default(); 3],
+        _maybe_key: None,
+        _maybe_bytes: None,
+        _nested: Some(None),
+        _key_options: [None, None],
+    });
+    assert_eq!(ix._maybe_key, None);
+    assert_eq!(ix._maybe_bytes, None);
+    assert_eq!(ix._nested, Some(None));
+    assert_eq!(ix._key_options, [None, None]);
+}
+
+#[test]
+fn instruction_sizes() {
+    assert_eq!(
+        instruction::Keys::DATA_LEN,
+        Some(8 + 3 * 32 + 33 + 33 + 34 + 2 * 33)
+    );
+    assert_eq!(instruction::Keys::ACCOUNTS_LEN, 0);
+    assert_eq!(instruction::Memo::DATA_LEN, None);
+    assert_eq!(instruction::Memo::ACCOUNTS_LEN, 3);
+    assert_eq!(accounts::Memo::ACCOUNTS_LEN, 3);
+}
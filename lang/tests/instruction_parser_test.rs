@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+
+mod common;
+
+#[program(derive(Debug, PartialEq))]
+pub mod instruction_parser {
+    use super::*;
+
+    pub fn deposit(_ctx: Context<Empty>, amount: u64, memo: String) -> ProgramResult {
+        let _ = (amount, memo);
+        Ok(())
+    }
+
+    pub fn freeze(_ctx: Context<Freeze>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[derive(Accounts)]
+pub struct Freeze<'info> {
+    pub authority: Signer<'info>,
+    pub vault: Vault<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Vault<'info> {
+    pub vault: UncheckedAccount<'info>,
+}
+
+use common::*;
+use instruction::__parser::{
+    try_parse_compiled_instruction, try_parse_compiled_instruction_for, try_parse_instruction,
+    try_parse_instruction_with_accounts, ParsedInstruction, ProgramInstruction,
+};
+use solana_program::instruction::CompiledInstruction;
+
+#[test]
+fn parses_instructions() {
+    let deposit = instruction::Deposit {
+        amount: 5,
+        memo: "rent".to_string(),
+    };
+    assert_eq!(
+        try_parse_instruction(&deposit.data()),
+        Some(ProgramInstruction::Deposit(deposit))
+    );
+    assert_eq!(
+        try_parse_instruction(&instruction::Freeze.data()),
+        Some(ProgramInstruction::Freeze(instruction::Freeze))
+    );
+}
+
+#[test]
+fn rejects_unknown_instructions() {
+    let mut data = instruction::Deposit {
+        amount: 5,
+        memo: "rent".to_string(),
+    }
+    .data();
+    assert_eq!(try_parse_instruction(&data[..7]), None);
+    assert_eq!(try_parse_instruction(&data[..12]), None);
+    data[0] ^= 1;
+    assert_eq!(try_parse_instruction(&data), None);
+}
+
+#[test]
+fn parses_instructions_with_accounts() {
+    let (authority, vault, extra) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let data = instruction::Freeze.data();
+    assert_eq!(
+        try_parse_instruction_with_accounts(&data, &[authority, vault, extra]),
+        Some(ParsedInstruction::Freeze {
+            args: instruction::Freeze,
+            accounts: accounts::Freeze {
+                authority,
+                vault: accounts::Vault { vault },
+            },
+            remaining_accounts: vec![extra],
+        })
+    );
+    assert_eq!(
+        try_parse_instruction_with_accounts(&data, &[authority]),
+        None
+    );
+
+    // The message's keys, which the instruction refers to by index.
+    let account_keys = [vault, ID, authority];
+    let ix = CompiledInstruction {
+        program_id_index: 1,
+        accounts: vec![2, 0],
+        data,
+    };
+    assert_eq!(
+        try_parse_compiled_instruction(&ix, &account_keys),
+        try_parse_instruction_with_accounts(&ix.data, &[authority, vault])
+    );
+    let other_program = CompiledInstruction {
+        program_id_index: 2,
+        ..ix.clone()
+    };
+    assert_eq!(
+        try_parse_compiled_instruction(&other_program, &account_keys),
+        None
+    );
+    // The program deployed under another id.
+    assert_eq!(
+        try_parse_compiled_instruction_for(&authority, &other_program, &account_keys),
+        try_parse_compiled_instruction(&ix, &account_keys)
+    );
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod instruction_prefix {
+    use super::*;
+
+    pub fn noop(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+
+    // Accepts the `Transfer { amount: u64 }` instruction, tagged with 3, of
+    // the native program being replaced.
+    #[instruction_prefix(3)]
+    pub fn legacy_transfer(
+        _program_id: &Pubkey,
+        _accounts: &[AccountInfo],
+        data: &[u8],
+    ) -> ProgramResult {
+        match data.len() {
+            9 => Ok(()),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn prefixed_instructions_bypass_dispatch() {
+    let mut data = vec![3];
+    data.extend_from_slice(&1u64.to_le_bytes());
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+    assert_eq!(
+        entry(&ID, &[], &[3]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn other_instructions_are_dispatched() {
+    assert_eq!(entry(&ID, &[], &instruction::Noop.data()), Ok(()));
+    assert_eq!(
+        entry(&ID, &[], &[4]),
+        Err(anchor_lang::__private::ErrorCode::InstructionMissing.into())
+    );
+}
@@ -0,0 +1,115 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::interface_registry::{interface_hash, InterfaceRegistration};
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[interface(registry)]
+pub trait Auth<'info, T: Accounts<'info>> {
+    fn is_authorized(ctx: Context<T>, current: u64, new: u64) -> ProgramResult;
+}
+
+#[program(interface_registry)]
+pub mod counter {
+    use super::*;
+
+    pub fn set_count(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn generated_helpers_match_the_registry() {
+    assert_eq!(auth::registry::INTERFACE_HASH, interface_hash("Auth"));
+    let (authority, implementation) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let ix = auth::registry::register_instruction(&ID, &authority, &implementation);
+    assert_eq!(ix.program_id, ID);
+    assert_eq!(
+        ix.accounts[0].pubkey,
+        auth::registry::address(&ID, &authority, &implementation).0
+    );
+    assert_eq!(
+        ix.data,
+        instruction::InterfaceRegister {
+            interface: interface_hash("Auth"),
+            implementation,
+        }
+        .data()
+    );
+    let ix = auth::registry::unregister_instruction(&ID, &authority, &implementation);
+    assert_eq!(
+        ix.data,
+        instruction::InterfaceUnregister {
+            interface: interface_hash("Auth"),
+            implementation,
+        }
+        .data()
+    );
+}
+
+#[test]
+fn registrations_are_verified() {
+    let mut authority = TestAccount::new(solana_program::system_program::ID, vec![])
+        .signer()
+        .writable()
+        .lamports(1);
+    let authority_key = authority.key;
+    let implementation = Pubkey::new_unique();
+    // The registration is allocated and assigned up front, as creating it
+    // needs the system program.
+    let mut registration = TestAccount::new(ID, vec![0; 80])
+        .at(auth::registry::address(&ID, &authority_key, &implementation).0)
+        .writable()
+        .lamports(10);
+    let (registration, authority) = (registration.info(), authority.info());
+    let args = |implementation| instruction::InterfaceRegister {
+        interface: auth::registry::INTERFACE_HASH,
+        implementation,
+    };
+    let verify =
+        |authority: &Pubkey| auth::registry::verify(&ID, &registration, authority, &implementation);
+
+    assert_eq!(
+        verify(&authority_key),
+        Err(ErrorCode::InterfaceNotRegistered.into())
+    );
+    let accounts = [registration.clone(), authority.clone(), authority.clone()];
+    assert_eq!(
+        entry(&ID, &accounts, &args(Pubkey::new_unique()).data()),
+        Err(ErrorCode::ConstraintSeeds.into())
+    );
+    assert_eq!(entry(&ID, &accounts, &args(implementation).data()), Ok(()));
+    assert_eq!(
+        InterfaceRegistration::try_from_account(&ID, &registration).unwrap(),
+        InterfaceRegistration {
+            interface: interface_hash("Auth"),
+            authority: authority_key,
+            implementation,
+        }
+    );
+    assert_eq!(verify(&authority_key), Ok(()));
+    assert_eq!(
+        verify(&Pubkey::new_unique()),
+        Err(ErrorCode::InterfaceNotRegistered.into())
+    );
+
+    let unregister = instruction::InterfaceUnregister {
+        interface: auth::registry::INTERFACE_HASH,
+        implementation,
+    }
+    .data();
+    assert_eq!(
+        entry(&ID, &[registration.clone(), authority.clone()], &unregister),
+        Ok(())
+    );
+    assert_eq!(authority.lamports(), 11);
+    assert_eq!(
+        verify(&authority_key),
+        Err(ErrorCode::InterfaceNotRegistered.into())
+    );
+}
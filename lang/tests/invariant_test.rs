@@ -0,0 +1,58 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+
+mod common;
+
+#[account]
+#[invariant(self.total >= self.locked)]
+#[invariant(self.owner != Pubkey::default())]
+pub struct Stake {
+    pub owner: Pubkey,
+    pub total: u64,
+    pub locked: u64,
+}
+
+// Serializes the stake without checking its invariants, like a corrupted
+// account on chain.
+fn raw_data(owner: Pubkey, total: u64, locked: u64) -> Vec<u8> {
+    let mut data = Stake::discriminator().to_vec();
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(&total.to_le_bytes());
+    data.extend_from_slice(&locked.to_le_bytes());
+    data
+}
+
+#[test]
+fn invariants_are_checked_on_deserialize() {
+    let owner = Pubkey::new_unique();
+    let stake = Stake::try_deserialize(&mut &raw_data(owner, 2, 1)[..]).unwrap();
+    assert_eq!((stake.total, stake.locked), (2, 1));
+
+    for data in [raw_data(owner, 1, 2), raw_data(Pubkey::default(), 2, 1)] {
+        assert_eq!(
+            Stake::try_deserialize(&mut &data[..]).err(),
+            Some(ErrorCode::AccountInvariantViolated.into())
+        );
+    }
+    // Uninitialized accounts aren't checked.
+    assert!(Stake::try_deserialize_unchecked(&mut &raw_data(owner, 1, 2)[..]).is_ok());
+}
+
+#[test]
+fn invariants_are_checked_on_serialize() {
+    let mut stake = Stake {
+        owner: Pubkey::new_unique(),
+        total: 1,
+        locked: 1,
+    };
+    let mut data = vec![];
+    stake.try_serialize(&mut data).unwrap();
+
+    stake.locked = 2;
+    assert_eq!(
+        stake.try_serialize(&mut vec![]),
+        Err(ErrorCode::AccountInvariantViolated.into())
+    );
+}
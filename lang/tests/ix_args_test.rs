@@ -0,0 +1,80 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program(deny_trailing_args)]
+pub mod strict_args {
+    use super::*;
+
+    pub fn transfer(_ctx: Context<Empty>, amount: u64, memo: String) -> ProgramResult {
+        if amount != 5 || memo != "rent" {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    pub fn ping(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+
+    #[instruction(zero_copy)]
+    pub fn swap(_ctx: Context<Empty>, amount_in: u64) -> ProgramResult {
+        if amount_in != 100 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+fn transfer_data() -> Vec<u8> {
+    instruction::Transfer {
+        amount: 5,
+        memo: "rent".to_string(),
+    }
+    .data()
+}
+
+#[test]
+fn args_deserialize_one_at_a_time() {
+    assert_eq!(entry(&ID, &[], &transfer_data()), Ok(()));
+
+    // Truncated in the first and in the second arg.
+    let data = transfer_data();
+    for len in [12, data.len() - 1].iter() {
+        assert_eq!(
+            entry(&ID, &[], &data[..*len]),
+            Err(ErrorCode::InstructionDidNotDeserialize.into())
+        );
+    }
+}
+
+#[test]
+fn trailing_bytes_are_refused() {
+    let mut data = transfer_data();
+    data.push(0);
+    assert_eq!(
+        entry(&ID, &[], &data),
+        Err(ErrorCode::InstructionDataTooLong.into())
+    );
+
+    let mut data = instruction::Ping.data();
+    data.push(0);
+    assert_eq!(
+        entry(&ID, &[], &data),
+        Err(ErrorCode::InstructionDataTooLong.into())
+    );
+
+    let mut data = instruction::Swap { amount_in: 100 }.data();
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+    data.push(0);
+    assert_eq!(
+        entry(&ID, &[], &data),
+        Err(ErrorCode::InstructionDataTooLong.into())
+    );
+}
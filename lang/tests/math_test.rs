@@ -0,0 +1,120 @@
+use anchor_lang::math::*;
+use proptest::prelude::*;
+
+// Value of the vault per share, as the fraction `assets / shares` including
+// the virtual offsets.
+fn share_price(total_assets: u64, total_shares: u64) -> (u128, u128) {
+    (
+        total_assets as u128 + VIRTUAL_ASSETS as u128,
+        total_shares as u128 + VIRTUAL_SHARES as u128,
+    )
+}
+
+// Checks the invariants for one vault state and operation size, where
+// `amount` is used both as assets and shares. Operations whose result doesn't
+// fit a u64 fail with `MathOverflow`, and are skipped.
+fn check_invariants(amount: u64, total_assets: u64, total_shares: u64) {
+    let (a, ta, ts) = (amount, total_assets, total_shares);
+    let (before_assets, before_shares) = share_price(ta, ts);
+
+    let deposit = shares_for_deposit(a, ta, ts)
+        .ok()
+        .filter(|shares| ts.checked_add(*shares).is_some());
+    if let Some(shares) = deposit {
+        // Depositing and then redeeming never returns more than was
+        // deposited.
+        let assets = assets_for_redeem(shares, ta + a, ts + shares).unwrap();
+        assert!(assets <= a, "deposit {} redeemed for {}", a, assets);
+
+        // Deposits never lower the share price for existing holders.
+        let (after_assets, after_shares) = share_price(ta + a, ts + shares);
+        assert!(after_assets * before_shares >= before_assets * after_shares);
+
+        // Withdrawing assets burns at least the shares depositing them mints.
+        if let Ok(burned) = shares_for_withdraw(a, ta, ts) {
+            assert!(burned >= shares);
+        }
+    }
+
+    // Minting shares costs at least what redeeming them returns.
+    if let Ok(cost) = assets_for_mint(a, ta, ts) {
+        assert!(cost >= assets_for_redeem(a, ta, ts).unwrap());
+    }
+
+    // Withdrawals never lower the share price either, as long as the vault
+    // holds the assets and shares.
+    if let Ok(burned) = shares_for_withdraw(a, ta, ts) {
+        if a <= ta && burned <= ts {
+            let (after_assets, after_shares) = share_price(ta - a, ts - burned);
+            assert!(after_assets * before_shares >= before_assets * after_shares);
+        }
+    }
+}
+
+#[test]
+fn invariants_hold_exhaustively_for_small_vaults() {
+    for amount in 0..40 {
+        for total_assets in 0..40 {
+            for total_shares in (0..40_000).step_by(997) {
+                check_invariants(amount, total_assets, total_shares);
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_vault_mints_at_virtual_price() {
+    assert_eq!(shares_for_deposit(1, 0, 0), Ok(VIRTUAL_SHARES));
+    assert_eq!(assets_for_redeem(VIRTUAL_SHARES, 1, VIRTUAL_SHARES), Ok(1));
+}
+
+#[test]
+fn donation_does_not_steal_deposits() {
+    // The attacker deposits a single unit and donates a large amount.
+    let attacker_shares = shares_for_deposit(1, 0, 0).unwrap();
+    let total_assets = 1 + 1_000_000;
+    let victim_shares = shares_for_deposit(1_000_000, total_assets, attacker_shares).unwrap();
+    assert!(victim_shares > 0);
+    let victim_assets = assets_for_redeem(
+        victim_shares,
+        total_assets + 1_000_000,
+        attacker_shares + victim_shares,
+    )
+    .unwrap();
+    // The victim loses at most a small fraction to rounding.
+    assert!(victim_assets >= 999_000);
+}
+
+#[test]
+fn overflow_is_an_error() {
+    assert_eq!(
+        shares_for_deposit(u64::MAX, 0, u64::MAX),
+        Err(anchor_lang::__private::ErrorCode::MathOverflow.into())
+    );
+}
+
+proptest! {
+    #[test]
+    fn invariants_hold(
+        amount in 0..u64::MAX / 4,
+        total_assets in 0..u64::MAX / 4,
+        total_shares in 0..u64::MAX / 4,
+    ) {
+        check_invariants(amount, total_assets, total_shares);
+    }
+
+    #[test]
+    fn rounding_differs_by_at_most_one(
+        amount in any::<u32>(),
+        total_assets in any::<u32>(),
+        total_shares in any::<u32>(),
+    ) {
+        let (a, ta, ts) = (amount as u64, total_assets as u64, total_shares as u64);
+        let down = shares_for_deposit(a, ta, ts).unwrap();
+        let up = shares_for_withdraw(a, ta, ts).unwrap();
+        prop_assert!(up == down || up == down + 1);
+        let down = assets_for_redeem(a, ta, ts).unwrap();
+        let up = assets_for_mint(a, ta, ts).unwrap();
+        prop_assert!(up == down || up == down + 1);
+    }
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_lang::AccountMetaTemplate;
+use common::*;
+use solana_program::instruction::AccountMeta;
+
+mod common;
+
+#[derive(Accounts)]
+pub struct Vault<'info> {
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub vault: Vault<'info>,
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+}
+
+fn vault_template() -> &'static [AccountMetaTemplate] {
+    &[
+        AccountMetaTemplate::Account {
+            is_signer: false,
+            is_writable: true,
+        },
+        AccountMetaTemplate::Account {
+            is_signer: true,
+            is_writable: false,
+        },
+    ]
+}
+
+#[test]
+fn template_describes_nested_positions() {
+    use __cpi_client_accounts_withdraw::Withdraw;
+    assert_eq!(
+        Withdraw::metas_template(),
+        &[
+            AccountMetaTemplate::Composite(vault_template),
+            AccountMetaTemplate::Account {
+                is_signer: false,
+                is_writable: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn template_raises_flags() {
+    use __cpi_client_accounts_withdraw::Withdraw;
+    let keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let mut metas: Vec<AccountMeta> = keys
+        .iter()
+        .map(|key| AccountMeta::new_readonly(*key, false))
+        .collect();
+    let covered = AccountMetaTemplate::apply(Withdraw::metas_template(), &mut metas);
+    assert_eq!(covered, 3);
+    assert_eq!(
+        metas,
+        vec![
+            AccountMeta::new(keys[0], false),
+            AccountMeta::new_readonly(keys[1], true),
+            AccountMeta::new(keys[2], false),
+        ]
+    );
+}
+
+#[test]
+fn cpi_context_keeps_extra_account_flags() {
+    use __cpi_client_accounts_vault::Vault;
+    let mut accounts: Vec<_> = (0..3)
+        .map(|_| TestAccount::new(ID, vec![]).writable())
+        .collect();
+    let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+    let infos = infos(&mut accounts);
+    let ctx = CpiContext::new(
+        infos[2].clone(),
+        Vault {
+            vault: infos[0].clone(),
+            authority: infos[1].clone(),
+        },
+    )
+    .with_extra(ExtraAccount::Writer(infos[2].clone()));
+    assert_eq!(
+        ctx.to_account_metas(None),
+        vec![
+            AccountMeta::new(keys[0], false),
+            AccountMeta::new_readonly(keys[1], true),
+            AccountMeta::new(keys[2], false),
+        ]
+    );
+}
+
+#[test]
+fn cpi_context_extra_accounts_have_the_flags_of_their_role() {
+    use __cpi_client_accounts_vault::Vault;
+    let mut accounts: Vec<_> = (0..4)
+        .map(|_| TestAccount::new(ID, vec![]).writable())
+        .collect();
+    let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+    let infos = infos(&mut accounts);
+    // The infos are all writable and not signers.
+    let ctx = CpiContext::new(
+        infos[0].clone(),
+        Vault {
+            vault: infos[0].clone(),
+            authority: infos[1].clone(),
+        },
+    )
+    .with_extra(ExtraAccount::Reader(infos[2].clone()))
+    .with_extra(ExtraAccount::Signer(infos[3].clone()))
+    .with_extra(ExtraAccount::WritableSigner(infos[3].clone()));
+    assert_eq!(
+        ctx.to_account_metas(None)[2..],
+        [
+            AccountMeta::new_readonly(keys[2], false),
+            AccountMeta::new_readonly(keys[3], true),
+            AccountMeta::new(keys[3], true),
+        ]
+    );
+    assert_eq!(ctx.to_account_infos().len(), 6);
+}
+
+#[test]
+fn cpi_context_extra_account_roles_override_the_info_flags() {
+    use __cpi_client_accounts_vault::Vault;
+    let mut accounts: Vec<_> = (0..5).map(|_| TestAccount::new(ID, vec![])).collect();
+    // The reader's info is a writable signer, the writer's and the signer's
+    // are read-only and not signers.
+    accounts[2] = accounts[2].clone().signer().writable();
+    let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+    let infos = infos(&mut accounts);
+    let ctx = CpiContext::new(
+        infos[0].clone(),
+        Vault {
+            vault: infos[0].clone(),
+            authority: infos[1].clone(),
+        },
+    )
+    .with_extra(ExtraAccount::Reader(infos[2].clone()))
+    .with_extra(ExtraAccount::Writer(infos[3].clone()))
+    .with_extra(ExtraAccount::Signer(infos[4].clone()));
+    assert_eq!(
+        ctx.to_account_metas(None)[2..],
+        [
+            AccountMeta::new_readonly(keys[2], false),
+            AccountMeta::new(keys[3], false),
+            AccountMeta::new_readonly(keys[4], true),
+        ]
+    );
+    // The infos passed to the callee carry the same flags.
+    let passed = ctx.to_account_infos();
+    assert!(!passed[2].is_signer && !passed[2].is_writable);
+    assert!(!passed[3].is_signer && passed[3].is_writable);
+    assert!(passed[4].is_signer && !passed[4].is_writable);
+}
+
+#[test]
+fn to_account_metas_is_object_safe() {
+    let accounts: Box<dyn ToAccountMetas> = Box::new(__client_accounts_vault::Vault {
+        vault: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+    });
+    assert_eq!(accounts.to_account_metas(None).len(), 2);
+}
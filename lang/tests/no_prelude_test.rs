@@ -0,0 +1,116 @@
+// Everything used by the generated code must be fully qualified, so that
+// programs not importing the prelude compile. Only the types recognized by
+// name by the parser are imported.
+
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::sysvar::clock::Clock;
+use anchor_lang::{
+    Account, AccountLoader, Context, InstructionData, Program, Signer, System, Sysvar,
+    UncheckedAccount,
+};
+use common::*;
+
+mod common;
+
+#[anchor_lang::constant]
+pub const SEED: &[u8] = b"counter";
+
+#[anchor_lang::program]
+pub mod no_prelude {
+    use super::*;
+
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        bump: u8,
+    ) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+        ctx.accounts.counter.bump = bump;
+        Ok(())
+    }
+
+    #[anchor_lang::access_control(ctx.accounts.check())]
+    pub fn increment(
+        ctx: Context<Increment>,
+        by: u64,
+    ) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+        anchor_lang::require!(by > 0, NoPreludeError::Forbidden);
+        ctx.accounts.counter.count += by;
+        anchor_lang::emit!(Incremented { by });
+        Ok(())
+    }
+
+    pub fn close(_ctx: Context<Close>) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(anchor_lang::Accounts)]
+#[instruction(bump: u8)]
+pub struct Initialize<'info> {
+    #[account(init, payer = authority, seeds = [SEED], bump = bump)]
+    pub counter: Account<'info, Counter>,
+    #[account(zero)]
+    pub big: AccountLoader<'info, Big>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(anchor_lang::Accounts)]
+pub struct Increment<'info> {
+    #[account(mut, has_one = authority, seeds = [SEED], bump = counter.bump)]
+    pub counter: Account<'info, Counter>,
+    #[account(constraint = authority.key != &anchor_lang::solana_program::system_program::ID @ NoPreludeError::Forbidden)]
+    pub authority: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> Increment<'info> {
+    fn check(&self) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(anchor_lang::Accounts)]
+pub struct Close<'info> {
+    pub increment: Increment<'info>,
+    #[account(mut, close = receiver, owner = ID)]
+    pub counter: Box<Account<'info, Counter>>,
+    #[account(init, payer = receiver)]
+    pub big: AccountLoader<'info, Big>,
+    #[account(mut, signer, rent_exempt = enforce)]
+    pub receiver: AccountInfo<'info>,
+    #[account(executable, address = anchor_lang::solana_program::system_program::ID)]
+    pub system_program: UncheckedAccount<'info>,
+}
+
+#[anchor_lang::account]
+#[derive(Default)]
+pub struct Counter {
+    pub authority: anchor_lang::solana_program::pubkey::Pubkey,
+    pub count: u64,
+    pub bump: u8,
+}
+
+#[anchor_lang::account(zero_copy)]
+#[derive(Default)]
+pub struct Big {
+    pub data: [u64; 8],
+}
+
+#[anchor_lang::event]
+pub struct Incremented {
+    #[index]
+    pub by: u64,
+}
+
+#[anchor_lang::error]
+pub enum NoPreludeError {
+    #[msg("Forbidden")]
+    Forbidden,
+}
+
+#[test]
+fn program_without_prelude_dispatches() {
+    assert_eq!(instruction::Increment { by: 1 }.data().len(), 16);
+    assert!(entry(&ID, &[], &[]).is_err());
+}
@@ -0,0 +1,68 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[account]
+pub struct Asset {
+    pub freeze_authority: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct Freeze<'info> {
+    #[account(has_one = freeze_authority)]
+    pub asset: Account<'info, Asset>,
+    pub freeze_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Thaw<'info> {
+    #[account(has_one::allow_none = freeze_authority)]
+    pub asset: Account<'info, Asset>,
+    pub freeze_authority: AccountInfo<'info>,
+}
+
+// Validates `Freeze`, and `Thaw` if `allow_none`, against an asset with the
+// given freeze authority and the `signer` account.
+fn try_accounts(
+    freeze_authority: Option<Pubkey>,
+    signer: Pubkey,
+    allow_none: bool,
+) -> anchor_lang::Result<()> {
+    let mut asset_data = vec![];
+    Asset { freeze_authority }
+        .try_serialize(&mut asset_data)
+        .unwrap();
+    let mut accounts = [
+        TestAccount::new(ID, asset_data).lamports(1),
+        TestAccount::new(ID, vec![]).at(signer).signer().lamports(1),
+    ];
+    let accounts = infos(&mut accounts);
+    match allow_none {
+        true => Thaw::try_accounts(&ID, &mut &accounts[..], &[]).map(|_| ()),
+        false => Freeze::try_accounts(&ID, &mut &accounts[..], &[]).map(|_| ()),
+    }
+}
+
+#[test]
+fn optional_key_must_match() {
+    let authority = Pubkey::new_unique();
+    for allow_none in [false, true] {
+        assert_eq!(try_accounts(Some(authority), authority, allow_none), Ok(()));
+        assert_eq!(
+            try_accounts(Some(authority), Pubkey::new_unique(), allow_none),
+            Err(ErrorCode::ConstraintHasOne.into())
+        );
+    }
+}
+
+#[test]
+fn unset_key_fails_unless_allowed() {
+    let signer = Pubkey::new_unique();
+    assert_eq!(
+        try_accounts(None, signer, false),
+        Err(ErrorCode::ConstraintHasOne.into())
+    );
+    assert_eq!(try_accounts(None, signer, true), Ok(()));
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+#[error]
+pub enum ErrorCode {
+    #[msg("The config is missing")]
+    MissingConfig,
+}
+
+fn with_macro(config: Option<u64>) -> std::result::Result<u64, ProgramError> {
+    let config = require_some!(config, MissingConfig);
+    Ok(config + 1)
+}
+
+fn with_macro_path(config: Option<u64>) -> std::result::Result<u64, ProgramError> {
+    let config = require_some!(config, ErrorCode::MissingConfig);
+    Ok(config + 1)
+}
+
+fn with_trait(config: Option<u64>) -> std::result::Result<u64, ProgramError> {
+    let config = config.or_err(ErrorCode::MissingConfig)?;
+    Ok(config + 1)
+}
+
+type Check = fn(Option<u64>) -> std::result::Result<u64, ProgramError>;
+
+#[test]
+fn missing_values_fail_with_the_given_error() {
+    let missing: ProgramError = ErrorCode::MissingConfig.into();
+    let checks: [Check; 3] = [with_macro, with_macro_path, with_trait];
+    for check in checks.iter() {
+        assert_eq!(check(Some(1)), Ok(2));
+        assert_eq!(check(None), Err(missing.clone()));
+    }
+
+    let parsed: std::result::Result<u64, _> = "nan".parse::<u64>();
+    assert_eq!(
+        parsed
+            .or_err(ErrorCode::MissingConfig)
+            .map_err(ProgramError::from),
+        Err(missing)
+    );
+    assert_eq!(
+        Ok::<_, ()>(3)
+            .or_err(ErrorCode::MissingConfig)
+            .map_err(ProgramError::from),
+        Ok(3)
+    );
+}
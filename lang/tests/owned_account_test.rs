@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use common::*;
+
+mod common;
+
+mod native_loader {
+    anchor_lang::solana_program::declare_id!("NativeLoader1111111111111111111111111111111");
+}
+
+#[derive(Accounts)]
+pub struct Builtins<'info> {
+    pub builtin: NativeAccount<'info>,
+    pub sysvar: SysvarAccount<'info>,
+}
+
+#[test]
+fn owner_checks() {
+    let mut builtin = TestAccount::new(native_loader::ID, vec![]).executable();
+    let mut sysvar = TestAccount::new(sysvar::ID, vec![]).at(sysvar::slot_hashes::ID);
+    let (builtin_key, sysvar_key) = (builtin.key, sysvar.key);
+    let (builtin, sysvar) = (builtin.info(), sysvar.info());
+    let accounts = [builtin.clone(), sysvar.clone()];
+    let parsed = Builtins::try_accounts(&ID, &mut &accounts[..], &[]).unwrap();
+    assert_eq!(parsed.builtin.key(), builtin_key);
+    assert_eq!(parsed.sysvar.key(), sysvar_key);
+
+    let swapped = [sysvar, builtin];
+    assert_eq!(
+        Builtins::try_accounts(&ID, &mut &swapped[..], &[]).err(),
+        Some(anchor_lang::__private::ErrorCode::AccountNotNativeOwned.into())
+    );
+    assert_eq!(
+        SysvarAccount::try_from(&swapped[1]).unwrap_err(),
+        anchor_lang::__private::ErrorCode::AccountNotSysvarOwned.into()
+    );
+}
@@ -0,0 +1,138 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::paged_vec::{self, Page, PagedVecHeader};
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use common::*;
+
+mod common;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub name: String,
+}
+
+fn entry(name: &str) -> Entry {
+    Entry {
+        name: name.to_string(),
+    }
+}
+
+// The data of an existing, empty page.
+fn page_data(header: &PagedVecHeader, list: Pubkey, index: u32) -> Vec<u8> {
+    let mut data = Page::<Entry>::discriminator().to_vec();
+    let page = Page::<Entry> {
+        list,
+        index,
+        items: vec![],
+    };
+    data.extend(page.try_to_vec().unwrap());
+    data.resize(header.page_space(), 0);
+    data
+}
+
+#[test]
+fn header_locates_items() {
+    let mut header = PagedVecHeader::new(2, 16).unwrap();
+    assert!(header.is_empty());
+    assert_eq!(header.page_count(), Ok(0));
+    header.len = 3;
+    assert_eq!(header.page_count(), Ok(2));
+    assert_eq!(header.locate(1), Ok((0, 1)));
+    assert_eq!(header.locate(2), Ok((1, 0)));
+    assert_eq!(header.next_page(), Ok(1));
+    assert_eq!(header.page_space(), 8 + 32 + 4 + 4 + 2 * 16);
+}
+
+#[test]
+fn zero_capacity_is_refused() {
+    let err: ProgramError = ErrorCode::PagedVecZeroCapacity.into();
+    assert_eq!(PagedVecHeader::new(0, 16), Err(err.clone()));
+
+    // E.g. the header of an account that was never initialized.
+    let header = PagedVecHeader {
+        len: 1,
+        page_capacity: 0,
+        item_size: 16,
+    };
+    assert_eq!(header.page_count(), Err(err.clone()));
+    assert_eq!(header.next_page(), Err(err.clone()));
+    let list = Pubkey::new_unique();
+    let mut page = TestAccount::new(ID, vec![])
+        .at(paged_vec::page_address(&ID, &list, 0).0)
+        .writable();
+    assert_eq!(
+        paged_vec::get::<Entry>(&ID, &list, &header, &page.info(), 0),
+        Err(err)
+    );
+}
+
+#[test]
+fn push_and_get_items() {
+    let list = Pubkey::new_unique();
+    let mut header = PagedVecHeader::new(2, 16).unwrap();
+    let system_key = solana_program::system_program::ID;
+    let mut page = TestAccount::new(ID, page_data(&header, list, 0))
+        .at(paged_vec::page_address(&ID, &list, 0).0)
+        .writable();
+    let mut payer = TestAccount::new(system_key, vec![]).signer().writable();
+    let mut system_program = TestAccount::native_program(system_key);
+    let (page, payer, system_program) = (page.info(), payer.info(), system_program.info());
+
+    for name in ["a", "bc"].iter() {
+        paged_vec::push(
+            &ID,
+            &list,
+            &mut header,
+            &page,
+            &payer,
+            &system_program,
+            &entry(name),
+        )
+        .unwrap();
+    }
+    assert_eq!(header.len, 2);
+    assert_eq!(
+        paged_vec::get::<Entry>(&ID, &list, &header, &page, 1),
+        Ok(entry("bc"))
+    );
+    assert_eq!(
+        paged_vec::get::<Entry>(&ID, &list, &header, &page, 2),
+        Err(ErrorCode::PagedVecIndexOutOfBounds.into())
+    );
+    let loaded = paged_vec::load_page::<Entry>(&ID, &list, &page).unwrap();
+    assert_eq!(loaded.items, vec![entry("a"), entry("bc")]);
+
+    // The next item belongs on the second page.
+    assert_eq!(
+        paged_vec::push(
+            &ID,
+            &list,
+            &mut header,
+            &page,
+            &payer,
+            &system_program,
+            &entry("d")
+        ),
+        Err(ErrorCode::PagedVecWrongPage.into())
+    );
+    // Items larger than `item_size` are refused.
+    header.len = 1;
+    assert_eq!(
+        paged_vec::push(
+            &ID,
+            &list,
+            &mut header,
+            &page,
+            &payer,
+            &system_program,
+            &entry("a name longer than the item size"),
+        ),
+        Err(ErrorCode::AccountDidNotSerialize.into())
+    );
+
+    // Pages of other lists are refused.
+    assert_eq!(
+        paged_vec::load_page::<Entry>(&ID, &Pubkey::new_unique(), &page).err(),
+        Some(ErrorCode::PagedVecWrongPage.into())
+    );
+}
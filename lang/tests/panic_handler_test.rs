@@ -0,0 +1,64 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+#[program(panic_handler = on_panic)]
+pub mod panic_handler {
+    use super::*;
+
+    pub fn noop(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+
+    pub fn boom(_ctx: Context<Empty>) -> ProgramResult {
+        panic!("boom");
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+fn on_panic<I: std::fmt::Display + ?Sized>(info: &I) {
+    anchor_lang::panic::log_panic(info);
+}
+
+#[test]
+fn program_with_panic_handler_dispatches() {
+    assert_eq!(entry(&ID, &[], &instruction::Noop.data()), Ok(()));
+}
+
+struct Stubs(Arc<Mutex<Vec<String>>>);
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        self.0.lock().unwrap().push(message.to_string());
+    }
+}
+
+#[test]
+fn panicking_handler_logs_program_panicked() {
+    let logs = Arc::new(Mutex::new(vec![]));
+    set_syscall_stubs(Box::new(Stubs(logs.clone())));
+    // On chain, the program's `custom_panic` runs the panic handler. Off
+    // chain, a panic hook stands in for it.
+    std::panic::set_hook(Box::new(|info| on_panic(info)));
+    let result = std::panic::catch_unwind(|| entry(&ID, &[], &instruction::Boom.data()));
+    let _ = std::panic::take_hook();
+    assert!(result.is_err());
+
+    let logs = logs.lock().unwrap();
+    let panicked = logs
+        .iter()
+        .position(|log| log.starts_with("Program panicked: ") && log.contains("boom"))
+        .expect("The panic isn't logged");
+    assert!(logs[panicked].contains("panic_handler_test.rs"));
+    assert_eq!(
+        logs[panicked + 1],
+        ProgramError::from(ErrorCode::ProgramPanicked).to_string()
+    );
+}
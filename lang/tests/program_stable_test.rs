@@ -0,0 +1,99 @@
+use anchor_lang::clock::MockClock;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use common::*;
+
+mod common;
+
+#[derive(Clone)]
+pub struct Callee;
+
+impl anchor_lang::Id for Callee {
+    fn id() -> Pubkey {
+        ID
+    }
+}
+
+impl anchor_lang::AccountDeserialize for Callee {
+    fn try_deserialize(buf: &mut &[u8]) -> std::result::Result<Self, ProgramError> {
+        Callee::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(_buf: &mut &[u8]) -> std::result::Result<Self, ProgramError> {
+        Ok(Callee)
+    }
+}
+
+#[derive(Accounts)]
+pub struct InvokeCallee<'info> {
+    #[account(program::stable = callee_programdata)]
+    pub callee: Program<'info, Callee>,
+    pub callee_programdata: AccountInfo<'info>,
+}
+
+// The upgradeable loader's account of the program `ID`, pointing to
+// `programdata_address`.
+fn upgradeable_program(programdata_address: Pubkey) -> TestAccount {
+    let data = bincode::serialize(&UpgradeableLoaderState::Program {
+        programdata_address,
+    })
+    .unwrap();
+    TestAccount::new(bpf_loader_upgradeable::ID, data)
+        .at(ID)
+        .executable()
+}
+
+#[test]
+fn non_upgradeable_program_is_stable() {
+    let mut callee = TestAccount::program(ID, Pubkey::default());
+    let info = callee.info();
+    let program = Program::<Callee>::try_from(&info).unwrap();
+    assert_eq!(program.programdata_address(), None);
+
+    let mut programdata = TestAccount::new(bpf_loader_upgradeable::ID, vec![]);
+    assert_eq!(program.check_stable(&programdata.info()), Ok(()));
+}
+
+#[test]
+fn programdata_must_match() {
+    let programdata_address = Pubkey::new_unique();
+    let mut callee = upgradeable_program(programdata_address);
+    let info = callee.info();
+    let program = Program::<Callee>::try_from(&info).unwrap();
+    assert_eq!(program.programdata_address(), Some(programdata_address));
+
+    let mut other = TestAccount::new(bpf_loader_upgradeable::ID, vec![]);
+    assert_eq!(
+        program.check_stable(&other.info()),
+        Err(anchor_lang::__private::ErrorCode::AccountNotProgramData.into())
+    );
+}
+
+#[test]
+fn program_upgraded_in_the_current_slot_is_unstable() {
+    let programdata_address = Pubkey::new_unique();
+    let programdata = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 10,
+        upgrade_authority_address: None,
+    })
+    .unwrap();
+    let mut accounts = [
+        upgradeable_program(programdata_address),
+        TestAccount::new(bpf_loader_upgradeable::ID, programdata).at(programdata_address),
+    ];
+    let accounts = infos(&mut accounts);
+    let try_accounts = || InvokeCallee::try_accounts(&ID, &mut &accounts[..], &[]).map(|_| ());
+
+    let clock = MockClock::new(Clock {
+        slot: 10,
+        ..Clock::default()
+    });
+    clock.install();
+    assert_eq!(
+        try_accounts(),
+        Err(anchor_lang::__private::ErrorCode::ConstraintProgramStable.into())
+    );
+    clock.warp_to_slot(11);
+    assert_eq!(try_accounts(), Ok(()));
+    anchor_lang::clock::clear_provider();
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_lang::ResolveAccounts;
+use common::*;
+use std::collections::HashMap;
+
+mod common;
+
+#[account]
+pub struct Market {
+    pub vault: Pubkey,
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(has_one = vault)]
+    pub market: Account<'info, Market>,
+    #[account(has_one = authority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Nested<'info> {
+    pub withdraw: Withdraw<'info>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(has_one = authority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"receipt", authority.key().as_ref()], bump)]
+    pub receipt: UncheckedAccount<'info>,
+}
+
+fn data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn relations_are_resolved_transitively() {
+    let (market, vault, authority) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let accounts: HashMap<Pubkey, Vec<u8>> = vec![
+        (market, data(&Market { vault })),
+        (vault, data(&Vault { authority })),
+    ]
+    .into_iter()
+    .collect();
+    let mut fetch = |address: &Pubkey| -> std::result::Result<_, ProgramError> {
+        Ok(accounts.get(address).cloned())
+    };
+
+    let mut nested = __client_accounts_nested::Nested {
+        withdraw: __client_accounts_withdraw::Withdraw {
+            market,
+            vault: Pubkey::default(),
+            authority: Pubkey::default(),
+        },
+        payer: Pubkey::new_unique(),
+    };
+    nested.resolve_accounts(&ID, &mut fetch).unwrap();
+    assert_eq!(nested.withdraw.vault, vault);
+    assert_eq!(nested.withdraw.authority, authority);
+
+    // Accounts that are already given are kept.
+    let other = Pubkey::new_unique();
+    let mut withdraw = __client_accounts_withdraw::Withdraw {
+        market,
+        vault: other,
+        authority: Pubkey::default(),
+    };
+    withdraw.resolve_accounts(&ID, &mut fetch).unwrap();
+    assert_eq!(withdraw.vault, other);
+    assert_eq!(withdraw.authority, Pubkey::default());
+}
+
+#[test]
+fn fetch_errors_are_returned() {
+    let mut withdraw = __client_accounts_withdraw::Withdraw {
+        market: Pubkey::new_unique(),
+        vault: Pubkey::default(),
+        authority: Pubkey::default(),
+    };
+    let mut fetch = |_: &Pubkey| -> std::result::Result<Option<Vec<u8>>, ProgramError> {
+        Err(ProgramError::AccountDataTooSmall)
+    };
+    assert_eq!(
+        withdraw.resolve_accounts(&ID, &mut fetch),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+
+    // Missing accounts leave the relation unresolved.
+    let mut fetch = |_: &Pubkey| -> std::result::Result<Option<Vec<u8>>, ProgramError> { Ok(None) };
+    withdraw.resolve_accounts(&ID, &mut fetch).unwrap();
+    assert_eq!(withdraw.vault, Pubkey::default());
+}
+
+#[test]
+fn pdas_are_derived_from_resolved_relations() {
+    let (vault, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let vault_data = data(&Vault { authority });
+    let mut fetch = |address: &Pubkey| -> std::result::Result<_, ProgramError> {
+        Ok((*address == vault).then(|| vault_data.clone()))
+    };
+    let mut claim = __client_accounts_claim::Claim {
+        vault,
+        authority: Pubkey::default(),
+        receipt: Pubkey::default(),
+    };
+    claim.resolve_accounts(&ID, &mut fetch).unwrap();
+    assert_eq!(claim.authority, authority);
+    assert_eq!(
+        claim.receipt,
+        Pubkey::find_program_address(&[b"receipt", authority.as_ref()], &ID).0
+    );
+}
@@ -0,0 +1,77 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+const TOKEN_PROGRAM: Pubkey = Pubkey::new_from_array([6; 32]);
+
+#[derive(Accounts)]
+#[remaining_accounts(min = 1, max = 2, owner = TOKEN_PROGRAM, writable)]
+pub struct Distribute<'info> {
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[remaining_accounts(max = 0)]
+pub struct Exact<'info> {
+    pub authority: Signer<'info>,
+}
+
+// The authority, followed by remaining accounts with the given owners and
+// writability.
+fn check(
+    remaining: &[(Pubkey, bool)],
+    try_accounts: impl FnOnce(&mut &[AccountInfo]) -> anchor_lang::Result<()>,
+) -> anchor_lang::Result<()> {
+    let authority = TestAccount::new(solana_program::system_program::ID, vec![]).signer();
+    let mut accounts = vec![authority.lamports(1)];
+    accounts.extend(remaining.iter().map(|(owner, writable)| {
+        let mut account = TestAccount::new(*owner, vec![]).lamports(1);
+        account.is_writable = *writable;
+        account
+    }));
+    let accounts = infos(&mut accounts);
+    try_accounts(&mut &accounts[..])
+}
+
+fn distribute(remaining: &[(Pubkey, bool)]) -> anchor_lang::Result<()> {
+    check(remaining, |accounts| {
+        Distribute::try_accounts(&ID, accounts, &[]).map(|_| ())
+    })
+}
+
+fn exact(remaining: &[(Pubkey, bool)]) -> anchor_lang::Result<()> {
+    check(remaining, |accounts| {
+        Exact::try_accounts(&ID, accounts, &[]).map(|_| ())
+    })
+}
+
+#[test]
+fn remaining_accounts_are_checked() {
+    let token = (TOKEN_PROGRAM, true);
+    assert_eq!(distribute(&[token]), Ok(()));
+    assert_eq!(distribute(&[token, token]), Ok(()));
+    assert_eq!(
+        distribute(&[]),
+        Err(ErrorCode::ConstraintRemainingAccountsCount.into())
+    );
+    assert_eq!(
+        distribute(&[token, token, token]),
+        Err(ErrorCode::ConstraintRemainingAccountsCount.into())
+    );
+    assert_eq!(
+        distribute(&[token, (ID, true)]),
+        Err(ErrorCode::ConstraintRemainingAccountsOwner.into())
+    );
+    assert_eq!(
+        distribute(&[(TOKEN_PROGRAM, false)]),
+        Err(ErrorCode::ConstraintRemainingAccountsMut.into())
+    );
+
+    assert_eq!(exact(&[]), Ok(()));
+    assert_eq!(
+        exact(&[token]),
+        Err(ErrorCode::ConstraintRemainingAccountsCount.into())
+    );
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use common::*;
+use solana_program::instruction::Instruction;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::system_instruction::SystemInstruction;
+
+mod common;
+
+#[derive(Accounts)]
+pub struct TopUp<'info> {
+    #[account(mut, rent_exempt = top_up(payer))]
+    pub vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Reads the default rent and executes system transfers.
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        solana_program::entrypoint::SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if let Ok(SystemInstruction::Transfer { lamports }) =
+            bincode::deserialize(&instruction.data)
+        {
+            **account_infos[0].try_borrow_mut_lamports()? -= lamports;
+            **account_infos[1].try_borrow_mut_lamports()? += lamports;
+        }
+        Ok(())
+    }
+}
+
+const DATA_LEN: usize = 100;
+
+// Tops up a vault holding `vault_lamports`, returning the vault's and the
+// payer's lamports after.
+fn top_up(vault_lamports: u64) -> (u64, u64) {
+    set_syscall_stubs(Box::new(Stubs));
+    let system_program = solana_program::system_program::ID;
+    let mut accounts = [
+        TestAccount::new(ID, vec![0; DATA_LEN])
+            .writable()
+            .lamports(vault_lamports),
+        TestAccount::new(system_program, vec![])
+            .signer()
+            .writable()
+            .lamports(10_000_000),
+        TestAccount::program(system_program, solana_program::bpf_loader::ID),
+    ];
+    let accounts = infos(&mut accounts);
+    let accounts = TopUp::try_accounts(&ID, &mut &accounts[..], &[]).unwrap();
+    assert!(accounts.vault.is_rent_exempt().unwrap());
+    assert_eq!(accounts.vault.lamports_needed_for_exemption().unwrap(), 0);
+    (accounts.vault.lamports(), accounts.payer.lamports())
+}
+
+#[test]
+fn top_up_transfers_the_shortfall() {
+    let minimum = Rent::default().minimum_balance(DATA_LEN);
+    assert_eq!(top_up(minimum - 10), (minimum, 10_000_000 - 10));
+    assert_eq!(top_up(0), (minimum, 10_000_000 - minimum));
+    assert_eq!(top_up(minimum + 10), (minimum + 10, 10_000_000));
+}
+
+#[test]
+fn shortfall_of_an_account() {
+    let rent = Rent::default();
+    let mut account = TestAccount::new(ID, vec![0; DATA_LEN]).writable();
+    let info = account.info();
+    assert_eq!(
+        anchor_lang::rent::lamports_needed_for_exemption(&rent, &info),
+        Ok(rent.minimum_balance(DATA_LEN))
+    );
+    **info.lamports.borrow_mut() = rent.minimum_balance(DATA_LEN) + 1;
+    assert_eq!(
+        anchor_lang::rent::lamports_needed_for_exemption(&rent, &info),
+        Ok(0)
+    );
+}
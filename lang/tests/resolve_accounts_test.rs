@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_lang::ResolveAccounts;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[derive(Accounts)]
+pub struct Create<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct Nested<'info> {
+    pub create: Create<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct Open<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"entry", vault.key().as_ref()], bump)]
+    pub entry: UncheckedAccount<'info>,
+    #[account(seeds = [b"vault", hash(authority.key().as_ref())], bump)]
+    pub vault: UncheckedAccount<'info>,
+    #[account(seeds = [b"indexed", le(index)], bump)]
+    pub indexed: UncheckedAccount<'info>,
+}
+
+// Resolves the accounts without any account to read.
+fn resolve(accounts: &mut impl ResolveAccounts) {
+    let mut fetch = |_: &Pubkey| -> std::result::Result<Option<Vec<u8>>, ProgramError> { Ok(None) };
+    accounts.resolve_accounts(&ID, &mut fetch).unwrap();
+}
+
+#[test]
+fn sysvars_and_programs_are_resolved() {
+    let payer = Pubkey::new_unique();
+    let mut nested = __client_accounts_nested::Nested {
+        create: __client_accounts_create::Create {
+            payer,
+            system_program: Pubkey::default(),
+            rent: Pubkey::default(),
+            clock: Pubkey::default(),
+        },
+        authority: Pubkey::default(),
+    };
+    resolve(&mut nested);
+    assert_eq!(nested.create.payer, payer);
+    assert_eq!(nested.create.system_program, System::id());
+    assert_eq!(
+        nested.create.rent,
+        anchor_lang::solana_program::sysvar::rent::ID
+    );
+    assert_eq!(
+        nested.create.clock,
+        anchor_lang::solana_program::sysvar::clock::ID
+    );
+    // Accounts that can't be derived stay unset.
+    assert_eq!(nested.authority, Pubkey::default());
+}
+
+#[test]
+fn given_accounts_are_kept() {
+    let rent = Pubkey::new_unique();
+    let mut create = __client_accounts_create::Create {
+        payer: Pubkey::new_unique(),
+        system_program: Pubkey::default(),
+        rent,
+        clock: Pubkey::default(),
+    };
+    resolve(&mut create);
+    assert_eq!(create.rent, rent);
+}
+
+#[test]
+fn pdas_are_resolved_from_the_other_accounts() {
+    let authority = Pubkey::new_unique();
+    let mut open = __client_accounts_open::Open {
+        authority: Pubkey::default(),
+        entry: Pubkey::default(),
+        vault: Pubkey::default(),
+        indexed: Pubkey::default(),
+    };
+    resolve(&mut open);
+    assert_eq!(open.vault, Pubkey::default());
+
+    open.authority = authority;
+    resolve(&mut open);
+    let hash = anchor_lang::seeds::hash(&[authority.as_ref()]);
+    let vault = Pubkey::find_program_address(&[b"vault", &hash[..]], &ID).0;
+    assert_eq!(open.vault, vault);
+    assert_eq!(
+        open.entry,
+        Pubkey::find_program_address(&[b"entry", vault.as_ref()], &ID).0
+    );
+    // Seeds reading instruction args can't be derived.
+    assert_eq!(open.indexed, Pubkey::default());
+}
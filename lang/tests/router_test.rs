@@ -0,0 +1,60 @@
+#![cfg(feature = "router")]
+
+use anchor_lang::prelude::*;
+use anchor_lang::router::{self, Router};
+use common::*;
+
+mod common;
+
+#[program]
+pub mod front {
+    use super::*;
+
+    pub fn initialize(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+
+    pub fn fallback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        Router::new()
+            .handler("deposit", deposit)
+            .dispatch(program_id, accounts, data)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+fn deposit(program_id: &Pubkey, accounts: &[AccountInfo], ix_data: &[u8]) -> ProgramResult {
+    router::handle(program_id, accounts, ix_data, |_ctx: Context<Empty>| {
+        let amount = u64::try_from_slice(ix_data)?;
+        if amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    })
+}
+
+fn ix_data(name: &str, amount: u64) -> Vec<u8> {
+    let preimage = format!("global:{}", name);
+    let mut data =
+        anchor_lang::solana_program::hash::hash(preimage.as_bytes()).to_bytes()[..8].to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[test]
+fn routes_registered_handlers() {
+    assert_eq!(entry(&ID, &[], &ix_data("deposit", 1)), Ok(()));
+    assert_eq!(
+        entry(&ID, &[], &ix_data("deposit", 0)),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn unrouted_instruction() {
+    assert_eq!(
+        entry(&ID, &[], &ix_data("withdraw", 1)),
+        Err(anchor_lang::__private::ErrorCode::InstructionFallbackNotFound.into())
+    );
+}
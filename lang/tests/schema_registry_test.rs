@@ -0,0 +1,97 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::schema_registry::{self, ProgramSchema, SchemaDrift};
+use anchor_lang::{AccountDeserialize, InstructionData};
+use common::*;
+use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+
+mod common;
+
+#[program(schema_registry)]
+pub mod indexed {
+    use super::*;
+
+    pub fn set_count(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn upgrade_authority_publishes_the_schema() {
+    let idl_hash = schema_registry::idl_hash(br#"{"version":"0.1.0","name":"indexed"}"#);
+    let mut authority = TestAccount::new(solana_program::system_program::ID, vec![])
+        .signer()
+        .writable()
+        .lamports(1);
+    let program_data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 42,
+        upgrade_authority_address: Some(authority.key),
+    })
+    .unwrap();
+    let mut program_data = TestAccount::new(bpf_loader_upgradeable::ID, program_data)
+        .at(schema_registry::program_data_address(&ID))
+        .lamports(1);
+    // The record is allocated and assigned up front, as creating it needs
+    // the system program.
+    let mut schema = TestAccount::new(ID, vec![0; schema_registry::SCHEMA_ACCOUNT_LEN])
+        .at(schema_registry::schema_address(&ID).0)
+        .writable()
+        .lamports(10);
+    let (schema, authority, program_data) = (schema.info(), authority.info(), program_data.info());
+    let data = instruction::SchemaPublish {
+        idl_hash,
+        event_schema_version: 2,
+    }
+    .data();
+
+    // Only the upgrade authority publishes.
+    let mut other = authority.clone();
+    let other_key = Pubkey::new_unique();
+    other.key = &other_key;
+    let accounts = [
+        schema.clone(),
+        other,
+        program_data.clone(),
+        authority.clone(),
+    ];
+    assert_eq!(
+        entry(&ID, &accounts, &data),
+        Err(ErrorCode::SchemaAuthorityMismatch.into())
+    );
+
+    let accounts = [schema.clone(), authority.clone(), program_data, authority];
+    assert_eq!(entry(&ID, &accounts, &data), Ok(()));
+    let record =
+        ProgramSchema::try_deserialize(&mut &schema.try_borrow_data().unwrap()[..]).unwrap();
+    assert_eq!(
+        record,
+        ProgramSchema {
+            idl_hash,
+            event_schema_version: 2,
+            deployment_slot: 42,
+        }
+    );
+
+    assert_eq!(record.check(&idl_hash, 2, 42), Ok(()));
+    assert_eq!(
+        record.check(&idl_hash, 2, 50),
+        Err(SchemaDrift::Stale {
+            published: 42,
+            deployed: 50
+        })
+    );
+    assert_eq!(
+        record.check(&schema_registry::idl_hash(b"{}"), 2, 42),
+        Err(SchemaDrift::IdlHash)
+    );
+    assert_eq!(
+        record.check(&idl_hash, 3, 42),
+        Err(SchemaDrift::EventSchemaVersion {
+            published: 2,
+            expected: 3
+        })
+    );
+}
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_lang::AccountBumps;
+use common::*;
+
+mod common;
+
+#[derive(Accounts)]
+#[instruction(order_id: u64, epoch: i16)]
+pub struct Fill<'info> {
+    #[account(seeds = [b"order", le(order_id), be(epoch), le(7u128)], bump)]
+    pub order: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, edition: u32)]
+pub struct Publish<'info> {
+    #[account(seeds = [b"listing", hash(name.as_bytes(), le(edition))], bump)]
+    pub listing: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: AccountInfo<'info>,
+    pub inner: Fill<'info>,
+}
+
+// Stands in for `invoke_signed`.
+fn signer_key(signers_seeds: &[&[&[u8]]]) -> Pubkey {
+    Pubkey::create_program_address(signers_seeds[0], &ID).unwrap()
+}
+
+#[test]
+fn int_seeds() {
+    let (order_id, epoch) = (42u64, -3i16);
+    let seeds: &[&[u8]] = &[
+        b"order",
+        seed_bytes!(order_id),
+        seed_bytes!(epoch, be),
+        seed_bytes!(7u128, le),
+    ];
+    let (order_key, _) = Pubkey::find_program_address(seeds, &ID);
+
+    let mut order = TestAccount::new(Pubkey::default(), vec![]).at(order_key);
+    let accounts = [order.info()];
+    let ix_data = [&order_id.to_le_bytes()[..], &epoch.to_le_bytes()[..]].concat();
+    assert!(Fill::try_accounts(&ID, &mut &accounts[..], &ix_data).is_ok());
+
+    let other_epoch = [&order_id.to_le_bytes()[..], &(epoch + 1).to_le_bytes()[..]].concat();
+    assert_eq!(
+        Fill::try_accounts(&ID, &mut &accounts[..], &other_epoch).err(),
+        Some(anchor_lang::__private::ErrorCode::ConstraintSeeds.into())
+    );
+}
+
+#[test]
+fn hashed_seeds() {
+    assert_ne!(
+        anchor_lang::seeds::hash(&[b"ab", b"c"]),
+        anchor_lang::seeds::hash(&[b"a", b"bc"])
+    );
+
+    let name = "a listing name longer than thirty-two bytes".to_string();
+    let edition = 2u32;
+    let seeds: &[&[u8]] = &[
+        b"listing",
+        &anchor_lang::seeds::hash(&[name.as_bytes(), &edition.to_le_bytes()]),
+    ];
+    let (listing_key, _) = Pubkey::find_program_address(seeds, &ID);
+
+    let mut listing = TestAccount::new(Pubkey::default(), vec![]).at(listing_key);
+    let accounts = [listing.info()];
+    let ix_data = (name.clone(), edition).try_to_vec().unwrap();
+    assert!(Publish::try_accounts(&ID, &mut &accounts[..], &ix_data).is_ok());
+
+    let other_edition = (name, edition + 1).try_to_vec().unwrap();
+    assert_eq!(
+        Publish::try_accounts(&ID, &mut &accounts[..], &other_edition).err(),
+        Some(anchor_lang::__private::ErrorCode::ConstraintSeeds.into())
+    );
+}
+
+#[test]
+fn bumps_sign_for_accounts() {
+    let (order_id, epoch) = (42u64, -3i16);
+    let (vault_key, vault_bump) = Pubkey::find_program_address(&[b"vault"], &ID);
+    let (order_key, order_bump) = Pubkey::find_program_address(
+        &[
+            b"order",
+            seed_bytes!(order_id),
+            seed_bytes!(epoch, be),
+            seed_bytes!(7u128, le),
+        ],
+        &ID,
+    );
+
+    let mut accounts =
+        [vault_key, order_key].map(|key| TestAccount::new(Pubkey::default(), vec![]).at(key));
+    let accounts = infos(&mut accounts);
+    let ix_data = [&order_id.to_le_bytes()[..], &epoch.to_le_bytes()[..]].concat();
+    let mut remaining = &accounts[..];
+    let mut bumps = WithdrawBumps::default();
+    let mut withdraw =
+        Withdraw::try_accounts_with_bumps(&ID, &mut remaining, &ix_data, &mut bumps).unwrap();
+    assert_eq!(bumps.vault, vault_bump);
+    assert_eq!(bumps.inner.order, order_bump);
+    assert_eq!(bumps.get("inner.order"), Some(order_bump));
+    assert_eq!(bumps.get("inner.missing"), None);
+
+    let ctx = Context::new(&ID, &mut withdraw, remaining).with_bumps(bumps);
+    let seeds = ctx.signer_seeds_for("vault", &[b"vault"]).unwrap();
+    assert_eq!(seeds.bump(), vault_bump);
+    assert_eq!(signer_key(&[&seeds]), vault_key);
+    assert_eq!(
+        ctx.signer_seeds_for("missing", &[b"vault"]).err(),
+        Some(anchor_lang::__private::ErrorCode::BumpNotFound.into())
+    );
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod simulation_only {
+    use super::*;
+
+    #[simulation_only]
+    pub fn aggregate(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+
+    pub fn regular(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn simulation_only_ix_is_not_dispatched() {
+    assert_eq!(
+        entry(&ID, &[], &instruction::Aggregate.data()),
+        Err(anchor_lang::__private::ErrorCode::InstructionSimulationOnly.into())
+    );
+    assert_eq!(entry(&ID, &[], &instruction::Regular.data()), Ok(()));
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod skip_exit {
+    use super::*;
+
+    #[skip_exit]
+    pub fn query(ctx: Context<Query>, expected: u64) -> ProgramResult {
+        if ctx.accounts.counter.count != expected {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Counter {
+    pub count: u64,
+}
+
+#[derive(Accounts)]
+pub struct Query<'info> {
+    pub counter: Account<'info, Counter>,
+    pub auth: Auth<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Auth<'info> {
+    pub authority: Signer<'info>,
+}
+
+fn assert_read_only<T: anchor_lang::ReadOnlyAccounts>() {}
+
+#[test]
+fn read_only_accounts() {
+    assert_read_only::<Query>();
+    assert_read_only::<Auth>();
+}
+
+#[test]
+fn skip_exit_handler_runs() {
+    let mut counter_data = vec![];
+    Counter { count: 3 }
+        .try_serialize(&mut counter_data)
+        .unwrap();
+    let mut accounts = [
+        TestAccount::new(ID, counter_data).lamports(1),
+        TestAccount::new(solana_program::system_program::ID, vec![])
+            .signer()
+            .lamports(1),
+    ];
+    let accounts = infos(&mut accounts);
+    assert_eq!(
+        entry(&ID, &accounts, &instruction::Query { expected: 3 }.data()),
+        Ok(())
+    );
+    assert_eq!(
+        entry(&ID, &accounts, &instruction::Query { expected: 4 }.data()),
+        Err(ProgramError::InvalidArgument)
+    );
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{AccountSnapshot, Discriminator};
+use common::*;
+
+mod common;
+
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct Counter {
+    pub count: u64,
+}
+
+#[account(zero_copy)]
+#[derive(Debug, PartialEq)]
+pub struct Totals {
+    pub total: u64,
+}
+
+#[derive(Accounts)]
+#[accounts(snapshot)]
+pub struct Increment<'info> {
+    #[account(mut)]
+    pub counter: Box<Account<'info, Counter>>,
+    pub totals: AccountLoader<'info, Totals>,
+    pub auth: Auth<'info>,
+}
+
+#[derive(Accounts)]
+#[accounts(snapshot)]
+pub struct Auth<'info> {
+    pub authority: Signer<'info>,
+}
+
+#[test]
+fn snapshots_capture_keys_lamports_and_values() {
+    let mut counter_data = vec![];
+    Counter { count: 1 }
+        .try_serialize(&mut counter_data)
+        .unwrap();
+    let mut totals_data = Totals::discriminator().to_vec();
+    totals_data.extend_from_slice(&7u64.to_le_bytes());
+    let mut accounts = [
+        TestAccount::new(ID, counter_data).writable().lamports(1),
+        TestAccount::new(ID, totals_data).lamports(2),
+        TestAccount::new(solana_program::system_program::ID, vec![])
+            .signer()
+            .lamports(3),
+    ];
+    let (counter_key, totals_key, authority_key) =
+        (accounts[0].key, accounts[1].key, accounts[2].key);
+    let accounts = infos(&mut accounts);
+    let mut increment = Increment::try_accounts(&ID, &mut &accounts[..], &[]).unwrap();
+
+    let before = IncrementSnapshot::from(&increment);
+    assert_eq!(
+        before,
+        IncrementSnapshot {
+            counter: AccountSnapshot {
+                key: counter_key,
+                lamports: 1,
+                value: Counter { count: 1 },
+            },
+            totals: AccountSnapshot {
+                key: totals_key,
+                lamports: 2,
+                value: Some(Totals { total: 7 }),
+            },
+            auth: AuthSnapshot {
+                authority: AccountSnapshot {
+                    key: authority_key,
+                    lamports: 3,
+                    value: (),
+                },
+            },
+        }
+    );
+
+    increment.counter.count += 1;
+    let after = IncrementSnapshot::from(&increment);
+    assert_ne!(before, after);
+    assert_eq!(after.counter.value, Counter { count: 2 });
+    assert_eq!(after.totals, before.totals);
+    assert_eq!(after.auth, before.auth);
+}
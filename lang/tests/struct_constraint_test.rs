@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[account]
+pub struct Vault {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[error]
+pub enum ErrorCode {
+    MintMismatch,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+#[accounts(constraint = from.mint == to.mint @ ErrorCode::MintMismatch)]
+#[accounts(constraint = from.amount >= amount)]
+pub struct Move<'info> {
+    pub from: Account<'info, Vault>,
+    pub to: Account<'info, Vault>,
+}
+
+fn vault_data(mint: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![];
+    Vault { mint, amount }.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn try_move(from: Vault, to: Vault, amount: u64) -> anchor_lang::Result<()> {
+    let mut accounts = [
+        TestAccount::new(ID, vault_data(from.mint, from.amount)).lamports(1),
+        TestAccount::new(ID, vault_data(to.mint, to.amount)).lamports(1),
+    ];
+    let accounts = infos(&mut accounts);
+    Move::try_accounts(&ID, &mut &accounts[..], &amount.to_le_bytes()).map(|_| ())
+}
+
+#[test]
+fn struct_constraints_are_checked() {
+    let mint = Pubkey::new_unique();
+    let vault = |mint, amount| Vault { mint, amount };
+    assert_eq!(try_move(vault(mint, 5), vault(mint, 0), 5), Ok(()));
+    assert_eq!(
+        try_move(vault(mint, 5), vault(Pubkey::new_unique(), 0), 5),
+        Err(ErrorCode::MintMismatch.into())
+    );
+    assert_eq!(
+        try_move(vault(mint, 5), vault(mint, 0), 6),
+        Err(anchor_lang::__private::ErrorCode::ConstraintRaw.into())
+    );
+}
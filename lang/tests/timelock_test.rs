@@ -0,0 +1,163 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::timelock::{
+    action_hash, queue_account_metas, queue_instruction, timelock_address, Timelock,
+};
+use anchor_lang::InstructionData;
+use common::*;
+use solana_program::instruction::Instruction;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+mod common;
+
+const NOW: i64 = 1_000;
+
+#[program(timelock)]
+pub mod timelock {
+    use super::*;
+
+    pub fn set_fee(ctx: Context<SetFee>, fee: u64) -> ProgramResult {
+        ctx.accounts.config.fee = fee;
+        Ok(())
+    }
+
+    pub fn set_min_fee(ctx: Context<SetFee>, min_fee: u64) -> ProgramResult {
+        ctx.accounts.config.min_fee = min_fee;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Config {
+    pub timelock: Pubkey,
+    pub fee: u64,
+    pub min_fee: u64,
+}
+
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    #[account(mut)]
+    pub config: Account<'info, Config>,
+    #[account(mut, timelock = config.timelock, min_delay = 60)]
+    pub timelock: UncheckedAccount<'info>,
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        solana_program::entrypoint::SUCCESS
+    }
+}
+
+fn set_fee_action(fee: u64) -> [u8; 32] {
+    action_hash(&ID, &instruction::SetFee { fee }.data())
+}
+
+#[test]
+fn queue_instruction_binds_the_action_to_its_program() {
+    let authority = Pubkey::new_unique();
+    let action = Instruction::new_with_bytes(ID, &instruction::SetFee { fee: 2 }.data(), vec![]);
+    assert_eq!(
+        queue_instruction(&authority, &action),
+        Instruction::new_with_bytes(
+            ID,
+            &instruction::TimelockQueue {
+                action: set_fee_action(2),
+            }
+            .data(),
+            queue_account_metas(&ID, &authority),
+        )
+    );
+    assert_ne!(
+        action_hash(&Pubkey::new_unique(), &action.data),
+        set_fee_action(2)
+    );
+}
+
+#[test]
+fn queued_action_executes_once_after_delay() {
+    set_syscall_stubs(Box::new(Stubs));
+    let system_program = solana_program::system_program::ID;
+    let mut authority = TestAccount::new(system_program, vec![])
+        .signer()
+        .lamports(1);
+    let authority_key = authority.key;
+    let timelock_key = timelock_address(&ID, &authority_key).0;
+    let mut timelock = TestAccount::new(ID, vec![0; 72])
+        .at(timelock_key)
+        .writable()
+        .lamports(1);
+    let mut config_data = vec![];
+    Config {
+        timelock: timelock_key,
+        fee: 1,
+        min_fee: 1,
+    }
+    .try_serialize(&mut config_data)
+    .unwrap();
+    let mut config = TestAccount::new(ID, config_data).writable().lamports(1);
+    let (timelock, authority, config) = (timelock.info(), authority.info(), config.info());
+    let queue = |fee: u64| {
+        let data = instruction::TimelockQueue {
+            action: set_fee_action(fee),
+        }
+        .data();
+        entry(
+            &ID,
+            &[timelock.clone(), authority.clone(), authority.clone()],
+            &data,
+        )
+    };
+    let set_fee = |fee: u64| {
+        let data = instruction::SetFee { fee }.data();
+        entry(&ID, &[config.clone(), timelock.clone()], &data)
+    };
+    let set_min_fee = |min_fee: u64| {
+        let data = instruction::SetMinFee { min_fee }.data();
+        entry(&ID, &[config.clone(), timelock.clone()], &data)
+    };
+    let fee = || {
+        Config::try_deserialize(&mut &config.try_borrow_data().unwrap()[..])
+            .unwrap()
+            .fee
+    };
+
+    assert_eq!(set_fee(2), Err(ErrorCode::ConstraintTimelock.into()));
+    assert_eq!(queue(2), Ok(()));
+    assert_eq!(queue(3), Err(ErrorCode::TimelockActionPending.into()));
+    assert_eq!(
+        Timelock::try_from_account(&ID, &timelock).unwrap(),
+        Timelock {
+            authority: authority_key,
+            action: Some(set_fee_action(2)),
+            queued_at: NOW,
+        }
+    );
+    assert_eq!(set_fee(2), Err(ErrorCode::TimelockNotElapsed.into()));
+
+    // Pretend the action was queued a minute ago.
+    timelock.try_borrow_mut_data().unwrap()[64..].copy_from_slice(&(NOW - 60).to_le_bytes());
+    assert_eq!(set_fee(3), Err(ErrorCode::ConstraintTimelock.into()));
+    // Instructions sharing the accounts struct execute other actions.
+    assert_eq!(set_min_fee(2), Err(ErrorCode::ConstraintTimelock.into()));
+    assert_eq!(set_fee(2), Ok(()));
+    assert_eq!(fee(), 2);
+    assert_eq!(set_fee(2), Err(ErrorCode::ConstraintTimelock.into()));
+
+    // Cancelled actions can't be executed.
+    assert_eq!(queue(4), Ok(()));
+    timelock.try_borrow_mut_data().unwrap()[64..].copy_from_slice(&(NOW - 60).to_le_bytes());
+    let cancel = instruction::TimelockCancel.data();
+    assert_eq!(
+        entry(&ID, &[timelock.clone(), authority.clone()], &cancel),
+        Ok(())
+    );
+    assert_eq!(set_fee(4), Err(ErrorCode::ConstraintTimelock.into()));
+    assert_eq!(fee(), 2);
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod trailing_args {
+    use super::*;
+
+    pub fn transfer(_ctx: Context<Empty>, amount: u64) -> ProgramResult {
+        if amount != 5 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    pub fn ping(_ctx: Context<Empty>) -> ProgramResult {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn trailing_bytes_are_ignored_by_default() {
+    // E.g. an arg appended by a newer client.
+    let mut data = instruction::Transfer { amount: 5 }.data();
+    data.extend([1, 2, 3].iter());
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+
+    let mut data = instruction::Ping.data();
+    data.push(0);
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod transfer_hook {
+    use super::*;
+
+    #[transfer_hook]
+    pub fn execute(_ctx: Context<Empty>, amount: u64) -> ProgramResult {
+        assert_eq!(amount, 5);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn transfer_hook_uses_interface_discriminator() {
+    let mut data = anchor_lang::solana_program::hash::hash(b"spl-transfer-hook-interface:execute")
+        .to_bytes()[..8]
+        .to_vec();
+    data.extend_from_slice(&5u64.to_le_bytes());
+    assert_eq!(instruction::Execute { amount: 5 }.data(), data);
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct Pool {
+    pub reserves: u64,
+    #[transient]
+    pub price: Option<u64>,
+}
+
+#[test]
+fn transient_fields_are_not_serialized() {
+    let pool = Pool {
+        reserves: 1,
+        price: Some(2),
+    };
+    let mut data = vec![];
+    pool.try_serialize(&mut data).unwrap();
+    assert_eq!(data.len(), 8 + 8);
+
+    let pool = Pool::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(
+        pool,
+        Pool {
+            reserves: 1,
+            price: None,
+        }
+    );
+}
@@ -0,0 +1,104 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[test]
+fn lamports_use_checked_arithmetic() {
+    let mut from = TestAccount::new(ID, vec![]).writable().lamports(10);
+    let mut to = TestAccount::new(ID, vec![])
+        .writable()
+        .lamports(u64::MAX - 5);
+    let from = UncheckedAccount::try_from(from.info());
+    let to = to.info();
+
+    let lamports = from.unchecked_lamports();
+    assert_eq!(lamports.sub(11), Err(ProgramError::InsufficientFunds));
+    assert_eq!(lamports.add(u64::MAX), Err(ErrorCode::MathOverflow.into()));
+    assert_eq!(
+        lamports.transfer_to(&to, 6),
+        Err(ErrorCode::MathOverflow.into())
+    );
+    assert_eq!(lamports.get(), Ok(10));
+
+    lamports.transfer_to(&to, 5).unwrap();
+    lamports.add(1).unwrap();
+    assert_eq!(lamports.get(), Ok(6));
+    assert_eq!(to.lamports(), u64::MAX);
+}
+
+#[test]
+fn lamports_of_read_only_accounts_cannot_change() {
+    let mut account = TestAccount::new(ID, vec![]).lamports(10);
+    let account = UncheckedAccount::try_from(account.info());
+    assert_eq!(
+        account.unchecked_lamports().sub(1),
+        Err(ErrorCode::AccountNotMutable.into())
+    );
+    assert_eq!(account.unchecked_lamports().get(), Ok(10));
+}
+
+#[test]
+fn assign_requires_zeroed_data() {
+    let owner = Pubkey::new_unique();
+    let mut account = TestAccount::new(ID, vec![0, 1]).writable().lamports(1);
+    let account = UncheckedAccount::try_from(account.info());
+    assert_eq!(
+        account.unchecked_assign(&owner),
+        Err(ErrorCode::AccountNotZeroed.into())
+    );
+    assert_eq!(account.owner, &ID);
+
+    account.try_borrow_mut_data().unwrap()[1] = 0;
+    account.unchecked_assign(&owner).unwrap();
+    assert_eq!(account.owner, &owner);
+}
+
+// Serializes a single writable account with `data` the way the runtime
+// passes it to programs, so that its data can be resized.
+#[cfg(feature = "solana-1-9")]
+fn program_input(key: &Pubkey, data: &[u8]) -> Vec<u8> {
+    use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+    let mut input = vec![];
+    input.extend_from_slice(&1u64.to_le_bytes());
+    input.extend_from_slice(&[u8::MAX, 0, 1, 0]);
+    input.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    input.extend_from_slice(key.as_ref());
+    input.extend_from_slice(ID.as_ref());
+    input.extend_from_slice(&1u64.to_le_bytes());
+    input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    input.extend_from_slice(data);
+    let alignment = (8 - data.len() % 8) % 8;
+    input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE + alignment, 0);
+    input.extend_from_slice(&0u64.to_le_bytes());
+    input.extend_from_slice(&0u64.to_le_bytes());
+    input.extend_from_slice(ID.as_ref());
+    input
+}
+
+#[test]
+#[cfg(feature = "solana-1-9")]
+fn realloc_is_bounded_by_the_original_length() {
+    use solana_program::entrypoint::{deserialize, MAX_PERMITTED_DATA_INCREASE};
+
+    let key = Pubkey::new_unique();
+    let mut input = program_input(&key, &[1, 2, 3]);
+    let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr()) };
+    let account = UncheckedAccount::try_from(accounts[0].clone());
+
+    account.unchecked_realloc(1).unwrap();
+    assert_eq!(&account.try_borrow_data().unwrap()[..], &[1]);
+
+    let max = 3 + MAX_PERMITTED_DATA_INCREASE;
+    assert_eq!(
+        account.unchecked_realloc(max + 1),
+        Err(ErrorCode::AccountReallocTooLarge.into())
+    );
+    account.unchecked_realloc(max).unwrap();
+    let data = account.try_borrow_data().unwrap();
+    assert_eq!(data.len(), max);
+    assert_eq!(data[0], 1);
+    assert!(data[1..].iter().all(|byte| *byte == 0));
+}
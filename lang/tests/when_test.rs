@@ -0,0 +1,58 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+
+mod common;
+
+#[derive(Accounts)]
+#[instruction(use_escrow: bool)]
+pub struct Deposit<'info> {
+    #[account(when = use_escrow, seeds = [b"escrow"], bump)]
+    pub escrow: UncheckedAccount<'info>,
+    #[account(when = !use_escrow, signer)]
+    pub owner: AccountInfo<'info>,
+}
+
+fn try_deposit(escrow: Pubkey, owner_signed: bool, use_escrow: bool) -> anchor_lang::Result<()> {
+    let mut owner = TestAccount::new(ID, vec![]).lamports(1);
+    owner.is_signer = owner_signed;
+    let mut accounts = [TestAccount::new(ID, vec![]).at(escrow).lamports(1), owner];
+    let accounts = infos(&mut accounts);
+    Deposit::try_accounts(&ID, &mut &accounts[..], &[use_escrow as u8]).map(|_| ())
+}
+
+#[test]
+fn constraints_are_checked_when_the_condition_holds() {
+    let (escrow, _) = Pubkey::find_program_address(&[b"escrow"], &ID);
+    let other = Pubkey::new_unique();
+
+    // Escrow mode checks the escrow's seeds but not the owner's signature.
+    assert_eq!(try_deposit(escrow, false, true), Ok(()));
+    assert_eq!(
+        try_deposit(other, false, true),
+        Err(ErrorCode::ConstraintSeeds.into())
+    );
+
+    // Direct mode checks the owner's signature but not the escrow's seeds.
+    assert_eq!(try_deposit(other, true, false), Ok(()));
+    assert_eq!(
+        try_deposit(other, false, false),
+        Err(ErrorCode::ConstraintSigner.into())
+    );
+}
+
+#[test]
+fn bumps_are_found_when_the_condition_holds() {
+    let (escrow, bump) = Pubkey::find_program_address(&[b"escrow"], &ID);
+    for (use_escrow, expected) in [(true, Some(bump)), (false, None)] {
+        let mut accounts = [
+            TestAccount::new(ID, vec![]).at(escrow).lamports(1),
+            TestAccount::new(ID, vec![]).lamports(1).signer(),
+        ];
+        let accounts = infos(&mut accounts);
+        let mut bumps = DepositBumps::default();
+        Deposit::try_accounts_with_bumps(&ID, &mut &accounts[..], &[use_escrow as u8], &mut bumps)
+            .unwrap();
+        assert_eq!(bumps.escrow, expected);
+    }
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::*;
+
+mod common;
+
+#[program]
+pub mod zero_copy_args {
+    use super::*;
+
+    #[instruction(zero_copy)]
+    pub fn swap(
+        _ctx: Context<Empty>,
+        amount_in: u64,
+        side: u8,
+        min_out: u64,
+        pool: Pubkey,
+    ) -> ProgramResult {
+        if amount_in != 100 || side != 1 || min_out != 95 || pool != Pubkey::new_from_array([7; 32])
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    #[instruction(zero_copy)]
+    pub fn mixed(
+        _ctx: Context<Empty>,
+        a: u8,
+        b: u32,
+        c: u16,
+        d: i64,
+        e: u128,
+        f: i32,
+    ) -> ProgramResult {
+        if a != 0x11
+            || b != 0x2222_3333
+            || c != 0x4455
+            || d != -0x0667_7889_9aab_bccd
+            || e != 0xdeef_f001_1223_3445_5667_7889_9aab_bccd
+            || f != -0x0102_0304
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[test]
+fn zero_copy_args_match_borsh_layout() {
+    let data = instruction::Swap {
+        amount_in: 100,
+        side: 1,
+        min_out: 95,
+        pool: Pubkey::new_from_array([7; 32]),
+    }
+    .data();
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+}
+
+#[test]
+fn zero_copy_args_mixed_widths() {
+    let data = instruction::Mixed {
+        a: 0x11,
+        b: 0x2222_3333,
+        c: 0x4455,
+        d: -0x0667_7889_9aab_bccd,
+        e: 0xdeef_f001_1223_3445_5667_7889_9aab_bccd,
+        f: -0x0102_0304,
+    }
+    .data();
+    assert_eq!(data.len(), 8 + 1 + 4 + 2 + 8 + 16 + 4);
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+}
+
+#[test]
+fn zero_copy_args_too_short() {
+    let data = instruction::Swap {
+        amount_in: 100,
+        side: 1,
+        min_out: 95,
+        pool: Pubkey::new_from_array([7; 32]),
+    }
+    .data();
+    assert_eq!(
+        entry(&ID, &[], &data[..data.len() - 1]),
+        Err(anchor_lang::__private::ErrorCode::InstructionDidNotDeserialize.into())
+    );
+}
+
+#[test]
+fn zero_copy_args_ignore_trailing_bytes() {
+    let mut data = instruction::Swap {
+        amount_in: 100,
+        side: 1,
+        min_out: 95,
+        pool: Pubkey::new_from_array([7; 32]),
+    }
+    .data();
+    data.push(0);
+    assert_eq!(entry(&ID, &[], &data), Ok(()));
+}
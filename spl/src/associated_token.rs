@@ -1,17 +1,83 @@
 use anchor_lang::solana_program::account_info::AccountInfo;
 use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 use anchor_lang::solana_program::program_error::ProgramError;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang::{Accounts, CpiContext};
 
 pub use spl_associated_token_account::{get_associated_token_address, ID};
 
+/// Creates the associated token account, failing if it already exists.
 pub fn create<'info>(ctx: CpiContext<'_, '_, '_, 'info, Create<'info>>) -> ProgramResult {
-    let ix = spl_associated_token_account::create_associated_token_account(
+    invoke_create(ctx, vec![])
+}
+
+/// Creates the associated token account unless it already exists, in which
+/// case its mint and owner are checked instead. Requires version 1.1.0 of the
+/// associated token program.
+pub fn create_idempotent<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Create<'info>>,
+) -> ProgramResult {
+    invoke_create(ctx, vec![1])
+}
+
+/// Instruction creating `authority`'s associated token account of `mint`,
+/// paid for by `payer`, unless it already exists.
+pub fn create_idempotent_instruction(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    create_instruction(
+        payer,
+        &get_associated_token_address(authority, mint),
+        authority,
+        mint,
+        &spl_token::ID,
+        vec![1],
+    )
+}
+
+// The associated token program's `Create` instruction is given as empty data
+// and `CreateIdempotent` as `1`. `CreateIdempotent` is only understood by
+// version 1.1.0 of the program onwards, and versions before 1.0.5 read the
+// rent sysvar as the last account, so it is always passed.
+fn create_instruction(
+    payer: &Pubkey,
+    associated_token: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    data: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*associated_token, false),
+            AccountMeta::new_readonly(*authority, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+        data,
+    }
+}
+
+fn invoke_create<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Create<'info>>,
+    data: Vec<u8>,
+) -> ProgramResult {
+    let ix = create_instruction(
         ctx.accounts.payer.key,
+        ctx.accounts.associated_token.key,
         ctx.accounts.authority.key,
         ctx.accounts.mint.key,
+        ctx.accounts.token_program.key,
+        data,
     );
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
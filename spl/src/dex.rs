@@ -53,6 +53,7 @@ pub fn new_order_v3<'info>(
         limit,
         max_native_pc_qty_including_fees,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &ToAccountInfos::to_account_infos(&ctx),
@@ -77,6 +78,7 @@ pub fn cancel_order_v2<'info>(
         side,
         order_id,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &ToAccountInfos::to_account_infos(&ctx),
@@ -102,6 +104,7 @@ pub fn settle_funds<'info>(
         referral.map(|r| r.key),
         ctx.accounts.vault_signer.key,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &ToAccountInfos::to_account_infos(&ctx),
@@ -120,6 +123,7 @@ pub fn init_open_orders<'info>(
         ctx.accounts.market.key,
         ctx.remaining_accounts.first().map(|acc| acc.key),
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &ToAccountInfos::to_account_infos(&ctx),
@@ -138,6 +142,7 @@ pub fn close_open_orders<'info>(
         ctx.accounts.destination.key,
         ctx.accounts.market.key,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &ToAccountInfos::to_account_infos(&ctx),
@@ -156,6 +161,7 @@ pub fn sweep_fees<'info>(ctx: CpiContext<'_, '_, '_, 'info, SweepFees<'info>>) -
         ctx.accounts.vault_signer.key,
         ctx.accounts.token_program.key,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &ToAccountInfos::to_account_infos(&ctx),
@@ -191,6 +197,7 @@ pub fn initialize_market<'info>(
         vault_signer_nonce,
         pc_dust_threshold,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &ToAccountInfos::to_account_infos(&ctx),
@@ -244,8 +251,9 @@ pub struct SettleFunds<'info> {
     pub token_program: AccountInfo<'info>,
 }
 
-/// To use an (optional) market authority, add it as the first account of the
-/// CpiContext's `remaining_accounts` Vec.
+/// To use an (optional) market authority, add it as the first of the
+/// CpiContext's remaining accounts, with
+/// `with_extra(ExtraAccount::Signer(market_authority))`.
 #[derive(Accounts)]
 pub struct InitOpenOrders<'info> {
     pub open_orders: AccountInfo<'info>,
@@ -15,3 +15,12 @@ pub mod governance;
 
 #[cfg(feature = "shmem")]
 pub mod shmem;
+
+#[cfg(feature = "pyth")]
+pub mod pyth;
+
+#[cfg(feature = "transfer_hook")]
+pub mod transfer_hook;
+
+#[cfg(feature = "wsol")]
+pub mod wsol;
@@ -0,0 +1,124 @@
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+use std::io::Write;
+
+#[cfg(not(feature = "devnet"))]
+anchor_lang::solana_program::declare_id!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+#[cfg(feature = "devnet")]
+anchor_lang::solana_program::declare_id!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s");
+
+const MAGIC: u32 = 0xa1b2c3d4;
+const VERSION: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+
+// Offsets into a v2 price account.
+const EXPO_OFFSET: usize = 20;
+const PRODUCT_OFFSET: usize = 112;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+
+/// Aggregate price of a Pyth price account.
+///
+/// Use as `Account<'info, PriceFeed>`, which checks the account is owned by
+/// the Pyth oracle program, together with the `price_feed::max_age`
+/// constraint to reject prices that haven't been published recently:
+///
+/// ```ignore
+/// #[derive(Accounts)]
+/// pub struct Liquidate<'info> {
+///     #[account(price_feed::max_age = 25)]
+///     pub price: Account<'info, PriceFeed>,
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PriceFeed {
+    /// Product account this price is for.
+    pub product: Pubkey,
+    pub price: i64,
+    /// Confidence interval around the price.
+    pub conf: u64,
+    /// Price exponent, e.g. a price of 12345 with an exponent of -2 is
+    /// 123.45.
+    pub expo: i32,
+    pub status: PriceStatus,
+    /// Slot the aggregate price was last published in.
+    pub publish_slot: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriceStatus {
+    Unknown,
+    Trading,
+    Halted,
+    Auction,
+}
+
+impl PriceFeed {
+    /// Returns the price if the product is currently trading.
+    pub fn current_price(&self) -> Option<i64> {
+        match self.status {
+            PriceStatus::Trading => Some(self.price),
+            _ => None,
+        }
+    }
+}
+
+impl anchor_lang::AccountDeserialize for PriceFeed {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        PriceFeed::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        let data: &[u8] = buf;
+        if data.len() < AGG_PUB_SLOT_OFFSET + 8
+            || read_u32(data, 0) != MAGIC
+            || read_u32(data, 4) != VERSION
+            || read_u32(data, 8) != ACCOUNT_TYPE_PRICE
+        {
+            return Err(anchor_lang::__private::ErrorCode::AccountDidNotDeserialize.into());
+        }
+        let status = match read_u32(data, AGG_STATUS_OFFSET) {
+            1 => PriceStatus::Trading,
+            2 => PriceStatus::Halted,
+            3 => PriceStatus::Auction,
+            _ => PriceStatus::Unknown,
+        };
+        Ok(PriceFeed {
+            product: Pubkey::new_from_array(
+                data[PRODUCT_OFFSET..PRODUCT_OFFSET + 32]
+                    .try_into()
+                    .unwrap(),
+            ),
+            price: read_u64(data, AGG_PRICE_OFFSET) as i64,
+            conf: read_u64(data, AGG_CONF_OFFSET),
+            expo: read_u32(data, EXPO_OFFSET) as i32,
+            status,
+            publish_slot: read_u64(data, AGG_PUB_SLOT_OFFSET),
+        })
+    }
+}
+
+impl anchor_lang::AccountSerialize for PriceFeed {
+    fn try_serialize<W: Write>(&self, _writer: &mut W) -> Result<(), ProgramError> {
+        // no-op
+        Ok(())
+    }
+}
+
+impl anchor_lang::Owner for PriceFeed {
+    fn owner() -> Pubkey {
+        ID
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
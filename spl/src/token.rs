@@ -22,6 +22,7 @@ pub fn transfer<'a, 'b, 'c, 'info>(
         &[],
         amount,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -46,6 +47,7 @@ pub fn mint_to<'a, 'b, 'c, 'info>(
         &[],
         amount,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -70,6 +72,7 @@ pub fn burn<'a, 'b, 'c, 'info>(
         &[],
         amount,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -94,6 +97,7 @@ pub fn approve<'a, 'b, 'c, 'info>(
         &[],
         amount,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -115,6 +119,7 @@ pub fn initialize_account<'a, 'b, 'c, 'info>(
         ctx.accounts.mint.key,
         ctx.accounts.authority.key,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -138,6 +143,7 @@ pub fn close_account<'a, 'b, 'c, 'info>(
         ctx.accounts.authority.key,
         &[], // TODO: support multisig
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -159,6 +165,7 @@ pub fn freeze_account<'a, 'b, 'c, 'info>(
         ctx.accounts.authority.key,
         &[], // TODO: Support multisig signers.
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -180,6 +187,7 @@ pub fn thaw_account<'a, 'b, 'c, 'info>(
         ctx.accounts.authority.key,
         &[], // TODO: Support multisig signers.
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -204,6 +212,7 @@ pub fn initialize_mint<'a, 'b, 'c, 'info>(
         freeze_authority,
         decimals,
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -233,6 +242,7 @@ pub fn set_authority<'a, 'b, 'c, 'info>(
         ctx.accounts.current_authority.key,
         &[], // TODO: Support multisig signers.
     )?;
+    ctx.check_compute_budget()?;
     solana_program::program::invoke_signed(
         &ix,
         &[
@@ -318,6 +328,11 @@ pub struct TokenAccount(spl_token::state::Account);
 
 impl TokenAccount {
     pub const LEN: usize = spl_token::state::Account::LEN;
+
+    /// The account's balance.
+    pub fn token_amount(&self) -> TokenAmount {
+        TokenAmount::new(self.amount)
+    }
 }
 
 impl anchor_lang::AccountDeserialize for TokenAccount {
@@ -356,6 +371,11 @@ pub struct Mint(spl_token::state::Mint);
 
 impl Mint {
     pub const LEN: usize = spl_token::state::Mint::LEN;
+
+    /// The total supply of the mint.
+    pub fn token_supply(&self) -> TokenAmount {
+        TokenAmount::new(self.supply)
+    }
 }
 
 impl anchor_lang::AccountDeserialize for Mint {
@@ -389,6 +409,90 @@ impl Deref for Mint {
     }
 }
 
+/// An amount of tokens in base units, i.e. the smallest unit of the mint, as
+/// held by token accounts and passed to the token program. Use
+/// [`TokenAmount::from_ui_amount`] and [`TokenAmount::ui_amount`] to convert
+/// from and to the decimal amounts displayed to users.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenAmount(u64);
+
+impl TokenAmount {
+    pub const fn new(base_units: u64) -> Self {
+        Self(base_units)
+    }
+
+    pub const fn base_units(self) -> u64 {
+        self.0
+    }
+
+    /// Converts a decimal amount, e.g. `"1.5"`, to base units of `mint`,
+    /// rounded to the nearest base unit. The amount is parsed with integer
+    /// arithmetic, so it's exact however large. Fails with
+    /// `InvalidTokenAmount` if it isn't a non-negative decimal number and with
+    /// `MathOverflow` if it doesn't fit a u64.
+    pub fn from_ui_amount(ui_amount: &str, mint: &Mint) -> Result<Self, ProgramError> {
+        let scale = pow10(mint.decimals)?;
+        let (whole, fraction) = ui_amount.split_once('.').unwrap_or((ui_amount, ""));
+        if whole.is_empty() && fraction.is_empty()
+            || !whole
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(anchor_lang::__private::ErrorCode::InvalidTokenAmount.into());
+        }
+        // The fraction's digits past the mint's decimals only round.
+        let decimals = mint.decimals as usize;
+        let (fraction, rest) = fraction.split_at(decimals.min(fraction.len()));
+        let round_up = rest.bytes().next().map_or(false, |digit| digit >= b'5');
+        let fraction_scale = pow10((decimals - fraction.len()) as u8)?;
+        parse_digits(whole)
+            .and_then(|whole| whole.checked_mul(scale))
+            .and_then(|base_units| {
+                let fraction = parse_digits(fraction)? * fraction_scale;
+                base_units.checked_add(fraction + round_up as u64)
+            })
+            .map(Self)
+            .ok_or_else(|| anchor_lang::__private::ErrorCode::MathOverflow.into())
+    }
+
+    /// Converts a whole number of tokens, e.g. `5` for 5 USDC, to base units
+    /// of `mint`.
+    pub fn from_whole_tokens(tokens: u64, mint: &Mint) -> Result<Self, ProgramError> {
+        tokens
+            .checked_mul(pow10(mint.decimals)?)
+            .map(Self)
+            .ok_or_else(|| anchor_lang::__private::ErrorCode::MathOverflow.into())
+    }
+
+    /// The decimal amount of tokens of `mint`.
+    pub fn ui_amount(self, mint: &Mint) -> Result<f64, ProgramError> {
+        Ok(self.0 as f64 / pow10(mint.decimals)? as f64)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+}
+
+// The value of a string of ASCII digits, if it fits a u64.
+fn parse_digits(digits: &str) -> Option<u64> {
+    digits.bytes().try_fold(0u64, |value, digit| {
+        value.checked_mul(10)?.checked_add((digit - b'0') as u64)
+    })
+}
+
+// Base units per token of a mint with `decimals`.
+fn pow10(decimals: u8) -> Result<u64, ProgramError> {
+    10u64
+        .checked_pow(decimals.into())
+        .ok_or_else(|| anchor_lang::__private::ErrorCode::MathOverflow.into())
+}
+
 #[derive(Clone)]
 pub struct Token;
 
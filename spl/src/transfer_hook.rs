@@ -0,0 +1,399 @@
+//! The SPL transfer hook interface, invoked by the token 2022 program on
+//! every transfer of a mint with the transfer hook extension.
+//!
+//! A program becomes a transfer hook by marking one of its handlers with
+//! `#[transfer_hook]`, so that it's dispatched by the interface's `Execute`
+//! discriminator. Its accounts must start with [`Execute`]'s, followed by
+//! the extra accounts described by the mint's extra account metas account,
+//! which can be checked with [`ExtraAccountMetaList::check_account_infos`]:
+//!
+//! ```ignore
+//! #[program]
+//! pub mod hook {
+//!     use super::*;
+//!
+//!     #[transfer_hook]
+//!     pub fn execute(ctx: Context<Hook>, amount: u64) -> ProgramResult {
+//!         let accounts = [ctx.accounts.to_account_infos(), ctx.remaining_accounts.to_vec()].concat();
+//!         ExtraAccountMetaList::check_account_infos(&accounts, amount, ctx.program_id)?;
+//!         ..
+//!     }
+//! }
+//! ```
+
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{Accounts, CpiContext, ToAccountInfos};
+use std::convert::TryInto;
+
+/// Discriminator of the `Execute` instruction, i.e. the first 8 bytes of
+/// `sha256("spl-transfer-hook-interface:execute")`. It also tags the list of
+/// extra accounts in the extra account metas account.
+pub const EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
+/// Seed of the extra account metas account, along with the mint.
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+// Number of accounts preceding the extra accounts in `Execute`.
+const EXECUTE_ACCOUNTS_LEN: usize = 5;
+
+// `ExtraAccountMeta` discriminator of a PDA of the hook program. PDAs of
+// other programs set the top bit, with the index of the program's account.
+const PDA_DISCRIMINATOR: u8 = 1;
+const EXTERNAL_PDA_FLAG: u8 = 1 << 7;
+
+/// Address of the extra account metas account of `mint` for the hook
+/// program `program_id`.
+pub fn get_extra_account_metas_address(mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], program_id).0
+}
+
+/// Instruction data of `Execute`.
+pub fn execute_data(amount: u64) -> Vec<u8> {
+    let mut data = EXECUTE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// Invokes the transfer hook program `ctx.program`, passing the extra
+/// accounts as the context's remaining accounts.
+pub fn execute<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, Execute<'info>>,
+    amount: u64,
+) -> ProgramResult {
+    let accounts = [
+        &ctx.accounts.source,
+        &ctx.accounts.mint,
+        &ctx.accounts.destination,
+        &ctx.accounts.authority,
+        &ctx.accounts.extra_account_meta_list,
+    ]
+    .iter()
+    .map(|acc| AccountMeta::new_readonly(*acc.key, false))
+    .chain(ctx.remaining_accounts.iter().map(|acc| AccountMeta {
+        pubkey: *acc.key,
+        is_signer: acc.is_signer,
+        is_writable: acc.is_writable,
+    }))
+    .collect();
+    let ix = Instruction {
+        program_id: *ctx.program.key,
+        accounts,
+        data: execute_data(amount),
+    };
+    ctx.check_compute_budget()?;
+    let mut acc_infos = ctx.accounts.to_account_infos();
+    acc_infos.extend_from_slice(&ctx.remaining_accounts);
+    acc_infos.push(ctx.program.clone());
+    solana_program::program::invoke_signed(&ix, &acc_infos, ctx.signer_seeds)
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    pub source: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub extra_account_meta_list: AccountInfo<'info>,
+}
+
+/// A seed of an extra account's PDA, resolved when the hook is executed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Seed {
+    /// Constant bytes.
+    Literal { bytes: Vec<u8> },
+    /// `length` bytes of the instruction data, starting at `index`.
+    InstructionData { index: u8, length: u8 },
+    /// The address of the account at `index`.
+    AccountKey { index: u8 },
+    /// `length` bytes of the data of the account at `account_index`,
+    /// starting at `data_index`.
+    AccountData {
+        account_index: u8,
+        data_index: u8,
+        length: u8,
+    },
+}
+
+impl Seed {
+    fn pack(&self, buf: &mut Vec<u8>) {
+        match self {
+            Seed::Literal { bytes } => {
+                buf.extend_from_slice(&[1, bytes.len() as u8]);
+                buf.extend_from_slice(bytes);
+            }
+            Seed::InstructionData { index, length } => buf.extend_from_slice(&[2, *index, *length]),
+            Seed::AccountKey { index } => buf.extend_from_slice(&[3, *index]),
+            Seed::AccountData {
+                account_index,
+                data_index,
+                length,
+            } => buf.extend_from_slice(&[4, *account_index, *data_index, *length]),
+        }
+    }
+
+    fn unpack_all(mut buf: &[u8]) -> Result<Vec<Seed>, ProgramError> {
+        let mut seeds = Vec::new();
+        while let Some((&tag, rest)) = buf.split_first() {
+            let (seed, len) = match (tag, rest) {
+                // Seeds are zero terminated, unless they fill the config.
+                (0, _) => break,
+                (1, [len, ..]) => {
+                    let len = *len as usize;
+                    let bytes = rest
+                        .get(1..1 + len)
+                        .ok_or(ProgramError::InvalidAccountData)?;
+                    (
+                        Seed::Literal {
+                            bytes: bytes.to_vec(),
+                        },
+                        1 + len,
+                    )
+                }
+                (2, [index, length, ..]) => (
+                    Seed::InstructionData {
+                        index: *index,
+                        length: *length,
+                    },
+                    2,
+                ),
+                (3, [index, ..]) => (Seed::AccountKey { index: *index }, 1),
+                (4, [account_index, data_index, length, ..]) => (
+                    Seed::AccountData {
+                        account_index: *account_index,
+                        data_index: *data_index,
+                        length: *length,
+                    },
+                    3,
+                ),
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+            seeds.push(seed);
+            buf = &rest[len..];
+        }
+        Ok(seeds)
+    }
+
+    fn resolve(&self, ix_data: &[u8], accounts: &[AccountInfo]) -> Result<Vec<u8>, ProgramError> {
+        let account = |index: u8| {
+            accounts
+                .get(index as usize)
+                .ok_or(ProgramError::NotEnoughAccountKeys)
+        };
+        match self {
+            Seed::Literal { bytes } => Ok(bytes.clone()),
+            Seed::InstructionData { index, length } => ix_data
+                .get(*index as usize..*index as usize + *length as usize)
+                .map(|bytes| bytes.to_vec())
+                .ok_or(ProgramError::InvalidInstructionData),
+            Seed::AccountKey { index } => Ok(account(*index)?.key.to_bytes().to_vec()),
+            Seed::AccountData {
+                account_index,
+                data_index,
+                length,
+            } => account(*account_index)?
+                .try_borrow_data()?
+                .get(*data_index as usize..*data_index as usize + *length as usize)
+                .map(|bytes| bytes.to_vec())
+                .ok_or(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// An extra account required by the hook, either a fixed address or a PDA
+/// derived from the instruction's data and accounts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtraAccountMeta {
+    pub discriminator: u8,
+    // The address, or the packed seeds of a PDA.
+    pub address_config: [u8; 32],
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ExtraAccountMeta {
+    /// Packed length of an extra account meta.
+    pub const LEN: usize = 35;
+
+    pub fn new_with_pubkey(pubkey: &Pubkey, is_signer: bool, is_writable: bool) -> Self {
+        Self {
+            discriminator: 0,
+            address_config: pubkey.to_bytes(),
+            is_signer,
+            is_writable,
+        }
+    }
+
+    /// A PDA of the hook program.
+    pub fn new_with_seeds(
+        seeds: &[Seed],
+        is_signer: bool,
+        is_writable: bool,
+    ) -> Result<Self, ProgramError> {
+        Ok(Self {
+            discriminator: PDA_DISCRIMINATOR,
+            address_config: pack_seeds(seeds)?,
+            is_signer,
+            is_writable,
+        })
+    }
+
+    /// A PDA of the program at account index `program_index`.
+    pub fn new_external_pda_with_seeds(
+        program_index: u8,
+        seeds: &[Seed],
+        is_signer: bool,
+        is_writable: bool,
+    ) -> Result<Self, ProgramError> {
+        if program_index >= EXTERNAL_PDA_FLAG {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Self {
+            discriminator: EXTERNAL_PDA_FLAG | program_index,
+            address_config: pack_seeds(seeds)?,
+            is_signer,
+            is_writable,
+        })
+    }
+
+    /// Resolves the extra account's address, given the `Execute`
+    /// instruction data, including its discriminator, and its accounts.
+    pub fn resolve(
+        &self,
+        ix_data: &[u8],
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> Result<Pubkey, ProgramError> {
+        let program_id = match self.discriminator {
+            0 => return Ok(Pubkey::new_from_array(self.address_config)),
+            PDA_DISCRIMINATOR => *program_id,
+            d if d & EXTERNAL_PDA_FLAG != 0 => {
+                *accounts
+                    .get((d & !EXTERNAL_PDA_FLAG) as usize)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .key
+            }
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let seeds = Seed::unpack_all(&self.address_config)?
+            .iter()
+            .map(|seed| seed.resolve(ix_data, accounts))
+            .collect::<Result<Vec<_>, _>>()?;
+        let seeds: Vec<&[u8]> = seeds.iter().map(|seed| &seed[..]).collect();
+        Ok(Pubkey::find_program_address(&seeds, &program_id).0)
+    }
+
+    fn pack(&self, buf: &mut [u8]) {
+        buf[0] = self.discriminator;
+        buf[1..33].copy_from_slice(&self.address_config);
+        buf[33] = self.is_signer as u8;
+        buf[34] = self.is_writable as u8;
+    }
+
+    fn unpack(buf: &[u8]) -> Self {
+        let mut address_config = [0; 32];
+        address_config.copy_from_slice(&buf[1..33]);
+        Self {
+            discriminator: buf[0],
+            address_config,
+            is_signer: buf[33] != 0,
+            is_writable: buf[34] != 0,
+        }
+    }
+}
+
+fn pack_seeds(seeds: &[Seed]) -> Result<[u8; 32], ProgramError> {
+    let mut buf = Vec::new();
+    for seed in seeds {
+        seed.pack(&mut buf);
+    }
+    if buf.len() > 32 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut config = [0; 32];
+    config[..buf.len()].copy_from_slice(&buf);
+    Ok(config)
+}
+
+/// The extra account metas account's data, laid out as
+///
+/// `EXECUTE_DISCRIMINATOR || length: u32 || count: u32 || metas`
+///
+/// where `length` is the byte length of the rest.
+pub struct ExtraAccountMetaList;
+
+impl ExtraAccountMetaList {
+    // Discriminator and length.
+    const HEADER_LEN: usize = 12;
+
+    /// Space required for `num_metas` extra accounts.
+    pub fn size_of(num_metas: usize) -> usize {
+        Self::HEADER_LEN + 4 + num_metas * ExtraAccountMeta::LEN
+    }
+
+    /// Writes `metas` to `data`, which must be at least
+    /// [`size_of`](Self::size_of) the metas long.
+    pub fn init(data: &mut [u8], metas: &[ExtraAccountMeta]) -> ProgramResult {
+        let len = Self::size_of(metas.len());
+        if data.len() < len {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..8].copy_from_slice(&EXECUTE_DISCRIMINATOR);
+        data[8..12].copy_from_slice(&((len - Self::HEADER_LEN) as u32).to_le_bytes());
+        data[12..16].copy_from_slice(&(metas.len() as u32).to_le_bytes());
+        for (meta, buf) in metas
+            .iter()
+            .zip(data[16..len].chunks_exact_mut(ExtraAccountMeta::LEN))
+        {
+            meta.pack(buf);
+        }
+        Ok(())
+    }
+
+    pub fn unpack(data: &[u8]) -> Result<Vec<ExtraAccountMeta>, ProgramError> {
+        if data.len() < Self::size_of(0) || data[..8] != EXECUTE_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let count = u32::from_le_bytes(data[12..16].try_into().unwrap()) as usize;
+        let metas = data
+            .get(16..Self::size_of(count))
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Ok(metas
+            .chunks_exact(ExtraAccountMeta::LEN)
+            .map(ExtraAccountMeta::unpack)
+            .collect())
+    }
+
+    /// Checks that the accounts given to `Execute` for `amount`, following
+    /// [`Execute`]'s, are the extra accounts listed in the extra account
+    /// metas account. In a handler, `accounts` are the context's accounts
+    /// followed by its remaining accounts.
+    pub fn check_account_infos(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let ix_data = execute_data(amount);
+        let list = accounts
+            .get(EXECUTE_ACCOUNTS_LEN - 1)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let metas = Self::unpack(&list.try_borrow_data()?)?;
+        for (idx, meta) in metas.iter().enumerate() {
+            let account = accounts
+                .get(EXECUTE_ACCOUNTS_LEN + idx)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let expected = meta.resolve(&ix_data, program_id, accounts)?;
+            if *account.key != expected
+                || (meta.is_signer && !account.is_signer)
+                || (meta.is_writable && !account.is_writable)
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        Ok(())
+    }
+}
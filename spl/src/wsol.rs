@@ -0,0 +1,199 @@
+//! Wrapping native SOL into wrapped SOL (WSOL) token accounts and unwrapping
+//! it back.
+//!
+//! The SOL is wrapped into a new token account, never into the owner's WSOL
+//! associated token account, so that unwrapping can't close an account
+//! holding more than what was wrapped. Clients build the instructions around
+//! a token instruction with [`WrappedSol`]. Programs wrap SOL with [`wrap`],
+//! and unwrap it by closing the account with [`unwrap`].
+
+use crate::token::{self, InitializeAccount};
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::solana_program::sysvar::rent::Rent;
+use anchor_lang::solana_program::sysvar::Sysvar;
+use anchor_lang::{Accounts, CpiContext};
+
+pub use spl_token::native_mint::ID as NATIVE_MINT;
+
+/// Builds the instructions wrapping SOL into a new WSOL token account of
+/// `owner`, and closing it afterwards, e.g.
+///
+/// ```ignore
+/// let account = Keypair::new();
+/// let ixs = WrappedSol::new(owner, account.pubkey(), lamports).around(vec![swap_ix]);
+/// ```
+///
+/// `account` must be a fresh keypair signing the transaction.
+#[derive(Debug, Clone)]
+pub struct WrappedSol {
+    owner: Pubkey,
+    account: Pubkey,
+    payer: Pubkey,
+    destination: Pubkey,
+    lamports: u64,
+}
+
+impl WrappedSol {
+    /// Wraps `lamports` from `owner` into `account`. The owner also pays for
+    /// and gets back the rent of the account.
+    pub fn new(owner: Pubkey, account: Pubkey, lamports: u64) -> Self {
+        Self {
+            owner,
+            account,
+            payer: owner,
+            destination: owner,
+            lamports,
+        }
+    }
+
+    /// Funds the account and its rent from `payer` instead of the owner.
+    pub fn payer(mut self, payer: Pubkey) -> Self {
+        self.payer = payer;
+        self
+    }
+
+    /// Sends the unwrapped SOL and the account's rent to `destination`
+    /// instead of the owner.
+    pub fn destination(mut self, destination: Pubkey) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    /// The WSOL token account.
+    pub fn address(&self) -> Pubkey {
+        self.account
+    }
+
+    /// Instructions creating the account with the lamports on top of its rent
+    /// exemption, and initializing it, which sets its token amount.
+    pub fn wrap_instructions(&self) -> Vec<Instruction> {
+        let rent = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+        vec![
+            system_instruction::create_account(
+                &self.payer,
+                &self.account,
+                rent + self.lamports,
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::ID,
+                &self.account,
+                &NATIVE_MINT,
+                &self.owner,
+            )
+            .unwrap(),
+        ]
+    }
+
+    /// Instruction closing the account, unwrapping its SOL.
+    pub fn unwrap_instruction(&self) -> Instruction {
+        close_account_instruction(&self.account, &self.destination, &self.owner)
+    }
+
+    /// Instructions wrapping the SOL, followed by `ixs`, followed by
+    /// unwrapping what's left.
+    pub fn around(&self, ixs: impl IntoIterator<Item = Instruction>) -> Vec<Instruction> {
+        let mut all = self.wrap_instructions();
+        all.extend(ixs);
+        all.push(self.unwrap_instruction());
+        all
+    }
+}
+
+/// Creates the WSOL token account of `authority` with `lamports` from the
+/// payer on top of its rent exemption. The account must not exist yet and
+/// sign, possibly through the seeds of `ctx`. The mint must be
+/// [`NATIVE_MINT`], and the program of `ctx` the token program.
+pub fn wrap<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Wrap<'info>>,
+    lamports: u64,
+) -> ProgramResult {
+    ctx.check_compute_budget()?;
+    let Wrap {
+        payer,
+        account,
+        authority,
+        mint,
+        system_program,
+        rent,
+    } = ctx.accounts;
+    if mint.key != &NATIVE_MINT {
+        return Err(anchor_lang::__private::ErrorCode::ConstraintTokenMint.into());
+    }
+    let exemption = Rent::from_account_info(&rent)?.minimum_balance(spl_token::state::Account::LEN);
+    let total = exemption
+        .checked_add(lamports)
+        .ok_or(anchor_lang::__private::ErrorCode::MathOverflow)?;
+    solana_program::program::invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            account.key,
+            total,
+            spl_token::state::Account::LEN as u64,
+            ctx.program.key,
+        ),
+        &[payer, account.clone(), system_program],
+        ctx.signer_seeds,
+    )?;
+    token::initialize_account(CpiContext::new(
+        ctx.program,
+        InitializeAccount {
+            account,
+            mint,
+            authority,
+            rent,
+        },
+    ))
+}
+
+/// Closes the WSOL token account, sending its SOL to the destination.
+pub fn unwrap<'info>(ctx: CpiContext<'_, '_, '_, 'info, Unwrap<'info>>) -> ProgramResult {
+    ctx.check_compute_budget()?;
+    solana_program::program::invoke_signed(
+        &close_account_instruction(
+            ctx.accounts.account.key,
+            ctx.accounts.destination.key,
+            ctx.accounts.authority.key,
+        ),
+        &[
+            ctx.accounts.account,
+            ctx.accounts.destination,
+            ctx.accounts.authority,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+}
+
+fn close_account_instruction(
+    account: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    spl_token::instruction::close_account(&spl_token::ID, account, destination, authority, &[])
+        .unwrap()
+}
+
+#[derive(Accounts)]
+pub struct Wrap<'info> {
+    pub payer: AccountInfo<'info>,
+    pub account: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unwrap<'info> {
+    pub account: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
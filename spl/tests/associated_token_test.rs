@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+use anchor_lang::ResolveAccounts;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use common::*;
+use solana_program::program_pack::Pack;
+
+#[path = "../../lang/tests/common/mod.rs"]
+mod common;
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    pub authority: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = payer,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CheckVault<'info> {
+    pub authority: UncheckedAccount<'info>,
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+    )]
+    pub vault: UncheckedAccount<'info>,
+}
+
+fn token_account_data(mint: Pubkey, owner: Pubkey) -> Vec<u8> {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+// Validates `CheckVault` with the account at the associated token address of
+// `authority` and `mint` holding `vault_data`.
+fn check_vault(authority: Pubkey, mint: Pubkey, vault_data: Vec<u8>) -> anchor_lang::Result<()> {
+    let vault = get_associated_token_address(&authority, &mint);
+    let system_program = solana_program::system_program::ID;
+    let vault_owner = match vault_data.is_empty() {
+        true => system_program,
+        false => anchor_spl::token::ID,
+    };
+    let mut accounts = [
+        TestAccount::new(system_program, vec![])
+            .at(authority)
+            .lamports(1),
+        TestAccount::new(anchor_spl::token::ID, vec![])
+            .at(mint)
+            .lamports(1),
+        TestAccount::new(vault_owner, vault_data)
+            .at(vault)
+            .lamports(1),
+    ];
+    let accounts = infos(&mut accounts);
+    CheckVault::try_accounts(&ID, &mut &accounts[..], &[]).map(|_| ())
+}
+
+#[test]
+fn associated_token_account_matches() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(
+        check_vault(authority, mint, token_account_data(mint, authority)),
+        Ok(())
+    );
+}
+
+#[test]
+fn associated_token_account_with_another_mint_is_refused() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(
+        check_vault(
+            authority,
+            mint,
+            token_account_data(Pubkey::new_unique(), authority)
+        ),
+        Err(anchor_lang::__private::ErrorCode::ConstraintTokenMint.into())
+    );
+}
+
+#[test]
+fn associated_token_account_with_another_owner_is_refused() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(
+        check_vault(
+            authority,
+            mint,
+            token_account_data(mint, Pubkey::new_unique())
+        ),
+        Err(anchor_lang::__private::ErrorCode::ConstraintTokenOwner.into())
+    );
+}
+
+#[test]
+fn associated_token_account_not_created_yet_passes() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(check_vault(authority, mint, vec![]), Ok(()));
+}
+
+// Resolves the accounts without any account to read.
+fn resolve(accounts: &mut impl ResolveAccounts) {
+    let mut fetch = |_: &Pubkey| -> std::result::Result<Option<Vec<u8>>, ProgramError> { Ok(None) };
+    accounts.resolve_accounts(&ID, &mut fetch).unwrap();
+}
+
+#[test]
+fn associated_token_accounts_are_resolved() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut deposit = __client_accounts_deposit::Deposit {
+        authority,
+        mint,
+        vault: Pubkey::default(),
+        token_program: Pubkey::default(),
+    };
+    resolve(&mut deposit);
+    assert_eq!(
+        deposit.vault,
+        get_associated_token_address(&authority, &mint)
+    );
+    assert_eq!(deposit.token_program, anchor_spl::token::ID);
+}
+
+#[test]
+fn init_associated_token_accounts_are_resolved() {
+    let (payer, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut create = __client_accounts_create_vault::CreateVault {
+        payer,
+        mint,
+        vault: Pubkey::default(),
+        system_program: Pubkey::default(),
+        token_program: Pubkey::default(),
+        associated_token_program: Pubkey::default(),
+        rent: Pubkey::default(),
+    };
+    resolve(&mut create);
+    assert_eq!(create.vault, get_associated_token_address(&payer, &mint));
+    assert_eq!(
+        create.associated_token_program,
+        anchor_spl::associated_token::ID
+    );
+    assert_eq!(create.rent, anchor_lang::solana_program::sysvar::rent::ID);
+}
+
+#[test]
+fn associated_token_accounts_need_their_wallet_and_mint() {
+    let mut deposit = __client_accounts_deposit::Deposit {
+        authority: Pubkey::default(),
+        mint: Pubkey::new_unique(),
+        vault: Pubkey::default(),
+        token_program: Pubkey::default(),
+    };
+    resolve(&mut deposit);
+    assert_eq!(deposit.vault, Pubkey::default());
+}
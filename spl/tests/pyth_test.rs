@@ -0,0 +1,164 @@
+#![cfg(feature = "pyth")]
+
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::clock::MockClock;
+use anchor_lang::prelude::*;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::pyth::{PriceFeed, PriceStatus};
+use common::*;
+
+#[path = "../../lang/tests/common/mod.rs"]
+mod common;
+
+const PUBLISH_SLOT: u64 = 118_204_376;
+
+#[derive(Accounts)]
+pub struct Liquidate<'info> {
+    #[account(price_feed::max_age = 25)]
+    pub price: Account<'info, PriceFeed>,
+}
+
+fn product() -> Pubkey {
+    Pubkey::new_from_array([7; 32])
+}
+
+// A SOL/USD price account with a single publisher, laid out as the v2
+// `PriceAccount` of the Pyth oracle program: a 240 byte header, ending with
+// the aggregate price, followed by 96 bytes per price component.
+fn price_account(status: u32) -> Vec<u8> {
+    let mut data = vec![];
+    data.extend_from_slice(&0xa1b2c3d4u32.to_le_bytes()); // magic
+    data.extend_from_slice(&2u32.to_le_bytes()); // version
+    data.extend_from_slice(&3u32.to_le_bytes()); // account type
+    data.extend_from_slice(&(240u32 + 96).to_le_bytes()); // size
+    data.extend_from_slice(&1u32.to_le_bytes()); // price type
+    data.extend_from_slice(&(-8i32).to_le_bytes()); // exponent
+    data.extend_from_slice(&1u32.to_le_bytes()); // components
+    data.extend_from_slice(&1u32.to_le_bytes()); // quoters
+    data.extend_from_slice(&PUBLISH_SLOT.to_le_bytes()); // last slot
+    data.extend_from_slice(&(PUBLISH_SLOT + 1).to_le_bytes()); // valid slot
+    for ema in [9_520_000_000i64, 3_900_000] {
+        data.extend_from_slice(&ema.to_le_bytes());
+        data.extend_from_slice(&(ema * 10).to_le_bytes());
+        data.extend_from_slice(&10i64.to_le_bytes());
+    }
+    data.extend_from_slice(&1_650_000_000i64.to_le_bytes()); // timestamp
+    data.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0]); // min publishers, derived values
+    data.extend_from_slice(product().as_ref());
+    data.extend_from_slice(&[0; 32]); // next price account
+    data.extend_from_slice(&(PUBLISH_SLOT - 1).to_le_bytes()); // previous slot
+    data.extend_from_slice(&9_522_000_000i64.to_le_bytes()); // previous price
+    data.extend_from_slice(&4_400_000u64.to_le_bytes()); // previous confidence
+    data.extend_from_slice(&1_649_999_999i64.to_le_bytes()); // previous timestamp
+    assert_eq!(data.len(), 208);
+    let aggregate = price_info(9_523_000_000, 4_500_000, status, PUBLISH_SLOT);
+    data.extend_from_slice(&aggregate);
+    data.extend_from_slice(&[9; 32]); // publisher
+    data.extend_from_slice(&aggregate);
+    data.extend_from_slice(&price_info(
+        9_524_000_000,
+        4_600_000,
+        status,
+        PUBLISH_SLOT + 1,
+    ));
+    assert_eq!(data.len(), 240 + 96);
+    data
+}
+
+fn price_info(price: i64, conf: u64, status: u32, pub_slot: u64) -> Vec<u8> {
+    let mut info = vec![];
+    info.extend_from_slice(&price.to_le_bytes());
+    info.extend_from_slice(&conf.to_le_bytes());
+    info.extend_from_slice(&status.to_le_bytes());
+    info.extend_from_slice(&0u32.to_le_bytes()); // corporate action
+    info.extend_from_slice(&pub_slot.to_le_bytes());
+    info
+}
+
+fn liquidate(owner: Pubkey, data: Vec<u8>) -> anchor_lang::Result<()> {
+    let mut price_feed = TestAccount::new(owner, data).lamports(1);
+    let accounts = [price_feed.info()];
+    Liquidate::try_accounts(&ID, &mut &accounts[..], &[]).map(|_| ())
+}
+
+fn at_slot(slot: u64) -> MockClock {
+    let clock = MockClock::new(Clock {
+        slot,
+        ..Clock::default()
+    });
+    clock.install();
+    clock
+}
+
+#[test]
+fn decodes_a_price_account() {
+    let feed = PriceFeed::try_deserialize(&mut &price_account(1)[..]).unwrap();
+    assert_eq!(feed.product, product());
+    assert_eq!(feed.price, 9_523_000_000);
+    assert_eq!(feed.conf, 4_500_000);
+    assert_eq!(feed.expo, -8);
+    assert_eq!(feed.status, PriceStatus::Trading);
+    assert_eq!(feed.publish_slot, PUBLISH_SLOT);
+    assert_eq!(feed.current_price(), Some(9_523_000_000));
+}
+
+#[test]
+fn prices_that_are_not_trading_have_no_current_price() {
+    for (status, expected) in [
+        (0, PriceStatus::Unknown),
+        (2, PriceStatus::Halted),
+        (3, PriceStatus::Auction),
+    ] {
+        let feed = PriceFeed::try_deserialize(&mut &price_account(status)[..]).unwrap();
+        assert_eq!(feed.status, expected);
+        assert_eq!(feed.current_price(), None);
+    }
+}
+
+#[test]
+fn other_accounts_are_refused() {
+    let not_deserialized = Err(ErrorCode::AccountDidNotDeserialize.into());
+    let data = price_account(1);
+    assert_eq!(
+        PriceFeed::try_deserialize(&mut &data[..239]).map(|_| ()),
+        not_deserialized
+    );
+    // Magic, version and account type, e.g. a product account.
+    for offset in [0, 4, 8] {
+        let mut data = data.clone();
+        data[offset] ^= 1;
+        assert_eq!(
+            PriceFeed::try_deserialize(&mut &data[..]).map(|_| ()),
+            not_deserialized
+        );
+    }
+}
+
+#[test]
+fn fresh_prices_are_accepted() {
+    let clock = at_slot(PUBLISH_SLOT);
+    assert_eq!(liquidate(anchor_spl::pyth::ID, price_account(1)), Ok(()));
+    clock.warp_to_slot(PUBLISH_SLOT + 25);
+    assert_eq!(liquidate(anchor_spl::pyth::ID, price_account(1)), Ok(()));
+    anchor_lang::clock::clear_provider();
+}
+
+#[test]
+fn stale_prices_are_refused() {
+    at_slot(PUBLISH_SLOT + 26);
+    assert_eq!(
+        liquidate(anchor_spl::pyth::ID, price_account(1)),
+        Err(ErrorCode::ConstraintPriceFeedStale.into())
+    );
+    anchor_lang::clock::clear_provider();
+}
+
+#[test]
+fn price_accounts_of_other_programs_are_refused() {
+    at_slot(PUBLISH_SLOT);
+    assert_eq!(
+        liquidate(Pubkey::new_unique(), price_account(1)),
+        Err(ErrorCode::AccountNotProgramOwned.into())
+    );
+    anchor_lang::clock::clear_provider();
+}
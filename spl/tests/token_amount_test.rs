@@ -0,0 +1,148 @@
+use anchor_lang::__private::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::{Mint, TokenAmount};
+use solana_program::program_pack::Pack;
+
+fn mint(decimals: u8) -> Mint {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        decimals,
+        is_initialized: true,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Mint::try_deserialize(&mut &data[..]).unwrap()
+}
+
+fn overflow<T>() -> std::result::Result<T, ProgramError> {
+    Err(ErrorCode::MathOverflow.into())
+}
+
+#[test]
+fn converts_ui_amounts() {
+    for (decimals, ui_amount, ui_value, base_units) in [
+        (0, "5", 5.0, 5),
+        (6, "1.5", 1.5, 1_500_000),
+        (6, "0.000001", 0.000_001, 1),
+        (9, "2.25", 2.25, 2_250_000_000),
+        (9, "0", 0.0, 0),
+        (9, ".5", 0.5, 500_000_000),
+        (9, "3.", 3.0, 3_000_000_000),
+    ] {
+        let mint = mint(decimals);
+        let amount = TokenAmount::from_ui_amount(ui_amount, &mint).unwrap();
+        assert_eq!(amount.base_units(), base_units);
+        assert_eq!(amount.ui_amount(&mint), Ok(ui_value));
+    }
+}
+
+#[test]
+fn converts_whole_tokens() {
+    assert_eq!(
+        TokenAmount::from_whole_tokens(5, &mint(0)),
+        Ok(TokenAmount::new(5))
+    );
+    assert_eq!(
+        TokenAmount::from_whole_tokens(5, &mint(6)),
+        Ok(TokenAmount::new(5_000_000))
+    );
+    assert_eq!(
+        TokenAmount::from_whole_tokens(5, &mint(9)),
+        Ok(TokenAmount::new(5_000_000_000))
+    );
+}
+
+#[test]
+fn ui_amounts_round_to_the_nearest_base_unit() {
+    let mint = mint(6);
+    for (ui_amount, base_units) in [
+        ("1.0000004", 1_000_000),
+        ("1.0000006", 1_000_001),
+        ("0.0000005", 1),
+        ("0.00000049999", 0),
+        ("0.9999999", 1_000_000),
+    ] {
+        assert_eq!(
+            TokenAmount::from_ui_amount(ui_amount, &mint),
+            Ok(TokenAmount::new(base_units))
+        );
+    }
+}
+
+#[test]
+fn negative_and_malformed_ui_amounts_are_refused() {
+    let mint = mint(6);
+    for ui_amount in [
+        "-1",
+        "-0.000001",
+        "",
+        ".",
+        "1.2.3",
+        "1e6",
+        "+1",
+        " 1",
+        "NaN",
+        "inf",
+    ] {
+        assert_eq!(
+            TokenAmount::from_ui_amount(ui_amount, &mint),
+            Err(ErrorCode::InvalidTokenAmount.into())
+        );
+    }
+}
+
+#[test]
+fn decimals_overflowing_a_u64_are_refused() {
+    // 10^19 is the largest power of ten fitting a u64.
+    let amount = TokenAmount::new(1);
+    assert_eq!(
+        amount.ui_amount(&mint(19)),
+        Ok(1.0 / 10_000_000_000_000_000_000.0)
+    );
+    for decimals in [20, u8::MAX] {
+        let mint = mint(decimals);
+        assert_eq!(amount.ui_amount(&mint), overflow());
+        assert_eq!(TokenAmount::from_ui_amount("1", &mint), overflow());
+        assert_eq!(TokenAmount::from_whole_tokens(1, &mint), overflow());
+    }
+}
+
+#[test]
+fn amounts_up_to_u64_max() {
+    let no_decimals = mint(0);
+    assert_eq!(
+        TokenAmount::from_whole_tokens(u64::MAX, &no_decimals),
+        Ok(TokenAmount::new(u64::MAX))
+    );
+    assert_eq!(
+        TokenAmount::from_whole_tokens(u64::MAX, &mint(6)),
+        overflow()
+    );
+    // Amounts above 2^53 are exact, up to u64::MAX.
+    assert_eq!(
+        TokenAmount::from_ui_amount("18446744073709551615", &no_decimals),
+        Ok(TokenAmount::new(u64::MAX))
+    );
+    assert_eq!(
+        TokenAmount::from_ui_amount("18446744073709551616", &no_decimals),
+        overflow()
+    );
+    assert_eq!(
+        TokenAmount::from_ui_amount("9007199254740.993", &mint(3)),
+        Ok(TokenAmount::new(9_007_199_254_740_993))
+    );
+    assert_eq!(
+        TokenAmount::from_ui_amount("18446744073709.551615", &mint(6)),
+        Ok(TokenAmount::new(u64::MAX))
+    );
+    assert_eq!(
+        TokenAmount::from_ui_amount("18446744073709.5516155", &mint(6)),
+        overflow()
+    );
+    assert_eq!(
+        TokenAmount::new(u64::MAX).checked_add(TokenAmount::new(1)),
+        None
+    );
+    assert_eq!(TokenAmount::new(0).checked_sub(TokenAmount::new(1)), None);
+}
@@ -0,0 +1,249 @@
+#![cfg(feature = "transfer_hook")]
+
+use anchor_lang::prelude::*;
+use anchor_spl::transfer_hook::{
+    self, execute_data, get_extra_account_metas_address, ExtraAccountMeta, ExtraAccountMetaList,
+    Seed, EXECUTE_DISCRIMINATOR, EXTRA_ACCOUNT_METAS_SEED,
+};
+use common::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use std::cell::RefCell;
+
+#[path = "../../lang/tests/common/mod.rs"]
+mod common;
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+const AMOUNT: u64 = 1_000;
+
+#[test]
+fn execute_data_starts_with_the_interface_discriminator() {
+    let hash = solana_program::hash::hash(b"spl-transfer-hook-interface:execute");
+    assert_eq!(EXECUTE_DISCRIMINATOR, hash.to_bytes()[..8]);
+    assert_eq!(
+        execute_data(AMOUNT),
+        [&EXECUTE_DISCRIMINATOR[..], &AMOUNT.to_le_bytes()].concat()
+    );
+}
+
+#[test]
+fn list_round_trips() {
+    let metas = [
+        ExtraAccountMeta::new_with_pubkey(&Pubkey::new_unique(), false, true),
+        ExtraAccountMeta::new_with_seeds(&[Seed::AccountKey { index: 0 }], false, false).unwrap(),
+    ];
+    let len = ExtraAccountMetaList::size_of(metas.len());
+    let mut data = vec![0; len];
+    assert_eq!(
+        ExtraAccountMetaList::init(&mut data[..len - 1], &metas),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+    ExtraAccountMetaList::init(&mut data, &metas).unwrap();
+    assert_eq!(ExtraAccountMetaList::unpack(&data), Ok(metas.to_vec()));
+
+    data[0] ^= 1;
+    assert_eq!(
+        ExtraAccountMetaList::unpack(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn seeds_must_fit_the_address_config() {
+    let seeds = [Seed::Literal { bytes: vec![7; 31] }];
+    assert_eq!(
+        ExtraAccountMeta::new_with_seeds(&seeds, false, false),
+        Err(ProgramError::InvalidSeeds)
+    );
+    assert_eq!(
+        ExtraAccountMeta::new_external_pda_with_seeds(128, &[], false, false),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+// The `Execute` accounts of a transfer, whose destination holds `[1, 2, 3,
+// 4]`, followed by an external program.
+fn execute_accounts() -> Vec<TestAccount> {
+    let token_program = Pubkey::new_unique();
+    vec![
+        TestAccount::new(token_program, vec![]),
+        TestAccount::new(token_program, vec![]),
+        TestAccount::new(token_program, vec![1, 2, 3, 4]),
+        TestAccount::new(Pubkey::default(), vec![]),
+        TestAccount::new(ID, vec![]),
+        TestAccount::program(Pubkey::new_unique(), Pubkey::new_unique()),
+    ]
+}
+
+#[test]
+fn extra_accounts_are_resolved() {
+    let mut accounts = execute_accounts();
+    let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+    let accounts = infos(&mut accounts);
+    let ix_data = execute_data(AMOUNT);
+    let seeds = [
+        Seed::Literal {
+            bytes: b"hook".to_vec(),
+        },
+        Seed::AccountKey { index: 1 },
+        Seed::InstructionData {
+            index: 8,
+            length: 8,
+        },
+        Seed::AccountData {
+            account_index: 2,
+            data_index: 1,
+            length: 2,
+        },
+    ];
+    let expected_seeds: [&[u8]; 4] = [b"hook", keys[1].as_ref(), &AMOUNT.to_le_bytes(), &[2, 3]];
+
+    let fixed = Pubkey::new_unique();
+    assert_eq!(
+        ExtraAccountMeta::new_with_pubkey(&fixed, false, false).resolve(&ix_data, &ID, &accounts),
+        Ok(fixed)
+    );
+    assert_eq!(
+        ExtraAccountMeta::new_with_seeds(&seeds, false, false)
+            .unwrap()
+            .resolve(&ix_data, &ID, &accounts),
+        Ok(Pubkey::find_program_address(&expected_seeds, &ID).0)
+    );
+    assert_eq!(
+        ExtraAccountMeta::new_external_pda_with_seeds(5, &seeds, false, false)
+            .unwrap()
+            .resolve(&ix_data, &ID, &accounts),
+        Ok(Pubkey::find_program_address(&expected_seeds, &keys[5]).0)
+    );
+}
+
+#[test]
+fn seeds_out_of_bounds_are_refused() {
+    let mut accounts = execute_accounts();
+    let accounts = infos(&mut accounts);
+    let ix_data = execute_data(AMOUNT);
+    let resolve = |seed: Seed| {
+        ExtraAccountMeta::new_with_seeds(&[seed], false, false)
+            .unwrap()
+            .resolve(&ix_data, &ID, &accounts)
+    };
+    assert_eq!(
+        resolve(Seed::AccountKey { index: 6 }),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        resolve(Seed::InstructionData {
+            index: 8,
+            length: 9,
+        }),
+        Err(ProgramError::InvalidInstructionData)
+    );
+    assert_eq!(
+        resolve(Seed::AccountData {
+            account_index: 2,
+            data_index: 3,
+            length: 2,
+        }),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+// The `Execute` accounts followed by a writable PDA of the destination and
+// the extra account given by `extra`, with the extra account metas account
+// listing both.
+fn check(extra: impl FnOnce(Pubkey) -> Option<TestAccount>) -> ProgramResult {
+    let fixed = Pubkey::new_unique();
+    let metas = [
+        ExtraAccountMeta::new_with_seeds(&[Seed::AccountKey { index: 2 }], false, true).unwrap(),
+        ExtraAccountMeta::new_with_pubkey(&fixed, false, false),
+    ];
+    let mut list = vec![0; ExtraAccountMetaList::size_of(metas.len())];
+    ExtraAccountMetaList::init(&mut list, &metas).unwrap();
+
+    let mut accounts = execute_accounts();
+    accounts.truncate(4);
+    accounts.push(TestAccount::new(ID, list));
+    let (pda, _) = Pubkey::find_program_address(&[accounts[2].key.as_ref()], &ID);
+    accounts.push(TestAccount::new(ID, vec![]).at(pda).writable());
+    accounts.extend(extra(fixed));
+    let accounts = infos(&mut accounts);
+    ExtraAccountMetaList::check_account_infos(&accounts, AMOUNT, &ID)
+}
+
+#[test]
+fn listed_extra_accounts_pass() {
+    assert_eq!(
+        check(|fixed| Some(TestAccount::new(ID, vec![]).at(fixed))),
+        Ok(())
+    );
+}
+
+#[test]
+fn unlisted_extra_accounts_are_refused() {
+    assert_eq!(
+        check(|_| Some(TestAccount::new(ID, vec![]))),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(check(|_| None), Err(ProgramError::NotEnoughAccountKeys));
+}
+
+#[test]
+fn execute_passes_the_extra_accounts() {
+    set_syscall_stubs(Box::new(Stubs));
+    let mut accounts = execute_accounts();
+    accounts.push(TestAccount::new(ID, vec![]).writable());
+    let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+    let accounts = infos(&mut accounts);
+    let ctx = CpiContext::new(
+        accounts[5].clone(),
+        transfer_hook::Execute {
+            source: accounts[0].clone(),
+            mint: accounts[1].clone(),
+            destination: accounts[2].clone(),
+            authority: accounts[3].clone(),
+            extra_account_meta_list: accounts[4].clone(),
+        },
+    )
+    .with_extra(ExtraAccount::Writer(accounts[6].clone()));
+    transfer_hook::execute(ctx, AMOUNT).unwrap();
+
+    let mut metas: Vec<AccountMeta> = keys[..5]
+        .iter()
+        .map(|key| AccountMeta::new_readonly(*key, false))
+        .collect();
+    metas.push(AccountMeta::new(keys[6], false));
+    assert_eq!(
+        INVOKED.with(|invoked| invoked.take()),
+        vec![Instruction {
+            program_id: keys[5],
+            accounts: metas,
+            data: execute_data(AMOUNT),
+        }]
+    );
+}
+
+#[test]
+fn extra_account_metas_address() {
+    let (mint, program) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(
+        get_extra_account_metas_address(&mint, &program),
+        Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], &program).0
+    );
+}
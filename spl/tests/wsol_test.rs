@@ -0,0 +1,185 @@
+#![cfg(feature = "wsol")]
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::wsol::{self, WrappedSol, NATIVE_MINT};
+use common::*;
+use solana_program::instruction::Instruction;
+use solana_program::program_pack::Pack;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::system_instruction;
+use std::cell::RefCell;
+
+#[path = "../../lang/tests/common/mod.rs"]
+mod common;
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+fn token_account_rent() -> u64 {
+    Rent::default().minimum_balance(spl_token::state::Account::LEN)
+}
+
+#[test]
+fn around_wraps_into_a_new_account() {
+    let (owner, account) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let token_ix = Instruction::new_with_bytes(anchor_spl::token::ID, &[3], vec![]);
+    let ixs = WrappedSol::new(owner, account, 5).around(vec![token_ix.clone()]);
+
+    assert_eq!(
+        ixs,
+        vec![
+            system_instruction::create_account(
+                &owner,
+                &account,
+                token_account_rent() + 5,
+                spl_token::state::Account::LEN as u64,
+                &anchor_spl::token::ID,
+            ),
+            spl_token::instruction::initialize_account(
+                &anchor_spl::token::ID,
+                &account,
+                &NATIVE_MINT,
+                &owner,
+            )
+            .unwrap(),
+            token_ix,
+            spl_token::instruction::close_account(
+                &anchor_spl::token::ID,
+                &account,
+                &owner,
+                &owner,
+                &[],
+            )
+            .unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn around_leaves_the_associated_token_account_alone() {
+    let owner = Pubkey::new_unique();
+    let associated_token = get_associated_token_address(&owner, &NATIVE_MINT);
+    let ixs = WrappedSol::new(owner, Pubkey::new_unique(), 5).around(vec![]);
+    assert!(ixs
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .all(|meta| meta.pubkey != associated_token));
+}
+
+#[test]
+fn payer_and_destination() {
+    let (owner, account) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (payer, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let wrapped = WrappedSol::new(owner, account, 5)
+        .payer(payer)
+        .destination(destination);
+
+    assert_eq!(wrapped.wrap_instructions()[0].accounts[0].pubkey, payer);
+    assert_eq!(
+        wrapped.unwrap_instruction(),
+        spl_token::instruction::close_account(
+            &anchor_spl::token::ID,
+            &account,
+            &destination,
+            &owner,
+            &[],
+        )
+        .unwrap()
+    );
+}
+
+fn rent_sysvar() -> TestAccount {
+    let rent = Rent::default();
+    let mut data = rent.lamports_per_byte_year.to_le_bytes().to_vec();
+    data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+    data.push(rent.burn_percent);
+    TestAccount::new(solana_program::sysvar::ID, data)
+        .at(solana_program::sysvar::rent::ID)
+        .lamports(1)
+}
+
+// Wraps 5 lamports into a new account of `mint` through the CPI helper.
+fn wrap(mint: Pubkey) -> (Vec<Pubkey>, ProgramResult) {
+    let system_program = solana_program::system_program::ID;
+    let mut accounts = [
+        TestAccount::new(system_program, vec![])
+            .signer()
+            .writable()
+            .lamports(10_000_000),
+        TestAccount::new(system_program, vec![]).signer().writable(),
+        TestAccount::new(system_program, vec![]),
+        TestAccount::new(anchor_spl::token::ID, vec![])
+            .at(mint)
+            .lamports(1),
+        TestAccount::native_program(system_program),
+        rent_sysvar(),
+        TestAccount::program(anchor_spl::token::ID, Pubkey::new_unique()),
+    ];
+    let keys = accounts.iter().map(|account| account.key).collect();
+    let infos = infos(&mut accounts);
+    let ctx = CpiContext::new(
+        infos[6].clone(),
+        wsol::Wrap {
+            payer: infos[0].clone(),
+            account: infos[1].clone(),
+            authority: infos[2].clone(),
+            mint: infos[3].clone(),
+            system_program: infos[4].clone(),
+            rent: infos[5].clone(),
+        },
+    );
+    (keys, wsol::wrap(ctx, 5))
+}
+
+#[test]
+fn wrap_creates_and_initializes_the_account() {
+    set_syscall_stubs(Box::new(Stubs));
+    let (keys, result) = wrap(NATIVE_MINT);
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        INVOKED.with(|invoked| invoked.take()),
+        vec![
+            system_instruction::create_account(
+                &keys[0],
+                &keys[1],
+                token_account_rent() + 5,
+                spl_token::state::Account::LEN as u64,
+                &anchor_spl::token::ID,
+            ),
+            spl_token::instruction::initialize_account(
+                &anchor_spl::token::ID,
+                &keys[1],
+                &NATIVE_MINT,
+                &keys[2],
+            )
+            .unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn wrap_refuses_other_mints() {
+    set_syscall_stubs(Box::new(Stubs));
+    let (_, result) = wrap(Pubkey::new_unique());
+    assert_eq!(
+        result,
+        Err(anchor_lang::__private::ErrorCode::ConstraintTokenMint.into())
+    );
+    assert!(INVOKED.with(|invoked| invoked.take()).is_empty());
+}
@@ -0,0 +1,22 @@
+//! Starts a local `solana-test-validator` for an Anchor workspace, the same
+//! way `anchor test` does, without spawning the `anchor` binary. The
+//! workspace's programs are loaded into the genesis block, along with the
+//! genesis programs, account fixtures and clones configured in the `[test]`
+//! section of Anchor.toml.
+//!
+//! ```ignore
+//! use anchor_test_validator::{Config, ConfigOverride};
+//!
+//! let cfg = Config::discover(&ConfigOverride::default())?
+//!     .expect("Not in an Anchor workspace");
+//! let validator = anchor_test_validator::start(&cfg)?;
+//! let client = RpcClient::new(validator.rpc_url().to_string());
+//! ..
+//! // Or let it be shut down when dropped.
+//! validator.shutdown()?;
+//! ```
+//!
+//! The workspace's programs must be built beforehand, e.g. with `anchor build`.
+
+pub use anchor_cli::config::{Config, ConfigOverride, WithPath};
+pub use anchor_cli::test_validator::{start, TestValidator};
@@ -0,0 +1,61 @@
+//! Helpers for testing Anchor programs with `solana-program-test`, moving
+//! the bank's clock to the slots, epochs and times a test needs, e.g.
+//!
+//! ```ignore
+//! let mut ctx = program_test.start_with_context().await;
+//! anchor_test::advance_time(&mut ctx, 86_400).await?;
+//! // The vesting period has elapsed.
+//! ```
+//!
+//! Unit tests invoking a program's entrypoint directly, without a bank, mock
+//! the clock with [`anchor_lang::clock::MockClock`] instead.
+
+use anchor_lang::solana_program::clock::{Clock, Epoch, Slot, UnixTimestamp};
+use solana_program_test::ProgramTestContext;
+use std::error::Error;
+
+pub use solana_program_test;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// The bank's clock.
+pub async fn clock(ctx: &mut ProgramTestContext) -> Result<Clock> {
+    Ok(ctx.banks_client.get_sysvar::<Clock>().await?)
+}
+
+/// Replaces the bank's clock.
+pub fn set_clock(ctx: &mut ProgramTestContext, clock: &Clock) {
+    ctx.set_sysvar(clock);
+}
+
+/// Warps the bank forward to `slot`.
+pub fn warp_to_slot(ctx: &mut ProgramTestContext, slot: Slot) -> Result<()> {
+    Ok(ctx.warp_to_slot(slot)?)
+}
+
+/// Warps the bank forward to the first slot of `epoch`.
+pub fn warp_to_epoch(ctx: &mut ProgramTestContext, epoch: Epoch) -> Result<()> {
+    let slot = ctx
+        .genesis_config()
+        .epoch_schedule
+        .get_first_slot_in_epoch(epoch);
+    warp_to_slot(ctx, slot)
+}
+
+/// Sets the unix timestamp of the bank's clock.
+pub async fn warp_to_timestamp(
+    ctx: &mut ProgramTestContext,
+    unix_timestamp: UnixTimestamp,
+) -> Result<()> {
+    let mut clock = clock(ctx).await?;
+    clock.unix_timestamp = unix_timestamp;
+    set_clock(ctx, &clock);
+    Ok(())
+}
+
+/// Moves the unix timestamp of the bank's clock `seconds` forward, or
+/// backward if negative.
+pub async fn advance_time(ctx: &mut ProgramTestContext, seconds: i64) -> Result<()> {
+    let unix_timestamp = clock(ctx).await?.unix_timestamp + seconds;
+    warp_to_timestamp(ctx, unix_timestamp).await
+}
@@ -21,6 +21,14 @@ mod errors {
         Err(MyError::HelloNext.into())
     }
 
+    pub fn insufficient_funds(_ctx: Context<Hello>) -> Result<()> {
+        Err(MyError::InsufficientFunds {
+            needed: 10,
+            available: 5,
+        }
+        .into())
+    }
+
     pub fn mut_error(_ctx: Context<MutError>) -> Result<()> {
         Ok(())
     }
@@ -90,4 +98,9 @@ pub enum MyError {
     HelloNoMsg = 123,
     HelloNext,
     HelloCustom,
+    #[msg("Insufficient funds: needed {needed}, available {available}")]
+    InsufficientFunds {
+        needed: u64,
+        available: u64,
+    },
 }
@@ -45,6 +45,22 @@ describe("errors", () => {
     }
   });
 
+  it("Emits an InsufficientFunds error with fields", async () => {
+    try {
+      const tx = await program.rpc.insufficientFunds();
+      assert.ok(false);
+    } catch (err) {
+      assert.equal(err.code, 6000 + 126);
+      const idlError = program.idl.errors.find(
+        (e) => e.name === "InsufficientFunds"
+      );
+      assert.deepEqual(idlError.fields, [
+        { name: "needed", type: "u64" },
+        { name: "available", type: "u64" },
+      ]);
+    }
+  });
+
   it("Emits a mut error", async () => {
     try {
       const tx = await program.rpc.mutError({
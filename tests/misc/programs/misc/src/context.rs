@@ -183,6 +183,24 @@ pub struct TestInit<'info> {
     pub system_program: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct TestInitSelfFunded<'info> {
+    #[account(init, payer = self)]
+    pub data: Account<'info, DataI8>,
+    pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TestInitSplitPayers<'info> {
+    #[account(init, payer = [payer, co_payer])]
+    pub data: Account<'info, DataI8>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub co_payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct TestInitZeroCopy<'info> {
     #[account(init, payer = payer, space = 8 + size_of::<DataZeroCopy>())]
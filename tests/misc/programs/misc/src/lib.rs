@@ -142,6 +142,16 @@ pub mod misc {
         Ok(())
     }
 
+    pub fn test_init_self_funded(ctx: Context<TestInitSelfFunded>) -> ProgramResult {
+        ctx.accounts.data.data = 3;
+        Ok(())
+    }
+
+    pub fn test_init_split_payers(ctx: Context<TestInitSplitPayers>) -> ProgramResult {
+        ctx.accounts.data.data = 3;
+        Ok(())
+    }
+
     pub fn test_init_zero_copy(ctx: Context<TestInitZeroCopy>) -> ProgramResult {
         let mut data = ctx.accounts.data.load_init()?;
         data.data = 10;
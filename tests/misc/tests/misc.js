@@ -416,6 +416,82 @@ describe("misc", () => {
     assert.ok(account.data === 3);
   });
 
+  it("Can init a prefunded account paying for itself", async () => {
+    const data = anchor.web3.Keypair.generate();
+    await program.rpc.testInitSelfFunded({
+      accounts: {
+        data: data.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      },
+      signers: [data],
+      instructions: [
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: program.provider.wallet.publicKey,
+          toPubkey: data.publicKey,
+          lamports: 4039280,
+        }),
+      ],
+    });
+
+    const account = await program.account.dataI8.fetch(data.publicKey);
+    assert.ok(account.data === 3);
+  });
+
+  it("Fails to init an underfunded account paying for itself", async () => {
+    const data = anchor.web3.Keypair.generate();
+    try {
+      await program.rpc.testInitSelfFunded({
+        accounts: {
+          data: data.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        },
+        signers: [data],
+        instructions: [
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: program.provider.wallet.publicKey,
+            toPubkey: data.publicKey,
+            lamports: 1000,
+          }),
+        ],
+      });
+      assert.ok(false);
+    } catch (err) {
+      assert.equal(err.code, 2020);
+    }
+  });
+
+  it("Can init an account with the rent split between payers", async () => {
+    const data = anchor.web3.Keypair.generate();
+    const coPayer = anchor.web3.Keypair.generate();
+    const connection = program.provider.connection;
+    await program.provider.send(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: program.provider.wallet.publicKey,
+          toPubkey: coPayer.publicKey,
+          lamports: 10000000,
+        })
+      )
+    );
+    await program.rpc.testInitSplitPayers({
+      accounts: {
+        data: data.publicKey,
+        payer: program.provider.wallet.publicKey,
+        coPayer: coPayer.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      },
+      signers: [data, coPayer],
+    });
+
+    const account = await program.account.dataI8.fetch(data.publicKey);
+    assert.ok(account.data === 3);
+    const rent = await connection.getMinimumBalanceForRentExemption(9);
+    assert.equal(
+      await connection.getBalance(coPayer.publicKey),
+      10000000 - Math.floor(rent / 2)
+    );
+  });
+
   it("Can init a random zero copy account", async () => {
     const data = anchor.web3.Keypair.generate();
     await program.rpc.testInitZeroCopy({
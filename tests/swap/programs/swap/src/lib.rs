@@ -348,7 +348,7 @@ impl<'info> OrderbookClient<'info> {
         };
         let mut ctx = CpiContext::new(self.dex_program.clone(), dex_accs);
         if let Some(referral) = referral {
-            ctx = ctx.with_remaining_accounts(vec![referral]);
+            ctx = ctx.with_extra(ExtraAccount::Writer(referral));
         }
         dex::new_order_v3(
             ctx,
@@ -377,7 +377,7 @@ impl<'info> OrderbookClient<'info> {
         };
         let mut ctx = CpiContext::new(self.dex_program.clone(), settle_accs);
         if let Some(referral) = referral {
-            ctx = ctx.with_remaining_accounts(vec![referral]);
+            ctx = ctx.with_extra(ExtraAccount::Writer(referral));
         }
         dex::settle_funds(ctx)
     }
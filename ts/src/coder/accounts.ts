@@ -1,6 +1,7 @@
 import { Buffer } from "buffer";
 import { Layout } from "buffer-layout";
-import { Idl } from "../idl.js";
+import { deflate, inflate } from "pako";
+import { Idl, IdlTypeDef } from "../idl.js";
 import { IdlCoder } from "./idl.js";
 import { sha256 } from "js-sha256";
 import camelcase from "camelcase";
@@ -10,6 +11,11 @@ import camelcase from "camelcase";
  */
 export const ACCOUNT_DISCRIMINATOR_SIZE = 8;
 
+/**
+ * Number of bytes of the length header preceding a compressed account body.
+ */
+const COMPRESSED_HEADER_SIZE = 8;
+
 /**
  * Encodes and decodes account objects.
  */
@@ -19,19 +25,63 @@ export class AccountsCoder<A extends string = string> {
    */
   private accountLayouts: Map<A, Layout>;
 
+  /**
+   * Accounts encoded with a program defined codec, which can't be derived
+   * from the IDL.
+   */
+  private customCodecAccounts: Set<A>;
+
+  /**
+   * Accounts whose borsh encoded body is zlib compressed.
+   */
+  private compressedAccounts: Set<A>;
+
+  /**
+   * Maps account type identifier to its discriminator.
+   */
+  private discriminators: Map<A, Buffer>;
+
   public constructor(idl: Idl) {
+    this.customCodecAccounts = new Set();
+    this.compressedAccounts = new Set();
     if (idl.accounts === undefined) {
       this.accountLayouts = new Map();
+      this.discriminators = new Map();
       return;
     }
-    const layouts: [A, Layout][] = idl.accounts.map((acc) => {
-      return [acc.name as A, IdlCoder.typeDefLayout(acc, idl.types)];
+    // Bytemuck accounts have no padding, so their fields are laid out the
+    // same way as with borsh. Accounts renamed in the IDL can also be coded
+    // by their aliases.
+    const layouts: [A, Layout][] = [];
+    const discriminators: [A, Buffer][] = [];
+    idl.accounts.forEach((acc) => {
+      const layout = IdlCoder.typeDefLayout(acc, idl.types);
+      const discriminator = AccountsCoder.idlAccountDiscriminator(acc);
+      [acc.name, ...(acc.aliases ?? [])].forEach((name) => {
+        if (acc.codec === "custom") {
+          this.customCodecAccounts.add(name as A);
+        } else if (acc.codec === "compressed") {
+          this.compressedAccounts.add(name as A);
+        }
+        layouts.push([name as A, layout]);
+        discriminators.push([name as A, discriminator]);
+      });
     });
 
     this.accountLayouts = new Map(layouts);
+    this.discriminators = new Map(discriminators);
+  }
+
+  private assertNotCustomCodec(accountName: A) {
+    if (this.customCodecAccounts.has(accountName)) {
+      throw new Error(
+        `Account ${accountName} uses a custom codec and can't be coded from the IDL`
+      );
+    }
   }
 
   public async encode<T = any>(accountName: A, account: T): Promise<Buffer> {
+    this.assertNotCustomCodec(accountName);
     const buffer = Buffer.alloc(1000); // TODO: use a tighter buffer.
     const layout = this.accountLayouts.get(accountName);
     if (!layout) {
@@ -39,13 +89,34 @@ export class AccountsCoder<A extends string = string> {
     }
     const len = layout.encode(account, buffer);
     let accountData = buffer.slice(0, len);
-    let discriminator = AccountsCoder.accountDiscriminator(accountName);
+    if (this.compressedAccounts.has(accountName)) {
+      const compressed = Buffer.from(deflate(accountData));
+      const header = Buffer.alloc(COMPRESSED_HEADER_SIZE);
+      header.writeUInt32LE(accountData.length, 0);
+      header.writeUInt32LE(compressed.length, 4);
+      accountData = Buffer.concat([header, compressed]);
+    }
+    let discriminator =
+      this.discriminators.get(accountName) ??
+      AccountsCoder.accountDiscriminator(accountName);
     return Buffer.concat([discriminator, accountData]);
   }
 
   public decode<T = any>(accountName: A, ix: Buffer): T {
+    this.assertNotCustomCodec(accountName);
     // Chop off the discriminator before decoding.
-    const data = ix.slice(ACCOUNT_DISCRIMINATOR_SIZE);
+    let data = ix.slice(ACCOUNT_DISCRIMINATOR_SIZE);
+    if (this.compressedAccounts.has(accountName)) {
+      const compressedLen = data.readUInt32LE(4);
+      data = Buffer.from(
+        inflate(
+          data.slice(
+            COMPRESSED_HEADER_SIZE,
+            COMPRESSED_HEADER_SIZE + compressedLen
+          )
+        )
+      );
+    }
     const layout = this.accountLayouts.get(accountName);
     if (!layout) {
       throw new Error(`Unknown account: ${accountName}`);
@@ -63,4 +134,16 @@ export class AccountsCoder<A extends string = string> {
       sha256.digest(`account:${camelcase(name, { pascalCase: true })}`)
     ).slice(0, ACCOUNT_DISCRIMINATOR_SIZE);
   }
+
+  /**
+   * Returns the discriminator of an IDL account, as given by the IDL, or
+   * calculated from its name for IDLs without discriminators.
+   *
+   * @param account The IDL account.
+   */
+  public static idlAccountDiscriminator(account: IdlTypeDef): Buffer {
+    return account.discriminator
+      ? Buffer.from(account.discriminator)
+      : AccountsCoder.accountDiscriminator(account.name);
+  }
 }
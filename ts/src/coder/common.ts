@@ -23,6 +23,9 @@ export function accountSize(idl: Idl, idlAccount: IdlTypeDef): number {
     );
     return Math.max(...variantSizes) + 1;
   }
+  if (idlAccount.type.kind === "flags") {
+    return idlAccount.type.repr === "u64" ? 8 : 4;
+  }
   if (idlAccount.type.fields === undefined) {
     return 0;
   }
@@ -84,6 +87,9 @@ function typeSize(idl: Idl, ty: IdlType): number {
         let arraySize = ty.array[1];
         return typeSize(idl, arrayTy) * arraySize;
       }
+      if ("fixedString" in ty) {
+        return ty.fixedString;
+      }
       throw new Error(`Invalid type ${JSON.stringify(ty)}`);
   }
 }
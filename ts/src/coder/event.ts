@@ -17,6 +17,12 @@ export class EventCoder {
    */
   private discriminators: Map<string, string>;
 
+  /**
+   * Name of the program's anonymous event, logged without a discriminator,
+   * if it declares exactly one.
+   */
+  private anonymous?: string;
+
   public constructor(idl: Idl) {
     if (idl.events === undefined) {
       this.layouts = new Map();
@@ -38,13 +44,22 @@ export class EventCoder {
     this.layouts = new Map(layouts);
 
     this.discriminators = new Map<string, string>(
-      idl.events === undefined
-        ? []
-        : idl.events.map((e) => [
-            base64.fromByteArray(eventDiscriminator(e.name)),
-            e.name,
-          ])
+      idl.events
+        .filter((e) => !e.anonymous)
+        .map((e) => [
+          base64.fromByteArray(
+            e.discriminator
+              ? Buffer.from(e.discriminator)
+              : eventDiscriminator(e.name)
+          ),
+          e.name,
+        ])
     );
+
+    const anonymous = idl.events.filter((e) => e.anonymous);
+    if (anonymous.length === 1) {
+      this.anonymous = anonymous[0].name;
+    }
   }
 
   public decode<E extends IdlEvent = IdlEvent, T = Record<string, never>>(
@@ -57,12 +72,74 @@ export class EventCoder {
     } catch (e) {
       return null;
     }
+    return this.decodeData(logArr);
+  }
+
+  /**
+   * Decodes all events of a log, which is either a single event, or a batch
+   * of the events buffered by an instruction with `ctx.events()`.
+   */
+  public decodeAll<E extends IdlEvent = IdlEvent, T = Record<string, never>>(
+    log: string
+  ): Event<E, T>[] {
+    let logArr: Buffer;
+    try {
+      logArr = Buffer.from(base64.toByteArray(log));
+    } catch (e) {
+      return [];
+    }
+    if (!logArr.slice(0, 8).equals(EVENT_BATCH_DISCRIMINATOR)) {
+      const event = this.decodeData<E, T>(logArr);
+      return event ? [event] : [];
+    }
+    // Each event is prefixed by its u32 length.
+    const events: Event<E, T>[] = [];
+    let offset = 8;
+    while (offset + 4 <= logArr.length) {
+      const len = logArr.readUInt32LE(offset);
+      offset += 4;
+      const event = this.decodeData<E, T>(
+        logArr.slice(offset, offset + len)
+      );
+      if (event) {
+        events.push(event);
+      }
+      offset += len;
+    }
+    return events;
+  }
+
+  /**
+   * Decodes a log of the anonymous event `name`, which clients identify from
+   * context as its log has no discriminator. Returns null if the log doesn't
+   * decode into the event.
+   */
+  public decodeAnonymous<
+    E extends IdlEvent = IdlEvent,
+    T = Record<string, never>
+  >(name: string, log: string): Event<E, T> | null {
+    let logArr: Buffer;
+    try {
+      logArr = Buffer.from(base64.toByteArray(log));
+    } catch (e) {
+      return null;
+    }
+    return this.decodeFields(name, logArr);
+  }
+
+  private decodeData<E extends IdlEvent = IdlEvent, T = Record<string, never>>(
+    logArr: Buffer
+  ): Event<E, T> | null {
     const disc = base64.fromByteArray(logArr.slice(0, 8));
 
-    // Only deserialize if the discriminator implies a proper event.
+    // Only deserialize if the discriminator implies a proper event. Other
+    // logs are taken as the program's anonymous event, if it has exactly
+    // one.
     const eventName = this.discriminators.get(disc);
     if (eventName === undefined) {
-      return null;
+      return this.anonymous === undefined
+        ? null
+        : this.decodeFields(this.anonymous, logArr);
     }
 
     const layout = this.layouts.get(eventName);
@@ -75,8 +152,33 @@ export class EventCoder {
     >;
     return { data, name: eventName };
   }
+
+  // Decodes the fields of the event `name`, or null if they don't decode.
+  private decodeFields<E extends IdlEvent, T>(
+    name: string,
+    data: Buffer
+  ): Event<E, T> | null {
+    const layout = this.layouts.get(name);
+    if (!layout) {
+      throw new Error(`Unknown event: ${name}`);
+    }
+    try {
+      return {
+        data: layout.decode(data) as EventData<E["fields"][number], T>,
+        name,
+      };
+    } catch (e) {
+      return null;
+    }
+  }
 }
 
+// Prefix of the log of the events buffered by an instruction, see
+// `EventBuffer` in anchor-lang.
+const EVENT_BATCH_DISCRIMINATOR = Buffer.from(
+  sha256.digest("anchor:event_batch")
+).slice(0, 8);
+
 export function eventDiscriminator(name: string): Buffer {
   return Buffer.from(sha256.digest(`event:${name}`)).slice(0, 8);
 }
@@ -100,6 +100,8 @@ export class IdlCoder {
             types
           );
           return borsh.array(innerLayout, arrayLen, fieldName);
+        } else if ("fixedString" in field.type) {
+          return new FixedString(field.type.fixedString, fieldName);
         } else {
           throw new Error(`Not yet implemented: ${field}`);
         }
@@ -117,6 +119,15 @@ export class IdlCoder {
         const x = IdlCoder.fieldLayout(field, types);
         return x;
       });
+      const aliases: [string, string[]][] = typeDef.type.fields
+        .filter((field) => field.aliases?.length)
+        .map((field): [string, string[]] => [
+          camelCase(field.name),
+          (field.aliases ?? []).map((alias) => camelCase(alias)),
+        ]);
+      if (aliases.length > 0) {
+        return new AliasedStruct(borsh.struct(fieldLayouts), aliases, name);
+      }
       return borsh.struct(fieldLayouts, name);
     } else if (typeDef.type.kind === "enum") {
       let variants = typeDef.type.variants.map((variant: IdlEnumVariant) => {
@@ -143,8 +154,80 @@ export class IdlCoder {
       }
 
       return borsh.rustEnum(variants, name);
+    } else if (typeDef.type.kind === "flags") {
+      // Flags are encoded as their raw bits.
+      return typeDef.type.repr === "u64" ? borsh.u64(name) : borsh.u32(name);
     } else {
       throw new Error(`Unknown type kint: ${typeDef}`);
     }
   }
 }
+
+/**
+ * Struct layout for structs with fields renamed in the IDL, encoding fields
+ * given by any of their names and decoding them under all of them.
+ */
+class AliasedStruct extends Layout {
+  constructor(
+    private inner: Layout,
+    private aliases: [string, string[]][],
+    property?: string
+  ) {
+    super(inner.span, property);
+  }
+
+  getSpan(b: Buffer, offset = 0): number {
+    return this.inner.getSpan(b, offset);
+  }
+
+  encode(src: any, b: Buffer, offset = 0): number {
+    const value = { ...src };
+    for (const [name, aliases] of this.aliases) {
+      if (value[name] === undefined) {
+        const alias = aliases.find((alias) => value[alias] !== undefined);
+        if (alias !== undefined) {
+          value[name] = value[alias];
+        }
+      }
+    }
+    return this.inner.encode(value, b, offset);
+  }
+
+  decode(b: Buffer, offset = 0): any {
+    const value = this.inner.decode(b, offset);
+    for (const [name, aliases] of this.aliases) {
+      for (const alias of aliases) {
+        value[alias] = value[name];
+      }
+    }
+    return value;
+  }
+}
+
+/**
+ * Layout of a `FixedStr<N>`, a UTF-8 string stored in `N` bytes padded with
+ * zeroes.
+ */
+class FixedString extends Layout {
+  constructor(span: number, property?: string) {
+    super(span, property);
+  }
+
+  encode(src: string, b: Buffer, offset = 0): number {
+    const bytes = Buffer.from(src, "utf8");
+    if (bytes.length > this.span) {
+      throw new RangeError(
+        `String of ${bytes.length} bytes doesn't fit in ${this.span} bytes`
+      );
+    }
+    b.fill(0, offset, offset + this.span);
+    bytes.copy(b, offset);
+    return this.span;
+  }
+
+  decode(b: Buffer, offset = 0): string {
+    const bytes = b.slice(offset, offset + this.span);
+    const len = bytes.indexOf(0);
+    return bytes.slice(0, len === -1 ? this.span : len).toString("utf8");
+  }
+}
@@ -37,12 +37,19 @@ export class InstructionCoder {
   // Base58 encoded sighash to instruction layout.
   private sighashLayouts: Map<string, { layout: Layout; name: string }>;
 
+  // Namespaced method to the sighash given by the IDL.
+  private idlSighashes: Map<string, Buffer>;
+
   public constructor(private idl: Idl) {
     this.ixLayout = InstructionCoder.parseIxLayout(idl);
 
     const sighashLayouts = new Map();
+    const idlSighashes = new Map();
     idl.instructions.forEach((ix) => {
-      const sh = sighash(SIGHASH_GLOBAL_NAMESPACE, ix.name);
+      const sh = ix.discriminator
+        ? Buffer.from(ix.discriminator)
+        : sighash(SIGHASH_GLOBAL_NAMESPACE, ix.name);
+      idlSighashes.set(`${SIGHASH_GLOBAL_NAMESPACE}:${ix.name}`, sh);
       sighashLayouts.set(bs58.encode(sh), {
         layout: this.ixLayout.get(ix.name),
         name: ix.name,
@@ -51,7 +58,10 @@ export class InstructionCoder {
 
     if (idl.state) {
       idl.state.methods.map((ix) => {
-        const sh = sighash(SIGHASH_STATE_NAMESPACE, ix.name);
+        const sh = ix.discriminator
+          ? Buffer.from(ix.discriminator)
+          : sighash(SIGHASH_STATE_NAMESPACE, ix.name);
+        idlSighashes.set(`${SIGHASH_STATE_NAMESPACE}:${ix.name}`, sh);
         sighashLayouts.set(bs58.encode(sh), {
           layout: this.ixLayout.get(ix.name) as Layout,
           name: ix.name,
@@ -60,6 +70,7 @@ export class InstructionCoder {
     }
 
     this.sighashLayouts = sighashLayouts;
+    this.idlSighashes = idlSighashes;
   }
 
   /**
@@ -85,7 +96,10 @@ export class InstructionCoder {
     }
     const len = layout.encode(ix, buffer);
     const data = buffer.slice(0, len);
-    return Buffer.concat([sighash(nameSpace, ixName), data]);
+    const sh =
+      this.idlSighashes.get(`${nameSpace}:${methodName}`) ??
+      sighash(nameSpace, ixName);
+    return Buffer.concat([sh, data]);
   }
 
   private static parseIxLayout(idl: Idl): Map<string, Layout> {
@@ -233,6 +247,9 @@ class InstructionFormatter {
     if ("array" in idlType) {
       return `Array<${idlType.array[0]}; ${idlType.array[1]}>`;
     }
+    if ("fixedString" in idlType) {
+      return `FixedString<${idlType.fixedString}>`;
+    }
 
     throw new Error(`Unknown IDL type: ${idlType}`);
   }
@@ -291,6 +308,9 @@ class InstructionFormatter {
         types
       );
     }
+    if ("fixedString" in idlField.type) {
+      return data.toString();
+    }
 
     return "unknown";
   }
@@ -314,6 +334,8 @@ class InstructionFormatter {
         })
         .join(", ");
       return "{ " + fields + " }";
+    } else if (typeDef.type.kind === "flags") {
+      return data.toString();
     } else {
       if (typeDef.type.variants.length === 0) {
         return "{}";
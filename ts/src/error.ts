@@ -56,6 +56,9 @@ const LangErrorCode = {
   InstructionFallbackNotFound: 101,
   InstructionDidNotDeserialize: 102,
   InstructionDidNotSerialize: 103,
+  InstructionSimulationOnly: 104,
+  InstructionSunset: 105,
+  InstructionDataTooLong: 106,
 
   // IDL instructions.
   IdlInstructionStub: 1000,
@@ -82,6 +85,16 @@ const LangErrorCode = {
   ConstraintMintFreezeAuthority: 2017,
   ConstraintMintDecimals: 2018,
   ConstraintSpace: 2019,
+  ConstraintSelfFunded: 2020,
+  ConstraintPriceFeedStale: 2021,
+  ConstraintProgramStable: 2022,
+  ConstraintTimelock: 2023,
+  ConstraintRemainingAccountsCount: 2024,
+  ConstraintRemainingAccountsOwner: 2025,
+  ConstraintRemainingAccountsMut: 2026,
+  ConstraintAssignTo: 2027,
+  ConstraintActiveWindowNotStarted: 2028,
+  ConstraintActiveWindowEnded: 2029,
 
   // Accounts.
   AccountDiscriminatorAlreadySet: 3000,
@@ -98,9 +111,39 @@ const LangErrorCode = {
   AccountNotSystemOwned: 3011,
   AccountNotInitialized: 3012,
   AccountNotProgramData: 3013,
+  AccountBorrowedMutably: 3014,
+  AccountAlreadyLoaded: 3015,
+  DependencyProgramMismatch: 3016,
+  DependencyIdlMismatch: 3017,
+  AccountNotNativeOwned: 3018,
+  AccountNotSysvarOwned: 3019,
+  AccountNotZeroed: 3020,
+  AccountReallocTooLarge: 3021,
+  AccountInvariantViolated: 3022,
   // State.
   StateInvalidAddress: 4000,
 
+  // Miscellaneous.
+  InsufficientComputeUnits: 4100,
+  ProgramPanicked: 4101,
+  MathOverflow: 4102,
+  InstructionAlreadyExecuted: 4103,
+  ReplayNotExpired: 4104,
+  ReplayPayerMismatch: 4105,
+  CpiProgramNotAllowed: 4106,
+  TimelockNotElapsed: 4107,
+  TimelockActionPending: 4108,
+  FixedStrTooLong: 4109,
+  FixedStrInvalidUtf8: 4110,
+  InterfaceNotRegistered: 4111,
+  BumpNotFound: 4112,
+  PagedVecWrongPage: 4113,
+  PagedVecIndexOutOfBounds: 4114,
+  SchemaAuthorityMismatch: 4115,
+  ArenaExhausted: 4116,
+  PagedVecZeroCapacity: 4117,
+  InvalidTokenAmount: 4118,
+
   // Used for APIs that shouldn't be used anymore.
   Deprecated: 5000,
 };
@@ -123,6 +166,18 @@ const LangErrorMessage = new Map([
     LangErrorCode.InstructionDidNotSerialize,
     "The program could not serialize the given instruction",
   ],
+  [
+    LangErrorCode.InstructionSimulationOnly,
+    "The instruction can only be executed in simulation",
+  ],
+  [
+    LangErrorCode.InstructionSunset,
+    "The deprecated instruction is past its sunset slot",
+  ],
+  [
+    LangErrorCode.InstructionDataTooLong,
+    "The instruction data is longer than the instruction's arguments",
+  ],
 
   // Idl instructions.
   [
@@ -167,6 +222,46 @@ const LangErrorMessage = new Map([
     "A mint decimals constraint was violated",
   ],
   [LangErrorCode.ConstraintSpace, "A space constraint was violated"],
+  [
+    LangErrorCode.ConstraintSelfFunded,
+    "The account doesn't hold enough lamports to pay for its own initialization",
+  ],
+  [
+    LangErrorCode.ConstraintPriceFeedStale,
+    "The price feed hasn't been updated within its max age",
+  ],
+  [
+    LangErrorCode.ConstraintProgramStable,
+    "The program was upgraded in the current slot",
+  ],
+  [
+    LangErrorCode.ConstraintTimelock,
+    "The action isn't queued on the timelock",
+  ],
+  [
+    LangErrorCode.ConstraintRemainingAccountsCount,
+    "The number of remaining accounts is out of the declared bounds",
+  ],
+  [
+    LangErrorCode.ConstraintRemainingAccountsOwner,
+    "A remaining account isn't owned by the declared owner",
+  ],
+  [
+    LangErrorCode.ConstraintRemainingAccountsMut,
+    "A remaining account isn't writable",
+  ],
+  [
+    LangErrorCode.ConstraintAssignTo,
+    "The account wasn't assigned to the expected owner",
+  ],
+  [
+    LangErrorCode.ConstraintActiveWindowNotStarted,
+    "The instruction's active window hasn't started yet",
+  ],
+  [
+    LangErrorCode.ConstraintActiveWindowEnded,
+    "The instruction's active window has ended",
+  ],
 
   // Accounts.
   [
@@ -207,6 +302,42 @@ const LangErrorMessage = new Map([
     LangErrorCode.AccountNotProgramData,
     "The given account is not a program data account",
   ],
+  [
+    LangErrorCode.AccountBorrowedMutably,
+    "The account data is mutably borrowed and cannot be loaded",
+  ],
+  [
+    LangErrorCode.AccountAlreadyLoaded,
+    "The account data is already loaded and cannot be borrowed mutably",
+  ],
+  [
+    LangErrorCode.DependencyProgramMismatch,
+    "The given program is not the declared dependency",
+  ],
+  [
+    LangErrorCode.DependencyIdlMismatch,
+    "The dependency's IDL doesn't match the declared IDL hash",
+  ],
+  [
+    LangErrorCode.AccountNotNativeOwned,
+    "The given account is not owned by the native loader",
+  ],
+  [
+    LangErrorCode.AccountNotSysvarOwned,
+    "The given account is not owned by the sysvar program",
+  ],
+  [
+    LangErrorCode.AccountNotZeroed,
+    "The account data must be zeroed before its owner is changed",
+  ],
+  [
+    LangErrorCode.AccountReallocTooLarge,
+    "The account data can't grow by more than 10 KiB per instruction",
+  ],
+  [
+    LangErrorCode.AccountInvariantViolated,
+    "An invariant of the account doesn't hold",
+  ],
 
   // State.
   [
@@ -214,6 +345,63 @@ const LangErrorMessage = new Map([
     "The given state account does not have the correct address",
   ],
 
+  // Miscellaneous.
+  [
+    LangErrorCode.InsufficientComputeUnits,
+    "Not enough compute units remain for the cross-program invocation",
+  ],
+  [LangErrorCode.ProgramPanicked, "The program panicked"],
+  [LangErrorCode.MathOverflow, "An arithmetic operation overflowed"],
+  [
+    LangErrorCode.InstructionAlreadyExecuted,
+    "The instruction was already executed with this nonce",
+  ],
+  [LangErrorCode.ReplayNotExpired, "The replay account hasn't expired yet"],
+  [
+    LangErrorCode.ReplayPayerMismatch,
+    "The replay account was funded by a different payer",
+  ],
+  [
+    LangErrorCode.CpiProgramNotAllowed,
+    "The program isn't allowed to be invoked",
+  ],
+  [
+    LangErrorCode.TimelockNotElapsed,
+    "The timelock's delay hasn't elapsed yet",
+  ],
+  [
+    LangErrorCode.TimelockActionPending,
+    "The timelock already has a queued action",
+  ],
+  [
+    LangErrorCode.FixedStrTooLong,
+    "The string doesn't fit in the fixed string",
+  ],
+  [LangErrorCode.FixedStrInvalidUtf8, "The fixed string isn't valid UTF-8"],
+  [
+    LangErrorCode.InterfaceNotRegistered,
+    "The program isn't a registered implementation of the interface",
+  ],
+  [LangErrorCode.BumpNotFound, "No bump was found for the account"],
+  [LangErrorCode.PagedVecWrongPage, "The account isn't the paged list's page"],
+  [
+    LangErrorCode.PagedVecIndexOutOfBounds,
+    "The index is out of the paged list's bounds",
+  ],
+  [
+    LangErrorCode.SchemaAuthorityMismatch,
+    "The signer isn't the program's upgrade authority",
+  ],
+  [LangErrorCode.ArenaExhausted, "The instruction's arena is exhausted"],
+  [
+    LangErrorCode.PagedVecZeroCapacity,
+    "The paged list's pages can't hold any item",
+  ],
+  [
+    LangErrorCode.InvalidTokenAmount,
+    "The token amount isn't a non-negative decimal number",
+  ],
+
   // Misc.
   [
     LangErrorCode.Deprecated,
@@ -12,6 +12,7 @@ export type Idl = {
   events?: IdlEvent[];
   errors?: IdlErrorCode[];
   constants?: IdlConstant[];
+  dependencies?: IdlDependency[];
 };
 
 export type IdlConstant = {
@@ -23,6 +24,9 @@ export type IdlConstant = {
 export type IdlEvent = {
   name: string;
   fields: IdlEventField[];
+  discriminator?: number[];
+  // Set for events logged without a discriminator.
+  anonymous?: boolean;
 };
 
 export type IdlEventField = {
@@ -31,10 +35,25 @@ export type IdlEventField = {
   index: boolean;
 };
 
+export type IdlDependency = {
+  name: string;
+  programId: string;
+  idlHash?: string;
+};
+
 export type IdlInstruction = {
   name: string;
   accounts: IdlAccountItem[];
   args: IdlField[];
+  simulationOnly?: boolean;
+  deprecated?: IdlDeprecation;
+  discriminator?: number[];
+};
+
+export type IdlDeprecation = {
+  since?: string;
+  note?: string;
+  sunsetSlot?: number;
 };
 
 export type IdlState = {
@@ -50,8 +69,26 @@ export type IdlAccount = {
   name: string;
   isMut: boolean;
   isSigner: boolean;
+  pda?: IdlPda;
+  relations?: string[];
+  activeWindow?: IdlActiveWindow;
+};
+
+export type IdlActiveWindow = {
+  start: string;
+  end: string;
+};
+
+export type IdlPda = {
+  seeds: IdlSeed[];
 };
 
+export type IdlSeed =
+  | { kind: "const"; value: number[] }
+  | { kind: "int"; value: string; type?: IdlType; endian: "le" | "be" }
+  | { kind: "hash"; seeds: IdlSeed[] }
+  | { kind: "expr"; value: string };
+
 // A nested/recursive version of IdlAccount.
 export type IdlAccounts = {
   name: string;
@@ -61,11 +98,15 @@ export type IdlAccounts = {
 export type IdlField = {
   name: string;
   type: IdlType;
+  aliases?: string[];
 };
 
 export type IdlTypeDef = {
   name: string;
   type: IdlTypeDefTy;
+  codec?: "bytemuck" | "custom" | "compressed";
+  discriminator?: number[];
+  aliases?: string[];
 };
 
 export type IdlTypeDefTyStruct = {
@@ -78,7 +119,18 @@ export type IdlTypeDefTyEnum = {
   variants: IdlEnumVariant[];
 };
 
-type IdlTypeDefTy = IdlTypeDefTyEnum | IdlTypeDefTyStruct;
+export type IdlTypeDefTyFlags = {
+  kind: "flags";
+  repr: "u32" | "u64";
+  flags: IdlFlag[];
+};
+
+export type IdlFlag = {
+  name: string;
+  value: number;
+};
+
+type IdlTypeDefTy = IdlTypeDefTyEnum | IdlTypeDefTyStruct | IdlTypeDefTyFlags;
 
 type IdlTypeDefStruct = Array<IdlField>;
 
@@ -100,7 +152,8 @@ export type IdlType =
   | IdlTypeDefined
   | IdlTypeOption
   | IdlTypeVec
-  | IdlTypeArray;
+  | IdlTypeArray
+  | IdlTypeFixedString;
 
 // User defined type.
 export type IdlTypeDefined = {
@@ -119,6 +172,11 @@ export type IdlTypeArray = {
   array: [idlType: IdlType, size: number];
 };
 
+// UTF-8 string stored in a fixed number of bytes, padded with zeroes.
+export type IdlTypeFixedString = {
+  fixedString: number;
+};
+
 export type IdlEnumVariant = {
   name: string;
   fields?: IdlEnumFields;
@@ -134,6 +192,7 @@ export type IdlErrorCode = {
   code: number;
   name: string;
   msg?: string;
+  fields?: IdlField[];
 };
 
 // Deterministic IDL address as a function of the program id.
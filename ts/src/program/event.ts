@@ -5,7 +5,8 @@ import Coder from "../coder/index.js";
 import { DecodeType } from "./namespace/types.js";
 import Provider from "../provider.js";
 
-const LOG_START_INDEX = "Program log: ".length;
+const PROGRAM_LOG = "Program log: ";
+const PROGRAM_DATA = "Program data: ";
 
 // Deserialized event.
 export type Event<
@@ -175,10 +176,8 @@ export class EventParser {
     const execution = new ExecutionContext(logScanner.next() as string);
     let log = logScanner.next();
     while (log !== null) {
-      let [event, newProgram, didPop] = this.handleLog(execution, log);
-      if (event) {
-        callback(event);
-      }
+      let [events, newProgram, didPop] = this.handleLog(execution, log);
+      events.forEach(callback);
       if (newProgram) {
         execution.push(newProgram);
       }
@@ -189,14 +188,14 @@ export class EventParser {
     }
   }
 
-  // Main log handler. Returns a three element array of the event, the
+  // Main log handler. Returns a three element array of the events, the
   // next program that was invoked for CPI, and a boolean indicating if
   // a program has completed execution (and thus should be popped off the
   // execution stack).
   private handleLog(
     execution: ExecutionContext,
     log: string
-  ): [Event | null, string | null, boolean] {
+  ): [Event[], string | null, boolean] {
     // Executing program is this program.
     if (
       execution.stack.length > 0 &&
@@ -206,23 +205,25 @@ export class EventParser {
     }
     // Executing program is not this program.
     else {
-      return [null, ...this.handleSystemLog(log)];
+      return [[], ...this.handleSystemLog(log)];
     }
   }
 
   // Handles logs from *this* program.
   private handleProgramLog(
     log: string
-  ): [Event | null, string | null, boolean] {
-    // This is a `msg!` log.
-    if (log.startsWith("Program log:")) {
-      const logStr = log.slice(LOG_START_INDEX);
-      const event = this.coder.events.decode(logStr);
-      return [event, null, false];
+  ): [Event[], string | null, boolean] {
+    // This is a `msg!` log, or an event logged as data.
+    if (log.startsWith(PROGRAM_LOG) || log.startsWith(PROGRAM_DATA)) {
+      const logStr = log.startsWith(PROGRAM_LOG)
+        ? log.slice(PROGRAM_LOG.length)
+        : log.slice(PROGRAM_DATA.length);
+      const events = this.coder.events.decodeAll(logStr);
+      return [events, null, false];
     }
     // System log.
     else {
-      return [null, ...this.handleSystemLog(log)];
+      return [[], ...this.handleSystemLog(log)];
     }
   }
 
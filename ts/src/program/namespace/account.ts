@@ -143,8 +143,8 @@ export class AccountClient<
     }
 
     // Assert the account discriminator is correct.
-    const discriminator = AccountsCoder.accountDiscriminator(
-      this._idlAccount.name
+    const discriminator = AccountsCoder.idlAccountDiscriminator(
+      this._idlAccount
     );
     if (discriminator.compare(accountInfo.data.slice(0, 8))) {
       throw new Error("Invalid account discriminator");
@@ -181,8 +181,8 @@ export class AccountClient<
       addresses.map((address) => translateAddress(address))
     );
 
-    const discriminator = AccountsCoder.accountDiscriminator(
-      this._idlAccount.name
+    const discriminator = AccountsCoder.idlAccountDiscriminator(
+      this._idlAccount
     );
     // Decode accounts where discriminator is correct, null otherwise
     return accounts.map((account) => {
@@ -216,8 +216,8 @@ export class AccountClient<
   async all(
     filters?: Buffer | GetProgramAccountsFilter[]
   ): Promise<ProgramAccount<T>[]> {
-    const discriminator = AccountsCoder.accountDiscriminator(
-      this._idlAccount.name
+    const discriminator = AccountsCoder.idlAccountDiscriminator(
+      this._idlAccount
     );
 
     let resp = await this._provider.connection.getProgramAccounts(
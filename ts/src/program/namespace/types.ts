@@ -82,6 +82,8 @@ export type InstructionContextFnArgs<
 type TypeMap = {
   publicKey: PublicKey;
   bool: boolean;
+  string: string;
+  bytes: Buffer;
 } & {
   [K in "u8" | "i8" | "u16" | "i16" | "u32" | "i32"]: number;
 } &
@@ -93,12 +95,14 @@ export type DecodeType<T extends IdlType, Defined> = T extends keyof TypeMap
   ? TypeMap[T]
   : T extends { defined: keyof Defined }
   ? Defined[T["defined"]]
-  : T extends { option: { defined: keyof Defined } }
-  ? Defined[T["option"]["defined"]] | null
-  : T extends { vec: keyof TypeMap }
-  ? TypeMap[T["vec"]][]
-  : T extends { array: [defined: keyof TypeMap, size: number] }
-  ? TypeMap[T["array"][0]][]
+  : T extends { option: IdlType }
+  ? DecodeType<T["option"], Defined> | null
+  : T extends { vec: IdlType }
+  ? DecodeType<T["vec"], Defined>[]
+  : T extends { array: [idlType: IdlType, size: number] }
+  ? DecodeType<T["array"][0], Defined>[]
+  : T extends { fixedString: number }
+  ? string
   : unknown;
 
 /**
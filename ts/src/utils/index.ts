@@ -4,3 +4,4 @@ export * as publicKey from "./pubkey.js";
 export * as bytes from "./bytes/index.js";
 export * as token from "./token.js";
 export * as features from "./features.js";
+export * as registry from "./registry.js";
@@ -69,6 +69,43 @@ export function findProgramAddressSync(
   throw new Error(`Unable to find a viable program address nonce`);
 }
 
+type IntSeedType =
+  | "u8"
+  | "i8"
+  | "u16"
+  | "i16"
+  | "u32"
+  | "i32"
+  | "u64"
+  | "i64"
+  | "u128"
+  | "i128";
+
+// Encodes an integer seed the way `le(<expr>)` or `be(<expr>)` in a `seeds`
+// constraint does, e.g. `intSeed(orderId, "u64", "le")`.
+export function intSeed(
+  value: BN | number,
+  type: IntSeedType,
+  endian: "le" | "be"
+): Buffer {
+  const bytes = parseInt(type.slice(1)) / 8;
+  return new BN(value).toTwos(bytes * 8).toArrayLike(Buffer, endian, bytes);
+}
+
+// Hashes seeds into a single seed the way `hash(<seed>, ..)` in a `seeds`
+// constraint does, i.e. the sha256 hash of the seeds, each prefixed by its
+// u32 little endian length.
+export function hashSeeds(...seeds: Array<Buffer | Uint8Array>): Buffer {
+  const data = Buffer.concat(
+    seeds.flatMap((seed) => {
+      const len = Buffer.alloc(4);
+      len.writeUInt32LE(seed.length);
+      return [len, toBuffer(seed)];
+    })
+  );
+  return Buffer.from(sha256Sync.digest(data));
+}
+
 const toBuffer = (arr: Buffer | Uint8Array | Array<number>): Buffer => {
   if (arr instanceof Buffer) {
     return arr;
@@ -0,0 +1,72 @@
+import { Buffer } from "buffer";
+import bs58 from "bs58";
+import { sha256 as sha256Sync } from "js-sha256";
+import { Connection, PublicKey } from "@solana/web3.js";
+import { AccountsCoder } from "../coder/accounts.js";
+import { Address, translateAddress } from "../program/common.js";
+import { findProgramAddressSync } from "./pubkey.js";
+
+// Seed prefix of the registrations kept by programs built with
+// `#[program(interface_registry)]`.
+const INTERFACE_REGISTRY_SEED = Buffer.from("anchor:interface");
+
+// Discriminator, interface hash, authority and implementation.
+const REGISTRATION_ACCOUNT_LEN = 8 + 8 + 32 + 32;
+
+// The hash identifying the `#[interface]` trait named `name`, i.e.
+// `Sha256("interface:<name>")[..8]`.
+export function interfaceHash(name: string): Buffer {
+  return Buffer.from(sha256Sync.digest(`interface:${name}`)).slice(0, 8);
+}
+
+// Address of the registration of `implementation` as implementing the
+// interface `name` by `authority`, in the registry program `programId`.
+export function registrationAddress(
+  programId: Address,
+  name: string,
+  authority: PublicKey,
+  implementation: PublicKey
+): [PublicKey, number] {
+  return findProgramAddressSync(
+    [
+      INTERFACE_REGISTRY_SEED,
+      interfaceHash(name),
+      authority.toBuffer(),
+      implementation.toBuffer(),
+    ],
+    translateAddress(programId)
+  );
+}
+
+// The programs registered by `authority` as implementing the interface
+// `name` in the registry program `programId`.
+export async function findImplementations(
+  connection: Connection,
+  programId: Address,
+  name: string,
+  authority: PublicKey
+): Promise<PublicKey[]> {
+  const accounts = await connection.getProgramAccounts(
+    translateAddress(programId),
+    {
+      filters: [
+        { dataSize: REGISTRATION_ACCOUNT_LEN },
+        {
+          memcmp: {
+            offset: 0,
+            bytes: bs58.encode(
+              Buffer.concat([
+                AccountsCoder.accountDiscriminator("InterfaceRegistration"),
+                interfaceHash(name),
+                authority.toBuffer(),
+              ])
+            ),
+          },
+        },
+      ],
+    }
+  );
+  return accounts.map(
+    ({ account }) => new PublicKey(account.data.slice(48, 80))
+  );
+}